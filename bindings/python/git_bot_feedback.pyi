@@ -17,13 +17,30 @@ class GitClient:
         ignore_index: bool = False,
     ) -> dict[str, FileDiffLines]: ...
     async def post_thread_comment(self, options: ThreadCommentOptions) -> None: ...
-    def append_step_summary(self, comment: str) -> None: ...
+    def append_step_summary(
+        self, comment: str, overflow: SummaryOverflow | None = None
+    ) -> None: ...
+    def write_step_summary(
+        self, comment: str, mode: SummaryWriteMode | None = None
+    ) -> None: ...
+    def upsert_step_summary(self, marker: str, content: str) -> None: ...
     async def cull_pr_reviews(self, options: ReviewOptions) -> ReviewOptions: ...
-    async def post_pr_review(self, options: ReviewOptions) -> None: ...
+    async def post_pr_review(self, options: ReviewOptions) -> ReviewOutcome: ...
+    def set_strict_mode(self, strict: bool) -> None: ...
     def write_output_variables(
         self, output_variables: list[OutputVariable]
     ) -> None: ...
-    def write_file_annotations(self, annotations: list[FileAnnotation]) -> None: ...
+    def export_environment_variables(self, vars: list[OutputVariable]) -> None: ...
+    def write_state_variables(self, vars: list[OutputVariable]) -> None: ...
+    def read_state_variable(self, name: str) -> str | None: ...
+    def add_to_path(self, dir: str | Path) -> None: ...
+    def write_file_annotations(
+        self,
+        annotations: list[FileAnnotation],
+        overflow: AnnotationOverflow | None = None,
+    ) -> int: ...
+    def mask_secret(self, secret: str) -> None: ...
+    def log_untrusted(self, content: str) -> None: ...
     @property
     def client_kind(self) -> str: ...
 
@@ -45,6 +62,19 @@ class AnnotationLevel(Enum):
     Warning = ...
     Error = ...
 
+class AnnotationOverflow(Enum):
+    Truncate = ...
+    Summarize = ...
+    Error = ...
+
+class SummaryOverflow(Enum):
+    Truncate = ...
+    Error = ...
+
+class SummaryWriteMode(Enum):
+    Append = ...
+    Overwrite = ...
+
 class FileAnnotation:
     def __init__(
         self,
@@ -106,6 +136,16 @@ class FileDiffLines:
     def added_ranges(self) -> list[tuple[int, int]]: ...
     @property
     def diff_hunks(self) -> list[tuple[int, int]]: ...
+    @property
+    def deleted_lines(self) -> list[int]: ...
+    @property
+    def old_hunks(self) -> list[tuple[int, int]]: ...
+    @property
+    def hunk_headers(self) -> list[DiffHunkHeader]: ...
+    @property
+    def status(self) -> FileStatus: ...
+    @property
+    def previous_path(self) -> str | None: ...
 
 class FileFilter:
     def __init__(
@@ -123,6 +163,17 @@ class FileFilter:
     def ignored(self) -> set[str]: ...
     @property
     def not_ignored(self) -> set[str]: ...
+    @property
+    def include_deleted(self) -> bool: ...
+    @include_deleted.setter
+    def include_deleted(self, val: bool) -> None: ...
+
+class FileStatus(Enum):
+    Added = ...
+    Modified = ...
+    Renamed = ...
+    Copied = ...
+    Removed = ...
 
 class LinesChangedOnly(Enum):
     On = ...
@@ -171,6 +222,7 @@ class ReviewOptions:
         allow_draft: bool = False,
         allow_closed: bool = False,
         delete_review_comments: bool = False,
+        dismissal_message: str | None = None,
     ) -> None: ...
     @property
     def comments(self) -> list[ReviewComment]: ...
@@ -200,6 +252,29 @@ class ReviewOptions:
     def delete_review_comments(self) -> bool: ...
     @delete_review_comments.setter
     def delete_review_comments(self, val: bool) -> None: ...
+    @property
+    def dismissal_message(self) -> str | None: ...
+    @dismissal_message.setter
+    def dismissal_message(self, val: str | None) -> None: ...
+
+class ReviewOutcome:
+    @property
+    def review_id(self) -> int | None: ...
+    @property
+    def html_url(self) -> str | None: ...
+    @property
+    def posted_comments(self) -> int: ...
+    @property
+    def reused_comments(self) -> int: ...
+    @property
+    def dismissed_reviews(self) -> int: ...
+    @property
+    def skipped(self) -> ReviewSkipReason | None: ...
+
+class ReviewSkipReason(Enum):
+    Draft = ...
+    Closed = ...
+    Locked = ...
 
 class CommentKind(Enum):
     Concerns = ...
@@ -209,6 +284,10 @@ class CommentPolicy(Enum):
     Anew = ...
     Update = ...
 
+class CommentFallback(Enum):
+    StepSummary = ...
+    Skip = ...
+
 class ThreadCommentOptions:
     def __init__(
         self,
@@ -217,6 +296,7 @@ class ThreadCommentOptions:
         kind: CommentKind | None = None,
         marker: str | None = None,
         no_lgtm: bool = False,
+        push_fallback: CommentFallback | None = None,
     ) -> None: ...
     @property
     def policy(self) -> CommentPolicy | None: ...
@@ -238,3 +318,7 @@ class ThreadCommentOptions:
     def no_lgtm(self) -> bool: ...
     @no_lgtm.setter
     def no_lgtm(self, val: bool) -> None: ...
+    @property
+    def push_fallback(self) -> CommentFallback: ...
+    @push_fallback.setter
+    def push_fallback(self, val: CommentFallback) -> None: ...
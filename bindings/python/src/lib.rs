@@ -23,7 +23,13 @@ mod git_bot_feedback {
     #[pymodule_export]
     use ::git_bot_feedback::AnnotationLevel;
     #[pymodule_export]
+    use ::git_bot_feedback::AnnotationOverflow;
+    #[pymodule_export]
     use ::git_bot_feedback::FileAnnotation;
+    #[pymodule_export]
+    use ::git_bot_feedback::summary::SummaryOverflow;
+    #[pymodule_export]
+    use ::git_bot_feedback::summary::SummaryWriteMode;
 
     #[pymodule_export]
     use ::git_bot_feedback::DiffHunkHeader;
@@ -32,6 +38,8 @@ mod git_bot_feedback {
     #[pymodule_export]
     use ::git_bot_feedback::FileFilter;
     #[pymodule_export]
+    use ::git_bot_feedback::FileStatus;
+    #[pymodule_export]
     use ::git_bot_feedback::LinesChangedOnly;
 
     /// A function to parse a diff string.
@@ -65,7 +73,13 @@ mod git_bot_feedback {
     use ::git_bot_feedback::ReviewComment;
     #[pymodule_export]
     use ::git_bot_feedback::ReviewOptions;
+    #[pymodule_export]
+    use ::git_bot_feedback::ReviewOutcome;
+    #[pymodule_export]
+    use ::git_bot_feedback::ReviewSkipReason;
 
+    #[pymodule_export]
+    use ::git_bot_feedback::CommentFallback;
     #[pymodule_export]
     use ::git_bot_feedback::CommentKind;
     #[pymodule_export]
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 use tokio::sync::Mutex;
 
 use ::git_bot_feedback::client::{RestApiClient, init_client};
@@ -118,12 +118,56 @@ impl GitClient {
     /// Not all Git servers natively support this type of feedback.
     /// GitHub and Gitea are known to support this.
     /// For all other git servers, this is a non-op returning [`Ok`]
+    ///
+    /// The `overflow` policy is applied if the `comment` would make the summary larger
+    /// than the Git server can display.
+    #[pyo3(
+        signature = (comment, overflow=None),
+        text_signature = "(comment: str, overflow: SummaryOverflow | None = None) -> None"
+    )]
+    pub fn append_step_summary(
+        &self,
+        comment: &str,
+        overflow: Option<::git_bot_feedback::summary::SummaryOverflow>,
+    ) -> PyResult<()> {
+        self.client
+            .blocking_lock()
+            .append_step_summary(comment, overflow.unwrap_or_default())?;
+        Ok(())
+    }
+
+    /// Writes a given comment to the CI workflow's summary page.
+    ///
+    /// This is like `append_step_summary()`, but the `mode` controls whether any
+    /// existing content of the summary page is kept.
+    #[pyo3(
+        signature = (comment, mode=None),
+        text_signature = "(comment: str, mode: SummaryWriteMode | None = None) -> None"
+    )]
+    pub fn write_step_summary(
+        &self,
+        comment: &str,
+        mode: Option<::git_bot_feedback::summary::SummaryWriteMode>,
+    ) -> PyResult<()> {
+        self.client
+            .blocking_lock()
+            .write_step_summary(comment, mode.unwrap_or_default())?;
+        Ok(())
+    }
+
+    /// Replaces this tool's section of the CI workflow's summary page with the given `content`.
+    ///
+    /// The section is delimited by ``<!-- marker:start -->`` and ``<!-- marker:end -->`` HTML comments,
+    /// where `marker` should uniquely identify the tool.
+    /// If the section does not exist yet, then it is appended like `append_step_summary()`.
     #[pyo3(
-        signature = (comment),
-        text_signature = "(comment: str) -> None"
+        signature = (marker, content),
+        text_signature = "(marker: str, content: str) -> None"
     )]
-    pub fn append_step_summary(&self, comment: &str) -> PyResult<()> {
-        self.client.blocking_lock().append_step_summary(comment)?;
+    pub fn upsert_step_summary(&self, marker: &str, content: &str) -> PyResult<()> {
+        self.client
+            .blocking_lock()
+            .upsert_step_summary(marker, content)?;
         Ok(())
     }
 
@@ -169,10 +213,13 @@ impl GitClient {
     /// avoid posting duplicates of existing comments. Once the duplicates are filtered out,
     /// the [`ReviewOptions::summary`] can be made to reflect the actual review being posted.
     ///
+    /// Returns a ``ReviewOutcome`` that describes the posted review along with
+    /// the number of comments reused and reviews dismissed by [`Self::cull_pr_reviews()`].
+    ///
     /// This function does nothing for non-PR events.
     #[pyo3(
         signature = (options),
-        text_signature = "(options: ReviewOptions) -> None"
+        text_signature = "(options: ReviewOptions) -> ReviewOutcome"
     )]
     pub fn post_pr_review<'py>(
         &self,
@@ -181,11 +228,21 @@ impl GitClient {
     ) -> PyResult<Bound<'py, PyAny>> {
         let client_clone = Arc::clone(&self.client);
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            client_clone.lock().await.post_pr_review(&options).await?;
-            Ok(())
+            let outcome = client_clone.lock().await.post_pr_review(&options).await?;
+            Ok(outcome)
         })
     }
 
+    /// Should a missing environment variable be an error when writing to the file it references?
+    ///
+    /// Methods like `write_output_variables()` and `append_step_summary()` write to a file
+    /// whose path is given by an environment variable (eg. ``GITHUB_OUTPUT``).
+    /// By default, some git servers silently do nothing when that environment variable is not set.
+    /// If `strict` is ``True``, then an exception is raised instead.
+    pub fn set_strict_mode(&self, strict: bool) {
+        self.client.blocking_lock().set_strict_mode(strict);
+    }
+
     /// Sets the given `vars` as output variables.
     ///
     /// These variables are designed to be consumed by other steps in the CI workflow.
@@ -201,20 +258,96 @@ impl GitClient {
         Ok(())
     }
 
+    /// Exports the given `vars` as environment variables.
+    ///
+    /// These environment variables are available to subsequent steps in the CI workflow.
+    #[pyo3(
+        signature = (vars),
+        text_signature = "(vars: list[OutputVariable]) -> None"
+    )]
+    pub fn export_environment_variables(
+        &self,
+        vars: Vec<::git_bot_feedback::OutputVariable>,
+    ) -> PyResult<()> {
+        self.client
+            .blocking_lock()
+            .export_environment_variables(&vars)?;
+        Ok(())
+    }
+
+    /// Saves the given `vars` as state for the ``pre`` or ``post`` steps of the same action.
+    ///
+    /// Use `read_state_variable()` to read a saved state variable in a later step.
+    #[pyo3(
+        signature = (vars),
+        text_signature = "(vars: list[OutputVariable]) -> None"
+    )]
+    pub fn write_state_variables(
+        &self,
+        vars: Vec<::git_bot_feedback::OutputVariable>,
+    ) -> PyResult<()> {
+        self.client.blocking_lock().write_state_variables(&vars)?;
+        Ok(())
+    }
+
+    /// Reads the value of a state variable named `name` that was saved by
+    /// `write_state_variables()` in a previous step of the same action.
+    ///
+    /// Returns ``None`` if the state variable was not saved.
+    pub fn read_state_variable(&self, name: &str) -> Option<String> {
+        self.client.blocking_lock().read_state_variable(name)
+    }
+
+    /// Prepends the given `dir` to the ``PATH`` environment variable of subsequent steps in the CI workflow.
+    ///
+    /// The `dir` must be an absolute path to an existing directory.
+    #[pyo3(
+        signature = (dir),
+        text_signature = "(dir: str | Path) -> None"
+    )]
+    pub fn add_to_path(&self, dir: PathBuf) -> PyResult<()> {
+        self.client.blocking_lock().add_to_path(&dir)?;
+        Ok(())
+    }
+
     /// Sets the given `annotations` as file annotations.
     ///
     /// Not all Git servers support this on their free tiers, namely GitLab.
+    ///
+    /// The `overflow` policy is applied if there are more `annotations` than the
+    /// Git server can display.
+    ///
+    /// Returns the number of annotations actually written.
     #[pyo3(
-        signature = (annotations),
-        text_signature = "(annotations: list[FileAnnotation]) -> None"
+        signature = (annotations, overflow=None),
+        text_signature = "(annotations: list[FileAnnotation], overflow: AnnotationOverflow | None = None) -> int"
     )]
     pub fn write_file_annotations(
         &self,
         annotations: Vec<::git_bot_feedback::FileAnnotation>,
-    ) -> PyResult<()> {
-        self.client
+        overflow: Option<::git_bot_feedback::AnnotationOverflow>,
+    ) -> PyResult<usize> {
+        Ok(self
+            .client
             .blocking_lock()
-            .write_file_annotations(&annotations)?;
+            .write_file_annotations(&annotations, overflow.unwrap_or_default())?)
+    }
+
+    /// Register the given `secret` with the CI runner, so it is masked in the job's logs.
+    ///
+    /// Multi-line secrets are masked line by line.
+    /// This does nothing for git servers that do not support workflow commands.
+    pub fn mask_secret(&self, secret: &str) -> PyResult<()> {
+        self.client.blocking_lock().mask_secret(secret)?;
+        Ok(())
+    }
+
+    /// Write untrusted `content` (eg. a PR title) to the job's log.
+    ///
+    /// For git servers that support workflow commands, the `content` is wrapped with
+    /// ``::stop-commands::`` so it cannot be interpreted as a workflow command.
+    pub fn log_untrusted(&self, content: &str) -> PyResult<()> {
+        self.client.blocking_lock().log_untrusted(content)?;
         Ok(())
     }
 
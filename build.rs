@@ -1,10 +1,18 @@
 //! This script adds a compile-time env var containing the compile-time's datetime.
 //! This added env var is used to generate a sane default comment marker when the
 //! consuming API (upstream) does not actually specify a comment marker to use.
+//!
+//! Without the `chrono` feature, the env var is not added.
+#[cfg(feature = "chrono")]
 use chrono::Local;
 
+#[cfg(feature = "chrono")]
 const ENV_VAR: &str = "COMPILE_DATETIME";
 
+#[cfg(not(feature = "chrono"))]
+fn main() {}
+
+#[cfg(feature = "chrono")]
 fn main() {
     let now = Local::now();
     println!(
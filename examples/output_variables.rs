@@ -1,7 +1,4 @@
-use git_bot_feedback::{
-    OutputVariable, RestClientError,
-    client::{RestApiClient, init_client},
-};
+use git_bot_feedback::prelude::*;
 
 struct ClientWrapper {
     client: Box<dyn RestApiClient + Send + Sync>,
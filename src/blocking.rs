@@ -0,0 +1,207 @@
+//! A module to use the REST API clients without an async runtime.
+//!
+//! Each client owns a single-threaded runtime that runs the async operations of the
+//! wrapped [`RestApiClient`] to completion.
+//! So, the blocking clients behave exactly like their async counterparts.
+//!
+//! Operations that are not async (eg. [`RestApiClient::append_step_summary()`]) are
+//! available via [`Deref`] to the wrapped client.
+//!
+//! ```no_run
+//! use git_bot_feedback::{
+//!     RestApiClient, ThreadCommentOptions, blocking::GithubApiClient, summary::SummaryOverflow,
+//! };
+//!
+//! # fn example() -> Result<(), git_bot_feedback::RestClientError> {
+//! let client = GithubApiClient::new()?;
+//! client.post_thread_comment(ThreadCommentOptions {
+//!     comment: "Hello from a CLI".to_string(),
+//!     ..Default::default()
+//! })?;
+//! client.append_step_summary("Posted a thread comment", SummaryOverflow::default())?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Do not use a blocking client within an async runtime;
+//! the blocking operations panic when called from an async context.
+use std::ops::{Deref, DerefMut};
+
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{
+    CommitStatus, RestApiClient, RestClientError, ReviewOptions, ReviewOutcome,
+    ThreadCommentOptions, client::RateLimitStatus,
+};
+#[cfg(feature = "file-changes")]
+use crate::{FileDiffLines, FileFilter, LinesChangedOnly};
+#[cfg(feature = "file-changes")]
+use std::collections::HashMap;
+
+/// A blocking client for GitHub's REST API.
+///
+/// See [`crate::client::GithubApiClient`] for the async client.
+#[cfg(feature = "github")]
+#[cfg_attr(docsrs, doc(cfg(feature = "github")))]
+pub type GithubApiClient = BlockingClient<crate::client::GithubApiClient>;
+
+/// A blocking client for Gitea's REST API.
+///
+/// See [`crate::client::GiteaApiClient`] for the async client.
+#[cfg(feature = "gitea")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gitea")))]
+pub type GiteaApiClient = BlockingClient<crate::client::GiteaApiClient>;
+
+/// A blocking client for GitLab's REST API.
+///
+/// See [`crate::client::GitlabApiClient`] for the async client.
+#[cfg(feature = "gitlab")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gitlab")))]
+pub type GitlabApiClient = BlockingClient<crate::client::GitlabApiClient>;
+
+/// A blocking client for Azure DevOps' REST API.
+///
+/// See [`crate::client::AzureDevopsApiClient`] for the async client.
+#[cfg(feature = "azure-devops")]
+#[cfg_attr(docsrs, doc(cfg(feature = "azure-devops")))]
+pub type AzureDevopsApiClient = BlockingClient<crate::client::AzureDevopsApiClient>;
+
+#[cfg(feature = "github")]
+impl GithubApiClient {
+    /// Create a blocking client from the CI environment.
+    ///
+    /// See [`crate::client::GithubApiClient::new()`].
+    pub fn new() -> Result<Self, RestClientError> {
+        Self::from_async(crate::client::GithubApiClient::new()?)
+    }
+}
+
+#[cfg(feature = "gitea")]
+impl GiteaApiClient {
+    /// Create a blocking client from the CI environment.
+    ///
+    /// See [`crate::client::GiteaApiClient::new()`].
+    pub fn new() -> Result<Self, RestClientError> {
+        Self::from_async(crate::client::GiteaApiClient::new()?)
+    }
+}
+
+#[cfg(feature = "gitlab")]
+impl GitlabApiClient {
+    /// Create a blocking client from the CI environment.
+    ///
+    /// See [`crate::client::GitlabApiClient::new()`].
+    pub fn new() -> Result<Self, RestClientError> {
+        Self::from_async(crate::client::GitlabApiClient::new()?)
+    }
+}
+
+#[cfg(feature = "azure-devops")]
+impl AzureDevopsApiClient {
+    /// Create a blocking client from the CI environment.
+    ///
+    /// See [`crate::client::AzureDevopsApiClient::new()`].
+    pub fn new() -> Result<Self, RestClientError> {
+        Self::from_async(crate::client::AzureDevopsApiClient::new()?)
+    }
+}
+
+/// Wraps an async [`RestApiClient`] to provide blocking operations.
+#[derive(Debug)]
+pub struct BlockingClient<C> {
+    inner: C,
+    runtime: Runtime,
+}
+
+impl<C: RestApiClient + Sync> BlockingClient<C> {
+    /// Wrap the given async `client`.
+    pub fn from_async(client: C) -> Result<Self, RestClientError> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| RestClientError::io("create async runtime", e))?;
+        Ok(Self {
+            inner: client,
+            runtime,
+        })
+    }
+
+    /// Unwrap the async client.
+    pub fn into_async(self) -> C {
+        self.inner
+    }
+
+    /// See [`RestApiClient::get_list_of_changed_files()`].
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    pub fn get_list_of_changed_files(
+        &self,
+        file_filter: &FileFilter,
+        lines_changed_only: &LinesChangedOnly,
+        base_diff: Option<String>,
+        ignore_index: bool,
+    ) -> Result<HashMap<String, FileDiffLines>, RestClientError> {
+        self.runtime.block_on(self.inner.get_list_of_changed_files(
+            file_filter,
+            lines_changed_only,
+            base_diff,
+            ignore_index,
+        ))
+    }
+
+    /// See [`RestApiClient::post_thread_comment()`].
+    pub fn post_thread_comment(
+        &self,
+        options: ThreadCommentOptions,
+    ) -> Result<(), RestClientError> {
+        self.runtime
+            .block_on(self.inner.post_thread_comment(options))
+    }
+
+    /// See [`RestApiClient::cull_pr_reviews()`].
+    pub fn cull_pr_reviews(&mut self, options: &mut ReviewOptions) -> Result<(), RestClientError> {
+        self.runtime.block_on(self.inner.cull_pr_reviews(options))
+    }
+
+    /// See [`RestApiClient::post_pr_review()`].
+    pub fn post_pr_review(
+        &mut self,
+        options: &ReviewOptions,
+    ) -> Result<ReviewOutcome, RestClientError> {
+        self.runtime.block_on(self.inner.post_pr_review(options))
+    }
+
+    /// See [`RestApiClient::post_commit_status()`].
+    pub fn post_commit_status(&self, status: &CommitStatus) -> Result<(), RestClientError> {
+        self.runtime.block_on(self.inner.post_commit_status(status))
+    }
+
+    /// See [`RestApiClient::get_file_content()`].
+    pub fn get_file_content(
+        &self,
+        path: &str,
+        reference: Option<&str>,
+    ) -> Result<String, RestClientError> {
+        self.runtime
+            .block_on(self.inner.get_file_content(path, reference))
+    }
+
+    /// See [`RestApiClient::rate_limit_status()`].
+    pub fn rate_limit_status(&self) -> Result<Option<RateLimitStatus>, RestClientError> {
+        self.runtime.block_on(self.inner.rate_limit_status())
+    }
+}
+
+impl<C> Deref for BlockingClient<C> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<C> DerefMut for BlockingClient<C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
@@ -0,0 +1,365 @@
+//! This module holds functionality specific to using Azure DevOps' REST API.
+//!
+//! In the root module, we just implement the RestApiClient trait.
+//! In other (private) submodules we implement behavior specific to Azure DevOps' REST API.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use reqwest::{
+    Client, Method, Request,
+    header::{self, HeaderMap, HeaderValue},
+};
+use url::Url;
+
+use super::{
+    CancellationToken, Capabilities, ClientError, CommandSink, Metrics, RateLimitSlot,
+    RateLimitSnapshot, RequestRecorder, ResponseCache, RestApiClient, RestApiRateLimitHeaders,
+    RetryPolicy, Throttle, Timeouts, assert_send_sync_clone, build_request, in_span,
+    log_unsupported, sealed, user_agent_header,
+};
+use crate::{
+    AnnotationOverflow, FileAnnotation, OutputVariable, ReviewOptions, ReviewOutcome,
+    ThreadCommentOptions,
+};
+mod serde_structs;
+mod specific_api;
+use specific_api::{escape_logging_command, fmt_set_variable};
+
+#[cfg(feature = "file-changes")]
+use crate::{FileDiffLines, FileFilter, LinesChangedOnly};
+#[cfg(feature = "file-changes")]
+use std::collections::HashMap;
+
+/// A structure to work with Azure DevOps' REST API (v7.0) in Azure Pipelines.
+///
+/// Thread comments are posted as threads on the Pull Request.
+/// Output variables and file annotations are written as [logging commands]
+/// (see [`RestApiClient::set_command_sink()`]).
+///
+/// [logging commands]: https://learn.microsoft.com/en-us/azure/devops/pipelines/scripts/logging-commands
+#[derive(Clone)]
+pub struct AzureDevopsApiClient {
+    /// The HTTP request client to be used for all REST API calls.
+    client: Client,
+
+    /// The value of the `SYSTEM_PULLREQUEST_PULLREQUESTID` environment variable.
+    pull_request_id: Option<u64>,
+
+    /// The value of the `BUILD_REASON` environment variable.
+    pub event_name: String,
+
+    /// The value of the `SYSTEM_COLLECTIONURI` environment variable.
+    collection_url: Url,
+
+    /// The value of the `SYSTEM_TEAMPROJECT` environment variable.
+    project: String,
+
+    /// The value of the `BUILD_REPOSITORY_NAME` environment variable.
+    repo: String,
+
+    /// The value of the `SYSTEM_DEBUG` environment variable.
+    pub debug_enabled: bool,
+
+    /// The response header names that describe the rate limit status.
+    rate_limit_headers: RestApiRateLimitHeaders,
+
+    /// The destination of logging commands (eg. file annotations).
+    command_sink: CommandSink,
+
+    /// See [`RestApiClient::set_dry_run()`].
+    dry_run: bool,
+
+    /// See [`RestApiClient::set_retry_policy()`].
+    retry_policy: RetryPolicy,
+
+    /// See [`RestApiClient::last_rate_limit()`].
+    last_rate_limit: RateLimitSlot,
+
+    /// See [`RestApiClient::set_user_agent()`].
+    user_agent: HeaderValue,
+
+    /// The headers needed by the REST API (eg. `Accept` and `Authorization`).
+    headers: HeaderMap,
+
+    /// Was the [`Self::client`] given to [`RestApiClient::set_http_client()`]?
+    custom_http_client: bool,
+
+    /// See [`RestApiClient::set_response_cache()`].
+    response_cache: Option<Arc<ResponseCache>>,
+
+    /// See [`RestApiClient::set_request_recorder()`].
+    request_recorder: Option<Arc<RequestRecorder>>,
+
+    /// See [`RestApiClient::set_throttle()`].
+    throttle: Arc<Throttle>,
+
+    /// See [`RestApiClient::set_metrics()`].
+    metrics: Option<Arc<dyn Metrics>>,
+
+    /// See [`RestApiClient::set_cancellation_token()`].
+    cancellation_token: Option<CancellationToken>,
+
+    /// See [`RestApiClient::set_timeouts()`].
+    timeouts: Timeouts,
+}
+
+const _: () = assert_send_sync_clone::<AzureDevopsApiClient>();
+
+#[async_trait]
+impl RestApiClient for AzureDevopsApiClient {
+    /// This starts a [collapsible group] of the job's log.
+    ///
+    /// [collapsible group]: https://learn.microsoft.com/en-us/azure/devops/pipelines/scripts/logging-commands#formatting-commands
+    fn start_log_group(&self, name: &str) {
+        log::info!(
+            target: "CI_LOG_GROUPING",
+            "##[group]{}",
+            name.replace(['\r', '\n'], " ")
+        );
+    }
+
+    fn end_log_group(&self, _name: &str) {
+        log::info!(target: "CI_LOG_GROUPING", "##[endgroup]");
+    }
+
+    fn is_pr_event(&self) -> bool {
+        self.pull_request_id.is_some()
+    }
+
+    fn event_name(&self) -> Option<String> {
+        Some(self.event_name.clone())
+    }
+
+    fn is_debug_enabled(&self) -> bool {
+        self.debug_enabled
+    }
+
+    fn set_user_agent(&mut self, user_agent: &str) -> Result<(), ClientError> {
+        self.user_agent = user_agent_header(user_agent)?;
+        Ok(())
+    }
+
+    fn set_http_client(&mut self, client: Client) {
+        self.client = client;
+        self.custom_http_client = true;
+    }
+
+    fn throttle(&self) -> Option<&Throttle> {
+        Some(&*self.throttle)
+    }
+
+    fn set_throttle(&mut self, throttle: Throttle) {
+        self.throttle = Arc::new(throttle);
+    }
+
+    fn cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
+    fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.cancellation_token = token;
+    }
+
+    fn metrics(&self) -> Option<&dyn Metrics> {
+        self.metrics.as_deref()
+    }
+
+    fn set_metrics(&mut self, metrics: Option<Arc<dyn Metrics>>) {
+        self.metrics = metrics;
+    }
+
+    fn response_cache(&self) -> Option<&ResponseCache> {
+        self.response_cache.as_deref()
+    }
+
+    fn set_response_cache(&mut self, cache: Option<ResponseCache>) {
+        self.response_cache = cache.map(Arc::new);
+    }
+
+    fn request_recorder(&self) -> Option<&RequestRecorder> {
+        self.request_recorder.as_deref()
+    }
+
+    fn set_request_recorder(&mut self, recorder: Option<RequestRecorder>) {
+        self.request_recorder = recorder.map(Arc::new);
+    }
+
+    fn timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+
+    fn set_timeouts(&mut self, timeouts: Timeouts) -> Result<(), ClientError> {
+        if !self.custom_http_client {
+            self.client = Self::make_client(&timeouts)?;
+        }
+        self.timeouts = timeouts;
+        Ok(())
+    }
+
+    /// The headers needed by the REST API and the [`Timeouts::request`] are applied to
+    /// every request. Any given `headers` take precedence.
+    fn make_api_request(
+        &self,
+        client: &Client,
+        url: Url,
+        method: Method,
+        data: Option<String>,
+        headers: Option<HeaderMap>,
+    ) -> Result<Request, ClientError> {
+        let mut merged = self.headers.clone();
+        merged.insert(header::USER_AGENT, self.user_agent.clone());
+        if data.is_some() {
+            merged.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            );
+        }
+        if let Some(headers) = headers {
+            merged.extend(headers);
+        }
+        let mut request = build_request(client, url, method, data, Some(merged))?;
+        request.timeout_mut().get_or_insert(self.timeouts.request);
+        Ok(request)
+    }
+
+    /// Thread comments are posted as threads on the Pull Request.
+    ///
+    /// An existing thread is updated by editing its first comment.
+    /// The thread's status is set to `active` for [`CommentKind::Concerns`] and
+    /// `fixed` for [`CommentKind::Lgtm`].
+    /// Azure Repos has no equivalent of commit comments,
+    /// so thread comments are not posted for other builds.
+    ///
+    /// [`CommentKind::Concerns`]: crate::CommentKind::Concerns
+    /// [`CommentKind::Lgtm`]: crate::CommentKind::Lgtm
+    async fn post_thread_comment(&self, options: ThreadCommentOptions) -> Result<(), ClientError> {
+        let Some(id) = self.pull_request_id else {
+            log_unsupported(self, "posting a thread comment outside of a pull request");
+            return Ok(());
+        };
+        let threads_url =
+            self.repo_endpoint(&["pullRequests", id.to_string().as_str(), "threads"])?;
+        let operation = self.timeouts.with_deadline(
+            "post thread comment",
+            self.update_comment(threads_url, options),
+        );
+        in_span!(
+            operation,
+            "post_thread_comment",
+            repo = self.repo.as_str(),
+            pr = self.pull_request_id,
+        )
+        .await
+    }
+
+    async fn cull_pr_reviews(&mut self, _options: &mut ReviewOptions) -> Result<(), ClientError> {
+        Ok(())
+    }
+
+    async fn post_pr_review(
+        &mut self,
+        _options: &ReviewOptions,
+    ) -> Result<ReviewOutcome, ClientError> {
+        log_unsupported(self, "posting a PR review");
+        Ok(ReviewOutcome::default())
+    }
+
+    /// Each of the `vars` is written as a `task.setvariable` logging command
+    /// (with `isoutput=true`), so it is available to later jobs in the pipeline.
+    fn write_output_variables(&self, vars: &[OutputVariable]) -> Result<(), ClientError> {
+        if vars.is_empty() {
+            return Ok(());
+        }
+        // Validate all variables before writing any of them.
+        let commands = vars
+            .iter()
+            .map(fmt_set_variable)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.command_sink.write_lines(&commands)
+    }
+
+    fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    fn last_rate_limit(&self) -> Option<RateLimitSnapshot> {
+        self.last_rate_limit.get()
+    }
+
+    fn record_rate_limit(&self, snapshot: RateLimitSnapshot, _token: sealed::Token) {
+        self.last_rate_limit.set(snapshot);
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = policy;
+    }
+
+    fn set_command_sink(&mut self, sink: CommandSink) {
+        self.command_sink = sink;
+    }
+
+    /// Untrusted `content` is escaped line by line,
+    /// so it cannot be interpreted as a logging command.
+    fn log_untrusted(&self, content: &str) -> Result<(), ClientError> {
+        self.command_sink.write_lines(
+            &content
+                .lines()
+                .map(escape_logging_command)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Errors and warnings are written as `task.logissue` logging commands.
+    /// Azure Pipelines does not limit the number of displayed issues,
+    /// so the `overflow` policy is not used.
+    fn write_file_annotations(
+        &self,
+        annotations: &[FileAnnotation],
+        _overflow: AnnotationOverflow,
+    ) -> Result<usize, ClientError> {
+        if annotations.is_empty() {
+            return Ok(0);
+        }
+        let commands = Self::render_annotations(annotations);
+        self.command_sink.write_lines(&commands)?;
+        Ok(commands.len())
+    }
+
+    /// Listing changed files is not yet supported for Azure DevOps,
+    /// so this returns an empty map.
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    async fn get_list_of_changed_files(
+        &self,
+        _file_filter: &FileFilter,
+        _lines_changed_only: &LinesChangedOnly,
+        _base_diff: Option<String>,
+        _ignore_index: bool,
+    ) -> Result<HashMap<String, FileDiffLines>, ClientError> {
+        log_unsupported(self, "listing changed files");
+        Ok(HashMap::new())
+    }
+
+    fn client_kind(&self) -> String {
+        "azure-devops".to_string()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            // Azure Repos has no equivalent of commit comments
+            thread_comments_on_push: false,
+            file_annotations: true,
+            output_variables: true,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! This submodule declares data structures used to
+//! deserialize (and serializer) JSON payload data.
+
+use serde::{Deserialize, Serialize};
+
+/// A structure for deserializing the list of threads on a Pull Request.
+#[derive(Debug, Deserialize)]
+pub struct ThreadList {
+    /// The threads on the Pull Request.
+    pub value: Vec<Thread>,
+}
+
+/// A structure for deserializing a thread (of comments) on a Pull Request.
+///
+/// Unknown fields are ignored.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Thread {
+    /// The thread's ID.
+    pub id: i64,
+    /// The comments in the thread; the first comment started the thread.
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+    /// Was the thread deleted?
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+/// A structure for deserializing a comment in a [`Thread`].
+///
+/// Unknown fields are ignored.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    /// The comment's ID (within the thread).
+    pub id: i64,
+    /// The comment's content.
+    #[serde(default)]
+    pub content: String,
+    /// The comment's author.
+    pub author: CommentAuthor,
+    /// Was the comment deleted?
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+/// A structure for deserializing the author of a [`Comment`].
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentAuthor {
+    /// The author's display name.
+    pub display_name: String,
+    /// The author's ID.
+    pub id: String,
+}
+
+/// A structure for serializing a new thread on a Pull Request.
+#[derive(Debug, Serialize)]
+pub struct NewThread {
+    pub comments: Vec<NewComment>,
+    pub status: &'static str,
+}
+
+/// A structure for serializing a new comment in a [`NewThread`].
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewComment {
+    pub parent_comment_id: i64,
+    pub content: String,
+    /// `1` is a "text" comment (as opposed to a "system" comment).
+    pub comment_type: u8,
+}
@@ -0,0 +1,541 @@
+//! This submodule implements functionality exclusively specific to Azure DevOps' REST API.
+
+use super::{
+    AzureDevopsApiClient,
+    serde_structs::{NewComment, NewThread, ThreadList},
+};
+use crate::{
+    AnnotationLevel, CommentKind, CommentPolicy, FileAnnotation, OutputVariable, RestApiClient,
+    RestApiRateLimitHeaders, ThreadCommentOptions,
+    client::{
+        ClientError, CommandSink, RateLimitSlot, RetryPolicy, Throttle, Timeouts, USER_AGENT,
+    },
+};
+use reqwest::{
+    Client, Method, Url,
+    header::{AUTHORIZATION, HeaderMap, HeaderValue},
+};
+use std::{collections::HashMap, env, sync::Arc};
+
+/// The version of the REST API that is requested.
+const API_VERSION: &str = "7.0";
+
+/// The environment variable that provides the token.
+///
+/// Azure Pipelines only exposes the job's access token to scripts that explicitly
+/// map `$(System.AccessToken)` to this environment variable.
+const TOKEN_ENV: &str = "SYSTEM_ACCESSTOKEN";
+
+/// Get the URL of the REST API endpoint for the `repo` in the `project` at the given `path`.
+///
+/// Each item of `path` is a path segment (which is percent-encoded as needed).
+fn repo_endpoint(
+    collection_url: &Url,
+    project: &str,
+    repo: &str,
+    path: &[&str],
+) -> Result<Url, ClientError> {
+    let mut url = collection_url.clone();
+    url.path_segments_mut()
+        .map_err(|_| ClientError::UrlParse(url::ParseError::RelativeUrlWithCannotBeABaseBase))?
+        .pop_if_empty()
+        .extend([project, "_apis", "git", "repositories", repo])
+        .extend(path);
+    url.query_pairs_mut()
+        .append_pair("api-version", API_VERSION);
+    Ok(url)
+}
+
+/// Encode the given `token` as the credentials of a `Basic` authorization header.
+///
+/// Azure DevOps ignores the user name, so it is left empty.
+fn basic_auth(token: &str) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let credentials = format!(":{token}");
+    let mut encoded = String::from("Basic ");
+    for chunk in credentials.as_bytes().chunks(3) {
+        let bytes = [0, 1, 2].map(|i| u32::from(chunk.get(i).copied().unwrap_or_default()));
+        let triple = (bytes[0] << 16) | (bytes[1] << 8) | bytes[2];
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((triple >> (18 - 6 * i)) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Escape the given `value` for use in a [logging command].
+///
+/// This applies to both the command's properties and its message,
+/// so untrusted content cannot end the command (or start another one).
+///
+/// [logging command]: https://learn.microsoft.com/en-us/azure/devops/pipelines/scripts/logging-commands
+pub(super) fn escape_logging_command(value: &str) -> String {
+    value
+        .replace('%', "%AZP25")
+        .replace(';', "%3B")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+        .replace(']', "%5D")
+}
+
+/// Format the given `var` as a logging command that sets an output variable.
+///
+/// Multi-line values are supported because line breaks are escaped.
+pub(super) fn fmt_set_variable(var: &OutputVariable) -> Result<String, ClientError> {
+    var.validate()?;
+    Ok(format!(
+        "##vso[task.setvariable variable={};isoutput=true]{}",
+        escape_logging_command(var.name.trim()),
+        escape_logging_command(var.value.trim()),
+    ))
+}
+
+impl AzureDevopsApiClient {
+    /// Instantiate a [`AzureDevopsApiClient`] object.
+    ///
+    /// The following environment variables (set by Azure Pipelines) are read:
+    ///
+    /// | variable | description |
+    /// |----------|-------------|
+    /// | `SYSTEM_COLLECTIONURI` | The organization's URL (eg. `https://dev.azure.com/my-org/`). |
+    /// | `SYSTEM_TEAMPROJECT` | The name of the project. |
+    /// | `BUILD_REPOSITORY_NAME` | The name of the repository. |
+    /// | `SYSTEM_PULLREQUEST_PULLREQUESTID` | The Pull Request's ID (only set in Pull Request builds). |
+    /// | `BUILD_REASON` | The [`RestApiClient::event_name()`] (eg. `PullRequest`). |
+    /// | `SYSTEM_DEBUG` | Enables [`RestApiClient::is_debug_enabled()`] if `true`. |
+    ///
+    /// The token is read from `SYSTEM_ACCESSTOKEN` (sent with `Basic` authentication).
+    /// It must be explicitly mapped from `$(System.AccessToken)` in the pipeline.
+    pub fn new() -> Result<Self, ClientError> {
+        let pull_request_id = match env::var("SYSTEM_PULLREQUEST_PULLREQUESTID") {
+            Ok(id) if !id.trim().is_empty() => Some(id.trim().parse::<u64>().map_err(|_| {
+                ClientError::MalformedEventInfo(format!(
+                    "SYSTEM_PULLREQUEST_PULLREQUESTID is not a number: {id}"
+                ))
+            })?),
+            _ => None,
+        };
+        let collection_url = env::var("SYSTEM_COLLECTIONURI")
+            .map_err(|e| ClientError::env_var("SYSTEM_COLLECTIONURI", e))?;
+        let collection_url = Url::parse(collection_url.trim())?;
+
+        Ok(Self {
+            client: Self::make_client(&Timeouts::default())?,
+            custom_http_client: false,
+            response_cache: None,
+            request_recorder: None,
+            throttle: Arc::new(Throttle::default()),
+            metrics: None,
+            cancellation_token: None,
+            headers: Self::make_headers()?,
+            pull_request_id,
+            event_name: env::var("BUILD_REASON").unwrap_or(String::from("unknown")),
+            collection_url,
+            project: env::var("SYSTEM_TEAMPROJECT")
+                .map_err(|e| ClientError::env_var("SYSTEM_TEAMPROJECT", e))?,
+            repo: env::var("BUILD_REPOSITORY_NAME")
+                .map_err(|e| ClientError::env_var("BUILD_REPOSITORY_NAME", e))?,
+            debug_enabled: env::var("SYSTEM_DEBUG").is_ok_and(|val| val.to_lowercase() == "true"),
+            rate_limit_headers: RestApiRateLimitHeaders {
+                reset: "x-ratelimit-reset".to_string(),
+                remaining: "x-ratelimit-remaining".to_string(),
+                retry: "retry-after".to_string(),
+            },
+            dry_run: false,
+            retry_policy: RetryPolicy::default(),
+            last_rate_limit: RateLimitSlot::default(),
+            user_agent: HeaderValue::from_static(USER_AGENT),
+            timeouts: Timeouts::default(),
+            command_sink: CommandSink::default(),
+        })
+    }
+
+    /// The ID of the Pull Request that triggered the pipeline.
+    ///
+    /// Returns [`None`] if the pipeline was not triggered by a Pull Request.
+    pub fn pull_request_id(&self) -> Option<u64> {
+        self.pull_request_id
+    }
+
+    /// Get the URL of the REST API endpoint for this repository at the given `path`.
+    pub(super) fn repo_endpoint(&self, path: &[&str]) -> Result<Url, ClientError> {
+        repo_endpoint(&self.collection_url, &self.project, &self.repo, path)
+    }
+
+    /// Build the default HTTP client.
+    ///
+    /// Headers and request timeouts are applied by [`RestApiClient::make_api_request()`],
+    /// so they also apply to a client given to [`RestApiClient::set_http_client()`].
+    pub(super) fn make_client(timeouts: &Timeouts) -> Result<Client, ClientError> {
+        Ok(Client::builder()
+            .connect_timeout(timeouts.connect)
+            .build()?)
+    }
+
+    pub(super) fn make_headers() -> Result<HeaderMap<HeaderValue>, ClientError> {
+        let mut headers = HeaderMap::new();
+        headers.insert("Accept", HeaderValue::from_str("application/json")?);
+        if let Ok(token) = env::var(TOKEN_ENV) {
+            log::debug!("Using {TOKEN_ENV} environment variable");
+            let mut val = HeaderValue::from_str(basic_auth(&token).as_str())?;
+            val.set_sensitive(true);
+            headers.insert(AUTHORIZATION, val);
+        } else {
+            log::warn!(
+                "No {TOKEN_ENV} environment variable found! Permission to post comments may be unsatisfied."
+            );
+        }
+        Ok(headers)
+    }
+
+    /// Format the given `annotations` as logging commands.
+    ///
+    /// See [`FileAnnotation::fmt_azure()`].
+    pub(super) fn render_annotations(annotations: &[FileAnnotation]) -> Vec<String> {
+        annotations.iter().map(FileAnnotation::fmt_azure).collect()
+    }
+
+    /// Update existing comment or remove old comment(s) and post a new comment
+    pub async fn update_comment(
+        &self,
+        url: Url,
+        options: ThreadCommentOptions,
+    ) -> Result<(), ClientError> {
+        let is_lgtm = options.kind == CommentKind::Lgtm;
+        let existing = self
+            .remove_bot_comments(
+                &url,
+                &options.marker,
+                (options.policy == CommentPolicy::Anew) || (is_lgtm && options.no_lgtm),
+            )
+            .await?;
+        // A thread that only says "Looks Good To Me" needs no attention.
+        let status = if is_lgtm { "fixed" } else { "active" };
+
+        if !is_lgtm || !options.no_lgtm {
+            let task = "post thread comment";
+            let (request, update_status) = match existing {
+                Some((thread_id, comment_id)) => {
+                    let thread_id = thread_id.to_string();
+                    let comment_url = self.repo_endpoint(&[
+                        "pullRequests",
+                        self.pull_request_number()?.as_str(),
+                        "threads",
+                        thread_id.as_str(),
+                        "comments",
+                        comment_id.to_string().as_str(),
+                    ])?;
+                    let payload = HashMap::from([("content", options.mark_comment())]);
+                    let request = self.make_api_request(
+                        &self.client,
+                        comment_url,
+                        Method::PATCH,
+                        Some(serde_json::json!(&payload).to_string()),
+                        None,
+                    )?;
+                    (request, Some(thread_id))
+                }
+                None => {
+                    let payload = NewThread {
+                        comments: vec![NewComment {
+                            parent_comment_id: 0,
+                            content: options.mark_comment(),
+                            comment_type: 1,
+                        }],
+                        status,
+                    };
+                    let request = self.make_api_request(
+                        &self.client,
+                        url,
+                        Method::POST,
+                        Some(
+                            serde_json::to_string(&payload)
+                                .map_err(|e| ClientError::json("serialize thread payload", e))?,
+                        ),
+                        None,
+                    )?;
+                    (request, None)
+                }
+            };
+            let response = self
+                .send_api_request(&self.client, request, &self.rate_limit_headers)
+                .await
+                .map_err(|e| e.with_context(task))?;
+            ClientError::check_response(response, task).await?;
+
+            if let Some(thread_id) = update_status {
+                let task = "update thread status";
+                let thread_url = self.repo_endpoint(&[
+                    "pullRequests",
+                    self.pull_request_number()?.as_str(),
+                    "threads",
+                    thread_id.as_str(),
+                ])?;
+                let payload = HashMap::from([("status", status)]);
+                let request = self.make_api_request(
+                    &self.client,
+                    thread_url,
+                    Method::PATCH,
+                    Some(serde_json::json!(&payload).to_string()),
+                    None,
+                )?;
+                let response = self
+                    .send_api_request(&self.client, request, &self.rate_limit_headers)
+                    .await
+                    .map_err(|e| e.with_context(task))?;
+                ClientError::check_response(response, task).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The Pull Request's ID as a path segment.
+    fn pull_request_number(&self) -> Result<String, ClientError> {
+        self.pull_request_id
+            .map(|id| id.to_string())
+            .ok_or_else(|| ClientError::MalformedEventInfo("Not a Pull Request build".to_string()))
+    }
+
+    /// Remove Pull Request threads previously started by this software.
+    ///
+    /// Returns the IDs of the thread and its first comment to update (if any and not `delete`).
+    async fn remove_bot_comments(
+        &self,
+        url: &Url,
+        comment_marker: &str,
+        delete: bool,
+    ) -> Result<Option<(i64, i64)>, ClientError> {
+        let task = "get list of existing thread comments";
+        let mut existing: Option<(i64, i64)> = None;
+        let request = self.make_api_request(&self.client, url.clone(), Method::GET, None, None)?;
+        let response = self
+            .send_api_request(&self.client, request, &self.rate_limit_headers)
+            .await
+            .map_err(|e| e.with_context(task))?;
+        if !response.status().is_success() {
+            self.log_response(response, "Failed to get list of existing thread comments")
+                .await;
+            return Ok(existing);
+        }
+        let threads = serde_json::from_str::<ThreadList>(
+            &response
+                .text()
+                .await
+                .map_err(|e| ClientError::Request(e).with_context(task))?,
+        )
+        .map_err(|e| ClientError::json("deserialize list of existing thread comments", e))?;
+        for thread in threads.value {
+            let Some(comment) = thread.comments.first() else {
+                continue;
+            };
+            if thread.is_deleted
+                || comment.is_deleted
+                || !comment.content.starts_with(comment_marker)
+            {
+                continue;
+            }
+            log::debug!(
+                "Found bot comment in thread id {} from user {} ({})",
+                thread.id,
+                comment.author.display_name,
+                comment.author.id,
+            );
+            if delete || existing.is_some() {
+                // if not updating: remove all outdated comments
+                // if updating: remove all outdated comments except the last one
+
+                // use last saved comment (if not None) or current comment
+                let (thread_id, comment_id) = existing.unwrap_or((thread.id, comment.id));
+                let del_url = self.repo_endpoint(&[
+                    "pullRequests",
+                    self.pull_request_number()?.as_str(),
+                    "threads",
+                    thread_id.to_string().as_str(),
+                    "comments",
+                    comment_id.to_string().as_str(),
+                ])?;
+                let request =
+                    self.make_api_request(&self.client, del_url, Method::DELETE, None, None)?;
+                let response = self
+                    .send_api_request(&self.client, request, &self.rate_limit_headers)
+                    .await
+                    .map_err(|e| e.with_context("delete old thread comment"))?;
+                ClientError::check_response(response, "delete old thread comment").await?;
+            }
+            if !delete {
+                existing = Some((thread.id, comment.id));
+            }
+        }
+        Ok(existing)
+    }
+}
+
+impl FileAnnotation {
+    /// Format the [`FileAnnotation`] struct as an Azure Pipelines [logging command].
+    ///
+    /// [`AnnotationLevel::Error`] and [`AnnotationLevel::Warning`] use the `task.logissue` command.
+    /// Azure Pipelines has no equivalent of a notice, so [`AnnotationLevel::Notice`] is
+    /// written as a plain log message; [`AnnotationLevel::Debug`] is written as a debug message.
+    ///
+    /// Example:
+    /// ```text
+    /// ##vso[task.logissue type=error;sourcepath={path};linenumber={line};columnnumber={col};]{title}: {message}
+    /// ```
+    ///
+    /// [logging command]: https://learn.microsoft.com/en-us/azure/devops/pipelines/scripts/logging-commands#logissue-log-an-error-or-warning
+    pub fn fmt_azure(&self) -> String {
+        let file_path = self
+            .path
+            .replace("\\", "/")
+            .trim_start()
+            .trim_start_matches('/')
+            .trim_start_matches("./")
+            .trim()
+            .to_string();
+        let message = escape_logging_command(
+            match &self.title {
+                Some(title) => format!("{title}: {}", self.message),
+                None => self.message.clone(),
+            }
+            .as_str(),
+        );
+        let start_line = self.start_line.map(|l| l.max(1));
+        let start_column = self.start_column.map(|c| c.max(1));
+        let kind = match self.severity {
+            AnnotationLevel::Error => "error",
+            AnnotationLevel::Warning => "warning",
+            AnnotationLevel::Notice | AnnotationLevel::Debug => {
+                let location = match (file_path.is_empty(), start_line, start_column) {
+                    (true, ..) => String::new(),
+                    (false, Some(line), Some(col)) => {
+                        format!("{}({line},{col}): ", escape_logging_command(&file_path))
+                    }
+                    (false, Some(line), None) => {
+                        format!("{}({line}): ", escape_logging_command(&file_path))
+                    }
+                    (false, None, _) => format!("{}: ", escape_logging_command(&file_path)),
+                };
+                let prefix = if self.severity == AnnotationLevel::Debug {
+                    "##[debug]"
+                } else {
+                    ""
+                };
+                return format!("{prefix}{location}{message}");
+            }
+        };
+        let mut properties = vec![format!("type={kind}")];
+        if !file_path.is_empty() {
+            properties.push(format!("sourcepath={}", escape_logging_command(&file_path)));
+            if let Some(line) = start_line {
+                properties.push(format!("linenumber={line}"));
+                if let Some(col) = start_column {
+                    properties.push(format!("columnnumber={col}"));
+                }
+            }
+        }
+        format!("##vso[task.logissue {};]{message}", properties.join(";"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use reqwest::Url;
+
+    use super::{basic_auth, escape_logging_command, fmt_set_variable, repo_endpoint};
+    use crate::{AnnotationLevel, FileAnnotation, OutputVariable};
+
+    #[test]
+    fn repo_endpoint_segments() {
+        for collection_url in ["https://dev.azure.com/org", "https://dev.azure.com/org/"] {
+            let collection_url = Url::parse(collection_url).unwrap();
+            let url = repo_endpoint(
+                &collection_url,
+                "My Project",
+                "repo",
+                &["pullRequests", "7", "threads"],
+            )
+            .unwrap();
+            assert_eq!(
+                url.as_str(),
+                "https://dev.azure.com/org/My%20Project/_apis/git/repositories/repo/pullRequests/7/threads?api-version=7.0"
+            );
+        }
+    }
+
+    #[test]
+    fn encode_basic_auth() {
+        assert_eq!(basic_auth(""), "Basic Og==");
+        assert_eq!(basic_auth("a"), "Basic OmE=");
+        assert_eq!(basic_auth("ab"), "Basic OmFi");
+        assert_eq!(basic_auth("123456"), "Basic OjEyMzQ1Ng==");
+    }
+
+    #[test]
+    fn escape_command() {
+        assert_eq!(
+            escape_logging_command("50%; done]\r\n##vso[task.complete]"),
+            "50%AZP25%3B done%5D%0D%0A##vso[task.complete%5D"
+        );
+    }
+
+    #[test]
+    fn set_variable() {
+        let var = OutputVariable::new("LINT_REPORT", " line 1\nline 2 ").unwrap();
+        assert_eq!(
+            fmt_set_variable(&var).unwrap(),
+            "##vso[task.setvariable variable=LINT_REPORT;isoutput=true]line 1%0Aline 2"
+        );
+    }
+
+    #[test]
+    fn annotate_file() {
+        let annotation = FileAnnotation {
+            severity: AnnotationLevel::Error,
+            path: "./src\\lib.rs".to_string(),
+            start_line: Some(10),
+            start_column: Some(5),
+            title: Some("clippy".to_string()),
+            message: "unused variable".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            annotation.fmt_azure(),
+            "##vso[task.logissue type=error;sourcepath=src/lib.rs;linenumber=10;columnnumber=5;]clippy: unused variable"
+        );
+    }
+
+    #[test]
+    fn generic_message() {
+        let annotation = FileAnnotation {
+            severity: AnnotationLevel::Warning,
+            message: "This is a warning".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            annotation.fmt_azure(),
+            "##vso[task.logissue type=warning;]This is a warning"
+        );
+    }
+
+    #[test]
+    fn notice_and_debug() {
+        let mut annotation = FileAnnotation {
+            severity: AnnotationLevel::Notice,
+            path: "src/lib.rs".to_string(),
+            start_line: Some(3),
+            message: "This is a notice".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(annotation.fmt_azure(), "src/lib.rs(3): This is a notice");
+        annotation.severity = AnnotationLevel::Debug;
+        assert_eq!(
+            annotation.fmt_azure(),
+            "##[debug]src/lib.rs(3): This is a notice"
+        );
+    }
+}
@@ -0,0 +1,45 @@
+//! A module to describe which kinds of feedback a git server supports.
+
+/// The kinds of feedback that a [`RestApiClient`](crate::RestApiClient) supports.
+///
+/// Use [`RestApiClient::capabilities()`](crate::RestApiClient::capabilities) to query a client.
+/// Feedback that is not supported is silently skipped (see the respective
+/// [`RestApiClient`](crate::RestApiClient) methods).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Capabilities {
+    /// Can thread comments be posted for push events (ie. on a commit)?
+    ///
+    /// Thread comments can be posted on PRs if [`Self::pr_reviews`] is supported.
+    pub thread_comments_on_push: bool,
+
+    /// Can PR reviews (and thread comments) be posted for pull request events?
+    pub pr_reviews: bool,
+
+    /// Can file annotations be displayed?
+    pub file_annotations: bool,
+
+    /// Can content be written to the CI workflow's summary page?
+    pub step_summary: bool,
+
+    /// Can output variables be set for other steps in the CI workflow?
+    pub output_variables: bool,
+
+    /// Can commit statuses be posted?
+    ///
+    /// See [`RestApiClient::post_commit_status()`](crate::RestApiClient::post_commit_status).
+    pub commit_statuses: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Capabilities;
+    use crate::{RestApiClient, client::LocalClient};
+
+    #[test]
+    fn local_capabilities() {
+        assert_eq!(
+            LocalClient::default().capabilities(),
+            Capabilities::default()
+        );
+    }
+}
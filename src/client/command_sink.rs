@@ -0,0 +1,199 @@
+//! A module to describe where CI workflow commands are written.
+use std::{
+    collections::hash_map::RandomState,
+    fmt::Debug,
+    hash::{BuildHasher, Hasher},
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{ClientError, lock_ignoring_poison};
+
+/// The destination of CI workflow commands (eg. file annotations).
+///
+/// By default, workflow commands are printed to stdout, which is where CI runners
+/// expect them. Use [`RestApiClient::set_command_sink()`](crate::RestApiClient::set_command_sink)
+/// to change this.
+///
+/// ```
+/// use std::{io::Write, sync::{Arc, Mutex}};
+/// use git_bot_feedback::client::CommandSink;
+///
+/// let buffer = Arc::new(Mutex::new(Vec::<u8>::new()));
+/// let sink = CommandSink::Writer(buffer.clone());
+/// sink.write_lines(&["::notice::Hello".to_string()]).unwrap();
+/// assert_eq!(buffer.lock().unwrap().as_slice(), b"::notice::Hello\n");
+/// ```
+#[derive(Clone, Default)]
+pub enum CommandSink {
+    /// Print workflow commands to stdout.
+    #[default]
+    Stdout,
+
+    /// Emit workflow commands as [`log::info`] statements with the log target `"CI_LOG_GROUPING"`.
+    ///
+    /// See [`RestApiClient::start_log_group()`](crate::RestApiClient::start_log_group)
+    /// about special handling of this log target.
+    Log,
+
+    /// Write workflow commands to a custom writer.
+    ///
+    /// This is useful to capture the output in tests.
+    Writer(Arc<Mutex<dyn Write + Send>>),
+}
+
+impl Debug for CommandSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stdout => write!(f, "Stdout"),
+            Self::Log => write!(f, "Log"),
+            Self::Writer(_) => write!(f, "Writer(..)"),
+        }
+    }
+}
+
+impl CommandSink {
+    /// Write the given `lines` (one workflow command per line) to this sink.
+    pub fn write_lines(&self, lines: &[String]) -> Result<(), ClientError> {
+        match self {
+            Self::Stdout => {
+                let stdout = io::stdout();
+                let mut handle = stdout.lock();
+                Self::write_all(&mut handle, lines)
+            }
+            Self::Log => {
+                for line in lines {
+                    log::info!(target: "CI_LOG_GROUPING", "{line}");
+                }
+                Ok(())
+            }
+            Self::Writer(writer) => {
+                let mut handle = lock_ignoring_poison(writer);
+                Self::write_all(&mut *handle, lines)
+            }
+        }
+    }
+
+    fn write_all(handle: &mut dyn Write, lines: &[String]) -> Result<(), ClientError> {
+        for line in lines {
+            writeln!(handle, "{line}").map_err(|e| ClientError::io("write workflow command", e))?;
+        }
+        handle
+            .flush()
+            .map_err(|e| ClientError::io("flush workflow commands", e))
+    }
+}
+
+/// Generate a random token (32 hexadecimal characters) that cannot be guessed from untrusted content.
+///
+/// [`RandomState`] is seeded with random keys, so no extra dependency is needed.
+pub(crate) fn random_token() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    (0..2)
+        .map(|_| {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u128(nanos);
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+/// A guard that disables the processing of workflow commands until it is dropped.
+///
+/// Use this when writing untrusted content (eg. a PR title or a diff snippet) to the job's log,
+/// so crafted text like `::set-env name=X::evil` is not interpreted as a workflow command.
+///
+/// Creating the guard writes `::stop-commands::<token>` to the [`CommandSink`],
+/// where `<token>` is randomly generated. Dropping the guard (even while unwinding from a panic)
+/// writes `::<token>::` to resume the processing of workflow commands.
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+/// use git_bot_feedback::client::{CommandSink, StopCommandsGuard};
+///
+/// let buffer = Arc::new(Mutex::new(Vec::<u8>::new()));
+/// let sink = CommandSink::Writer(buffer.clone());
+/// let guard = StopCommandsGuard::new(sink).unwrap();
+/// let token = guard.token().to_string();
+/// guard.write_lines(&["::error::not a command".to_string()]).unwrap();
+/// drop(guard);
+///
+/// let output = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+/// assert_eq!(
+///     output,
+///     format!("::stop-commands::{token}\n::error::not a command\n::{token}::\n")
+/// );
+/// ```
+#[derive(Debug)]
+pub struct StopCommandsGuard {
+    sink: CommandSink,
+    token: String,
+}
+
+impl StopCommandsGuard {
+    /// Disable the processing of workflow commands written to the given `sink`.
+    pub fn new(sink: CommandSink) -> Result<Self, ClientError> {
+        let token = random_token();
+        sink.write_lines(&[format!("::stop-commands::{token}")])?;
+        Ok(Self { sink, token })
+    }
+
+    /// The token that resumes the processing of workflow commands.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Write the given `lines` to the guarded sink.
+    ///
+    /// The `lines` are not interpreted as workflow commands.
+    pub fn write_lines(&self, lines: &[String]) -> Result<(), ClientError> {
+        self.sink.write_lines(lines)
+    }
+}
+
+impl Drop for StopCommandsGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.sink.write_lines(&[format!("::{}::", self.token)]) {
+            log::error!("Failed to resume workflow commands: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::panic)]
+    use std::{
+        panic::{AssertUnwindSafe, catch_unwind},
+        sync::{Arc, Mutex},
+    };
+
+    use super::{CommandSink, StopCommandsGuard, random_token};
+
+    #[test]
+    fn unique_tokens() {
+        let first = random_token();
+        let second = random_token();
+        assert_eq!(first.len(), 32);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn resume_on_panic() {
+        let buffer = Arc::new(Mutex::new(Vec::<u8>::new()));
+        let sink = CommandSink::Writer(buffer.clone());
+        let token = Arc::new(Mutex::new(String::new()));
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let guard = StopCommandsGuard::new(sink).unwrap();
+            *token.lock().unwrap() = guard.token().to_string();
+            panic!("interrupted while writing untrusted content");
+        }));
+        assert!(result.is_err());
+        let token = token.lock().unwrap();
+        let output = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        assert_eq!(output, format!("::stop-commands::{token}\n::{token}::\n"));
+    }
+}
@@ -1,7 +1,19 @@
 #![cfg(any(feature = "gitea", feature = "github"))]
 
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
 use serde::Deserialize;
 
+use super::{ClientError, env_file};
+use crate::{
+    AnnotationLevel, FileAnnotation, OutputVariable, ReviewOptions, ReviewSkipReason,
+    summary::{SummaryOverflow, SummaryWriteMode, fit_markdown, fit_step_summary},
+};
+
 #[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum PullRequestState {
@@ -10,6 +22,7 @@ pub enum PullRequestState {
 }
 
 /// PR event payload.
+#[cfg(feature = "github")]
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 pub struct PullRequestEventPayload {
     /// The Pull Request's info.
@@ -22,9 +35,452 @@ pub struct PullRequestInfo {
     /// Is this PR a draft?
     pub draft: bool,
     /// Is this PR locked?
+    ///
+    /// Gitea names this field `is_locked`.
+    #[serde(alias = "is_locked")]
     pub locked: bool,
     /// The Pull Request's number.
     pub number: u64,
     /// What is current state of this PR?
     pub state: PullRequestState,
 }
+
+impl PullRequestInfo {
+    /// Get the reason (if any) that a review should not be posted on this PR.
+    ///
+    /// This only uses info from the event payload, so no extra request is made.
+    pub fn review_skip_reason(&self, options: &ReviewOptions) -> Option<ReviewSkipReason> {
+        if self.locked {
+            Some(ReviewSkipReason::Locked)
+        } else if !options.allow_closed && self.state == PullRequestState::Closed {
+            Some(ReviewSkipReason::Closed)
+        } else if !options.allow_draft && self.draft {
+            Some(ReviewSkipReason::Draft)
+        } else {
+            None
+        }
+    }
+}
+
+/// A structure for deserializing the response to a newly posted PR review.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct PostedReview {
+    /// The review's ID.
+    pub id: i64,
+    /// The URL to view the review in a browser.
+    pub html_url: Option<String>,
+}
+
+/// Escape a workflow command's data (the text after the last `::`).
+///
+/// This follows the same escaping rules as the
+/// [actions/toolkit](https://github.com/actions/toolkit/blob/main/packages/core/src/command.ts)
+/// so the CI runner does not truncate or misinterpret the command.
+pub fn escape_command_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command's property value (eg. `title=<value>`).
+///
+/// This is the same as [`escape_command_data()`] but also escapes `:` and `,`.
+pub fn escape_command_property(value: &str) -> String {
+    escape_command_data(value)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+/// Get the names of the variables already written to an output file's `content`.
+fn written_variable_names(content: &str) -> Vec<&str> {
+    let mut names = vec![];
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        // a single-line value may contain `<<`
+        if let Some((name, delimiter)) = line.split_once("<<")
+            && !name.contains('=')
+        {
+            names.push(name);
+            // skip the multi-line value
+            for value_line in lines.by_ref() {
+                if value_line == delimiter {
+                    break;
+                }
+            }
+        } else if let Some((name, _)) = line.split_once('=') {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Append the given `vars` to the file referenced by the environment variable `file_env`
+/// (eg. `GITHUB_OUTPUT` or `GITHUB_ENV`).
+///
+/// Each variable is written as exactly one entry (see [`OutputVariable::validate()`]).
+/// If a name is given more than once, then only the last value is written,
+/// and a warning names the duplicates. A warning is also logged for any names
+/// that were already written to the file by previous calls or steps.
+///
+/// See [`env_file()`] about the `strict` handling of an unset environment variable `file_env`.
+pub(crate) fn append_variables_file(
+    file_env: &str,
+    vars: &[OutputVariable],
+    strict: bool,
+) -> Result<(), ClientError> {
+    if vars.is_empty() {
+        // Should probably be an error. This check is only here to prevent needlessly
+        // fetching the env var value and opening the referenced file.
+        return Ok(());
+    }
+    let Some(path) = env_file(file_env, strict)? else {
+        return Ok(());
+    };
+
+    let mut unique: Vec<&OutputVariable> = Vec::with_capacity(vars.len());
+    let mut duplicates: Vec<&str> = vec![];
+    for var in vars {
+        let name = var.name.trim();
+        match unique.iter().position(|v| v.name.trim() == name) {
+            Some(index) => {
+                if !duplicates.contains(&name) {
+                    duplicates.push(name);
+                }
+                unique[index] = var;
+            }
+            None => unique.push(var),
+        }
+    }
+    if !duplicates.is_empty() {
+        log::warn!(
+            "Only the last value is written for duplicate {file_env} names: {}",
+            duplicates.join(", ")
+        );
+    }
+    // Validate all variables before writing any of them.
+    let entries = unique
+        .iter()
+        .map(|var| var.fmt_output_entry())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Ok(existing) = fs::read_to_string(&path) {
+        let existing = written_variable_names(&existing);
+        let overwritten = unique
+            .iter()
+            .map(|var| var.name.trim())
+            .filter(|name| existing.contains(name))
+            .collect::<Vec<_>>();
+        if !overwritten.is_empty() {
+            log::warn!(
+                "Overwriting names already written to {file_env}: {}",
+                overwritten.join(", ")
+            );
+        }
+    }
+
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .map_err(|e| ClientError::io(format!("open {file_env} file").as_str(), e))?;
+    for entry in entries {
+        writeln!(&mut file, "{entry}")
+            .map_err(|e| ClientError::io(format!("write to {file_env} file").as_str(), e))?;
+    }
+    Ok(())
+}
+
+/// Write the given `comment` to the step summary file referenced by the environment variable `file_env`.
+///
+/// Nothing is written if `file_env` is not set (see [`env_file()`] about the `strict` parameter).
+/// See [`write_summary_file()`] about the other parameters.
+pub(crate) fn write_summary_env(
+    file_env: &str,
+    strict: bool,
+    comment: &str,
+    mode: SummaryWriteMode,
+    limit: u64,
+    overflow: SummaryOverflow,
+) -> Result<(), ClientError> {
+    match env_file(file_env, strict)? {
+        Some(path) => write_summary_file(file_env, &path, comment, mode, limit, overflow),
+        None => Ok(()),
+    }
+}
+
+/// Write the given `comment` to the step summary file at `path`
+/// (referenced by the environment variable `file_env`).
+///
+/// The file is created if it does not exist.
+/// The `overflow` policy is applied if the file would become larger than `limit` bytes.
+pub(crate) fn write_summary_file(
+    file_env: &str,
+    path: &str,
+    comment: &str,
+    mode: SummaryWriteMode,
+    limit: u64,
+    overflow: SummaryOverflow,
+) -> Result<(), ClientError> {
+    // step summary MD file can be overwritten/removed in CI runners
+    let mut options = OpenOptions::new();
+    match mode {
+        SummaryWriteMode::Append => options.append(true),
+        SummaryWriteMode::Overwrite => options.write(true).truncate(true),
+    };
+    let mut file = options
+        .create(true)
+        .open(path)
+        .map_err(|e| ClientError::io(format!("open {file_env} file").as_str(), e))?;
+    let current_size = file
+        .metadata()
+        .map_err(|e| ClientError::io(format!("get size of {file_env} file").as_str(), e))?
+        .len();
+    match fit_step_summary(comment, current_size, limit, overflow)? {
+        Some(content) => file
+            .write_all(content.as_bytes())
+            .map_err(|e| ClientError::io(format!("write to {file_env} file").as_str(), e)),
+        None => {
+            log::warn!("{file_env} file is full ({current_size} bytes); comment was not appended");
+            Ok(())
+        }
+    }
+}
+
+/// Find the first position of `needle` in `haystack`.
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Replace the region delimited by `marker` in the step summary file at `path`
+/// (referenced by the environment variable `file_env`) with the given `content`.
+///
+/// The region starts with `<!-- marker:start -->` and ends with `<!-- marker:end -->`.
+/// If the region is not found, then it is appended to the file.
+/// Anything outside the region is preserved byte-for-byte.
+/// The `overflow` policy is applied if the `content` would make the file larger than `limit` bytes.
+pub(crate) fn upsert_summary_file(
+    file_env: &str,
+    path: &str,
+    marker: &str,
+    content: &str,
+    limit: u64,
+    overflow: SummaryOverflow,
+) -> Result<(), ClientError> {
+    let start_tag = format!("<!-- {marker}:start -->");
+    let end_tag = format!("<!-- {marker}:end -->");
+    // a missing file is created (like in write_summary_file())
+    let existing = match fs::read(path) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(ClientError::io(format!("read {file_env} file").as_str(), e)),
+    };
+    let region = find_bytes(&existing, start_tag.as_bytes()).and_then(|start| {
+        find_bytes(&existing[start..], end_tag.as_bytes())
+            .map(|end| (start, start + end + end_tag.len()))
+    });
+    // the size of the file without the content
+    let mut current_size = (existing.len() + start_tag.len() + end_tag.len() + 2) as u64;
+    current_size = match region {
+        Some((start, end)) => current_size - (end - start) as u64,
+        // an appended region is surrounded by blank lines
+        None => current_size + 3,
+    };
+    let Some(content) = fit_markdown(content, current_size, limit, overflow)? else {
+        log::warn!("{file_env} file is full ({current_size} bytes); section was not written");
+        return Ok(());
+    };
+    let block = format!("{start_tag}\n{content}\n{end_tag}");
+    match region {
+        Some((start, end)) => {
+            let mut updated = Vec::with_capacity(existing.len() + block.len());
+            updated.extend_from_slice(&existing[..start]);
+            updated.extend_from_slice(block.as_bytes());
+            updated.extend_from_slice(&existing[end..]);
+            fs::write(path, updated)
+                .map_err(|e| ClientError::io(format!("rewrite {file_env} file").as_str(), e))
+        }
+        None => {
+            let mut file = OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(path)
+                .map_err(|e| ClientError::io(format!("open {file_env} file").as_str(), e))?;
+            writeln!(&mut file, "\n{block}\n")
+                .map_err(|e| ClientError::io(format!("write to {file_env} file").as_str(), e))
+        }
+    }
+}
+
+/// Append the given `dir` to the file referenced by the environment variable `file_env`
+/// (eg. `GITHUB_PATH`), so the CI runner prepends it to the `PATH` of subsequent steps.
+///
+/// The `dir` must be an absolute path to an existing directory; it is canonicalized before it is written.
+/// See [`env_file()`] about the `strict` handling of an unset environment variable `file_env`.
+pub(crate) fn append_to_path_file(
+    file_env: &str,
+    dir: &Path,
+    strict: bool,
+) -> Result<(), ClientError> {
+    if !dir.is_absolute() {
+        return Err(ClientError::io(
+            "add directory to PATH",
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("path is not absolute: {}", dir.display()),
+            ),
+        ));
+    }
+    let dir = fs::canonicalize(dir).map_err(|e| ClientError::io("canonicalize directory", e))?;
+    if !dir.is_dir() {
+        return Err(ClientError::io(
+            "add directory to PATH",
+            io::Error::new(
+                io::ErrorKind::NotADirectory,
+                format!("path is not a directory: {}", dir.display()),
+            ),
+        ));
+    }
+    let Some(path_file) = env_file(file_env, strict)? else {
+        return Ok(());
+    };
+    let mut path_file = OpenOptions::new()
+        .append(true)
+        .open(path_file)
+        .map_err(|e| ClientError::io(format!("open {file_env} file").as_str(), e))?;
+    writeln!(&mut path_file, "{}", dir.display())
+        .map_err(|e| ClientError::io(format!("write to {file_env} file").as_str(), e))
+}
+
+/// Format the workflow commands that register the given `secret` with the CI runner.
+///
+/// The CI runner matches masked values per line, so a multi-line `secret`
+/// yields one `add-mask` command per non-empty line.
+pub(crate) fn fmt_mask_commands(secret: &str) -> Vec<String> {
+    secret
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| format!("::add-mask::{}", escape_command_data(line)))
+        .collect()
+}
+
+impl FileAnnotation {
+    /// Format this annotation as a workflow command (as used by GitHub and Gitea Actions).
+    ///
+    /// Special characters are escaped with [`escape_command_data()`] and [`escape_command_property()`].
+    /// The `endLine` and `endColumn` properties are only included if `end_positions` is `true`.
+    pub(crate) fn fmt_workflow_command(&self, end_positions: bool) -> String {
+        let severity = match self.severity {
+            AnnotationLevel::Debug => "debug",
+            AnnotationLevel::Notice => "notice",
+            AnnotationLevel::Warning => "warning",
+            AnnotationLevel::Error => "error",
+        };
+        let file_path = self
+            .path
+            .replace("\\", "/")
+            .trim_start()
+            .trim_start_matches('/')
+            .trim_start_matches("./")
+            .trim()
+            .to_string();
+        let mut properties = Vec::new();
+        if !file_path.is_empty() {
+            properties.push(format!("file={}", escape_command_property(&file_path)));
+            if let Some(start_line) = self.start_line.map(|l| l.max(1)) {
+                properties.push(format!("line={start_line}"));
+                let col = self.start_column.map(|c| c.max(1));
+                if let Some(col) = col {
+                    properties.push(format!("col={col}"));
+                }
+                if end_positions
+                    && let Some(end_line) = self.end_line.map(|l| l.max(1))
+                    && end_line > start_line
+                {
+                    properties.push(format!("endLine={end_line}"));
+                    if let Some(end_col) = self.end_column.map(|c| c.max(1)) {
+                        properties.push(format!("endColumn={end_col}"));
+                    }
+                } else if end_positions
+                    && let Some(end_col) = self.end_column.map(|c| c.max(1))
+                    && col.is_none_or(|c| c < end_col)
+                {
+                    properties.push(format!("endColumn={end_col}"));
+                }
+            }
+        }
+        if let Some(title) = &self.title {
+            properties.push(format!("title={}", escape_command_property(title)));
+        }
+        let message = escape_command_data(&self.message);
+        if properties.is_empty() {
+            format!("::{severity}::{message}")
+        } else {
+            format!("::{severity} {}::{message}", properties.join(","))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        escape_command_data, escape_command_property, fmt_mask_commands, written_variable_names,
+    };
+
+    #[test]
+    fn escape_data() {
+        assert_eq!(
+            escape_command_data("100% done\r\n::error::injected"),
+            "100%25 done%0D%0A::error::injected"
+        );
+    }
+
+    #[test]
+    fn escape_property() {
+        assert_eq!(escape_command_property("a,b: 50%\nc"), "a%2Cb%3A 50%25%0Ac");
+    }
+
+    #[test]
+    fn mask_single_line() {
+        assert_eq!(
+            fmt_mask_commands("ghs_abc%123"),
+            ["::add-mask::ghs_abc%25123"]
+        );
+    }
+
+    #[test]
+    fn mask_multi_line() {
+        assert_eq!(
+            fmt_mask_commands("-----BEGIN KEY-----\r\nabc\n\n-----END KEY-----\n"),
+            [
+                "::add-mask::-----BEGIN KEY-----",
+                "::add-mask::abc",
+                "::add-mask::-----END KEY-----",
+            ]
+        );
+    }
+
+    #[test]
+    fn mask_empty() {
+        assert!(fmt_mask_commands("").is_empty());
+    }
+
+    #[test]
+    fn names_in_output_file() {
+        let content = "a=1\nb<<EOF\nc=not a name\nEOF\nd=\n";
+        assert_eq!(written_variable_names(content), ["a", "b", "d"]);
+    }
+
+    #[test]
+    fn names_with_shifted_values() {
+        let content = "a=b<<c
+d=1
+c
+e<<EOF
+x<<y
+EOF
+f=
+";
+        assert_eq!(written_variable_names(content), ["a", "d", "e", "f"]);
+    }
+}
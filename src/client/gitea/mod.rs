@@ -1,32 +1,45 @@
-use std::{env, fs::OpenOptions, io::Write};
+use std::{env, path::Path, sync::Arc};
 
 use async_trait::async_trait;
-use reqwest::{Client, Method};
+use reqwest::{
+    Client, Method, Request,
+    header::{self, HeaderMap, HeaderValue},
+};
 use url::Url;
 
-use super::{ClientError, RestApiClient, RestApiRateLimitHeaders, common::PullRequestInfo};
+use super::{
+    CancellationToken, Capabilities, ClientError, CommandSink, Metrics, RateLimitSlot,
+    RateLimitSnapshot, RequestRecorder, ResponseCache, RestApiClient, RestApiRateLimitHeaders,
+    RetryPolicy, StopCommandsGuard, Throttle, Timeouts, assert_send_sync_clone, build_request,
+    common::{
+        PostedReview, append_to_path_file, append_variables_file, escape_command_data,
+        fmt_mask_commands, upsert_summary_file, write_summary_env,
+    },
+    env_file, in_span, read_file_content, sealed, user_agent_header,
+};
 use crate::{
-    OutputVariable, ReviewAction, ReviewOptions, ThreadCommentOptions,
-    client::common::PullRequestState,
+    AnnotationOverflow, CommitStatus, FileAnnotation, OutputVariable, ReviewAction, ReviewOptions,
+    ReviewOutcome, StatusState, ThreadCommentOptions,
+    summary::{SummaryOverflow, SummaryWriteMode},
 };
 mod serde_structs;
-use serde_structs::{FullReview, ReviewDiffComment};
+use serde_structs::{CommitStatusPayload, EventPayload, FullReview, ReviewDiffComment};
 mod specific_api;
+use specific_api::repo_endpoint;
 
 #[cfg(feature = "file-changes")]
 use crate::{FileDiffLines, FileFilter, LinesChangedOnly, parse_diff};
 #[cfg(feature = "file-changes")]
-use reqwest::header::{HeaderMap, HeaderValue};
-#[cfg(feature = "file-changes")]
 use std::collections::HashMap;
 
 /// A structure to work with Gitea REST API.
+#[derive(Clone)]
 pub struct GiteaApiClient {
     /// The HTTP request client to be used for all REST API calls.
     client: Client,
 
     /// The CI run's event payload from the webhook that triggered the workflow.
-    pull_request: Option<PullRequestInfo>,
+    event_payload: Option<EventPayload>,
 
     /// The name of the event that was triggered when running cpp_linter.
     pub event_name: String,
@@ -45,12 +58,73 @@ pub struct GiteaApiClient {
 
     /// The response header names that describe the rate limit status.
     rate_limit_headers: RestApiRateLimitHeaders,
+
+    /// The bookkeeping from [`RestApiClient::cull_pr_reviews()`] that
+    /// is reported by [`RestApiClient::post_pr_review()`].
+    review_outcome: ReviewOutcome,
+
+    /// The destination of workflow commands (eg. file annotations).
+    command_sink: CommandSink,
+
+    /// Is a missing environment variable for a file-based writer an error?
+    ///
+    /// See [`RestApiClient::set_strict_mode()`].
+    strict: bool,
+
+    /// See [`RestApiClient::set_dry_run()`].
+    dry_run: bool,
+
+    /// See [`RestApiClient::set_step_summary_limit()`].
+    step_summary_limit: u64,
+
+    /// See [`RestApiClient::set_retry_policy()`].
+    retry_policy: RetryPolicy,
+
+    /// See [`RestApiClient::last_rate_limit()`].
+    last_rate_limit: RateLimitSlot,
+
+    /// See [`RestApiClient::set_user_agent()`].
+    user_agent: HeaderValue,
+
+    /// The headers needed by the REST API (eg. `Accept` and `Authorization`).
+    headers: HeaderMap,
+
+    /// Was the [`Self::client`] given to [`RestApiClient::set_http_client()`]?
+    custom_http_client: bool,
+
+    /// See [`RestApiClient::set_response_cache()`].
+    response_cache: Option<Arc<ResponseCache>>,
+
+    /// See [`RestApiClient::set_request_recorder()`].
+    request_recorder: Option<Arc<RequestRecorder>>,
+
+    /// See [`RestApiClient::set_throttle()`].
+    throttle: Arc<Throttle>,
+
+    /// See [`RestApiClient::set_metrics()`].
+    metrics: Option<Arc<dyn Metrics>>,
+
+    /// See [`RestApiClient::set_cancellation_token()`].
+    cancellation_token: Option<CancellationToken>,
+
+    /// See [`RestApiClient::set_timeouts()`].
+    timeouts: Timeouts,
+
+    /// See [`RestApiClient::set_changed_files_per_page()`].
+    #[cfg(feature = "file-changes")]
+    changed_files_per_page: u32,
 }
 
+const _: () = assert_send_sync_clone::<GiteaApiClient>();
+
 #[async_trait]
 impl RestApiClient for GiteaApiClient {
     fn start_log_group(&self, name: &str) {
-        log::info!(target: "CI_LOG_GROUPING", "::group::{name}");
+        log::info!(
+            target: "CI_LOG_GROUPING",
+            "::group::{}",
+            escape_command_data(name)
+        );
     }
 
     fn end_log_group(&self, _name: &str) {
@@ -58,49 +132,127 @@ impl RestApiClient for GiteaApiClient {
     }
 
     fn is_pr_event(&self) -> bool {
-        self.pull_request.is_some()
+        self.event_payload.is_some()
     }
 
     fn set_user_agent(&mut self, user_agent: &str) -> Result<(), ClientError> {
-        self.client = Client::builder()
-            .default_headers(Self::make_headers()?)
-            .user_agent(user_agent)
-            .build()?;
+        self.user_agent = user_agent_header(user_agent)?;
         Ok(())
     }
 
-    /// Does not support push events, only PR events.
+    fn set_http_client(&mut self, client: Client) {
+        self.client = client;
+        self.custom_http_client = true;
+    }
+
+    fn throttle(&self) -> Option<&Throttle> {
+        Some(&*self.throttle)
+    }
+
+    fn set_throttle(&mut self, throttle: Throttle) {
+        self.throttle = Arc::new(throttle);
+    }
+
+    fn cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
+    fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.cancellation_token = token;
+    }
+
+    fn metrics(&self) -> Option<&dyn Metrics> {
+        self.metrics.as_deref()
+    }
+
+    fn set_metrics(&mut self, metrics: Option<Arc<dyn Metrics>>) {
+        self.metrics = metrics;
+    }
+
+    fn response_cache(&self) -> Option<&ResponseCache> {
+        self.response_cache.as_deref()
+    }
+
+    fn set_response_cache(&mut self, cache: Option<ResponseCache>) {
+        self.response_cache = cache.map(Arc::new);
+    }
+
+    fn request_recorder(&self) -> Option<&RequestRecorder> {
+        self.request_recorder.as_deref()
+    }
+
+    fn set_request_recorder(&mut self, recorder: Option<RequestRecorder>) {
+        self.request_recorder = recorder.map(Arc::new);
+    }
+
+    fn timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+
+    fn set_timeouts(&mut self, timeouts: Timeouts) -> Result<(), ClientError> {
+        if !self.custom_http_client {
+            self.client = Self::make_client(&timeouts)?;
+        }
+        self.timeouts = timeouts;
+        Ok(())
+    }
+
+    /// The headers needed by the REST API and the [`Timeouts::request`] are applied to
+    /// every request. Any given `headers` take precedence.
+    fn make_api_request(
+        &self,
+        client: &Client,
+        url: Url,
+        method: Method,
+        data: Option<String>,
+        headers: Option<HeaderMap>,
+    ) -> Result<Request, ClientError> {
+        let mut merged = self.headers.clone();
+        merged.insert(header::USER_AGENT, self.user_agent.clone());
+        if let Some(headers) = headers {
+            merged.extend(headers);
+        }
+        let mut request = build_request(client, url, method, data, Some(merged))?;
+        request.timeout_mut().get_or_insert(self.timeouts.request);
+        Ok(request)
+    }
+
+    /// Gitea only supports thread comments on PRs.
+    /// For push events, the [`ThreadCommentOptions::push_fallback`] is used instead.
     async fn post_thread_comment(&self, options: ThreadCommentOptions) -> Result<(), ClientError> {
-        let comments_url = match &self.pull_request {
+        let comments_url = match self.pull_request() {
             Some(pr_info) => {
                 if pr_info.locked {
                     return Ok(()); // cannot comment on locked PRs
                 }
-                env::var("GITEA_TOKEN").map_err(|e| ClientError::env_var("GITEA_TOKEN", e))?;
+                Self::env_setting("TOKEN")?;
                 self.api_url.join(
                     format!("repos/{}/issues/{}/comments", self.repo, pr_info.number).as_str(),
                 )?
             }
             None => {
                 // This feature is supported in non-PR events on other git servers.
-                // But Gitea only supports comments on PRs or issues.
-                // Leave a informative log entry to highlight this and return early.
-                log::info!(
-                    "Gitea support for posting thread comments is limited to pull requests only."
-                );
-                return Ok(());
+                // But Gitea only supports comments on PRs or issues (see `Self::capabilities()`).
+                return self.post_push_comment_fallback(&options);
             }
         };
-        self.update_comment(comments_url, options).await
+        let operation = self.timeouts.with_deadline(
+            "post thread comment",
+            self.update_comment(comments_url, options),
+        );
+        in_span!(
+            operation,
+            "post_thread_comment",
+            repo = self.repo.as_str(),
+            pr = self.pull_request().map(|pr| pr.number),
+        )
+        .await
     }
 
     async fn cull_pr_reviews(&mut self, options: &mut ReviewOptions) -> Result<(), ClientError> {
-        if let Some(pr_info) = self.pull_request.as_ref() {
+        if let Some(pr_info) = self.pull_request().cloned() {
             // Guard checks for unsuitable PR states
-            if (!options.allow_draft && pr_info.draft)
-                || (!options.allow_closed && pr_info.state == PullRequestState::Closed)
-                || pr_info.locked
-            {
+            if pr_info.review_skip_reason(options).is_some() {
                 return Ok(());
             }
 
@@ -156,7 +308,10 @@ impl RestApiClient for GiteaApiClient {
             }
 
             // Remove reused comments from proposed comments
+            let proposed_comments = options.comments.len();
             options.comments.retain(|c| !reused_comments.contains(c));
+            self.review_outcome.reused_comments = proposed_comments - options.comments.len();
+            self.review_outcome.dismissed_reviews = outdated_review_ids.len();
 
             // Delete outdated comments and reviews
             if !outdated_comment_ids.is_empty() || !outdated_review_ids.is_empty() {
@@ -165,6 +320,7 @@ impl RestApiClient for GiteaApiClient {
                     outdated_comment_ids,
                     outdated_review_ids,
                     options.delete_review_comments,
+                    &options.dismissal_text(),
                 )
                 .await?;
             }
@@ -172,100 +328,277 @@ impl RestApiClient for GiteaApiClient {
         Ok(())
     }
 
-    async fn post_pr_review(&mut self, options: &ReviewOptions) -> Result<(), ClientError> {
-        if let Some(pr_info) = self.pull_request.as_ref() {
-            if (!options.allow_draft && pr_info.draft)
-                || (!options.allow_closed && pr_info.state == PullRequestState::Closed)
-                || pr_info.locked
-            {
-                return Ok(());
-            }
-            env::var("GITEA_TOKEN").map_err(|e| ClientError::env_var("GITEA_TOKEN", e))?;
-            let url = self
-                .api_url
-                .join(format!("repos/{}/pulls/{}/reviews", self.repo, pr_info.number).as_str())?;
-            let payload = FullReview {
-                event: match options.action {
-                    ReviewAction::Comment => String::from("COMMENT"),
-                    ReviewAction::Approve => String::from("APPROVED"),
-                    ReviewAction::RequestChanges => String::from("REQUEST_CHANGES"),
-                },
-                body: format!("{}{}", options.marker, options.summary),
-                comments: options
-                    .comments
-                    .iter()
-                    .map(ReviewDiffComment::from)
-                    .map(|mut r| {
-                        if !r.body.starts_with(&options.marker) {
-                            r.body = format!("{}{}", options.marker, r.body);
+    async fn post_pr_review(
+        &mut self,
+        options: &ReviewOptions,
+    ) -> Result<ReviewOutcome, ClientError> {
+        let mut outcome = std::mem::take(&mut self.review_outcome);
+        in_span!(
+            async move {
+                if let Some(pr_info) = self.pull_request() {
+                    if let Some(reason) = pr_info.review_skip_reason(options) {
+                        log::info!("Skipped posting a review because the PR is {reason}");
+                        outcome.skipped = Some(reason);
+                        return Ok(outcome);
+                    }
+                    Self::env_setting("TOKEN")?;
+                    let url = self.api_url.join(
+                        format!("repos/{}/pulls/{}/reviews", self.repo, pr_info.number).as_str(),
+                    )?;
+                    let payload = FullReview {
+                        event: match options.action {
+                            ReviewAction::Comment => String::from("COMMENT"),
+                            ReviewAction::Approve => String::from("APPROVED"),
+                            ReviewAction::RequestChanges => String::from("REQUEST_CHANGES"),
+                        },
+                        body: format!("{}{}", options.marker, options.summary),
+                        comments: options
+                            .comments
+                            .iter()
+                            .map(|comment| ReviewDiffComment {
+                                body: comment.mark_comment(&options.marker),
+                                ..ReviewDiffComment::from(comment)
+                            })
+                            .collect(),
+                        commit_id: self.sha.clone(),
+                    };
+                    let request = self.make_api_request(
+                        &self.client,
+                        url,
+                        Method::POST,
+                        Some(
+                            serde_json::to_string(&payload)
+                                .map_err(|e| ClientError::json("serialize PR review payload", e))?,
+                        ),
+                        None,
+                    )?;
+                    let response = self
+                        .send_api_request(&self.client, request, &self.rate_limit_headers)
+                        .await;
+                    match response {
+                        Ok(response) => {
+                            let response =
+                                ClientError::check_response(response, "post PR review").await?;
+                            // a dry run does not actually create a review
+                            if !self.dry_run {
+                                let review = serde_json::from_str::<PostedReview>(
+                                    response.text().await?.as_str(),
+                                )
+                                .map_err(|e| {
+                                    ClientError::json("deserialize posted PR review", e)
+                                })?;
+                                outcome.review_id = Some(review.id);
+                                outcome.html_url =
+                                    review.html_url.and_then(|u| Url::parse(&u).ok());
+                            }
+                            outcome.posted_comments = payload.comments.len();
                         }
-                        r
-                    })
-                    .collect(),
-                commit_id: self.sha.clone(),
-            };
+                        Err(e) => {
+                            return Err(e.with_context("post PR review"));
+                        }
+                    }
+                }
+                Ok(outcome)
+            },
+            "post_pr_review",
+            repo = self.repo.as_str(),
+            pr = self.pull_request().map(|pr| pr.number),
+        )
+        .await
+    }
+
+    /// The status is posted for the PR's head commit (if known) or the commit that triggered the workflow.
+    async fn post_commit_status(&self, status: &CommitStatus) -> Result<(), ClientError> {
+        status.validate()?;
+        Self::env_setting("TOKEN")?;
+        let sha = self.head_sha().unwrap_or(self.sha.as_str());
+        let url = repo_endpoint(&self.api_url, &self.repo, &["statuses", sha])?;
+        let payload = CommitStatusPayload {
+            state: match status.state {
+                StatusState::Pending => "pending",
+                StatusState::Success => "success",
+                StatusState::Error => "error",
+                StatusState::Failure => "failure",
+                StatusState::Warning => "warning",
+            }
+            .to_string(),
+            context: status.context.clone(),
+            description: status.truncated_description(),
+            target_url: status.target_url.clone(),
+        };
+        let operation = self.timeouts.with_deadline("post commit status", async {
             let request = self.make_api_request(
                 &self.client,
                 url,
                 Method::POST,
                 Some(
                     serde_json::to_string(&payload)
-                        .map_err(|e| ClientError::json("serialize PR review payload", e))?,
+                        .map_err(|e| ClientError::json("serialize commit status payload", e))?,
                 ),
                 None,
             )?;
             let response = self
                 .send_api_request(&self.client, request, &self.rate_limit_headers)
-                .await;
-            match response {
-                Ok(response) => {
-                    self.log_response(response, "Failed to post PR review")
-                        .await;
-                }
-                Err(e) => {
-                    return Err(e.add_request_context("post PR review"));
-                }
-            }
+                .await?;
+            ClientError::check_response(response, "post commit status").await?;
+            Ok(())
+        });
+        in_span!(
+            operation,
+            "post_commit_status",
+            repo = self.repo.as_str(),
+            sha = sha,
+        )
+        .await
+    }
+
+    fn write_step_summary(&self, comment: &str, mode: SummaryWriteMode) -> Result<(), ClientError> {
+        write_summary_env(
+            &Self::env_var_name("STEP_SUMMARY"),
+            self.strict,
+            comment,
+            mode,
+            self.step_summary_limit,
+            SummaryOverflow::default(),
+        )
+    }
+
+    fn upsert_step_summary(&self, marker: &str, content: &str) -> Result<(), ClientError> {
+        let name = Self::env_var_name("STEP_SUMMARY");
+        if let Some(gh_out) = env_file(&name, self.strict)? {
+            return upsert_summary_file(
+                &name,
+                &gh_out,
+                marker,
+                content,
+                self.step_summary_limit,
+                SummaryOverflow::default(),
+            );
         }
         Ok(())
     }
 
     fn write_output_variables(&self, vars: &[OutputVariable]) -> Result<(), ClientError> {
-        if vars.is_empty() {
-            // Should probably be an error. This check is only here to prevent needlessly
-            // fetching the env var GITEA_OUTPUT value and opening the referenced file.
-            return Ok(());
-        }
-        if let Ok(gh_out) = env::var("GITEA_OUTPUT") {
-            return match OpenOptions::new().append(true).open(gh_out) {
-                Ok(mut gh_out_file) => {
-                    for out_var in vars {
-                        out_var.validate()?;
-                        writeln!(&mut gh_out_file, "{}={}\n", out_var.name, out_var.value)
-                            .map_err(|e| ClientError::io("write to GITEA_OUTPUT file", e))?;
-                    }
-                    Ok(())
-                }
-                Err(e) => Err(ClientError::io("write to GITEA_OUTPUT file", e)),
-            };
-        }
-        Ok(())
+        append_variables_file(&Self::env_var_name("OUTPUT"), vars, self.strict)
     }
 
-    fn append_step_summary(&self, comment: &str) -> Result<(), ClientError> {
-        if let Ok(gh_out) = env::var("GITEA_STEP_SUMMARY") {
-            // step summary MD file can be overwritten/removed in CI runners
-            return match OpenOptions::new().append(true).open(gh_out) {
-                Ok(mut gh_out_file) => {
-                    let result = writeln!(&mut gh_out_file, "\n{comment}\n");
-                    result.map_err(|e| ClientError::io("write to GITHUB_STEP_SUMMARY file", e))
-                }
-                Err(e) => Err(ClientError::io("write to GITHUB_STEP_SUMMARY file", e)),
-            };
+    fn export_environment_variables(&self, vars: &[OutputVariable]) -> Result<(), ClientError> {
+        append_variables_file(&Self::env_var_name("ENV"), vars, self.strict)
+    }
+
+    fn write_state_variables(&self, vars: &[OutputVariable]) -> Result<(), ClientError> {
+        append_variables_file(&Self::env_var_name("STATE"), vars, self.strict)
+    }
+
+    fn read_state_variable(&self, name: &str) -> Option<String> {
+        env::var(format!("STATE_{}", name.trim())).ok()
+    }
+
+    fn add_to_path(&self, dir: &Path) -> Result<(), ClientError> {
+        append_to_path_file(&Self::env_var_name("PATH"), dir, self.strict)
+    }
+
+    fn set_strict_mode(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    fn last_rate_limit(&self) -> Option<RateLimitSnapshot> {
+        self.last_rate_limit.get()
+    }
+
+    fn record_rate_limit(&self, snapshot: RateLimitSnapshot, _token: sealed::Token) {
+        self.last_rate_limit.set(snapshot);
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = policy;
+    }
+
+    fn set_command_sink(&mut self, sink: CommandSink) {
+        self.command_sink = sink;
+    }
+
+    fn mask_secret(&self, secret: &str) -> Result<(), ClientError> {
+        self.command_sink.write_lines(&fmt_mask_commands(secret))
+    }
+
+    fn log_untrusted(&self, content: &str) -> Result<(), ClientError> {
+        let guard = StopCommandsGuard::new(self.command_sink.clone())?;
+        guard.write_lines(&content.lines().map(str::to_string).collect::<Vec<_>>())
+    }
+
+    /// Gitea's act_runner uses the same workflow commands as GitHub Actions.
+    ///
+    /// The `endLine` and `endColumn` properties are omitted because act_runner does not support them.
+    /// There is no known limit to the number of annotations, so the `overflow` policy is not used.
+    fn write_file_annotations(
+        &self,
+        annotations: &[FileAnnotation],
+        _overflow: AnnotationOverflow,
+    ) -> Result<usize, ClientError> {
+        if annotations.is_empty() {
+            return Ok(0);
         }
-        Ok(())
+        let commands = annotations
+            .iter()
+            .map(|a| a.fmt_workflow_command(false))
+            .collect::<Vec<_>>();
+        self.command_sink.write_lines(&commands)?;
+        Ok(commands.len())
+    }
+
+    fn append_step_summary(
+        &self,
+        comment: &str,
+        overflow: SummaryOverflow,
+    ) -> Result<(), ClientError> {
+        write_summary_env(
+            &Self::env_var_name("STEP_SUMMARY"),
+            self.strict,
+            comment,
+            SummaryWriteMode::Append,
+            self.step_summary_limit,
+            overflow,
+        )
+    }
+
+    /// act_runner does not document a size limit for step summaries,
+    /// so GitHub's [`MAX_STEP_SUMMARY_SIZE`](crate::summary::MAX_STEP_SUMMARY_SIZE) is assumed
+    /// unless changed with [`Self::set_step_summary_limit()`].
+    fn step_summary_limit(&self) -> u64 {
+        self.step_summary_limit
+    }
+
+    fn set_step_summary_limit(&mut self, limit: u64) {
+        self.step_summary_limit = limit;
+    }
+
+    #[cfg(feature = "file-changes")]
+    fn changed_files_per_page(&self) -> Option<u32> {
+        Some(self.changed_files_per_page)
     }
 
+    /// Gitea caps the page size at its `MAX_RESPONSE_ITEMS` setting (`50` by default).
+    #[cfg(feature = "file-changes")]
+    fn set_changed_files_per_page(&mut self, per_page: u32) {
+        self.changed_files_per_page = per_page;
+    }
+
+    /// For PR events, the changed files are listed by the paginated `pulls/{index}/files` endpoint.
+    /// The PR's `.diff` is parsed instead if the server lacks that endpoint or
+    /// omits the patches of the files.
     #[cfg(feature = "file-changes")]
     #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
     async fn get_list_of_changed_files(
@@ -275,28 +608,88 @@ impl RestApiClient for GiteaApiClient {
         _base_diff: Option<String>,
         _ignore_index: bool,
     ) -> Result<HashMap<String, FileDiffLines>, ClientError> {
-        let url_path = match &self.pull_request {
-            Some(pr_info) => format!("repos/{}/pulls/{}.diff", self.repo, pr_info.number),
-            None => format!("repos/{}/commits/{}.diff", self.repo, self.sha),
-        };
-        let url = self.api_url.join(&url_path)?;
-        let mut headers = HeaderMap::new();
-        headers.insert("Accept", HeaderValue::from_str("text/plain")?);
-        let request = self.make_api_request(&self.client, url, Method::GET, None, Some(headers))?;
-        let response = self
-            .send_api_request(&self.client, request, &self.rate_limit_headers)
-            .await?;
-        if let Err(e) = response.error_for_status_ref() {
-            if let Ok(body) = response.text().await {
-                log::error!("Failed to get list of changed files: {e:?}\n{body}");
-            }
-            return Err(ClientError::Request(e).add_request_context("get list of changed files"));
-        }
-        let body = (response.text()).await?.to_string();
-        parse_diff(&body, file_filter, lines_changed_only).map_err(ClientError::DiffError)
+        let operation = self
+            .timeouts
+            .with_deadline("get list of changed files", async {
+                if let Some(pr_info) = self.pull_request()
+                    && let Some(files) = self
+                        .get_pr_changed_files(pr_info.number, file_filter, lines_changed_only)
+                        .await?
+                {
+                    return Ok(files);
+                }
+                let url_path = match self.pull_request() {
+                    Some(pr_info) => format!("repos/{}/pulls/{}.diff", self.repo, pr_info.number),
+                    None => format!("repos/{}/commits/{}.diff", self.repo, self.sha),
+                };
+                let url = self.api_url.join(&url_path)?;
+                let mut headers = HeaderMap::new();
+                headers.insert("Accept", HeaderValue::from_str("text/plain")?);
+                let request =
+                    self.make_api_request(&self.client, url, Method::GET, None, Some(headers))?;
+                let response = self
+                    .send_api_request(&self.client, request, &self.rate_limit_headers)
+                    .await?;
+                if let Err(e) = response.error_for_status_ref() {
+                    if let Ok(body) = response.text().await {
+                        log::error!("Failed to get list of changed files: {e:?}\n{body}");
+                    }
+                    return Err(ClientError::Request(e).with_context("get list of changed files"));
+                }
+                let body = (response.text()).await?.to_string();
+                parse_diff(&body, file_filter, lines_changed_only).map_err(ClientError::DiffError)
+            });
+        in_span!(
+            operation,
+            "get_list_of_changed_files",
+            repo = self.repo.as_str(),
+            pr = self.pull_request().map(|pr| pr.number),
+        )
+        .await
+    }
+
+    /// The file is requested from the `repos/{repo}/raw/{reference}/{path}` endpoint.
+    async fn get_file_content(
+        &self,
+        path: &str,
+        reference: Option<&str>,
+    ) -> Result<String, ClientError> {
+        let task = format!("get content of {path}");
+        let reference = reference.unwrap_or(self.sha.as_str());
+        let mut segments = vec!["raw", reference];
+        segments.extend(path.trim_start_matches('/').split('/'));
+        let url = repo_endpoint(&self.api_url, &self.repo, &segments)?;
+        let operation = self.timeouts.with_deadline(&task, async {
+            let request = self.make_api_request(&self.client, url, Method::GET, None, None)?;
+            let response = self
+                .send_api_request(&self.client, request, &self.rate_limit_headers)
+                .await
+                .map_err(|e| e.with_context(&task))?;
+            let response = ClientError::check_response(response, &task).await?;
+            read_file_content(response, path, &task).await
+        });
+        in_span!(
+            operation,
+            "get_file_content",
+            repo = self.repo.as_str(),
+            reference = reference,
+        )
+        .await
     }
 
     fn client_kind(&self) -> String {
         "gitea".to_string()
     }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            // Gitea only supports comments on PRs or issues
+            thread_comments_on_push: false,
+            pr_reviews: true,
+            file_annotations: true,
+            step_summary: true,
+            output_variables: true,
+            commit_statuses: true,
+        }
+    }
 }
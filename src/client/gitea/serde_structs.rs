@@ -3,6 +3,108 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::client::common::{PullRequestInfo, PullRequestState};
+
+/// A structure for deserializing the event payload of a `pull_request` webhook.
+///
+/// Unknown fields are ignored.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct EventPayload {
+    /// The Pull Request's number.
+    pub number: Option<u64>,
+    /// The Pull Request's info.
+    pub pull_request: PullRequest,
+    /// The repository that the Pull Request targets.
+    pub repository: Option<Repository>,
+}
+
+/// A structure for deserializing a Pull Request from an event payload.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct PullRequest {
+    /// The Pull Request's number, state, etc.
+    #[serde(flatten)]
+    pub info: PullRequestInfo,
+    /// The branch being merged.
+    pub head: Option<BranchRef>,
+    /// The branch being merged into.
+    pub base: Option<BranchRef>,
+}
+
+/// A structure for deserializing the `head` or `base` of a Pull Request.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct BranchRef {
+    /// The name of the branch.
+    #[serde(rename = "ref")]
+    pub name: String,
+    /// The SHA of the branch's latest commit.
+    pub sha: Option<String>,
+}
+
+/// A structure for deserializing a repository from an event payload.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct Repository {
+    /// The repository's name prefixed with its owner (eg. `owner/repo`).
+    pub full_name: String,
+}
+
+/// A structure for deserializing the event payload of an `issue_comment` webhook.
+///
+/// Gitea triggers this event for comments on issues and Pull Requests alike.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct IssueCommentPayload {
+    /// The issue (or Pull Request) that was commented on.
+    pub issue: Issue,
+    /// The repository that the issue belongs to.
+    pub repository: Option<Repository>,
+}
+
+/// A structure for deserializing an issue from an event payload.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct Issue {
+    /// The issue's number.
+    pub number: u64,
+    /// Is this issue locked?
+    #[serde(default, alias = "is_locked")]
+    pub locked: bool,
+    /// What is current state of this issue?
+    pub state: PullRequestState,
+    /// Only present if the issue is a Pull Request.
+    pub pull_request: Option<IssuePullRequest>,
+}
+
+/// A structure for deserializing the Pull Request info of an [`Issue`].
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct IssuePullRequest {
+    /// Is this PR a draft?
+    ///
+    /// Older versions of Gitea do not provide this.
+    #[serde(default)]
+    pub draft: bool,
+}
+
+impl IssueCommentPayload {
+    /// Convert this payload into an [`EventPayload`] if the issue is a Pull Request.
+    ///
+    /// The Pull Request's branches are not described by this payload.
+    pub fn into_event_payload(self) -> Option<EventPayload> {
+        let pull_request = self.issue.pull_request?;
+        Some(EventPayload {
+            number: Some(self.issue.number),
+            pull_request: PullRequest {
+                info: PullRequestInfo {
+                    draft: pull_request.draft,
+                    locked: self.issue.locked,
+                    number: self.issue.number,
+                    state: self.issue.state,
+                },
+                head: None,
+                base: None,
+            },
+            repository: self.repository,
+        })
+    }
+}
+
 /// A structure for deserializing a comment from a response's json.
 #[derive(Debug, Deserialize, PartialEq, Clone)]
 pub struct ThreadComment {
@@ -33,6 +135,41 @@ pub struct FullReview {
     pub event: String,
 }
 
+/// A structure for deserializing a file changed in a Pull Request.
+///
+/// Unknown fields are ignored.
+#[cfg(feature = "file-changes")]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct ChangedFile {
+    /// The file's name (including relative path to repo root)
+    pub filename: String,
+    /// If renamed, this will be the file's old name.
+    ///
+    /// Gitea uses an empty string (instead of `null`) if the file was not renamed.
+    #[serde(default)]
+    pub previous_filename: Option<String>,
+    /// How the file was changed (eg. `added`, `deleted`, `changed`, `renamed`).
+    #[serde(default)]
+    pub status: String,
+    /// The individual patch that describes the file's changes.
+    ///
+    /// Not all versions of Gitea provide this.
+    #[serde(default)]
+    pub patch: Option<String>,
+    /// The number of changes to the file contents.
+    pub changes: i64,
+}
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct CommitStatusPayload {
+    pub state: String,
+    pub context: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_url: Option<String>,
+}
+
 #[derive(Debug, Serialize, PartialEq, Clone)]
 pub struct ReviewDiffComment {
     pub body: String,
@@ -102,4 +239,75 @@ pub struct GiteaReviewComment {
     pub new_position: i64,
     /// The comment body text.
     pub body: String,
+    /// The user that resolved the comment (if resolved).
+    #[serde(default)]
+    pub resolver: Option<User>,
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use std::fs;
+
+    use super::{EventPayload, IssueCommentPayload};
+    use crate::client::common::PullRequestState;
+
+    #[test]
+    fn pull_request_event_payload() {
+        let payload =
+            fs::read_to_string("tests/assets/event_payload/gitea/pull_request.json").unwrap();
+        let payload = serde_json::from_str::<EventPayload>(&payload).unwrap();
+        assert_eq!(payload.number, Some(7));
+        let pr = payload.pull_request;
+        assert_eq!(pr.info.number, 7);
+        assert!(pr.info.draft);
+        assert!(!pr.info.locked);
+        assert_eq!(pr.info.state, PullRequestState::Open);
+        let head = pr.head.unwrap();
+        assert_eq!(head.name, "feature");
+        assert_eq!(
+            head.sha.as_deref(),
+            Some("a1b2c3d4e5f60718293a4b5c6d7e8f9012345678")
+        );
+        assert_eq!(pr.base.unwrap().name, "main");
+        assert_eq!(
+            payload.repository.unwrap().full_name,
+            "2bndy5/git-bot-feedback"
+        );
+    }
+
+    #[test]
+    fn minimal_event_payload() {
+        let payload = serde_json::from_str::<EventPayload>(
+            r#"{"pull_request": {"number": 42, "draft": false, "locked": true, "state": "closed"}}"#,
+        )
+        .unwrap();
+        assert_eq!(payload.number, None);
+        assert!(payload.pull_request.info.locked);
+        assert!(payload.pull_request.head.is_none());
+        assert!(payload.repository.is_none());
+    }
+
+    #[test]
+    fn issue_comment_event_payload() {
+        let payload =
+            fs::read_to_string("tests/assets/event_payload/gitea/issue_comment.json").unwrap();
+        let payload = serde_json::from_str::<IssueCommentPayload>(&payload)
+            .unwrap()
+            .into_event_payload()
+            .unwrap();
+        assert_eq!(payload.number, Some(7));
+        let pr = payload.pull_request;
+        assert_eq!(pr.info.number, 7);
+        assert!(!pr.info.draft);
+        assert!(!pr.info.locked);
+        assert_eq!(pr.info.state, PullRequestState::Open);
+        assert!(pr.head.is_none());
+
+        let payload =
+            fs::read_to_string("tests/assets/event_payload/gitea/issue_comment_issue.json")
+                .unwrap();
+        let payload = serde_json::from_str::<IssueCommentPayload>(&payload).unwrap();
+        assert!(payload.into_event_payload().is_none());
+    }
 }
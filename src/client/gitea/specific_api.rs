@@ -2,78 +2,442 @@
 
 use super::{
     GiteaApiClient,
-    serde_structs::{GiteaReviewComment, ReviewInfo, ThreadComment},
+    serde_structs::{
+        EventPayload, GiteaReviewComment, IssueCommentPayload, ReviewInfo, ThreadComment,
+    },
 };
+#[cfg(feature = "file-changes")]
+use crate::client::{DEFAULT_CHANGED_FILES_PER_PAGE, check_cancelled};
 use crate::{
-    CommentKind, CommentPolicy, RestApiClient, RestApiRateLimitHeaders, ReviewComment,
-    ThreadCommentOptions,
-    client::{ClientError, USER_AGENT, common::PullRequestEventPayload},
+    CommentFallback, CommentKind, CommentPolicy, RestApiClient, RestApiRateLimitHeaders,
+    ReviewComment, ReviewOutcome, ThreadCommentOptions,
+    client::{
+        ClientError, CommandSink, Paginator, RateLimitDetection, RateLimitSlot, RetryPolicy,
+        Throttle, Timeouts, USER_AGENT, common::PullRequestInfo, log_unsupported,
+    },
+    comments::review_comments::PostedReviewComment,
+    summary::{MAX_STEP_SUMMARY_SIZE, SummaryOverflow},
 };
 use reqwest::{
     Client, Method, Url,
     header::{AUTHORIZATION, HeaderMap, HeaderValue},
 };
-use std::{collections::HashMap, env, fs};
+use std::{collections::HashMap, env, fs, sync::Arc};
+
+#[cfg(feature = "file-changes")]
+use super::serde_structs::ChangedFile;
+#[cfg(feature = "file-changes")]
+use crate::{FileDiffLines, FileFilter, FileStatus, LinesChangedOnly, parse_diff};
+#[cfg(feature = "file-changes")]
+use reqwest::StatusCode;
+#[cfg(feature = "file-changes")]
+use std::path::Path;
+
+/// The prefixes of environment variables that provide a setting, in order of precedence.
+///
+/// Forgejo (a fork of Gitea) uses the `FORGEJO_` prefix.
+/// Both also set `GITHUB_` variables for compatibility with GitHub workflows.
+const ENV_PREFIXES: [&str; 3] = ["FORGEJO", "GITEA", "GITHUB"];
+
+/// The shapes of event payloads, as far as feedback is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EventKind {
+    /// The payload describes a Pull Request (in its `pull_request` field).
+    PullRequest,
+    /// The payload describes a commented issue (in its `issue` field),
+    /// which may be a Pull Request.
+    IssueComment,
+    /// The payload does not describe a Pull Request (eg. a `push` event).
+    Other,
+}
+
+/// The names of events (used by Gitea and Forgejo) whose payload may describe a Pull Request.
+const PR_EVENTS: [(&str, EventKind); 9] = [
+    ("pull_request", EventKind::PullRequest),
+    ("pull_request_target", EventKind::PullRequest),
+    ("pull_request_sync", EventKind::PullRequest),
+    ("pull_request_review", EventKind::PullRequest),
+    ("pull_request_review_approved", EventKind::PullRequest),
+    ("pull_request_review_rejected", EventKind::PullRequest),
+    ("pull_request_review_comment", EventKind::PullRequest),
+    ("pull_request_comment", EventKind::IssueComment),
+    ("issue_comment", EventKind::IssueComment),
+];
+
+impl EventKind {
+    /// Get the kind of payload for the given `event_name`.
+    fn from_event_name(event_name: &str) -> Self {
+        PR_EVENTS
+            .iter()
+            .find(|(name, _)| *name == event_name)
+            .map(|(_, kind)| *kind)
+            .unwrap_or(Self::Other)
+    }
+
+    /// Deserialize the event `payload`.
+    ///
+    /// Returns [`None`] if the payload does not describe a Pull Request.
+    fn parse_payload(self, payload: &str) -> Result<Option<EventPayload>, ClientError> {
+        let task = "deserialize event payload";
+        match self {
+            Self::PullRequest => serde_json::from_str::<EventPayload>(payload)
+                .map(Some)
+                .map_err(|e| ClientError::json(task, e)),
+            Self::IssueComment => serde_json::from_str::<IssueCommentPayload>(payload)
+                .map(IssueCommentPayload::into_event_payload)
+                .map_err(|e| ClientError::json(task, e)),
+            Self::Other => Ok(None),
+        }
+    }
+}
+
+/// Parse the given server or API `url` into the REST API's root URL (ending with `/api/v1/`).
+///
+/// The trailing slash is needed for [`Url::join()`] to append paths instead of replacing
+/// the last path segment.
+fn normalize_api_url(url: &str) -> Result<Url, ClientError> {
+    let url = url.trim().trim_end_matches('/');
+    let url = if url.ends_with("/api/v1") {
+        format!("{url}/")
+    } else {
+        format!("{url}/api/v1/")
+    };
+    Ok(Url::parse(&url)?)
+}
+
+/// Get the URL of the REST API endpoint for the `repo` at the given `path`.
+///
+/// Each item of `path` is a path segment (which is percent-encoded as needed).
+/// End the `path` with an empty segment to get a URL that ends with a slash,
+/// so that [`Url::join()`] appends to it.
+pub(super) fn repo_endpoint(api_url: &Url, repo: &str, path: &[&str]) -> Result<Url, ClientError> {
+    let mut url = api_url.clone();
+    url.path_segments_mut()
+        .map_err(|_| ClientError::UrlParse(url::ParseError::RelativeUrlWithCannotBeABaseBase))?
+        .pop_if_empty()
+        .push("repos")
+        .extend(repo.split('/'))
+        .extend(path);
+    Ok(url)
+}
 
 impl GiteaApiClient {
     /// Instantiate a [`GiteaApiClient`] object.
+    ///
+    /// This also supports Forgejo runners.
+    /// Each setting is read from the first environment variable that is set among
+    /// `FORGEJO_*`, `GITEA_*`, and `GITHUB_*` (see [`Self::env_var_name()`]).
+    ///
+    /// The REST API's URL is read from the `API_URL` setting (eg. `GITEA_API_URL`).
+    /// If that is not set, then it is derived from the `SERVER_URL` setting
+    /// (eg. `GITEA_SERVER_URL`) by appending `/api/v1/`.
+    ///
+    /// Besides `pull_request`, other events are treated as PR events if their payload
+    /// describes a Pull Request (eg. `pull_request_target`, or `issue_comment` on a PR).
     pub fn new() -> Result<Self, ClientError> {
-        let event_name = env::var("GITEA_EVENT_NAME").unwrap_or(String::from("unknown"));
-        let pull_request = {
-            match event_name.as_str() {
-                "pull_request" => {
-                    // GITEA_*** env vars cannot be overwritten in CI runners on GitHub.
-                    let event_payload_path = env::var("GITEA_EVENT_PATH")
-                        .map_err(|e| ClientError::env_var("GITEA_EVENT_PATH", e))?;
-                    // event payload JSON file can be overwritten/removed in CI runners
-                    let file_buf = fs::read_to_string(event_payload_path.clone())
-                        .map_err(|e| ClientError::io("read event payload", e))?;
-                    let pr_info = serde_json::from_str::<PullRequestEventPayload>(&file_buf)
-                        .map_err(|e| ClientError::json("deserialize event payload", e))?
-                        .pull_request;
-                    Some(pr_info)
-                }
-                _ => None,
+        let event_name = Self::env_setting("EVENT_NAME").unwrap_or(String::from("unknown"));
+        let event_payload = match EventKind::from_event_name(&event_name) {
+            EventKind::Other => None,
+            kind => {
+                // GITEA_*** env vars cannot be overwritten in CI runners on GitHub.
+                let event_payload_path = Self::env_setting("EVENT_PATH")?;
+                // event payload JSON file can be overwritten/removed in CI runners
+                let file_buf = fs::read_to_string(event_payload_path.clone())
+                    .map_err(|e| ClientError::io("read event payload", e))?;
+                kind.parse_payload(&file_buf)?
             }
         };
-        // GITEA_*** env vars cannot be overwritten in CI runners on GitHub.
-        let gh_api_url = format!(
-            "{}/api/v1/",
-            env::var("GITEA_API_URL").map_err(|e| ClientError::env_var("GITEA_API_URL", e))?
-        );
-        let api_url = Url::parse(gh_api_url.as_str())?;
+        let api_url = Self::resolve_api_url()?;
 
         Ok(Self {
-            client: Client::builder()
-                .default_headers(Self::make_headers()?)
-                .user_agent(USER_AGENT)
-                .build()?,
-            pull_request,
+            client: Self::make_client(&Timeouts::default())?,
+            custom_http_client: false,
+            response_cache: None,
+            request_recorder: None,
+            throttle: Arc::new(Throttle::default()),
+            metrics: None,
+            cancellation_token: None,
+            headers: Self::make_headers()?,
+            event_payload,
             event_name,
             api_url,
-            repo: env::var("GITEA_REPOSITORY")
-                .map_err(|e| ClientError::env_var("GITEA_REPOSITORY", e))?,
-            sha: env::var("GITEA_SHA").map_err(|e| ClientError::env_var("GITEA_SHA", e))?,
+            repo: Self::env_setting("REPOSITORY")?,
+            sha: Self::env_setting("SHA")?,
             debug_enabled: env::var("ACTIONS_STEP_DEBUG").is_ok_and(|val| &val == "true"),
             rate_limit_headers: RestApiRateLimitHeaders {
                 reset: "x-ratelimit-reset".to_string(),
                 remaining: "x-ratelimit-remaining".to_string(),
                 retry: "retry-after".to_string(),
             },
+            review_outcome: ReviewOutcome::default(),
+            command_sink: CommandSink::default(),
+            strict: false,
+            dry_run: false,
+            step_summary_limit: MAX_STEP_SUMMARY_SIZE,
+            // most self-hosted instances do not send rate limit headers
+            retry_policy: RetryPolicy {
+                rate_limit_detection: RateLimitDetection::StatusOnly,
+                ..Default::default()
+            },
+            last_rate_limit: RateLimitSlot::default(),
+            user_agent: HeaderValue::from_static(USER_AGENT),
+            timeouts: Timeouts::default(),
+            #[cfg(feature = "file-changes")]
+            changed_files_per_page: DEFAULT_CHANGED_FILES_PER_PAGE,
         })
     }
 
+    /// Get the name of the environment variable that provides the given `setting`.
+    ///
+    /// The `setting` is the variable's name without a prefix (eg. `"TOKEN"`).
+    /// This returns the first variable that is set among `FORGEJO_{setting}`,
+    /// `GITEA_{setting}`, and `GITHUB_{setting}`.
+    /// If none are set, then `GITEA_{setting}` is returned.
+    ///
+    /// This is useful to diagnose which variable a setting was read from.
+    /// ```
+    /// use git_bot_feedback::client::GiteaApiClient;
+    ///
+    /// // SAFETY: no other thread is reading environment variables
+    /// unsafe {
+    ///     std::env::set_var("FORGEJO_REPOSITORY", "owner/repo");
+    /// }
+    /// assert_eq!(GiteaApiClient::env_var_name("REPOSITORY"), "FORGEJO_REPOSITORY");
+    /// ```
+    pub fn env_var_name(setting: &str) -> String {
+        ENV_PREFIXES
+            .iter()
+            .map(|prefix| format!("{prefix}_{setting}"))
+            .find(|name| env::var_os(name).is_some())
+            .unwrap_or_else(|| format!("GITEA_{setting}"))
+    }
+
+    /// The REST API's root URL (ending with `/api/v1/`).
+    ///
+    /// See [`Self::new()`] about how this is resolved from the environment.
+    pub fn api_url(&self) -> &Url {
+        &self.api_url
+    }
+
+    /// Get the REST API's root URL from the environment.
+    ///
+    /// The first of the following is used:
+    ///
+    /// 1. The `API_URL` setting (eg. `GITEA_API_URL`).
+    /// 2. The `SERVER_URL` setting (eg. `GITEA_SERVER_URL`).
+    ///
+    /// Either may be given with or without the `/api/v1` path.
+    /// See [`Self::env_var_name()`] about the precedence of prefixes.
+    pub(super) fn resolve_api_url() -> Result<Url, ClientError> {
+        let mut checked = vec![];
+        for setting in ["API_URL", "SERVER_URL"] {
+            let name = Self::env_var_name(setting);
+            if let Ok(url) = Self::env_setting(setting) {
+                return normalize_api_url(&url)
+                    .map_err(|e| e.with_context(&format!("parse {name}")));
+            }
+            checked.extend(
+                ENV_PREFIXES
+                    .iter()
+                    .map(|prefix| format!("{prefix}_{setting}")),
+            );
+        }
+        Err(ClientError::MissingEnvVars { checked })
+    }
+
+    /// Read the value of the given `setting` from the environment.
+    ///
+    /// See [`Self::env_var_name()`].
+    pub(super) fn env_setting(setting: &str) -> Result<String, ClientError> {
+        let name = Self::env_var_name(setting);
+        let value = env::var(&name).map_err(|e| ClientError::env_var(&name, e))?;
+        log::debug!("Using {name} environment variable");
+        Ok(value)
+    }
+
+    /// The info about the Pull Request that triggered the workflow (if any).
+    pub(super) fn pull_request(&self) -> Option<&PullRequestInfo> {
+        self.event_payload.as_ref().map(|p| &p.pull_request.info)
+    }
+
+    /// Is the Pull Request that triggered the workflow a draft?
+    ///
+    /// Returns `false` if the workflow was not triggered by a Pull Request.
+    pub fn is_draft(&self) -> bool {
+        self.pull_request().is_some_and(|pr| pr.draft)
+    }
+
+    /// The SHA of the latest commit on the Pull Request's branch.
+    ///
+    /// Returns [`None`] if the workflow was not triggered by a Pull Request
+    /// or the event payload does not describe the branch.
+    pub fn head_sha(&self) -> Option<&str> {
+        self.event_payload
+            .as_ref()
+            .and_then(|p| p.pull_request.head.as_ref())
+            .and_then(|head| head.sha.as_deref())
+    }
+
+    /// The name of the Pull Request's branch.
+    ///
+    /// Returns [`None`] if the workflow was not triggered by a Pull Request
+    /// or the event payload does not describe the branch.
+    pub fn head_ref(&self) -> Option<&str> {
+        self.event_payload
+            .as_ref()
+            .and_then(|p| p.pull_request.head.as_ref())
+            .map(|head| head.name.as_str())
+    }
+
+    /// The name of the branch that the Pull Request would be merged into.
+    ///
+    /// Returns [`None`] if the workflow was not triggered by a Pull Request
+    /// or the event payload does not describe the branch.
+    pub fn base_ref(&self) -> Option<&str> {
+        self.event_payload
+            .as_ref()
+            .and_then(|p| p.pull_request.base.as_ref())
+            .map(|base| base.name.as_str())
+    }
+
+    /// Apply the [`ThreadCommentOptions::push_fallback`] for a thread comment on a push event.
+    pub(super) fn post_push_comment_fallback(
+        &self,
+        options: &ThreadCommentOptions,
+    ) -> Result<(), ClientError> {
+        if options.kind == CommentKind::Lgtm && options.no_lgtm {
+            return Ok(());
+        }
+        match options.push_fallback {
+            CommentFallback::StepSummary => {
+                log::info!(
+                    "Gitea does not support thread comments for push events; appending the comment to the step summary instead"
+                );
+                self.append_step_summary(&options.comment, SummaryOverflow::default())
+            }
+            CommentFallback::Skip => {
+                log_unsupported(self, "posting a thread comment for a push event");
+                Ok(())
+            }
+        }
+    }
+
+    /// Get the files changed by the Pull Request with the given `pr_number`
+    /// from the paginated `pulls/{index}/files` endpoint.
+    ///
+    /// Only the patches of files that satisfy the `file_filter` are parsed.
+    /// Returns [`None`] if the PR's `.diff` should be used instead because
+    ///
+    /// - the server lacks this endpoint (older versions of Gitea respond with `404`), or
+    /// - the server does not provide the patch of a qualified file.
+    #[cfg(feature = "file-changes")]
+    pub(super) async fn get_pr_changed_files(
+        &self,
+        pr_number: u64,
+        file_filter: &FileFilter,
+        lines_changed_only: &LinesChangedOnly,
+    ) -> Result<Option<HashMap<String, FileDiffLines>>, ClientError> {
+        let task = "get list of changed files";
+        let mut files: HashMap<String, FileDiffLines> = HashMap::new();
+        let mut url = repo_endpoint(
+            &self.api_url,
+            &self.repo,
+            &["pulls", pr_number.to_string().as_str(), "files"],
+        )?;
+        url.query_pairs_mut()
+            .append_pair("page", "1")
+            .append_pair("limit", self.changed_files_per_page.to_string().as_str());
+        let mut files_url = Some(url);
+        while let Some(url) = files_url.take() {
+            check_cancelled(self.cancellation_token.as_ref(), task)?;
+            let request = self.make_api_request(&self.client, url, Method::GET, None, None)?;
+            let response = self
+                .send_api_request(&self.client, request, &self.rate_limit_headers)
+                .await
+                .map_err(|e| e.with_context(task))?;
+            if response.status() == StatusCode::NOT_FOUND {
+                log::info!("Server cannot list a PR's changed files; using the PR's diff instead");
+                return Ok(None);
+            }
+            let response = ClientError::check_response(response, task).await?;
+            files_url = self.page_links(response.headers(), response.url()).next;
+            let page = serde_json::from_str::<Vec<ChangedFile>>(
+                &response
+                    .text()
+                    .await
+                    .map_err(|e| ClientError::Request(e).with_context(task))?,
+            )
+            .map_err(|e| ClientError::json("deserialize list of changed files", e))?;
+            for file in page {
+                if !file_filter.is_qualified(Path::new(&file.filename)) {
+                    continue;
+                }
+                let previous_filename = file.previous_filename.filter(|name| !name.is_empty());
+                let status = file.status.parse::<FileStatus>().unwrap_or_default();
+                let previous_path = previous_filename
+                    .clone()
+                    .filter(|_| matches!(status, FileStatus::Renamed | FileStatus::Copied));
+                if status == FileStatus::Removed {
+                    if file_filter.include_deleted {
+                        files
+                            .entry(file.filename)
+                            .or_insert(FileDiffLines::default().with_status(status, None));
+                    }
+                    continue;
+                }
+                match file.patch {
+                    Some(patch) => {
+                        let diff = format!(
+                            "diff --git a/{old} b/{new}\n--- a/{old}\n+++ b/{new}\n{patch}\n",
+                            old = previous_filename.unwrap_or(file.filename.clone()),
+                            new = file.filename,
+                        );
+                        for (name, info) in parse_diff(&diff, file_filter, lines_changed_only)? {
+                            files
+                                .entry(name)
+                                .or_insert(info.with_status(status, previous_path.clone()));
+                        }
+                    }
+                    None if file.changes == 0 => {
+                        // file may have been only renamed.
+                        // include it in case files-changed-only is enabled.
+                        files
+                            .entry(file.filename)
+                            .or_insert(FileDiffLines::default().with_status(status, previous_path));
+                    }
+                    None => {
+                        log::debug!(
+                            "Server did not provide the patch for {}; using the PR's diff instead",
+                            file.filename
+                        );
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+        Ok(Some(files))
+    }
+
+    /// Build the default HTTP client.
+    ///
+    /// Headers and request timeouts are applied by [`RestApiClient::make_api_request()`],
+    /// so they also apply to a client given to [`RestApiClient::set_http_client()`].
+    pub(super) fn make_client(timeouts: &Timeouts) -> Result<Client, ClientError> {
+        Ok(Client::builder()
+            .connect_timeout(timeouts.connect)
+            .build()?)
+    }
+
     pub(super) fn make_headers() -> Result<HeaderMap<HeaderValue>, ClientError> {
         let mut headers = HeaderMap::new();
         headers.insert("Accept", HeaderValue::from_str("application/json")?);
-        if let Ok(token) = env::var("GITEA_TOKEN") {
-            log::debug!("Using auth token from GITEA_TOKEN environment variable");
+        if let Ok(token) = Self::env_setting("TOKEN") {
             let mut val = HeaderValue::from_str(format!("token {token}").as_str())?;
             val.set_sensitive(true);
             headers.insert(AUTHORIZATION, val);
         } else {
             log::warn!(
-                "No GITEA_TOKEN environment variable found! Permission to post comments may be unsatisfied."
+                "No FORGEJO_TOKEN, GITEA_TOKEN, or GITHUB_TOKEN environment variable found! Permission to post comments may be unsatisfied."
             );
         }
         Ok(headers)
@@ -109,18 +473,11 @@ impl GiteaApiClient {
                 Some(serde_json::json!(&payload).to_string()),
                 None,
             )?;
-            match self
+            let response = self
                 .send_api_request(&self.client, request, &self.rate_limit_headers)
                 .await
-            {
-                Ok(response) => {
-                    self.log_response(response, "Failed to post thread comment")
-                        .await;
-                }
-                Err(e) => {
-                    return Err(e.add_request_context("post thread comment"));
-                }
-            }
+                .map_err(|e| e.with_context("post thread comment"))?;
+            ClientError::check_response(response, "post thread comment").await?;
         }
         Ok(())
     }
@@ -133,95 +490,58 @@ impl GiteaApiClient {
         delete: bool,
     ) -> Result<Option<Url>, ClientError> {
         let mut comment_url = None;
-        let mut comments_url = Some(Url::parse_with_params(url.as_str(), &[("page", "1")])?);
-        let base_comment_url = format!("{}repos/{}/issues/comments", self.api_url, self.repo);
-        while let Some(endpoint) = comments_url.take() {
-            let request = self.make_api_request(&self.client, endpoint, Method::GET, None, None)?;
-            let result = self
-                .send_api_request(&self.client, request, &self.rate_limit_headers)
-                .await;
-            match result {
-                Err(e) => {
-                    return Err(e.add_request_context("get list of existing thread comments"));
+        let base_comment_url =
+            repo_endpoint(&self.api_url, &self.repo, &["issues", "comments", ""])?;
+        let mut pages: Paginator<_, Vec<ThreadComment>> = Paginator::new(
+            self,
+            &self.client,
+            url.clone(),
+            &self.rate_limit_headers,
+            "list of existing thread comments",
+        );
+        while let Some(page) = pages.next_page().await {
+            let payload = match page {
+                Ok(payload) => payload,
+                // the paginator already logged the failed response
+                Err(ClientError::RequestContext { source, .. }) if source.is_status() => {
+                    return Ok(comment_url);
                 }
-                Ok(response) => {
-                    if !response.status().is_success() {
-                        self.log_response(
-                            response,
-                            "Failed to get list of existing thread comments",
-                        )
-                        .await;
-                        return Ok(comment_url);
+                Err(e) => return Err(e),
+            };
+            for comment in payload {
+                if comment.body.starts_with(comment_marker) {
+                    log::debug!(
+                        "Found bot comment id {} from user {} ({})",
+                        comment.id,
+                        comment.user.login,
+                        comment.user.id,
+                    );
+                    let this_comment_url = base_comment_url.join(&comment.id.to_string())?;
+                    if delete || comment_url.is_some() {
+                        // if not updating: remove all outdated comments
+                        // if updating: remove all outdated comments except the last one
+
+                        // use last saved comment_url (if not None) or current comment url
+                        let del_url = if let Some(last_url) = &comment_url {
+                            last_url
+                        } else {
+                            &this_comment_url
+                        };
+                        let req = self.make_api_request(
+                            &self.client,
+                            del_url.clone(),
+                            Method::DELETE,
+                            None,
+                            None,
+                        )?;
+                        let result = self
+                            .send_api_request(&self.client, req, &self.rate_limit_headers)
+                            .await
+                            .map_err(|e| e.with_context("delete old thread comment"))?;
+                        ClientError::check_response(result, "delete old thread comment").await?;
                     }
-                    comments_url = self.try_next_page(response.headers());
-                    let payload =
-                        serde_json::from_str::<Vec<ThreadComment>>(&response.text().await?);
-                    match payload {
-                        Err(e) => {
-                            return Err(ClientError::json(
-                                "deserialize list of existing thread comments",
-                                e,
-                            ));
-                        }
-                        Ok(payload) => {
-                            for comment in payload {
-                                if comment.body.starts_with(comment_marker) {
-                                    log::debug!(
-                                        "Found bot comment id {} from user {} ({})",
-                                        comment.id,
-                                        comment.user.login,
-                                        comment.user.id,
-                                    );
-                                    let this_comment_url = Url::parse(
-                                        format!("{base_comment_url}/{}", comment.id).as_str(),
-                                    )?;
-                                    if delete || comment_url.is_some() {
-                                        // if not updating: remove all outdated comments
-                                        // if updating: remove all outdated comments except the last one
-
-                                        // use last saved comment_url (if not None) or current comment url
-                                        let del_url = if let Some(last_url) = &comment_url {
-                                            last_url
-                                        } else {
-                                            &this_comment_url
-                                        };
-                                        let req = self.make_api_request(
-                                            &self.client,
-                                            del_url.clone(),
-                                            Method::DELETE,
-                                            None,
-                                            None,
-                                        )?;
-                                        match self
-                                            .send_api_request(
-                                                &self.client,
-                                                req,
-                                                &self.rate_limit_headers,
-                                            )
-                                            .await
-                                        {
-                                            Ok(result) => {
-                                                if !result.status().is_success() {
-                                                    self.log_response(
-                                                        result,
-                                                        "Failed to delete old thread comment",
-                                                    )
-                                                    .await;
-                                                }
-                                            }
-                                            Err(e) => {
-                                                return Err(e.add_request_context(
-                                                    "delete old thread comment",
-                                                ));
-                                            }
-                                        }
-                                    }
-                                    if !delete {
-                                        comment_url = Some(this_comment_url)
-                                    }
-                                }
-                            }
-                        }
+                    if !delete {
+                        comment_url = Some(this_comment_url)
                     }
                 }
             }
@@ -237,45 +557,32 @@ impl GiteaApiClient {
         marker: &str,
     ) -> Result<Vec<GiteaReviewComment>, ClientError> {
         let mut review_comments = vec![];
-        let mut comments_url = Some(Url::parse(
+        let url = Url::parse(
             format!(
                 "{}repos/{}/pulls/{pr_number}/reviews/{review_id}/comments",
                 self.api_url, self.repo
             )
             .as_str(),
-        )?);
-        while let Some(url) = comments_url.take() {
-            let request = self.make_api_request(&self.client, url, Method::GET, None, None)?;
-            let result = self
-                .send_api_request(&self.client, request, &self.rate_limit_headers)
-                .await;
-            match result {
-                Err(e) => {
-                    return Err(e.add_request_context("get comments for a review"));
-                }
-                Ok(response) => {
-                    if !response.status().is_success() {
-                        self.log_response(response, "Failed to get comments for a review")
-                            .await;
-                        continue;
-                    }
-                    comments_url = self.try_next_page(response.headers());
-                    let comments_payload =
-                        serde_json::from_str::<Vec<GiteaReviewComment>>(&response.text().await?);
-                    match comments_payload {
-                        Err(e) => {
-                            return Err(ClientError::json("deserialize comments for a review", e));
-                        }
-                        Ok(comments) => {
-                            for comment in comments {
-                                if comment.body.starts_with(marker) {
-                                    review_comments.push(comment);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+        )?;
+        let mut pages: Paginator<_, Vec<GiteaReviewComment>> = Paginator::new(
+            self,
+            &self.client,
+            url,
+            &self.rate_limit_headers,
+            "comments for a review",
+        );
+        while let Some(page) = pages.next_page().await {
+            let comments = match page {
+                Ok(comments) => comments,
+                // the paginator already logged the failed response
+                Err(ClientError::RequestContext { source, .. }) if source.is_status() => break,
+                Err(e) => return Err(e),
+            };
+            review_comments.extend(
+                comments
+                    .into_iter()
+                    .filter(|comment| comment.body.starts_with(marker)),
+            );
         }
         Ok(review_comments)
     }
@@ -287,54 +594,40 @@ impl GiteaApiClient {
         marker: &str,
     ) -> Result<Vec<ReviewInfo>, ClientError> {
         let mut reviews = Vec::new();
-        let mut reviews_url = Some(Url::parse_with_params(
+        let url = Url::parse(
             format!(
                 "{}repos/{}/pulls/{pr_number}/reviews",
                 self.api_url, self.repo,
             )
             .as_str(),
-            &[("page", "1")],
-        )?);
-
-        while let Some(endpoint) = reviews_url.take() {
-            let request = self.make_api_request(&self.client, endpoint, Method::GET, None, None)?;
-            let result = self
-                .send_api_request(&self.client, request, &self.rate_limit_headers)
-                .await;
-            match result {
-                Err(e) => {
-                    return Err(e.add_request_context("get existing PR reviews"));
-                }
-                Ok(response) => {
-                    if !response.status().is_success() {
-                        self.log_response(response, "Failed to get existing PR reviews")
-                            .await;
-                        break;
-                    }
-                    reviews_url = self.try_next_page(response.headers());
-                    let payload = serde_json::from_str::<Vec<ReviewInfo>>(&response.text().await?);
-                    match payload {
-                        Err(e) => {
-                            return Err(ClientError::json("deserialize existing PR reviews", e));
-                        }
-                        Ok(payload) => {
-                            for mut review in payload {
-                                if review.body.starts_with(marker) {
-                                    log::debug!(
-                                        "Found bot review id {} with {} comments",
-                                        review.id,
-                                        review.comments_count
-                                    );
-                                    if review.comments_count > 0 {
-                                        review.comments = self
-                                            .get_review_comments(review.id, pr_number, marker)
-                                            .await?;
-                                    }
-                                    reviews.push(review);
-                                }
-                            }
-                        }
+        )?;
+        let mut pages: Paginator<_, Vec<ReviewInfo>> = Paginator::new(
+            self,
+            &self.client,
+            url,
+            &self.rate_limit_headers,
+            "existing PR reviews",
+        );
+        while let Some(page) = pages.next_page().await {
+            let payload = match page {
+                Ok(payload) => payload,
+                // the paginator already logged the failed response
+                Err(ClientError::RequestContext { source, .. }) if source.is_status() => break,
+                Err(e) => return Err(e),
+            };
+            for mut review in payload {
+                if review.body.starts_with(marker) {
+                    log::debug!(
+                        "Found bot review id {} with {} comments",
+                        review.id,
+                        review.comments_count
+                    );
+                    if review.comments_count > 0 {
+                        review.comments = self
+                            .get_review_comments(review.id, pr_number, marker)
+                            .await?;
                     }
+                    reviews.push(review);
                 }
             }
         }
@@ -349,21 +642,19 @@ impl GiteaApiClient {
         proposed: &ReviewComment,
         marker: &str,
     ) -> bool {
-        let proposed_body = if !proposed.comment.starts_with(marker) {
-            format!("{marker}{}", proposed.comment)
-        } else {
-            proposed.comment.clone()
-        };
-
-        // Path must match
-        existing.path == proposed.path
-        // Check line position: proposed line_end maps to new_position
-        // If proposed has line_start, it should map to the line range
-        && existing.new_position == proposed.line_end as i64
-        // existing comment body must start with the marker
-        && existing.body.starts_with(marker)
-        // compare comment bodies
-        && existing.body == proposed_body
+        proposed.is_posted_as(
+            &PostedReviewComment {
+                path: &existing.path,
+                line_start: None,
+                // proposed line_end maps to new_position
+                line_end: existing.new_position,
+                // Gitea only positions a comment on a single line
+                single_line: true,
+                body: &existing.body,
+                resolved: existing.resolver.is_some(),
+            },
+            marker,
+        )
     }
 
     /// Delete outdated review comments and reviews by their IDs.
@@ -373,6 +664,7 @@ impl GiteaApiClient {
         comment_ids: Vec<i64>,
         review_ids: Vec<i64>,
         delete: bool,
+        dismissal_message: &str,
     ) -> Result<(), ClientError> {
         let base_url = format!("{}repos/{}/pulls/{pr_number}", self.api_url, self.repo);
         // resolve individual comments first
@@ -390,7 +682,7 @@ impl GiteaApiClient {
                         .await;
                 }
                 Err(e) => {
-                    return Err(e.add_request_context("resolve outdated review comment"));
+                    return Err(e.with_context("resolve outdated review comment"));
                 }
             }
         }
@@ -406,7 +698,7 @@ impl GiteaApiClient {
                 let url =
                     Url::parse(format!("{base_url}/reviews/{review_id}/dismissals").as_str())?;
                 let body = serde_json::json!({
-                    "message": "outdated review",
+                    "message": dismissal_message,
                     "priors": false // do not dismiss all prior reviews, only this one
                 });
                 let request = self.make_api_request(
@@ -423,11 +715,14 @@ impl GiteaApiClient {
                 .send_api_request(&self.client, request, &self.rate_limit_headers)
                 .await
             {
+                Ok(result) if delete => {
+                    ClientError::check_response(result, "delete outdated review").await?;
+                }
                 Ok(result) => {
                     self.log_response(result, log_prompt).await;
                 }
                 Err(e) => {
-                    return Err(e.add_request_context(log_prompt));
+                    return Err(e.with_context(log_prompt));
                 }
             }
         }
@@ -435,3 +730,72 @@ impl GiteaApiClient {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use reqwest::Url;
+
+    use super::{EventKind, normalize_api_url, repo_endpoint};
+
+    #[test]
+    fn normalize_trailing_slash() {
+        for url in [
+            "https://gitea.com",
+            "https://gitea.com/",
+            "https://gitea.com/api/v1",
+            "https://gitea.com/api/v1/",
+            "https://gitea.com/api/v1//",
+        ] {
+            let url = normalize_api_url(url).unwrap();
+            assert_eq!(url.as_str(), "https://gitea.com/api/v1/");
+            assert_eq!(
+                url.join("repos/o/r").unwrap().as_str(),
+                "https://gitea.com/api/v1/repos/o/r"
+            );
+        }
+    }
+
+    #[test]
+    fn normalize_sub_path() {
+        let url = normalize_api_url("https://example.com/gitea").unwrap();
+        assert_eq!(url.as_str(), "https://example.com/gitea/api/v1/");
+    }
+
+    #[test]
+    fn normalize_invalid() {
+        assert!(normalize_api_url("not a url").is_err());
+    }
+
+    #[test]
+    fn event_kinds() {
+        assert_eq!(
+            EventKind::from_event_name("pull_request_target"),
+            EventKind::PullRequest
+        );
+        assert_eq!(
+            EventKind::from_event_name("issue_comment"),
+            EventKind::IssueComment
+        );
+        assert_eq!(EventKind::from_event_name("push"), EventKind::Other);
+        assert_eq!(EventKind::Other.parse_payload("not json").unwrap(), None);
+        assert!(EventKind::PullRequest.parse_payload("{}").is_err());
+    }
+
+    #[test]
+    fn repo_endpoint_segments() {
+        for api_url in ["https://gitea.com/api/v1", "https://gitea.com/api/v1/"] {
+            let api_url = Url::parse(api_url).unwrap();
+            let url =
+                repo_endpoint(&api_url, "owner/my repo", &["issues", "comments", ""]).unwrap();
+            assert_eq!(
+                url.as_str(),
+                "https://gitea.com/api/v1/repos/owner/my%20repo/issues/comments/"
+            );
+            assert_eq!(
+                url.join("42").unwrap().as_str(),
+                "https://gitea.com/api/v1/repos/owner/my%20repo/issues/comments/42"
+            );
+        }
+    }
+}
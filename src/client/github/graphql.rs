@@ -6,7 +6,8 @@ use serde_json::json;
 
 use crate::{
     ReviewOptions,
-    client::{ClientError, RestApiClient},
+    client::{ClientError, Paginator, RestApiClient, check_cancelled},
+    comments::review_comments::PostedReviewComment,
 };
 
 use super::{
@@ -75,9 +76,6 @@ const HIDE_REVIEW_COMMENT: &str = r#"mutation($subjectId: ID!) {
   }
 }"#;
 
-/// A constant string used as a payload to dismiss PR reviews.
-const REVIEW_DISMISSAL: &str = r#"{"event":"DISMISS","message":"outdated review"}"#;
-
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct ThreadInfo {
     pub id: String,
@@ -220,6 +218,10 @@ impl GithubApiClient {
         let mut has_next_page = true;
         let graphql_url = self.api_url.join("/graphql")?;
         while has_next_page {
+            check_cancelled(
+                self.cancellation_token.as_ref(),
+                "get list of existing review thread comments",
+            )?;
             let variables = json!({
                 "owner": repo_owner.to_string(),
                 "name": repo_name.to_string(),
@@ -239,9 +241,7 @@ impl GithubApiClient {
                 .await
             {
                 Err(e) => {
-                    return Err(
-                        e.add_request_context("get list of existing review thread comments")
-                    );
+                    return Err(e.with_context("get list of existing review thread comments"));
                 }
                 Ok(response) => {
                     if !response.status().is_success() {
@@ -336,21 +336,18 @@ impl GithubApiClient {
                 let line_start = comment.start_line.or(comment.original_start_line);
                 let line_end = comment.line.unwrap_or(comment.original_line);
                 let mut keep = false; // should we `keep` the review comment?
+                let posted = PostedReviewComment {
+                    path: &comment.path,
+                    line_start,
+                    line_end,
+                    single_line: false,
+                    body: &comment.body,
+                    resolved: thread.info.is_resolved
+                        || thread.info.is_collapsed
+                        || comment.pull_request_review.is_minimized,
+                };
                 for suggestion in options.comments.iter() {
-                    let proposed_comment =
-                        if suggestion.comment.starts_with(options.marker.as_str()) {
-                            suggestion.comment.clone()
-                        } else {
-                            format!("{}{}", options.marker, suggestion.comment)
-                        };
-                    if suggestion.path == comment.path
-                        && suggestion.line_start.map(|i| i as i64) == line_start
-                        && suggestion.line_end as i64 == line_end
-                        && proposed_comment == comment.body
-                        && !thread.info.is_resolved
-                        && !thread.info.is_collapsed
-                        && !comment.pull_request_review.is_minimized
-                    {
+                    if suggestion.is_posted_as(&posted, &options.marker) {
                         log::info!(
                             "Using existing review comment: path='{}', line_start='{line_start:?}', line_end='{line_end}'",
                             comment.path,
@@ -409,101 +406,104 @@ impl GithubApiClient {
             .send_api_request(&self.client, request, &self.rate_limit_headers)
             .await
         {
+            Ok(response) if delete => {
+                ClientError::check_response(response, format!("{op} review {id}").as_str()).await?;
+                Ok(())
+            }
             Ok(response) => {
                 self.log_response(response, format!("Failed to {op} review {id}").as_str())
                     .await;
                 Ok(())
             }
-            Err(e) => Err(e.add_request_context(format!("{op} review {id}").as_str())),
+            Err(e) => Err(e.with_context(format!("{op} review {id}").as_str())),
         }
     }
 
     /// Hide and dismiss review that were previously created by this software.
     ///
     /// The `keep_reviews` parameter is a list of reviews' Node IDs to keep displayed.
-    /// This also will dismiss any review (as "outdated") if it is not being kept.
+    /// This also will dismiss any review (with the given `dismissal_message`)
+    /// if it is not being kept.
+    ///
+    /// Reviews that are already dismissed were hidden by a previous run, so they are left as is.
+    ///
+    /// Returns the number of outdated reviews that this call hid or dismissed.
     pub(super) async fn hide_outdated_reviews(
         &self,
         url: Url,
         keep_reviews: Vec<String>,
         marker: &str,
-    ) -> Result<(), ClientError> {
-        let mut next_page = Some(Url::parse_with_params(url.as_str(), [("page", "1")])?);
+        dismissal_message: &str,
+    ) -> Result<usize, ClientError> {
+        let mut hidden_reviews = 0;
+        let dismissal = json!({"event": "DISMISS", "message": dismissal_message}).to_string();
         let graphql_url = self.api_url.join("/graphql")?;
-        while let Some(endpoint) = next_page.take() {
-            let request = self.make_api_request(&self.client, endpoint, Method::GET, None, None)?;
-            let response = self
-                .send_api_request(&self.client, request, &self.rate_limit_headers)
-                .await;
-            match response {
-                Err(e) => {
-                    return Err(e.add_request_context("get list of existing reviews"));
+        let mut pages: Paginator<_, Vec<ReviewSummary>> = Paginator::new(
+            self,
+            &self.client,
+            url.clone(),
+            &self.rate_limit_headers,
+            "list of existing reviews",
+        );
+        while let Some(page) = pages.next_page().await {
+            let reviews = page?;
+            for review in reviews {
+                if keep_reviews.contains(&review.node_id)
+                    || review.state == ReviewState::Dismissed
+                    || review.body.as_ref().is_none_or(|b| !b.starts_with(marker))
+                {
+                    // if the review is being reused, was already dismissed, or is not authored
+                    // by this software, then leave it as is and skip to the next review.
+                    continue;
                 }
-                Ok(response) => {
-                    next_page = self.try_next_page(response.headers());
-                    let reviews =
-                        serde_json::from_str::<Vec<ReviewSummary>>(response.text().await?.as_str())
-                            .map_err(|e| {
-                                ClientError::json("deserialize list of PR review comments", e)
-                            })?;
-                    for review in reviews {
-                        if keep_reviews.contains(&review.node_id)
-                            || review.body.as_ref().is_none_or(|b| !b.starts_with(marker))
-                        {
-                            // if the review is being reused or is not authored by this software, then
-                            // leave it as is and skip to the next review.
-                            continue;
-                        }
-                        let req = self.make_api_request(
-                            &self.client,
-                            graphql_url.clone(),
-                            Method::POST,
-                            Some(json!({"query": HIDE_REVIEW_COMMENT, "variables": {"subjectId": review.node_id}}).to_string()),
-                            None
-                        )?;
-                        match self
-                            .send_api_request(&self.client, req, &self.rate_limit_headers)
-                            .await
-                        {
-                            Ok(result) => {
-                                self.log_response(result, "Failed to hide outdated review comment")
-                                    .await;
-                            }
-                            Err(e) => {
-                                return Err(e.add_request_context("hide outdated review comment"));
-                            }
-                        }
-                        if review.state != ReviewState::Dismissed {
-                            let dismissal_url =
-                                url.join(format!("reviews/{}/dismissals", review.id).as_str())?;
-                            let dismiss_request = self.make_api_request(
-                                &self.client,
-                                dismissal_url,
-                                Method::PUT,
-                                Some(REVIEW_DISMISSAL.to_string()),
-                                None,
-                            )?;
-                            match self
-                                .send_api_request(
-                                    &self.client,
-                                    dismiss_request,
-                                    &self.rate_limit_headers,
-                                )
-                                .await
-                            {
-                                Ok(result) => {
-                                    self.log_response(result, "Failed to dismiss outdated review")
-                                        .await;
-                                }
-                                Err(e) => {
-                                    return Err(e.add_request_context("dismiss outdated review"));
-                                }
-                            }
-                        }
+                let mut changed = false;
+                let req = self.make_api_request(
+                    &self.client,
+                    graphql_url.clone(),
+                    Method::POST,
+                    Some(json!({"query": HIDE_REVIEW_COMMENT, "variables": {"subjectId": review.node_id}}).to_string()),
+                    None
+                )?;
+                match self
+                    .send_api_request(&self.client, req, &self.rate_limit_headers)
+                    .await
+                {
+                    Ok(result) => {
+                        changed |= result.status().is_success();
+                        self.log_response(result, "Failed to hide outdated review comment")
+                            .await;
                     }
+                    Err(e) => {
+                        return Err(e.with_context("hide outdated review comment"));
+                    }
+                }
+                let dismissal_url =
+                    url.join(format!("reviews/{}/dismissals", review.id).as_str())?;
+                let dismiss_request = self.make_api_request(
+                    &self.client,
+                    dismissal_url,
+                    Method::PUT,
+                    Some(dismissal.clone()),
+                    None,
+                )?;
+                match self
+                    .send_api_request(&self.client, dismiss_request, &self.rate_limit_headers)
+                    .await
+                {
+                    Ok(result) => {
+                        changed |= result.status().is_success();
+                        self.log_response(result, "Failed to dismiss outdated review")
+                            .await;
+                    }
+                    Err(e) => {
+                        return Err(e.with_context("dismiss outdated review"));
+                    }
+                }
+                if changed {
+                    hidden_reviews += 1;
                 }
             }
         }
-        Ok(())
+        Ok(hidden_reviews)
     }
 }
@@ -3,21 +3,30 @@
 //! In the root module, we just implement the RestApiClient trait.
 //! In other (private) submodules we implement behavior specific to Github's REST API.
 
-use std::{
-    env,
-    fs::OpenOptions,
-    io::{self, Write},
-};
+use std::{env, path::Path, sync::Arc};
 
 use async_trait::async_trait;
-use reqwest::{Client, Method, Url};
+use reqwest::{
+    Client, Method, Request, Url,
+    header::{self, HeaderMap, HeaderValue},
+};
 
 use crate::{
-    FileAnnotation, OutputVariable, ReviewAction, ReviewOptions, ThreadCommentOptions,
+    AnnotationOverflow, FileAnnotation, OutputVariable, ReviewAction, ReviewOptions, ReviewOutcome,
+    ThreadCommentOptions,
     client::{
-        ClientError, RestApiClient, RestApiRateLimitHeaders,
-        common::{PullRequestInfo, PullRequestState},
+        CancellationToken, Capabilities, ClientError, CommandSink, Metrics, RateLimitSlot,
+        RateLimitSnapshot, RateLimitStatus, RequestRecorder, ResponseCache, RestApiClient,
+        RestApiRateLimitHeaders, RetryPolicy, StopCommandsGuard, Throttle, Timeouts,
+        assert_send_sync_clone, build_request,
+        common::{
+            PostedReview, PullRequestInfo, PullRequestState, append_to_path_file,
+            append_variables_file, escape_command_data, fmt_mask_commands, upsert_summary_file,
+            write_summary_env,
+        },
+        in_span, read_file_content, sealed, user_agent_header,
     },
+    summary::{SummaryOverflow, SummaryWriteMode},
 };
 mod graphql;
 mod serde_structs;
@@ -25,11 +34,14 @@ use serde_structs::{FullReview, ReviewDiffComment};
 mod specific_api;
 
 #[cfg(feature = "file-changes")]
-use crate::{FileDiffLines, FileFilter, LinesChangedOnly, parse_diff};
+use crate::{FileDiffLines, FileFilter, LinesChangedOnly};
+#[cfg(feature = "file-changes")]
+use reqwest::StatusCode;
 #[cfg(feature = "file-changes")]
-use std::{collections::HashMap, path::Path};
+use std::collections::HashMap;
 
 /// A structure to work with Github REST API.
+#[derive(Clone)]
 pub struct GithubApiClient {
     /// The HTTP request client to be used for all REST API calls.
     client: Client,
@@ -37,6 +49,12 @@ pub struct GithubApiClient {
     /// The CI run's event payload from the webhook that triggered the workflow.
     pull_request: Option<PullRequestInfo>,
 
+    /// The range of commits pushed (if triggered by a `push` event).
+    ///
+    /// This is [`None`] if the event payload is unavailable or the push created a branch.
+    #[cfg(feature = "file-changes")]
+    push_event: Option<serde_structs::PushEventPayload>,
+
     /// The name of the event that was triggered when running cpp_linter.
     pub event_name: String,
 
@@ -54,8 +72,66 @@ pub struct GithubApiClient {
 
     /// The response header names that describe the rate limit status.
     rate_limit_headers: RestApiRateLimitHeaders,
+
+    /// The bookkeeping from [`RestApiClient::cull_pr_reviews()`] that
+    /// is reported by [`RestApiClient::post_pr_review()`].
+    review_outcome: ReviewOutcome,
+
+    /// The destination of workflow commands (eg. file annotations).
+    command_sink: CommandSink,
+
+    /// Is a missing environment variable for a file-based writer an error?
+    ///
+    /// See [`RestApiClient::set_strict_mode()`].
+    strict: bool,
+
+    /// See [`RestApiClient::set_dry_run()`].
+    dry_run: bool,
+
+    /// See [`RestApiClient::set_retry_policy()`].
+    retry_policy: RetryPolicy,
+
+    /// See [`RestApiClient::last_rate_limit()`].
+    last_rate_limit: RateLimitSlot,
+
+    /// See [`RestApiClient::set_user_agent()`].
+    user_agent: HeaderValue,
+
+    /// The headers needed by the REST API (eg. `Accept` and `Authorization`).
+    headers: HeaderMap,
+
+    /// Was the [`Self::client`] given to [`RestApiClient::set_http_client()`]?
+    custom_http_client: bool,
+
+    /// See [`RestApiClient::set_response_cache()`].
+    response_cache: Option<Arc<ResponseCache>>,
+
+    /// See [`RestApiClient::set_request_recorder()`].
+    request_recorder: Option<Arc<RequestRecorder>>,
+
+    /// See [`RestApiClient::set_throttle()`].
+    throttle: Arc<Throttle>,
+
+    /// See [`RestApiClient::set_metrics()`].
+    metrics: Option<Arc<dyn Metrics>>,
+
+    /// See [`RestApiClient::set_cancellation_token()`].
+    cancellation_token: Option<CancellationToken>,
+
+    /// See [`RestApiClient::set_timeouts()`].
+    timeouts: Timeouts,
+
+    /// See [`RestApiClient::set_changed_files_per_page()`].
+    #[cfg(feature = "file-changes")]
+    changed_files_per_page: u32,
+
+    /// See [`RestApiClient::set_diff_fallback()`].
+    #[cfg(feature = "file-changes")]
+    diff_fallback: bool,
 }
 
+const _: () = assert_send_sync_clone::<GithubApiClient>();
+
 // implement the RestApiClient trait for the GithubApiClient
 #[async_trait]
 impl RestApiClient for GithubApiClient {
@@ -63,6 +139,17 @@ impl RestApiClient for GithubApiClient {
         "github".to_string()
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            thread_comments_on_push: true,
+            pr_reviews: true,
+            file_annotations: true,
+            step_summary: true,
+            output_variables: true,
+            commit_statuses: false,
+        }
+    }
+
     /// This prints a line to indicate the beginning of a related group of [`log`] statements.
     ///
     /// For apps' [`log`] implementations, this function's [`log::info`] output needs to have
@@ -91,7 +178,11 @@ impl RestApiClient for GithubApiClient {
     /// }
     /// ```
     fn start_log_group(&self, name: &str) {
-        log::info!(target: "CI_LOG_GROUPING", "::group::{name}");
+        log::info!(
+            target: "CI_LOG_GROUPING",
+            "::group::{}",
+            escape_command_data(name)
+        );
     }
 
     /// This prints a line to indicate the ending of a related group of [`log`] statements.
@@ -111,13 +202,109 @@ impl RestApiClient for GithubApiClient {
     }
 
     fn set_user_agent(&mut self, user_agent: &str) -> Result<(), ClientError> {
-        self.client = Client::builder()
-            .default_headers(Self::make_headers()?)
-            .user_agent(user_agent)
-            .build()?;
+        self.user_agent = user_agent_header(user_agent)?;
+        Ok(())
+    }
+
+    fn set_http_client(&mut self, client: Client) {
+        self.client = client;
+        self.custom_http_client = true;
+    }
+
+    fn throttle(&self) -> Option<&Throttle> {
+        Some(&*self.throttle)
+    }
+
+    fn set_throttle(&mut self, throttle: Throttle) {
+        self.throttle = Arc::new(throttle);
+    }
+
+    fn cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
+    fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.cancellation_token = token;
+    }
+
+    #[cfg(feature = "file-changes")]
+    fn changed_files_per_page(&self) -> Option<u32> {
+        Some(self.changed_files_per_page)
+    }
+
+    #[cfg(feature = "file-changes")]
+    fn set_changed_files_per_page(&mut self, per_page: u32) {
+        self.changed_files_per_page = per_page;
+    }
+
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    fn diff_fallback(&self) -> Option<bool> {
+        Some(self.diff_fallback)
+    }
+
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    fn set_diff_fallback(&mut self, enabled: bool) {
+        self.diff_fallback = enabled;
+    }
+
+    fn metrics(&self) -> Option<&dyn Metrics> {
+        self.metrics.as_deref()
+    }
+
+    fn set_metrics(&mut self, metrics: Option<Arc<dyn Metrics>>) {
+        self.metrics = metrics;
+    }
+
+    fn response_cache(&self) -> Option<&ResponseCache> {
+        self.response_cache.as_deref()
+    }
+
+    fn set_response_cache(&mut self, cache: Option<ResponseCache>) {
+        self.response_cache = cache.map(Arc::new);
+    }
+
+    fn request_recorder(&self) -> Option<&RequestRecorder> {
+        self.request_recorder.as_deref()
+    }
+
+    fn set_request_recorder(&mut self, recorder: Option<RequestRecorder>) {
+        self.request_recorder = recorder.map(Arc::new);
+    }
+
+    fn timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+
+    fn set_timeouts(&mut self, timeouts: Timeouts) -> Result<(), ClientError> {
+        if !self.custom_http_client {
+            self.client = Self::make_client(&timeouts)?;
+        }
+        self.timeouts = timeouts;
         Ok(())
     }
 
+    /// The headers needed by the REST API and the [`Timeouts::request`] are applied to
+    /// every request. Any given `headers` take precedence.
+    fn make_api_request(
+        &self,
+        client: &Client,
+        url: Url,
+        method: Method,
+        data: Option<String>,
+        headers: Option<HeaderMap>,
+    ) -> Result<Request, ClientError> {
+        let mut merged = self.headers.clone();
+        merged.insert(header::USER_AGENT, self.user_agent.clone());
+        if let Some(headers) = headers {
+            merged.extend(headers);
+        }
+        let mut request = build_request(client, url, method, data, Some(merged))?;
+        request.timeout_mut().get_or_insert(self.timeouts.request);
+        Ok(request)
+    }
+
     async fn post_thread_comment(&self, options: ThreadCommentOptions) -> Result<(), ClientError> {
         env::var("GITHUB_TOKEN").map_err(|e| ClientError::env_var("GITHUB_TOKEN", e))?;
         let comments_url = match &self.pull_request {
@@ -133,7 +320,17 @@ impl RestApiClient for GithubApiClient {
                 .api_url
                 .join(format!("repos/{}/commits/{}/comments", self.repo, self.sha).as_str())?,
         };
-        self.update_comment(comments_url, options).await
+        let operation = self.timeouts.with_deadline(
+            "post thread comment",
+            self.update_comment(comments_url, options),
+        );
+        in_span!(
+            operation,
+            "post_thread_comment",
+            repo = self.repo.as_str(),
+            pr = self.pull_request.as_ref().map(|pr| pr.number),
+        )
+        .await
     }
 
     #[inline]
@@ -141,54 +338,142 @@ impl RestApiClient for GithubApiClient {
         self.pull_request.is_some()
     }
 
-    fn append_step_summary(&self, comment: &str) -> Result<(), ClientError> {
+    fn append_step_summary(
+        &self,
+        comment: &str,
+        overflow: SummaryOverflow,
+    ) -> Result<(), ClientError> {
+        write_summary_env(
+            "GITHUB_STEP_SUMMARY",
+            true,
+            comment,
+            SummaryWriteMode::Append,
+            self.step_summary_limit(),
+            overflow,
+        )
+    }
+
+    fn write_step_summary(&self, comment: &str, mode: SummaryWriteMode) -> Result<(), ClientError> {
+        write_summary_env(
+            "GITHUB_STEP_SUMMARY",
+            true,
+            comment,
+            mode,
+            self.step_summary_limit(),
+            SummaryOverflow::default(),
+        )
+    }
+
+    fn upsert_step_summary(&self, marker: &str, content: &str) -> Result<(), ClientError> {
         let gh_out = env::var("GITHUB_STEP_SUMMARY")
             .map_err(|e| ClientError::env_var("GITHUB_STEP_SUMMARY", e))?;
-        // step summary MD file can be overwritten/removed in CI runners
-        match OpenOptions::new().append(true).open(gh_out) {
-            Ok(mut gh_out_file) => writeln!(&mut gh_out_file, "\n{comment}\n")
-                .map_err(|e| ClientError::io("write to GITHUB_STEP_SUMMARY file", e)),
-            Err(e) => Err(ClientError::io("open GITHUB_STEP_SUMMARY file", e)),
-        }
+        upsert_summary_file(
+            "GITHUB_STEP_SUMMARY",
+            &gh_out,
+            marker,
+            content,
+            self.step_summary_limit(),
+            SummaryOverflow::default(),
+        )
     }
 
     fn write_output_variables(&self, vars: &[OutputVariable]) -> Result<(), ClientError> {
-        if vars.is_empty() {
-            // Should probably be an error. This check is only here to prevent needlessly
-            // fetching the env var GITHUB_OUTPUT value and opening the referenced file.
-            return Ok(());
-        }
-        let gh_out =
-            env::var("GITHUB_OUTPUT").map_err(|e| ClientError::env_var("GITHUB_OUTPUT", e))?;
-        match OpenOptions::new().append(true).open(gh_out) {
-            Ok(mut gh_out_file) => {
-                for out_var in vars {
-                    out_var.validate()?;
-                    writeln!(&mut gh_out_file, "{out_var}")
-                        .map_err(|e| ClientError::io("write to GITHUB_OUTPUT file", e))?;
-                }
-                Ok(())
-            }
-            Err(e) => Err(ClientError::io("open GITHUB_OUTPUT file", e)),
-        }
+        append_variables_file("GITHUB_OUTPUT", vars, true)
+    }
+
+    fn export_environment_variables(&self, vars: &[OutputVariable]) -> Result<(), ClientError> {
+        append_variables_file("GITHUB_ENV", vars, true)
+    }
+
+    fn write_state_variables(&self, vars: &[OutputVariable]) -> Result<(), ClientError> {
+        append_variables_file("GITHUB_STATE", vars, true)
+    }
+
+    fn read_state_variable(&self, name: &str) -> Option<String> {
+        env::var(format!("STATE_{}", name.trim())).ok()
+    }
+
+    fn add_to_path(&self, dir: &Path) -> Result<(), ClientError> {
+        append_to_path_file("GITHUB_PATH", dir, self.strict)
+    }
+
+    /// GitHub always sets `GITHUB_OUTPUT`, `GITHUB_STEP_SUMMARY`, `GITHUB_ENV`, and `GITHUB_STATE`,
+    /// so writing to them is always strict. This only affects [`RestApiClient::add_to_path()`].
+    fn set_strict_mode(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    fn dry_run(&self) -> bool {
+        self.dry_run
     }
 
-    fn write_file_annotations(&self, annotations: &[FileAnnotation]) -> Result<(), ClientError> {
+    fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    async fn rate_limit_status(&self) -> Result<Option<RateLimitStatus>, ClientError> {
+        let url = self.api_url.join("rate_limit")?;
+        let request = self.make_api_request(&self.client, url, Method::GET, None, None)?;
+        // this is an explicit query, so it is not retried like other requests
+        let response = self
+            .client
+            .execute(request)
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ClientError::Request(e).with_context("get rate limit status"))?;
+        let body = response.text().await?;
+        let status: serde_structs::RateLimitResponse = serde_json::from_str(&body)
+            .map_err(|e| ClientError::json("deserialize rate limit status", e))?;
+        Ok(Some(RateLimitStatus {
+            core: status.resources.core.into(),
+            graphql: status.resources.graphql.map(Into::into),
+        }))
+    }
+
+    fn last_rate_limit(&self) -> Option<RateLimitSnapshot> {
+        self.last_rate_limit.get()
+    }
+
+    fn record_rate_limit(&self, snapshot: RateLimitSnapshot, _token: sealed::Token) {
+        self.last_rate_limit.set(snapshot);
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = policy;
+    }
+
+    fn set_command_sink(&mut self, sink: CommandSink) {
+        self.command_sink = sink;
+    }
+
+    fn mask_secret(&self, secret: &str) -> Result<(), ClientError> {
+        self.command_sink.write_lines(&fmt_mask_commands(secret))
+    }
+
+    fn log_untrusted(&self, content: &str) -> Result<(), ClientError> {
+        let guard = StopCommandsGuard::new(self.command_sink.clone())?;
+        guard.write_lines(&content.lines().map(str::to_string).collect::<Vec<_>>())
+    }
+
+    /// GitHub only displays 10 annotations of each severity (error, warning, notice) per CI step.
+    /// See [`AnnotationOverflow`] about handling annotations beyond that limit.
+    fn write_file_annotations(
+        &self,
+        annotations: &[FileAnnotation],
+        overflow: AnnotationOverflow,
+    ) -> Result<usize, ClientError> {
         if annotations.is_empty() {
             // Should probably be an error.
             // This check is only here to prevent needlessly locking stdout.
-            return Ok(());
+            return Ok(0);
         }
-        let stdout = io::stdout();
-        let mut handle = stdout.lock();
-        for annotation in annotations {
-            writeln!(&mut handle, "{}", annotation.fmt_github())
-                .map_err(|e| ClientError::io("write to file annotation to stdout", e))?;
-        }
-        handle
-            .flush()
-            .map_err(|e| ClientError::io("flush stdout with file annotations", e))?;
-        Ok(())
+        let commands = Self::render_annotations(annotations, overflow)?;
+        self.command_sink.write_lines(&commands)?;
+        Ok(commands.len())
     }
 
     #[cfg(feature = "file-changes")]
@@ -200,71 +485,115 @@ impl RestApiClient for GithubApiClient {
         _base_diff: Option<String>,
         _ignore_index: bool,
     ) -> Result<HashMap<String, FileDiffLines>, ClientError> {
-        let (url, is_pr) = match &self.pull_request {
-            Some(pr_event) => (
-                self.api_url.join(
-                    format!("repos/{}/pulls/{}/files", self.repo, pr_event.number).as_str(),
-                )?,
-                true,
-            ),
-            None => (
-                self.api_url
-                    .join(format!("repos/{}/commits/{}", self.repo, self.sha).as_str())?,
-                false,
-            ),
-        };
-        let mut url = Some(Url::parse_with_params(url.as_str(), &[("page", "1")])?);
-        let mut files: HashMap<String, FileDiffLines> = HashMap::new();
-        while let Some(endpoint) = url.take() {
-            let request = self.make_api_request(&self.client, endpoint, Method::GET, None, None)?;
+        let operation = self
+            .timeouts
+            .with_deadline("get list of changed files", async {
+                match (&self.pull_request, &self.push_event) {
+                    (Some(pr_event), _) => {
+                        let pr_url = self.api_url.join(
+                            format!("repos/{}/pulls/{}", self.repo, pr_event.number).as_str(),
+                        )?;
+                        let files_url = self.api_url.join(
+                            format!("repos/{}/pulls/{}/files", self.repo, pr_event.number).as_str(),
+                        )?;
+                        self.list_changed_files(
+                            files_url,
+                            pr_url,
+                            specific_api::MAX_LISTED_PR_FILES,
+                            file_filter,
+                            lines_changed_only,
+                        )
+                        .await
+                    }
+                    (None, Some(push_event)) => {
+                        let compare_url = self.api_url.join(
+                            format!(
+                                "repos/{}/compare/{}...{}",
+                                self.repo, push_event.before, push_event.after
+                            )
+                            .as_str(),
+                        )?;
+                        match self
+                            .list_changed_files(
+                                compare_url.clone(),
+                                compare_url,
+                                specific_api::MAX_LISTED_COMMIT_FILES,
+                                file_filter,
+                                lines_changed_only,
+                            )
+                            .await
+                        {
+                            // the `before` commit may be unknown after a force-push
+                            Err(ClientError::RequestContext { source, .. })
+                                if matches!(
+                                    source.status(),
+                                    Some(StatusCode::NOT_FOUND | StatusCode::UNPROCESSABLE_ENTITY)
+                                ) =>
+                            {
+                                log::warn!(
+                                    "Failed to compare {}...{}; only listing the changes in {}",
+                                    push_event.before,
+                                    push_event.after,
+                                    self.sha
+                                );
+                                self.list_commit_changed_files(file_filter, lines_changed_only)
+                                    .await
+                            }
+                            result => result,
+                        }
+                    }
+                    (None, None) => {
+                        self.list_commit_changed_files(file_filter, lines_changed_only)
+                            .await
+                    }
+                }
+            });
+        in_span!(
+            operation,
+            "get_list_of_changed_files",
+            repo = self.repo.as_str(),
+            pr = self.pull_request.as_ref().map(|pr| pr.number),
+        )
+        .await
+    }
+
+    /// The file is requested from the `repos/{repo}/contents/{path}` endpoint (as raw content).
+    async fn get_file_content(
+        &self,
+        path: &str,
+        reference: Option<&str>,
+    ) -> Result<String, ClientError> {
+        let task = format!("get content of {path}");
+        let reference = reference.unwrap_or(self.sha.as_str());
+        let mut url = self
+            .api_url
+            .join(format!("repos/{}/contents", self.repo).as_str())?;
+        url.path_segments_mut()
+            .map_err(|_| ClientError::UrlParse(url::ParseError::RelativeUrlWithCannotBeABaseBase))?
+            .extend(path.trim_start_matches('/').split('/'));
+        url.query_pairs_mut().append_pair("ref", reference);
+        let operation = self.timeouts.with_deadline(&task, async {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                "Accept",
+                HeaderValue::from_static("application/vnd.github.raw+json"),
+            );
+            let request =
+                self.make_api_request(&self.client, url, Method::GET, None, Some(headers))?;
             let response = self
                 .send_api_request(&self.client, request, &self.rate_limit_headers)
                 .await
-                .map_err(|e| e.add_request_context("get list of changed files"))?;
-            url = self.try_next_page(response.headers());
-            if let Err(e) = response.error_for_status_ref() {
-                if let Ok(body) = response.text().await {
-                    log::error!("Failed to get list of changed files: {e:?}\n{body}");
-                }
-                return Err(
-                    ClientError::Request(e).add_request_context("get list of changed files")
-                );
-            }
-            let body = response.text().await?;
-            let files_list = if !is_pr {
-                let json_value: serde_structs::PushEventFiles = serde_json::from_str(&body)
-                    .map_err(|e| ClientError::json("deserialize list of changed files", e))?;
-                json_value.files
-            } else {
-                serde_json::from_str::<Vec<serde_structs::GithubChangedFile>>(&body)
-                    .map_err(|e| ClientError::json("deserialize list of changed files", e))?
-            };
-            for file in files_list {
-                let ext = Path::new(&file.filename).extension().unwrap_or_default();
-                if !file_filter
-                    .extensions
-                    .contains(&ext.to_string_lossy().to_string())
-                {
-                    continue;
-                }
-                if let Some(patch) = file.patch {
-                    let diff = format!(
-                        "diff --git a/{old} b/{new}\n--- a/{old}\n+++ b/{new}\n{patch}\n",
-                        old = file.previous_filename.unwrap_or(file.filename.clone()),
-                        new = file.filename,
-                    );
-                    for (name, info) in parse_diff(&diff, file_filter, lines_changed_only)? {
-                        files.entry(name).or_insert(info);
-                    }
-                } else if file.changes == 0 {
-                    // file may have been only renamed.
-                    // include it in case files-changed-only is enabled.
-                    files.entry(file.filename).or_default();
-                }
-                // else changes are too big (per git server limits) or we don't care
-            }
-        }
-        Ok(files)
+                .map_err(|e| e.with_context(&task))?;
+            let response = ClientError::check_response(response, &task).await?;
+            read_file_content(response, path, &task).await
+        });
+        in_span!(
+            operation,
+            "get_file_content",
+            repo = self.repo.as_str(),
+            reference = reference,
+        )
+        .await
     }
 
     async fn cull_pr_reviews(&mut self, options: &mut ReviewOptions) -> Result<(), ClientError> {
@@ -278,71 +607,101 @@ impl RestApiClient for GithubApiClient {
 
             // Check existing comments to see if we can reuse any of them.
             // This also removes duplicate comments (if any) from the `options.comments`.
+            let proposed_comments = options.comments.len();
             let keep_reviews = self.check_reused_comments(options).await?;
+            self.review_outcome.reused_comments = proposed_comments - options.comments.len();
             // Next hide/resolve any previous reviews that are completely outdated.
             let url = self
                 .api_url
                 .join(format!("repos/{}/pulls/{}/reviews", self.repo, pr_info.number).as_str())?;
-            self.hide_outdated_reviews(url, keep_reviews, &options.marker)
+            self.review_outcome.dismissed_reviews = self
+                .hide_outdated_reviews(
+                    url,
+                    keep_reviews,
+                    &options.marker,
+                    &options.dismissal_text(),
+                )
                 .await?;
         }
         Ok(())
     }
 
-    async fn post_pr_review(&mut self, options: &ReviewOptions) -> Result<(), ClientError> {
-        if let Some(pr_info) = self.pull_request.as_ref() {
-            if (!options.allow_draft && pr_info.draft)
-                || (!options.allow_closed && pr_info.state == PullRequestState::Closed)
-                || pr_info.locked
-            {
-                return Ok(());
-            }
-            env::var("GITHUB_TOKEN").map_err(|e| ClientError::env_var("GITHUB_TOKEN", e))?;
-            let url = self
-                .api_url
-                .join(format!("repos/{}/pulls/{}/reviews", self.repo, pr_info.number).as_str())?;
-            let payload = FullReview {
-                event: match options.action {
-                    ReviewAction::Comment => String::from("COMMENT"),
-                    ReviewAction::Approve => String::from("APPROVE"),
-                    ReviewAction::RequestChanges => String::from("REQUEST_CHANGES"),
-                },
-                body: format!("{}{}", options.marker, options.summary),
-                comments: options
-                    .comments
-                    .iter()
-                    .map(ReviewDiffComment::from)
-                    .map(|mut r| {
-                        if !r.body.starts_with(&options.marker) {
-                            r.body = format!("{}{}", options.marker, r.body);
-                        }
-                        r
-                    })
-                    .collect(),
-            };
-            let request = self.make_api_request(
-                &self.client,
-                url,
-                Method::POST,
-                Some(
-                    serde_json::to_string(&payload)
-                        .map_err(|e| ClientError::json("serialize PR review payload", e))?,
-                ),
-                None,
-            )?;
-            let response = self
-                .send_api_request(&self.client, request, &self.rate_limit_headers)
-                .await;
-            match response {
-                Ok(response) => {
-                    self.log_response(response, "Failed to post PR review")
+    async fn post_pr_review(
+        &mut self,
+        options: &ReviewOptions,
+    ) -> Result<ReviewOutcome, ClientError> {
+        let mut outcome = std::mem::take(&mut self.review_outcome);
+        in_span!(
+            async move {
+                if let Some(pr_info) = self.pull_request.as_ref() {
+                    if let Some(reason) = pr_info.review_skip_reason(options) {
+                        log::info!("Skipped posting a review because the PR is {reason}");
+                        outcome.skipped = Some(reason);
+                        return Ok(outcome);
+                    }
+                    env::var("GITHUB_TOKEN")
+                        .map_err(|e| ClientError::env_var("GITHUB_TOKEN", e))?;
+                    let url = self.api_url.join(
+                        format!("repos/{}/pulls/{}/reviews", self.repo, pr_info.number).as_str(),
+                    )?;
+                    let payload = FullReview {
+                        event: match options.action {
+                            ReviewAction::Comment => String::from("COMMENT"),
+                            ReviewAction::Approve => String::from("APPROVE"),
+                            ReviewAction::RequestChanges => String::from("REQUEST_CHANGES"),
+                        },
+                        body: format!("{}{}", options.marker, options.summary),
+                        comments: options
+                            .comments
+                            .iter()
+                            .map(|comment| ReviewDiffComment {
+                                body: comment.mark_comment(&options.marker),
+                                ..ReviewDiffComment::from(comment)
+                            })
+                            .collect(),
+                    };
+                    let request = self.make_api_request(
+                        &self.client,
+                        url,
+                        Method::POST,
+                        Some(
+                            serde_json::to_string(&payload)
+                                .map_err(|e| ClientError::json("serialize PR review payload", e))?,
+                        ),
+                        None,
+                    )?;
+                    let response = self
+                        .send_api_request(&self.client, request, &self.rate_limit_headers)
                         .await;
+                    match response {
+                        Ok(response) => {
+                            let response =
+                                ClientError::check_response(response, "post PR review").await?;
+                            // a dry run does not actually create a review
+                            if !self.dry_run {
+                                let review = serde_json::from_str::<PostedReview>(
+                                    response.text().await?.as_str(),
+                                )
+                                .map_err(|e| {
+                                    ClientError::json("deserialize posted PR review", e)
+                                })?;
+                                outcome.review_id = Some(review.id);
+                                outcome.html_url =
+                                    review.html_url.and_then(|u| Url::parse(&u).ok());
+                            }
+                            outcome.posted_comments = payload.comments.len();
+                        }
+                        Err(e) => {
+                            return Err(e.with_context("post PR review"));
+                        }
+                    }
                 }
-                Err(e) => {
-                    return Err(e.add_request_context("post PR review"));
-                }
-            }
-        }
-        Ok(())
+                Ok(outcome)
+            },
+            "post_pr_review",
+            repo = self.repo.as_str(),
+            pr = self.pull_request.as_ref().map(|pr| pr.number),
+        )
+        .await
     }
 }
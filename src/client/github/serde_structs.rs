@@ -3,6 +3,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::client::timestamp;
+
 #[derive(Debug, Serialize)]
 pub struct FullReview {
     pub event: String,
@@ -85,6 +87,9 @@ pub struct GithubChangedFile {
     pub filename: String,
     /// If renamed, this will be the file's old name as a [`Some`], otherwise [`None`].
     pub previous_filename: Option<String>,
+    /// How the file was changed (eg. `added`, `removed`, `modified`, `renamed`).
+    #[serde(default)]
+    pub status: String,
     /// The individual patch that describes the file's changes.
     pub patch: Option<String>,
     /// The number of changes to the file contents.
@@ -98,3 +103,64 @@ pub struct PushEventFiles {
     /// The list of changed files.
     pub files: Vec<GithubChangedFile>,
 }
+
+/// A structure for deserializing the range of commits in a Push event's payload.
+#[cfg(feature = "file-changes")]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct PushEventPayload {
+    /// The SHA of the most recent commit on the ref before the push.
+    pub before: String,
+    /// The SHA of the most recent commit on the ref after the push.
+    pub after: String,
+}
+
+/// A structure for deserializing a page of changed files
+/// (as listed for a Push event or a Pull Request event).
+#[cfg(feature = "file-changes")]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum ChangedFilesPage {
+    /// A Push event's commit lists its files in an object.
+    Push(PushEventFiles),
+    /// A Pull Request's files are listed directly.
+    PullRequest(Vec<GithubChangedFile>),
+}
+
+#[cfg(feature = "file-changes")]
+impl ChangedFilesPage {
+    /// Get the list of changed files in this page.
+    pub fn into_files(self) -> Vec<GithubChangedFile> {
+        match self {
+            Self::Push(push) => push.files,
+            Self::PullRequest(files) => files,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RateLimitResponse {
+    pub resources: RateLimitResources,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RateLimitResources {
+    pub core: RateLimitResource,
+    pub graphql: Option<RateLimitResource>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RateLimitResource {
+    pub limit: u64,
+    pub remaining: u64,
+    pub reset: i64,
+}
+
+impl From<RateLimitResource> for crate::client::RateLimitResource {
+    fn from(value: RateLimitResource) -> Self {
+        Self {
+            limit: value.limit,
+            remaining: value.remaining,
+            reset: timestamp::from_unix(value.reset).unwrap_or(timestamp::UNIX_EPOCH),
+        }
+    }
+}
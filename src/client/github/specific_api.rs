@@ -2,15 +2,39 @@
 
 use super::{GithubApiClient, serde_structs::ThreadComment};
 use crate::{
-    AnnotationLevel, CommentKind, CommentPolicy, FileAnnotation, RestApiClient,
-    RestApiRateLimitHeaders, ThreadCommentOptions,
-    client::{ClientError, USER_AGENT, common::PullRequestEventPayload},
+    AnnotationLevel, AnnotationOverflow, CommentKind, CommentPolicy, FileAnnotation, RestApiClient,
+    RestApiRateLimitHeaders, ReviewOutcome, ThreadCommentOptions,
+    client::{
+        ClientError, CommandSink, Paginator, RateLimitSlot, RetryPolicy, Throttle, Timeouts,
+        USER_AGENT, common::PullRequestEventPayload,
+    },
 };
 use reqwest::{
     Client, Method, Url,
     header::{AUTHORIZATION, HeaderMap, HeaderValue},
 };
-use std::{collections::HashMap, env, fs};
+use std::{collections::HashMap, env, fs, sync::Arc};
+
+#[cfg(feature = "file-changes")]
+use super::serde_structs::{ChangedFilesPage, PushEventPayload};
+#[cfg(feature = "file-changes")]
+use crate::{
+    FileDiffLines, FileFilter, FileStatus, LinesChangedOnly,
+    client::DEFAULT_CHANGED_FILES_PER_PAGE, parse_diff,
+};
+#[cfg(feature = "file-changes")]
+use std::path::Path;
+
+/// The maximum number of changed files that GitHub lists for a commit (or a comparison of commits).
+#[cfg(feature = "file-changes")]
+pub(super) const MAX_LISTED_COMMIT_FILES: usize = 300;
+
+/// The maximum number of changed files that GitHub lists for a Pull Request.
+#[cfg(feature = "file-changes")]
+pub(super) const MAX_LISTED_PR_FILES: usize = 3000;
+
+/// The maximum number of annotations (per severity) that GitHub displays for a CI step.
+pub(super) const MAX_ANNOTATIONS: usize = 10;
 
 impl GithubApiClient {
     /// Instantiate a [`GithubApiClient`] object.
@@ -38,16 +62,27 @@ impl GithubApiClient {
                 _ => None,
             }
         };
+        #[cfg(feature = "file-changes")]
+        let push_event = match event_name.as_str() {
+            "push" => Self::read_push_event(),
+            _ => None,
+        };
         // GITHUB_*** env vars cannot be overwritten in CI runners on GitHub.
         let gh_api_url = env::var("GITHUB_API_URL").unwrap_or("https://api.github.com".to_string());
         let api_url = Url::parse(gh_api_url.as_str())?;
 
         Ok(Self {
-            client: Client::builder()
-                .default_headers(Self::make_headers()?)
-                .user_agent(USER_AGENT)
-                .build()?,
+            client: Self::make_client(&Timeouts::default())?,
+            custom_http_client: false,
+            response_cache: None,
+            request_recorder: None,
+            throttle: Arc::new(Throttle::default()),
+            metrics: None,
+            cancellation_token: None,
+            headers: Self::make_headers()?,
             pull_request,
+            #[cfg(feature = "file-changes")]
+            push_event,
             event_name,
             api_url,
             repo: env::var("GITHUB_REPOSITORY")
@@ -59,9 +94,54 @@ impl GithubApiClient {
                 remaining: "x-ratelimit-remaining".to_string(),
                 retry: "retry-after".to_string(),
             },
+            review_outcome: ReviewOutcome::default(),
+            command_sink: CommandSink::default(),
+            strict: false,
+            dry_run: false,
+            retry_policy: RetryPolicy::default(),
+            last_rate_limit: RateLimitSlot::default(),
+            user_agent: HeaderValue::from_static(USER_AGENT),
+            timeouts: Timeouts::default(),
+            #[cfg(feature = "file-changes")]
+            changed_files_per_page: DEFAULT_CHANGED_FILES_PER_PAGE,
+            #[cfg(feature = "file-changes")]
+            diff_fallback: true,
         })
     }
 
+    /// Read the range of pushed commits from the `push` event's payload.
+    ///
+    /// Returns [`None`] if the payload is unavailable (or malformed),
+    /// or if the push created a branch (the `before` commit is all zeros).
+    #[cfg(feature = "file-changes")]
+    fn read_push_event() -> Option<PushEventPayload> {
+        let event_payload_path = env::var("GITHUB_EVENT_PATH").ok()?;
+        let payload = fs::read_to_string(&event_payload_path)
+            .map_err(|e| log::debug!("Failed to read event payload from {event_payload_path}: {e}"))
+            .ok()?;
+        let push_event = serde_json::from_str::<PushEventPayload>(&payload)
+            .map_err(|e| log::debug!("Failed to deserialize push event payload: {e}"))
+            .ok()?;
+        if push_event.before.bytes().all(|b| b == b'0') {
+            log::debug!(
+                "The push created a branch; only listing the changes in {}",
+                push_event.after
+            );
+            return None;
+        }
+        Some(push_event)
+    }
+
+    /// Build the default HTTP client.
+    ///
+    /// Headers and request timeouts are applied by [`RestApiClient::make_api_request()`],
+    /// so they also apply to a client given to [`RestApiClient::set_http_client()`].
+    pub(super) fn make_client(timeouts: &Timeouts) -> Result<Client, ClientError> {
+        Ok(Client::builder()
+            .connect_timeout(timeouts.connect)
+            .build()?)
+    }
+
     pub(super) fn make_headers() -> Result<HeaderMap<HeaderValue>, ClientError> {
         let mut headers = HeaderMap::new();
         headers.insert(
@@ -111,18 +191,11 @@ impl GithubApiClient {
                 Some(serde_json::json!(&payload).to_string()),
                 None,
             )?;
-            match self
+            let response = self
                 .send_api_request(&self.client, request, &self.rate_limit_headers)
                 .await
-            {
-                Ok(response) => {
-                    self.log_response(response, "Failed to post thread comment")
-                        .await;
-                }
-                Err(e) => {
-                    return Err(e.add_request_context("post thread comment"));
-                }
-            }
+                .map_err(|e| e.with_context("post thread comment"))?;
+            ClientError::check_response(response, "post thread comment").await?;
         }
         Ok(())
     }
@@ -135,7 +208,6 @@ impl GithubApiClient {
         delete: bool,
     ) -> Result<Option<Url>, ClientError> {
         let mut comment_url = None;
-        let mut comments_url = Some(Url::parse_with_params(url.as_str(), &[("page", "1")])?);
         let repo = format!(
             "repos/{}{}/comments",
             // if we got here, then we know it is on a CI runner as self.repo should be known
@@ -143,81 +215,329 @@ impl GithubApiClient {
             if self.is_pr_event() { "/issues" } else { "" },
         );
         let base_comment_url = self.api_url.join(&repo)?;
-        while let Some(endpoint) = comments_url.take() {
-            let request = self.make_api_request(&self.client, endpoint, Method::GET, None, None)?;
-            let result = self
-                .send_api_request(&self.client, request, &self.rate_limit_headers)
-                .await;
-            match result {
-                Err(e) => {
-                    return Err(e.add_request_context("get list of existing thread comments"));
+        let mut pages: Paginator<_, Vec<ThreadComment>> = Paginator::new(
+            self,
+            &self.client,
+            url.clone(),
+            &self.rate_limit_headers,
+            "list of existing thread comments",
+        );
+        while let Some(page) = pages.next_page().await {
+            let payload = match page {
+                Ok(payload) => payload,
+                // the paginator already logged the failed response
+                Err(ClientError::RequestContext { source, .. }) if source.is_status() => {
+                    return Ok(comment_url);
                 }
-                Ok(response) => {
-                    if !response.status().is_success() {
-                        self.log_response(
-                            response,
-                            "Failed to get list of existing thread comments",
-                        )
-                        .await;
-                        return Ok(comment_url);
+                Err(e) => return Err(e),
+            };
+            for comment in payload {
+                if comment.body.starts_with(comment_marker) {
+                    log::debug!(
+                        "Found bot comment id {} from user {} ({})",
+                        comment.id,
+                        comment.user.login,
+                        comment.user.id,
+                    );
+                    let this_comment_url =
+                        Url::parse(format!("{base_comment_url}/{}", comment.id).as_str())?;
+                    if delete || comment_url.is_some() {
+                        // if not updating: remove all outdated comments
+                        // if updating: remove all outdated comments except the last one
+
+                        // use last saved comment_url (if not None) or current comment url
+                        let del_url = if let Some(last_url) = &comment_url {
+                            last_url
+                        } else {
+                            &this_comment_url
+                        };
+                        let req = self.make_api_request(
+                            &self.client,
+                            del_url.to_owned(),
+                            Method::DELETE,
+                            None,
+                            None,
+                        )?;
+                        let result = self
+                            .send_api_request(&self.client, req, &self.rate_limit_headers)
+                            .await
+                            .map_err(|e| e.with_context("delete old thread comment"))?;
+                        ClientError::check_response(result, "delete old thread comment").await?;
                     }
-                    comments_url = self.try_next_page(response.headers());
-                    let payload =
-                        serde_json::from_str::<Vec<ThreadComment>>(&response.text().await?)
-                            .map_err(|e| {
-                                ClientError::json("deserialize list of existing thread comments", e)
-                            })?;
-                    for comment in payload {
-                        if comment.body.starts_with(comment_marker) {
-                            log::debug!(
-                                "Found bot comment id {} from user {} ({})",
-                                comment.id,
-                                comment.user.login,
-                                comment.user.id,
-                            );
-                            let this_comment_url =
-                                Url::parse(format!("{base_comment_url}/{}", comment.id).as_str())?;
-                            if delete || comment_url.is_some() {
-                                // if not updating: remove all outdated comments
-                                // if updating: remove all outdated comments except the last one
-
-                                // use last saved comment_url (if not None) or current comment url
-                                let del_url = if let Some(last_url) = &comment_url {
-                                    last_url
-                                } else {
-                                    &this_comment_url
-                                };
-                                let req = self.make_api_request(
-                                    &self.client,
-                                    del_url.to_owned(),
-                                    Method::DELETE,
-                                    None,
-                                    None,
-                                )?;
-                                let result = self
-                                    .send_api_request(&self.client, req, &self.rate_limit_headers)
-                                    .await
-                                    .map_err(|e| {
-                                        e.add_request_context("delete old thread comment")
-                                    })?;
-                                self.log_response(result, "Failed to delete old thread comment")
-                                    .await;
-                            }
-                            if !delete {
-                                comment_url = Some(this_comment_url)
-                            }
-                        }
+                    if !delete {
+                        comment_url = Some(this_comment_url)
                     }
                 }
             }
         }
         Ok(comment_url)
     }
+
+    /// List the changed files from the paginated `files_url`.
+    ///
+    /// The `diff_url` is used to get the full diff if the server omits any patches,
+    /// or if the server lists `max_files` (the documented cap for the `files_url`).
+    /// The server silently truncates the list of files at its cap.
+    #[cfg(feature = "file-changes")]
+    pub(super) async fn list_changed_files(
+        &self,
+        files_url: Url,
+        diff_url: Url,
+        max_files: usize,
+        file_filter: &FileFilter,
+        lines_changed_only: &LinesChangedOnly,
+    ) -> Result<HashMap<String, FileDiffLines>, ClientError> {
+        let mut listed = 0;
+        let mut files: HashMap<String, FileDiffLines> = HashMap::new();
+        // files whose patch was omitted (because it is too large)
+        let mut omitted: Vec<(String, FileDiffLines)> = Vec::new();
+        let mut pages: Paginator<_, ChangedFilesPage> = Paginator::new(
+            self,
+            &self.client,
+            files_url,
+            &self.rate_limit_headers,
+            "list of changed files",
+        )
+        .per_page(self.changed_files_per_page);
+        while let Some(page) = pages.next_page().await {
+            let files_list = page?.into_files();
+            listed += files_list.len();
+            for file in files_list {
+                // renamed files are judged by their new path
+                if !file_filter.is_qualified(Path::new(&file.filename)) {
+                    continue;
+                }
+                let status = file.status.parse::<FileStatus>().unwrap_or_default();
+                let previous_path = file
+                    .previous_filename
+                    .clone()
+                    .filter(|_| matches!(status, FileStatus::Renamed | FileStatus::Copied));
+                if status == FileStatus::Removed {
+                    if file_filter.include_deleted {
+                        files
+                            .entry(file.filename)
+                            .or_insert(FileDiffLines::default().with_status(status, None));
+                    }
+                } else if let Some(patch) = file.patch {
+                    let diff = format!(
+                        "diff --git a/{old} b/{new}\n--- a/{old}\n+++ b/{new}\n{patch}\n",
+                        old = file.previous_filename.unwrap_or(file.filename.clone()),
+                        new = file.filename,
+                    );
+                    for (name, info) in parse_diff(&diff, file_filter, lines_changed_only)? {
+                        files
+                            .entry(name)
+                            .or_insert(info.with_status(status, previous_path.clone()));
+                    }
+                } else if file.changes == 0 {
+                    // file may have been only renamed.
+                    // include it in case files-changed-only is enabled.
+                    files
+                        .entry(file.filename)
+                        .or_insert(FileDiffLines::default().with_status(status, previous_path));
+                } else {
+                    // changes are too big (per git server limits)
+                    omitted.push((
+                        file.filename,
+                        FileDiffLines::default().with_status(status, previous_path),
+                    ));
+                }
+            }
+        }
+        let mut diff_fallback = self.diff_fallback;
+        if listed >= max_files {
+            log::warn!("Server listed {listed} changed files, which may be truncated");
+            if diff_fallback {
+                match self.get_full_diff(diff_url.clone()).await {
+                    Ok(diff) => return Ok(parse_diff(&diff, file_filter, lines_changed_only)?),
+                    Err(e @ ClientError::Http { .. }) => {
+                        log::warn!("Failed to get the full diff: {e}");
+                        // don't request it again for omitted patches
+                        diff_fallback = false;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        if !omitted.is_empty() {
+            self.recover_omitted_patches(
+                diff_fallback.then_some(diff_url),
+                omitted,
+                &mut files,
+                file_filter,
+                lines_changed_only,
+            )
+            .await?;
+        }
+        Ok(files)
+    }
+
+    /// List the files changed by the commit that triggered the CI run.
+    #[cfg(feature = "file-changes")]
+    pub(super) async fn list_commit_changed_files(
+        &self,
+        file_filter: &FileFilter,
+        lines_changed_only: &LinesChangedOnly,
+    ) -> Result<HashMap<String, FileDiffLines>, ClientError> {
+        let url = self
+            .api_url
+            .join(format!("repos/{}/commits/{}", self.repo, self.sha).as_str())?;
+        self.list_changed_files(
+            url.clone(),
+            url,
+            MAX_LISTED_COMMIT_FILES,
+            file_filter,
+            lines_changed_only,
+        )
+        .await
+    }
+
+    /// Get the full diff from the given `url`.
+    ///
+    /// The `url` should describe a Pull Request, a commit, or a comparison of commits.
+    #[cfg(feature = "file-changes")]
+    pub(super) async fn get_full_diff(&self, url: Url) -> Result<String, ClientError> {
+        let task = "get full diff";
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Accept",
+            HeaderValue::from_static("application/vnd.github.diff"),
+        );
+        let request = self.make_api_request(&self.client, url, Method::GET, None, Some(headers))?;
+        let response = self
+            .send_api_request(&self.client, request, &self.rate_limit_headers)
+            .await
+            .map_err(|e| e.with_context(task))?;
+        let response = ClientError::check_response(response, task).await?;
+        response
+            .text()
+            .await
+            .map_err(|e| ClientError::Request(e).with_context(task))
+    }
+
+    /// Get the changed lines of the `omitted` files (whose patches are too large to be
+    /// listed) from the full diff, and add them to the listed `files`.
+    ///
+    /// The full diff is requested from the `diff_url` (if any).
+    /// If the full diff is unavailable, then the `omitted` files are only
+    /// added for [`LinesChangedOnly::Off`].
+    #[cfg(feature = "file-changes")]
+    pub(super) async fn recover_omitted_patches(
+        &self,
+        diff_url: Option<Url>,
+        omitted: Vec<(String, FileDiffLines)>,
+        files: &mut HashMap<String, FileDiffLines>,
+        file_filter: &FileFilter,
+        lines_changed_only: &LinesChangedOnly,
+    ) -> Result<(), ClientError> {
+        let mut full_diff = HashMap::new();
+        if let Some(diff_url) = diff_url {
+            log::debug!(
+                "Server omitted the patch of {} file(s); using the full diff instead",
+                omitted.len()
+            );
+            match self.get_full_diff(diff_url).await {
+                Ok(diff) => full_diff = parse_diff(&diff, file_filter, lines_changed_only)?,
+                Err(e @ ClientError::Http { .. }) => {
+                    log::warn!("Failed to get the full diff: {e}");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        for (name, info) in omitted {
+            match full_diff.remove(&name) {
+                Some(diff) => {
+                    files
+                        .entry(name)
+                        .or_insert(diff.with_status(info.status, info.previous_path));
+                }
+                None if lines_changed_only.is_change_valid(false, false) => {
+                    files.entry(name).or_insert(info);
+                }
+                None => log::warn!("The changed lines of {name} are unknown (patch omitted)"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Render the given `annotations` as workflow commands while
+    /// respecting GitHub's limit of displayed annotations per severity.
+    ///
+    /// See [`MAX_ANNOTATIONS`] and [`AnnotationOverflow`].
+    pub(super) fn render_annotations(
+        annotations: &[FileAnnotation],
+        overflow: AnnotationOverflow,
+    ) -> Result<Vec<String>, ClientError> {
+        // Debug messages are not annotations; they are not limited.
+        let limited = [
+            AnnotationLevel::Error,
+            AnnotationLevel::Warning,
+            AnnotationLevel::Notice,
+        ];
+        let totals = limited.map(|severity| {
+            annotations
+                .iter()
+                .filter(|a| a.severity == severity)
+                .count()
+        });
+        if overflow == AnnotationOverflow::Error
+            && let Some((severity, count)) = limited
+                .into_iter()
+                .zip(totals)
+                .find(|(_, count)| *count > MAX_ANNOTATIONS)
+        {
+            return Err(ClientError::TooManyAnnotations {
+                severity,
+                count,
+                limit: MAX_ANNOTATIONS,
+            });
+        }
+        let mut written = [0; 3];
+        let mut commands = Vec::new();
+        for annotation in annotations {
+            if let Some(index) = limited.iter().position(|s| *s == annotation.severity) {
+                let limit = if overflow == AnnotationOverflow::Summarize
+                    && totals[index] > MAX_ANNOTATIONS
+                {
+                    // reserve a spot for the summary
+                    MAX_ANNOTATIONS - 1
+                } else {
+                    MAX_ANNOTATIONS
+                };
+                if written[index] >= limit {
+                    continue;
+                }
+                written[index] += 1;
+            }
+            commands.push(annotation.fmt_github());
+        }
+        for ((severity, total), written) in limited.into_iter().zip(totals).zip(written) {
+            if total <= MAX_ANNOTATIONS {
+                continue;
+            }
+            let omitted = total - written;
+            log::warn!(
+                "Omitted {omitted} {severity:?} annotations because GitHub only displays {MAX_ANNOTATIONS} per step"
+            );
+            if overflow == AnnotationOverflow::Summarize {
+                let summary = FileAnnotation {
+                    severity,
+                    message: format!("{omitted} more findings omitted; see step summary"),
+                    ..Default::default()
+                };
+                commands.push(summary.fmt_github());
+            }
+        }
+        Ok(commands)
+    }
 }
 
 impl FileAnnotation {
     /// Format the [`FileAnnotation`] struct into the specific string format compatible with Github Actions.
     ///
+    /// Special characters in the [`FileAnnotation::message`], [`FileAnnotation::path`],
+    /// and [`FileAnnotation::title`] are escaped (eg. line breaks become `%0A`).
+    ///
     /// See [Github workflow commands documentation](https://docs.github.com/en/actions/reference/workflows-and-actions/workflow-commands#setting-a-debug-message).
     ///
     /// Example:
@@ -225,60 +545,15 @@ impl FileAnnotation {
     /// ::notice file={name},line={line},col={col},endLine={endLine},endColumn={endColumn},title={title}::{message}
     /// ```
     pub fn fmt_github(&self) -> String {
-        let mut annotation_str = format!(
-            "::{}",
-            match self.severity {
-                AnnotationLevel::Debug => "debug",
-                AnnotationLevel::Notice => "notice",
-                AnnotationLevel::Warning => "warning",
-                AnnotationLevel::Error => "error",
-            }
-        );
-        let file_path = self
-            .path
-            .replace("\\", "/")
-            .trim_start()
-            .trim_start_matches('/')
-            .trim_start_matches("./")
-            .trim()
-            .to_string();
-        if !file_path.is_empty() {
-            annotation_str.push_str(" file=");
-            annotation_str.push_str(file_path.as_str());
-            if let Some(start_line) = self.start_line.map(|l| l.max(1)) {
-                annotation_str.push_str(format!(",line={start_line}").as_str());
-                let col = self.start_column.map(|c| c.max(1));
-                if let Some(col) = col {
-                    annotation_str.push_str(format!(",col={col}").as_str());
-                }
-                if let Some(end_line) = self.end_line.map(|l| l.max(1))
-                    && end_line > start_line
-                {
-                    annotation_str.push_str(format!(",endline={end_line}").as_str());
-                    if let Some(end_col) = self.end_column.map(|c| c.max(1)) {
-                        annotation_str.push_str(format!(",endColumn={end_col}").as_str());
-                    }
-                } else if let Some(end_col) = self.end_column.map(|c| c.max(1))
-                    && col.is_none_or(|c| c < end_col)
-                {
-                    annotation_str.push_str(format!(",endColumn={end_col}").as_str());
-                }
-            }
-            if let Some(title) = &self.title {
-                annotation_str.push_str(",title=");
-                annotation_str.push_str(title.as_str());
-            }
-        } else if let Some(title) = &self.title {
-            annotation_str.push_str(" title=");
-            annotation_str.push_str(title.as_str());
-        }
-        format!("{annotation_str}::{}", self.message)
+        self.fmt_workflow_command(true)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{AnnotationLevel, FileAnnotation};
+    #![allow(clippy::unwrap_used)]
+    use super::{GithubApiClient, MAX_ANNOTATIONS};
+    use crate::{AnnotationLevel, AnnotationOverflow, FileAnnotation, RestClientError};
 
     #[test]
     fn generic_message() {
@@ -348,7 +623,7 @@ mod tests {
         };
         assert_eq!(
             annotation.fmt_github(),
-            "::notice file=src/lib.rs,line=10,endline=20::This is a notice message"
+            "::notice file=src/lib.rs,line=10,endLine=20::This is a notice message"
         );
     }
 
@@ -366,7 +641,7 @@ mod tests {
         };
         assert_eq!(
             annotation.fmt_github(),
-            "::notice file=src/lib.rs,line=10,col=5,endline=20,endColumn=15::This is a notice message"
+            "::notice file=src/lib.rs,line=10,col=5,endLine=20,endColumn=15::This is a notice message"
         );
     }
 
@@ -413,4 +688,132 @@ mod tests {
         };
         assert_eq!(annotation.fmt_github(), "::debug::This is a debug message");
     }
+
+    #[test]
+    fn annotate_blank_path_with_title_only() {
+        let annotation = FileAnnotation {
+            severity: AnnotationLevel::Notice,
+            message: "msg".to_string(),
+            title: Some("My Title".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(annotation.fmt_github(), "::notice title=My Title::msg");
+    }
+
+    #[test]
+    fn annotate_file_with_col_no_line() {
+        let annotation = FileAnnotation {
+            severity: AnnotationLevel::Warning,
+            path: "src/lib.rs".to_string(),
+            message: "This is a warning message".to_string(),
+            start_column: Some(5),
+            end_column: Some(15),
+            title: Some("Warning Title".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            annotation.fmt_github(),
+            "::warning file=src/lib.rs,title=Warning Title::This is a warning message"
+        );
+    }
+
+    #[test]
+    fn annotate_multi_line_message() {
+        let annotation = FileAnnotation {
+            severity: AnnotationLevel::Error,
+            message: "first line\r\nsecond line (100%)\n::warning::not a command".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            annotation.fmt_github(),
+            "::error::first line%0D%0Asecond line (100%25)%0A::warning::not a command"
+        );
+    }
+
+    #[test]
+    fn annotate_title_with_commas_and_colons() {
+        let annotation = FileAnnotation {
+            severity: AnnotationLevel::Warning,
+            path: "src/a,b.rs".to_string(),
+            start_line: Some(3),
+            title: Some("clippy::needless_return, again".to_string()),
+            message: "msg".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            annotation.fmt_github(),
+            "::warning file=src/a%2Cb.rs,line=3,title=clippy%3A%3Aneedless_return%2C again::msg"
+        );
+    }
+
+    fn warnings(count: usize) -> Vec<FileAnnotation> {
+        (0..count)
+            .map(|i| FileAnnotation {
+                severity: AnnotationLevel::Warning,
+                message: format!("warning {i}"),
+                ..Default::default()
+            })
+            .chain([FileAnnotation {
+                severity: AnnotationLevel::Error,
+                message: "error".to_string(),
+                ..Default::default()
+            }])
+            .collect()
+    }
+
+    #[test]
+    fn truncate_annotations() {
+        let commands =
+            GithubApiClient::render_annotations(&warnings(25), AnnotationOverflow::Truncate)
+                .unwrap();
+        assert_eq!(commands.len(), MAX_ANNOTATIONS + 1);
+        assert_eq!(commands[MAX_ANNOTATIONS - 1], "::warning::warning 9");
+        assert_eq!(commands[MAX_ANNOTATIONS], "::error::error");
+    }
+
+    #[test]
+    fn summarize_annotations() {
+        let commands =
+            GithubApiClient::render_annotations(&warnings(25), AnnotationOverflow::Summarize)
+                .unwrap();
+        assert_eq!(commands.len(), MAX_ANNOTATIONS + 1);
+        assert_eq!(
+            commands
+                .iter()
+                .filter(|c| c.starts_with("::warning"))
+                .count(),
+            MAX_ANNOTATIONS
+        );
+        assert_eq!(commands[MAX_ANNOTATIONS - 2], "::warning::warning 8");
+        assert_eq!(
+            commands.last().unwrap(),
+            "::warning::16 more findings omitted; see step summary"
+        );
+    }
+
+    #[test]
+    fn error_on_annotation_overflow() {
+        let err = GithubApiClient::render_annotations(&warnings(25), AnnotationOverflow::Error)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            RestClientError::TooManyAnnotations {
+                severity: AnnotationLevel::Warning,
+                count: 25,
+                limit: MAX_ANNOTATIONS
+            }
+        ));
+    }
+
+    #[test]
+    fn annotations_within_limit() {
+        for overflow in [
+            AnnotationOverflow::Truncate,
+            AnnotationOverflow::Summarize,
+            AnnotationOverflow::Error,
+        ] {
+            let commands = GithubApiClient::render_annotations(&warnings(10), overflow).unwrap();
+            assert_eq!(commands.len(), 11);
+        }
+    }
 }
@@ -0,0 +1,493 @@
+use std::{collections::HashSet, env, sync::Arc};
+
+use async_trait::async_trait;
+use reqwest::{
+    Client, Method, Request,
+    header::{self, HeaderMap, HeaderValue},
+};
+use url::Url;
+
+use super::{
+    CancellationToken, Capabilities, ClientError, Metrics, RateLimitSlot, RateLimitSnapshot,
+    RequestRecorder, ResponseCache, RestApiClient, RestApiRateLimitHeaders, RetryPolicy, Throttle,
+    Timeouts, assert_send_sync_clone, build_request, in_span, log_unsupported, sealed,
+    user_agent_header,
+};
+use crate::{OutputVariable, ReviewOptions, ReviewOutcome, ThreadCommentOptions};
+mod serde_structs;
+mod specific_api;
+use serde_structs::NewDiscussion;
+use specific_api::{DiffRefs, section_name, section_timestamp};
+
+#[cfg(feature = "file-changes")]
+use crate::{FileDiffLines, FileFilter, LinesChangedOnly};
+#[cfg(feature = "file-changes")]
+use std::collections::HashMap;
+
+/// A structure to work with GitLab's REST API (v4).
+///
+/// GitLab CI has no equivalent of a step summary, so
+/// [`RestApiClient::append_step_summary()`] does nothing.
+/// Output variables are written to a [dotenv report] file
+/// (see [`RestApiClient::write_output_variables()`]).
+///
+/// [dotenv report]: https://docs.gitlab.com/ci/yaml/artifacts_reports/#artifactsreportsdotenv
+#[derive(Clone)]
+pub struct GitlabApiClient {
+    /// The HTTP request client to be used for all REST API calls.
+    client: Client,
+
+    /// The value of the `CI_MERGE_REQUEST_IID` environment variable.
+    merge_request_iid: Option<u64>,
+
+    /// The value of the `CI_PIPELINE_SOURCE` environment variable.
+    pub event_name: String,
+
+    /// The value of the `CI_API_V4_URL` environment variable.
+    api_url: Url,
+
+    /// The value of the `CI_PROJECT_ID` environment variable.
+    project_id: String,
+
+    /// The value of the `CI_COMMIT_SHA` environment variable.
+    sha: String,
+
+    /// The value of the `CI_DEBUG_TRACE` environment variable.
+    pub debug_enabled: bool,
+
+    /// The response header names that describe the rate limit status.
+    rate_limit_headers: RestApiRateLimitHeaders,
+
+    /// Is a missing environment variable for a file-based writer an error?
+    ///
+    /// See [`RestApiClient::set_strict_mode()`].
+    strict: bool,
+
+    /// See [`RestApiClient::set_dry_run()`].
+    dry_run: bool,
+
+    /// See [`RestApiClient::set_retry_policy()`].
+    retry_policy: RetryPolicy,
+
+    /// See [`RestApiClient::last_rate_limit()`].
+    last_rate_limit: RateLimitSlot,
+
+    /// See [`RestApiClient::set_user_agent()`].
+    user_agent: HeaderValue,
+
+    /// The headers needed by the REST API (eg. `Accept` and `PRIVATE-TOKEN`).
+    headers: HeaderMap,
+
+    /// Was the [`Self::client`] given to [`RestApiClient::set_http_client()`]?
+    custom_http_client: bool,
+
+    /// See [`RestApiClient::set_response_cache()`].
+    response_cache: Option<Arc<ResponseCache>>,
+
+    /// See [`RestApiClient::set_request_recorder()`].
+    request_recorder: Option<Arc<RequestRecorder>>,
+
+    /// See [`RestApiClient::set_throttle()`].
+    throttle: Arc<Throttle>,
+
+    /// See [`RestApiClient::set_metrics()`].
+    metrics: Option<Arc<dyn Metrics>>,
+
+    /// See [`RestApiClient::set_cancellation_token()`].
+    cancellation_token: Option<CancellationToken>,
+
+    /// See [`RestApiClient::set_timeouts()`].
+    timeouts: Timeouts,
+
+    /// The outcome of [`RestApiClient::cull_pr_reviews()`] to be completed by
+    /// [`RestApiClient::post_pr_review()`].
+    review_outcome: ReviewOutcome,
+}
+
+const _: () = assert_send_sync_clone::<GitlabApiClient>();
+
+#[async_trait]
+impl RestApiClient for GitlabApiClient {
+    /// This starts a [collapsible section] of the job's log.
+    ///
+    /// [collapsible section]: https://docs.gitlab.com/ci/jobs/job_logs/#custom-collapsible-sections
+    fn start_log_group(&self, name: &str) {
+        log::info!(
+            target: "CI_LOG_GROUPING",
+            "\x1b[0Ksection_start:{}:{}[collapsed=true]\r\x1b[0K{}",
+            section_timestamp(),
+            section_name(name),
+            name.replace(['\r', '\n'], " ")
+        );
+    }
+
+    /// The `name` must be the same as given to [`RestApiClient::start_log_group()`].
+    fn end_log_group(&self, name: &str) {
+        log::info!(
+            target: "CI_LOG_GROUPING",
+            "\x1b[0Ksection_end:{}:{}\r\x1b[0K",
+            section_timestamp(),
+            section_name(name)
+        );
+    }
+
+    fn is_pr_event(&self) -> bool {
+        self.merge_request_iid.is_some()
+    }
+
+    fn event_name(&self) -> Option<String> {
+        Some(self.event_name.clone())
+    }
+
+    fn is_debug_enabled(&self) -> bool {
+        self.debug_enabled
+    }
+
+    fn set_user_agent(&mut self, user_agent: &str) -> Result<(), ClientError> {
+        self.user_agent = user_agent_header(user_agent)?;
+        Ok(())
+    }
+
+    fn set_http_client(&mut self, client: Client) {
+        self.client = client;
+        self.custom_http_client = true;
+    }
+
+    fn throttle(&self) -> Option<&Throttle> {
+        Some(&*self.throttle)
+    }
+
+    fn set_throttle(&mut self, throttle: Throttle) {
+        self.throttle = Arc::new(throttle);
+    }
+
+    fn cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
+    fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.cancellation_token = token;
+    }
+
+    fn metrics(&self) -> Option<&dyn Metrics> {
+        self.metrics.as_deref()
+    }
+
+    fn set_metrics(&mut self, metrics: Option<Arc<dyn Metrics>>) {
+        self.metrics = metrics;
+    }
+
+    fn response_cache(&self) -> Option<&ResponseCache> {
+        self.response_cache.as_deref()
+    }
+
+    fn set_response_cache(&mut self, cache: Option<ResponseCache>) {
+        self.response_cache = cache.map(Arc::new);
+    }
+
+    fn request_recorder(&self) -> Option<&RequestRecorder> {
+        self.request_recorder.as_deref()
+    }
+
+    fn set_request_recorder(&mut self, recorder: Option<RequestRecorder>) {
+        self.request_recorder = recorder.map(Arc::new);
+    }
+
+    fn timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+
+    fn set_timeouts(&mut self, timeouts: Timeouts) -> Result<(), ClientError> {
+        if !self.custom_http_client {
+            self.client = Self::make_client(&timeouts)?;
+        }
+        self.timeouts = timeouts;
+        Ok(())
+    }
+
+    /// The headers needed by the REST API and the [`Timeouts::request`] are applied to
+    /// every request. Any given `headers` take precedence.
+    fn make_api_request(
+        &self,
+        client: &Client,
+        url: Url,
+        method: Method,
+        data: Option<String>,
+        headers: Option<HeaderMap>,
+    ) -> Result<Request, ClientError> {
+        let mut merged = self.headers.clone();
+        merged.insert(header::USER_AGENT, self.user_agent.clone());
+        if data.is_some() {
+            // GitLab parses a body without this header as form data
+            merged.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            );
+        }
+        if let Some(headers) = headers {
+            merged.extend(headers);
+        }
+        let mut request = build_request(client, url, method, data, Some(merged))?;
+        request.timeout_mut().get_or_insert(self.timeouts.request);
+        Ok(request)
+    }
+
+    /// Thread comments are posted as notes on the Merge Request.
+    ///
+    /// GitLab's commit comments cannot be updated or deleted,
+    /// so thread comments are not posted for other pipelines.
+    async fn post_thread_comment(&self, options: ThreadCommentOptions) -> Result<(), ClientError> {
+        let Some(iid) = self.merge_request_iid else {
+            log_unsupported(self, "posting a thread comment outside of a merge request");
+            return Ok(());
+        };
+        Self::check_token()?;
+        let notes_url =
+            self.project_endpoint(&["merge_requests", iid.to_string().as_str(), "notes"])?;
+        let operation = self.timeouts.with_deadline(
+            "post thread comment",
+            self.update_comment(notes_url, options),
+        );
+        in_span!(
+            operation,
+            "post_thread_comment",
+            repo = self.project_id.as_str(),
+            pr = self.merge_request_iid,
+        )
+        .await
+    }
+
+    /// Existing review comments are the unresolved discussions (started by this software)
+    /// on the Merge Request.
+    ///
+    /// Outdated discussions are resolved (or deleted if [`ReviewOptions::delete_review_comments`]
+    /// is `true`). GitLab has no equivalent of a review's dismissal message, so
+    /// [`ReviewOptions::dismissal_message`] is not used.
+    async fn cull_pr_reviews(&mut self, options: &mut ReviewOptions) -> Result<(), ClientError> {
+        let Some(iid) = self.merge_request_iid else {
+            return Ok(());
+        };
+        Self::check_token()?;
+        let discussions = self.get_bot_discussions(iid, &options.marker).await?;
+        if discussions.is_empty() {
+            return Ok(());
+        }
+
+        let mut outdated = Vec::new();
+        let mut dismissed_reviews = 0;
+        let mut reused_comments = HashSet::new();
+        for discussion in &discussions {
+            let Some(note) = discussion.notes.first() else {
+                continue;
+            };
+            if note.position.is_none() {
+                // the summary of a previous review
+                dismissed_reviews += 1;
+                outdated.push(discussion);
+                continue;
+            }
+            match options.comments.iter().find(|proposed| {
+                !reused_comments.contains(*proposed)
+                    && Self::match_review_comment(note, proposed, &options.marker)
+            }) {
+                Some(proposed) => {
+                    log::info!(
+                        "Using existing review comment: path='{}', line_end={}",
+                        proposed.path,
+                        proposed.line_end
+                    );
+                    reused_comments.insert(proposed.clone());
+                }
+                None => outdated.push(discussion),
+            }
+        }
+
+        // Remove reused comments from proposed comments
+        let proposed_comments = options.comments.len();
+        options.comments.retain(|c| !reused_comments.contains(c));
+        self.review_outcome.reused_comments = proposed_comments - options.comments.len();
+        self.review_outcome.dismissed_reviews = dismissed_reviews;
+
+        self.resolve_outdated_discussions(iid, &outdated, options.delete_review_comments)
+            .await
+    }
+
+    /// A review is posted as a discussion (the summary) and a discussion for each comment
+    /// positioned in the Merge Request's diff.
+    ///
+    /// GitLab rejects comments on lines that are not in the diff;
+    /// such comments are skipped (with a logged error).
+    /// The review's [`ReviewOptions::action`] is applied with GitLab's approvals API:
+    /// [`ReviewAction::Approve`] approves the Merge Request and
+    /// [`ReviewAction::RequestChanges`] revokes a previous approval.
+    /// If the token is not permitted to do so, the review is downgraded to only comments
+    /// (with a logged warning).
+    ///
+    /// The positions of comments are read from the `CI_MERGE_REQUEST_DIFF_BASE_SHA`,
+    /// `CI_MERGE_REQUEST_TARGET_BRANCH_SHA`, and `CI_MERGE_REQUEST_SOURCE_BRANCH_SHA`
+    /// environment variables.
+    ///
+    /// [`ReviewAction::Approve`]: crate::ReviewAction::Approve
+    /// [`ReviewAction::RequestChanges`]: crate::ReviewAction::RequestChanges
+    async fn post_pr_review(
+        &mut self,
+        options: &ReviewOptions,
+    ) -> Result<ReviewOutcome, ClientError> {
+        let mut outcome = std::mem::take(&mut self.review_outcome);
+        in_span!(
+            async move {
+                let Some(iid) = self.merge_request_iid else {
+                    log_unsupported(self, "posting a review outside of a merge request");
+                    return Ok(outcome);
+                };
+                Self::check_token()?;
+                let diff_refs = if options.comments.is_empty() {
+                    None
+                } else {
+                    Some(DiffRefs::from_env(&self.sha)?)
+                };
+
+                let summary = NewDiscussion {
+                    body: format!("{}{}", options.marker, options.summary),
+                    position: None,
+                };
+                if let Some(discussion) = self
+                    .post_discussion(iid, &summary, "post PR review")
+                    .await?
+                {
+                    outcome.review_id = discussion.notes.first().map(|note| note.id);
+                    outcome.html_url = outcome.review_id.and_then(|id| {
+                        let project_url = env::var("CI_MERGE_REQUEST_PROJECT_URL").ok()?;
+                        Url::parse(
+                            format!(
+                                "{}/-/merge_requests/{iid}#note_{id}",
+                                project_url.trim_end_matches('/')
+                            )
+                            .as_str(),
+                        )
+                        .ok()
+                    });
+                }
+
+                if let Some(diff_refs) = diff_refs {
+                    for comment in &options.comments {
+                        let payload = NewDiscussion {
+                            body: comment.mark_comment(&options.marker),
+                            position: Some(diff_refs.position(comment)),
+                        };
+                        let task = format!(
+                            "post review comment for {}:{}",
+                            comment.path, comment.line_end
+                        );
+                        // a dry run still counts the comments that would be posted
+                        if self.post_discussion(iid, &payload, &task).await?.is_some()
+                            || self.dry_run
+                        {
+                            outcome.posted_comments += 1;
+                        }
+                    }
+                }
+
+                self.set_approval(iid, &options.action).await?;
+                Ok(outcome)
+            },
+            "post_pr_review",
+            repo = self.project_id.as_str(),
+            pr = self.merge_request_iid,
+        )
+        .await
+    }
+
+    /// The `vars` are appended to the file referenced by the `GIT_BOT_FEEDBACK_DOTENV`
+    /// environment variable (the file is created if it does not exist).
+    /// GitLab does not set this variable; it is specific to this crate.
+    /// The job should set it to the same path that it declares as its `artifacts:reports:dotenv`,
+    /// so the variables are available to later jobs in the pipeline:
+    ///
+    /// ```yaml
+    /// lint:
+    ///   variables:
+    ///     GIT_BOT_FEEDBACK_DOTENV: feedback.env
+    ///   artifacts:
+    ///     reports:
+    ///       dotenv: feedback.env
+    /// ```
+    ///
+    /// If `GIT_BOT_FEEDBACK_DOTENV` is not set, then the `vars` are not written
+    /// (or a [`ClientError::EnvVar`] error is returned in [strict mode](RestApiClient::set_strict_mode)).
+    ///
+    /// Dotenv files do not support multi-line values nor names that contain `-`.
+    fn write_output_variables(&self, vars: &[OutputVariable]) -> Result<(), ClientError> {
+        self.append_dotenv_file(vars)
+    }
+
+    fn set_strict_mode(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    fn last_rate_limit(&self) -> Option<RateLimitSnapshot> {
+        self.last_rate_limit.get()
+    }
+
+    fn record_rate_limit(&self, snapshot: RateLimitSnapshot, _token: sealed::Token) {
+        self.last_rate_limit.set(snapshot);
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = policy;
+    }
+
+    /// For merge request pipelines, the changed files are listed by the paginated
+    /// `merge_requests/{iid}/diffs` endpoint. Otherwise, the files changed by
+    /// the commit that triggered the pipeline are listed.
+    ///
+    /// If GitLab omits the diff of a file (because it is too large),
+    /// then the Merge Request's raw diff is parsed instead (if available).
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    async fn get_list_of_changed_files(
+        &self,
+        file_filter: &FileFilter,
+        lines_changed_only: &LinesChangedOnly,
+        _base_diff: Option<String>,
+        _ignore_index: bool,
+    ) -> Result<HashMap<String, FileDiffLines>, ClientError> {
+        let operation = self.timeouts.with_deadline(
+            "get list of changed files",
+            self.get_changed_files(file_filter, lines_changed_only),
+        );
+        in_span!(
+            operation,
+            "get_list_of_changed_files",
+            repo = self.project_id.as_str(),
+            pr = self.merge_request_iid,
+        )
+        .await
+    }
+
+    fn client_kind(&self) -> String {
+        "gitlab".to_string()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            // GitLab's commit comments cannot be updated or deleted
+            thread_comments_on_push: false,
+            output_variables: true,
+            pr_reviews: true,
+            ..Default::default()
+        }
+    }
+}
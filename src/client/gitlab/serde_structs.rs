@@ -0,0 +1,110 @@
+//! This submodule declares data structures used to
+//! deserialize (and serializer) JSON payload data.
+
+use serde::{Deserialize, Serialize};
+
+/// A structure for deserializing a note (comment) on a Merge Request.
+#[derive(Debug, Deserialize)]
+pub struct Note {
+    /// The note's ID.
+    pub id: i64,
+    /// The note's content.
+    pub body: String,
+    /// The note's author.
+    pub author: NoteAuthor,
+    /// Is this note generated by GitLab (eg. "added 1 commit")?
+    #[serde(default)]
+    pub system: bool,
+    /// Can this note be resolved (as part of a [`Discussion`])?
+    #[serde(default)]
+    pub resolvable: bool,
+    /// Is this note resolved?
+    #[serde(default)]
+    pub resolved: bool,
+    /// The position of a note in the Merge Request's diff.
+    ///
+    /// This is [`None`] for notes that are not about a specific line.
+    #[serde(default)]
+    pub position: Option<NotePosition>,
+}
+
+/// A structure for deserializing the author of a [`Note`].
+#[derive(Debug, Deserialize)]
+pub struct NoteAuthor {
+    /// The author's user name.
+    pub username: String,
+    /// The author's ID.
+    pub id: u64,
+}
+
+/// A structure for deserializing the position of a [`Note`] in a Merge Request's diff.
+///
+/// Unknown fields are ignored.
+#[derive(Debug, Deserialize)]
+pub struct NotePosition {
+    /// The file's name (including relative path to repo root) after the changes.
+    pub new_path: Option<String>,
+    /// The line number (in the changed file) that the note is about.
+    ///
+    /// This is [`None`] for notes about a removed line.
+    pub new_line: Option<u32>,
+}
+
+/// A structure for deserializing a discussion (a thread of notes) on a Merge Request.
+#[derive(Debug, Deserialize)]
+pub struct Discussion {
+    /// The discussion's ID.
+    pub id: String,
+    /// The notes in the discussion; the first note started the discussion.
+    pub notes: Vec<Note>,
+}
+
+/// A structure for serializing a new discussion on a Merge Request.
+#[derive(Debug, Serialize)]
+pub struct NewDiscussion {
+    pub body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<DiffPosition>,
+}
+
+/// A structure for serializing the position of a new [`Discussion`] in a Merge Request's diff.
+#[derive(Debug, Serialize, Clone)]
+pub struct DiffPosition {
+    pub position_type: &'static str,
+    pub base_sha: String,
+    pub start_sha: String,
+    pub head_sha: String,
+    pub old_path: String,
+    pub new_path: String,
+    pub new_line: u32,
+}
+
+/// A structure for deserializing a file changed in a Merge Request (or commit).
+///
+/// Unknown fields are ignored.
+#[cfg(feature = "file-changes")]
+#[derive(Debug, Deserialize)]
+pub struct ChangedFile {
+    /// The file's name (including relative path to repo root) before the changes.
+    pub old_path: String,
+    /// The file's name (including relative path to repo root) after the changes.
+    pub new_path: String,
+    /// The hunks that describe the file's changes (without the `diff --git` front matter).
+    #[serde(default)]
+    pub diff: String,
+    /// Was the file created?
+    #[serde(default)]
+    pub new_file: bool,
+    /// Was the file renamed?
+    #[serde(default)]
+    pub renamed_file: bool,
+    /// Was the file deleted?
+    #[serde(default)]
+    pub deleted_file: bool,
+    /// Was the [`Self::diff`] omitted because it exceeds GitLab's size limits?
+    #[serde(default)]
+    pub too_large: bool,
+    /// Was the [`Self::diff`] omitted because it exceeds GitLab's limits for displayed diffs?
+    #[serde(default)]
+    pub collapsed: bool,
+}
@@ -0,0 +1,751 @@
+//! This submodule implements functionality exclusively specific to GitLab's REST API.
+
+use super::{
+    GitlabApiClient,
+    serde_structs::{DiffPosition, Discussion, NewDiscussion, Note, NotePosition},
+};
+use crate::{
+    CommentKind, CommentPolicy, OutputVariable, RestApiClient, RestApiRateLimitHeaders,
+    ReviewAction, ReviewComment, ReviewOutcome, ThreadCommentOptions,
+    client::{
+        ClientError, Paginator, RateLimitSlot, RetryPolicy, Throttle, Timeouts, USER_AGENT,
+        env_file,
+    },
+    comments::review_comments::PostedReviewComment,
+};
+use reqwest::{
+    Client, Method, StatusCode, Url,
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+use std::{
+    collections::HashMap,
+    env,
+    fs::OpenOptions,
+    io::Write,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "file-changes")]
+use super::serde_structs::ChangedFile;
+#[cfg(feature = "file-changes")]
+use crate::{FileDiffLines, FileFilter, FileStatus, LinesChangedOnly, parse_diff};
+#[cfg(feature = "file-changes")]
+use std::path::Path;
+
+/// The environment variables that provide a token, in order of precedence,
+/// along with the header used to send it.
+///
+/// A `CI_JOB_TOKEN` has limited permissions; posting notes usually requires a
+/// project (or personal) access token in `GITLAB_TOKEN`.
+const TOKEN_ENV_VARS: [(&str, &str); 2] = [
+    ("GITLAB_TOKEN", "private-token"),
+    ("CI_JOB_TOKEN", "job-token"),
+];
+
+/// The environment variable that references the dotenv file written by
+/// [`RestApiClient::write_output_variables()`].
+const DOTENV_ENV: &str = "GIT_BOT_FEEDBACK_DOTENV";
+
+/// Get the URL of the REST API endpoint for the project with the given `project_id` at the given `path`.
+///
+/// Each item of `path` is a path segment (which is percent-encoded as needed).
+fn project_endpoint(api_url: &Url, project_id: &str, path: &[&str]) -> Result<Url, ClientError> {
+    let mut url = api_url.clone();
+    url.path_segments_mut()
+        .map_err(|_| ClientError::UrlParse(url::ParseError::RelativeUrlWithCannotBeABaseBase))?
+        .pop_if_empty()
+        .push("projects")
+        .push(project_id)
+        .extend(path);
+    Ok(url)
+}
+
+/// The commits that describe a Merge Request's diff.
+///
+/// These are needed to position a review comment in the diff.
+pub(super) struct DiffRefs {
+    base_sha: String,
+    start_sha: String,
+    head_sha: String,
+}
+
+impl DiffRefs {
+    /// Read the diff's commits from the environment variables set in a merge request pipeline.
+    ///
+    /// The `head_sha` is used if `CI_MERGE_REQUEST_SOURCE_BRANCH_SHA` is not set.
+    pub(super) fn from_env(head_sha: &str) -> Result<Self, ClientError> {
+        let non_empty = |name: &str| env::var(name).ok().filter(|val| !val.trim().is_empty());
+        let base_sha = env::var("CI_MERGE_REQUEST_DIFF_BASE_SHA")
+            .map_err(|e| ClientError::env_var("CI_MERGE_REQUEST_DIFF_BASE_SHA", e))?;
+        Ok(Self {
+            // only set in merged results pipelines
+            start_sha: non_empty("CI_MERGE_REQUEST_TARGET_BRANCH_SHA")
+                .unwrap_or_else(|| base_sha.clone()),
+            // In merged results pipelines, `CI_COMMIT_SHA` is the (temporary) merge commit.
+            head_sha: non_empty("CI_MERGE_REQUEST_SOURCE_BRANCH_SHA")
+                .unwrap_or_else(|| head_sha.to_string()),
+            base_sha,
+        })
+    }
+
+    /// The position of the given `comment` in the diff.
+    ///
+    /// GitLab only positions a note on a single line, so the comment is
+    /// placed on its [`ReviewComment::line_end`].
+    pub(super) fn position(&self, comment: &ReviewComment) -> DiffPosition {
+        DiffPosition {
+            position_type: "text",
+            base_sha: self.base_sha.clone(),
+            start_sha: self.start_sha.clone(),
+            head_sha: self.head_sha.clone(),
+            old_path: comment.path.clone(),
+            new_path: comment.path.clone(),
+            new_line: comment.line_end,
+        }
+    }
+}
+
+/// Sanitize the given `name` for use as the name of a collapsible log section.
+///
+/// GitLab only allows ASCII alphanumeric characters, `_`, `.`, and `-` in section names.
+pub(super) fn section_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// The current unix timestamp (in seconds) used to mark the boundaries of a log section.
+pub(super) fn section_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+impl GitlabApiClient {
+    /// Instantiate a [`GitlabApiClient`] object.
+    ///
+    /// The following environment variables (set by GitLab CI) are read:
+    ///
+    /// | variable | description |
+    /// |----------|-------------|
+    /// | `CI_API_V4_URL` | The REST API's root URL (eg. `https://gitlab.com/api/v4`). |
+    /// | `CI_PROJECT_ID` | The ID of the project. |
+    /// | `CI_COMMIT_SHA` | The commit being checked. |
+    /// | `CI_MERGE_REQUEST_IID` | The Merge Request's ID within the project (only set in merge request pipelines). |
+    /// | `CI_PIPELINE_SOURCE` | The [`RestApiClient::event_name()`] (eg. `merge_request_event`). |
+    /// | `CI_DEBUG_TRACE` | Enables [`RestApiClient::is_debug_enabled()`] if `true`. |
+    ///
+    /// The token is read from `GITLAB_TOKEN` (sent as a `PRIVATE-TOKEN` header)
+    /// or `CI_JOB_TOKEN` (sent as a `JOB-TOKEN` header).
+    pub fn new() -> Result<Self, ClientError> {
+        let merge_request_iid = match env::var("CI_MERGE_REQUEST_IID") {
+            Ok(iid) if !iid.trim().is_empty() => Some(iid.trim().parse::<u64>().map_err(|_| {
+                ClientError::MalformedEventInfo(format!(
+                    "CI_MERGE_REQUEST_IID is not a number: {iid}"
+                ))
+            })?),
+            _ => None,
+        };
+        let api_url =
+            env::var("CI_API_V4_URL").map_err(|e| ClientError::env_var("CI_API_V4_URL", e))?;
+        // The trailing slash is needed for `Url::join()` to append paths.
+        let api_url = Url::parse(format!("{}/", api_url.trim().trim_end_matches('/')).as_str())?;
+
+        Ok(Self {
+            client: Self::make_client(&Timeouts::default())?,
+            custom_http_client: false,
+            response_cache: None,
+            request_recorder: None,
+            throttle: Arc::new(Throttle::default()),
+            metrics: None,
+            cancellation_token: None,
+            headers: Self::make_headers()?,
+            merge_request_iid,
+            event_name: env::var("CI_PIPELINE_SOURCE").unwrap_or(String::from("unknown")),
+            api_url,
+            project_id: env::var("CI_PROJECT_ID")
+                .map_err(|e| ClientError::env_var("CI_PROJECT_ID", e))?,
+            sha: env::var("CI_COMMIT_SHA").map_err(|e| ClientError::env_var("CI_COMMIT_SHA", e))?,
+            debug_enabled: env::var("CI_DEBUG_TRACE").is_ok_and(|val| &val == "true"),
+            rate_limit_headers: RestApiRateLimitHeaders {
+                reset: "ratelimit-reset".to_string(),
+                remaining: "ratelimit-remaining".to_string(),
+                retry: "retry-after".to_string(),
+            },
+            strict: false,
+            dry_run: false,
+            retry_policy: RetryPolicy::default(),
+            last_rate_limit: RateLimitSlot::default(),
+            user_agent: HeaderValue::from_static(USER_AGENT),
+            timeouts: Timeouts::default(),
+            review_outcome: ReviewOutcome::default(),
+        })
+    }
+
+    /// The ID of the Merge Request (within the project) that triggered the pipeline.
+    ///
+    /// Returns [`None`] if the pipeline was not triggered by a Merge Request.
+    pub fn merge_request_iid(&self) -> Option<u64> {
+        self.merge_request_iid
+    }
+
+    /// The SHA of the commit that triggered the pipeline.
+    pub fn sha(&self) -> &str {
+        &self.sha
+    }
+
+    /// Get the URL of the REST API endpoint for this project at the given `path`.
+    pub(super) fn project_endpoint(&self, path: &[&str]) -> Result<Url, ClientError> {
+        project_endpoint(&self.api_url, &self.project_id, path)
+    }
+
+    /// Get the files changed in the Merge Request (if any) or the commit that triggered the pipeline.
+    ///
+    /// The files are listed by the paginated `merge_requests/{iid}/diffs` endpoint
+    /// (or `repository/commits/{sha}/diff` endpoint).
+    /// Only the patches of files that satisfy the `file_filter` are parsed.
+    ///
+    /// If GitLab omits the patch of a qualified file (because it is too large), then
+    /// the Merge Request's raw diff is parsed instead.
+    /// If the raw diff is unavailable, such files are listed without any changed lines.
+    #[cfg(feature = "file-changes")]
+    pub(super) async fn get_changed_files(
+        &self,
+        file_filter: &FileFilter,
+        lines_changed_only: &LinesChangedOnly,
+    ) -> Result<HashMap<String, FileDiffLines>, ClientError> {
+        let mut files: HashMap<String, FileDiffLines> = HashMap::new();
+        let mut truncated = false;
+        let url = match self.merge_request_iid {
+            Some(iid) => {
+                self.project_endpoint(&["merge_requests", iid.to_string().as_str(), "diffs"])?
+            }
+            None => self.project_endpoint(&["repository", "commits", &self.sha, "diff"])?,
+        };
+        let mut pages: Paginator<_, Vec<ChangedFile>> = Paginator::new(
+            self,
+            &self.client,
+            url,
+            &self.rate_limit_headers,
+            "list of changed files",
+        );
+        while let Some(page) = pages.next_page().await {
+            for file in page? {
+                if !file_filter.is_qualified(Path::new(&file.new_path)) {
+                    continue;
+                }
+                if file.deleted_file {
+                    if file_filter.include_deleted {
+                        files.entry(file.new_path).or_insert(
+                            FileDiffLines::default().with_status(FileStatus::Removed, None),
+                        );
+                    }
+                    continue;
+                }
+                let (status, previous_path) = if file.new_file {
+                    (FileStatus::Added, None)
+                } else if file.renamed_file {
+                    (FileStatus::Renamed, Some(file.old_path.clone()))
+                } else {
+                    (FileStatus::Modified, None)
+                };
+                if file.too_large || file.collapsed {
+                    log::warn!("GitLab omitted the diff of {} (too large)", file.new_path);
+                    truncated = true;
+                    files
+                        .entry(file.new_path)
+                        .or_insert(FileDiffLines::default().with_status(status, previous_path));
+                } else if file.diff.is_empty() {
+                    // file may have been only renamed (or its mode changed).
+                    // include it in case files-changed-only is enabled.
+                    files
+                        .entry(file.new_path)
+                        .or_insert(FileDiffLines::default().with_status(status, previous_path));
+                } else {
+                    let diff = format!(
+                        "diff --git a/{old} b/{new}\n--- a/{old}\n+++ b/{new}\n{patch}\n",
+                        old = if file.renamed_file {
+                            &file.old_path
+                        } else {
+                            &file.new_path
+                        },
+                        new = file.new_path,
+                        patch = file.diff,
+                    );
+                    for (name, info) in parse_diff(&diff, file_filter, lines_changed_only)? {
+                        files
+                            .entry(name)
+                            .or_insert(info.with_status(status, previous_path.clone()));
+                    }
+                }
+            }
+        }
+        if truncated
+            && let Some(iid) = self.merge_request_iid
+            && let Some(raw_files) = self
+                .get_raw_mr_diff(iid, file_filter, lines_changed_only)
+                .await?
+        {
+            return Ok(raw_files);
+        }
+        Ok(files)
+    }
+
+    /// Parse the raw diff of the Merge Request with the given `iid`.
+    ///
+    /// Returns [`None`] if the server lacks the `merge_requests/{iid}/raw_diffs`
+    /// endpoint (added in GitLab v17.9).
+    #[cfg(feature = "file-changes")]
+    async fn get_raw_mr_diff(
+        &self,
+        iid: u64,
+        file_filter: &FileFilter,
+        lines_changed_only: &LinesChangedOnly,
+    ) -> Result<Option<HashMap<String, FileDiffLines>>, ClientError> {
+        let task = "get raw diff of merge request";
+        let url =
+            self.project_endpoint(&["merge_requests", iid.to_string().as_str(), "raw_diffs"])?;
+        let mut headers = HeaderMap::new();
+        headers.insert("Accept", HeaderValue::from_str("text/plain")?);
+        let request = self.make_api_request(&self.client, url, Method::GET, None, Some(headers))?;
+        let response = self
+            .send_api_request(&self.client, request, &self.rate_limit_headers)
+            .await
+            .map_err(|e| e.with_context(task))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            log::info!("Server cannot provide a merge request's raw diff");
+            return Ok(None);
+        }
+        let response = ClientError::check_response(response, task).await?;
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ClientError::Request(e).with_context(task))?;
+        Ok(Some(parse_diff(&body, file_filter, lines_changed_only)?))
+    }
+
+    /// Build the default HTTP client.
+    ///
+    /// Headers and request timeouts are applied by [`RestApiClient::make_api_request()`],
+    /// so they also apply to a client given to [`RestApiClient::set_http_client()`].
+    pub(super) fn make_client(timeouts: &Timeouts) -> Result<Client, ClientError> {
+        Ok(Client::builder()
+            .connect_timeout(timeouts.connect)
+            .build()?)
+    }
+
+    pub(super) fn make_headers() -> Result<HeaderMap<HeaderValue>, ClientError> {
+        let mut headers = HeaderMap::new();
+        headers.insert("Accept", HeaderValue::from_str("application/json")?);
+        match TOKEN_ENV_VARS
+            .iter()
+            .find_map(|(name, header)| env::var(name).ok().map(|token| (*name, *header, token)))
+        {
+            Some((name, header, token)) => {
+                log::debug!("Using {name} environment variable");
+                let mut val = HeaderValue::from_str(token.as_str())?;
+                val.set_sensitive(true);
+                headers.insert(HeaderName::from_static(header), val);
+            }
+            None => {
+                log::warn!(
+                    "No GITLAB_TOKEN or CI_JOB_TOKEN environment variable found! Permission to post comments may be unsatisfied."
+                );
+            }
+        }
+        Ok(headers)
+    }
+
+    /// Ensure that a token is available to authenticate requests that need permission.
+    pub(super) fn check_token() -> Result<(), ClientError> {
+        if TOKEN_ENV_VARS
+            .iter()
+            .any(|(name, _)| env::var_os(name).is_some())
+        {
+            return Ok(());
+        }
+        Err(ClientError::MissingEnvVars {
+            checked: TOKEN_ENV_VARS
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .collect(),
+        })
+    }
+
+    /// Append the given `vars` to the dotenv file referenced by [`DOTENV_ENV`].
+    ///
+    /// Dotenv files do not support multi-line values (see [`OutputVariable::fmt_dotenv_entry()`]).
+    pub(super) fn append_dotenv_file(&self, vars: &[OutputVariable]) -> Result<(), ClientError> {
+        if vars.is_empty() {
+            return Ok(());
+        }
+        let Some(path) = env_file(DOTENV_ENV, self.strict)? else {
+            return Ok(());
+        };
+        // Validate all variables before writing any of them.
+        let entries = vars
+            .iter()
+            .map(OutputVariable::fmt_dotenv_entry)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| ClientError::io(format!("open {DOTENV_ENV} file").as_str(), e))?;
+        for entry in entries {
+            writeln!(&mut file, "{entry}")
+                .map_err(|e| ClientError::io(format!("write to {DOTENV_ENV} file").as_str(), e))?;
+        }
+        Ok(())
+    }
+
+    /// Update existing comment or remove old comment(s) and post a new comment
+    pub async fn update_comment(
+        &self,
+        url: Url,
+        options: ThreadCommentOptions,
+    ) -> Result<(), ClientError> {
+        let is_lgtm = options.kind == CommentKind::Lgtm;
+        let comment_url = self
+            .remove_bot_comments(
+                &url,
+                &options.marker,
+                (options.policy == CommentPolicy::Anew) || (is_lgtm && options.no_lgtm),
+            )
+            .await?;
+        let payload = HashMap::from([("body", options.mark_comment())]);
+
+        if !is_lgtm || !options.no_lgtm {
+            let req_meth = if comment_url.is_some() {
+                Method::PUT
+            } else {
+                Method::POST
+            };
+            let request = self.make_api_request(
+                &self.client,
+                comment_url.unwrap_or(url),
+                req_meth,
+                Some(serde_json::json!(&payload).to_string()),
+                None,
+            )?;
+            let response = self
+                .send_api_request(&self.client, request, &self.rate_limit_headers)
+                .await
+                .map_err(|e| e.with_context("post thread comment"))?;
+            ClientError::check_response(response, "post thread comment").await?;
+        }
+        Ok(())
+    }
+
+    /// Remove Merge Request notes previously posted by this software.
+    ///
+    /// Returns the URL of the note to update (if any and not `delete`).
+    async fn remove_bot_comments(
+        &self,
+        url: &Url,
+        comment_marker: &str,
+        delete: bool,
+    ) -> Result<Option<Url>, ClientError> {
+        let mut comment_url = None;
+        let mut pages: Paginator<_, Vec<Note>> = Paginator::new(
+            self,
+            &self.client,
+            url.clone(),
+            &self.rate_limit_headers,
+            "list of existing thread comments",
+        );
+        while let Some(page) = pages.next_page().await {
+            let notes = match page {
+                Ok(notes) => notes,
+                // the paginator already logged the failed response
+                Err(ClientError::RequestContext { source, .. }) if source.is_status() => {
+                    return Ok(comment_url);
+                }
+                Err(e) => return Err(e),
+            };
+            for note in notes {
+                if note.system || !note.body.starts_with(comment_marker) {
+                    continue;
+                }
+                log::debug!(
+                    "Found bot comment id {} from user {} ({})",
+                    note.id,
+                    note.author.username,
+                    note.author.id,
+                );
+                let this_comment_url = {
+                    let mut note_url = url.clone();
+                    note_url
+                        .path_segments_mut()
+                        .map_err(|_| {
+                            ClientError::UrlParse(url::ParseError::RelativeUrlWithCannotBeABaseBase)
+                        })?
+                        .push(note.id.to_string().as_str());
+                    note_url
+                };
+                if delete || comment_url.is_some() {
+                    // if not updating: remove all outdated comments
+                    // if updating: remove all outdated comments except the last one
+
+                    // use last saved comment_url (if not None) or current comment url
+                    let del_url = comment_url.as_ref().unwrap_or(&this_comment_url).clone();
+                    let request =
+                        self.make_api_request(&self.client, del_url, Method::DELETE, None, None)?;
+                    let response = self
+                        .send_api_request(&self.client, request, &self.rate_limit_headers)
+                        .await
+                        .map_err(|e| e.with_context("delete old thread comment"))?;
+                    ClientError::check_response(response, "delete old thread comment").await?;
+                }
+                if !delete {
+                    comment_url = Some(this_comment_url);
+                }
+            }
+        }
+        Ok(comment_url)
+    }
+
+    /// Fetch the unresolved discussions on a Merge Request that were started by this software.
+    ///
+    /// A discussion is started by this software if its first note starts with the `marker`.
+    pub(super) async fn get_bot_discussions(
+        &self,
+        iid: u64,
+        marker: &str,
+    ) -> Result<Vec<Discussion>, ClientError> {
+        let mut discussions = Vec::new();
+        let url =
+            self.project_endpoint(&["merge_requests", iid.to_string().as_str(), "discussions"])?;
+        let mut pages: Paginator<_, Vec<Discussion>> = Paginator::new(
+            self,
+            &self.client,
+            url,
+            &self.rate_limit_headers,
+            "existing MR discussions",
+        );
+        while let Some(page) = pages.next_page().await {
+            let payload = match page {
+                Ok(payload) => payload,
+                // the paginator already logged the failed response
+                Err(ClientError::RequestContext { source, .. }) if source.is_status() => break,
+                Err(e) => return Err(e),
+            };
+            for discussion in payload {
+                let Some(note) = discussion.notes.first() else {
+                    continue;
+                };
+                if note.system
+                    || !note.resolvable
+                    || note.resolved
+                    || !note.body.starts_with(marker)
+                {
+                    continue;
+                }
+                log::debug!(
+                    "Found bot discussion id {} from user {} ({})",
+                    discussion.id,
+                    note.author.username,
+                    note.author.id,
+                );
+                discussions.push(discussion);
+            }
+        }
+        Ok(discussions)
+    }
+
+    /// Check if an existing note matches a proposed review comment.
+    ///
+    /// Matching is based on: path, line position, and comment body (with marker prepended).
+    pub(super) fn match_review_comment(
+        existing: &Note,
+        proposed: &ReviewComment,
+        marker: &str,
+    ) -> bool {
+        let Some(NotePosition {
+            new_path: Some(path),
+            new_line: Some(line),
+        }) = &existing.position
+        else {
+            return false;
+        };
+        proposed.is_posted_as(
+            &PostedReviewComment {
+                path,
+                line_start: None,
+                line_end: i64::from(*line),
+                // GitLab only positions a note on a single line
+                single_line: true,
+                body: &existing.body,
+                resolved: existing.resolved,
+            },
+            marker,
+        )
+    }
+
+    /// Resolve (or delete if `delete` is `true`) the given outdated `discussions`.
+    pub(super) async fn resolve_outdated_discussions(
+        &self,
+        iid: u64,
+        discussions: &[&Discussion],
+        delete: bool,
+    ) -> Result<(), ClientError> {
+        let iid = iid.to_string();
+        for discussion in discussions {
+            let mut path = vec![
+                "merge_requests",
+                iid.as_str(),
+                "discussions",
+                &discussion.id,
+            ];
+            if delete {
+                let Some(note) = discussion.notes.first() else {
+                    continue;
+                };
+                let note_id = note.id.to_string();
+                path.extend(["notes", note_id.as_str()]);
+                let url = self.project_endpoint(&path)?;
+                let request =
+                    self.make_api_request(&self.client, url, Method::DELETE, None, None)?;
+                let response = self
+                    .send_api_request(&self.client, request, &self.rate_limit_headers)
+                    .await
+                    .map_err(|e| e.with_context("delete outdated review comment"))?;
+                ClientError::check_response(response, "delete outdated review comment").await?;
+            } else {
+                let url = Url::parse_with_params(
+                    self.project_endpoint(&path)?.as_str(),
+                    &[("resolved", "true")],
+                )?;
+                let request = self.make_api_request(&self.client, url, Method::PUT, None, None)?;
+                let response = self
+                    .send_api_request(&self.client, request, &self.rate_limit_headers)
+                    .await
+                    .map_err(|e| e.with_context("resolve outdated review comment"))?;
+                self.log_response(response, "Failed to resolve outdated review comment")
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Start a discussion on the Merge Request.
+    ///
+    /// Returns [`None`] if GitLab rejected the `payload` (with a `400 Bad Request` response).
+    /// In a dry run, the posted discussion is also [`None`].
+    pub(super) async fn post_discussion(
+        &self,
+        iid: u64,
+        payload: &NewDiscussion,
+        task: &str,
+    ) -> Result<Option<Discussion>, ClientError> {
+        let url =
+            self.project_endpoint(&["merge_requests", iid.to_string().as_str(), "discussions"])?;
+        let request = self.make_api_request(
+            &self.client,
+            url,
+            Method::POST,
+            Some(
+                serde_json::to_string(payload)
+                    .map_err(|e| ClientError::json("serialize MR discussion payload", e))?,
+            ),
+            None,
+        )?;
+        let response = self
+            .send_api_request(&self.client, request, &self.rate_limit_headers)
+            .await
+            .map_err(|e| e.with_context(task))?;
+        if response.status() == StatusCode::BAD_REQUEST {
+            self.log_response(response, format!("Failed to {task}").as_str())
+                .await;
+            return Ok(None);
+        }
+        let response = ClientError::check_response(response, task).await?;
+        // a dry run does not actually create a discussion
+        if self.dry_run {
+            return Ok(None);
+        }
+        let discussion = serde_json::from_str::<Discussion>(
+            response
+                .text()
+                .await
+                .map_err(|e| ClientError::Request(e).with_context(task))?
+                .as_str(),
+        )
+        .map_err(|e| ClientError::json("deserialize posted MR discussion", e))?;
+        Ok(Some(discussion))
+    }
+
+    /// Approve the Merge Request or revoke this software's approval, as per the review's `action`.
+    ///
+    /// If the token is not permitted to do so, then the review is downgraded to
+    /// (only) comments and a warning is logged.
+    pub(super) async fn set_approval(
+        &self,
+        iid: u64,
+        action: &ReviewAction,
+    ) -> Result<(), ClientError> {
+        let (endpoint, task) = match action {
+            ReviewAction::Comment => return Ok(()),
+            ReviewAction::Approve => ("approve", "approve the merge request"),
+            ReviewAction::RequestChanges => ("unapprove", "revoke approval of the merge request"),
+        };
+        let url = self.project_endpoint(&["merge_requests", iid.to_string().as_str(), endpoint])?;
+        let request = self.make_api_request(&self.client, url, Method::POST, None, None)?;
+        let response = self
+            .send_api_request(&self.client, request, &self.rate_limit_headers)
+            .await
+            .map_err(|e| e.with_context(task))?;
+        let status = response.status();
+        if *action == ReviewAction::RequestChanges && status == StatusCode::NOT_FOUND {
+            log::debug!("The merge request was not approved, so there is no approval to revoke");
+            return Ok(());
+        }
+        if status.is_client_error() {
+            log::warn!("Failed to {task} ({status}); the review was downgraded to only comments");
+            return Ok(());
+        }
+        ClientError::check_response(response, task).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use reqwest::Url;
+
+    use super::{project_endpoint, section_name};
+
+    #[test]
+    fn project_endpoint_segments() {
+        for api_url in ["https://gitlab.com/api/v4", "https://gitlab.com/api/v4/"] {
+            let api_url = Url::parse(api_url).unwrap();
+            let url = project_endpoint(&api_url, "42", &["merge_requests", "7", "notes"]).unwrap();
+            assert_eq!(
+                url.as_str(),
+                "https://gitlab.com/api/v4/projects/42/merge_requests/7/notes"
+            );
+        }
+        // a project's path (instead of its ID) is percent-encoded as a single segment
+        let api_url = Url::parse("https://gitlab.com/api/v4/").unwrap();
+        let url = project_endpoint(&api_url, "group/project", &[]).unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://gitlab.com/api/v4/projects/group%2Fproject"
+        );
+    }
+
+    #[test]
+    fn sanitize_section_name() {
+        assert_eq!(section_name("posting comment"), "posting_comment");
+        assert_eq!(section_name("lint.v2-ok_"), "lint.v2-ok_");
+    }
+}
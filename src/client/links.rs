@@ -0,0 +1,186 @@
+//! A module to parse the `link` header of a paginated REST API response.
+use std::{iter::Peekable, str::CharIndices};
+
+use reqwest::{Url, header::HeaderMap};
+
+/// The pagination links found in the `link` header(s) of a response.
+///
+/// The header is parsed per [RFC 8288](https://www.rfc-editor.org/rfc/rfc8288#section-3).
+/// Relative links are resolved against the URL of the request.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PageLinks {
+    /// The link with `rel="next"`.
+    pub next: Option<Url>,
+    /// The link with `rel="prev"` (or `rel="previous"`).
+    pub prev: Option<Url>,
+    /// The link with `rel="first"`.
+    pub first: Option<Url>,
+    /// The link with `rel="last"`.
+    pub last: Option<Url>,
+}
+
+impl PageLinks {
+    /// Parse the `link` header(s) in the given `headers`.
+    ///
+    /// The `base` URL (typically the URL of the request) is used to resolve relative links.
+    pub fn from_headers(headers: &HeaderMap, base: &Url) -> Self {
+        Self::parse_headers(headers, Some(base))
+    }
+
+    /// Parse the `link` header(s) in the given `headers`, ignoring relative links if no `base` is given.
+    pub(crate) fn parse_headers(headers: &HeaderMap, base: Option<&Url>) -> Self {
+        let mut links = Self::default();
+        for value in headers.get_all("link") {
+            match value.to_str() {
+                Ok(value) => links.parse(value, base),
+                Err(_) => log::debug!("Response header link for pagination is not valid text"),
+            }
+        }
+        links
+    }
+
+    /// The page number of the [`PageLinks::last`] link, if any.
+    ///
+    /// This is useful to report progress (eg. "page 3 of 12").
+    pub fn last_page(&self) -> Option<u32> {
+        self.last.as_ref().and_then(page_number)
+    }
+
+    /// Parse a `link` header's `value` into `self`.
+    ///
+    /// Links already found (eg. in a previous `link` header) take precedence.
+    fn parse(&mut self, value: &str, base: Option<&Url>) {
+        let mut parser = LinkParser {
+            value,
+            chars: value.char_indices().peekable(),
+        };
+        while let Some(link) = parser.next_link() {
+            let Some((uri, rel)) = link else {
+                log::debug!("Response header link for pagination is malformed");
+                continue;
+            };
+            let Some(rel) = rel else {
+                continue;
+            };
+            let url = match base {
+                Some(base) => base.join(uri),
+                None => Url::parse(uri),
+            };
+            let Ok(url) = url else {
+                log::debug!("Failed to parse {rel:?} page link from response header");
+                continue;
+            };
+            for relation in rel.split_ascii_whitespace() {
+                let slot = match relation.to_ascii_lowercase().as_str() {
+                    "next" => &mut self.next,
+                    "prev" | "previous" => &mut self.prev,
+                    "first" => &mut self.first,
+                    "last" => &mut self.last,
+                    _ => continue,
+                };
+                if slot.is_none() {
+                    *slot = Some(url.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Get the value of the `page` query parameter in the given `url`.
+pub(crate) fn page_number(url: &Url) -> Option<u32> {
+    url.query_pairs()
+        .find(|(name, _)| name == "page")
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+/// A tokenizer for the comma-separated link values in a `link` header.
+struct LinkParser<'a> {
+    value: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> LinkParser<'a> {
+    /// Get the next link value.
+    ///
+    /// Returns [`None`] at the end of the header.
+    /// Yields `Some(None)` for a malformed link value (which is skipped).
+    /// Otherwise, yields the link's URI-reference and its first `rel` parameter (if any).
+    fn next_link(&mut self) -> Option<Option<(&'a str, Option<String>)>> {
+        self.skip_while(|c| c == ',' || c.is_whitespace());
+        self.chars.peek()?;
+        if self.chars.next_if(|(_, c)| *c == '<').is_none() {
+            self.skip_link();
+            return Some(None);
+        }
+        let uri = self.take_while(|c| c != '>');
+        if self.chars.next().is_none() {
+            return Some(None);
+        }
+        let mut rel = None;
+        loop {
+            self.skip_while(char::is_whitespace);
+            match self.chars.next() {
+                None | Some((_, ',')) => break,
+                Some((_, ';')) => {}
+                Some(_) => {
+                    self.skip_link();
+                    return Some(None);
+                }
+            }
+            self.skip_while(char::is_whitespace);
+            let name = self.take_while(|c| !matches!(c, '=' | ';' | ',') && !c.is_whitespace());
+            self.skip_while(char::is_whitespace);
+            let value = if self.chars.next_if(|(_, c)| *c == '=').is_some() {
+                self.skip_while(char::is_whitespace);
+                Some(self.param_value())
+            } else {
+                None
+            };
+            // per RFC 8288, any `rel` parameters after the first are ignored
+            if rel.is_none() && name.eq_ignore_ascii_case("rel") {
+                rel = value;
+            }
+        }
+        Some(Some((uri, rel)))
+    }
+
+    /// Get a parameter's value, which may be a token or a (double or single) quoted string.
+    fn param_value(&mut self) -> String {
+        let Some((_, quote)) = self.chars.next_if(|(_, c)| *c == '"' || *c == '\'') else {
+            return self
+                .take_while(|c| c != ';' && c != ',' && !c.is_whitespace())
+                .to_string();
+        };
+        let mut value = String::new();
+        while let Some((_, c)) = self.chars.next() {
+            match c {
+                '\\' if quote == '"' => value.extend(self.chars.next().map(|(_, c)| c)),
+                c if c == quote => break,
+                c => value.push(c),
+            }
+        }
+        value
+    }
+
+    /// Skip the rest of a malformed link value.
+    fn skip_link(&mut self) {
+        self.skip_while(|c| c != ',');
+    }
+
+    fn skip_while(&mut self, predicate: impl Fn(char) -> bool) {
+        while self.chars.next_if(|(_, c)| predicate(*c)).is_some() {}
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self.position();
+        self.skip_while(predicate);
+        &self.value[start..self.position()]
+    }
+
+    fn position(&mut self) -> usize {
+        self.chars
+            .peek()
+            .map(|(index, _)| *index)
+            .unwrap_or(self.value.len())
+    }
+}
@@ -1,5 +1,14 @@
-use super::RestApiClient;
-use crate::{OutputVariable, RestClientError as ClientError, ReviewOptions, ThreadCommentOptions};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use super::{Capabilities, RestApiClient, log_unsupported};
+use crate::{
+    AnnotationOverflow, CommentKind, FileAnnotation, OutputVariable,
+    RestClientError as ClientError, ReviewOptions, ReviewOutcome, ThreadCommentOptions,
+};
 
 #[cfg(feature = "file-changes")]
 use crate::{FileDiffLines, FileFilter, LinesChangedOnly, parse_diff};
@@ -9,15 +18,16 @@ use std::{collections::HashMap, process::Command};
 /// A (mostly) non-operational implementation of [`RestApiClient`].
 ///
 /// This is primarily meant for use in local contexts (or in unsupported CI
-/// platforms/contexts) because the following methods silently do nothing:
+/// platforms/contexts) because the following methods do nothing
+/// (see [`RestApiClient::capabilities()`]):
 ///
-/// - [`Self::post_thread_comment`]
-/// - [`Self::cull_pr_reviews`]
-/// - [`Self::post_pr_review`]
-/// - [`Self::set_user_agent`]
+/// - [`RestApiClient::post_thread_comment()`]
+/// - [`RestApiClient::cull_pr_reviews()`]
+/// - [`RestApiClient::post_pr_review()`]
+/// - [`RestApiClient::set_user_agent()`]
 ///
-/// However, [`Self::get_list_of_changed_files`] does use the git CLI
-/// to get a list of changed files.
+/// However, [`RestApiClient::get_list_of_changed_files()`] does use the git CLI
+/// to get a list of changed files (see [`LocalClient::set_diff_scope()`]).
 ///
 /// Instantiate with [`Default::default()`].
 /// ```rust
@@ -25,8 +35,196 @@ use std::{collections::HashMap, process::Command};
 ///
 /// let client = LocalClient::default();
 /// ```
+///
+/// # Rendering feedback to local files
+///
+/// A client created with [`LocalClient::with_feedback_dir()`] renders feedback
+/// for a developer to read instead:
+///
+/// | feedback | destination |
+/// |----------|-------------|
+/// | [`RestApiClient::post_thread_comment()`] | `comment.md` in the feedback directory |
+/// | [`RestApiClient::post_pr_review()`] | `review.md` in the feedback directory |
+/// | [`RestApiClient::write_file_annotations()`] | stderr, formatted as `path:line:column: level: message` |
+/// | [`RestApiClient::write_output_variables()`] | `outputs.env` in the feedback directory |
+///
+/// The feedback directory is created if it does not exist.
+/// [`from_env()`](crate::client::from_env) falls back to such a client (instead of failing)
+/// if no CI platform is detected and the `GIT_BOT_FEEDBACK_DIR` environment variable is set.
+///
+/// ```no_run
+/// use git_bot_feedback::{RestApiClient, ThreadCommentOptions, client::LocalClient};
+///
+/// # async fn example() -> Result<(), git_bot_feedback::RestClientError> {
+/// let client = LocalClient::with_feedback_dir("feedback");
+/// client
+///     .post_thread_comment(ThreadCommentOptions {
+///         comment: "Some concerns".to_string(),
+///         ..Default::default()
+///     })
+///     .await?;
+/// // see feedback/comment.md
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
-pub struct LocalClient;
+pub struct LocalClient {
+    /// See [`LocalClient::with_feedback_dir()`].
+    feedback_dir: Option<PathBuf>,
+
+    /// See [`LocalClient::set_diff_scope()`].
+    #[cfg(feature = "file-changes")]
+    diff_scope: Option<DiffScope>,
+
+    /// See [`LocalClient::set_max_deepen()`].
+    #[cfg(feature = "file-changes")]
+    max_deepen: u32,
+}
+
+/// The changes listed by [`LocalClient`]'s
+/// [`RestApiClient::get_list_of_changed_files()`] (see [`LocalClient::set_diff_scope()`]).
+#[cfg(feature = "file-changes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffScope {
+    /// The uncommitted changes (staged or not) in the working tree, compared to `HEAD`.
+    WorkingTree,
+
+    /// Like [`Self::WorkingTree`], but untracked files (that are not ignored by git)
+    /// are also listed as added files.
+    WorkingTreeWithUntracked,
+
+    /// Only the staged changes (like `git diff --cached`), compared to `HEAD`.
+    Staged,
+
+    /// The changes in the working tree compared to the given reference (eg. `HEAD~1`).
+    ///
+    /// A reference that is a decimal number is used as the number of parents from `HEAD`.
+    AgainstRef(String),
+
+    /// The changes committed since `HEAD` diverged from the given `base` (eg. `origin/main`).
+    ///
+    /// This is like `git diff base...HEAD`.
+    MergeBase {
+        /// The reference whose merge-base with `HEAD` is compared to `HEAD`.
+        base: String,
+    },
+}
+
+impl LocalClient {
+    /// Create a client that renders feedback to files in the given directory.
+    #[cfg_attr(not(feature = "file-changes"), allow(clippy::needless_update))]
+    pub fn with_feedback_dir(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            feedback_dir: Some(dir.into()),
+            ..Default::default()
+        }
+    }
+
+    /// The changes listed by [`RestApiClient::get_list_of_changed_files()`].
+    ///
+    /// Returns [`None`] if the changes are decided by the `base_diff` and
+    /// `ignore_index` arguments (the default).
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    pub fn diff_scope(&self) -> Option<&DiffScope> {
+        self.diff_scope.as_ref()
+    }
+
+    /// Set the changes listed by [`RestApiClient::get_list_of_changed_files()`].
+    ///
+    /// If a `scope` is set, then the `base_diff` and `ignore_index` arguments are ignored.
+    /// A reference in the `scope` that does not exist yields a
+    /// [`ClientError::GitRefNotFound`] error.
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    pub fn set_diff_scope(&mut self, scope: Option<DiffScope>) {
+        self.diff_scope = scope;
+    }
+
+    /// The most commits fetched to deepen a shallow clone (see [`Self::set_max_deepen()`]).
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    pub fn max_deepen(&self) -> u32 {
+        self.max_deepen
+    }
+
+    /// Set the most commits fetched to deepen a shallow clone (eg. a CI checkout with
+    /// `--depth=1`) when the history needed by the [`Self::diff_scope()`] is missing.
+    ///
+    /// The clone is deepened with `git fetch --deepen`, doubling the fetched history each
+    /// time until the needed history is found or `max` commits were fetched.
+    /// Defaults to `0`, so a [`ClientError::ShallowClone`] error is returned instead.
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    pub fn set_max_deepen(&mut self, max: u32) {
+        self.max_deepen = max;
+    }
+
+    /// The directory that feedback is rendered to.
+    ///
+    /// Returns [`None`] if feedback is not rendered (the default).
+    pub fn feedback_dir(&self) -> Option<&Path> {
+        self.feedback_dir.as_deref()
+    }
+
+    /// Get the path to the file `name` in the feedback `dir` (which is created if needed).
+    fn feedback_file(dir: &Path, name: &str) -> Result<PathBuf, ClientError> {
+        fs::create_dir_all(dir).map_err(|e| {
+            ClientError::io(
+                format!("create feedback directory {}", dir.display()).as_str(),
+                e,
+            )
+        })?;
+        Ok(dir.join(name))
+    }
+
+    /// Write the given `content` to the file `name` in the feedback `dir`.
+    fn write_feedback(dir: &Path, name: &str, content: &str) -> Result<(), ClientError> {
+        let path = Self::feedback_file(dir, name)?;
+        fs::write(&path, content)
+            .map_err(|e| ClientError::io(format!("write {}", path.display()).as_str(), e))?;
+        log::info!("Wrote feedback to {}", path.display());
+        Ok(())
+    }
+}
+
+/// Format the given `annotation` as `path:line:column: level: message` (for a human to read).
+fn fmt_annotation(annotation: &FileAnnotation) -> String {
+    let mut location = annotation.path.clone();
+    if !location.is_empty()
+        && let Some(line) = annotation.start_line
+    {
+        location.push_str(format!(":{line}").as_str());
+        if let Some(column) = annotation.start_column {
+            location.push_str(format!(":{column}").as_str());
+        }
+    }
+    let title = annotation
+        .title
+        .as_ref()
+        .map(|t| format!("{t}: "))
+        .unwrap_or_default();
+    let message = format!("{}: {title}{}", annotation.severity, annotation.message);
+    if location.is_empty() {
+        message
+    } else {
+        format!("{location}: {message}")
+    }
+}
+
+/// Format the given review `options` as a markdown document.
+fn fmt_review(options: &ReviewOptions) -> String {
+    let mut review = format!("# Review: {}\n\n{}\n", options.action, options.summary);
+    for comment in &options.comments {
+        let lines = match comment.line_start {
+            Some(start) if start != comment.line_end => format!("{start}-{}", comment.line_end),
+            _ => comment.line_end.to_string(),
+        };
+        review.push_str(format!("\n## {}:{lines}\n\n{}\n", comment.path, comment.comment).as_str());
+    }
+    review
+}
 
 /// Helper function to resolve a git reference to a commit hash using `git rev-parse`.
 #[cfg(feature = "file-changes")]
@@ -54,6 +252,133 @@ fn git_rev_parse(base: &str) -> Result<String, ClientError> {
     }
 }
 
+/// Resolve a git `reference` of a [`DiffScope`] to a commit hash.
+///
+/// Fails with a [`ClientError::GitRefNotFound`] error if the `reference` does not exist.
+#[cfg(feature = "file-changes")]
+fn resolve_scope_ref(reference: &str) -> Result<String, ClientError> {
+    git_rev_parse(reference).map_err(|e| match e {
+        ClientError::GitCommand(_) => ClientError::GitRefNotFound(reference.to_string()),
+        _ => e,
+    })
+}
+
+/// Get the arguments of `git diff` that compare the changes described by the given `scope`.
+///
+/// If the history needed by the `scope` is missing from a shallow clone, then the clone is
+/// deepened by at most `max_deepen` commits (see [`LocalClient::set_max_deepen()`]).
+#[cfg(feature = "file-changes")]
+fn scope_diff_args(scope: &DiffScope, max_deepen: u32) -> Result<Vec<String>, ClientError> {
+    let mut deepened = 0;
+    loop {
+        match resolve_scope(scope) {
+            Err(ClientError::GitCommand(_) | ClientError::GitRefNotFound(_))
+                if is_shallow_repo()? =>
+            {
+                let needed_ref = match scope {
+                    DiffScope::WorkingTree
+                    | DiffScope::WorkingTreeWithUntracked
+                    | DiffScope::Staged => "HEAD",
+                    DiffScope::AgainstRef(reference) => reference,
+                    DiffScope::MergeBase { base } => base,
+                };
+                if deepened >= max_deepen {
+                    return Err(ClientError::ShallowClone {
+                        missing_ref: needed_ref.to_string(),
+                    });
+                }
+                // double the fetched history each time (within the bounds)
+                let depth = deepened.max(1).min(max_deepen - deepened);
+                log::info!("Deepening the shallow clone by {depth} commits to find {needed_ref}");
+                git_output(&["fetch", format!("--deepen={depth}").as_str()])?;
+                deepened += depth;
+            }
+            diff_args => return diff_args,
+        }
+    }
+}
+
+/// Resolve the references of the given `scope` to the arguments of `git diff`.
+#[cfg(feature = "file-changes")]
+fn resolve_scope(scope: &DiffScope) -> Result<Vec<String>, ClientError> {
+    match scope {
+        DiffScope::WorkingTree | DiffScope::WorkingTreeWithUntracked => {
+            Ok(vec!["HEAD".to_string()])
+        }
+        DiffScope::Staged => Ok(vec!["--cached".to_string(), "HEAD".to_string()]),
+        DiffScope::AgainstRef(reference) => Ok(vec![resolve_scope_ref(reference)?]),
+        DiffScope::MergeBase { base } => {
+            let resolved_base = resolve_scope_ref(base)?;
+            let merge_base = git_output(&["merge-base", resolved_base.as_str(), "HEAD"])?;
+            Ok(vec![merge_base.trim().to_string(), "HEAD".to_string()])
+        }
+    }
+}
+
+/// Get a diff that adds the untracked files (that are not ignored by git).
+///
+/// Only files that satisfy the `file_filter` are included.
+#[cfg(feature = "file-changes")]
+fn untracked_diff(file_filter: &FileFilter) -> Result<String, ClientError> {
+    let untracked = git_output(&["ls-files", "--others", "--exclude-standard", "-z"])?;
+    let mut diff = String::new();
+    for file_name in untracked.split('\0').filter(|f| !f.is_empty()) {
+        if !file_filter.is_qualified(Path::new(file_name)) {
+            continue;
+        }
+        let args = [
+            "--no-pager",
+            "diff",
+            "--no-index",
+            "--",
+            "/dev/null",
+            file_name,
+        ];
+        match Command::new("git").args(args).output() {
+            Err(e) => {
+                return Err(ClientError::Io {
+                    task: format!("invoke `git {}`", args.join(" ")),
+                    source: e,
+                });
+            }
+            // `git diff --no-index` exits with 1 if there are differences
+            Ok(output) if matches!(output.status.code(), Some(0 | 1)) => {
+                diff.push_str(String::from_utf8_lossy(&output.stdout).as_ref());
+            }
+            Ok(output) => {
+                let err_msg = String::from_utf8_lossy(&output.stderr).to_string();
+                return Err(ClientError::GitCommand(err_msg));
+            }
+        }
+    }
+    Ok(diff)
+}
+
+/// Is the current git repository a shallow clone?
+#[cfg(feature = "file-changes")]
+fn is_shallow_repo() -> Result<bool, ClientError> {
+    Ok(git_output(&["rev-parse", "--is-shallow-repository"])?.trim() == "true")
+}
+
+/// Run `git` with the given `args` and get its stdout.
+#[cfg(feature = "file-changes")]
+fn git_output(args: &[&str]) -> Result<String, ClientError> {
+    match Command::new("git").args(args).output() {
+        Err(e) => Err(ClientError::Io {
+            task: format!("invoke `git {}`", args.join(" ")),
+            source: e,
+        }),
+        Ok(output) => {
+            if output.status.success() {
+                Ok(String::from_utf8_lossy(&output.stdout).to_string())
+            } else {
+                let err_msg = String::from_utf8_lossy(&output.stderr).to_string();
+                Err(ClientError::GitCommand(err_msg))
+            }
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl RestApiClient for LocalClient {
     fn client_kind(&self) -> String {
@@ -68,6 +393,21 @@ impl RestApiClient for LocalClient {
         base_diff: Option<String>,
         ignore_index: bool,
     ) -> Result<HashMap<String, FileDiffLines>, ClientError> {
+        if let Some(scope) = &self.diff_scope {
+            let diff_args = scope_diff_args(scope, self.max_deepen)?;
+            let mut args = vec!["--no-pager", "diff"];
+            args.extend(diff_args.iter().map(String::as_str));
+            log::debug!("Getting diff with `git {}`", args.join(" "));
+            let mut diff_str = git_output(&args)?;
+            if *scope == DiffScope::WorkingTreeWithUntracked {
+                diff_str.push_str(untracked_diff(file_filter)?.as_str());
+            }
+            return Ok(parse_diff(
+                diff_str.trim(),
+                file_filter,
+                lines_changed_only,
+            )?);
+        }
         let git_status = if ignore_index {
             0
         } else {
@@ -155,22 +495,139 @@ impl RestApiClient for LocalClient {
         Ok(())
     }
 
-    async fn post_thread_comment(&self, _options: ThreadCommentOptions) -> Result<(), ClientError> {
-        Ok(())
+    fn capabilities(&self) -> Capabilities {
+        let rendered = self.feedback_dir.is_some();
+        Capabilities {
+            thread_comments_on_push: rendered,
+            pr_reviews: rendered,
+            file_annotations: rendered,
+            output_variables: rendered,
+            ..Default::default()
+        }
+    }
+
+    async fn post_thread_comment(&self, options: ThreadCommentOptions) -> Result<(), ClientError> {
+        let Some(dir) = &self.feedback_dir else {
+            log_unsupported(self, "posting a thread comment");
+            return Ok(());
+        };
+        if options.kind == CommentKind::Lgtm && options.no_lgtm {
+            // like a git server, remove the outdated comment instead of posting a new one
+            let path = dir.join("comment.md");
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| {
+                    ClientError::io(format!("remove {}", path.display()).as_str(), e)
+                })?;
+            }
+            return Ok(());
+        }
+        Self::write_feedback(dir, "comment.md", &options.comment)
     }
 
     async fn cull_pr_reviews(&mut self, _options: &mut ReviewOptions) -> Result<(), ClientError> {
         Ok(())
     }
 
-    async fn post_pr_review(&mut self, _options: &ReviewOptions) -> Result<(), ClientError> {
-        Ok(())
+    async fn post_pr_review(
+        &mut self,
+        options: &ReviewOptions,
+    ) -> Result<ReviewOutcome, ClientError> {
+        let Some(dir) = &self.feedback_dir else {
+            log_unsupported(self, "posting a PR review");
+            return Ok(ReviewOutcome::default());
+        };
+        Self::write_feedback(dir, "review.md", &fmt_review(options))?;
+        Ok(ReviewOutcome {
+            posted_comments: options.comments.len(),
+            ..Default::default()
+        })
     }
 
     fn write_output_variables(&self, vars: &[OutputVariable]) -> Result<(), ClientError> {
-        for var in vars {
-            log::info!("{}: {}", var.name, var.value);
+        let Some(dir) = &self.feedback_dir else {
+            for var in vars {
+                var.validate()?;
+                log::info!("{}: {}", var.name, var.value);
+            }
+            return Ok(());
+        };
+        // Validate all variables before writing any of them.
+        let entries = vars
+            .iter()
+            .map(|var| var.fmt_output_entry())
+            .collect::<Result<Vec<_>, _>>()?;
+        let path = Self::feedback_file(dir, "outputs.env")?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| ClientError::io(format!("open {}", path.display()).as_str(), e))?;
+        for entry in entries {
+            writeln!(&mut file, "{entry}")
+                .map_err(|e| ClientError::io(format!("write to {}", path.display()).as_str(), e))?;
         }
         Ok(())
     }
+
+    fn write_file_annotations(
+        &self,
+        annotations: &[FileAnnotation],
+        _overflow: AnnotationOverflow,
+    ) -> Result<usize, ClientError> {
+        if self.feedback_dir.is_none() {
+            for annotation in annotations {
+                log::info!("{annotation:#?}");
+            }
+            return Ok(annotations.len());
+        }
+        let mut stderr = std::io::stderr().lock();
+        for annotation in annotations {
+            writeln!(stderr, "{}", fmt_annotation(annotation))
+                .map_err(|e| ClientError::io("write file annotation to stderr", e))?;
+        }
+        Ok(annotations.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LocalClient, fmt_annotation};
+    use crate::{AnnotationLevel, FileAnnotation, RestApiClient};
+
+    #[test]
+    fn annotation_format() {
+        let mut annotation = FileAnnotation {
+            severity: AnnotationLevel::Warning,
+            path: "src/lib.rs".to_string(),
+            start_line: Some(4),
+            start_column: Some(2),
+            message: "Unused import".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            fmt_annotation(&annotation),
+            "src/lib.rs:4:2: warning: Unused import"
+        );
+        annotation.title = Some("clippy".to_string());
+        annotation.start_column = None;
+        assert_eq!(
+            fmt_annotation(&annotation),
+            "src/lib.rs:4: warning: clippy: Unused import"
+        );
+        annotation.path.clear();
+        assert_eq!(
+            fmt_annotation(&annotation),
+            "warning: clippy: Unused import"
+        );
+    }
+
+    #[test]
+    fn feedback_dir_capabilities() {
+        let capabilities = LocalClient::with_feedback_dir("feedback").capabilities();
+        assert!(capabilities.thread_comments_on_push);
+        assert!(capabilities.pr_reviews);
+        assert!(capabilities.file_annotations);
+        assert!(capabilities.output_variables);
+        assert!(!capabilities.step_summary);
+    }
 }
@@ -0,0 +1,119 @@
+//! A module to observe the REST API requests sent by a client.
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use reqwest::{Method, StatusCode, Url};
+
+use super::lock_ignoring_poison;
+
+/// Hooks invoked by [`RestApiClient::send_api_request()`](crate::RestApiClient::send_api_request)
+/// to observe the requests it sends.
+///
+/// All methods do nothing by default, so an implementation only needs to override the hooks
+/// it is interested in (eg. to export Prometheus counters).
+/// Use [`RestApiClient::set_metrics()`](crate::RestApiClient::set_metrics) to register an
+/// implementation with a client. When no implementation is registered, no hooks are invoked.
+///
+/// See [`CountingMetrics`] for a simple implementation.
+pub trait Metrics: Debug + Send + Sync {
+    /// Called before each attempt to send a request.
+    fn on_request_start(&self, _method: &Method, _url: &Url) {}
+
+    /// Called after each attempt to send a request.
+    ///
+    /// The `status` is [`None`] if no response was received (eg. the connection failed).
+    fn on_request_complete(
+        &self,
+        _method: &Method,
+        _status: Option<StatusCode>,
+        _elapsed: Duration,
+    ) {
+    }
+
+    /// Called before a request is retried.
+    ///
+    /// The `attempt` is the number of the upcoming attempt (starting at `2` for the first retry).
+    fn on_retry(&self, _method: &Method, _attempt: u8) {}
+
+    /// Called before waiting for a rate limit to reset or a server's requested backoff.
+    fn on_rate_limit_sleep(&self, _duration: Duration) {}
+}
+
+/// A [`Metrics`] implementation that counts requests, retries and time slept for rate limits.
+///
+/// ```
+/// use std::sync::Arc;
+/// use git_bot_feedback::{RestApiClient, client::{CountingMetrics, init_client}};
+///
+/// # fn example() -> Result<(), git_bot_feedback::RestClientError> {
+/// let metrics = Arc::new(CountingMetrics::default());
+/// let mut client = init_client()?;
+/// client.set_metrics(Some(metrics.clone()));
+/// // ... send some requests ...
+/// log::info!("Sent {} requests ({} retries)", metrics.requests(), metrics.retries());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct CountingMetrics {
+    requests: AtomicU64,
+    retries: AtomicU64,
+    rate_limit_sleep_millis: AtomicU64,
+    completed: Mutex<BTreeMap<(String, Option<u16>), u64>>,
+}
+
+impl CountingMetrics {
+    /// The number of attempts made to send a request (including retries).
+    pub fn requests(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// The number of retries performed.
+    pub fn retries(&self) -> u64 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    /// The total time slept for rate limits.
+    pub fn rate_limit_sleep(&self) -> Duration {
+        Duration::from_millis(self.rate_limit_sleep_millis.load(Ordering::Relaxed))
+    }
+
+    /// The number of attempts with the given `method` that completed with the given `status`.
+    ///
+    /// Pass [`None`] as the `status` to count attempts that received no response.
+    pub fn completed(&self, method: &Method, status: Option<StatusCode>) -> u64 {
+        lock_ignoring_poison(&self.completed)
+            .get(&(method.to_string(), status.map(|s| s.as_u16())))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl Metrics for CountingMetrics {
+    fn on_request_start(&self, _method: &Method, _url: &Url) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_request_complete(&self, method: &Method, status: Option<StatusCode>, _elapsed: Duration) {
+        *lock_ignoring_poison(&self.completed)
+            .entry((method.to_string(), status.map(|s| s.as_u16())))
+            .or_default() += 1;
+    }
+
+    fn on_retry(&self, _method: &Method, _attempt: u8) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_rate_limit_sleep(&self, duration: Duration) {
+        let millis = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+        self.rate_limit_sleep_millis
+            .fetch_add(millis, Ordering::Relaxed);
+    }
+}
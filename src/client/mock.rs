@@ -0,0 +1,307 @@
+//! A module to test feedback logic without a git server.
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use super::{Capabilities, RestApiClient, lock_ignoring_poison};
+use crate::{
+    AnnotationOverflow, CommitStatus, FileAnnotation, OutputVariable,
+    RestClientError as ClientError, ReviewOptions, ReviewOutcome, ThreadCommentOptions,
+    summary::SummaryOverflow,
+};
+
+#[cfg(feature = "file-changes")]
+use crate::{FileDiffLines, FileFilter, LinesChangedOnly};
+#[cfg(feature = "file-changes")]
+use std::collections::HashMap;
+
+/// The arguments given to a [`MockClient`]'s
+/// [`RestApiClient::get_list_of_changed_files()`].
+#[cfg(feature = "file-changes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+#[derive(Debug, Clone)]
+pub struct ChangedFilesRequest {
+    /// The filter used to include or exclude files.
+    pub file_filter: FileFilter,
+
+    /// Which lines of the changed files were requested.
+    pub lines_changed_only: LinesChangedOnly,
+
+    /// The base of the diff (if any).
+    pub base_diff: Option<String>,
+
+    /// Was the git index ignored?
+    pub ignore_index: bool,
+}
+
+/// The calls recorded by a [`MockClient`].
+///
+/// See [`MockClient::calls()`].
+#[derive(Debug, Clone, Default)]
+pub struct MockCalls {
+    /// The options given to [`RestApiClient::post_thread_comment()`].
+    pub thread_comments: Vec<ThreadCommentOptions>,
+
+    /// The variables given to [`RestApiClient::write_output_variables()`].
+    pub output_variables: Vec<OutputVariable>,
+
+    /// The comments given to [`RestApiClient::append_step_summary()`].
+    pub step_summaries: Vec<String>,
+
+    /// The options given to [`RestApiClient::post_pr_review()`].
+    pub pr_reviews: Vec<ReviewOptions>,
+
+    /// The annotations given to [`RestApiClient::write_file_annotations()`].
+    pub file_annotations: Vec<FileAnnotation>,
+
+    /// The statuses given to [`RestApiClient::post_commit_status()`].
+    pub commit_statuses: Vec<CommitStatus>,
+
+    /// The arguments given to [`RestApiClient::get_list_of_changed_files()`].
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    pub changed_files: Vec<ChangedFilesRequest>,
+}
+
+/// The results queued for a [`MockClient`] to return.
+#[derive(Debug, Default)]
+struct MockResults {
+    thread_comments: VecDeque<Result<(), ClientError>>,
+    output_variables: VecDeque<Result<(), ClientError>>,
+    step_summaries: VecDeque<Result<(), ClientError>>,
+    pr_reviews: VecDeque<Result<ReviewOutcome, ClientError>>,
+    commit_statuses: VecDeque<Result<(), ClientError>>,
+    #[cfg(feature = "file-changes")]
+    changed_files: VecDeque<Result<HashMap<String, FileDiffLines>, ClientError>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    calls: MockCalls,
+    results: MockResults,
+}
+
+/// An implementation of [`RestApiClient`] that records its calls instead of
+/// contacting a git server.
+///
+/// This is meant to unit-test the feedback logic of an application.
+/// Any queued result (eg. [`Self::queue_thread_comment_result()`]) is returned by
+/// the next call of the respective method.
+/// When no result is queued, the method succeeds with a default value.
+///
+/// Clones share the recorded calls and queued results.
+/// So, a clone can be given to the code under test (eg. as a
+/// `Box<dyn RestApiClient + Send + Sync>`) while the original is used for assertions:
+///
+/// ```
+/// use git_bot_feedback::{
+///     OutputVariable, RestApiClient, RestClientError, ThreadCommentOptions,
+///     client::MockClient,
+/// };
+///
+/// async fn report(client: Box<dyn RestApiClient + Send + Sync>) -> Result<(), RestClientError> {
+///     if client.is_pr_event() {
+///         client
+///             .post_thread_comment(ThreadCommentOptions {
+///                 comment: "All good".to_string(),
+///                 ..Default::default()
+///             })
+///             .await?;
+///     }
+///     client.write_output_variables(&[OutputVariable {
+///         name: "checks-failed".to_string(),
+///         value: "0".to_string(),
+///     }])
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mock = MockClient::new(true);
+/// report(Box::new(mock.clone())).await.unwrap();
+/// let calls = mock.calls();
+/// assert_eq!(calls.thread_comments.len(), 1);
+/// assert_eq!(calls.thread_comments[0].comment, "All good");
+/// assert_eq!(calls.output_variables[0].name, "checks-failed");
+///
+/// // errors can be simulated as well
+/// mock.queue_thread_comment_result(Err(RestClientError::RateLimitNoReset));
+/// assert!(report(Box::new(mock.clone())).await.is_err());
+/// // the failed call is still recorded
+/// assert_eq!(mock.calls().thread_comments.len(), 2);
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockClient {
+    pr_event: bool,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockClient {
+    /// Create a mock client for a PR event (if `pr_event` is `true`) or a push event.
+    pub fn new(pr_event: bool) -> Self {
+        Self {
+            pr_event,
+            ..Default::default()
+        }
+    }
+
+    /// Set the value returned by [`RestApiClient::is_pr_event()`] (for this client only).
+    pub fn set_pr_event(&mut self, pr_event: bool) {
+        self.pr_event = pr_event;
+    }
+
+    /// Get a copy of the calls recorded so far.
+    pub fn calls(&self) -> MockCalls {
+        self.lock().calls.clone()
+    }
+
+    /// Forget all recorded calls (and queued results).
+    pub fn reset(&self) {
+        *self.lock() = MockState::default();
+    }
+
+    /// Queue a `result` for [`RestApiClient::post_thread_comment()`].
+    pub fn queue_thread_comment_result(&self, result: Result<(), ClientError>) {
+        self.lock().results.thread_comments.push_back(result);
+    }
+
+    /// Queue a `result` for [`RestApiClient::write_output_variables()`].
+    pub fn queue_output_variables_result(&self, result: Result<(), ClientError>) {
+        self.lock().results.output_variables.push_back(result);
+    }
+
+    /// Queue a `result` for [`RestApiClient::append_step_summary()`].
+    pub fn queue_step_summary_result(&self, result: Result<(), ClientError>) {
+        self.lock().results.step_summaries.push_back(result);
+    }
+
+    /// Queue a `result` for [`RestApiClient::post_pr_review()`].
+    pub fn queue_pr_review_result(&self, result: Result<ReviewOutcome, ClientError>) {
+        self.lock().results.pr_reviews.push_back(result);
+    }
+
+    /// Queue a `result` for [`RestApiClient::post_commit_status()`].
+    pub fn queue_commit_status_result(&self, result: Result<(), ClientError>) {
+        self.lock().results.commit_statuses.push_back(result);
+    }
+
+    /// Queue a `result` for [`RestApiClient::get_list_of_changed_files()`].
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    pub fn queue_changed_files_result(
+        &self,
+        result: Result<HashMap<String, FileDiffLines>, ClientError>,
+    ) {
+        self.lock().results.changed_files.push_back(result);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        lock_ignoring_poison(&self.state)
+    }
+}
+
+#[async_trait::async_trait]
+impl RestApiClient for MockClient {
+    fn client_kind(&self) -> String {
+        "mock".to_string()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            thread_comments_on_push: true,
+            pr_reviews: true,
+            file_annotations: true,
+            step_summary: true,
+            output_variables: true,
+            commit_statuses: true,
+        }
+    }
+
+    fn is_pr_event(&self) -> bool {
+        self.pr_event
+    }
+
+    fn set_user_agent(&mut self, _user_agent: &str) -> Result<(), ClientError> {
+        Ok(())
+    }
+
+    #[cfg(feature = "file-changes")]
+    async fn get_list_of_changed_files(
+        &self,
+        file_filter: &FileFilter,
+        lines_changed_only: &LinesChangedOnly,
+        base_diff: Option<String>,
+        ignore_index: bool,
+    ) -> Result<HashMap<String, FileDiffLines>, ClientError> {
+        let mut state = self.lock();
+        state.calls.changed_files.push(ChangedFilesRequest {
+            file_filter: file_filter.clone(),
+            lines_changed_only: *lines_changed_only,
+            base_diff,
+            ignore_index,
+        });
+        state
+            .results
+            .changed_files
+            .pop_front()
+            .unwrap_or_else(|| Ok(HashMap::new()))
+    }
+
+    async fn post_thread_comment(&self, options: ThreadCommentOptions) -> Result<(), ClientError> {
+        let mut state = self.lock();
+        state.calls.thread_comments.push(options);
+        state.results.thread_comments.pop_front().unwrap_or(Ok(()))
+    }
+
+    fn append_step_summary(
+        &self,
+        comment: &str,
+        _overflow: SummaryOverflow,
+    ) -> Result<(), ClientError> {
+        let mut state = self.lock();
+        state.calls.step_summaries.push(comment.to_string());
+        state.results.step_summaries.pop_front().unwrap_or(Ok(()))
+    }
+
+    async fn cull_pr_reviews(&mut self, _options: &mut ReviewOptions) -> Result<(), ClientError> {
+        Ok(())
+    }
+
+    async fn post_pr_review(
+        &mut self,
+        options: &ReviewOptions,
+    ) -> Result<ReviewOutcome, ClientError> {
+        let mut state = self.lock();
+        state.calls.pr_reviews.push(options.clone());
+        state
+            .results
+            .pr_reviews
+            .pop_front()
+            .unwrap_or_else(|| Ok(ReviewOutcome::default()))
+    }
+
+    async fn post_commit_status(&self, status: &CommitStatus) -> Result<(), ClientError> {
+        let mut state = self.lock();
+        state.calls.commit_statuses.push(status.clone());
+        state.results.commit_statuses.pop_front().unwrap_or(Ok(()))
+    }
+
+    fn write_output_variables(&self, vars: &[OutputVariable]) -> Result<(), ClientError> {
+        let mut state = self.lock();
+        state.calls.output_variables.extend_from_slice(vars);
+        state.results.output_variables.pop_front().unwrap_or(Ok(()))
+    }
+
+    fn write_file_annotations(
+        &self,
+        annotations: &[FileAnnotation],
+        _overflow: AnnotationOverflow,
+    ) -> Result<usize, ClientError> {
+        self.lock()
+            .calls
+            .file_annotations
+            .extend_from_slice(annotations);
+        Ok(annotations.len())
+    }
+}
@@ -1,11 +1,24 @@
 //! A module to contain traits and structs that are needed by the rest of the git-bot-feedback crate's API.
-use std::{env, fmt::Debug, time::Duration};
+use std::{
+    env,
+    fmt::Debug,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
-use chrono::DateTime;
-use reqwest::{Client, Method, Request, Response, Url, header::HeaderMap};
+use bytes::Bytes;
+use reqwest::{
+    Body, Client, Method, Request, Response, ResponseBuilderExt, StatusCode, Url, Version,
+    header::{HeaderMap, HeaderValue, IF_NONE_MATCH},
+};
 
-use crate::{FileAnnotation, OutputVariable, RestClientError, ReviewOptions, ThreadCommentOptions};
+use crate::{
+    AnnotationOverflow, CommitStatus, FileAnnotation, OutputVariable, RestClientError,
+    ReviewOptions, ReviewOutcome, ThreadCommentOptions,
+    summary::{MAX_STEP_SUMMARY_SIZE, SummaryOverflow, SummaryWriteMode},
+};
 
 #[cfg(feature = "gitea")]
 mod gitea;
@@ -17,14 +30,92 @@ mod github;
 #[cfg(feature = "github")]
 pub use github::GithubApiClient;
 
+#[cfg(feature = "gitlab")]
+mod gitlab;
+#[cfg(feature = "gitlab")]
+pub use gitlab::GitlabApiClient;
+
+#[cfg(feature = "azure-devops")]
+mod azure_devops;
+#[cfg(feature = "azure-devops")]
+pub use azure_devops::AzureDevopsApiClient;
+
 mod local;
+#[cfg(feature = "file-changes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+pub use local::DiffScope;
 pub use local::LocalClient;
 
+#[cfg(feature = "test-utils")]
+mod mock;
+#[cfg(all(feature = "test-utils", feature = "file-changes"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(feature = "test-utils", feature = "file-changes")))
+)]
+pub use mock::ChangedFilesRequest;
+#[cfg(feature = "test-utils")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-utils")))]
+pub use mock::{MockCalls, MockClient};
+
 mod common;
 
+mod capabilities;
+pub use capabilities::Capabilities;
+
+mod command_sink;
+pub(crate) use command_sink::random_token;
+pub use command_sink::{CommandSink, StopCommandsGuard};
+
+mod rate_limit;
+pub(crate) use rate_limit::RateLimitSlot;
+pub use rate_limit::{RateLimitResource, RateLimitSnapshot, RateLimitStatus};
+
+mod retry_policy;
+pub use retry_policy::{PrimaryLimit, RateLimitDetection, RetryPolicy};
+
+mod timeouts;
+pub use timeouts::Timeouts;
+
+mod response_cache;
+pub use response_cache::ResponseCache;
+
+mod recorder;
+pub use recorder::RequestRecorder;
+
+mod metrics;
+pub use metrics::{CountingMetrics, Metrics};
+
+mod links;
+pub use links::PageLinks;
+
+mod pagination;
+#[cfg(feature = "file-changes")]
+pub use pagination::DEFAULT_CHANGED_FILES_PER_PAGE;
+pub use pagination::Paginator;
+
+mod timer;
+
+pub(crate) mod timestamp;
+pub use timestamp::Timestamp;
+
+mod throttle;
+pub use throttle::{DEFAULT_MAX_CONCURRENT_REQUESTS, Throttle};
+pub use tokio_util::sync::CancellationToken;
+
+mod sealed {
+    /// A token that only this crate can create.
+    ///
+    /// A trait method that takes this token can only be called (or overridden) by this crate.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Token;
+}
+
 #[cfg(not(any(
     feature = "github",
     feature = "gitea",
+    feature = "gitlab",
+    feature = "azure-devops",
     feature = "custom-git-server-impl",
 )))]
 compile_error!(
@@ -36,9 +127,54 @@ use crate::{FileDiffLines, FileFilter, LinesChangedOnly};
 #[cfg(feature = "file-changes")]
 use std::collections::HashMap;
 
+/// Run the given `future` within a [`tracing`] span, if the `tracing` feature is enabled.
+///
+/// The remaining arguments describe the span (as passed to `tracing::info_span!()`).
+/// The span is created before the `future` is evaluated.
+macro_rules! in_span {
+    ($future:expr, $($span:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        let future = {
+            let span = tracing::info_span!($($span)+);
+            tracing::Instrument::instrument($future, span)
+        };
+        #[cfg(not(feature = "tracing"))]
+        let future = $future;
+        future
+    }};
+}
+pub(crate) use in_span;
+
+/// Record the `value` of a `field` in the current [`tracing`] span, if the `tracing` feature is enabled.
+macro_rules! record_span_field {
+    ($field:literal, $value:expr) => {
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record($field, $value);
+    };
+}
+
 /// The User-Agent header value included in all HTTP requests.
+///
+/// A custom user agent (see [`RestApiClient::set_user_agent()`]) is suffixed with this value.
 pub static USER_AGENT: &str = concat!(env!("CARGO_CRATE_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
+/// Create the User-Agent header value from the given `user_agent`.
+///
+/// The [`USER_AGENT`] is appended as a suffix, unless the `user_agent` already includes it.
+/// An empty `user_agent` yields the [`USER_AGENT`].
+pub(crate) fn user_agent_header(user_agent: &str) -> Result<HeaderValue, ClientError> {
+    let user_agent = user_agent.trim();
+    if user_agent.is_empty() {
+        Ok(HeaderValue::from_static(USER_AGENT))
+    } else if user_agent.contains(USER_AGENT) {
+        Ok(HeaderValue::from_str(user_agent)?)
+    } else {
+        Ok(HeaderValue::from_str(
+            format!("{user_agent} {USER_AGENT}").as_str(),
+        )?)
+    }
+}
+
 /// A structure to contain the different forms of headers that
 /// describe a REST API's rate limit status.
 #[derive(Debug, Clone)]
@@ -51,13 +187,222 @@ pub struct RestApiRateLimitHeaders {
     pub retry: String,
 }
 
+impl RestApiRateLimitHeaders {
+    /// Header names for a server that does not describe its rate limit in response headers.
+    ///
+    /// Each header name is empty, so no header is ever found.
+    /// Combine this with [`RateLimitDetection::StatusOnly`] to still back off
+    /// when a `429 Too Many Requests` response is received.
+    pub fn disabled() -> Self {
+        Self {
+            reset: String::new(),
+            remaining: String::new(),
+            retry: String::new(),
+        }
+    }
+}
+
 /// The [`Result::Err`] type returned for fallible functions in this trait.
+///
+/// This is only a crate-internal shorthand; all clients (and the public API) return
+/// the same [`RestClientError`] type.
 pub(crate) type ClientError = RestClientError;
 
-/// The number of attempts made when contending a secondary rate limit in REST API requests.
+/// The number of attempts made when contending a secondary rate limit
+/// or a transient failure in REST API requests.
 pub(crate) const MAX_RETRIES: u8 = 5;
 
+/// HTTP status codes that indicate a transient server-side failure.
+const RETRYABLE_STATUS_CODES: [u16; 4] = [500, 502, 503, 504];
+
+/// Is the given `status` a server error that is typically transient?
+///
+/// See also [`RestClientError::is_retryable()`].
+pub(crate) fn is_retryable_status(status: StatusCode) -> bool {
+    RETRYABLE_STATUS_CODES.contains(&status.as_u16())
+}
+
+/// Is the given error caused by a transient network failure (eg. a timeout or a dropped connection)?
+pub(crate) fn is_transient_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect() || error.is_request()
+}
+
+/// Does the given response `body` announce that a secondary rate limit was exceeded?
+///
+/// GitHub's message reads "You have exceeded a secondary rate limit".
+fn is_secondary_limit_message(body: &[u8]) -> bool {
+    String::from_utf8_lossy(body)
+        .to_lowercase()
+        .contains("secondary rate limit")
+}
+
+/// Read the body of the given `response`, so it can be inspected without being lost.
+///
+/// Returns a copy of the `response` (with the same status, headers and URL) and its body.
+async fn buffer_response(response: Response) -> Result<(Response, Vec<u8>), ClientError> {
+    let status = response.status();
+    let version = response.version();
+    let url = response.url().clone();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?.to_vec();
+    let response = rebuild_response(status, version, url, headers, body.clone())?;
+    Ok((response, body))
+}
+
+/// Construct a [`Response`] from its parts.
+pub(crate) fn rebuild_response(
+    status: StatusCode,
+    version: Version,
+    url: Url,
+    headers: HeaderMap,
+    body: Vec<u8>,
+) -> Result<Response, ClientError> {
+    let mut builder = http::Response::builder()
+        .status(status)
+        .version(version)
+        .url(url);
+    if let Some(builder_headers) = builder.headers_mut() {
+        *builder_headers = headers;
+    }
+    let response = builder
+        .body(body)
+        .map_err(|e| ClientError::io("buffer response body", std::io::Error::other(e)))?;
+    Ok(Response::from(response))
+}
+
+/// Build a HTTP request with the given `client`.
+///
+/// This is the default implementation of [`RestApiClient::make_api_request()`].
+pub(crate) fn build_request(
+    client: &Client,
+    url: Url,
+    method: Method,
+    data: Option<String>,
+    headers: Option<HeaderMap>,
+) -> Result<Request, ClientError> {
+    let mut req = client.request(method, url);
+    if let Some(h) = headers {
+        req = req.headers(h);
+    }
+    if let Some(d) = data {
+        req = req.body(d);
+    }
+    req.build()
+        .map_err(|e| ClientError::add_request_context(ClientError::Request(e), "build request"))
+}
+
+/// Get the value of the environment variable `file_env` that references a file (eg. `GITHUB_OUTPUT`).
+///
+/// If the environment variable is not set, then this returns [`None`] unless `strict` is `true`,
+/// in which case a [`ClientError::EnvVar`] error is returned.
+#[cfg(any(feature = "gitea", feature = "github", feature = "gitlab"))]
+pub(crate) fn env_file(file_env: &str, strict: bool) -> Result<Option<String>, ClientError> {
+    match env::var(file_env) {
+        Ok(path) => {
+            log::debug!("Using {file_env} environment variable");
+            Ok(Some(path))
+        }
+        Err(e) if strict => Err(ClientError::env_var(file_env, e)),
+        Err(_) => {
+            log::debug!("{file_env} is not set; skipping");
+            Ok(None)
+        }
+    }
+}
+
+/// Fail with [`ClientError::Cancelled`] if the given `token` was cancelled.
+pub(crate) fn check_cancelled(
+    token: Option<&CancellationToken>,
+    task: &str,
+) -> Result<(), ClientError> {
+    if token.is_some_and(CancellationToken::is_cancelled) {
+        return Err(ClientError::Cancelled {
+            task: task.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Lock the given `mutex`, even if it is poisoned.
+///
+/// A poisoned lock only means another thread panicked while holding it;
+/// the guarded state (eg. a counter or a file handle) is still usable.
+pub(crate) fn lock_ignoring_poison<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Wait for the given `interval` before retrying a request.
+///
+/// Fails with [`ClientError::Cancelled`] if the given `token` is cancelled before or while waiting.
+async fn wait_before_retry(
+    interval: Duration,
+    token: Option<&CancellationToken>,
+) -> Result<(), ClientError> {
+    check_cancelled(token, "retry request")?;
+    #[cfg(feature = "test-skip-wait-for-rate-limit")]
+    {
+        // Output a log statement to use the `interval` variable.
+        log::warn!(
+            "Skipped waiting {} seconds to expedite test",
+            interval.as_secs()
+        );
+    }
+    #[cfg(not(feature = "test-skip-wait-for-rate-limit"))]
+    {
+        match token {
+            Some(token) => {
+                if token
+                    .run_until_cancelled(timer::sleep(interval))
+                    .await
+                    .is_none()
+                {
+                    return check_cancelled(Some(token), "retry request");
+                }
+            }
+            None => timer::sleep(interval).await,
+        }
+    }
+    Ok(())
+}
+
+/// Fail to compile if the client type `T` cannot be cloned and shared across threads.
+///
+/// See the [`RestApiClient`] docs about clones.
+pub(crate) const fn assert_send_sync_clone<T: Send + Sync + Clone>() {}
+
 /// A custom trait that templates necessary functionality with a Git server's REST API.
+///
+/// The clients provided by this crate are cheap to clone and can be shared across threads
+/// (they are [`Send`] and [`Sync`]).
+/// Clones share the underlying HTTP connection pool, the [`Self::last_rate_limit()`],
+/// the [`Self::throttle()`], the [`Self::response_cache()`] and the [`Self::request_recorder()`].
+/// Other settings (eg. [`Self::set_retry_policy()`]) only apply to the
+/// client that they are set on.
+///
+/// So, a clone can be moved into each concurrent task:
+///
+/// ```no_run
+/// use git_bot_feedback::{RestApiClient, RestClientError, ThreadCommentOptions};
+///
+/// async fn post_in_task<C>(client: &C) -> Result<(), RestClientError>
+/// where
+///     C: RestApiClient + Clone + Send + Sync + 'static,
+/// {
+///     let task = tokio::spawn({
+///         let client = client.clone();
+///         async move {
+///             client
+///                 .post_thread_comment(ThreadCommentOptions {
+///                     comment: "Hello from a task".to_string(),
+///                     ..Default::default()
+///                 })
+///                 .await
+///         }
+///     });
+///     let rate_limit = client.rate_limit_status().await?;
+///     task.await.expect("task panicked")
+/// }
+/// ```
 #[async_trait]
 pub trait RestApiClient {
     /// This prints a line to indicate the beginning of a related group of [`log`] statements.
@@ -123,6 +468,11 @@ pub trait RestApiClient {
     ///
     /// By default the user agent is set to this lib's name and version.
     /// See [`USER_AGENT`] for the default value.
+    ///
+    /// The given `user_agent` should identify the app using this lib (eg. `"my-tool/1.2"`).
+    /// The default value is appended as a suffix (eg. `"my-tool/1.2 git_bot_feedback/0.8.1"`),
+    /// unless the given `user_agent` already includes it.
+    /// Returns an error if the `user_agent` is not a valid header value.
     fn set_user_agent(&mut self, user_agent: &str) -> Result<(), ClientError>;
 
     /// A way to get the list of changed files in the context of the CI event.
@@ -143,6 +493,51 @@ pub trait RestApiClient {
         ignore_index: bool,
     ) -> Result<HashMap<String, FileDiffLines>, ClientError>;
 
+    /// Get the number of changed files requested per page by
+    /// [`Self::get_list_of_changed_files()`].
+    ///
+    /// Returns [`None`] if the changed files are not listed in pages (eg. from a diff).
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    fn changed_files_per_page(&self) -> Option<u32> {
+        None
+    }
+
+    /// Set the number of changed files requested per page by
+    /// [`Self::get_list_of_changed_files()`].
+    ///
+    /// Defaults to [`DEFAULT_CHANGED_FILES_PER_PAGE`].
+    /// Git servers limit the page size (eg. GitHub allows at most `100`).
+    /// This does nothing for clients that do not list changed files in pages.
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    fn set_changed_files_per_page(&mut self, _per_page: u32) {}
+
+    /// Is the full diff requested by [`Self::get_list_of_changed_files()`] when
+    /// the Git server omits the patch of a changed file (because it is too large)
+    /// or truncates the list of changed files (because there are too many)?
+    ///
+    /// Returns [`None`] if the client does not list patches per file.
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    fn diff_fallback(&self) -> Option<bool> {
+        None
+    }
+
+    /// Enable or disable requesting the full diff when the Git server omits
+    /// the patch of a changed file or truncates the list of changed files
+    /// (see [`Self::diff_fallback()`]).
+    ///
+    /// Enabled by default. At most 1 extra request is made per call to
+    /// [`Self::get_list_of_changed_files()`], regardless of how many patches were omitted.
+    /// If disabled (or the full diff is unavailable), then the truncated list of changed files
+    /// is used, and files with omitted patches are only listed (without any changed lines)
+    /// when [`LinesChangedOnly::Off`] is used.
+    /// This does nothing for clients that do not list patches per file.
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    fn set_diff_fallback(&mut self, _enabled: bool) {}
+
     /// A way to post feedback to the Git server's GUI.
     ///
     /// The given [`ThreadCommentOptions::comment`] should be compliant with
@@ -155,11 +550,57 @@ pub trait RestApiClient {
     /// Not all Git servers natively support this type of feedback.
     /// GitHub and Gitea are known to support this.
     /// For all other git servers, this is a non-op returning [`Ok`]
-    fn append_step_summary(&self, comment: &str) -> Result<(), ClientError> {
-        let _ = comment;
+    ///
+    /// The `overflow` policy is applied if the `comment` would make the summary larger
+    /// than the Git server can display (see [`Self::step_summary_limit()`]).
+    fn append_step_summary(
+        &self,
+        comment: &str,
+        overflow: SummaryOverflow,
+    ) -> Result<(), ClientError> {
+        let _ = (comment, overflow);
+        Ok(())
+    }
+
+    /// Writes a given comment to the CI workflow's summary page.
+    ///
+    /// This is like [`Self::append_step_summary()`] (with the default [`SummaryOverflow`] policy),
+    /// but the `mode` controls whether any existing content of the summary page is kept.
+    /// The summary file is created if it does not exist.
+    fn write_step_summary(&self, comment: &str, mode: SummaryWriteMode) -> Result<(), ClientError> {
+        let _ = mode;
+        self.append_step_summary(comment, SummaryOverflow::default())
+    }
+
+    /// Replaces this tool's section of the CI workflow's summary page with the given `content`.
+    ///
+    /// The section is delimited by `<!-- marker:start -->` and `<!-- marker:end -->` HTML comments,
+    /// where `marker` should uniquely identify the tool (and not contain `-->`).
+    /// If the section does not exist yet, then it is appended like [`Self::append_step_summary()`].
+    /// The section is truncated (see [`SummaryOverflow::Truncate`]) if it would make the summary
+    /// larger than [`Self::step_summary_limit()`].
+    /// This is useful to avoid duplicate sections when a step is re-run in the same job.
+    ///
+    /// For git servers that do not support step summaries, this is a non-op returning [`Ok`].
+    fn upsert_step_summary(&self, marker: &str, content: &str) -> Result<(), ClientError> {
+        let _ = (marker, content);
         Ok(())
     }
 
+    /// Get the size limit (in bytes) of the CI workflow's summary page.
+    ///
+    /// Defaults to [`MAX_STEP_SUMMARY_SIZE`] (GitHub's limit).
+    fn step_summary_limit(&self) -> u64 {
+        MAX_STEP_SUMMARY_SIZE
+    }
+
+    /// Set the size limit (in bytes) of the CI workflow's summary page.
+    ///
+    /// This is useful for CI runners that display a different amount than GitHub
+    /// (eg. a self-hosted Gitea runner).
+    /// This does nothing for clients whose limit is fixed or that do not support step summaries.
+    fn set_step_summary_limit(&mut self, _limit: u64) {}
+
     /// Resolve outdated PR review comments and remove duplicate/reused comments.
     ///
     /// This should be used before [`Self::post_pr_review()`] to avoid posting duplicates of existing comments.
@@ -182,22 +623,308 @@ pub trait RestApiClient {
     /// avoid posting duplicates of existing comments. Once the duplicates are filtered out,
     /// the [`ReviewOptions::summary`] can be made to reflect the actual review being posted.
     ///
-    /// This function does nothing for non-PR events.
-    async fn post_pr_review(&mut self, options: &ReviewOptions) -> Result<(), ClientError>;
+    /// Returns a [`ReviewOutcome`] that describes the posted review along with
+    /// the number of comments reused and reviews dismissed by [`Self::cull_pr_reviews()`].
+    ///
+    /// This function does nothing for non-PR events (returning a default [`ReviewOutcome`]).
+    async fn post_pr_review(
+        &mut self,
+        options: &ReviewOptions,
+    ) -> Result<ReviewOutcome, ClientError>;
+
+    /// Post the given `status` for the commit being checked.
+    ///
+    /// For PR events, the status is attached to the PR's head commit.
+    /// The [`CommitStatus::description`] is truncated (see [`CommitStatus::truncated_description()`])
+    /// and the `status` is validated (see [`CommitStatus::validate()`]) before it is posted.
+    ///
+    /// Git servers that do not distinguish a
+    /// [`StatusState::Warning`](crate::StatusState::Warning) state post it as a success.
+    ///
+    /// By default, this does nothing (see [`Capabilities::commit_statuses`]).
+    async fn post_commit_status(&self, status: &CommitStatus) -> Result<(), ClientError> {
+        let _ = status;
+        log_unsupported(self, "posting a commit status");
+        Ok(())
+    }
+
+    /// Get the content of the file at the given `path` (relative to the repository's root).
+    ///
+    /// The file is read at the given `reference` (eg. a commit SHA, branch or tag).
+    /// If `reference` is [`None`], then the commit that triggered the CI run is used.
+    /// This is useful to get the upstream version of a changed file
+    /// (eg. when it is missing from a shallow checkout).
+    ///
+    /// Returns a [`ClientError::BinaryFile`] error if the file's content is not valid UTF-8.
+    /// By default, this returns a [`ClientError::Unsupported`] error.
+    async fn get_file_content(
+        &self,
+        path: &str,
+        reference: Option<&str>,
+    ) -> Result<String, ClientError> {
+        let _ = reference;
+        Err(ClientError::Unsupported {
+            task: format!("get content of {path}"),
+            server: self.client_kind(),
+        })
+    }
 
     /// Sets the given `vars` as output variables.
     ///
     /// These variables are designed to be consumed by other steps in the CI workflow.
     fn write_output_variables(&self, vars: &[OutputVariable]) -> Result<(), ClientError>;
 
+    /// Exports the given `vars` as environment variables.
+    ///
+    /// These environment variables are available to subsequent steps in the CI workflow.
+    /// The `vars` are validated and formatted the same way as [`Self::write_output_variables()`].
+    /// This does nothing for git servers that do not support it.
+    fn export_environment_variables(&self, _vars: &[OutputVariable]) -> Result<(), ClientError> {
+        Ok(())
+    }
+
+    /// Should a missing environment variable be an error when writing to the file it references?
+    ///
+    /// Methods like [`Self::write_output_variables()`] and [`Self::append_step_summary()`]
+    /// write to a file whose path is given by an environment variable (eg. `GITHUB_OUTPUT`).
+    /// By default, some git servers silently do nothing when that environment variable is not set.
+    /// If `strict` is `true`, then a [`ClientError::EnvVar`] error is returned instead.
+    fn set_strict_mode(&mut self, _strict: bool) {}
+
+    /// Is this client in dry-run mode?
+    ///
+    /// See [`Self::set_dry_run()`].
+    fn dry_run(&self) -> bool {
+        false
+    }
+
+    /// Set the client in dry-run mode (if `dry_run` is `true`).
+    ///
+    /// In dry-run mode, [`Self::send_api_request()`] does not send requests that would
+    /// change something on the git server (eg. posting, updating or deleting a comment).
+    /// Instead, the request is logged (eg. "Dry run: would PATCH /repos/owner/repo/issues/comments/123
+    /// with 5120 bytes") and an empty `204 No Content` response is returned.
+    /// Read-only requests (including GraphQL queries) are still sent,
+    /// so the decisions made (eg. by [`Self::cull_pr_reviews()`]) are realistic.
+    /// Consequently, [`Self::post_pr_review()`] reports a [`ReviewOutcome`] without a
+    /// [`ReviewOutcome::review_id`].
+    ///
+    /// This does not affect files written for the CI workflow (eg. [`Self::write_output_variables()`]).
+    /// This does nothing for git servers that do not support it.
+    fn set_dry_run(&mut self, _dry_run: bool) {}
+
+    /// Saves the given `vars` as state for the `pre` or `post` steps of the same action.
+    ///
+    /// The `vars` are validated and formatted the same way as [`Self::write_output_variables()`].
+    /// Use [`Self::read_state_variable()`] to read a saved state variable in a later step.
+    /// This does nothing for git servers that do not support it.
+    fn write_state_variables(&self, _vars: &[OutputVariable]) -> Result<(), ClientError> {
+        Ok(())
+    }
+
+    /// Reads the value of a state variable named `name` that was saved by
+    /// [`Self::write_state_variables()`] in a previous step of the same action.
+    ///
+    /// The CI runner exposes saved state as environment variables named `STATE_<name>`.
+    /// Returns [`None`] if the state variable was not saved or the git server does not support it.
+    fn read_state_variable(&self, _name: &str) -> Option<String> {
+        None
+    }
+
+    /// Prepends the given `dir` to the `PATH` environment variable of subsequent steps in the CI workflow.
+    ///
+    /// The `dir` must be an absolute path to an existing directory.
+    /// This does nothing for git servers that do not support it.
+    fn add_to_path(&self, _dir: &Path) -> Result<(), ClientError> {
+        Ok(())
+    }
+
+    /// Use the given `client` to send all HTTP requests.
+    ///
+    /// This is useful to configure a proxy or a custom root certificate.
+    /// The headers needed by the git server's REST API (eg. `User-Agent`, `Accept` and
+    /// `Authorization`) are still applied to every request made with
+    /// [`Self::make_api_request()`].
+    /// The [`Timeouts::connect`] setting is not applied to the given `client`.
+    ///
+    /// This does nothing for clients that do not send REST API requests.
+    fn set_http_client(&mut self, _client: Client) {}
+
+    /// Get the [`Throttle`] that limits how many requests [`Self::send_api_request()`]
+    /// sends at once.
+    ///
+    /// Returns [`None`] if requests are not throttled.
+    fn throttle(&self) -> Option<&Throttle> {
+        None
+    }
+
+    /// Set the [`Throttle`] that limits how many requests [`Self::send_api_request()`]
+    /// sends at once.
+    ///
+    /// This does nothing for clients that do not send REST API requests.
+    fn set_throttle(&mut self, _throttle: Throttle) {}
+
+    /// Get the [`CancellationToken`] that interrupts long-running operations.
+    ///
+    /// Returns [`None`] if operations cannot be cancelled (the default).
+    fn cancellation_token(&self) -> Option<&CancellationToken> {
+        None
+    }
+
+    /// Set the [`CancellationToken`] that interrupts long-running operations.
+    ///
+    /// Once the `token` is cancelled, paginated operations (eg. finding previous comments)
+    /// stop before fetching the next page, and [`Self::send_api_request()`] stops waiting to
+    /// retry a request. The interrupted operation fails with [`ClientError::Cancelled`].
+    ///
+    /// Pass [`None`] to make operations uninterruptible.
+    /// This does nothing for clients that do not send REST API requests.
+    fn set_cancellation_token(&mut self, _token: Option<CancellationToken>) {}
+
+    /// Get the [`Metrics`] hooks invoked by [`Self::send_api_request()`].
+    ///
+    /// Returns [`None`] if no hooks are registered (the default).
+    fn metrics(&self) -> Option<&dyn Metrics> {
+        None
+    }
+
+    /// Register the [`Metrics`] hooks invoked by [`Self::send_api_request()`].
+    ///
+    /// Pass [`None`] to unregister the hooks.
+    /// This does nothing for clients that do not send REST API requests.
+    fn set_metrics(&mut self, _metrics: Option<Arc<dyn Metrics>>) {}
+
+    /// Get the [`ResponseCache`] used by [`Self::send_api_request()`] for GET requests.
+    ///
+    /// Returns [`None`] if caching is disabled (the default).
+    fn response_cache(&self) -> Option<&ResponseCache> {
+        None
+    }
+
+    /// Set the [`ResponseCache`] used by [`Self::send_api_request()`] for GET requests.
+    ///
+    /// Pass [`None`] to disable caching.
+    /// This does nothing for clients that do not send REST API requests.
+    fn set_response_cache(&mut self, _cache: Option<ResponseCache>) {}
+
+    /// Get the [`RequestRecorder`] that keeps an audit trail of the requests sent by
+    /// [`Self::send_api_request()`].
+    ///
+    /// Returns [`None`] if requests are not recorded (the default).
+    fn request_recorder(&self) -> Option<&RequestRecorder> {
+        None
+    }
+
+    /// Set the [`RequestRecorder`] that keeps an audit trail of the requests sent by
+    /// [`Self::send_api_request()`].
+    ///
+    /// Pass [`None`] to stop recording.
+    /// This does nothing for clients that do not send REST API requests.
+    fn set_request_recorder(&mut self, _recorder: Option<RequestRecorder>) {}
+
+    /// Get the [`Timeouts`] that limit how long requests may take.
+    fn timeouts(&self) -> Timeouts {
+        Timeouts::default()
+    }
+
+    /// Set the [`Timeouts`] that limit how long requests may take.
+    ///
+    /// This does nothing for clients that do not send REST API requests.
+    fn set_timeouts(&mut self, _timeouts: Timeouts) -> Result<(), ClientError> {
+        Ok(())
+    }
+
+    /// Query the git server for the current [`RateLimitStatus`].
+    ///
+    /// This sends a request explicitly (without any retries), so the status is current.
+    /// Returns [`None`] if the git server does not report its rate limits
+    /// (eg. Gitea often has no rate limiting enabled).
+    ///
+    /// ```no_run
+    /// # async fn scan_comments() {}
+    /// use git_bot_feedback::{RestApiClient, client::init_client};
+    ///
+    /// # async fn example() -> Result<(), git_bot_feedback::RestClientError> {
+    /// let client = init_client()?;
+    /// let status = client.rate_limit_status().await?;
+    /// if status.is_some_and(|s| s.core.remaining < 100) {
+    ///     log::warn!("Skipping expensive scan to preserve the rate limit");
+    /// } else {
+    ///     scan_comments().await;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn rate_limit_status(&self) -> Result<Option<RateLimitStatus>, ClientError> {
+        Ok(None)
+    }
+
+    /// Get the rate limit captured from the most recent successful response
+    /// of [`Self::send_api_request()`].
+    ///
+    /// Returns [`None`] if no response described the rate limit yet
+    /// (or if this client does not keep track of it).
+    fn last_rate_limit(&self) -> Option<RateLimitSnapshot> {
+        None
+    }
+
+    /// Store the given `snapshot` as the [`Self::last_rate_limit()`].
+    ///
+    /// This is called by [`Self::send_api_request()`] for every successful response.
+    /// It is sealed, so the [`Self::last_rate_limit()`] cannot be forged outside of this crate.
+    #[doc(hidden)]
+    fn record_rate_limit(&self, _snapshot: RateLimitSnapshot, _token: sealed::Token) {}
+
+    /// Get the [`RetryPolicy`] used by [`Self::send_api_request()`].
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::default()
+    }
+
+    /// Set the [`RetryPolicy`] used by [`Self::send_api_request()`].
+    ///
+    /// This does nothing for clients that do not send REST API requests.
+    fn set_retry_policy(&mut self, _policy: RetryPolicy) {}
+
+    /// Set the destination of CI workflow commands (eg. file annotations).
+    ///
+    /// By default, workflow commands are printed to stdout.
+    /// This does nothing for git servers that do not support workflow commands.
+    fn set_command_sink(&mut self, _sink: CommandSink) {}
+
+    /// Register the given `secret` with the CI runner, so it is masked in the job's logs.
+    ///
+    /// Multi-line secrets are masked line by line because the CI runner matches masked values per line.
+    /// This does nothing for git servers that do not support workflow commands.
+    fn mask_secret(&self, _secret: &str) -> Result<(), ClientError> {
+        Ok(())
+    }
+
+    /// Write untrusted `content` (eg. a PR title) to the job's log.
+    ///
+    /// For git servers that support workflow commands, the `content` is wrapped with a
+    /// [`StopCommandsGuard`], so it cannot be interpreted as a workflow command.
+    fn log_untrusted(&self, content: &str) -> Result<(), ClientError> {
+        log::info!("{content}");
+        Ok(())
+    }
+
     /// Sets the given `annotations` as file annotations.
     ///
     /// Not all Git servers support this on their free tiers, namely GitLab.
-    fn write_file_annotations(&self, annotations: &[FileAnnotation]) -> Result<(), ClientError> {
+    ///
+    /// The `overflow` policy is applied if there are more `annotations` than the
+    /// Git server can display.
+    ///
+    /// Returns the number of annotations actually written.
+    fn write_file_annotations(
+        &self,
+        annotations: &[FileAnnotation],
+        _overflow: AnnotationOverflow,
+    ) -> Result<usize, ClientError> {
         for annotation in annotations {
             log::info!("{annotation:#?}");
         }
-        Ok(())
+        Ok(annotations.len())
     }
 
     /// Construct a HTTP request to be sent.
@@ -225,15 +952,7 @@ pub trait RestApiClient {
         data: Option<String>,
         headers: Option<HeaderMap>,
     ) -> Result<Request, ClientError> {
-        let mut req = client.request(method, url);
-        if let Some(h) = headers {
-            req = req.headers(h);
-        }
-        if let Some(d) = data {
-            req = req.body(d);
-        }
-        req.build()
-            .map_err(|e| ClientError::add_request_context(ClientError::Request(e), "build request"))
+        build_request(client, url, method, data, headers)
     }
 
     /// A convenience function to send HTTP requests and respect a REST API rate limits.
@@ -241,87 +960,101 @@ pub trait RestApiClient {
     /// This method respects both primary and secondary rate limits.
     /// In the event where the secondary rate limits is reached,
     /// this function will wait for a time interval (if specified by the server) and retry afterward.
+    ///
+    /// Transient failures (timeouts, connection errors, and `500`, `502`, `503` or `504` responses)
+    /// are also retried with an increasing backoff. If all attempts fail, then the last error
+    /// (or response) is returned.
+    ///
+    /// If the primary rate limit is exhausted, then the [`RetryPolicy::on_primary_limit`]
+    /// decides whether to fail or to wait until the rate limit resets.
+    ///
+    /// Only safe requests (eg. `GET` or `HEAD` requests) are replayed freely. Other requests
+    /// are only retried after failing to connect, unless [`RetryPolicy::retry_unsafe`] is set.
+    /// Instead of replaying such a request, a [`ClientError::UnsafeRetry`] is returned.
+    /// A server error response to such a request is returned as is.
+    ///
+    /// If the client has a [`Throttle`], then a permit is acquired before sending the request
+    /// and held until all retries are done.
+    ///
+    /// If the client has [`Metrics`] hooks, then they are invoked for every attempt,
+    /// retry and rate limit sleep.
+    ///
+    /// If the client has a [`ResponseCache`], then GET requests are conditional
+    /// (using the cached ETag) and a `304 Not Modified` response yields the cached response.
+    ///
+    /// The request is rebuilt from its method, URL, headers and body for each retry.
+    /// A request whose body is a stream cannot be rebuilt, so it is not retried;
+    /// use [`Self::send_api_request_with()`] to retry such a request.
     async fn send_api_request(
         &self,
         client: &Client,
         request: Request,
         rate_limit_headers: &RestApiRateLimitHeaders,
     ) -> Result<Response, ClientError> {
-        for i in 0..MAX_RETRIES {
-            let response = client
-                .execute(request.try_clone().ok_or(ClientError::CannotCloneRequest)?)
-                .await?;
-            if [403u16, 429u16].contains(&response.status().as_u16()) {
-                // rate limit may have been exceeded
-
-                // check if primary rate limit was violated
-                let mut requests_remaining = None;
-                if let Some(remaining) = response.headers().get(&rate_limit_headers.remaining) {
-                    requests_remaining = Some(remaining.to_str()?.parse::<i64>()?);
-                } else {
-                    // NOTE: I guess it is sometimes valid for a response to
-                    // not include remaining rate limit attempts
-                    log::debug!("Response headers do not include remaining API usage count");
-                }
-                if requests_remaining.is_some_and(|v| v <= 0) {
-                    if let Some(reset_value) = response.headers().get(&rate_limit_headers.reset)
-                        && let Some(reset) =
-                            DateTime::from_timestamp(reset_value.to_str()?.parse::<i64>()?, 0)
-                    {
-                        return Err(ClientError::RateLimitPrimary(reset));
-                    }
-                    return Err(ClientError::RateLimitNoReset);
-                }
-
-                // check if secondary rate limit is violated. If so, then backoff and try again.
-                if let Some(retry_value) = response.headers().get(&rate_limit_headers.retry) {
-                    let interval = Duration::from_secs(
-                        retry_value.to_str()?.parse::<u64>()? + (i as u64).pow(2),
-                    );
-                    #[cfg(feature = "test-skip-wait-for-rate-limit")]
-                    {
-                        // Output a log statement to use the `interval` variable.
-                        log::warn!(
-                            "Skipped waiting {} seconds to expedite test",
-                            interval.as_secs()
-                        );
-                    }
-                    #[cfg(not(feature = "test-skip-wait-for-rate-limit"))]
-                    {
-                        tokio::time::sleep(interval).await;
-                    }
-                    continue;
-                }
+        let template = RequestTemplate::new(&request);
+        let factory;
+        let rebuild: Option<&RequestFactory<'_>> = match &template {
+            Some(template) => {
+                factory = move || Ok(template.build());
+                Some(&factory)
             }
-            return Ok(response);
-        }
-        Err(ClientError::RateLimitSecondary)
+            None => None,
+        };
+        in_span!(
+            send_with_retries(self, client, request, rebuild, rate_limit_headers),
+            "send_api_request",
+            method = request.method().as_str(),
+            url_path = request.url().path(),
+            status = tracing::field::Empty,
+            retries = tracing::field::Empty,
+        )
+        .await
+    }
+
+    /// Like [`Self::send_api_request()`], but the given `make_request` function builds
+    /// the request for each attempt.
+    ///
+    /// This allows retrying requests that cannot be rebuilt from their parts
+    /// (eg. requests with a streamed body).
+    async fn send_api_request_with(
+        &self,
+        client: &Client,
+        make_request: &RequestFactory<'_>,
+        rate_limit_headers: &RestApiRateLimitHeaders,
+    ) -> Result<Response, ClientError> {
+        let request = make_request()?;
+        in_span!(
+            send_with_retries(
+                self,
+                client,
+                request,
+                Some(make_request),
+                rate_limit_headers
+            ),
+            "send_api_request",
+            method = request.method().as_str(),
+            url_path = request.url().path(),
+            status = tracing::field::Empty,
+            retries = tracing::field::Empty,
+        )
+        .await
+    }
+
+    /// Gets the pagination links from the headers in a paginated response.
+    ///
+    /// The `url` of the request is used to resolve relative links.
+    /// See [`PageLinks`] for more detail.
+    fn page_links(&self, headers: &HeaderMap, url: &Url) -> PageLinks {
+        PageLinks::from_headers(headers, url)
     }
 
     /// Gets the URL for the next page from the headers in a paginated response.
     ///
     /// Returns [`None`] if current response is the last page.
+    /// Relative links are ignored because the URL of the request is unknown.
+    #[deprecated(note = "use `page_links()` instead")]
     fn try_next_page(&self, headers: &HeaderMap) -> Option<Url> {
-        if let Some(links) = headers.get("link")
-            && let Ok(pg_str) = links.to_str()
-        {
-            let pages = pg_str.split(", ");
-            for page in pages {
-                if page.ends_with("; rel=\"next\"") {
-                    if let Some(link) = page.split_once(">;") {
-                        let url = link.0.trim_start_matches("<").to_string();
-                        if let Ok(next) = Url::parse(&url) {
-                            return Some(next);
-                        } else {
-                            log::debug!("Failed to parse next page link from response header");
-                        }
-                    } else {
-                        log::debug!("Response header link for pagination is malformed");
-                    }
-                }
-            }
-        }
-        None
+        PageLinks::parse_headers(headers, None).next
     }
 
     /// A helper function to log the response of an API request with context.
@@ -338,23 +1071,494 @@ pub trait RestApiClient {
 
     /// The name of git server implementation is being used.
     fn client_kind(&self) -> String;
+
+    /// The kinds of feedback supported by this git server.
+    ///
+    /// Unsupported feedback is skipped (with a log message), so this is useful to
+    /// avoid preparing feedback that would not be shown.
+    /// By default, nothing is supported.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
 }
 
-/// Instantiate an implementation of [`RestApiClient`] based on the environment.
+/// Log that the given `feedback` is skipped because it is unsupported by the `api` client.
+///
+/// See [`RestApiClient::capabilities()`].
+pub(crate) fn log_unsupported<C: RestApiClient + ?Sized>(api: &C, feedback: &str) {
+    log::info!(
+        "Skipped {feedback} because it is unsupported on this server ({})",
+        api.client_kind()
+    );
+}
+
+/// Read the body of a `response` that holds the raw content of the file at `path`.
 ///
-/// This will fallback to an instance of [`LocalClient`] if
+/// Fails with a [`ClientError::BinaryFile`] error if the content is not valid UTF-8.
+#[cfg(any(feature = "gitea", feature = "github"))]
+pub(crate) async fn read_file_content(
+    response: Response,
+    path: &str,
+    task: &str,
+) -> Result<String, ClientError> {
+    let content = response
+        .bytes()
+        .await
+        .map_err(|e| ClientError::Request(e).with_context(task))?;
+    String::from_utf8(content.to_vec()).map_err(|_| ClientError::BinaryFile {
+        path: path.to_string(),
+    })
+}
+
+/// A function that builds a request for each attempt to send it.
 ///
-/// - the `GITHUB_ACTIONS` environment variable is not set
-pub fn init_client() -> Result<Box<dyn RestApiClient + Send + Sync>, ClientError> {
-    if cfg!(feature = "gitea")
-        && env::var("GITEA_ACTIONS").is_ok_and(|v| v.to_lowercase() == "true")
+/// See [`RestApiClient::send_api_request_with()`].
+pub type RequestFactory<'a> = dyn Fn() -> Result<Request, ClientError> + Send + Sync + 'a;
+
+/// The parts of a [`Request`] needed to rebuild it for each attempt to send it.
+struct RequestTemplate {
+    method: Method,
+    url: Url,
+    headers: HeaderMap,
+    body: Option<Bytes>,
+    timeout: Option<Duration>,
+    version: Version,
+}
+
+impl RequestTemplate {
+    /// Copy the parts of the given `request`.
+    ///
+    /// Returns [`None`] if the `request` body is a stream (which can only be read once).
+    fn new(request: &Request) -> Option<Self> {
+        let body = match request.body() {
+            Some(body) => Some(Bytes::copy_from_slice(body.as_bytes()?)),
+            None => None,
+        };
+        Some(Self {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            headers: request.headers().clone(),
+            body,
+            timeout: request.timeout().copied(),
+            version: request.version(),
+        })
+    }
+
+    /// Build a new request from the copied parts.
+    fn build(&self) -> Request {
+        let mut request = Request::new(self.method.clone(), self.url.clone());
+        *request.headers_mut() = self.headers.clone();
+        *request.body_mut() = self.body.clone().map(Body::from);
+        *request.timeout_mut() = self.timeout;
+        *request.version_mut() = self.version;
+        request
+    }
+}
+
+/// Consume the body of a `response` that is discarded, so the connection can be reused.
+async fn discard_response(response: Response) {
+    let _ = response.bytes().await;
+}
+
+/// Would the given `request` change something on the git server?
+///
+/// Safe requests (eg. `GET`) and GraphQL queries (`POST` requests whose `query` is not a mutation)
+/// are read-only.
+fn is_mutating(request: &Request) -> bool {
+    if request.method().is_safe() {
+        return false;
+    }
+    if request.method() != Method::POST || !request.url().path().ends_with("/graphql") {
+        return true;
+    }
+    request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .and_then(|body| serde_json::from_slice::<serde_json::Value>(body).ok())
+        .and_then(|payload| {
+            payload
+                .get("query")
+                .and_then(|q| q.as_str())
+                .map(|q| q.trim_start().starts_with("mutation"))
+        })
+        .unwrap_or(true)
+}
+
+/// Parse the integer in a rate limit header's `value`.
+fn parse_rate_limit_header(value: &HeaderValue) -> Result<i64, ClientError> {
+    value
+        .to_str()
+        .map_err(ClientError::from)
+        .and_then(|v| v.parse::<i64>().map_err(ClientError::from))
+        .map_err(|e| e.with_context("parse rate limit headers"))
+}
+
+/// The implementation of [`RestApiClient::send_api_request()`] for the given `api` client.
+///
+/// The `request` is sent first. Retries use a request from `rebuild`;
+/// if that is [`None`], then the request is not retried.
+async fn send_with_retries<C: RestApiClient + Sync + ?Sized>(
+    api: &C,
+    client: &Client,
+    request: Request,
+    rebuild: Option<&RequestFactory<'_>>,
+    rate_limit_headers: &RestApiRateLimitHeaders,
+) -> Result<Response, ClientError> {
+    if api.dry_run() && is_mutating(&request) {
+        let size = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|body| body.len())
+            .unwrap_or_default();
+        let url = request.url().clone();
+        log::info!(
+            "Dry run: would {} {} with {size} bytes",
+            request.method(),
+            url.path()
+        );
+        return rebuild_response(
+            StatusCode::NO_CONTENT,
+            Version::HTTP_11,
+            url,
+            HeaderMap::new(),
+            Vec::new(),
+        );
+    }
+    let mut retries = 0;
+    let Some(recorder) = api.request_recorder() else {
+        return send_attempts(
+            api,
+            client,
+            request,
+            rebuild,
+            rate_limit_headers,
+            &mut retries,
+        )
+        .await;
+    };
+    let method = request.method().clone();
+    let url = request.url().clone();
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .filter(|_| recorder.includes_bodies())
+        .map(<[u8]>::to_vec);
+    let started = Instant::now();
+    let result = send_attempts(
+        api,
+        client,
+        request,
+        rebuild,
+        rate_limit_headers,
+        &mut retries,
+    )
+    .await;
+    let status = result.as_ref().ok().map(|r| r.status().as_u16());
+    recorder.record(
+        &method,
+        &url,
+        status,
+        retries,
+        started.elapsed(),
+        body.as_deref(),
+    );
+    result
+}
+
+/// Send the `request` (and its retries) for [`send_with_retries()`].
+///
+/// The number of retries done so far is stored in `retries`.
+async fn send_attempts<C: RestApiClient + Sync + ?Sized>(
+    api: &C,
+    client: &Client,
+    request: Request,
+    rebuild: Option<&RequestFactory<'_>>,
+    rate_limit_headers: &RestApiRateLimitHeaders,
+    retries: &mut u8,
+) -> Result<Response, ClientError> {
+    let method = request.method().clone();
+    let url = request.url().clone();
+    let cache = api.response_cache().filter(|_| method == Method::GET);
+    let etag = cache.and_then(|cache| cache.etag(&url));
+    // the permit is held until the request (including retries) is done
+    let _permit = match api.throttle() {
+        Some(throttle) => Some(throttle.acquire(&method).await?),
+        None => None,
+    };
+    // replaying a request that may not be idempotent could duplicate its effect
+    let replayable =
+        rebuild.is_some() && (!is_mutating(&request) || api.retry_policy().retry_unsafe);
+    let skip_retry = |reason: String| ClientError::UnsafeRetry {
+        method: method.to_string(),
+        reason,
+    };
+    let mut request = Some(request);
+    let metrics = api.metrics();
+    let cancellation = api.cancellation_token();
+    let mut waited_for_reset = false;
+    // the delay most recently requested for a secondary rate limit
+    let mut retry_after = None;
+    for i in 0..MAX_RETRIES {
+        let is_last_attempt = i + 1 == MAX_RETRIES;
+        record_span_field!("retries", i);
+        *retries = i;
+        let mut attempt = match (request.take(), rebuild) {
+            (Some(request), _) => request,
+            (None, Some(rebuild)) => rebuild()?,
+            (None, None) => return Err(skip_retry("the request body cannot be replayed".into())),
+        };
+        if let Some(etag) = &etag {
+            attempt.headers_mut().insert(IF_NONE_MATCH, etag.clone());
+        }
+        let started = metrics.map(|m| {
+            if i > 0 {
+                m.on_retry(&method, i + 1);
+            }
+            m.on_request_start(&method, &url);
+            Instant::now()
+        });
+        let result = client.execute(attempt).await;
+        record_span_field!("status", result.as_ref().ok().map(|r| r.status().as_u16()));
+        if let Some(m) = metrics
+            && let Some(started) = started
+        {
+            let status = result.as_ref().ok().map(|r| r.status());
+            m.on_request_complete(&method, status, started.elapsed());
+        }
+        let response = match result {
+            Ok(response) => response,
+            Err(e) if !is_last_attempt && is_transient_error(&e) => {
+                // a failed connection means the request was never sent
+                if !replayable && (rebuild.is_none() || !e.is_connect()) {
+                    return Err(skip_retry(format!("transient failure: {e}")));
+                }
+                log::warn!("Retrying request after transient failure: {e}");
+                wait_before_retry(Duration::from_secs((i as u64).pow(2)), cancellation).await?;
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let Some(cached) = cache.and_then(|c| c.get(&url))
+        {
+            log::debug!("Using cached response for {url}");
+            return cached;
+        }
+        if replayable && !is_last_attempt && is_retryable_status(response.status()) {
+            log::warn!(
+                "Retrying request after server responded with {}",
+                response.status()
+            );
+            discard_response(response).await;
+            wait_before_retry(Duration::from_secs((i as u64).pow(2)), cancellation).await?;
+            continue;
+        }
+        if response.status().is_success()
+            && let Some(snapshot) =
+                RateLimitSnapshot::capture(response.headers(), rate_limit_headers)
+        {
+            api.record_rate_limit(snapshot, sealed::Token);
+        }
+        let detection = api.retry_policy().rate_limit_detection;
+        let maybe_rate_limited = match response.status() {
+            StatusCode::TOO_MANY_REQUESTS => true,
+            StatusCode::FORBIDDEN => {
+                detection == RateLimitDetection::Headers
+                    || response.headers().contains_key(&rate_limit_headers.retry)
+            }
+            _ => false,
+        };
+        if maybe_rate_limited {
+            // rate limit may have been exceeded
+
+            // check if primary rate limit was violated
+            let mut requests_remaining = None;
+            if let Some(remaining) = response.headers().get(&rate_limit_headers.remaining) {
+                requests_remaining = Some(parse_rate_limit_header(remaining)?);
+            } else {
+                // NOTE: I guess it is sometimes valid for a response to
+                // not include remaining rate limit attempts
+                log::debug!("Response headers do not include remaining API usage count");
+            }
+            if requests_remaining.is_some_and(|v| v <= 0) {
+                if let Some(reset_value) = response.headers().get(&rate_limit_headers.reset)
+                    && let Some(reset) = timestamp::from_unix(parse_rate_limit_header(reset_value)?)
+                {
+                    if !waited_for_reset
+                        && !is_last_attempt
+                        && let Some(interval) = api.retry_policy().wait_until_reset(reset)
+                    {
+                        if !replayable {
+                            return Err(skip_retry(format!(
+                                "primary rate limit exhausted until {}",
+                                timestamp::display(&reset)
+                            )));
+                        }
+                        log::info!("Primary rate limit exhausted; waiting until it resets");
+                        discard_response(response).await;
+                        waited_for_reset = true;
+                        if let Some(m) = metrics {
+                            m.on_rate_limit_sleep(interval);
+                        }
+                        wait_before_retry(interval, cancellation).await?;
+                        continue;
+                    }
+                    return Err(ClientError::RateLimitPrimary(reset));
+                }
+                return Err(ClientError::RateLimitNoReset);
+            }
+
+            // check if secondary rate limit is violated. If so, then backoff and try again.
+            if let Some(retry_value) = response.headers().get(&rate_limit_headers.retry) {
+                let delay = retry_value
+                    .to_str()
+                    .map_err(ClientError::from)
+                    .and_then(|value| api.retry_policy().parse_retry_after(value))
+                    .map_err(|e| e.with_context("parse rate limit headers"))?;
+                if !replayable {
+                    return Err(skip_retry(format!(
+                        "server asked to retry after {} seconds",
+                        delay.as_secs()
+                    )));
+                }
+                retry_after = Some(delay);
+                let delay = delay + Duration::from_secs((i as u64).pow(2));
+                if let Some(m) = metrics {
+                    m.on_rate_limit_sleep(delay);
+                }
+                discard_response(response).await;
+                wait_before_retry(delay, cancellation).await?;
+                continue;
+            }
+
+            if !is_last_attempt {
+                let (response, limited) = match detection {
+                    // only a `429` status gets here
+                    RateLimitDetection::StatusOnly => (response, true),
+                    // GitHub may only announce a secondary rate limit in the response body
+                    RateLimitDetection::Headers => {
+                        let (response, body) = buffer_response(response).await?;
+                        let limited = is_secondary_limit_message(&body);
+                        (response, limited)
+                    }
+                };
+                if limited {
+                    if !replayable {
+                        return Err(skip_retry("secondary rate limit exceeded".to_string()));
+                    }
+                    log::warn!("Secondary rate limit exceeded; backing off before retrying");
+                    let delay = api.retry_policy().default_delay;
+                    retry_after = Some(delay);
+                    let delay = delay + Duration::from_secs((i as u64).pow(2));
+                    if let Some(m) = metrics {
+                        m.on_rate_limit_sleep(delay);
+                    }
+                    discard_response(response).await;
+                    wait_before_retry(delay, cancellation).await?;
+                    continue;
+                }
+                return Ok(response);
+            }
+        }
+        if let Some(cache) = cache
+            && response.status().is_success()
+        {
+            return cache.insert(response).await;
+        }
+        return Ok(response);
+    }
+    Err(ClientError::RateLimitSecondary { retry_after })
+}
+
+/// CI platforms that are recognized (via the given environment variable) but not supported.
+const UNSUPPORTED_CI: &[(&str, &str)] = &[
+    #[cfg(not(feature = "gitlab"))]
+    ("GITLAB_CI", "GitLab CI"),
+    #[cfg(not(feature = "azure-devops"))]
+    ("TF_BUILD", "Azure Pipelines"),
+    ("BITBUCKET_BUILD_NUMBER", "Bitbucket Pipelines"),
+];
+
+/// Is the given environment variable set to `true` (case-insensitive)?
+fn env_is_true(name: &str) -> bool {
+    env::var(name).is_ok_and(|v| v.to_lowercase() == "true")
+}
+
+/// Instantiate the [`RestApiClient`] for the CI platform detected from the environment.
+///
+/// The most specific signal is checked first:
+///
+/// 1. Gitea, if the `GITEA_ACTIONS` environment variable is `true` or `GITEA_WORK_DIR` is set.
+///    Forgejo (detected if `FORGEJO_ACTIONS` is `true`) also uses the Gitea client.
+///    Both also set `GITHUB_ACTIONS` for compatibility with GitHub workflows.
+/// 2. GitHub, if the `GITHUB_ACTIONS` environment variable is `true`.
+/// 3. GitLab, if the `GITLAB_CI` environment variable is `true`.
+/// 4. Azure DevOps, if the `TF_BUILD` environment variable is `true`.
+///
+/// Only platforms enabled via cargo features are considered.
+/// If none is detected, then this fails with [`ClientError::UnsupportedCi`].
+/// Use [`init_client()`] to fallback to a [`LocalClient`] instead.
+///
+/// For runs outside of CI, setting the `GIT_BOT_FEEDBACK_DIR` environment variable opts in to
+/// a [`LocalClient`] that renders feedback to files in the given directory
+/// (see [`LocalClient::with_feedback_dir()`]).
+/// This only applies if no CI platform (supported or not) is detected.
+pub fn from_env() -> Result<Box<dyn RestApiClient + Send + Sync>, ClientError> {
+    let mut checked = vec![];
+    #[cfg(feature = "gitea")]
     {
-        Ok(Box::new(GiteaApiClient::new()?))
-    } else if cfg!(feature = "github")
-        && env::var("GITHUB_ACTIONS").is_ok_and(|v| v.to_lowercase() == "true")
+        if env_is_true("FORGEJO_ACTIONS")
+            || env_is_true("GITEA_ACTIONS")
+            || env::var_os("GITEA_WORK_DIR").is_some()
+        {
+            return Ok(Box::new(GiteaApiClient::new()?));
+        }
+        checked.extend(["FORGEJO_ACTIONS", "GITEA_ACTIONS", "GITEA_WORK_DIR"]);
+    }
+    #[cfg(feature = "github")]
     {
-        Ok(Box::new(GithubApiClient::new()?))
-    } else {
-        Ok(Box::new(LocalClient))
+        if env_is_true("GITHUB_ACTIONS") {
+            return Ok(Box::new(GithubApiClient::new()?));
+        }
+        checked.push("GITHUB_ACTIONS");
+    }
+    #[cfg(feature = "gitlab")]
+    {
+        if env_is_true("GITLAB_CI") {
+            return Ok(Box::new(GitlabApiClient::new()?));
+        }
+        checked.push("GITLAB_CI");
+    }
+    #[cfg(feature = "azure-devops")]
+    {
+        if env_is_true("TF_BUILD") {
+            return Ok(Box::new(AzureDevopsApiClient::new()?));
+        }
+        checked.push("TF_BUILD");
+    }
+    let detected = UNSUPPORTED_CI
+        .iter()
+        .find(|(name, _)| env::var(name).is_ok_and(|v| !v.is_empty()))
+        .map(|(_, platform)| platform.to_string());
+    checked.extend(UNSUPPORTED_CI.iter().map(|(name, _)| *name));
+    if detected.is_none()
+        && let Some(dir) = env::var_os("GIT_BOT_FEEDBACK_DIR").filter(|v| !v.is_empty())
+    {
+        return Ok(Box::new(LocalClient::with_feedback_dir(dir)));
+    }
+    Err(ClientError::UnsupportedCi {
+        detected,
+        checked: checked.into_iter().map(str::to_string).collect(),
+    })
+}
+
+/// Instantiate an implementation of [`RestApiClient`] based on the environment.
+///
+/// See [`from_env()`] about how the CI platform is detected.
+/// This will fallback to an instance of [`LocalClient`] if no supported CI platform is detected.
+pub fn init_client() -> Result<Box<dyn RestApiClient + Send + Sync>, ClientError> {
+    match from_env() {
+        Err(ClientError::UnsupportedCi { .. }) => Ok(Box::new(LocalClient::default())),
+        result => result,
     }
 }
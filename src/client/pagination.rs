@@ -0,0 +1,252 @@
+//! A module to iterate over the pages of a paginated REST API response.
+use std::marker::PhantomData;
+
+use reqwest::{Client, Method, Url};
+use serde::de::DeserializeOwned;
+
+use super::{
+    ClientError, RestApiClient, RestApiRateLimitHeaders, check_cancelled, links::page_number,
+};
+
+/// The default number of changed files requested per page.
+///
+/// See [`RestApiClient::set_changed_files_per_page()`].
+#[cfg(feature = "file-changes")]
+pub const DEFAULT_CHANGED_FILES_PER_PAGE: u32 = 100;
+
+/// Fetches the pages of a paginated REST API response, one page at a time.
+///
+/// Each page is deserialized as `T` (typically a `Vec` of items).
+/// The next page is found via the `link` header of the current page
+/// (see [`RestApiClient::page_links()`]).
+///
+/// Failures are reported per page, so the caller decides whether to continue:
+///
+/// - If a page fails to deserialize, then the next page can still be fetched.
+/// - If a request fails (or the server responds with an error status), then there are no more pages.
+///   An error status is logged along with the response body.
+///
+/// Dropping the paginator stops fetching pages.
+///
+/// ```no_run
+/// use git_bot_feedback::{
+///     RestApiRateLimitHeaders,
+///     client::{Paginator, init_client},
+///     reqwest::{Client, Url},
+/// };
+///
+/// # async fn example() -> Result<(), git_bot_feedback::RestClientError> {
+/// let api = init_client()?;
+/// let client = Client::new();
+/// let rate_limit_headers = RestApiRateLimitHeaders {
+///     reset: "x-ratelimit-reset".to_string(),
+///     remaining: "x-ratelimit-remaining".to_string(),
+///     retry: "retry-after".to_string(),
+/// };
+/// let url = Url::parse("https://api.github.com/repos/2bndy5/git-bot-feedback/issues/1/comments")?;
+/// let mut pages: Paginator<_, Vec<serde_json::Value>> =
+///     Paginator::new(&*api, &client, url, &rate_limit_headers, "list of comments").per_page(100);
+/// while let Some(page) = pages.next_page().await {
+///     log::info!("Found {} comments", page?.len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Paginator<'a, C: ?Sized, T> {
+    api: &'a C,
+    client: &'a Client,
+    rate_limit_headers: &'a RestApiRateLimitHeaders,
+    subject: String,
+    next_url: Option<Url>,
+    page: PhantomData<fn() -> T>,
+}
+
+impl<'a, C: ?Sized, T> Paginator<'a, C, T> {
+    /// Create a paginator that starts at the first page of the given `url`.
+    ///
+    /// The `api` client is used to send requests with the given `client`.
+    /// The `subject` describes what is being listed (eg. "list of existing thread comments");
+    /// it is used as context in errors and logs.
+    pub fn new(
+        api: &'a C,
+        client: &'a Client,
+        mut url: Url,
+        rate_limit_headers: &'a RestApiRateLimitHeaders,
+        subject: &str,
+    ) -> Self {
+        url.query_pairs_mut().append_pair("page", "1");
+        Self {
+            api,
+            client,
+            rate_limit_headers,
+            subject: subject.to_string(),
+            next_url: Some(url),
+            page: PhantomData,
+        }
+    }
+
+    /// Request the given number of items per page.
+    ///
+    /// Git servers limit the number of items per page (eg. GitHub allows at most `100`).
+    pub fn per_page(mut self, per_page: u32) -> Self {
+        if let Some(url) = &mut self.next_url {
+            url.query_pairs_mut()
+                .append_pair("per_page", per_page.to_string().as_str());
+        }
+        self
+    }
+}
+
+impl<C, T> Paginator<'_, C, T>
+where
+    C: RestApiClient + Sync + ?Sized,
+    T: DeserializeOwned,
+{
+    /// Fetch the next page.
+    ///
+    /// Returns [`None`] if there are no more pages.
+    pub async fn next_page(&mut self) -> Option<Result<T, ClientError>> {
+        let url = self.next_url.take()?;
+        Some(self.fetch(url).await)
+    }
+
+    async fn fetch(&mut self, url: Url) -> Result<T, ClientError> {
+        let task = format!("get {}", self.subject);
+        check_cancelled(self.api.cancellation_token(), &task)?;
+        let request = self
+            .api
+            .make_api_request(self.client, url, Method::GET, None, None)?;
+        let response = self
+            .api
+            .send_api_request(self.client, request, self.rate_limit_headers)
+            .await
+            .map_err(|e| e.with_context(&task))?;
+        if let Err(e) = response.error_for_status_ref() {
+            if let Ok(body) = response.text().await {
+                log::error!("Failed to {task}: {e:?}\n{body}");
+            }
+            return Err(ClientError::Request(e).with_context(&task));
+        }
+        let links = self.api.page_links(response.headers(), response.url());
+        if let Some(page) = page_number(response.url()) {
+            match links.last_page() {
+                Some(last) => log::debug!("Got page {page} of {last} for {}", self.subject),
+                None => log::debug!("Got page {page} for {}", self.subject),
+            }
+        }
+        self.next_url = links.next;
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ClientError::Request(e).with_context(&task))?;
+        serde_json::from_str::<T>(&body)
+            .map_err(|e| ClientError::json(format!("deserialize {}", self.subject).as_str(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use mockito::{Matcher, Mock, Server, ServerGuard};
+    use reqwest::{Client, Url};
+
+    use super::{ClientError, Paginator};
+    use crate::{RestApiRateLimitHeaders, client::LocalClient};
+
+    fn rate_limit_headers() -> RestApiRateLimitHeaders {
+        RestApiRateLimitHeaders {
+            reset: "x-ratelimit-reset".to_string(),
+            remaining: "x-ratelimit-remaining".to_string(),
+            retry: "retry-after".to_string(),
+        }
+    }
+
+    /// Mock 3 pages of numbers, where the given `bad_page` (if any) has a malformed body.
+    fn mock_pages(server: &mut ServerGuard, bad_page: Option<u8>) -> Vec<Mock> {
+        (1..=3u8)
+            .map(|page| {
+                let mut mock = server
+                    .mock("GET", "/items")
+                    .match_query(Matcher::AllOf(vec![
+                        Matcher::UrlEncoded("page".to_string(), page.to_string()),
+                        Matcher::UrlEncoded("per_page".to_string(), "2".to_string()),
+                    ]))
+                    .with_body(if bad_page == Some(page) {
+                        "not json".to_string()
+                    } else {
+                        format!("[{}, {}]", page * 2 - 1, page * 2)
+                    });
+                if page < 3 {
+                    let next = format!(
+                        "<{}/items?page={}&per_page=2>; rel=\"next\"",
+                        server.url(),
+                        page + 1
+                    );
+                    mock = mock.with_header("link", next.as_str());
+                }
+                mock.create()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn all_pages() {
+        let mut server = Server::new_async().await;
+        let mocks = mock_pages(&mut server, None);
+        let client = Client::new();
+        let headers = rate_limit_headers();
+        let url = Url::parse(&format!("{}/items", server.url())).unwrap();
+        let api = LocalClient::default();
+        let mut pages: Paginator<_, Vec<u8>> =
+            Paginator::new(&api, &client, url, &headers, "list of items").per_page(2);
+        let mut items: Vec<u8> = vec![];
+        while let Some(page) = pages.next_page().await {
+            items.extend(page.unwrap());
+        }
+        assert_eq!(items, [1, 2, 3, 4, 5, 6]);
+        for mock in mocks {
+            mock.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn error_on_second_page() {
+        let mut server = Server::new_async().await;
+        let mocks = mock_pages(&mut server, Some(2));
+        let client = Client::new();
+        let headers = rate_limit_headers();
+        let url = Url::parse(&format!("{}/items", server.url())).unwrap();
+        let api = LocalClient::default();
+        let mut pages: Paginator<_, Vec<u8>> =
+            Paginator::new(&api, &client, url, &headers, "list of items").per_page(2);
+        assert_eq!(pages.next_page().await.unwrap().unwrap(), [1, 2]);
+        let err = pages.next_page().await.unwrap().unwrap_err();
+        assert!(
+            matches!(err, ClientError::Json { task, .. } if task == "deserialize list of items")
+        );
+        // the caller may continue after a malformed page
+        assert_eq!(pages.next_page().await.unwrap().unwrap(), [5, 6]);
+        assert!(pages.next_page().await.is_none());
+        for mock in mocks {
+            mock.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn drop_early() {
+        let mut server = Server::new_async().await;
+        let mocks = mock_pages(&mut server, None);
+        let client = Client::new();
+        let headers = rate_limit_headers();
+        let url = Url::parse(&format!("{}/items", server.url())).unwrap();
+        let api = LocalClient::default();
+        let mut pages: Paginator<_, Vec<u8>> =
+            Paginator::new(&api, &client, url, &headers, "list of items").per_page(2);
+        assert_eq!(pages.next_page().await.unwrap().unwrap(), [1, 2]);
+        drop(pages);
+        mocks[0].assert();
+        for mock in &mocks[1..] {
+            assert!(!mock.matched());
+        }
+    }
+}
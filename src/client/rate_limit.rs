@@ -0,0 +1,89 @@
+//! A module to describe the rate limit status of a git server's REST API.
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::HeaderMap;
+
+use super::{RestApiRateLimitHeaders, Timestamp, lock_ignoring_poison, timestamp};
+
+/// The rate limit of a single API resource (eg. the REST API or the GraphQL API).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitResource {
+    /// The maximum number of requests allowed per rate limit window.
+    pub limit: u64,
+
+    /// The number of requests remaining in the current rate limit window.
+    pub remaining: u64,
+
+    /// The time when the current rate limit window resets.
+    pub reset: Timestamp,
+}
+
+/// The rate limit status reported by
+/// [`RestApiClient::rate_limit_status()`](crate::RestApiClient::rate_limit_status).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// The rate limit of the REST API.
+    pub core: RateLimitResource,
+
+    /// The rate limit of the GraphQL API, if the git server has one.
+    pub graphql: Option<RateLimitResource>,
+}
+
+/// The rate limit headers captured from the most recent successful response.
+///
+/// See [`RestApiClient::last_rate_limit()`](crate::RestApiClient::last_rate_limit).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitSnapshot {
+    /// The number of requests remaining in the current rate limit window.
+    pub remaining: Option<i64>,
+
+    /// The time when the current rate limit window resets.
+    pub reset: Option<Timestamp>,
+
+    /// The time when this snapshot was captured.
+    pub captured_at: Timestamp,
+}
+
+impl RateLimitSnapshot {
+    /// Capture a snapshot from the given response `headers`.
+    ///
+    /// Malformed header values are ignored.
+    /// Returns [`None`] if the `headers` do not describe the rate limit.
+    pub(crate) fn capture(
+        headers: &HeaderMap,
+        rate_limit_headers: &RestApiRateLimitHeaders,
+    ) -> Option<Self> {
+        let remaining = headers
+            .get(&rate_limit_headers.remaining)
+            .and_then(|v| v.to_str().ok()?.parse::<i64>().ok());
+        let reset = headers
+            .get(&rate_limit_headers.reset)
+            .and_then(|v| timestamp::from_unix(v.to_str().ok()?.parse::<i64>().ok()?));
+        if remaining.is_none() && reset.is_none() {
+            return None;
+        }
+        Some(Self {
+            remaining,
+            reset,
+            captured_at: timestamp::now(),
+        })
+    }
+}
+
+/// Holds the [`RateLimitSnapshot`] recorded from the most recent successful response.
+///
+/// Clones share the same snapshot.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RateLimitSlot(Arc<Mutex<Option<RateLimitSnapshot>>>);
+
+impl RateLimitSlot {
+    /// Get the recorded snapshot (if any).
+    pub(crate) fn get(&self) -> Option<RateLimitSnapshot> {
+        *lock_ignoring_poison(&self.0)
+    }
+
+    /// Record the given `snapshot`, replacing any previous one.
+    pub(crate) fn set(&self, snapshot: RateLimitSnapshot) {
+        *lock_ignoring_poison(&self.0) = Some(snapshot);
+    }
+}
@@ -0,0 +1,191 @@
+//! A module to keep an audit trail of the REST API requests sent by a client.
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+use reqwest::{Method, Url};
+use serde::Serialize;
+
+use super::{ClientError, lock_ignoring_poison};
+
+/// Query parameters whose values are never recorded.
+const SENSITIVE_QUERY_PARAMS: [&str; 6] =
+    ["token", "access_token", "key", "secret", "sig", "password"];
+
+/// A line written by a [`RequestRecorder`].
+#[derive(Debug, Serialize)]
+struct RequestRecord<'a> {
+    /// Milliseconds since the unix epoch when the request was done.
+    timestamp_ms: u64,
+    method: &'a str,
+    /// The URL's path and (redacted) query.
+    path: String,
+    /// [`None`] if no response was received.
+    status: Option<u16>,
+    retries: u8,
+    elapsed_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<&'a str>,
+}
+
+/// Appends a JSON line to a file for every request sent by
+/// [`RestApiClient::send_api_request()`](crate::RestApiClient::send_api_request).
+///
+/// Each line describes a request (including its retries) with the following fields:
+///
+/// | field | description |
+/// |-------|-------------|
+/// | `timestamp_ms` | When the request was done (milliseconds since the unix epoch). |
+/// | `method` | The HTTP method (eg. `"PATCH"`). |
+/// | `path` | The URL's path and query. The values of sensitive query parameters (eg. `token`) are redacted. |
+/// | `status` | The status code of the response, or `null` if no response was received. |
+/// | `retries` | The number of times the request was retried. |
+/// | `elapsed_ms` | The time spent on the request (including retries) in milliseconds. |
+/// | `body` | The request's body; only recorded if [`Self::include_bodies()`] is enabled. |
+///
+/// Headers are never recorded.
+/// Failing to write a line is logged as a warning; it does not fail the request.
+///
+/// Use [`RestApiClient::set_request_recorder()`](crate::RestApiClient::set_request_recorder)
+/// to record the requests of a client.
+///
+/// ```no_run
+/// use git_bot_feedback::{RestApiClient, client::{RequestRecorder, init_client}};
+///
+/// # fn example() -> Result<(), git_bot_feedback::RestClientError> {
+/// let mut client = init_client()?;
+/// client.set_request_recorder(Some(RequestRecorder::new("requests.jsonl")?));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RequestRecorder {
+    path: PathBuf,
+    file: Mutex<File>,
+    include_bodies: bool,
+}
+
+impl RequestRecorder {
+    /// Create a recorder that appends to the file at the given `path`.
+    ///
+    /// The file is created if it does not exist.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, ClientError> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| {
+                ClientError::io(
+                    format!("open request record file {}", path.display()).as_str(),
+                    e,
+                )
+            })?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            include_bodies: false,
+        })
+    }
+
+    /// Also record the body of each request.
+    ///
+    /// # Security
+    ///
+    /// Request bodies may contain sensitive information (eg. the content of private files).
+    /// Only enable this when the recorded file is kept private.
+    pub fn include_bodies(mut self, include: bool) -> Self {
+        self.include_bodies = include;
+        self
+    }
+
+    /// Are the bodies of requests recorded?
+    pub fn includes_bodies(&self) -> bool {
+        self.include_bodies
+    }
+
+    /// The path to the file that requests are recorded in.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a line that describes a request.
+    pub(crate) fn record(
+        &self,
+        method: &Method,
+        url: &Url,
+        status: Option<u16>,
+        retries: u8,
+        elapsed: Duration,
+        body: Option<&[u8]>,
+    ) {
+        let body = body
+            .filter(|_| self.include_bodies)
+            .map(String::from_utf8_lossy);
+        let record = RequestRecord {
+            timestamp_ms: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            method: method.as_str(),
+            path: redact(url),
+            status,
+            retries,
+            elapsed_ms: elapsed.as_millis() as u64,
+            body: body.as_deref(),
+        };
+        let line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                log::warn!("Failed to serialize request record: {e}");
+                return;
+            }
+        };
+        let mut file = lock_ignoring_poison(&self.file);
+        if let Err(e) = writeln!(file, "{line}").and_then(|_| file.flush()) {
+            log::warn!("Failed to record request in {}: {e}", self.path.display());
+        }
+    }
+}
+
+/// Get the path and query of the given `url` with the values of sensitive
+/// query parameters redacted.
+fn redact(url: &Url) -> String {
+    let mut pairs = url.query_pairs().peekable();
+    if pairs.peek().is_none() {
+        return url.path().to_string();
+    }
+    let query = pairs
+        .map(|(name, value)| {
+            if SENSITIVE_QUERY_PARAMS.contains(&name.to_ascii_lowercase().as_str()) {
+                format!("{name}=REDACTED")
+            } else {
+                format!("{name}={value}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{}?{query}", url.path())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use reqwest::Url;
+
+    use super::redact;
+
+    #[test]
+    fn redact_query() {
+        let url = Url::parse("https://api.github.com/repos/o/r/pulls?page=2").unwrap();
+        assert_eq!(redact(&url), "/repos/o/r/pulls?page=2");
+        let url = Url::parse("https://gitea.com/api/v1/repos?access_token=abc&page=1").unwrap();
+        assert_eq!(redact(&url), "/api/v1/repos?access_token=REDACTED&page=1");
+        let url = Url::parse("https://api.github.com/graphql").unwrap();
+        assert_eq!(redact(&url), "/graphql");
+    }
+}
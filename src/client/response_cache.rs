@@ -0,0 +1,183 @@
+//! A module to cache the responses of GET requests by their ETag.
+use std::{collections::VecDeque, sync::Mutex};
+
+use reqwest::{
+    Response, StatusCode, Url, Version,
+    header::{ETAG, HeaderMap, HeaderValue},
+};
+
+use super::{ClientError, lock_ignoring_poison, rebuild_response};
+
+/// A response stored in the [`ResponseCache`].
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    url: Url,
+    etag: HeaderValue,
+    version: Version,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+/// A bounded, in-memory cache of successful GET responses.
+///
+/// When a client has a cache (see
+/// [`RestApiClient::set_response_cache()`](crate::RestApiClient::set_response_cache)),
+/// [`RestApiClient::send_api_request()`](crate::RestApiClient::send_api_request)
+/// sends the cached ETag of a URL in the `If-None-Match` header.
+/// If the server responds with `304 Not Modified`, then the cached response is returned instead.
+/// On GitHub, a `304` response does not count against the rate limit.
+///
+/// When the cache is full, the least recently used response is evicted.
+#[derive(Debug)]
+pub struct ResponseCache {
+    capacity: usize,
+    entries: Mutex<VecDeque<CachedResponse>>,
+}
+
+impl ResponseCache {
+    /// Create a cache that holds at most `capacity` responses.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// The number of responses in the cache.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Is the cache empty?
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Remove all responses from the cache.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<CachedResponse>> {
+        lock_ignoring_poison(&self.entries)
+    }
+
+    /// Get the ETag of the cached response for the given `url`.
+    pub(crate) fn etag(&self, url: &Url) -> Option<HeaderValue> {
+        self.lock()
+            .iter()
+            .find(|entry| entry.url == *url)
+            .map(|entry| entry.etag.clone())
+    }
+
+    /// Get the cached response for the given `url` (and mark it as recently used).
+    pub(crate) fn get(&self, url: &Url) -> Option<Result<Response, ClientError>> {
+        let mut entries = self.lock();
+        let index = entries.iter().position(|entry| entry.url == *url)?;
+        let entry = entries.remove(index)?;
+        let response = rebuild_response(
+            StatusCode::OK,
+            entry.version,
+            entry.url.clone(),
+            entry.headers.clone(),
+            entry.body.clone(),
+        );
+        entries.push_back(entry);
+        Some(response)
+    }
+
+    /// Cache the given successful `response`, if it has an ETag.
+    ///
+    /// Returns the `response` (with its body intact).
+    pub(crate) async fn insert(&self, response: Response) -> Result<Response, ClientError> {
+        let Some(etag) = response.headers().get(ETAG).cloned() else {
+            return Ok(response);
+        };
+        if self.capacity == 0 {
+            return Ok(response);
+        }
+        let (response, body) = super::buffer_response(response).await?;
+        let url = response.url().clone();
+        let mut entries = self.lock();
+        entries.retain(|entry| entry.url != url);
+        while entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(CachedResponse {
+            url,
+            etag,
+            version: response.version(),
+            headers: response.headers().clone(),
+            body,
+        });
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use reqwest::{
+        Response, StatusCode, Url, Version,
+        header::{ETAG, HeaderMap, HeaderValue},
+    };
+
+    use super::{ResponseCache, rebuild_response};
+
+    fn response(url: &str, etag: Option<&'static str>) -> Response {
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = etag {
+            headers.insert(ETAG, HeaderValue::from_static(etag));
+        }
+        rebuild_response(
+            StatusCode::OK,
+            Version::HTTP_11,
+            Url::parse(url).unwrap(),
+            headers,
+            url.as_bytes().to_vec(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn evict_least_recently_used() {
+        let cache = ResponseCache::new(2);
+        for (url, etag) in [
+            ("https://example.com/a", "\"a\""),
+            ("https://example.com/b", "\"b\""),
+        ] {
+            let response = cache.insert(response(url, Some(etag))).await.unwrap();
+            // the body is still available to the caller
+            assert_eq!(response.text().await.unwrap(), url);
+        }
+        let url_a = Url::parse("https://example.com/a").unwrap();
+        // using "a" makes "b" the least recently used
+        let cached = cache.get(&url_a).unwrap().unwrap();
+        assert_eq!(cached.text().await.unwrap(), url_a.as_str());
+
+        cache
+            .insert(response("https://example.com/c", Some("\"c\"")))
+            .await
+            .unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.etag(&url_a).unwrap(), "\"a\"");
+        assert!(
+            cache
+                .etag(&Url::parse("https://example.com/b").unwrap())
+                .is_none()
+        );
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn skip_without_etag() {
+        let cache = ResponseCache::new(2);
+        cache
+            .insert(response("https://example.com/a", None))
+            .await
+            .unwrap();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,223 @@
+//! A module to describe how REST API requests are retried.
+use std::time::Duration;
+
+use super::{ClientError, Timestamp, timestamp};
+
+/// What to do when the primary rate limit is exhausted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrimaryLimit {
+    /// Return a [`RestClientError::RateLimitPrimary`](crate::RestClientError::RateLimitPrimary) error.
+    #[default]
+    Fail,
+
+    /// Wait until the rate limit resets, then retry the request once.
+    ///
+    /// If the reset time is later than `max_wait`, then only `max_wait` is waited.
+    WaitUntilReset {
+        /// The longest time to wait for the rate limit to reset.
+        max_wait: Duration,
+    },
+}
+
+/// How [`RestApiClient::send_api_request()`](crate::RestApiClient::send_api_request)
+/// recognizes a response to a request that exceeded a rate limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RateLimitDetection {
+    /// A `403 Forbidden` or `429 Too Many Requests` response is inspected for
+    /// rate limit headers and GitHub's secondary rate limit message.
+    ///
+    /// A response without either is returned to the caller.
+    #[default]
+    Headers,
+
+    /// Only a `429 Too Many Requests` response is a rate limit;
+    /// it is retried after [`RetryPolicy::default_delay`] if it has no `retry-after` header.
+    ///
+    /// A `403 Forbidden` response is returned to the caller immediately (usually to surface
+    /// a permissions problem) unless it has a `retry-after` header.
+    /// This suits servers that do not send rate limit headers (eg. most self-hosted Gitea instances).
+    StatusOnly,
+}
+
+/// Options that control how [`RestApiClient::send_api_request()`](crate::RestApiClient::send_api_request)
+/// retries a request.
+///
+/// Use [`RestApiClient::set_retry_policy()`](crate::RestApiClient::set_retry_policy) to change
+/// the policy of a client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The longest time to wait when a server asks to retry a request later
+    /// (via the `retry-after` header).
+    ///
+    /// Defaults to 60 seconds.
+    pub max_retry_after: Duration,
+
+    /// What to do when the primary rate limit is exhausted.
+    ///
+    /// Defaults to [`PrimaryLimit::Fail`].
+    pub on_primary_limit: PrimaryLimit,
+
+    /// The time to wait when a secondary rate limit is announced without a `retry-after` header.
+    ///
+    /// Defaults to 60 seconds (as recommended by GitHub's REST API docs).
+    pub default_delay: Duration,
+
+    /// Allow retrying requests that may not be idempotent (eg. `POST` or `DELETE` requests).
+    ///
+    /// By default, only safe requests (eg. `GET` or `HEAD` requests) and GraphQL queries
+    /// (`POST` requests whose `query` is not a mutation) are retried.
+    /// Other requests are only retried if they failed to connect to the server
+    /// (meaning the request was never sent).
+    /// Replaying a mutating request could otherwise duplicate its effect
+    /// (eg. posting the same comment twice).
+    ///
+    /// Defaults to `false`.
+    pub retry_unsafe: bool,
+
+    /// How a response to a request that exceeded a rate limit is recognized.
+    ///
+    /// Defaults to [`RateLimitDetection::Headers`]
+    /// ([`RateLimitDetection::StatusOnly`] for the Gitea client).
+    pub rate_limit_detection: RateLimitDetection,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retry_after: Duration::from_secs(60),
+            on_primary_limit: PrimaryLimit::default(),
+            default_delay: Duration::from_secs(60),
+            retry_unsafe: false,
+            rate_limit_detection: RateLimitDetection::default(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Parse the value of a `retry-after` header into the time to wait before retrying.
+    ///
+    /// The `value` can either be a number of seconds or an HTTP-date (as described by
+    /// [RFC 7231](https://www.rfc-editor.org/rfc/rfc7231#section-7.1.3)).
+    /// A date is relative to the current time; a date in the past yields no delay.
+    /// The result is clamped to [`Self::max_retry_after`].
+    pub(crate) fn parse_retry_after(&self, value: &str) -> Result<Duration, ClientError> {
+        let value = value.trim();
+        let delay = match value.parse::<u64>() {
+            Ok(secs) => {
+                log::debug!("Parsed retry-after header as seconds: {secs}");
+                Duration::from_secs(secs)
+            }
+            Err(e) => {
+                let date = timestamp::parse_http_date(value).ok_or(e)?;
+                log::debug!(
+                    "Parsed retry-after header as an HTTP-date: {}",
+                    timestamp::display(&date)
+                );
+                timestamp::until(date)
+            }
+        };
+        Ok(delay.min(self.max_retry_after))
+    }
+
+    /// Get the time to wait for the primary rate limit to `reset`.
+    ///
+    /// Returns [`None`] if the policy is to fail instead of waiting.
+    pub(crate) fn wait_until_reset(&self, reset: Timestamp) -> Option<Duration> {
+        match self.on_primary_limit {
+            PrimaryLimit::Fail => None,
+            PrimaryLimit::WaitUntilReset { max_wait } => {
+                Some(timestamp::until(reset).min(max_wait))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use std::time::Duration;
+
+    #[cfg(feature = "chrono")]
+    use chrono::{TimeDelta, Utc};
+
+    use super::{ClientError, PrimaryLimit, RetryPolicy, timestamp};
+
+    #[test]
+    fn retry_after_seconds() {
+        let policy = RetryPolicy::default();
+        assert_eq!(
+            policy.parse_retry_after("30").unwrap(),
+            Duration::from_secs(30)
+        );
+        // clamped to the maximum
+        assert_eq!(
+            policy.parse_retry_after("3600").unwrap(),
+            policy.max_retry_after
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn retry_after_date() {
+        let policy = RetryPolicy {
+            max_retry_after: Duration::from_secs(120),
+            ..Default::default()
+        };
+        let date = (Utc::now() + TimeDelta::seconds(90)).to_rfc2822();
+        let delay = policy
+            .parse_retry_after(&date.replace("+0000", "GMT"))
+            .unwrap();
+        assert!(delay > Duration::from_secs(80) && delay <= Duration::from_secs(90));
+
+        let date = (Utc::now() + TimeDelta::hours(1)).to_rfc2822();
+        assert_eq!(
+            policy.parse_retry_after(&date).unwrap(),
+            policy.max_retry_after
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn retry_after_past_date() {
+        let policy = RetryPolicy::default();
+        assert_eq!(
+            policy
+                .parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT")
+                .unwrap(),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn retry_after_invalid() {
+        let policy = RetryPolicy::default();
+        assert!(matches!(
+            policy.parse_retry_after("soon"),
+            Err(ClientError::HeaderParseInt(_))
+        ));
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    #[test]
+    fn retry_after_date_needs_chrono() {
+        let policy = RetryPolicy::default();
+        assert!(matches!(
+            policy.parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Err(ClientError::HeaderParseInt(_))
+        ));
+    }
+
+    #[test]
+    fn wait_until_reset() {
+        let reset = timestamp::now() + Duration::from_secs(3600);
+        assert!(RetryPolicy::default().wait_until_reset(reset).is_none());
+        let max_wait = Duration::from_secs(300);
+        let policy = RetryPolicy {
+            on_primary_limit: PrimaryLimit::WaitUntilReset { max_wait },
+            ..Default::default()
+        };
+        assert_eq!(policy.wait_until_reset(reset), Some(max_wait));
+        let past = timestamp::now() - Duration::from_secs(60);
+        assert_eq!(policy.wait_until_reset(past), Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,92 @@
+//! A module to limit how many REST API requests are sent at once.
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use reqwest::Method;
+use tokio::sync::{Mutex, Semaphore, SemaphorePermit};
+
+use super::{ClientError, timer};
+
+/// The default number of requests that may be sent at once.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// Limits the number of REST API requests that a client sends at once.
+///
+/// Sending many (mutating) requests at once can trigger a git server's abuse detection
+/// (eg. GitHub's secondary rate limits).
+/// [`RestApiClient::send_api_request()`](crate::RestApiClient::send_api_request) waits
+/// for a permit from this throttle before sending a request, and keeps the permit until
+/// the request (including any retries) is done.
+///
+/// Use [`RestApiClient::set_throttle()`](crate::RestApiClient::set_throttle) to change
+/// the throttle of a client.
+#[derive(Debug)]
+pub struct Throttle {
+    semaphore: Semaphore,
+    max_concurrent: usize,
+    mutation_delay: Option<Duration>,
+    last_mutation: Mutex<Option<Instant>>,
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONCURRENT_REQUESTS, None)
+    }
+}
+
+impl Throttle {
+    /// Create a throttle that allows `max_concurrent` requests at once.
+    ///
+    /// If `mutation_delay` is given, then mutating requests (`POST`, `PUT`, `PATCH` and `DELETE`)
+    /// are sent at least `mutation_delay` apart.
+    /// A `max_concurrent` of `0` is treated as `1`.
+    pub fn new(max_concurrent: usize, mutation_delay: Option<Duration>) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            semaphore: Semaphore::new(max_concurrent),
+            max_concurrent,
+            mutation_delay,
+            last_mutation: Mutex::new(None),
+        }
+    }
+
+    /// The number of requests that may be sent at once.
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    /// The minimum time between mutating requests.
+    pub fn mutation_delay(&self) -> Option<Duration> {
+        self.mutation_delay
+    }
+
+    /// Wait until a request with the given `method` may be sent.
+    ///
+    /// The request may be sent for as long as the returned permit is held.
+    pub(crate) async fn acquire(
+        &self,
+        method: &Method,
+    ) -> Result<SemaphorePermit<'_>, ClientError> {
+        let permit = self
+            .semaphore
+            .acquire()
+            .await
+            .map_err(|e| ClientError::io("acquire request permit", io::Error::other(e)))?;
+        if let Some(delay) = self.mutation_delay
+            && [Method::POST, Method::PUT, Method::PATCH, Method::DELETE].contains(method)
+        {
+            // holding the lock while waiting ensures mutations are spaced out in order
+            let mut last_mutation = self.last_mutation.lock().await;
+            if let Some(last) = *last_mutation {
+                let elapsed = last.elapsed();
+                if elapsed < delay {
+                    timer::sleep(delay - elapsed).await;
+                }
+            }
+            *last_mutation = Some(Instant::now());
+        }
+        Ok(permit)
+    }
+}
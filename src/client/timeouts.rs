@@ -0,0 +1,105 @@
+//! A module to describe how long REST API requests may take.
+use std::time::Duration;
+
+use super::{ClientError, timer};
+
+/// Limits on how long REST API requests may take.
+///
+/// Use [`RestApiClient::set_timeouts()`](crate::RestApiClient::set_timeouts) to change
+/// the timeouts of a client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// The longest time to wait for a connection to the git server.
+    ///
+    /// Defaults to 10 seconds.
+    pub connect: Duration,
+
+    /// The longest time to wait for a single request to complete.
+    ///
+    /// Defaults to 30 seconds.
+    pub request: Duration,
+
+    /// The longest time that a composite operation (eg.
+    /// [`RestApiClient::post_thread_comment()`](crate::RestApiClient::post_thread_comment))
+    /// may take, including all its requests and retries.
+    ///
+    /// Defaults to [`None`] (no deadline).
+    pub deadline: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_secs(10),
+            request: Duration::from_secs(30),
+            deadline: None,
+        }
+    }
+}
+
+impl Timeouts {
+    /// Run the given `operation`, failing with [`ClientError::Timeout`] if it exceeds the
+    /// [`Self::deadline`].
+    ///
+    /// Errors from the `operation` are given the `task` as context (see [`ClientError::with_context()`]).
+    pub(crate) async fn with_deadline<T>(
+        &self,
+        task: &str,
+        operation: impl Future<Output = Result<T, ClientError>>,
+    ) -> Result<T, ClientError> {
+        match self.deadline {
+            Some(deadline) => {
+                timer::timeout(deadline, operation)
+                    .await
+                    .ok_or_else(|| ClientError::Timeout {
+                        task: task.to_string(),
+                        deadline,
+                    })?
+            }
+            None => operation.await,
+        }
+        .map_err(|e| e.with_context(task))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use std::time::Duration;
+
+    use super::{ClientError, Timeouts, timer};
+
+    #[tokio::test]
+    async fn deadline_exceeded() {
+        let timeouts = Timeouts {
+            deadline: Some(Duration::from_millis(10)),
+            ..Default::default()
+        };
+        let result = timeouts
+            .with_deadline("wait", async {
+                timer::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            })
+            .await;
+        assert!(matches!(result, Err(ClientError::Timeout { task, .. }) if task == "wait"));
+    }
+
+    #[tokio::test]
+    async fn deadline_met() {
+        let timeouts = Timeouts::default();
+        let result = timeouts.with_deadline("compute", async { Ok(42) }).await;
+        assert_eq!(result.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn operation_error_has_context() {
+        let timeouts = Timeouts::default();
+        let result: Result<(), _> = timeouts
+            .with_deadline("parse", async { Err(ClientError::RateLimitNoReset) })
+            .await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Failed to parse: Primary Rate Limit exceeded (no reset time provided)"
+        );
+    }
+}
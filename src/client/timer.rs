@@ -0,0 +1,63 @@
+//! A module to wait for a time interval without requiring a specific async runtime.
+use std::{future::poll_fn, pin::pin, task::Poll, time::Duration};
+
+/// Wait for the given `duration`.
+///
+/// With the `tokio` feature (enabled by default), this uses tokio's timer,
+/// which requires a tokio runtime.
+/// Otherwise, a thread is spawned to wake the waiting task after the `duration`,
+/// which works with any async runtime (eg. async-std or smol).
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(feature = "tokio")]
+    tokio::time::sleep(duration).await;
+    #[cfg(not(feature = "tokio"))]
+    {
+        if duration.is_zero() {
+            return;
+        }
+        let (wake, woken) = tokio::sync::oneshot::channel::<()>();
+        std::thread::spawn(move || {
+            std::thread::sleep(duration);
+            // the waiting task may have been dropped (eg. cancelled)
+            let _ = wake.send(());
+        });
+        let _ = woken.await;
+    }
+}
+
+/// Run the given `operation` for at most the given `duration`.
+///
+/// Returns [`None`] if the `operation` did not finish in time.
+pub(crate) async fn timeout<F: Future>(duration: Duration, operation: F) -> Option<F::Output> {
+    let mut operation = pin!(operation);
+    let mut expired = pin!(sleep(duration));
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = operation.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        expired.as_mut().poll(cx).map(|_| None)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{sleep, timeout};
+
+    #[tokio::test]
+    async fn sleep_waits() {
+        let start = Instant::now();
+        sleep(Duration::from_millis(20)).await;
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn timeout_expires() {
+        let result = timeout(Duration::from_millis(10), sleep(Duration::from_secs(5))).await;
+        assert!(result.is_none());
+        let result = timeout(Duration::from_secs(5), async { 42 }).await;
+        assert_eq!(result, Some(42));
+    }
+}
@@ -0,0 +1,116 @@
+//! A module to represent points in time with or without the `chrono` feature.
+use std::{fmt::Display, time::Duration};
+
+/// A point in time, as reported by a git server (eg. when a rate limit resets).
+///
+/// With the `chrono` feature (enabled by default), this is a [`chrono::DateTime`] in UTC.
+/// Otherwise, this is a [`std::time::SystemTime`].
+#[cfg(feature = "chrono")]
+pub type Timestamp = chrono::DateTime<chrono::Utc>;
+
+/// A point in time, as reported by a git server (eg. when a rate limit resets).
+///
+/// With the `chrono` feature (enabled by default), this is a `chrono::DateTime` in UTC.
+/// Otherwise, this is a [`std::time::SystemTime`].
+#[cfg(not(feature = "chrono"))]
+pub type Timestamp = std::time::SystemTime;
+
+/// The [`Timestamp`] of the unix epoch (1970-01-01 00:00:00 UTC).
+#[cfg(feature = "chrono")]
+pub(crate) const UNIX_EPOCH: Timestamp = chrono::DateTime::<chrono::Utc>::UNIX_EPOCH;
+#[cfg(not(feature = "chrono"))]
+pub(crate) const UNIX_EPOCH: Timestamp = std::time::SystemTime::UNIX_EPOCH;
+
+/// Get the current [`Timestamp`].
+pub(crate) fn now() -> Timestamp {
+    #[cfg(feature = "chrono")]
+    return chrono::Utc::now();
+    #[cfg(not(feature = "chrono"))]
+    std::time::SystemTime::now()
+}
+
+/// Convert the given number of seconds since the unix epoch into a [`Timestamp`].
+///
+/// Returns [`None`] if the value is out of range.
+pub(crate) fn from_unix(secs: i64) -> Option<Timestamp> {
+    #[cfg(feature = "chrono")]
+    return chrono::DateTime::from_timestamp(secs, 0);
+    #[cfg(not(feature = "chrono"))]
+    {
+        let offset = Duration::from_secs(secs.unsigned_abs());
+        if secs < 0 {
+            UNIX_EPOCH.checked_sub(offset)
+        } else {
+            UNIX_EPOCH.checked_add(offset)
+        }
+    }
+}
+
+/// Get the time remaining until the given `timestamp`.
+///
+/// A `timestamp` in the past yields no time remaining.
+pub(crate) fn until(timestamp: Timestamp) -> Duration {
+    #[cfg(feature = "chrono")]
+    return (timestamp - chrono::Utc::now())
+        .to_std()
+        .unwrap_or_default();
+    #[cfg(not(feature = "chrono"))]
+    timestamp
+        .duration_since(std::time::SystemTime::now())
+        .unwrap_or_default()
+}
+
+/// Parse an HTTP-date (as described by
+/// [RFC 7231](https://www.rfc-editor.org/rfc/rfc7231#section-7.1.1.1)).
+///
+/// Requires the `chrono` feature; otherwise, this always returns [`None`].
+pub(crate) fn parse_http_date(value: &str) -> Option<Timestamp> {
+    // an HTTP-date is a fixed form of the RFC 2822 date format
+    #[cfg(feature = "chrono")]
+    return chrono::DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|date| date.with_timezone(&chrono::Utc));
+    #[cfg(not(feature = "chrono"))]
+    {
+        log::debug!("Parsing an HTTP-date ({value}) requires the `chrono` feature");
+        None
+    }
+}
+
+/// Display the given `timestamp` in log messages.
+///
+/// Without the `chrono` feature, this is the number of seconds since the unix epoch.
+pub(crate) fn display(timestamp: &Timestamp) -> impl Display {
+    #[cfg(feature = "chrono")]
+    return *timestamp;
+    #[cfg(not(feature = "chrono"))]
+    timestamp
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_else(|e| -(e.duration().as_secs() as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use std::time::Duration;
+
+    use super::{UNIX_EPOCH, display, from_unix, now, until};
+
+    #[test]
+    fn unix_round_trip() {
+        assert_eq!(from_unix(0), Some(UNIX_EPOCH));
+        let reset = from_unix(1760000000).unwrap();
+        #[cfg(feature = "chrono")]
+        assert_eq!(display(&reset).to_string(), "2025-10-09 08:53:20 UTC");
+        #[cfg(not(feature = "chrono"))]
+        assert_eq!(display(&reset).to_string(), "1760000000");
+    }
+
+    #[test]
+    fn time_until() {
+        assert_eq!(until(UNIX_EPOCH), Duration::ZERO);
+        let soon = until(now() + Duration::from_secs(60));
+        assert!(soon > Duration::from_secs(55) && soon <= Duration::from_secs(60));
+    }
+}
@@ -1,6 +1,11 @@
 pub mod review_comments;
 pub mod thread_comments;
 
+/// The marker used to identify thread comments when none is specified.
+///
+/// This includes the crate's name, version and the compile-time's datetime.
+/// Without the `chrono` feature, the datetime is omitted.
+#[cfg(feature = "chrono")]
 pub const DEFAULT_MARKER: &str = concat!(
     "<!-- ",
     env!("CARGO_CRATE_NAME"),
@@ -10,3 +15,24 @@ pub const DEFAULT_MARKER: &str = concat!(
     env!("COMPILE_DATETIME"), // env var set by build.rs
     " -->\n"
 );
+
+/// The marker used to identify thread comments when none is specified.
+///
+/// This includes the crate's name, version and the compile-time's datetime.
+/// Without the `chrono` feature, the datetime is omitted.
+#[cfg(not(feature = "chrono"))]
+pub const DEFAULT_MARKER: &str = concat!(
+    "<!-- ",
+    env!("CARGO_CRATE_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " -->\n"
+);
+
+/// Is the given `marker` the [`DEFAULT_MARKER`]?
+///
+/// Used to skip serializing a default marker.
+#[cfg(feature = "serde")]
+fn is_default_marker(marker: &str) -> bool {
+    marker == DEFAULT_MARKER
+}
@@ -1,17 +1,48 @@
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
 
+use std::str::FromStr;
+
+use url::Url;
+
 use super::DEFAULT_MARKER;
+use crate::error::ParseEnumError;
+#[cfg(feature = "file-changes")]
+use crate::{AnnotationLevel, FileAnnotation, FileDiffLines};
+#[cfg(feature = "file-changes")]
+use std::collections::HashMap;
 
 /// A struct to describe a Pull Request review.
 ///
 /// Each review is considered to be about the PR event's changes.
 /// There is no support for posting reviews on older/outdated PR events.
+///
+/// With the `serde` feature, this can be deserialized from a configuration file.
+/// Missing fields take their [`Default`] value, and enum variants are in kebab-case.
+/// For example, in TOML:
+///
+/// ```toml
+/// action = "request-changes"
+/// summary = "Please address the concerns below."
+/// marker = "<!-- my-cool-CI-app-name -->"
+/// allow_draft = true
+///
+/// [[comments]]
+/// path = "src/lib.rs"
+/// line_start = 10
+/// line_end = 12
+/// comment = "Consider using a constant here."
+/// ```
 #[derive(Debug, PartialEq, Eq, Clone)]
 #[cfg_attr(
     feature = "pyo3",
     pyclass(module = "git_bot_feedback", from_py_object, get_all, set_all)
 )]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ReviewOptions {
     /// The course of action that the PR review suggests.
     pub action: ReviewAction,
@@ -41,6 +72,10 @@ pub struct ReviewOptions {
     /// For example:
     ///
     /// ``<!-- git-bot-feedback/0.1.0/Jul-14-2025_17-00 -->``
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "super::is_default_marker")
+    )]
     pub marker: String,
 
     /// Allow posting reviews on draft Pull Requests?
@@ -59,6 +94,42 @@ pub struct ReviewOptions {
     /// Note, this does not apply to PR review summary comments nor threads of
     /// discussion within a review.
     pub delete_review_comments: bool,
+
+    /// The message shown in the PR's timeline when an outdated review is dismissed.
+    ///
+    /// If this is [`None`], then the message is derived from the [`Self::marker`].
+    /// For example, a marker like ``<!-- my-cool-CI-app-name -->`` yields
+    ///
+    /// ``outdated review (my-cool-CI-app-name)``
+    ///
+    /// See also [`Self::dismissal_text()`].
+    pub dismissal_message: Option<String>,
+}
+
+impl ReviewOptions {
+    /// The message used when dismissing an outdated review.
+    ///
+    /// Returns the [`Self::dismissal_message`] (if set).
+    /// Otherwise, a message is generated from the name within the [`Self::marker`]
+    /// (if the marker is an HTML comment).
+    pub fn dismissal_text(&self) -> String {
+        if let Some(msg) = &self.dismissal_message {
+            return msg.clone();
+        }
+        let name = self
+            .marker
+            .trim()
+            .strip_prefix("<!--")
+            .and_then(|m| m.strip_suffix("-->"))
+            .and_then(|m| m.split('/').next())
+            .map(str::trim)
+            .unwrap_or_default();
+        if name.is_empty() {
+            "outdated review".to_string()
+        } else {
+            format!("outdated review ({name})")
+        }
+    }
 }
 
 impl Default for ReviewOptions {
@@ -71,6 +142,7 @@ impl Default for ReviewOptions {
             allow_draft: false,
             allow_closed: false,
             delete_review_comments: false,
+            dismissal_message: None,
         }
     }
 }
@@ -80,6 +152,7 @@ impl Default for ReviewOptions {
 impl ReviewOptions {
     /// Create a new review options instance.
     #[new]
+    #[allow(clippy::too_many_arguments)]
     #[pyo3(
         signature = (
             comments,
@@ -88,9 +161,10 @@ impl ReviewOptions {
             marker=None,
             allow_draft=None,
             allow_closed=None,
-            delete_review_comments=None
+            delete_review_comments=None,
+            dismissal_message=None
         ),
-        text_signature = "(comments: list[ReviewComment], action: ReviewAction | None = None, summary: str | None = None, marker: str | None = None, allow_draft: bool = False, allow_closed: bool = False, delete_review_comments: bool = False)"
+        text_signature = "(comments: list[ReviewComment], action: ReviewAction | None = None, summary: str | None = None, marker: str | None = None, allow_draft: bool = False, allow_closed: bool = False, delete_review_comments: bool = False, dismissal_message: str | None = None)"
     )]
     pub fn new(
         comments: Vec<ReviewComment>,
@@ -100,6 +174,7 @@ impl ReviewOptions {
         allow_draft: Option<bool>,
         allow_closed: Option<bool>,
         delete_review_comments: Option<bool>,
+        dismissal_message: Option<String>,
     ) -> Self {
         Self {
             action: action.unwrap_or_default(),
@@ -109,13 +184,27 @@ impl ReviewOptions {
             allow_draft: allow_draft.unwrap_or(false),
             allow_closed: allow_closed.unwrap_or(false),
             delete_review_comments: delete_review_comments.unwrap_or(false),
+            dismissal_message,
         }
     }
 }
 
 /// An enumeration of possible recommended actions for a Pull Request review.
+///
+/// This can be parsed from a string (case-insensitive):
+///
+/// | Variant | Spellings |
+/// |---------|-----------|
+/// | [`ReviewAction::Approve`] | `approve` |
+/// | [`ReviewAction::RequestChanges`] | `request-changes`, `request_changes` |
+/// | [`ReviewAction::Comment`] | `comment` |
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
 #[cfg_attr(feature = "pyo3", pyclass(module = "git_bot_feedback", from_py_object))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
 pub enum ReviewAction {
     /// Approve the current Pull Request's changes.
     Approve,
@@ -128,12 +217,40 @@ pub enum ReviewAction {
     Comment,
 }
 
+impl FromStr for ReviewAction {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ParseEnumError::parse(
+            "review action",
+            s,
+            &[
+                ("approve", Self::Approve),
+                ("request-changes", Self::RequestChanges),
+                ("request_changes", Self::RequestChanges),
+                ("comment", Self::Comment),
+            ],
+        )
+    }
+}
+
+impl std::fmt::Display for ReviewAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Approve => write!(f, "approve"),
+            Self::RequestChanges => write!(f, "request-changes"),
+            Self::Comment => write!(f, "comment"),
+        }
+    }
+}
+
 /// A struct to describe a single comment in a Pull Request review.
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 #[cfg_attr(
     feature = "pyo3",
     pyclass(module = "git_bot_feedback", from_py_object, get_all, set_all)
 )]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReviewComment {
     /// The file's line number in the diff that begins the the focus of the comment's concerns.
     pub line_start: Option<u32>,
@@ -171,3 +288,458 @@ impl ReviewComment {
         }
     }
 }
+
+impl ReviewComment {
+    /// Ensure that the [`Self::comment`] is marked with the given `marker`
+    /// (typically the [`ReviewOptions::marker`]).
+    ///
+    /// Typically only used by implementations of
+    /// [`RestApiClient::post_pr_review`](crate::client::RestApiClient::post_pr_review).
+    pub fn mark_comment(&self, marker: &str) -> String {
+        if !self.comment.starts_with(marker) {
+            return format!("{marker}{}", self.comment);
+        }
+        self.comment.clone()
+    }
+
+    /// Can the given `posted` comment be reused instead of posting this comment?
+    ///
+    /// The path, line(s), and marked body (see [`Self::mark_comment()`]) must be the same.
+    /// A resolved comment is never reused.
+    #[cfg(any(feature = "github", feature = "gitea", feature = "gitlab"))]
+    pub(crate) fn is_posted_as(&self, posted: &PostedReviewComment, marker: &str) -> bool {
+        !posted.resolved
+            && posted.path == self.path
+            && posted.line_end == i64::from(self.line_end)
+            && (posted.single_line || posted.line_start == self.line_start.map(i64::from))
+            && posted.body == self.mark_comment(marker)
+    }
+}
+
+/// A review comment that was previously posted (by this software) on the git server.
+///
+/// See [`ReviewComment::is_posted_as()`].
+#[cfg(any(feature = "github", feature = "gitea", feature = "gitlab"))]
+pub(crate) struct PostedReviewComment<'a> {
+    /// The file that the comment pertains to.
+    pub path: &'a str,
+
+    /// The first line of the comment's range (if any).
+    pub line_start: Option<i64>,
+
+    /// The line that the comment is positioned on (or the last line of its range).
+    pub line_end: i64,
+
+    /// Does the git server only position comments on a single line?
+    ///
+    /// If so, then the comment was posted on the [`ReviewComment::line_end`]
+    /// and the [`ReviewComment::line_start`] is not compared.
+    pub single_line: bool,
+
+    /// The comment's body (including the marker).
+    pub body: &'a str,
+
+    /// Was the comment resolved (or hidden) since it was posted?
+    pub resolved: bool,
+}
+
+#[cfg(feature = "file-changes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+impl ReviewComment {
+    /// Convert the given `annotations` into review comments about the changes in the given `files`.
+    ///
+    /// The `files` are typically obtained from
+    /// [`RestApiClient::get_list_of_changed_files()`](crate::client::RestApiClient::get_list_of_changed_files).
+    ///
+    /// Only annotations with a [`FileAnnotation::path`] found in `files` are converted.
+    /// Furthermore,
+    ///
+    /// - An annotation with a [`FileAnnotation::start_line`] is only converted if that line
+    ///   is within a diff hunk. If the [`FileAnnotation::end_line`] is within the same hunk,
+    ///   then the comment spans both lines ([`Self::line_start`] to [`Self::line_end`]).
+    ///   Otherwise, the comment is placed on the [`FileAnnotation::start_line`] only.
+    /// - An annotation without a [`FileAnnotation::start_line`] (scoped to the entire file)
+    ///   is placed on the first line of the file's first diff hunk.
+    ///
+    /// Each comment is prefixed with the annotation's severity (and title) in bold markdown text.
+    ///
+    /// Returns a tuple of the converted comments and the annotations that could not be converted.
+    /// The leftover annotations can be posted as
+    /// [`RestApiClient::write_file_annotations()`](crate::client::RestApiClient::write_file_annotations)
+    /// or summarized elsewhere.
+    pub fn from_annotations<'a>(
+        annotations: &'a [FileAnnotation],
+        files: &HashMap<String, FileDiffLines>,
+    ) -> (Vec<ReviewComment>, Vec<&'a FileAnnotation>) {
+        let mut comments = Vec::new();
+        let mut leftovers = Vec::new();
+        for annotation in annotations {
+            let diff_lines = if annotation.path.is_empty() {
+                None
+            } else {
+                files.get(&annotation.path)
+            };
+            let lines = diff_lines.and_then(|diff| match annotation.start_line {
+                Some(start) => {
+                    let start = start as u32;
+                    let hunk = diff.diff_hunks.iter().find(|h| h.contains(&start))?;
+                    match annotation.end_line {
+                        Some(end) if end as u32 > start && hunk.contains(&(end as u32)) => {
+                            Some((Some(start), end as u32))
+                        }
+                        _ => Some((None, start)),
+                    }
+                }
+                None => diff.diff_hunks.first().map(|h| (None, h.start)),
+            });
+            match lines {
+                Some((line_start, line_end)) => comments.push(ReviewComment {
+                    line_start,
+                    line_end,
+                    comment: Self::annotation_to_markdown(annotation),
+                    path: annotation.path.clone(),
+                }),
+                None => leftovers.push(annotation),
+            }
+        }
+        (comments, leftovers)
+    }
+
+    /// Format a [`FileAnnotation`] as markdown text with a bold severity prefix.
+    fn annotation_to_markdown(annotation: &FileAnnotation) -> String {
+        let severity = match annotation.severity {
+            AnnotationLevel::Debug => "Debug",
+            AnnotationLevel::Notice => "Notice",
+            AnnotationLevel::Warning => "Warning",
+            AnnotationLevel::Error => "Error",
+        };
+        match &annotation.title {
+            Some(title) => format!("**{severity}: {title}**\n\n{}", annotation.message),
+            None => format!("**{severity}:** {}", annotation.message),
+        }
+    }
+}
+
+/// A summary of the outcome from posting a Pull Request review.
+///
+/// This is returned by
+/// [`RestApiClient::post_pr_review()`](crate::client::RestApiClient::post_pr_review).
+/// The counts of reused comments and dismissed reviews are gathered by a preceding call to
+/// [`RestApiClient::cull_pr_reviews()`](crate::client::RestApiClient::cull_pr_reviews).
+/// Those counts are reset to `0` after every call to
+/// [`RestApiClient::post_pr_review()`](crate::client::RestApiClient::post_pr_review).
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "pyo3",
+    pyclass(module = "git_bot_feedback", skip_from_py_object, frozen)
+)]
+pub struct ReviewOutcome {
+    /// The ID of the newly created review.
+    ///
+    /// This is [`None`] if no review was posted.
+    pub review_id: Option<i64>,
+
+    /// The URL to view the newly created review in a browser.
+    ///
+    /// This is [`None`] if no review was posted or
+    /// the git server did not provide a URL.
+    pub html_url: Option<Url>,
+
+    /// The number of comments posted in the newly created review.
+    pub posted_comments: usize,
+
+    /// The number of existing review comments that were reused
+    /// instead of posting duplicates.
+    pub reused_comments: usize,
+
+    /// The number of outdated reviews that were dismissed (or deleted).
+    pub dismissed_reviews: usize,
+
+    /// The reason a review was not posted.
+    ///
+    /// This is [`None`] if a review was posted (or attempted).
+    pub skipped: Option<ReviewSkipReason>,
+}
+
+/// An enumeration of reasons for not posting a Pull Request review.
+///
+/// See [`ReviewOutcome::skipped`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(
+    feature = "pyo3",
+    pyclass(module = "git_bot_feedback", from_py_object, eq)
+)]
+pub enum ReviewSkipReason {
+    /// The Pull Request is a draft and [`ReviewOptions::allow_draft`] is `false`.
+    Draft,
+
+    /// The Pull Request is closed and [`ReviewOptions::allow_closed`] is `false`.
+    Closed,
+
+    /// The Pull Request is locked.
+    Locked,
+}
+
+impl std::fmt::Display for ReviewSkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReviewSkipReason::Draft => write!(f, "draft"),
+            ReviewSkipReason::Closed => write!(f, "closed"),
+            ReviewSkipReason::Locked => write!(f, "locked"),
+        }
+    }
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl ReviewOutcome {
+    /// The ID of the newly created review.
+    ///
+    /// This is ``None`` if no review was posted.
+    #[getter(review_id)]
+    pub fn get_review_id(&self) -> Option<i64> {
+        self.review_id
+    }
+
+    /// The URL to view the newly created review in a browser.
+    ///
+    /// This is ``None`` if no review was posted or
+    /// the git server did not provide a URL.
+    #[getter(html_url)]
+    pub fn get_html_url(&self) -> Option<String> {
+        self.html_url.as_ref().map(|u| u.to_string())
+    }
+
+    /// The number of comments posted in the newly created review.
+    #[getter(posted_comments)]
+    pub fn get_posted_comments(&self) -> usize {
+        self.posted_comments
+    }
+
+    /// The number of existing review comments that were reused
+    /// instead of posting duplicates.
+    #[getter(reused_comments)]
+    pub fn get_reused_comments(&self) -> usize {
+        self.reused_comments
+    }
+
+    /// The number of outdated reviews that were dismissed (or deleted).
+    #[getter(dismissed_reviews)]
+    pub fn get_dismissed_reviews(&self) -> usize {
+        self.dismissed_reviews
+    }
+
+    /// The reason a review was not posted.
+    ///
+    /// This is ``None`` if a review was posted (or attempted).
+    #[getter(skipped)]
+    pub fn get_skipped(&self) -> Option<ReviewSkipReason> {
+        self.skipped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    #[cfg(feature = "file-changes")]
+    use super::{AnnotationLevel, FileAnnotation, FileDiffLines, HashMap, ReviewComment};
+    use super::{DEFAULT_MARKER, ReviewOptions};
+    #[cfg(feature = "serde")]
+    use super::{ReviewAction, ReviewComment as Comment};
+
+    #[test]
+    fn default_dismissal_text() {
+        let opts = ReviewOptions::default();
+        assert!(DEFAULT_MARKER.contains(env!("CARGO_CRATE_NAME")));
+        assert_eq!(
+            opts.dismissal_text(),
+            format!("outdated review ({})", env!("CARGO_CRATE_NAME"))
+        );
+    }
+
+    #[test]
+    fn dismissal_text_from_plain_marker() {
+        let opts = ReviewOptions {
+            marker: "my-app: ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(opts.dismissal_text(), "outdated review");
+    }
+
+    #[test]
+    fn custom_dismissal_text() {
+        let msg = "Superseded by \"my-app\"\nSee the latest review.";
+        let opts = ReviewOptions {
+            dismissal_message: Some(msg.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(opts.dismissal_text(), msg);
+    }
+
+    #[cfg(feature = "file-changes")]
+    fn changed_files() -> HashMap<String, FileDiffLines> {
+        HashMap::from([(
+            "src/lib.rs".to_string(),
+            FileDiffLines::with_info(vec![4, 5, 12], vec![3..7, 10..15]),
+        )])
+    }
+
+    #[cfg(feature = "file-changes")]
+    fn annotation(
+        path: &str,
+        start_line: Option<usize>,
+        end_line: Option<usize>,
+    ) -> FileAnnotation {
+        FileAnnotation {
+            severity: AnnotationLevel::Warning,
+            path: path.to_string(),
+            start_line,
+            end_line,
+            message: "unused variable".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(feature = "file-changes")]
+    #[test]
+    fn annotations_in_diff() {
+        let mut ranged = annotation("src/lib.rs", Some(11), Some(13));
+        ranged.severity = AnnotationLevel::Error;
+        ranged.title = Some("E0308".to_string());
+        let annotations = [
+            annotation("src/lib.rs", Some(4), None),
+            ranged,
+            // end_line is in a different hunk
+            annotation("src/lib.rs", Some(5), Some(12)),
+            annotation("src/lib.rs", None, None),
+        ];
+        let (comments, leftovers) = ReviewComment::from_annotations(&annotations, &changed_files());
+        assert!(leftovers.is_empty());
+        let lines = comments
+            .iter()
+            .map(|c| (c.line_start, c.line_end))
+            .collect::<Vec<_>>();
+        assert_eq!(lines, [(None, 4), (Some(11), 13), (None, 5), (None, 3)]);
+        assert!(comments.iter().all(|c| c.path == "src/lib.rs"));
+        assert_eq!(comments[0].comment, "**Warning:** unused variable");
+        assert_eq!(comments[1].comment, "**Error: E0308**\n\nunused variable");
+    }
+
+    #[cfg(feature = "file-changes")]
+    #[test]
+    fn annotations_out_of_diff() {
+        let annotations = [
+            annotation("src/lib.rs", Some(8), None),
+            annotation("src/lib.rs", Some(20), Some(22)),
+        ];
+        let (comments, leftovers) = ReviewComment::from_annotations(&annotations, &changed_files());
+        assert!(comments.is_empty());
+        assert_eq!(leftovers.len(), 2);
+        assert_eq!(leftovers[0].start_line, Some(8));
+        assert_eq!(leftovers[1].start_line, Some(20));
+    }
+
+    #[cfg(feature = "file-changes")]
+    #[test]
+    fn annotations_without_changed_path() {
+        let annotations = [
+            annotation("", Some(4), None),
+            annotation("src/main.rs", Some(4), None),
+            annotation("src/main.rs", None, None),
+        ];
+        let (comments, leftovers) = ReviewComment::from_annotations(&annotations, &changed_files());
+        assert!(comments.is_empty());
+        assert_eq!(leftovers.len(), annotations.len());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let opts = ReviewOptions {
+            action: ReviewAction::RequestChanges,
+            summary: "summary".to_string(),
+            comments: vec![Comment {
+                line_start: None,
+                line_end: 2,
+                comment: "comment".to_string(),
+                path: "src/lib.rs".to_string(),
+            }],
+            marker: "<!-- my-app -->".to_string(),
+            allow_draft: true,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&opts).unwrap();
+        assert_eq!(json["action"], "request-changes");
+        assert_eq!(json["marker"], "<!-- my-app -->");
+        assert_eq!(serde_json::from_value::<ReviewOptions>(json).unwrap(), opts);
+
+        // missing fields take their default values
+        let opts = serde_json::from_str::<ReviewOptions>(r#"{"action": "approve"}"#).unwrap();
+        assert_eq!(
+            opts,
+            ReviewOptions {
+                action: ReviewAction::Approve,
+                ..Default::default()
+            }
+        );
+        // the default marker is not serialized
+        let json = serde_json::to_value(&opts).unwrap();
+        assert!(json.get("marker").is_none());
+        assert_eq!(serde_json::from_value::<ReviewOptions>(json).unwrap(), opts);
+    }
+
+    #[test]
+    fn parse_review_action() {
+        use super::ReviewAction;
+
+        for (spelling, expected) in [
+            ("approve", ReviewAction::Approve),
+            ("request-changes", ReviewAction::RequestChanges),
+            ("REQUEST_CHANGES", ReviewAction::RequestChanges),
+            ("Comment", ReviewAction::Comment),
+        ] {
+            assert_eq!(spelling.parse::<ReviewAction>().unwrap(), expected);
+            assert_eq!(
+                expected.to_string().parse::<ReviewAction>().unwrap(),
+                expected
+            );
+        }
+        assert_eq!(
+            "reject".parse::<ReviewAction>().unwrap_err().to_string(),
+            "Unknown review action 'reject'; expected one of: \
+             approve, request-changes, request_changes, comment"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "github")]
+    fn reuse_posted_comment() {
+        use super::{PostedReviewComment, ReviewComment};
+
+        let marker = "<!-- my-app -->\n";
+        let proposed = ReviewComment {
+            line_start: Some(3),
+            line_end: 5,
+            comment: "unused variable".to_string(),
+            path: "src/lib.rs".to_string(),
+        };
+        let body = proposed.mark_comment(marker);
+        assert_eq!(body, "<!-- my-app -->\nunused variable");
+        let mut posted = PostedReviewComment {
+            path: "src/lib.rs",
+            line_start: Some(3),
+            line_end: 5,
+            single_line: false,
+            body: &body,
+            resolved: false,
+        };
+        assert!(proposed.is_posted_as(&posted, marker));
+        posted.line_start = None;
+        assert!(!proposed.is_posted_as(&posted, marker));
+        // the line_start is not compared on servers that only position a single line
+        posted.single_line = true;
+        assert!(proposed.is_posted_as(&posted, marker));
+        posted.resolved = true;
+        assert!(!proposed.is_posted_as(&posted, marker));
+    }
+}
@@ -1,13 +1,28 @@
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
 
+use std::str::FromStr;
+
 use super::DEFAULT_MARKER;
+use crate::error::ParseEnumError;
 
 /// An enumeration of possible type of comments being posted.
 ///
 /// The default is [`CommentKind::Concerns`].
+///
+/// This can be parsed from a string (case-insensitive):
+///
+/// | Variant | Spellings |
+/// |---------|-----------|
+/// | [`CommentKind::Concerns`] | `concerns` |
+/// | [`CommentKind::Lgtm`] | `lgtm` |
 #[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "pyo3", pyclass(module = "git_bot_feedback", from_py_object))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
 pub enum CommentKind {
     /// A comment that admonishes concerns for end-users' attention.
     #[default]
@@ -20,8 +35,20 @@ pub enum CommentKind {
 /// An enumeration of supported behaviors about posting comments.
 ///
 /// See [`ThreadCommentOptions::policy`](crate::ThreadCommentOptions::policy).
+///
+/// This can be parsed from a string (case-insensitive):
+///
+/// | Variant | Spellings |
+/// |---------|-----------|
+/// | [`CommentPolicy::Anew`] | `anew` |
+/// | [`CommentPolicy::Update`] | `update` |
 #[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "pyo3", pyclass(module = "git_bot_feedback", from_py_object))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
 pub enum CommentPolicy {
     /// Each thread comment is posted as a new comment.
     ///
@@ -36,12 +63,126 @@ pub enum CommentPolicy {
     Update,
 }
 
+/// An enumeration of fallbacks for thread comments that the git server cannot post.
+///
+/// Some git servers (eg. Gitea) only support thread comments on Pull Requests.
+/// This controls what happens to a thread comment for a push event on such servers.
+///
+/// See [`ThreadCommentOptions::push_fallback`](crate::ThreadCommentOptions::push_fallback).
+///
+/// This can be parsed from a string (case-insensitive):
+///
+/// | Variant | Spellings |
+/// |---------|-----------|
+/// | [`CommentFallback::StepSummary`] | `step-summary`, `step_summary` |
+/// | [`CommentFallback::Skip`] | `skip` |
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "pyo3", pyclass(module = "git_bot_feedback", from_py_object))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum CommentFallback {
+    /// Append the comment to the CI workflow's summary page instead.
+    ///
+    /// See [`RestApiClient::append_step_summary()`](crate::client::RestApiClient::append_step_summary).
+    #[default]
+    StepSummary,
+
+    /// Do not post the comment anywhere.
+    Skip,
+}
+
+impl FromStr for CommentKind {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ParseEnumError::parse(
+            "comment kind",
+            s,
+            &[("concerns", Self::Concerns), ("lgtm", Self::Lgtm)],
+        )
+    }
+}
+
+impl std::fmt::Display for CommentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Concerns => write!(f, "concerns"),
+            Self::Lgtm => write!(f, "lgtm"),
+        }
+    }
+}
+
+impl FromStr for CommentPolicy {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ParseEnumError::parse(
+            "comment policy",
+            s,
+            &[("anew", Self::Anew), ("update", Self::Update)],
+        )
+    }
+}
+
+impl std::fmt::Display for CommentPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Anew => write!(f, "anew"),
+            Self::Update => write!(f, "update"),
+        }
+    }
+}
+
+impl FromStr for CommentFallback {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ParseEnumError::parse(
+            "comment fallback",
+            s,
+            &[
+                ("step-summary", Self::StepSummary),
+                ("step_summary", Self::StepSummary),
+                ("skip", Self::Skip),
+            ],
+        )
+    }
+}
+
+impl std::fmt::Display for CommentFallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StepSummary => write!(f, "step-summary"),
+            Self::Skip => write!(f, "skip"),
+        }
+    }
+}
+
 /// Options that control posting comments on a thread.
+///
+/// With the `serde` feature, this can be deserialized from a configuration file.
+/// Missing fields take their [`Default`] value, and enum variants are in kebab-case.
+/// A [`Self::marker`] equal to the default is not serialized.
+/// For example, in TOML:
+///
+/// ```toml
+/// policy = "anew"
+/// kind = "lgtm"
+/// no_lgtm = true
+/// ```
 #[derive(Debug, Clone)]
 #[cfg_attr(
     feature = "pyo3",
     pyclass(module = "git_bot_feedback", from_py_object, get_all, set_all)
 )]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ThreadCommentOptions {
     /// Controls posting comments on a thread that concerns a Pull Request or Push event.
     ///
@@ -70,6 +211,10 @@ pub struct ThreadCommentOptions {
     /// For example:
     ///
     /// ``<!-- git-bot-feedback/0.1.0/Jul-14-2025_17-00 -->``
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "super::is_default_marker")
+    )]
     pub marker: String,
 
     /// Disallow posting "Looks Good To Me" comments.
@@ -77,6 +222,10 @@ pub struct ThreadCommentOptions {
     /// Setting this option to `true` may instigate the deletion of old bot comment(s),
     /// if any exist.
     pub no_lgtm: bool,
+
+    /// What to do with the comment for a push event if the git server
+    /// only supports thread comments on Pull Requests (eg. Gitea).
+    pub push_fallback: CommentFallback,
 }
 
 impl Default for ThreadCommentOptions {
@@ -87,6 +236,7 @@ impl Default for ThreadCommentOptions {
             kind: Default::default(),
             marker: DEFAULT_MARKER.to_string(),
             no_lgtm: Default::default(),
+            push_fallback: Default::default(),
         }
     }
 }
@@ -118,8 +268,9 @@ impl ThreadCommentOptions {
             kind = None,
             marker = None,
             no_lgtm = None,
+            push_fallback = None,
         ),
-        text_signature = "(policy: CommentPolicy | None = None, comment: str | None = None, kind: CommentKind | None = None, marker: str | None = None, no_lgtm: bool = False)",
+        text_signature = "(policy: CommentPolicy | None = None, comment: str | None = None, kind: CommentKind | None = None, marker: str | None = None, no_lgtm: bool = False, push_fallback: CommentFallback | None = None)",
     )]
     pub fn new(
         policy: Option<CommentPolicy>,
@@ -127,6 +278,7 @@ impl ThreadCommentOptions {
         kind: Option<CommentKind>,
         marker: Option<String>,
         no_lgtm: Option<bool>,
+        push_fallback: Option<CommentFallback>,
     ) -> Self {
         Self {
             policy: policy.unwrap_or_default(),
@@ -134,6 +286,7 @@ impl ThreadCommentOptions {
             kind: kind.unwrap_or_default(),
             marker: marker.unwrap_or_else(|| DEFAULT_MARKER.to_string()),
             no_lgtm: no_lgtm.unwrap_or_default(),
+            push_fallback: push_fallback.unwrap_or_default(),
         }
     }
 }
@@ -143,26 +296,125 @@ mod test {
     #![allow(clippy::unwrap_used)]
 
     use super::{DEFAULT_MARKER, ThreadCommentOptions};
+    #[cfg(feature = "chrono")]
     use chrono::NaiveDateTime;
 
     #[test]
     fn default_marker() {
         let mut opts = ThreadCommentOptions::default();
         assert_eq!(opts.marker, DEFAULT_MARKER);
-        let datetime_start = concat!(
-            "<!-- ",
-            env!("CARGO_CRATE_NAME"),
-            "/",
-            env!("CARGO_PKG_VERSION"),
-            "/",
-        )
-        .len();
-        let datetime_end = DEFAULT_MARKER.len() - 5;
-        let datetime_str = &DEFAULT_MARKER[datetime_start..datetime_end];
-        NaiveDateTime::parse_from_str(datetime_str, "%b-%d-%Y_%H-%M").unwrap();
+        #[cfg(feature = "chrono")]
+        {
+            let datetime_start = concat!(
+                "<!-- ",
+                env!("CARGO_CRATE_NAME"),
+                "/",
+                env!("CARGO_PKG_VERSION"),
+                "/",
+            )
+            .len();
+            let datetime_end = DEFAULT_MARKER.len() - 5;
+            let datetime_str = &DEFAULT_MARKER[datetime_start..datetime_end];
+            NaiveDateTime::parse_from_str(datetime_str, "%b-%d-%Y_%H-%M").unwrap();
+        }
+        #[cfg(not(feature = "chrono"))]
+        assert_eq!(
+            DEFAULT_MARKER,
+            concat!(
+                "<!-- ",
+                env!("CARGO_CRATE_NAME"),
+                "/",
+                env!("CARGO_PKG_VERSION"),
+                " -->\n"
+            )
+        );
         assert_eq!(opts.mark_comment(), DEFAULT_MARKER);
         let comment = format!("{DEFAULT_MARKER}Some text data.");
         opts.comment = comment.clone();
         assert_eq!(opts.mark_comment(), comment);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        use super::{CommentFallback, CommentKind, CommentPolicy};
+
+        let opts = ThreadCommentOptions {
+            policy: CommentPolicy::Anew,
+            kind: CommentKind::Lgtm,
+            no_lgtm: true,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&opts).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "policy": "anew",
+                "comment": "",
+                "kind": "lgtm",
+                "no_lgtm": true,
+                "push_fallback": "step-summary",
+            })
+        );
+        let parsed = serde_json::from_value::<ThreadCommentOptions>(json).unwrap();
+        assert_eq!(parsed.policy, opts.policy);
+        assert_eq!(parsed.kind, opts.kind);
+        assert_eq!(parsed.marker, DEFAULT_MARKER);
+        assert!(parsed.no_lgtm);
+
+        let parsed =
+            serde_json::from_str::<ThreadCommentOptions>(r#"{"marker": "<!-- my-app -->"}"#)
+                .unwrap();
+        assert_eq!(parsed.marker, "<!-- my-app -->");
+        assert_eq!(parsed.policy, CommentPolicy::Update);
+        assert_eq!(parsed.kind, CommentKind::Concerns);
+        assert_eq!(parsed.push_fallback, CommentFallback::StepSummary);
+    }
+
+    #[test]
+    fn parse_comment_enums() {
+        use super::{CommentFallback, CommentKind, CommentPolicy};
+
+        for (spelling, expected) in [
+            ("anew", CommentPolicy::Anew),
+            ("Update", CommentPolicy::Update),
+        ] {
+            assert_eq!(spelling.parse::<CommentPolicy>().unwrap(), expected);
+            assert_eq!(
+                expected.to_string().parse::<CommentPolicy>().unwrap(),
+                expected
+            );
+        }
+        assert_eq!(
+            "updates".parse::<CommentPolicy>().unwrap_err().to_string(),
+            "Unknown comment policy 'updates'; expected one of: anew, update"
+        );
+
+        for (spelling, expected) in [
+            ("concerns", CommentKind::Concerns),
+            ("LGTM", CommentKind::Lgtm),
+        ] {
+            assert_eq!(spelling.parse::<CommentKind>().unwrap(), expected);
+            assert_eq!(
+                expected.to_string().parse::<CommentKind>().unwrap(),
+                expected
+            );
+        }
+        assert_eq!(
+            "nit".parse::<CommentKind>().unwrap_err().to_string(),
+            "Unknown comment kind 'nit'; expected one of: concerns, lgtm"
+        );
+
+        for (spelling, expected) in [
+            ("step-summary", CommentFallback::StepSummary),
+            ("Step_Summary", CommentFallback::StepSummary),
+            ("skip", CommentFallback::Skip),
+        ] {
+            assert_eq!(spelling.parse::<CommentFallback>().unwrap(), expected);
+            assert_eq!(
+                expected.to_string().parse::<CommentFallback>().unwrap(),
+                expected
+            );
+        }
+    }
 }
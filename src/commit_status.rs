@@ -0,0 +1,208 @@
+use std::str::FromStr;
+
+use reqwest::Url;
+
+use crate::{RestClientError, error::ParseEnumError};
+
+/// The maximum number of characters in a commit status' description.
+///
+/// Longer descriptions are truncated (see [`CommitStatus::truncated_description()`]).
+pub const MAX_STATUS_DESCRIPTION: usize = 140;
+
+/// The state of a [`CommitStatus`].
+///
+/// This can be parsed from a string (case-insensitive):
+///
+/// | Variant | Spellings |
+/// |---------|-----------|
+/// | [`StatusState::Pending`] | `pending` |
+/// | [`StatusState::Success`] | `success` |
+/// | [`StatusState::Error`] | `error` |
+/// | [`StatusState::Failure`] | `failure` |
+/// | [`StatusState::Warning`] | `warning`, `warn` |
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum StatusState {
+    /// The check has not finished yet.
+    #[default]
+    Pending,
+    /// The check passed.
+    Success,
+    /// The check could not be completed.
+    Error,
+    /// The check failed.
+    Failure,
+    /// The check passed with warnings.
+    ///
+    /// Not all git servers have a distinct warning state (see [`RestApiClient::post_commit_status()`](crate::RestApiClient::post_commit_status)).
+    Warning,
+}
+
+impl FromStr for StatusState {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ParseEnumError::parse(
+            "status state",
+            s,
+            &[
+                ("pending", Self::Pending),
+                ("success", Self::Success),
+                ("error", Self::Error),
+                ("failure", Self::Failure),
+                ("warning", Self::Warning),
+                ("warn", Self::Warning),
+            ],
+        )
+    }
+}
+
+impl std::fmt::Display for StatusState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pending => write!(f, "pending"),
+            Self::Success => write!(f, "success"),
+            Self::Error => write!(f, "error"),
+            Self::Failure => write!(f, "failure"),
+            Self::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A status to attach to a commit.
+///
+/// Git servers typically display commit statuses next to a commit (or a PR's head commit).
+/// See [`RestApiClient::post_commit_status()`](crate::RestApiClient::post_commit_status).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CommitStatus {
+    /// The state of the status.
+    pub state: StatusState,
+
+    /// A label that identifies the status (eg. `"lint/clang-tidy"`).
+    ///
+    /// Posting a status with the same context replaces the previous status.
+    /// This must not be blank.
+    pub context: String,
+
+    /// A short description of the status.
+    ///
+    /// This is truncated to [`MAX_STATUS_DESCRIPTION`] characters.
+    pub description: Option<String>,
+
+    /// A link to more details about the status (eg. the CI workflow's run).
+    ///
+    /// This must be an absolute `http` or `https` URL.
+    pub target_url: Option<String>,
+}
+
+impl CommitStatus {
+    /// Validate that the commit status can be posted.
+    ///
+    /// Fails with a [`RestClientError::InvalidCommitStatus`] if the [`Self::context`]
+    /// is blank or the [`Self::target_url`] is not an absolute `http(s)` URL.
+    pub fn validate(&self) -> Result<(), RestClientError> {
+        if self.context.trim().is_empty() {
+            return Err(RestClientError::InvalidCommitStatus(
+                "context is blank".to_string(),
+            ));
+        }
+        if let Some(target_url) = &self.target_url {
+            match Url::parse(target_url) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                _ => {
+                    return Err(RestClientError::InvalidCommitStatus(format!(
+                        "target URL is not an http(s) URL: {target_url}"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the [`Self::description`] truncated to [`MAX_STATUS_DESCRIPTION`] characters.
+    ///
+    /// A truncated description ends with an ellipsis (`…`).
+    pub fn truncated_description(&self) -> Option<String> {
+        self.description.as_ref().map(|description| {
+            if description.chars().count() <= MAX_STATUS_DESCRIPTION {
+                description.clone()
+            } else {
+                let mut truncated = description
+                    .chars()
+                    .take(MAX_STATUS_DESCRIPTION - 1)
+                    .collect::<String>();
+                truncated.push('…');
+                truncated
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::{CommitStatus, MAX_STATUS_DESCRIPTION, StatusState};
+    use crate::RestClientError;
+
+    #[test]
+    fn parse_state() {
+        assert_eq!(
+            "Success".parse::<StatusState>().unwrap(),
+            StatusState::Success
+        );
+        assert_eq!("warn".parse::<StatusState>().unwrap(), StatusState::Warning);
+        assert!("passed".parse::<StatusState>().is_err());
+        for state in [
+            StatusState::Pending,
+            StatusState::Success,
+            StatusState::Error,
+            StatusState::Failure,
+            StatusState::Warning,
+        ] {
+            assert_eq!(state.to_string().parse::<StatusState>().unwrap(), state);
+        }
+    }
+
+    #[test]
+    fn truncate_description() {
+        let mut status = CommitStatus {
+            context: "lint".to_string(),
+            description: Some("é".repeat(MAX_STATUS_DESCRIPTION)),
+            ..Default::default()
+        };
+        assert_eq!(status.truncated_description(), status.description);
+        status.description = Some("é".repeat(MAX_STATUS_DESCRIPTION + 1));
+        let truncated = status.truncated_description().unwrap();
+        assert_eq!(truncated.chars().count(), MAX_STATUS_DESCRIPTION);
+        assert!(truncated.ends_with('…'));
+        status.description = None;
+        assert!(status.truncated_description().is_none());
+    }
+
+    #[test]
+    fn validate() {
+        let mut status = CommitStatus {
+            context: "lint".to_string(),
+            target_url: Some("https://example.com/runs/1".to_string()),
+            ..Default::default()
+        };
+        status.validate().unwrap();
+        for target_url in ["ftp://example.com", "runs/1"] {
+            status.target_url = Some(target_url.to_string());
+            assert!(matches!(
+                status.validate(),
+                Err(RestClientError::InvalidCommitStatus(_))
+            ));
+        }
+        status.target_url = None;
+        status.context = " ".to_string();
+        assert!(matches!(
+            status.validate(),
+            Err(RestClientError::InvalidCommitStatus(_))
+        ));
+    }
+}
@@ -0,0 +1,310 @@
+//! A module to parse compiler diagnostics into [`FileAnnotation`]s.
+use crate::{AnnotationLevel, FileAnnotation};
+
+/// The diagnostic levels recognized in compiler output.
+const LEVELS: [(&str, AnnotationLevel); 4] = [
+    ("fatal error", AnnotationLevel::Error),
+    ("error", AnnotationLevel::Error),
+    ("warning", AnnotationLevel::Warning),
+    ("note", AnnotationLevel::Notice),
+];
+
+/// A single diagnostic line parsed from compiler output.
+struct Diagnostic<'a> {
+    path: &'a str,
+    line: usize,
+    column: Option<usize>,
+    level: AnnotationLevel,
+    is_note: bool,
+    code: Option<&'a str>,
+    message: &'a str,
+}
+
+impl<'a> Diagnostic<'a> {
+    /// Parse a `path:line[:col]: level[code]: message` line.
+    ///
+    /// Returns [`None`] if the `line` is not a diagnostic.
+    fn parse(line: &'a str) -> Option<Self> {
+        // Search each ": " separator (instead of splitting on every colon)
+        // so that Windows drive letters (`C:\`) are kept in the path.
+        for (index, _) in line.match_indices(": ") {
+            let rest = &line[index + 2..];
+            let Some((name, level)) = LEVELS.iter().find(|(name, _)| rest.starts_with(name)) else {
+                continue;
+            };
+            let rest = &rest[name.len()..];
+            // rustc includes an error code: `error[E0308]: message`
+            let (code, rest) = match rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+                Some((code, rest)) => (Some(code), rest),
+                None => (None, rest),
+            };
+            let Some(message) = rest.strip_prefix(':') else {
+                continue;
+            };
+            let Some((path, line_number, column)) = Self::parse_location(&line[..index]) else {
+                continue;
+            };
+            return Some(Self {
+                path,
+                line: line_number,
+                column,
+                level: *level,
+                is_note: *name == "note",
+                code,
+                message: message.trim(),
+            });
+        }
+        None
+    }
+
+    /// Parse a `path:line[:col]` location.
+    fn parse_location(location: &str) -> Option<(&str, usize, Option<usize>)> {
+        let (rest, last) = location.rsplit_once(':')?;
+        let last = last.parse::<usize>().ok()?;
+        if let Some((path, line)) = rest.rsplit_once(':')
+            && let Ok(line) = line.parse::<usize>()
+            && !path.is_empty()
+        {
+            return Some((path, line, Some(last)));
+        }
+        if rest.is_empty() {
+            return None;
+        }
+        Some((rest, last, None))
+    }
+}
+
+impl FileAnnotation {
+    /// Parse the `text` output from a compiler into a list of file annotations.
+    ///
+    /// This recognizes lines formatted as `path:line:col: level: message`
+    /// (or `path:line: level: message`), as emitted by
+    ///
+    /// - GCC
+    /// - Clang
+    /// - rustc (with `--error-format=short`)
+    ///
+    /// The `level` is mapped to [`FileAnnotation::severity`]:
+    ///
+    /// | level                    | severity                     |
+    /// |--------------------------|------------------------------|
+    /// | `error` or `fatal error` | [`AnnotationLevel::Error`]   |
+    /// | `warning`                | [`AnnotationLevel::Warning`] |
+    /// | `note`                   | [`AnnotationLevel::Notice`]  |
+    ///
+    /// A diagnostic code (like rustc's `error[E0308]` or GCC's trailing `[-Wunused-variable]`)
+    /// is used as the [`FileAnnotation::title`].
+    ///
+    /// Any `note` lines that immediately follow a diagnostic are folded into that
+    /// diagnostic's [`FileAnnotation::message`]. Indented lines (like source code snippets)
+    /// between them are ignored. A `note` line without a preceding diagnostic yields
+    /// its own annotation.
+    pub fn from_compiler_output(text: &str) -> Vec<FileAnnotation> {
+        let mut annotations: Vec<FileAnnotation> = Vec::new();
+        // Can the next note be folded into the last annotation?
+        let mut can_fold = false;
+        for line in text.lines() {
+            let Some(diagnostic) = Diagnostic::parse(line) else {
+                // Indented lines are typically source code snippets
+                // that belong to the previous diagnostic.
+                if !line.starts_with(char::is_whitespace) {
+                    can_fold = false;
+                }
+                continue;
+            };
+            if diagnostic.is_note
+                && can_fold
+                && let Some(last) = annotations.last_mut()
+            {
+                last.message.push_str(
+                    format!(
+                        " (note: {}:{}: {})",
+                        diagnostic.path, diagnostic.line, diagnostic.message
+                    )
+                    .as_str(),
+                );
+                continue;
+            }
+            let (message, title) = match diagnostic.code {
+                Some(code) => (diagnostic.message, Some(code)),
+                None => match diagnostic
+                    .message
+                    .strip_suffix(']')
+                    .and_then(|m| m.rsplit_once(" ["))
+                {
+                    // GCC and Clang append the warning's flag: `message [-Wflag]`
+                    Some((message, flag)) if flag.starts_with('-') => (message, Some(flag)),
+                    _ => (diagnostic.message, None),
+                },
+            };
+            annotations.push(FileAnnotation {
+                severity: diagnostic.level,
+                path: diagnostic.path.replace('\\', "/"),
+                start_line: Some(diagnostic.line),
+                start_column: diagnostic.column,
+                title: title.map(str::to_string),
+                message: message.to_string(),
+                ..Default::default()
+            });
+            can_fold = !diagnostic.is_note;
+        }
+        annotations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnnotationLevel, FileAnnotation};
+
+    /// A condensed form of a [`FileAnnotation`] used for comparisons.
+    type Summary<'a> = (
+        AnnotationLevel,
+        &'a str,
+        Option<usize>,
+        Option<usize>,
+        Option<&'a str>,
+    );
+
+    fn summarize(annotations: &[FileAnnotation]) -> Vec<Summary<'_>> {
+        annotations
+            .iter()
+            .map(|a| {
+                (
+                    a.severity,
+                    a.path.as_str(),
+                    a.start_line,
+                    a.start_column,
+                    a.title.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn gcc() {
+        let output = "\
+src/main.c: In function 'main':
+src/main.c:10:9: warning: unused variable 'x' [-Wunused-variable]
+   10 |     int x;
+      |         ^
+src/main.c:14:5: error: implicit declaration of function 'foo' [-Wimplicit-function-declaration]
+   14 |     foo();
+      |     ^~~
+src/main.c:3:6: note: declared here
+    3 | void bar(void);
+      |      ^~~
+make: *** [Makefile:2: all] Error 1
+";
+        let annotations = FileAnnotation::from_compiler_output(output);
+        assert_eq!(
+            summarize(&annotations),
+            [
+                (
+                    AnnotationLevel::Warning,
+                    "src/main.c",
+                    Some(10),
+                    Some(9),
+                    Some("-Wunused-variable")
+                ),
+                (
+                    AnnotationLevel::Error,
+                    "src/main.c",
+                    Some(14),
+                    Some(5),
+                    Some("-Wimplicit-function-declaration")
+                ),
+            ]
+        );
+        assert_eq!(annotations[0].message, "unused variable 'x'");
+        assert_eq!(
+            annotations[1].message,
+            "implicit declaration of function 'foo' (note: src/main.c:3: declared here)"
+        );
+    }
+
+    #[test]
+    fn clang() {
+        let output = "\
+C:\\src\\x.c:10:2: warning: expression result unused [-Wunused-value]
+        1 + 2;
+        ~ ^ ~
+C:\\src\\x.c:22:1: fatal error: 'missing.h' file not found
+#include \"missing.h\"
+^~~~~~~~~~~
+lib/util.h:7:1: note: previous definition is here
+2 warnings and 1 error generated.
+";
+        let annotations = FileAnnotation::from_compiler_output(output);
+        assert_eq!(
+            summarize(&annotations),
+            [
+                (
+                    AnnotationLevel::Warning,
+                    "C:/src/x.c",
+                    Some(10),
+                    Some(2),
+                    Some("-Wunused-value")
+                ),
+                (
+                    AnnotationLevel::Error,
+                    "C:/src/x.c",
+                    Some(22),
+                    Some(1),
+                    None
+                ),
+                // not folded because a non-indented line separates it from the error
+                (
+                    AnnotationLevel::Notice,
+                    "lib/util.h",
+                    Some(7),
+                    Some(1),
+                    None
+                ),
+            ]
+        );
+        assert_eq!(annotations[1].message, "'missing.h' file not found");
+    }
+
+    #[test]
+    fn rustc() {
+        let output = "\
+src/main.rs:2:9: warning: unused variable: `x`
+src/lib.rs:5:18: error[E0308]: mismatched types: expected `u32`, found `&str`
+src/lib.rs:3:8: note: function defined here
+build.rs:12: warning: a location without a column
+error: could not compile `demo` (lib) due to 1 previous error
+";
+        let annotations = FileAnnotation::from_compiler_output(output);
+        assert_eq!(
+            summarize(&annotations),
+            [
+                (
+                    AnnotationLevel::Warning,
+                    "src/main.rs",
+                    Some(2),
+                    Some(9),
+                    None
+                ),
+                (
+                    AnnotationLevel::Error,
+                    "src/lib.rs",
+                    Some(5),
+                    Some(18),
+                    Some("E0308")
+                ),
+                (AnnotationLevel::Warning, "build.rs", Some(12), None, None),
+            ]
+        );
+        assert_eq!(annotations[0].message, "unused variable: `x`");
+        assert_eq!(
+            annotations[1].message,
+            "mismatched types: expected `u32`, found `&str` (note: src/lib.rs:3: function defined here)"
+        );
+    }
+
+    #[test]
+    fn no_diagnostics() {
+        let output = "Compiling demo v0.1.0\nFinished `dev` profile: 0 warnings\n";
+        assert!(FileAnnotation::from_compiler_output(output).is_empty());
+    }
+}
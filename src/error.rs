@@ -1,17 +1,27 @@
 //! Error types used across the git-bot-feedback crate.
 #[cfg(feature = "pyo3")]
 use pyo3::{
-    exceptions::{PyOSError, PyRuntimeError, PyValueError},
+    exceptions::{
+        PyOSError, PyRuntimeError, PyTimeoutError, PyValueError, asyncio::CancelledError,
+    },
     prelude::*,
 };
 
 #[cfg(feature = "file-changes")]
 use std::path::PathBuf;
 
-use chrono::{DateTime, Utc};
+use std::time::Duration;
 use thiserror::Error;
 
-use crate::client::MAX_RETRIES;
+use reqwest::{Response, StatusCode};
+
+use crate::{
+    AnnotationLevel,
+    client::{MAX_RETRIES, Timestamp, is_retryable_status, is_transient_error},
+};
+
+/// The most bytes of a response body kept in a [`RestClientError::Http`] error.
+pub const MAX_HTTP_ERROR_BODY: usize = 4096;
 
 /// The possible errors emitted when parsing git diffs.
 #[derive(Debug, thiserror::Error)]
@@ -42,6 +52,17 @@ pub enum OutputVariableError {
     /// The output variable's value contains non-printable characters.
     #[error("The output variable's value contains non-printable characters: '{0}'")]
     ValueContainsNonPrintableCharacters(String),
+    /// A delimiter for the output variable's multi-line value could not be generated
+    /// because every attempted delimiter occurs in the value.
+    #[error("The output variable's value contains the generated heredoc delimiter: '{0}'")]
+    ValueContainsDelimiter(String),
+    /// The output variable's value spans multiple lines,
+    /// which cannot be written to a dotenv file.
+    #[error("The output variable's value spans multiple lines: '{0}'")]
+    MultiLineValue(String),
+    /// The output variable's name contains a `-`, which cannot be written to a dotenv file.
+    #[error("The output variable's name contains a hyphen: '{0}'")]
+    NameContainsHyphen(String),
     /// Unsupported CI platform.
     #[error("Unsupported CI platform")]
     UnsupportedPlatform,
@@ -56,6 +77,14 @@ pub enum RestClientError {
     #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
     DiffError(#[from] DiffError),
 
+    /// Errors related to walking a directory.
+    ///
+    /// See [`FileFilter::walk_dir()`](crate::FileFilter::walk_dir).
+    #[error(transparent)]
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    DirWalk(#[from] DirWalkError),
+
     /// Error emitted when encountering malformed event information.
     #[error("Encountered malformed event info: {0}")]
     MalformedEventInfo(String),
@@ -74,6 +103,26 @@ pub enum RestClientError {
         source: reqwest::Error,
     },
 
+    /// Error emitted when an operation exceeds its deadline.
+    ///
+    /// See [`Timeouts::deadline`](crate::client::Timeouts::deadline).
+    #[error("Failed to {task}: deadline of {} seconds exceeded", .deadline.as_secs_f64())]
+    Timeout {
+        /// The task being attempted.
+        task: String,
+        /// The deadline that was exceeded.
+        deadline: Duration,
+    },
+
+    /// Error emitted when an operation is interrupted by its cancellation token.
+    ///
+    /// See [`RestApiClient::set_cancellation_token()`](crate::RestApiClient::set_cancellation_token).
+    #[error("Failed to {task}: operation was cancelled")]
+    Cancelled {
+        /// The task being attempted.
+        task: String,
+    },
+
     /// Errors related to standard I/O.
     #[error("Failed to {task}: {source}")]
     Io {
@@ -90,20 +139,103 @@ pub enum RestClientError {
     #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
     GitCommand(String),
 
+    /// Error emitted when a git reference (eg. a branch or commit) does not exist.
+    ///
+    /// See [`DiffScope`](crate::client::DiffScope).
+    #[error("Git reference '{0}' does not exist")]
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    GitRefNotFound(String),
+
+    /// Error emitted when the history needed to compare changes is missing from a shallow clone.
+    ///
+    /// See [`LocalClient::set_max_deepen()`](crate::client::LocalClient::set_max_deepen).
+    #[error(
+        "The history needed for '{missing_ref}' is missing from the shallow clone; \
+        fetch more history (eg. `git fetch --deepen=50`) or clone without `--depth`"
+    )]
+    #[cfg(feature = "file-changes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+    ShallowClone {
+        /// The git reference whose history is missing.
+        missing_ref: String,
+    },
+
     /// Error related to exceeding REST API Rate limits and
     /// no reset time is provided in the response headers.
     #[error("Primary Rate Limit exceeded (no reset time provided)")]
     RateLimitNoReset,
 
     /// Error related to exceeding REST API Rate limits with a known reset time.
-    #[error("Primary Rate Limit exceeded; resets at {0}")]
-    RateLimitPrimary(DateTime<Utc>),
+    #[error(
+        "Primary Rate Limit exceeded; resets at {}",
+        crate::client::timestamp::display(.0)
+    )]
+    RateLimitPrimary(Timestamp),
 
     /// Error related to exhausting all retries after hitting REST API Rate limits.
-    #[error("Rate Limit exceeded after all {MAX_RETRIES} retries exhausted")]
-    RateLimitSecondary,
+    #[error(
+        "Rate Limit exceeded after all {MAX_RETRIES} retries exhausted{}",
+        .retry_after.map(|d| format!("; retry after {} seconds", d.as_secs())).unwrap_or_default()
+    )]
+    RateLimitSecondary {
+        /// The time to wait before retrying, as last requested by the server.
+        retry_after: Option<Duration>,
+    },
+
+    /// Error emitted when a failed request is not retried because it may not be idempotent.
+    ///
+    /// See [`RetryPolicy::retry_unsafe`](crate::client::RetryPolicy::retry_unsafe).
+    #[error("Not retrying {method} request because it may not be idempotent: {reason}")]
+    UnsafeRetry {
+        /// The HTTP method of the request.
+        method: String,
+        /// Why the request would have been retried.
+        reason: String,
+    },
+
+    /// Error emitted when the server responds with an error status.
+    ///
+    /// See [`RestClientError::check_response()`].
+    #[error("Failed to {context}: server responded with {status}")]
+    Http {
+        /// The status code of the response.
+        status: StatusCode,
+        /// The response body (truncated to [`MAX_HTTP_ERROR_BODY`] bytes).
+        body: String,
+        /// The task that failed.
+        context: String,
+    },
+
+    /// Error emitted with context about the task that failed.
+    ///
+    /// See [`RestClientError::with_context()`].
+    #[error("Failed to {context}: {source}")]
+    WithContext {
+        /// The task being attempted.
+        context: String,
+        /// The original error being propagated.
+        #[source]
+        source: Box<RestClientError>,
+    },
+
+    /// Error emitted when no supported CI platform is detected.
+    ///
+    /// See [`client::from_env()`](crate::client::from_env).
+    #[error(
+        "No supported CI platform detected{}; checked env vars: {}",
+        .detected.as_ref().map(|p| format!(" ({p} is not supported)")).unwrap_or_default(),
+        .checked.join(", ")
+    )]
+    UnsupportedCi {
+        /// The CI platform that was detected but is not supported (if any).
+        detected: Option<String>,
+        /// The environment variables that were checked.
+        checked: Vec<String>,
+    },
 
     /// Error emitted when failing to clone a request object.
+    #[deprecated(note = "requests are rebuilt for each attempt instead of cloned")]
     #[error("Failed to clone request object for auto-retries")]
     CannotCloneRequest,
 
@@ -143,6 +275,58 @@ pub enum RestClientError {
         source: std::env::VarError,
     },
 
+    /// Error emitted when none of the environment variables that can provide a setting are set.
+    #[error("None of the following env vars are set: {}", .checked.join(", "))]
+    MissingEnvVars {
+        /// The environment variables that were checked (in order of precedence).
+        checked: Vec<String>,
+    },
+
+    /// Error emitted when there are more file annotations than the Git server can display
+    /// and [`AnnotationOverflow::Error`](crate::AnnotationOverflow::Error) is used.
+    #[error("Too many {severity:?} annotations ({count}); only {limit} can be displayed")]
+    TooManyAnnotations {
+        /// The severity of annotations that exceeded the limit.
+        severity: AnnotationLevel,
+        /// The number of annotations given with the `severity`.
+        count: usize,
+        /// The maximum number of annotations (with the `severity`) that can be displayed.
+        limit: usize,
+    },
+
+    /// Error emitted when appending to a CI step's summary would exceed its size limit
+    /// and [`SummaryOverflow::Error`](crate::summary::SummaryOverflow::Error) is used.
+    #[error("Step summary would be {size} bytes; only {limit} bytes can be displayed")]
+    StepSummaryTooLarge {
+        /// The size (in bytes) the step summary would have been.
+        size: u64,
+        /// The maximum size (in bytes) of the step summary.
+        limit: u64,
+    },
+
+    /// Error emitted when a [`CommitStatus`](crate::CommitStatus) cannot be posted
+    /// (eg. its target URL is not an `http(s)` URL).
+    #[error("Commit status is invalid: {0}")]
+    InvalidCommitStatus(String),
+
+    /// Error emitted when a requested file is binary (its content is not valid UTF-8).
+    ///
+    /// See [`RestApiClient::get_file_content()`](crate::RestApiClient::get_file_content).
+    #[error("File '{path}' is binary (its content is not valid UTF-8)")]
+    BinaryFile {
+        /// The path of the requested file.
+        path: String,
+    },
+
+    /// Error emitted when an operation is not supported by the git server (or client).
+    #[error("Failed to {task}: unsupported on this server ({server})")]
+    Unsupported {
+        /// The task being attempted.
+        task: String,
+        /// The kind of client (see [`RestApiClient::client_kind()`](crate::RestApiClient::client_kind)).
+        server: String,
+    },
+
     /// An error emitted when encountering an invalid [`OutputVariable`](crate::output_variable::OutputVariable).
     #[error("OutputVariable is malformed: {0}")]
     OutputVar(#[from] OutputVariableError),
@@ -179,6 +363,32 @@ impl RestClientError {
         }
     }
 
+    /// Builder function to add context to any error.
+    ///
+    /// - A [`Self::Request`] error becomes a [`Self::RequestContext`] error.
+    /// - An error that already describes the task being attempted (eg. [`Self::Json`]
+    ///   or [`Self::Http`]) is returned unchanged.
+    /// - Any other error is wrapped in a [`Self::WithContext`] error.
+    pub fn with_context(self, context: &str) -> Self {
+        match self {
+            Self::Request(_) => self.add_request_context(context),
+            Self::RequestContext { .. }
+            | Self::Timeout { .. }
+            | Self::Cancelled { .. }
+            | Self::Io { .. }
+            | Self::Http { .. }
+            | Self::Unsupported { .. }
+            | Self::BinaryFile { .. }
+            | Self::WithContext { .. }
+            | Self::Json { .. }
+            | Self::EnvVar { .. } => self,
+            _ => Self::WithContext {
+                context: context.to_string(),
+                source: Box::new(self),
+            },
+        }
+    }
+
     /// Helper function to create a [`Self::Json`] error with task context.
     pub fn json(task: &str, source: serde_json::Error) -> Self {
         Self::Json {
@@ -186,6 +396,120 @@ impl RestClientError {
             source,
         }
     }
+
+    /// The HTTP status code of the response that caused this error, if any.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Request(e) | Self::RequestContext { source: e, .. } => e.status(),
+            Self::Http { status, .. } => Some(*status),
+            Self::WithContext { source, .. } => source.status(),
+            _ => None,
+        }
+    }
+
+    /// Is this error caused by exceeding a REST API rate limit?
+    ///
+    /// This includes responses with a `429 Too Many Requests` status.
+    pub fn is_rate_limit(&self) -> bool {
+        match self {
+            Self::RateLimitNoReset
+            | Self::RateLimitPrimary(_)
+            | Self::RateLimitSecondary { .. } => true,
+            Self::WithContext { source, .. } => source.is_rate_limit(),
+            _ => self.status() == Some(StatusCode::TOO_MANY_REQUESTS),
+        }
+    }
+
+    /// Is the failed operation worth retrying (later)?
+    ///
+    /// This uses the same classification as the retries in
+    /// [`RestApiClient::send_api_request()`](crate::RestApiClient::send_api_request).
+    /// An error is retryable if it is caused by
+    ///
+    /// - exceeding a rate limit (see [`Self::is_rate_limit()`]),
+    /// - exceeding a [`Self::Timeout`] deadline,
+    /// - a transient network failure (eg. a dropped connection),
+    /// - a server error that is typically transient (`500`, `502`, `503` or `504`), or
+    /// - a transient failure that was not retried because the request may not be idempotent
+    ///   (see [`Self::UnsafeRetry`]).
+    ///
+    /// Other errors (eg. client errors like `422 Unprocessable Entity`,
+    /// malformed JSON, or missing environment variables) are not retryable.
+    pub fn is_retryable(&self) -> bool {
+        if self.is_rate_limit() || self.status().is_some_and(is_retryable_status) {
+            return true;
+        }
+        match self {
+            Self::Request(e) | Self::RequestContext { source: e, .. } => is_transient_error(e),
+            Self::Timeout { .. } | Self::UnsafeRetry { .. } => true,
+            Self::WithContext { source, .. } => source.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// Check the status of the given `response`.
+    ///
+    /// Returns the `response` if the status is not an error.
+    /// Otherwise, the `response` is consumed into a [`Self::Http`] error
+    /// with the given `context` (eg. "post thread comment").
+    /// Only the first [`MAX_HTTP_ERROR_BODY`] bytes of the response body are kept.
+    pub async fn check_response(response: Response, context: &str) -> Result<Response, Self> {
+        let status = response.status();
+        if !(status.is_client_error() || status.is_server_error()) {
+            return Ok(response);
+        }
+        let mut response = response;
+        let mut body = Vec::new();
+        while body.len() < MAX_HTTP_ERROR_BODY {
+            match response.chunk().await {
+                Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+                // the body is only informative; a failure to read it is not the error to report
+                Ok(None) | Err(_) => break,
+            }
+        }
+        body.truncate(MAX_HTTP_ERROR_BODY);
+        Err(Self::Http {
+            status,
+            body: String::from_utf8_lossy(&body).into_owned(),
+            context: context.to_string(),
+        })
+    }
+}
+
+/// Error emitted when parsing a string into one of the public enums fails.
+///
+/// See the [`FromStr`](std::str::FromStr) implementations of
+/// [`CommentPolicy`](crate::CommentPolicy), [`CommentKind`](crate::CommentKind),
+/// [`ReviewAction`](crate::ReviewAction), [`AnnotationLevel`] and `LinesChangedOnly`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("Unknown {name} '{value}'; expected one of: {}", .expected.join(", "))]
+pub struct ParseEnumError {
+    /// The name of the enum being parsed (eg. "comment policy").
+    pub name: &'static str,
+    /// The value that failed to parse.
+    pub value: String,
+    /// The accepted spellings.
+    pub expected: Vec<&'static str>,
+}
+
+impl ParseEnumError {
+    /// Parse the given `value` as one of the given `spellings` (case-insensitive).
+    pub(crate) fn parse<T: Clone>(
+        name: &'static str,
+        value: &str,
+        spellings: &[(&'static str, T)],
+    ) -> Result<T, Self> {
+        let lowered = value.trim().to_ascii_lowercase();
+        spellings
+            .iter()
+            .find(|(spelling, _)| *spelling == lowered)
+            .map(|(_, variant)| variant.clone())
+            .ok_or_else(|| Self {
+                name,
+                value: value.to_string(),
+                expected: spellings.iter().map(|(spelling, _)| *spelling).collect(),
+            })
+    }
 }
 
 /// The possible errors emitted by file system operations.
@@ -218,9 +542,10 @@ impl From<OutputVariableError> for PyErr {
             OutputVariableError::NameIsEmpty
             | OutputVariableError::NameStartsWithNumber(_)
             | OutputVariableError::NameContainsNonPrintableCharacters(_)
-            | OutputVariableError::ValueContainsNonPrintableCharacters(_) => {
-                PyValueError::new_err(format!("{e:?}"))
-            }
+            | OutputVariableError::ValueContainsNonPrintableCharacters(_)
+            | OutputVariableError::ValueContainsDelimiter(_)
+            | OutputVariableError::MultiLineValue(_)
+            | OutputVariableError::NameContainsHyphen(_) => PyValueError::new_err(format!("{e:?}")),
             OutputVariableError::UnsupportedPlatform => PyRuntimeError::new_err(format!("{e:?}")),
         }
     }
@@ -245,29 +570,57 @@ impl From<DirWalkError> for PyErr {
 }
 
 #[cfg(feature = "pyo3")]
+#[allow(deprecated)]
 impl From<RestClientError> for PyErr {
     fn from(err: RestClientError) -> Self {
         match err {
             #[cfg(feature = "file-changes")]
             RestClientError::DiffError(e) => e.into(),
-            RestClientError::MalformedEventInfo(_) => PyRuntimeError::new_err(format!("{err:?}")),
+            #[cfg(feature = "file-changes")]
+            RestClientError::DirWalk(e) => e.into(),
+            RestClientError::MalformedEventInfo(_) | RestClientError::UnsupportedCi { .. } => {
+                PyRuntimeError::new_err(format!("{err:?}"))
+            }
             RestClientError::Request(e) => PyOSError::new_err(format!("{e:?}")),
+            RestClientError::Timeout { .. } => PyTimeoutError::new_err(format!("{err:?}")),
+            RestClientError::Cancelled { .. } => CancelledError::new_err(format!("{err:?}")),
             RestClientError::RequestContext { task: _, source: _ }
             | RestClientError::Io { task: _, source: _ }
             | RestClientError::RateLimitNoReset
             | RestClientError::RateLimitPrimary(_)
-            | RestClientError::RateLimitSecondary => PyOSError::new_err(format!("{err:?}")),
+            | RestClientError::RateLimitSecondary { .. }
+            | RestClientError::UnsafeRetry { .. }
+            | RestClientError::Http { .. }
+            | RestClientError::WithContext { .. } => PyOSError::new_err(format!("{err:?}")),
             RestClientError::CannotCloneRequest
             | RestClientError::InvalidHeaderValue(_)
             | RestClientError::UnexpectedHeaderValue(_)
             | RestClientError::HeaderParseInt(_)
             | RestClientError::UrlParse(_)
             | RestClientError::Json { task: _, source: _ }
-            | RestClientError::EnvVar { name: _, source: _ } => {
+            | RestClientError::EnvVar { name: _, source: _ }
+            | RestClientError::MissingEnvVars { checked: _ } => {
                 PyValueError::new_err(format!("{err:?}"))
             }
             #[cfg(feature = "file-changes")]
-            RestClientError::GitCommand(_) => PyValueError::new_err(format!("{err:?}")),
+            RestClientError::GitCommand(_) | RestClientError::GitRefNotFound(_) => {
+                PyValueError::new_err(format!("{err:?}"))
+            }
+            #[cfg(feature = "file-changes")]
+            RestClientError::ShallowClone { missing_ref: _ } => {
+                PyRuntimeError::new_err(format!("{err:?}"))
+            }
+            RestClientError::TooManyAnnotations {
+                severity: _,
+                count: _,
+                limit: _,
+            }
+            | RestClientError::StepSummaryTooLarge { size: _, limit: _ }
+            | RestClientError::InvalidCommitStatus(_)
+            | RestClientError::BinaryFile { path: _ } => PyValueError::new_err(format!("{err:?}")),
+            RestClientError::Unsupported { task: _, server: _ } => {
+                PyRuntimeError::new_err(format!("{err:?}"))
+            }
             RestClientError::OutputVar(e) => e.into(),
         }
     }
@@ -275,9 +628,17 @@ impl From<RestClientError> for PyErr {
 
 #[cfg(test)]
 mod tests {
-    use super::RestClientError;
+    #![allow(clippy::unwrap_used)]
+    use std::time::Duration;
+
+    use reqwest::{Response, StatusCode};
+
+    use super::{AnnotationLevel, MAX_HTTP_ERROR_BODY, OutputVariableError, RestClientError};
+    #[cfg(feature = "file-changes")]
+    use super::{DiffError, DirWalkError};
 
     #[test]
+    #[allow(deprecated)]
     fn no_added_req_ctx() {
         let err = RestClientError::CannotCloneRequest;
         assert!(matches!(
@@ -285,4 +646,291 @@ mod tests {
             RestClientError::CannotCloneRequest
         ));
     }
+
+    #[test]
+    fn with_context() {
+        let err =
+            RestClientError::UrlParse(url::ParseError::EmptyHost).with_context("get comments");
+        assert_eq!(
+            err.to_string(),
+            "Failed to get comments: Failed to parse URL:empty host"
+        );
+        // errors that already have context are not wrapped again
+        let err = err.with_context("post comment");
+        assert!(matches!(
+            &err,
+            RestClientError::WithContext { context, .. } if context == "get comments"
+        ));
+        let err = RestClientError::json(
+            "deserialize comments",
+            serde_json::from_str::<u8>("").unwrap_err(),
+        );
+        assert!(matches!(
+            err.with_context("get comments"),
+            RestClientError::Json { task, .. } if task == "deserialize comments"
+        ));
+    }
+
+    fn response(status: u16, body: String) -> Response {
+        http::Response::builder()
+            .status(status)
+            .body(body)
+            .unwrap()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn http_error_truncated_body() {
+        let body = "x".repeat(MAX_HTTP_ERROR_BODY * 2);
+        let result = RestClientError::check_response(response(422, body), "post comment").await;
+        let err = result.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to post comment: server responded with 422 Unprocessable Entity"
+        );
+        assert!(matches!(
+            err,
+            RestClientError::Http { status, body, .. }
+                if status == StatusCode::UNPROCESSABLE_ENTITY && body.len() == MAX_HTTP_ERROR_BODY
+        ));
+    }
+
+    #[tokio::test]
+    async fn http_success_passes() {
+        let result = RestClientError::check_response(response(201, "ok".to_string()), "post").await;
+        assert_eq!(result.unwrap().text().await.unwrap(), "ok");
+    }
+
+    /// Get a [`reqwest::Error`] from failing to connect to a closed port.
+    async fn connect_error() -> reqwest::Error {
+        reqwest::Client::new()
+            .get("http://127.0.0.1:1")
+            .send()
+            .await
+            .unwrap_err()
+    }
+
+    /// Get a [`reqwest::Error`] from a response with the given `status`.
+    fn status_error(status: u16) -> reqwest::Error {
+        response(status, String::new())
+            .error_for_status()
+            .unwrap_err()
+    }
+
+    #[tokio::test]
+    #[allow(deprecated)]
+    async fn classify_errors() {
+        let http = |status: u16| RestClientError::Http {
+            status: StatusCode::from_u16(status).unwrap(),
+            body: String::new(),
+            context: "post comment".to_string(),
+        };
+        let json_err = serde_json::from_str::<u8>("").unwrap_err();
+        // (error, status, is rate limit, is retryable)
+        let cases = vec![
+            #[cfg(feature = "file-changes")]
+            (
+                RestClientError::DiffError(DiffError::MalformedDiffError("".to_string())),
+                None,
+                false,
+                false,
+            ),
+            #[cfg(feature = "file-changes")]
+            (
+                RestClientError::GitCommand("".to_string()),
+                None,
+                false,
+                false,
+            ),
+            #[cfg(feature = "file-changes")]
+            (
+                RestClientError::DirWalk(DirWalkError::OsError(std::io::Error::other("oops"))),
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::MalformedEventInfo("".to_string()),
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::Request(connect_error().await),
+                None,
+                false,
+                true,
+            ),
+            (
+                RestClientError::Request(status_error(503)),
+                Some(StatusCode::SERVICE_UNAVAILABLE),
+                false,
+                true,
+            ),
+            (
+                RestClientError::Request(status_error(429)).add_request_context("get comments"),
+                Some(StatusCode::TOO_MANY_REQUESTS),
+                true,
+                true,
+            ),
+            (
+                RestClientError::Timeout {
+                    task: "post comment".to_string(),
+                    deadline: Duration::from_secs(1),
+                },
+                None,
+                false,
+                true,
+            ),
+            (
+                RestClientError::Cancelled {
+                    task: "post comment".to_string(),
+                },
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::io("read file", std::io::Error::other("oops")),
+                None,
+                false,
+                false,
+            ),
+            (RestClientError::RateLimitNoReset, None, true, true),
+            (
+                RestClientError::RateLimitPrimary(crate::client::timestamp::UNIX_EPOCH),
+                None,
+                true,
+                true,
+            ),
+            (
+                RestClientError::RateLimitSecondary { retry_after: None },
+                None,
+                true,
+                true,
+            ),
+            (
+                RestClientError::UnsafeRetry {
+                    method: "POST".to_string(),
+                    reason: "transient failure".to_string(),
+                },
+                None,
+                false,
+                true,
+            ),
+            (
+                http(422),
+                Some(StatusCode::UNPROCESSABLE_ENTITY),
+                false,
+                false,
+            ),
+            (http(502), Some(StatusCode::BAD_GATEWAY), false, true),
+            (
+                http(404).with_context("post comment"),
+                Some(StatusCode::NOT_FOUND),
+                false,
+                false,
+            ),
+            (
+                RestClientError::RateLimitNoReset.with_context("post comment"),
+                None,
+                true,
+                true,
+            ),
+            (RestClientError::CannotCloneRequest, None, false, false),
+            (
+                RestClientError::UnsupportedCi {
+                    detected: None,
+                    checked: vec!["GITHUB_ACTIONS".to_string()],
+                },
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::InvalidHeaderValue(
+                    reqwest::header::HeaderValue::from_str("\n").unwrap_err(),
+                ),
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::UnexpectedHeaderValue(
+                    reqwest::header::HeaderValue::from_bytes(b"\xff")
+                        .unwrap()
+                        .to_str()
+                        .unwrap_err(),
+                ),
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::HeaderParseInt("x".parse::<i64>().unwrap_err()),
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::UrlParse(url::ParseError::EmptyHost),
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::json("deserialize comments", json_err),
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::env_var("GITHUB_TOKEN", std::env::VarError::NotPresent),
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::MissingEnvVars {
+                    checked: vec!["GITEA_API_URL".to_string()],
+                },
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::TooManyAnnotations {
+                    severity: AnnotationLevel::Notice,
+                    count: 2,
+                    limit: 1,
+                },
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::StepSummaryTooLarge { size: 2, limit: 1 },
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::InvalidCommitStatus("context is blank".to_string()),
+                None,
+                false,
+                false,
+            ),
+            (
+                RestClientError::OutputVar(OutputVariableError::NameIsEmpty),
+                None,
+                false,
+                false,
+            ),
+        ];
+        for (err, status, is_rate_limit, is_retryable) in cases {
+            assert_eq!(err.status(), status, "{err:?}");
+            assert_eq!(err.is_rate_limit(), is_rate_limit, "{err:?}");
+            assert_eq!(err.is_retryable(), is_retryable, "{err:?}");
+        }
+    }
 }
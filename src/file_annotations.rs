@@ -1,6 +1,15 @@
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
 
+use std::str::FromStr;
+
+use crate::error::ParseEnumError;
+
+#[cfg(feature = "file-changes")]
+use crate::{FileDiffLines, LinesChangedOnly};
+#[cfg(feature = "file-changes")]
+use std::collections::HashMap;
+
 /// A structure to describe the output of a file annotation.
 #[derive(Debug, Default, Clone)]
 #[cfg_attr(
@@ -98,12 +107,65 @@ impl FileAnnotation {
     }
 }
 
+#[cfg(feature = "file-changes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+impl FileAnnotation {
+    /// Split the given `annotations` into those about the changes in `files` and those that are not.
+    ///
+    /// The `files` are typically obtained from
+    /// [`RestApiClient::get_list_of_changed_files()`](crate::client::RestApiClient::get_list_of_changed_files).
+    ///
+    /// An annotation is kept if its [`FileAnnotation::path`] is in `files` and
+    ///
+    /// - its [`FileAnnotation::start_line`] is within the lines selected by `lines_changed_only`
+    ///   (see [`FileDiffLines::get_ranges()`]), or
+    /// - it has no [`FileAnnotation::start_line`] (scoped to the entire file), or
+    /// - `lines_changed_only` is [`LinesChangedOnly::Off`].
+    ///
+    /// Returns a tuple of the kept annotations and the dropped annotations.
+    pub fn retain_in_diff(
+        annotations: Vec<FileAnnotation>,
+        files: &HashMap<String, FileDiffLines>,
+        lines_changed_only: &LinesChangedOnly,
+    ) -> (Vec<FileAnnotation>, Vec<FileAnnotation>) {
+        annotations.into_iter().partition(|annotation| {
+            let Some(diff_lines) = files.get(&annotation.path) else {
+                return false;
+            };
+            match (
+                annotation.start_line,
+                diff_lines.get_ranges(lines_changed_only),
+            ) {
+                (Some(line), Some(ranges)) => {
+                    let line = line as u32;
+                    ranges.iter().any(|range| range.contains(&line))
+                }
+                _ => true,
+            }
+        })
+    }
+}
+
 /// The severity of a [`FileAnnotation`].
+///
+/// This can be parsed from a string (case-insensitive):
+///
+/// | Variant | Spellings |
+/// |---------|-----------|
+/// | [`AnnotationLevel::Debug`] | `debug` |
+/// | [`AnnotationLevel::Notice`] | `notice` |
+/// | [`AnnotationLevel::Warning`] | `warning`, `warn` |
+/// | [`AnnotationLevel::Error`] | `error` |
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(
     feature = "pyo3",
     pyclass(module = "git_bot_feedback", from_py_object, eq)
 )]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
 pub enum AnnotationLevel {
     /// The annotation is for debugging purposes.
     Debug,
@@ -115,3 +177,159 @@ pub enum AnnotationLevel {
     /// The annotation is for error purposes.
     Error,
 }
+
+impl FromStr for AnnotationLevel {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ParseEnumError::parse(
+            "annotation level",
+            s,
+            &[
+                ("debug", Self::Debug),
+                ("notice", Self::Notice),
+                ("warning", Self::Warning),
+                ("warn", Self::Warning),
+                ("error", Self::Error),
+            ],
+        )
+    }
+}
+
+impl std::fmt::Display for AnnotationLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Debug => write!(f, "debug"),
+            Self::Notice => write!(f, "notice"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// The policy applied when there are more [`FileAnnotation`]s than a Git server can display.
+///
+/// For example, GitHub only displays 10 errors, 10 warnings, and 10 notices per CI step.
+/// Any annotations beyond that limit are silently dropped by GitHub.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "pyo3",
+    pyclass(module = "git_bot_feedback", from_py_object, eq)
+)]
+pub enum AnnotationOverflow {
+    /// Only write the annotations that will be displayed.
+    ///
+    /// A warning is logged about the number of annotations that were dropped.
+    #[default]
+    Truncate,
+    /// Replace the last displayed annotation of each severity with an annotation that
+    /// states how many annotations were omitted.
+    Summarize,
+    /// Do not write any annotations and return an error instead.
+    Error,
+}
+
+#[cfg(all(test, feature = "file-changes"))]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::{FileAnnotation, FileDiffLines, HashMap, LinesChangedOnly};
+
+    /// A synthetic diff with 1 file that has 2 hunks.
+    ///
+    /// Lines 3-7 and 10-14 are in the diff, but only lines 4, 5, and 12 were added.
+    fn changed_files() -> HashMap<String, FileDiffLines> {
+        HashMap::from([(
+            "src/lib.rs".to_string(),
+            FileDiffLines::with_info(vec![4, 5, 12], vec![3..8, 10..15]),
+        )])
+    }
+
+    fn annotations() -> Vec<FileAnnotation> {
+        [
+            ("src/lib.rs", Some(4)),
+            ("src/lib.rs", Some(6)),
+            ("src/lib.rs", Some(9)),
+            ("src/lib.rs", None),
+            ("src/main.rs", Some(4)),
+            ("src/main.rs", None),
+        ]
+        .into_iter()
+        .map(|(path, start_line)| FileAnnotation {
+            path: path.to_string(),
+            start_line,
+            message: "some message".to_string(),
+            ..Default::default()
+        })
+        .collect()
+    }
+
+    fn retain(lines_changed_only: LinesChangedOnly) -> (Vec<Option<usize>>, usize) {
+        let (kept, dropped) =
+            FileAnnotation::retain_in_diff(annotations(), &changed_files(), &lines_changed_only);
+        assert!(kept.iter().all(|a| a.path == "src/lib.rs"));
+        (kept.iter().map(|a| a.start_line).collect(), dropped.len())
+    }
+
+    #[test]
+    fn retain_off() {
+        assert_eq!(
+            retain(LinesChangedOnly::Off),
+            (vec![Some(4), Some(6), Some(9), None], 2)
+        );
+    }
+
+    #[test]
+    fn retain_diff() {
+        assert_eq!(
+            retain(LinesChangedOnly::Diff),
+            (vec![Some(4), Some(6), None], 3)
+        );
+    }
+
+    #[test]
+    fn retain_on() {
+        assert_eq!(retain(LinesChangedOnly::On), (vec![Some(4), None], 4));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_severity() {
+        use super::AnnotationLevel;
+
+        for (level, name) in [
+            (AnnotationLevel::Debug, "\"debug\""),
+            (AnnotationLevel::Notice, "\"notice\""),
+            (AnnotationLevel::Warning, "\"warning\""),
+            (AnnotationLevel::Error, "\"error\""),
+        ] {
+            assert_eq!(serde_json::to_string(&level).unwrap(), name);
+            assert_eq!(
+                serde_json::from_str::<AnnotationLevel>(name).unwrap(),
+                level
+            );
+        }
+    }
+
+    #[test]
+    fn parse_annotation_level() {
+        use super::AnnotationLevel;
+
+        for (spelling, expected) in [
+            ("debug", AnnotationLevel::Debug),
+            ("Notice", AnnotationLevel::Notice),
+            ("warning", AnnotationLevel::Warning),
+            ("WARN", AnnotationLevel::Warning),
+            ("error", AnnotationLevel::Error),
+        ] {
+            assert_eq!(spelling.parse::<AnnotationLevel>().unwrap(), expected);
+            assert_eq!(
+                expected.to_string().parse::<AnnotationLevel>().unwrap(),
+                expected
+            );
+        }
+        assert_eq!(
+            "fatal".parse::<AnnotationLevel>().unwrap_err().to_string(),
+            "Unknown annotation level 'fatal'; expected one of: debug, notice, warning, warn, error"
+        );
+    }
+}
@@ -36,6 +36,15 @@ pub struct FileFilter {
     /// no extension (eg. ".clang-format").
     pub extensions: HashSet<String>,
 
+    /// Include deleted files in the list of changed files?
+    ///
+    /// If `true`, then qualified files that were deleted are listed with an empty
+    /// [`FileDiffLines`](crate::FileDiffLines) and a
+    /// [`FileStatus::Removed`](crate::FileStatus::Removed) status
+    /// (regardless of the [`LinesChangedOnly`](crate::LinesChangedOnly) value).
+    /// Defaults to `false`.
+    pub include_deleted: bool,
+
     /// An optional scope name for logging purposes.
     log_scope: Option<String>,
 }
@@ -74,6 +83,7 @@ impl FileFilter {
             ignored,
             not_ignored,
             extensions,
+            include_deleted: false,
             log_scope: log_scope.map(|s| s.to_string()),
         }
     }
@@ -225,19 +235,8 @@ impl FileFilter {
     /// Note, the given `file_path` should be relative to the same directory that
     /// the paths in [`FileFilter::ignored`] and [`FileFilter::not_ignored`] are relative to.
     pub fn is_qualified(&self, file_path: &Path) -> bool {
-        if !self.extensions.is_empty() && !file_path.is_dir() {
-            let extension = file_path
-                .extension()
-                .unwrap_or_default() // allow for matching files with no extension
-                .to_string_lossy()
-                .to_string();
-            if !self.extensions.contains(&extension) {
-                log::debug!(
-                    "File {} has an undesired file extension.",
-                    file_path.to_string_lossy()
-                );
-                return false;
-            }
+        if !self.has_qualified_extension(file_path) {
+            return false;
         }
         let is_not_ignored = self.is_file_not_ignored(file_path);
         is_not_ignored || {
@@ -257,6 +256,28 @@ impl FileFilter {
         }
     }
 
+    /// Does `file_path` use at least 1 of [`FileFilter::extensions`]?
+    ///
+    /// Always `true` if [`FileFilter::extensions`] is empty or `file_path` is a directory.
+    fn has_qualified_extension(&self, file_path: &Path) -> bool {
+        if self.extensions.is_empty() || file_path.is_dir() {
+            return true;
+        }
+        let extension = file_path
+            .extension()
+            .unwrap_or_default() // allow for matching files with no extension
+            .to_string_lossy()
+            .to_string();
+        if !self.extensions.contains(&extension) {
+            log::debug!(
+                "File {} has an undesired file extension.",
+                file_path.to_string_lossy()
+            );
+            return false;
+        }
+        true
+    }
+
     /// Walks a given `root_path` recursively and returns a set of discovered source files.
     ///
     /// Only files that satisfy the following conditions are included in the returned set:
@@ -284,7 +305,12 @@ impl FileFilter {
             source: e,
         })?;
         for entry in entries {
-            let path = entry?.path();
+            let path = entry
+                .map_err(|e| DirWalkError::ReadDir {
+                    path: root_path.as_ref().to_path_buf(),
+                    source: e,
+                })?
+                .path();
             if path.is_dir() {
                 files.extend(self.walk_dir_inner(&path, strip_prefix)?);
             } else {
@@ -404,6 +430,18 @@ impl FileFilter {
     pub fn extensions(&self) -> HashSet<String> {
         self.extensions.clone()
     }
+
+    /// Include deleted files in the list of changed files?
+    #[getter]
+    pub fn get_include_deleted(&self) -> bool {
+        self.include_deleted
+    }
+
+    /// Set whether deleted files are included in the list of changed files.
+    #[setter]
+    pub fn set_include_deleted(&mut self, value: bool) {
+        self.include_deleted = value;
+    }
 }
 
 #[cfg(test)]
@@ -1,12 +1,23 @@
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
 
-use std::ops::Range;
+use std::{ops::Range, str::FromStr};
 
 pub mod file_filter;
-use crate::DiffHunkHeader;
+use crate::{DiffHunkHeader, error::ParseEnumError};
 
 /// An enum to help determine what constitutes a changed file based on the diff contents.
+///
+/// This can be parsed from a string (case-insensitive):
+///
+/// | Variant | Spellings |
+/// |---------|-----------|
+/// | [`LinesChangedOnly::Off`] | `off`, `false` |
+/// | [`LinesChangedOnly::Diff`] | `diff` |
+/// | [`LinesChangedOnly::On`] | `on`, `true` |
+///
+/// The `true`/`false` spellings are legacy aliases used by some tools.
+/// When displayed, the legacy spellings are used (eg. `true` instead of `on`).
 #[derive(PartialEq, Clone, Copy, Debug, Default)]
 #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
 #[cfg_attr(feature = "pyo3", pyclass(module = "git_bot_feedback", from_py_object))]
@@ -43,6 +54,24 @@ impl LinesChangedOnly {
     }
 }
 
+impl FromStr for LinesChangedOnly {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ParseEnumError::parse(
+            "lines-changed-only value",
+            s,
+            &[
+                ("off", Self::Off),
+                ("false", Self::Off),
+                ("diff", Self::Diff),
+                ("on", Self::On),
+                ("true", Self::On),
+            ],
+        )
+    }
+}
+
 impl std::fmt::Display for LinesChangedOnly {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -53,6 +82,79 @@ impl std::fmt::Display for LinesChangedOnly {
     }
 }
 
+/// An enum to describe how a file was changed.
+///
+/// This can be parsed from a string (case-insensitive):
+///
+/// | Variant | Spellings |
+/// |---------|-----------|
+/// | [`FileStatus::Added`] | `added` |
+/// | [`FileStatus::Modified`] | `modified`, `changed` |
+/// | [`FileStatus::Renamed`] | `renamed` |
+/// | [`FileStatus::Copied`] | `copied` |
+/// | [`FileStatus::Removed`] | `removed`, `deleted` |
+///
+/// The alternate spellings are used by some Git servers (eg. Gitea).
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+#[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+#[cfg_attr(
+    feature = "pyo3",
+    pyclass(module = "git_bot_feedback", from_py_object, eq)
+)]
+pub enum FileStatus {
+    /// The file was created.
+    Added,
+
+    /// The file's contents (or mode) were changed.
+    #[default]
+    Modified,
+
+    /// The file was moved from [`FileDiffLines::previous_path`].
+    ///
+    /// Its contents may also have been changed.
+    Renamed,
+
+    /// The file was copied from [`FileDiffLines::previous_path`].
+    ///
+    /// Its contents may also have been changed.
+    Copied,
+
+    /// The file was deleted.
+    Removed,
+}
+
+impl FromStr for FileStatus {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ParseEnumError::parse(
+            "file status",
+            s,
+            &[
+                ("added", Self::Added),
+                ("modified", Self::Modified),
+                ("changed", Self::Modified),
+                ("renamed", Self::Renamed),
+                ("copied", Self::Copied),
+                ("removed", Self::Removed),
+                ("deleted", Self::Removed),
+            ],
+        )
+    }
+}
+
+impl std::fmt::Display for FileStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileStatus::Added => write!(f, "added"),
+            FileStatus::Modified => write!(f, "modified"),
+            FileStatus::Renamed => write!(f, "renamed"),
+            FileStatus::Copied => write!(f, "copied"),
+            FileStatus::Removed => write!(f, "removed"),
+        }
+    }
+}
+
 /// A structure to represent a file's changes per line numbers.
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
@@ -71,19 +173,66 @@ pub struct FileDiffLines {
     ///
     /// The line numbers here disregard the old line numbers in the diff hunks.
     pub diff_hunks: Vec<Range<u32>>,
+
+    /// The list of line numbers (in the old file) with deletions.
+    pub deleted_lines: Vec<u32>,
+
+    /// The list of ranges that span the old file's line numbers present in diff chunks.
+    ///
+    /// This is the counterpart of [`Self::diff_hunks`] for the old file.
+    pub old_hunks: Vec<Range<u32>>,
+
+    /// The header of each hunk in the diff.
+    ///
+    /// The [`Self::diff_hunks`] and [`Self::old_hunks`] are derived from these
+    /// when the diff is parsed with [`parse_diff()`](crate::parse_diff).
+    pub hunk_headers: Vec<DiffHunkHeader>,
+
+    /// How the file was changed.
+    pub status: FileStatus,
+
+    /// The file's path before it was renamed or copied.
+    ///
+    /// This is [`None`] unless the [`Self::status`] is
+    /// [`FileStatus::Renamed`] or [`FileStatus::Copied`].
+    pub previous_path: Option<String>,
 }
 
 impl FileDiffLines {
     /// Instantiate an object with changed lines information.
+    ///
+    /// The [`Self::status`] defaults to [`FileStatus::Modified`].
+    /// Use [`Self::with_status()`] to describe other changes.
     pub fn with_info(added_lines: Vec<u32>, diff_hunks: Vec<Range<u32>>) -> Self {
         let added_ranges = Self::consolidate_numbers_to_ranges(&added_lines);
         Self {
             added_lines,
             added_ranges,
             diff_hunks,
+            ..Default::default()
         }
     }
 
+    /// Set the [`Self::deleted_lines`] and [`Self::old_hunks`] of this object.
+    pub fn with_deletions(mut self, deleted_lines: Vec<u32>, old_hunks: Vec<Range<u32>>) -> Self {
+        self.deleted_lines = deleted_lines;
+        self.old_hunks = old_hunks;
+        self
+    }
+
+    /// Set the [`Self::hunk_headers`] of this object.
+    pub fn with_hunk_headers(mut self, hunk_headers: Vec<DiffHunkHeader>) -> Self {
+        self.hunk_headers = hunk_headers;
+        self
+    }
+
+    /// Set the [`Self::status`] and [`Self::previous_path`] of this object.
+    pub fn with_status(mut self, status: FileStatus, previous_path: Option<String>) -> Self {
+        self.status = status;
+        self.previous_path = previous_path;
+        self
+    }
+
     /// A helper function to consolidate a [Vec<u32>] of line numbers into a
     /// [Vec<Range<u32>>] in which each range describes the beginning and
     /// ending of a group of consecutive line numbers.
@@ -191,6 +340,7 @@ impl FileDiffLines {
                 .into_iter()
                 .map(|(start, end)| start..end)
                 .collect(),
+            ..Default::default()
         }
     }
 
@@ -243,6 +393,42 @@ impl FileDiffLines {
             .collect()
     }
 
+    /// The list of line numbers (in the old file) whose lines were deleted.
+    #[getter]
+    pub fn get_deleted_lines(&self) -> Vec<u32> {
+        self.deleted_lines.clone()
+    }
+
+    /// The range of the old file's line numbers that span the diff hunks.
+    ///
+    /// This takes the form of a list of tuples of
+    /// ``(inclusive_start, exclusive_end)`` to represent ranges.
+    #[getter]
+    pub fn get_old_hunks(&self) -> Vec<(u32, u32)> {
+        self.old_hunks
+            .iter()
+            .map(|range| (range.start, range.end))
+            .collect()
+    }
+
+    /// The header of each hunk in the diff.
+    #[getter]
+    pub fn get_hunk_headers(&self) -> Vec<DiffHunkHeader> {
+        self.hunk_headers.clone()
+    }
+
+    /// How the file was changed.
+    #[getter]
+    pub fn get_status(&self) -> FileStatus {
+        self.status
+    }
+
+    /// The file's path before it was renamed or copied.
+    #[getter]
+    pub fn get_previous_path(&self) -> Option<String> {
+        self.previous_path.clone()
+    }
+
     /// Check if the given hunk header describes a hunk contained in the ``diff_hunks``.
     #[pyo3(
         name = "is_hunk_in_diff",
@@ -268,7 +454,7 @@ impl FileDiffLines {
 mod test {
     #![allow(clippy::unwrap_used)]
 
-    use super::{FileDiffLines, LinesChangedOnly};
+    use super::{FileDiffLines, FileStatus, LinesChangedOnly};
 
     #[test]
     fn display_lines_changed_only() {
@@ -309,4 +495,48 @@ mod test {
         let file_obj = FileDiffLines::default();
         assert!(!file_obj.is_line_in_diff(&42));
     }
+
+    #[test]
+    fn parse_lines_changed_only() {
+        for (spelling, expected) in [
+            ("off", LinesChangedOnly::Off),
+            ("FALSE", LinesChangedOnly::Off),
+            ("Diff", LinesChangedOnly::Diff),
+            ("on", LinesChangedOnly::On),
+            ("true", LinesChangedOnly::On),
+        ] {
+            assert_eq!(spelling.parse::<LinesChangedOnly>().unwrap(), expected);
+            // the displayed value can be parsed back
+            assert_eq!(
+                expected.to_string().parse::<LinesChangedOnly>().unwrap(),
+                expected
+            );
+        }
+        let err = "yes".parse::<LinesChangedOnly>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown lines-changed-only value 'yes'; expected one of: off, false, diff, on, true"
+        );
+    }
+
+    #[test]
+    fn parse_file_status() {
+        for (spelling, expected) in [
+            ("added", FileStatus::Added),
+            ("Modified", FileStatus::Modified),
+            ("changed", FileStatus::Modified),
+            ("renamed", FileStatus::Renamed),
+            ("copied", FileStatus::Copied),
+            ("removed", FileStatus::Removed),
+            ("DELETED", FileStatus::Removed),
+        ] {
+            assert_eq!(spelling.parse::<FileStatus>().unwrap(), expected);
+            // the displayed value can be parsed back
+            assert_eq!(
+                expected.to_string().parse::<FileStatus>().unwrap(),
+                expected
+            );
+        }
+        assert!("unchanged".parse::<FileStatus>().is_err());
+    }
 }
@@ -2,9 +2,9 @@
 use pyo3::prelude::*;
 
 use regex::Regex;
-use std::{collections::HashMap, ops::Range, path::Path};
+use std::{collections::HashMap, path::Path, sync::LazyLock};
 
-use crate::{FileDiffLines, FileFilter, LinesChangedOnly, error::DiffError};
+use crate::{FileDiffLines, FileFilter, FileStatus, LinesChangedOnly, error::DiffError};
 
 /// A struct to represent the header information of a diff hunk.
 #[cfg_attr(
@@ -39,62 +39,267 @@ impl DiffHunkHeader {
     }
 }
 
-fn get_filename_from_front_matter(front_matter: &str) -> Result<Option<&str>, DiffError> {
-    let diff_file_name = Regex::new(r"(?m)^\+\+\+\sb?/(.*)$")?;
-    if let Some(captures) = diff_file_name.captures(front_matter)
+/// Decode a path from a diff's front matter.
+///
+/// Git quotes paths that contain special characters (eg. `"b/caf\303\251.cpp"`).
+/// The quotes are removed and the C-style escapes are decoded
+/// (invalid UTF-8 is replaced with `U+FFFD`).
+/// An unquoted path is returned as is (without any trailing line ending).
+fn unquote_path(path: &str) -> String {
+    let path = path.trim_end_matches(['\r', '\n']);
+    let Some(quoted) = path
+        .strip_prefix('"')
+        .and_then(|p| p.strip_suffix('"'))
+        .filter(|_| path.len() > 1)
+    else {
+        return path.to_string();
+    };
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut chars = quoted.bytes().peekable();
+    while let Some(byte) = chars.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match chars.next() {
+            Some(b'a') => bytes.push(0x07),
+            Some(b'b') => bytes.push(0x08),
+            Some(b't') => bytes.push(b'\t'),
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b'v') => bytes.push(0x0B),
+            Some(b'f') => bytes.push(0x0C),
+            Some(b'r') => bytes.push(b'\r'),
+            Some(digit @ b'0'..=b'7') => {
+                // up to 3 octal digits describe a byte
+                let mut value = u32::from(digit - b'0');
+                for _ in 0..2 {
+                    match chars.peek() {
+                        Some(next @ b'0'..=b'7') => {
+                            value = value * 8 + u32::from(next - b'0');
+                            chars.next();
+                        }
+                        _ => break,
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            // eg. `\"` and `\\`
+            Some(other) => bytes.push(other),
+            None => bytes.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Compile one of the constant patterns below.
+#[allow(
+    clippy::expect_used,
+    reason = "the patterns are constant and exercised by unit tests"
+)]
+fn compile_pattern(pattern: &str) -> Regex {
+    Regex::new(pattern).expect("regex pattern should be valid")
+}
+
+/// The header of a hunk (eg. `@@ -3,7 +3,7 @@`).
+static HUNK_INFO_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| compile_pattern(r"(?m)@@\s\-(\d+),?(\d*)\s\+(\d+),?(\d*)\s@@"));
+/// The line that begins each file's diff.
+static FILE_DELIMITER_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| compile_pattern(r#"(?m)^diff \-\-git "?a/.*$"#));
+/// The old file's name in the front matter.
+static OLD_NAME_PATTERN: LazyLock<Regex> = LazyLock::new(|| compile_pattern(r"(?m)^---\s(.*)$"));
+/// The new file's name in the front matter.
+static NEW_NAME_PATTERN: LazyLock<Regex> = LazyLock::new(|| compile_pattern(r"(?m)^\+\+\+\s(.*)$"));
+/// The similarity index of a renamed or copied file.
+static SIMILARITY_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| compile_pattern(r"(?m)^(?:dis)?similarity index \d+%"));
+/// The new path of a renamed or copied file.
+static RENAMED_TO_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| compile_pattern(r"(?m)^(?:rename|copy) to (.*)$"));
+/// The previous path of a renamed file.
+static RENAMED_FROM_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| compile_pattern(r"(?m)^rename from (.*)$"));
+/// The previous path of a copied file.
+static COPIED_FROM_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| compile_pattern(r"(?m)^copy from (.*)$"));
+/// The note about a changed binary file.
+static BINARY_FILE_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| compile_pattern(r"(?m)^Binary\sfiles\s"));
+/// The front matter of a file whose mode was changed (without changing its content).
+static MODE_ONLY_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| compile_pattern(r"(?x)\Aold\ mode\ [0-7]{6}\r?\nnew\ mode\ [0-7]{6}\r?\n?\z"));
+/// The mode of an added file.
+static NEW_FILE_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| compile_pattern(r"(?m)^new file mode "));
+/// The mode of a deleted file.
+static DELETED_FILE_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| compile_pattern(r"(?m)^deleted file mode "));
+
+fn get_filename_from_front_matter(front_matter: &str) -> Result<Option<String>, DiffError> {
+    if let Some(captures) = NEW_NAME_PATTERN.captures(front_matter)
         && let Some(name) = captures.get(1)
     {
-        return Ok(Some(name.as_str().trim_end_matches(['\r', '\n'])));
+        let name = unquote_path(name.as_str());
+        if let Some(name) = name.strip_prefix("b/").or_else(|| name.strip_prefix('/')) {
+            return Ok(Some(name.to_string()));
+        }
     }
-    let diff_renamed_file = Regex::new(r"(?m)^rename to (.*)$")?;
-    if front_matter.starts_with("similarity")
-        && let Some(captures) = diff_renamed_file.captures(front_matter)
+    // renamed and copied files without changes have no `+++` line
+    if SIMILARITY_PATTERN.is_match(front_matter)
+        && let Some(captures) = RENAMED_TO_PATTERN.captures(front_matter)
         && let Some(name) = captures.get(1)
     {
-        return Ok(Some(name.as_str().trim_end_matches(['\r', '\n'])));
+        return Ok(Some(unquote_path(name.as_str())));
     }
-    let diff_binary_file = Regex::new(r"(?m)^Binary\sfiles\s")?;
-    let diff_mode_only = Regex::new(r"(?x)\Aold\ mode\ [0-7]{6}\r?\nnew\ mode\ [0-7]{6}\r?\n?\z")?;
-    if diff_mode_only.is_match(front_matter) || diff_binary_file.is_match(front_matter) {
+    if MODE_ONLY_PATTERN.is_match(front_matter) || BINARY_FILE_PATTERN.is_match(front_matter) {
         return Ok(None);
     }
     Err(DiffError::MalformedDiffError(front_matter.to_string()))
 }
 
-/// A regex pattern used in multiple functions
-static HUNK_INFO_PATTERN: &str = r"(?m)@@\s\-\d+,?\d*\s\+(\d+),?(\d*)\s@@";
-
-/// Parses a single file's patch containing one or more hunks
+/// Infer how a file was changed from the given diff's `front_matter`.
 ///
 /// Returns a 2-item tuple:
 ///
-/// - the line numbers that contain additions
-/// - the ranges of lines that span each hunk
-fn parse_patch(patch: &str) -> Result<(Vec<u32>, Vec<Range<u32>>), DiffError> {
-    let mut diff_hunks = Vec::new();
+/// - the [`FileStatus`]
+/// - the file's previous path (if renamed or copied)
+fn get_status_from_front_matter(front_matter: &str) -> (FileStatus, Option<String>) {
+    let trim_path = |path: &str| -> String {
+        let path = unquote_path(path);
+        path.strip_prefix('/').unwrap_or(&path).to_string()
+    };
+    let old_name = OLD_NAME_PATTERN
+        .captures(front_matter)
+        .and_then(|captures| captures.get(1))
+        .map(|name| unquote_path(name.as_str()));
+    let new_name = NEW_NAME_PATTERN
+        .captures(front_matter)
+        .and_then(|captures| captures.get(1))
+        .map(|name| unquote_path(name.as_str()));
+    if NEW_FILE_PATTERN.is_match(front_matter) || old_name.as_deref() == Some("/dev/null") {
+        return (FileStatus::Added, None);
+    }
+    if DELETED_FILE_PATTERN.is_match(front_matter) || new_name.as_deref() == Some("/dev/null") {
+        return (FileStatus::Removed, None);
+    }
+    for (pattern, status) in [
+        (&RENAMED_FROM_PATTERN, FileStatus::Renamed),
+        (&COPIED_FROM_PATTERN, FileStatus::Copied),
+    ] {
+        if let Some(captures) = pattern.captures(front_matter)
+            && let Some(name) = captures.get(1)
+        {
+            return (status, Some(trim_path(name.as_str())));
+        }
+    }
+    // Diffs synthesized from a Git server's list of changed files
+    // only describe a rename via the `---` and `+++` lines.
+    if let (Some(old), Some(new)) = (&old_name, &new_name)
+        && let (Some(old), Some(new)) = (old.strip_prefix("a/"), new.strip_prefix("b/"))
+        && old != new
+    {
+        return (FileStatus::Renamed, Some(trim_path(old)));
+    }
+    (FileStatus::Modified, None)
+}
+
+/// Get the name of a deleted file from its diff's `header` (`diff --git a/.. b/..`)
+/// or its diff's `front_matter`.
+fn get_deleted_filename(header: Option<&str>, front_matter: &str) -> Option<String> {
+    if let Some(captures) = OLD_NAME_PATTERN.captures(front_matter)
+        && let Some(name) = captures.get(1)
+        && let Some(name) = unquote_path(name.as_str()).strip_prefix("a/")
+    {
+        return Some(name.to_string());
+    }
+    // Binary (or empty) files have no `---` line in the front matter.
+    // The header names the same path twice (`a/<path> b/<path>`) for deleted files.
+    let paths =
+        header.and_then(|h| h.trim_end_matches(['\r', '\n']).strip_prefix("diff --git "))?;
+    // quoted paths are delimited by `" "` instead of a space
+    let (prefix, delimiter, suffix) = if paths.starts_with('"') {
+        ("\"a/", "\" \"b/", "\"")
+    } else {
+        ("a/", " b/", "")
+    };
+    if let Some(paths) = paths
+        .strip_prefix(prefix)
+        .and_then(|p| p.strip_suffix(suffix))
+        && paths.len() > delimiter.len()
+    {
+        let name_len = (paths.len() - delimiter.len()) / 2;
+        if paths.is_char_boundary(name_len)
+            && paths[name_len..].starts_with(delimiter)
+            && paths[..name_len] == paths[name_len + delimiter.len()..]
+        {
+            let name = &paths[..name_len];
+            return Some(if suffix.is_empty() {
+                name.to_string()
+            } else {
+                unquote_path(format!("\"{name}\"").as_str())
+            });
+        }
+    }
+    None
+}
+
+/// Parses a single file's patch containing one or more hunks
+///
+/// Returns the changed lines of both the new file
+/// (the added lines and hunks) and the old file (the deleted lines and hunks).
+fn parse_patch(patch: &str) -> FileDiffLines {
+    let mut hunk_headers = Vec::new();
     let mut additions = Vec::new();
+    let mut deletions = Vec::new();
 
-    let hunk_info = Regex::new(HUNK_INFO_PATTERN)?;
-    let hunk_headers = hunk_info.captures_iter(patch).collect::<Vec<_>>();
-    if !hunk_headers.is_empty() {
+    let captured_headers = HUNK_INFO_PATTERN.captures_iter(patch).collect::<Vec<_>>();
+    if !captured_headers.is_empty() {
         // skip the first split because it is anything that precedes first hunk header
-        let hunks = hunk_info.split(patch).skip(1);
-        for (hunk, header) in hunks.zip(hunk_headers) {
-            // header.unwrap() is safe because the hunk_headers.iter() is parallel to hunk_info.split()
-            let [start_line, end_range] = header.extract().1.map(|v| v.parse::<u32>().unwrap_or(1));
-            let mut line_numb_in_diff = start_line;
-            diff_hunks.push(start_line..start_line + end_range);
+        let hunks = HUNK_INFO_PATTERN.split(patch).skip(1);
+        for (hunk, header) in hunks.zip(captured_headers) {
+            // header.unwrap() is safe because the captured_headers.iter() is parallel to HUNK_INFO_PATTERN.split()
+            let [old_start, old_lines, new_start, new_lines] =
+                header.extract().1.map(|v| v.parse::<u32>().unwrap_or(1));
+            let mut line_numb_in_diff = new_start;
+            let mut old_line_numb_in_diff = old_start;
+            hunk_headers.push(DiffHunkHeader {
+                old_start,
+                old_lines,
+                new_start,
+                new_lines,
+            });
             for (line_index, line) in hunk.split('\n').enumerate() {
+                if line.starts_with('\\') {
+                    // "\ No newline at end of file" is not a line in either file
+                    continue;
+                }
                 if line.starts_with('+') {
                     additions.push(line_numb_in_diff);
+                } else if line.starts_with('-') {
+                    deletions.push(old_line_numb_in_diff);
                 }
-                if line_index > 0 && !line.starts_with('-') {
-                    line_numb_in_diff += 1;
+                if line_index > 0 {
+                    if !line.starts_with('-') {
+                        line_numb_in_diff += 1;
+                    }
+                    if !line.starts_with('+') {
+                        old_line_numb_in_diff += 1;
+                    }
                 }
             }
         }
     }
-    Ok((additions, diff_hunks))
+    let diff_hunks = hunk_headers
+        .iter()
+        .map(|h| h.new_start..h.new_start + h.new_lines)
+        .collect();
+    let old_hunks = hunk_headers
+        .iter()
+        .map(|h| h.old_start..h.old_start + h.old_lines)
+        .collect();
+    FileDiffLines::with_info(additions, diff_hunks)
+        .with_deletions(deletions, old_hunks)
+        .with_hunk_headers(hunk_headers)
 }
 
 /// Parses a git `diff` string into a map of file names to their corresponding
@@ -103,36 +308,57 @@ fn parse_patch(patch: &str) -> Result<(Vec<u32>, Vec<Range<u32>>), DiffError> {
 /// The `file_filter` is used to filter out files that are not of interest.
 /// The `lines_changed_only` parameter determines whether to include files
 /// based on their contents' changes.
+///
+/// Deleted files are skipped unless [`FileFilter::include_deleted`] is enabled.
 pub fn parse_diff(
     diff: &str,
     file_filter: &FileFilter,
     lines_changed_only: &LinesChangedOnly,
 ) -> Result<HashMap<String, FileDiffLines>, DiffError> {
     let mut results = HashMap::new();
-    let diff_file_delimiter = Regex::new(r"(?m)^diff \-\-git a/.*$")?;
-    let hunk_info = Regex::new(HUNK_INFO_PATTERN)?;
 
-    let file_diffs = diff_file_delimiter.split(diff);
-    for file_diff in file_diffs {
-        if file_diff.is_empty() || file_diff.starts_with("deleted file") {
+    // anything that precedes the first delimiter has no header
+    let headers = std::iter::once(None).chain(
+        FILE_DELIMITER_PATTERN
+            .find_iter(diff)
+            .map(|m| Some(m.as_str())),
+    );
+    let file_diffs = FILE_DELIMITER_PATTERN.split(diff);
+    for (header, file_diff) in headers.zip(file_diffs) {
+        if file_diff.is_empty() {
             continue;
         }
-        let hunk_start = if let Some(first_hunk) = hunk_info.find(file_diff) {
+        let hunk_start = if let Some(first_hunk) = HUNK_INFO_PATTERN.find(file_diff) {
             first_hunk.start()
         } else {
             file_diff.len()
         };
         let front_matter = &file_diff[..hunk_start];
+        let (status, previous_path) = get_status_from_front_matter(front_matter.trim_start());
+        if status == FileStatus::Removed {
+            if file_filter.include_deleted
+                && let Some(file_name) = get_deleted_filename(header, front_matter)
+            {
+                let file_name = file_name.strip_prefix('/').unwrap_or(&file_name);
+                if file_filter.is_qualified(Path::new(file_name)) {
+                    results
+                        .entry(file_name.to_string())
+                        .or_insert_with(|| FileDiffLines::default().with_status(status, None));
+                }
+            }
+            continue;
+        }
         if let Some(file_name) = get_filename_from_front_matter(front_matter.trim_start())? {
-            let file_name = file_name.strip_prefix('/').unwrap_or(file_name);
+            let file_name = file_name.strip_prefix('/').unwrap_or(&file_name);
             if file_filter.is_qualified(Path::new(file_name)) {
-                let (added_lines, diff_hunks) = parse_patch(&file_diff[hunk_start..])?;
-                if lines_changed_only
-                    .is_change_valid(!added_lines.is_empty(), !diff_hunks.is_empty())
-                {
+                let diff_lines = parse_patch(&file_diff[hunk_start..]);
+                if lines_changed_only.is_change_valid(
+                    !diff_lines.added_lines.is_empty(),
+                    !diff_lines.diff_hunks.is_empty(),
+                ) {
                     results
                         .entry(file_name.to_string())
-                        .or_insert_with(|| FileDiffLines::with_info(added_lines, diff_hunks));
+                        .or_insert_with(|| diff_lines.with_status(status, previous_path));
                 }
             }
         }
@@ -145,8 +371,8 @@ pub fn parse_diff(
 mod test {
     #![allow(clippy::unwrap_used)]
 
-    use super::parse_diff;
-    use crate::{FileFilter, LinesChangedOnly, error::DiffError};
+    use super::{DiffHunkHeader, parse_diff};
+    use crate::{FileFilter, FileStatus, LinesChangedOnly, error::DiffError};
 
     const BAD_DIFF: &str = r#"{"message":"Resource not accessible by integration"}"#;
 
@@ -182,6 +408,11 @@ Binary files /dev/null and b/some picture.png differ
         let git_file = files.get("tests/demo/some source.c").unwrap();
         assert!(git_file.added_lines.is_empty());
         assert!(git_file.diff_hunks.is_empty());
+        assert_eq!(git_file.status, FileStatus::Renamed);
+        assert_eq!(
+            git_file.previous_path.as_deref(),
+            Some("tests/demo/some source.cpp")
+        );
     }
 
     #[test]
@@ -217,6 +448,7 @@ rename to /tests/demo/some source.c
         let git_file = files.get("tests/demo/some source.c").unwrap();
         assert!(!git_file.is_line_in_diff(&1));
         assert!(git_file.is_line_in_diff(&4));
+        assert_eq!(git_file.status, FileStatus::Renamed);
     }
 
     const TYPICAL_DIFF: &str = "diff --git a/path/for/Some file.cpp b/path/to/Some file.cpp\n\
@@ -234,7 +466,42 @@ rename to /tests/demo/some source.c
             &LinesChangedOnly::On,
         )
         .unwrap();
-        assert!(!files.is_empty());
+        let git_file = files.get("path/to/Some file.cpp").unwrap();
+        // a rename is inferred from the `---` and `+++` lines
+        assert_eq!(git_file.status, FileStatus::Renamed);
+        assert_eq!(
+            git_file.previous_path.as_deref(),
+            Some("path/for/Some file.cpp")
+        );
+        assert_eq!(git_file.added_lines, vec![6]);
+        assert_eq!(git_file.diff_hunks, vec![3..10]);
+        assert_eq!(git_file.deleted_lines, vec![6]);
+        assert_eq!(git_file.old_hunks, vec![3..10]);
+    }
+
+    const ADDED_DIFF: &str = "diff --git a/src/new.cpp b/src/new.cpp\n\
+                              new file mode 100644\n\
+                              --- /dev/null\n\
+                              +++ b/src/new.cpp\n\
+                              @@ -0,0 +1,2 @@\n\
+                              +#include <new.hpp>\n\
+                              +\n";
+
+    #[test]
+    fn parse_added_diff() {
+        let files = parse_diff(
+            &String::from_iter([ADDED_DIFF, TERSE_HEADERS]),
+            &FileFilter::new(&[], &["cpp"], None),
+            &LinesChangedOnly::On,
+        )
+        .unwrap();
+        let git_file = files.get("src/new.cpp").unwrap();
+        assert_eq!(git_file.status, FileStatus::Added);
+        assert!(git_file.previous_path.is_none());
+        assert_eq!(git_file.added_lines, vec![1, 2]);
+        let git_file = files.get("src/demo.cpp").unwrap();
+        assert_eq!(git_file.status, FileStatus::Modified);
+        assert!(git_file.previous_path.is_none());
     }
 
     const IGNORED_DIFF: &str = "diff --git a/some picture.png b/some picture.png\n\
@@ -275,5 +542,211 @@ rename to /tests/demo/some source.c
         let files = parse_diff(TERSE_HEADERS, &file_filter, &LinesChangedOnly::Diff).unwrap();
         let file_diff = files.get("src/demo.cpp").unwrap();
         assert_eq!(file_diff.diff_hunks, vec![3..4, 5..7, 17..19]);
+        assert_eq!(file_diff.added_lines, vec![3, 5, 6, 17, 18]);
+        // the old file's line numbers
+        assert_eq!(file_diff.deleted_lines, vec![3, 18]);
+        assert_eq!(file_diff.old_hunks, vec![3..4, 4..4, 18..19]);
+        // omitted line counts default to 1
+        let headers = [(3, 1, 3, 1), (4, 0, 5, 2), (18, 1, 17, 2)].map(
+            |(old_start, old_lines, new_start, new_lines)| DiffHunkHeader {
+                old_start,
+                old_lines,
+                new_start,
+                new_lines,
+            },
+        );
+        assert_eq!(file_diff.hunk_headers, headers);
+    }
+
+    const DELETED_DIFF: &str = "diff --git a/src/old.cpp b/src/old.cpp\n\
+                                deleted file mode 100644\n\
+                                index 3b18e51..0000000\n\
+                                --- a/src/old.cpp\n\
+                                +++ /dev/null\n\
+                                @@ -1,2 +0,0 @@\n\
+                                -#include <old.hpp>\n\
+                                -\n\
+                                diff --git a/src/old picture.png b/src/old picture.png\n\
+                                deleted file mode 100644\n\
+                                Binary files a/src/old picture.png and /dev/null differ\n";
+
+    #[test]
+    fn parse_deleted_diff() {
+        let diff = String::from_iter([DELETED_DIFF, TERSE_HEADERS]);
+        let mut file_filter = FileFilter::new(&[], &["cpp", "png"], None);
+        let files = parse_diff(&diff, &file_filter, &LinesChangedOnly::Off).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files.contains_key("src/demo.cpp"));
+
+        file_filter.include_deleted = true;
+        // deleted files are included regardless of lines-changed-only value
+        let files = parse_diff(&diff, &file_filter, &LinesChangedOnly::On).unwrap();
+        assert_eq!(files.len(), 3);
+        for name in ["src/old.cpp", "src/old picture.png"] {
+            let git_file = files.get(name).unwrap();
+            assert_eq!(git_file.status, FileStatus::Removed);
+            assert!(git_file.previous_path.is_none());
+            assert!(git_file.added_lines.is_empty());
+            assert!(git_file.diff_hunks.is_empty());
+        }
+
+        // deleted files are still subject to the file filter
+        let mut file_filter = FileFilter::new(&["src/old.cpp"], &["cpp"], None);
+        file_filter.include_deleted = true;
+        let files = parse_diff(&diff, &file_filter, &LinesChangedOnly::Off).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files.contains_key("src/demo.cpp"));
+    }
+
+    const QUOTED_DIFF: &str = r#"diff --git "a/src/weird\303\251 name.cpp" "b/src/weird\303\251 name.cpp"
+index 3b18e51..a1b2c3d 100644
+--- "a/src/weird\303\251 name.cpp"
++++ "b/src/weird\303\251 name.cpp"
+@@ -1,0 +2 @@
++#include "\"quoted\".hpp"
+diff --git "a/src/old \"name\".cpp" "b/src/new\tname.cpp"
+similarity index 100%
+rename from "src/old \"name\".cpp"
+rename to "src/new\tname.cpp"
+diff --git "a/src/gone\342\234\223.png" "b/src/gone\342\234\223.png"
+deleted file mode 100644
+Binary files "a/src/gone\342\234\223.png" and /dev/null differ
+"#;
+
+    #[test]
+    fn parse_quoted_diff() {
+        let mut file_filter = FileFilter::new(&[], &["cpp", "png"], None);
+        file_filter.include_deleted = true;
+        let files = parse_diff(QUOTED_DIFF, &file_filter, &LinesChangedOnly::Off).unwrap();
+        assert_eq!(files.len(), 3);
+
+        let git_file = files.get("src/weird\u{e9} name.cpp").unwrap();
+        assert_eq!(git_file.status, FileStatus::Modified);
+        assert_eq!(git_file.added_lines, vec![2]);
+
+        let git_file = files.get("src/new\tname.cpp").unwrap();
+        assert_eq!(git_file.status, FileStatus::Renamed);
+        assert_eq!(
+            git_file.previous_path.as_deref(),
+            Some("src/old \"name\".cpp")
+        );
+
+        let git_file = files.get("src/gone\u{2713}.png").unwrap();
+        assert_eq!(git_file.status, FileStatus::Removed);
+    }
+
+    #[test]
+    fn unquote_invalid_utf8() {
+        assert_eq!(
+            super::unquote_path(r#""a/bad\377 byte""#),
+            "a/bad\u{fffd} byte"
+        );
+        assert_eq!(super::unquote_path("a/plain name\r\n"), "a/plain name");
+    }
+
+    const NO_NEWLINE_DIFF: &str = "diff --git a/src/eof.cpp b/src/eof.cpp\n\
+                                   --- a/src/eof.cpp\n\
+                                   +++ b/src/eof.cpp\n\
+                                   @@ -1,2 +1,3 @@\n \
+                                   int main() {\n\
+                                   -}\n\
+                                   \\ No newline at end of file\n\
+                                   +  return 0;\n\
+                                   +}\n\
+                                   \\ No newline at end of file\n";
+
+    #[test]
+    fn no_newline_at_eof() {
+        let files = parse_diff(
+            NO_NEWLINE_DIFF,
+            &FileFilter::new(&[], &["cpp"], None),
+            &LinesChangedOnly::On,
+        )
+        .unwrap();
+        let file_diff = files.get("src/eof.cpp").unwrap();
+        assert_eq!(file_diff.added_lines, vec![2, 3]);
+        assert_eq!(file_diff.deleted_lines, vec![2]);
+        assert_eq!(file_diff.diff_hunks, vec![1..4]);
+    }
+
+    const COPIED_DIFF: &str = "diff --git a/src/demo.cpp b/src/copy.cpp\n\
+                               similarity index 100%\n\
+                               copy from src/demo.cpp\n\
+                               copy to src/copy.cpp\n\
+                               diff --git a/src/demo.cpp b/src/changed copy.cpp\n\
+                               old mode 100644\n\
+                               new mode 100755\n\
+                               dissimilarity index 10%\n\
+                               copy from src/demo.cpp\n\
+                               copy to src/changed copy.cpp\n\
+                               --- a/src/demo.cpp\n\
+                               +++ b/src/changed copy.cpp\n\
+                               @@ -3,2 +3,2 @@\n \
+                               #include <demo.hpp>\n\
+                               -#include <old.hpp>\n\
+                               +#include <new.hpp>\n";
+
+    #[test]
+    fn parse_copied_diff() {
+        let file_filter = FileFilter::new(&[], &["cpp"], None);
+        let files = parse_diff(COPIED_DIFF, &file_filter, &LinesChangedOnly::Off).unwrap();
+        assert_eq!(files.len(), 2);
+        for name in ["src/copy.cpp", "src/changed copy.cpp"] {
+            let git_file = files.get(name).unwrap();
+            assert_eq!(git_file.status, FileStatus::Copied);
+            assert_eq!(git_file.previous_path.as_deref(), Some("src/demo.cpp"));
+        }
+        assert!(files.get("src/copy.cpp").unwrap().added_lines.is_empty());
+        assert_eq!(
+            files.get("src/changed copy.cpp").unwrap().added_lines,
+            vec![4]
+        );
+
+        // a pure copy has no changed lines
+        let files = parse_diff(COPIED_DIFF, &file_filter, &LinesChangedOnly::Diff).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files.contains_key("src/changed copy.cpp"));
+    }
+
+    #[test]
+    #[ignore = "benchmark; run with `--ignored --nocapture`"]
+    fn bench_parse_diff() {
+        use super::{
+            DELETED_FILE_PATTERN, HUNK_INFO_PATTERN, NEW_FILE_PATTERN, NEW_NAME_PATTERN,
+            OLD_NAME_PATTERN, RENAMED_FROM_PATTERN,
+        };
+        use regex::Regex;
+        use std::time::Instant;
+
+        const FILES: usize = 2000;
+        let diff = (0..FILES)
+            .map(|i| TYPICAL_DIFF.replace("Some file", &format!("file {i}")))
+            .collect::<String>();
+        let file_filter = FileFilter::new(&[], &["cpp"], None);
+
+        let start = Instant::now();
+        let files = parse_diff(&diff, &file_filter, &LinesChangedOnly::Off).unwrap();
+        let parsing = start.elapsed();
+        assert_eq!(files.len(), FILES);
+
+        // compiling the patterns for every file was the bulk of the overhead
+        let start = Instant::now();
+        for _ in 0..FILES {
+            for pattern in [
+                &NEW_NAME_PATTERN,
+                &OLD_NAME_PATTERN,
+                &NEW_FILE_PATTERN,
+                &DELETED_FILE_PATTERN,
+                &RENAMED_FROM_PATTERN,
+                &HUNK_INFO_PATTERN,
+            ] {
+                Regex::new(pattern.as_str()).unwrap();
+            }
+        }
+        let compiling = start.elapsed();
+        println!(
+            "parsed {FILES} files in {parsing:?}; compiling their patterns took {compiling:?}"
+        );
+        assert!(parsing < compiling);
     }
 }
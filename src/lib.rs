@@ -10,18 +10,37 @@
 )]
 
 pub mod client;
-pub use client::{RestApiClient, RestApiRateLimitHeaders};
+pub use client::{RestApiClient, RestApiRateLimitHeaders, RetryPolicy};
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+pub mod blocking;
 pub mod error;
+pub mod prelude;
+#[cfg(feature = "file-changes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+pub use error::DirWalkError;
 pub use error::RestClientError;
 mod comments;
 pub use comments::{
-    review_comments::{ReviewAction, ReviewComment, ReviewOptions},
-    thread_comments::{CommentKind, CommentPolicy, ThreadCommentOptions},
+    review_comments::{
+        ReviewAction, ReviewComment, ReviewOptions, ReviewOutcome, ReviewSkipReason,
+    },
+    thread_comments::{CommentFallback, CommentKind, CommentPolicy, ThreadCommentOptions},
 };
+mod commit_status;
+pub use commit_status::{CommitStatus, MAX_STATUS_DESCRIPTION, StatusState};
 mod output_variable;
 pub use output_variable::OutputVariable;
 mod file_annotations;
-pub use file_annotations::{AnnotationLevel, FileAnnotation};
+pub use file_annotations::{AnnotationLevel, AnnotationOverflow, FileAnnotation};
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
+#[cfg(feature = "sarif")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sarif")))]
+pub mod sarif;
+pub mod summary;
+#[cfg(feature = "sarif")]
+pub use sarif::parse_sarif;
 
 #[cfg(feature = "file-changes")]
 mod git_diff;
@@ -30,7 +49,10 @@ pub use git_diff::{DiffHunkHeader, parse_diff};
 #[cfg(feature = "file-changes")]
 mod file_utils;
 #[cfg(feature = "file-changes")]
-pub use file_utils::{FileDiffLines, LinesChangedOnly, file_filter::FileFilter};
+pub use file_utils::{FileDiffLines, FileStatus, LinesChangedOnly, file_filter::FileFilter};
+
+// Re-export dependencies whose types appear in the public API
+pub use reqwest;
 
 // Re-export dependencies for users of optional feature
 #[cfg(feature = "file-changes")]
@@ -3,7 +3,12 @@ use pyo3::prelude::*;
 
 use std::fmt::Display;
 
-use crate::error::OutputVariableError;
+use serde::Serialize;
+
+use crate::{RestClientError, client::random_token, error::OutputVariableError};
+
+/// The number of attempts to generate a heredoc delimiter that does not occur in a multi-line value.
+const MAX_DELIMITER_ATTEMPTS: usize = 8;
 
 /// A type to represent an output variable.
 ///
@@ -11,7 +16,8 @@ use crate::error::OutputVariableError;
 /// config file formats but with some limitations:
 ///
 /// - Both [`Self::name`] and [`Self::value`] must be UTF-8 encoded.
-/// - The [`Self::value`] cannot span multiple lines.
+/// - The [`Self::value`] may span multiple lines, in which case it is written
+///   with the heredoc syntax (`name<<DELIMITER`).
 #[derive(Debug, Clone)]
 #[cfg_attr(
     feature = "pyo3",
@@ -52,6 +58,45 @@ impl OutputVariable {
 }
 
 impl OutputVariable {
+    /// Create a new output variable after [validating](Self::validate()) it.
+    ///
+    /// The `name` may only contain ASCII alphanumeric characters, `_`, or `-`,
+    /// and it cannot start with a number.
+    pub fn new(name: &str, value: &str) -> Result<Self, RestClientError> {
+        let var = Self {
+            name: name.to_string(),
+            value: value.to_string(),
+        };
+        var.validate()?;
+        Ok(var)
+    }
+
+    /// Create a new output variable whose value is `true` or `false`.
+    pub fn bool(name: &str, value: bool) -> Result<Self, RestClientError> {
+        Self::new(name, value.to_string().as_str())
+    }
+
+    /// Create a new output variable whose value is an integer.
+    pub fn int(name: &str, value: i64) -> Result<Self, RestClientError> {
+        Self::new(name, value.to_string().as_str())
+    }
+
+    /// Create a new output variable whose value is JSON.
+    ///
+    /// The JSON is compactly encoded on a single line.
+    ///
+    /// ```
+    /// use git_bot_feedback::OutputVariable;
+    ///
+    /// let var = OutputVariable::json("files", &["src/lib.rs", "README.md"]).unwrap();
+    /// assert_eq!(var.value, r#"["src/lib.rs","README.md"]"#);
+    /// ```
+    pub fn json<T: Serialize + ?Sized>(name: &str, value: &T) -> Result<Self, RestClientError> {
+        let value = serde_json::to_string(value)
+            .map_err(|e| RestClientError::json("serialize output variable value", e))?;
+        Self::new(name, value.as_str())
+    }
+
     /// Validate that the output variable is well-formed.
     ///
     /// Typically only used by implementations of
@@ -72,16 +117,66 @@ impl OutputVariable {
             }
         }
         let value = self.value.trim();
-        if !value
-            .chars()
-            .all(|c| c.is_ascii_alphanumeric() || c.is_ascii_punctuation() || !c.is_ascii_control())
-        {
+        if !value.chars().all(|c| {
+            c.is_ascii_alphanumeric()
+                || c.is_ascii_punctuation()
+                || matches!(c, '\n' | '\r')
+                || !c.is_ascii_control()
+        }) {
             return Err(OutputVariableError::ValueContainsNonPrintableCharacters(
                 value.to_string(),
             ));
         }
         Ok(())
     }
+
+    /// Format this output variable as an entry in an output file (eg. `GITHUB_OUTPUT`).
+    ///
+    /// Single-line values use the `name=value` form.
+    /// Multi-line values use the heredoc form with a randomly generated delimiter:
+    ///
+    /// ```text
+    /// name<<ghadelimiter_<random>
+    /// line 1
+    /// line 2
+    /// ghadelimiter_<random>
+    /// ```
+    pub(crate) fn fmt_output_entry(&self) -> Result<String, OutputVariableError> {
+        self.validate()?;
+        if !self.value.contains(['\n', '\r']) {
+            return Ok(self.to_string());
+        }
+        for _ in 0..MAX_DELIMITER_ATTEMPTS {
+            let delimiter = format!("ghadelimiter_{}", random_token());
+            if !self.value.contains(&delimiter) {
+                return Ok(format!(
+                    "{}<<{delimiter}\n{}\n{delimiter}",
+                    self.name.trim(),
+                    self.value
+                ));
+            }
+        }
+        Err(OutputVariableError::ValueContainsDelimiter(
+            self.value.clone(),
+        ))
+    }
+
+    /// Format this output variable as an entry in a dotenv file (eg. GitLab's dotenv report).
+    ///
+    /// Dotenv files only support the `name=value` form, so this fails for
+    /// multi-line values and names that contain `-`.
+    #[cfg(feature = "gitlab")]
+    pub(crate) fn fmt_dotenv_entry(&self) -> Result<String, OutputVariableError> {
+        self.validate()?;
+        let name = self.name.trim();
+        if name.contains('-') {
+            return Err(OutputVariableError::NameContainsHyphen(name.to_string()));
+        }
+        if self.value.trim().contains(['\n', '\r']) {
+            return Err(OutputVariableError::MultiLineValue(self.value.clone()));
+        }
+        Ok(self.to_string())
+    }
 }
 
 impl Display for OutputVariable {
@@ -95,6 +190,7 @@ mod tests {
     #![allow(clippy::unwrap_used)]
 
     use super::{OutputVariable, OutputVariableError};
+    use crate::RestClientError;
 
     #[test]
     fn empty_name() {
@@ -137,12 +233,59 @@ mod tests {
     fn value_contains_non_printable_characters() {
         let var = OutputVariable {
             name: "var".to_string(),
-            value: "(val)\nline2".to_string(),
+            value: "(val)\x07line2".to_string(),
         };
         assert_eq!(
             var.validate(),
             Err(OutputVariableError::ValueContainsNonPrintableCharacters(
-                "(val)\nline2".to_string()
+                "(val)\x07line2".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn single_line_entry() {
+        let var = OutputVariable {
+            name: " var ".to_string(),
+            value: " value ".to_string(),
+        };
+        assert_eq!(var.fmt_output_entry().unwrap(), "var=value");
+    }
+
+    #[test]
+    fn multi_line_entry() {
+        let var = OutputVariable {
+            name: "var".to_string(),
+            value: "line 1\r\nline 2\n".to_string(),
+        };
+        let entry = var.fmt_output_entry().unwrap();
+        let (header, rest) = entry.split_once('\n').unwrap();
+        let delimiter = header.strip_prefix("var<<ghadelimiter_").unwrap();
+        assert_eq!(delimiter.len(), 32);
+        let value = rest
+            .strip_suffix(format!("\nghadelimiter_{delimiter}").as_str())
+            .unwrap();
+        assert_eq!(value, var.value);
+    }
+
+    #[cfg(feature = "gitlab")]
+    #[test]
+    fn dotenv_entry() {
+        let mut var = OutputVariable {
+            name: " VAR_NAME ".to_string(),
+            value: " value \n".to_string(),
+        };
+        assert_eq!(var.fmt_dotenv_entry().unwrap(), "VAR_NAME=value");
+        var.value = "line 1\nline 2".to_string();
+        assert_eq!(
+            var.fmt_dotenv_entry(),
+            Err(OutputVariableError::MultiLineValue(var.value.clone()))
+        );
+        var.name = "var-name".to_string();
+        assert_eq!(
+            var.fmt_dotenv_entry(),
+            Err(OutputVariableError::NameContainsHyphen(
+                "var-name".to_string()
             ))
         );
     }
@@ -156,4 +299,69 @@ mod tests {
         .validate()
         .unwrap();
     }
+
+    fn invalid_name(name: &str) -> Option<OutputVariableError> {
+        match OutputVariable::new(name, "value") {
+            Err(RestClientError::OutputVar(e)) => Some(e),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn new_name_with_space() {
+        assert_eq!(
+            invalid_name("my var"),
+            Some(OutputVariableError::NameContainsNonPrintableCharacters(
+                "my var".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn new_name_with_equals() {
+        assert_eq!(
+            invalid_name("var=value"),
+            Some(OutputVariableError::NameContainsNonPrintableCharacters(
+                "var=value".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn new_name_with_leading_digit() {
+        assert_eq!(
+            invalid_name("2var"),
+            Some(OutputVariableError::NameStartsWithNumber(
+                "2var".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn new_empty_name() {
+        assert_eq!(invalid_name(""), Some(OutputVariableError::NameIsEmpty));
+    }
+
+    #[test]
+    fn typed_values() {
+        assert_eq!(OutputVariable::bool("ok", true).unwrap().value, "true");
+        assert_eq!(OutputVariable::int("count", -42).unwrap().value, "-42");
+    }
+
+    #[test]
+    fn json_value() {
+        let value = serde_json::json!({
+            "files": ["src/lib.rs"],
+            "summary": "line 1\nline 2",
+        });
+        let var = OutputVariable::json("result", &value).unwrap();
+        assert_eq!(
+            var.value,
+            r#"{"files":["src/lib.rs"],"summary":"line 1\nline 2"}"#
+        );
+        assert_eq!(
+            var.fmt_output_entry().unwrap(),
+            format!("result={}", var.value)
+        );
+    }
 }
@@ -0,0 +1,37 @@
+//! A module to conveniently import the commonly used types and traits.
+//!
+//! ```no_run
+//! use git_bot_feedback::prelude::*;
+//!
+//! # async fn example() -> Result<(), RestClientError> {
+//! let client = init_client()?;
+//! client
+//!     .post_thread_comment(ThreadCommentOptions {
+//!         comment: "Hello from a CI tool".to_string(),
+//!         ..Default::default()
+//!     })
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+#[cfg(feature = "azure-devops")]
+#[cfg_attr(docsrs, doc(cfg(feature = "azure-devops")))]
+pub use crate::client::AzureDevopsApiClient;
+#[cfg(feature = "gitea")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gitea")))]
+pub use crate::client::GiteaApiClient;
+#[cfg(feature = "github")]
+#[cfg_attr(docsrs, doc(cfg(feature = "github")))]
+pub use crate::client::GithubApiClient;
+#[cfg(feature = "gitlab")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gitlab")))]
+pub use crate::client::GitlabApiClient;
+pub use crate::{
+    AnnotationLevel, CommentFallback, CommentKind, CommentPolicy, CommitStatus, FileAnnotation,
+    OutputVariable, RestApiClient, RestClientError, ReviewAction, ReviewComment, ReviewOptions,
+    StatusState, ThreadCommentOptions,
+    client::{LocalClient, from_env, init_client},
+};
+#[cfg(feature = "file-changes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+pub use crate::{FileDiffLines, FileFilter, FileStatus, LinesChangedOnly};
@@ -0,0 +1,220 @@
+//! A module to ingest [SARIF] (Static Analysis Results Interchange Format) logs.
+//!
+//! [SARIF]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+use std::{
+    collections::HashMap,
+    env,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use url::Url;
+
+use crate::{AnnotationLevel, FileAnnotation, RestClientError};
+#[cfg(feature = "file-changes")]
+use crate::{FileDiffLines, ReviewComment};
+
+#[derive(Debug, Deserialize)]
+struct SarifLog {
+    #[serde(default)]
+    runs: Vec<Run>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    #[serde(default)]
+    results: Vec<SarifResult>,
+    #[serde(default)]
+    original_uri_base_ids: HashMap<String, ArtifactLocation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tool {
+    driver: ToolComponent,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolComponent {
+    #[serde(default)]
+    rules: Vec<ReportingDescriptor>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportingDescriptor {
+    id: String,
+    default_configuration: Option<ReportingConfiguration>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportingConfiguration {
+    level: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: Option<String>,
+    rule_index: Option<usize>,
+    level: Option<String>,
+    message: Message,
+    #[serde(default)]
+    locations: Vec<Location>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: Option<PhysicalLocation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: Option<ArtifactLocation>,
+    region: Option<Region>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: Option<String>,
+    uri_base_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: Option<usize>,
+    start_column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+}
+
+impl Run {
+    /// Get the level of a `result`.
+    ///
+    /// Falls back to the rule's default level, then to SARIF's default level (`"warning"`).
+    fn result_level(&self, result: &SarifResult) -> AnnotationLevel {
+        let level = result.level.as_deref().or_else(|| {
+            let rules = &self.tool.driver.rules;
+            result
+                .rule_index
+                .and_then(|i| rules.get(i))
+                .or_else(|| {
+                    let id = result.rule_id.as_deref()?;
+                    rules.iter().find(|r| r.id == id)
+                })
+                .and_then(|r| r.default_configuration.as_ref()?.level.as_deref())
+        });
+        match level {
+            Some("error") => AnnotationLevel::Error,
+            Some("note") => AnnotationLevel::Notice,
+            Some("none") => AnnotationLevel::Debug,
+            _ => AnnotationLevel::Warning,
+        }
+    }
+
+    /// Resolve the `location` into a path relative to the `workspace` root.
+    ///
+    /// Returns [`None`] if the `location` has no URI.
+    fn resolve_path(&self, location: &ArtifactLocation, workspace: &Path) -> Option<String> {
+        let uri = location.uri.as_deref()?;
+        let base = location
+            .uri_base_id
+            .as_ref()
+            .and_then(|id| self.original_uri_base_ids.get(id))
+            .and_then(|base| base.uri.as_deref())
+            .and_then(|base| Url::parse(base).ok());
+        let url = match base {
+            Some(base) => base.join(uri).ok(),
+            None => Url::parse(uri).ok(),
+        };
+        let path = match url {
+            Some(url) if url.scheme() == "file" => url
+                .to_file_path()
+                .unwrap_or_else(|_| PathBuf::from(url.path())),
+            // a relative path (or an unsupported URI scheme)
+            _ => PathBuf::from(uri),
+        };
+        let path = path.strip_prefix(workspace).unwrap_or(&path);
+        let path = path.to_string_lossy().replace('\\', "/");
+        Some(path.trim_start_matches("./").to_string())
+    }
+}
+
+/// Parse a SARIF log from the given `reader` into a list of [`FileAnnotation`]s.
+///
+/// Each SARIF result is mapped as follows:
+///
+/// - The result's `level` is mapped to [`FileAnnotation::severity`].
+///   If not specified, the rule's default level is used (which defaults to `"warning"`).
+/// - The result's first physical location is mapped to [`FileAnnotation::path`].
+///   File URIs (`file://`) and absolute paths are made relative to the workspace root.
+/// - The location's region is mapped to [`FileAnnotation::start_line`],
+///   [`FileAnnotation::end_line`], [`FileAnnotation::start_column`], and [`FileAnnotation::end_column`].
+/// - The result's `ruleId` is mapped to [`FileAnnotation::title`].
+/// - The result's message text is mapped to [`FileAnnotation::message`].
+///
+/// The workspace root is the value of the `GITHUB_WORKSPACE` environment variable
+/// (set by GitHub and Gitea CI runners). If that is not set, then the current working
+/// directory is used.
+pub fn parse_sarif<R: Read>(reader: R) -> Result<Vec<FileAnnotation>, RestClientError> {
+    let workspace = match env::var("GITHUB_WORKSPACE") {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => env::current_dir()
+            .map_err(|e| RestClientError::io("get current working directory", e))?,
+    };
+    let log: SarifLog = serde_json::from_reader(reader)
+        .map_err(|e| RestClientError::json("deserialize SARIF log", e))?;
+    let mut annotations = Vec::new();
+    for run in &log.runs {
+        for result in &run.results {
+            let physical = result
+                .locations
+                .iter()
+                .find_map(|l| l.physical_location.as_ref());
+            let path = physical
+                .and_then(|p| p.artifact_location.as_ref())
+                .and_then(|a| run.resolve_path(a, &workspace))
+                .unwrap_or_default();
+            let region = physical.and_then(|p| p.region.as_ref());
+            annotations.push(FileAnnotation {
+                severity: run.result_level(result),
+                path,
+                start_line: region.and_then(|r| r.start_line),
+                end_line: region.and_then(|r| r.end_line),
+                start_column: region.and_then(|r| r.start_column),
+                end_column: region.and_then(|r| r.end_column),
+                title: result.rule_id.clone(),
+                message: result.message.text.clone().unwrap_or_default(),
+            });
+        }
+    }
+    Ok(annotations)
+}
+
+/// Parse a SARIF log from the given `reader` into [`ReviewComment`]s about the changes in `files`.
+///
+/// This is a convenience function that combines [`parse_sarif()`] with
+/// [`ReviewComment::from_annotations()`].
+///
+/// Returns a tuple of the review comments and the annotations that are not within the diff.
+#[cfg(feature = "file-changes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "file-changes")))]
+pub fn parse_sarif_review_comments<R: Read>(
+    reader: R,
+    files: &HashMap<String, FileDiffLines>,
+) -> Result<(Vec<ReviewComment>, Vec<FileAnnotation>), RestClientError> {
+    let annotations = parse_sarif(reader)?;
+    let (comments, leftovers) = ReviewComment::from_annotations(&annotations, files);
+    Ok((comments, leftovers.into_iter().cloned().collect()))
+}
@@ -0,0 +1,622 @@
+//! A module to generate markdown summaries that can be passed to
+//! [`RestApiClient::append_step_summary()`](crate::RestApiClient::append_step_summary).
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+use std::fmt::Write;
+
+use crate::{AnnotationLevel, FileAnnotation, RestApiClient, RestClientError};
+
+/// The maximum size (in bytes) of a CI step's summary.
+///
+/// GitHub refuses to display a step summary that is larger than 1 MiB.
+pub const MAX_STEP_SUMMARY_SIZE: u64 = 1024 * 1024;
+
+/// The note that ends a comment truncated by [`SummaryOverflow::Truncate`].
+pub const TRUNCATED_NOTE: &str = "… output truncated";
+
+/// The policy applied when a comment would make a CI step's summary exceed its size limit.
+///
+/// See [`MAX_STEP_SUMMARY_SIZE`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "pyo3",
+    pyclass(module = "git_bot_feedback", from_py_object, eq)
+)]
+pub enum SummaryOverflow {
+    /// Truncate the comment at a line boundary (outside of any code fence)
+    /// and end it with a [`TRUNCATED_NOTE`].
+    #[default]
+    Truncate,
+    /// Do not append the comment and return an error instead.
+    Error,
+}
+
+/// Options that control the markdown generated by [`from_annotations()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummaryOptions {
+    /// The heading of the generated markdown section.
+    pub heading: String,
+
+    /// The maximum number of characters shown for each annotation's message.
+    ///
+    /// Longer messages are truncated with a trailing `…`.
+    pub max_message_width: usize,
+
+    /// The maximum number of rows in the table of annotations.
+    ///
+    /// Any remaining annotations are only mentioned by count.
+    pub max_rows: usize,
+}
+
+impl Default for SummaryOptions {
+    fn default() -> Self {
+        Self {
+            heading: "### File annotations".to_string(),
+            max_message_width: 80,
+            max_rows: 50,
+        }
+    }
+}
+
+/// The order in which severities are presented.
+const SEVERITIES: [(AnnotationLevel, &str); 4] = [
+    (AnnotationLevel::Error, "Error"),
+    (AnnotationLevel::Warning, "Warning"),
+    (AnnotationLevel::Notice, "Notice"),
+    (AnnotationLevel::Debug, "Debug"),
+];
+
+fn severity_rank(severity: AnnotationLevel) -> usize {
+    SEVERITIES
+        .iter()
+        .position(|(s, _)| *s == severity)
+        .unwrap_or_default()
+}
+
+/// Make the given `text` safe to use in a markdown table cell.
+///
+/// Line breaks are replaced with spaces and pipe characters are escaped.
+/// If `max_width` is given, then the text is truncated to that many characters.
+fn table_cell(text: &str, max_width: Option<usize>) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let text = match max_width {
+        Some(width) if text.chars().count() > width => {
+            let mut truncated = text
+                .chars()
+                .take(width.saturating_sub(1))
+                .collect::<String>();
+            truncated.push('…');
+            truncated
+        }
+        _ => text,
+    };
+    text.replace('|', "\\|")
+}
+
+/// Generate a markdown section that summarizes the given `annotations`.
+///
+/// The section includes the number of annotations per severity and a table of
+/// the annotations' path, line, title, and message.
+/// Table rows are grouped by file (sorted by path) and then sorted by line number and severity.
+///
+/// ```
+/// use git_bot_feedback::{AnnotationLevel, FileAnnotation, summary};
+///
+/// let annotations = [FileAnnotation {
+///     severity: AnnotationLevel::Warning,
+///     path: "src/main.rs".to_string(),
+///     start_line: Some(4),
+///     message: "unused variable".to_string(),
+///     ..Default::default()
+/// }];
+/// let markdown = summary::from_annotations(&annotations, &Default::default());
+/// assert!(markdown.contains("| `src/main.rs` | 4 |  | unused variable |"));
+/// ```
+pub fn from_annotations(annotations: &[FileAnnotation], options: &SummaryOptions) -> String {
+    let mut markdown = format!("{}\n\n", options.heading);
+    if annotations.is_empty() {
+        markdown.push_str("No annotations.\n");
+        return markdown;
+    }
+
+    markdown.push_str("| Severity | Count |\n|:---------|------:|\n");
+    for (severity, name) in SEVERITIES {
+        let count = annotations
+            .iter()
+            .filter(|a| a.severity == severity)
+            .count();
+        if count > 0 {
+            let _ = writeln!(markdown, "| {name} | {count} |");
+        }
+    }
+
+    let mut sorted = annotations.iter().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| {
+        a.path
+            .cmp(&b.path)
+            .then(a.start_line.cmp(&b.start_line))
+            .then(severity_rank(a.severity).cmp(&severity_rank(b.severity)))
+            .then(a.message.cmp(&b.message))
+    });
+
+    markdown.push_str("\n| Path | Line | Title | Message |\n|:-----|-----:|:------|:--------|\n");
+    for annotation in sorted.iter().take(options.max_rows) {
+        let path = if annotation.path.is_empty() {
+            String::new()
+        } else {
+            format!("`{}`", table_cell(&annotation.path, None))
+        };
+        let line = match (annotation.start_line, annotation.end_line) {
+            (Some(start), Some(end)) if end > start => format!("{start}-{end}"),
+            (Some(start), _) => start.to_string(),
+            _ => String::new(),
+        };
+        let title = annotation
+            .title
+            .as_deref()
+            .map(|t| table_cell(t, None))
+            .unwrap_or_default();
+        let message = table_cell(&annotation.message, Some(options.max_message_width));
+        let _ = writeln!(markdown, "| {path} | {line} | {title} | {message} |");
+    }
+    if sorted.len() > options.max_rows {
+        let _ = writeln!(markdown, "\n… and {} more", sorted.len() - options.max_rows);
+    }
+    markdown
+}
+
+/// How a comment is written to a CI step's summary.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "pyo3",
+    pyclass(module = "git_bot_feedback", from_py_object, eq)
+)]
+pub enum SummaryWriteMode {
+    /// Append the comment after any existing content.
+    #[default]
+    Append,
+    /// Replace any existing content with the comment.
+    Overwrite,
+}
+
+/// A builder of markdown that can be passed to
+/// [`RestApiClient::append_step_summary()`](crate::RestApiClient::append_step_summary).
+///
+/// Each element is separated by a blank line.
+/// Text in table cells is escaped, and code blocks always use a balanced fence.
+///
+/// ```
+/// use git_bot_feedback::summary::SummaryBuilder;
+///
+/// let mut builder = SummaryBuilder::default();
+/// builder
+///     .heading(3, "Lint results")
+///     .table(&["File", "Warnings"], &[vec!["src/main.rs".to_string(), "2".to_string()]]);
+/// assert_eq!(
+///     builder.build(),
+///     "### Lint results\n\n| File | Warnings |\n|---|---|\n| src/main.rs | 2 |\n"
+/// );
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SummaryBuilder {
+    markdown: String,
+}
+
+impl SummaryBuilder {
+    /// Append the given `element` with a separating blank line.
+    fn push(&mut self, element: &str) -> &mut Self {
+        if !self.markdown.is_empty() {
+            self.markdown.push('\n');
+        }
+        self.markdown.push_str(element);
+        if !element.ends_with('\n') {
+            self.markdown.push('\n');
+        }
+        self
+    }
+
+    /// Add a heading of the given `level` (clamped to the range 1 to 6).
+    pub fn heading(&mut self, level: u8, text: &str) -> &mut Self {
+        let level = usize::from(level.clamp(1, 6));
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        self.push(format!("{} {text}", "#".repeat(level)).as_str())
+    }
+
+    /// Add a paragraph of text.
+    pub fn paragraph(&mut self, text: &str) -> &mut Self {
+        self.push(text.trim_end())
+    }
+
+    /// Add a table with the given `headers` and `rows`.
+    ///
+    /// Line breaks and `|` in cells are escaped.
+    /// Missing cells in a row are left empty; extra cells are ignored.
+    pub fn table<S: AsRef<str>>(&mut self, headers: &[&str], rows: &[Vec<S>]) -> &mut Self {
+        let mut table = format!(
+            "| {} |\n|{}\n",
+            headers
+                .iter()
+                .map(|h| table_cell(h, None))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            "---|".repeat(headers.len())
+        );
+        for row in rows {
+            let cells = (0..headers.len())
+                .map(|i| {
+                    row.get(i)
+                        .map(|cell| table_cell(cell.as_ref(), None))
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>();
+            let _ = writeln!(table, "| {} |", cells.join(" | "));
+        }
+        self.push(&table)
+    }
+
+    /// Add a collapsible section with the given `summary` that reveals the `body` (markdown) when expanded.
+    pub fn details(&mut self, summary: &str, body: &str) -> &mut Self {
+        let summary = summary
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        self.push(
+            format!(
+                "<details><summary>{summary}</summary>\n\n{}\n\n</details>",
+                body.trim_end()
+            )
+            .as_str(),
+        )
+    }
+
+    /// Add a fenced code block with the given `lang` for syntax highlighting.
+    ///
+    /// The fence is longer than any run of backticks in the `content`.
+    pub fn code_block(&mut self, lang: &str, content: &str) -> &mut Self {
+        let mut longest = 0;
+        let mut current = 0;
+        for c in content.chars() {
+            current = if c == '`' { current + 1 } else { 0 };
+            longest = longest.max(current);
+        }
+        let fence = "`".repeat(longest.max(2) + 1);
+        self.push(format!("{fence}{lang}\n{}\n{fence}", content.trim_end_matches('\n')).as_str())
+    }
+
+    /// Add a link to the given `url`.
+    pub fn link(&mut self, text: &str, url: &str) -> &mut Self {
+        let text = text.replace('[', "\\[").replace(']', "\\]");
+        let url = url.replace(' ', "%20").replace(')', "%29");
+        self.push(format!("[{text}]({url})").as_str())
+    }
+
+    /// The size (in bytes) of the markdown built so far.
+    ///
+    /// Compare this with [`MAX_STEP_SUMMARY_SIZE`] to avoid exceeding the size limit
+    /// of a step summary.
+    pub fn len(&self) -> usize {
+        self.markdown.len()
+    }
+
+    /// Is there no markdown built yet?
+    pub fn is_empty(&self) -> bool {
+        self.markdown.is_empty()
+    }
+
+    /// Get the built markdown.
+    pub fn build(&self) -> String {
+        self.markdown.clone()
+    }
+
+    /// Append the built markdown to the CI workflow's summary page.
+    ///
+    /// See [`RestApiClient::append_step_summary()`].
+    pub fn write_to<C: RestApiClient + ?Sized>(
+        &self,
+        client: &C,
+        overflow: SummaryOverflow,
+    ) -> Result<(), RestClientError> {
+        client.append_step_summary(&self.markdown, overflow)
+    }
+}
+
+/// Get the longest prefix of the given `markdown` that is at most `max_len` bytes.
+///
+/// The prefix ends at a line boundary that is not inside a fenced code block.
+#[cfg(any(feature = "gitea", feature = "github"))]
+fn truncate_markdown(markdown: &str, max_len: usize) -> &str {
+    let mut end = 0;
+    let mut offset = 0;
+    let mut fence: Option<&str> = None;
+    for line in markdown.split_inclusive('\n') {
+        offset += line.len();
+        if offset > max_len {
+            break;
+        }
+        let trimmed = line.trim_start();
+        match fence {
+            Some(marker) if trimmed.starts_with(marker) => fence = None,
+            None => {
+                fence = ["```", "~~~"]
+                    .into_iter()
+                    .find(|marker| trimmed.starts_with(marker));
+            }
+            _ => {}
+        }
+        if fence.is_none() {
+            end = offset;
+        }
+    }
+    &markdown[..end]
+}
+
+/// Fit the given `markdown` into a step summary that is `current_size` bytes without it.
+///
+/// If the `markdown` would make the step summary larger than `limit` bytes,
+/// then the `overflow` policy is applied.
+/// Returns [`None`] if the step summary cannot fit any more text (not even the [`TRUNCATED_NOTE`]).
+#[cfg(any(feature = "gitea", feature = "github"))]
+pub(crate) fn fit_markdown(
+    markdown: &str,
+    current_size: u64,
+    limit: u64,
+    overflow: SummaryOverflow,
+) -> Result<Option<String>, RestClientError> {
+    let available = usize::try_from(limit.saturating_sub(current_size)).unwrap_or(usize::MAX);
+    if markdown.len() <= available {
+        return Ok(Some(markdown.to_string()));
+    }
+    if overflow == SummaryOverflow::Error {
+        return Err(RestClientError::StepSummaryTooLarge {
+            size: current_size + markdown.len() as u64,
+            limit,
+        });
+    }
+    // the space needed for the truncation note
+    let overhead = format!("\n{TRUNCATED_NOTE}").len();
+    let Some(budget) = available.checked_sub(overhead) else {
+        return Ok(None);
+    };
+    let kept = truncate_markdown(markdown, budget);
+    let separator = if kept.is_empty() || kept.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    Ok(Some(format!("{kept}{separator}\n{TRUNCATED_NOTE}")))
+}
+
+/// Format the given `comment` to be appended to a step summary that is already `current_size` bytes.
+///
+/// The returned text is surrounded by blank lines.
+/// See [`fit_markdown()`] about the other parameters.
+#[cfg(any(feature = "gitea", feature = "github"))]
+pub(crate) fn fit_step_summary(
+    comment: &str,
+    current_size: u64,
+    limit: u64,
+    overflow: SummaryOverflow,
+) -> Result<Option<String>, RestClientError> {
+    // the surrounding blank lines take 3 bytes
+    let fitted = fit_markdown(comment, current_size + 3, limit, overflow)?;
+    Ok(fitted.map(|content| format!("\n{content}\n\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::{SummaryBuilder, SummaryOptions, from_annotations};
+    use crate::{AnnotationLevel, FileAnnotation};
+
+    fn annotation(
+        severity: AnnotationLevel,
+        path: &str,
+        start_line: Option<usize>,
+        message: &str,
+    ) -> FileAnnotation {
+        FileAnnotation {
+            severity,
+            path: path.to_string(),
+            start_line,
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn mixed_annotations() -> Vec<FileAnnotation> {
+        let mut ranged = annotation(
+            AnnotationLevel::Error,
+            "src/lib.rs",
+            Some(12),
+            "never loops",
+        );
+        ranged.end_line = Some(15);
+        ranged.title = Some("clippy::never_loop".to_string());
+        vec![
+            annotation(
+                AnnotationLevel::Warning,
+                "src/main.rs",
+                Some(3),
+                "unused variable `x`",
+            ),
+            ranged,
+            annotation(
+                AnnotationLevel::Notice,
+                "src/lib.rs",
+                Some(2),
+                "use `a | b`\ninstead of `a || b`",
+            ),
+            annotation(
+                AnnotationLevel::Warning,
+                "src/lib.rs",
+                None,
+                "file is too long",
+            ),
+            annotation(AnnotationLevel::Warning, "", None, "workflow warning"),
+        ]
+    }
+
+    #[test]
+    fn snapshot() {
+        let markdown = from_annotations(&mixed_annotations(), &SummaryOptions::default());
+        assert_eq!(
+            markdown,
+            "\
+### File annotations
+
+| Severity | Count |
+|:---------|------:|
+| Error | 1 |
+| Warning | 3 |
+| Notice | 1 |
+
+| Path | Line | Title | Message |
+|:-----|-----:|:------|:--------|
+|  |  |  | workflow warning |
+| `src/lib.rs` |  |  | file is too long |
+| `src/lib.rs` | 2 |  | use `a \\| b` instead of `a \\|\\| b` |
+| `src/lib.rs` | 12-15 | clippy::never_loop | never loops |
+| `src/main.rs` | 3 |  | unused variable `x` |
+"
+        );
+    }
+
+    #[test]
+    fn truncated_snapshot() {
+        let options = SummaryOptions {
+            heading: "## Lint results".to_string(),
+            max_message_width: 10,
+            max_rows: 2,
+        };
+        let markdown = from_annotations(&mixed_annotations(), &options);
+        assert_eq!(
+            markdown,
+            "\
+## Lint results
+
+| Severity | Count |
+|:---------|------:|
+| Error | 1 |
+| Warning | 3 |
+| Notice | 1 |
+
+| Path | Line | Title | Message |
+|:-----|-----:|:------|:--------|
+|  |  |  | workflow … |
+| `src/lib.rs` |  |  | file is t… |
+
+… and 3 more
+"
+        );
+    }
+
+    #[test]
+    fn no_annotations() {
+        assert_eq!(
+            from_annotations(&[], &SummaryOptions::default()),
+            "### File annotations\n\nNo annotations.\n"
+        );
+    }
+
+    #[cfg(any(feature = "gitea", feature = "github"))]
+    mod step_summary {
+        use super::super::{SummaryOverflow, TRUNCATED_NOTE, fit_step_summary, truncate_markdown};
+        use crate::RestClientError;
+
+        const FENCED: &str = "# Results\n\n```diff\n-old\n+new\n```\n\nDone.\n";
+
+        #[test]
+        fn truncate_outside_fence() {
+            // cutting anywhere inside the code fence backs up to before the fence
+            for max_len in 11..FENCED.find("\n\nDone").unwrap() {
+                assert_eq!(truncate_markdown(FENCED, max_len), "# Results\n\n");
+            }
+            assert_eq!(
+                truncate_markdown(FENCED, FENCED.len() - 1),
+                "# Results\n\n```diff\n-old\n+new\n```\n\n"
+            );
+            assert_eq!(truncate_markdown(FENCED, FENCED.len()), FENCED);
+            assert_eq!(truncate_markdown(FENCED, 5), "");
+        }
+
+        #[test]
+        fn fit_without_overflow() {
+            let fitted = fit_step_summary("text", 0, 100, SummaryOverflow::Error).unwrap();
+            assert_eq!(fitted.as_deref(), Some("\ntext\n\n"));
+        }
+
+        #[test]
+        fn fit_with_truncation() {
+            let limit = 1000;
+            let current_size = limit - 40;
+            let fitted = fit_step_summary(FENCED, current_size, limit, SummaryOverflow::Truncate)
+                .unwrap()
+                .unwrap();
+            assert_eq!(fitted, format!("\n# Results\n\n\n{TRUNCATED_NOTE}\n\n"));
+            assert!(current_size + fitted.len() as u64 <= limit);
+        }
+
+        #[test]
+        fn fit_when_full() {
+            let fitted = fit_step_summary(FENCED, 995, 1000, SummaryOverflow::Truncate).unwrap();
+            assert!(fitted.is_none());
+        }
+
+        #[test]
+        fn fit_with_error() {
+            let err = fit_step_summary(FENCED, 995, 1000, SummaryOverflow::Error).unwrap_err();
+            assert!(matches!(
+                err,
+                RestClientError::StepSummaryTooLarge { limit: 1000, .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn builder_snapshot() {
+        let mut builder = SummaryBuilder::default();
+        assert!(builder.is_empty());
+        builder
+            .heading(9, "Lint\nresults")
+            .paragraph("Found **2** problems.\n")
+            .table(
+                &["File", "Message"],
+                &[
+                    vec!["src/lib.rs", "use `a | b`\ninstead"],
+                    vec!["src/main.rs"],
+                ],
+            )
+            .details("Raw <output>", "```\nwarning: unused\n```")
+            .code_block("rust", "let s = \"```\";\n")
+            .link("[docs]", "https://example.com/a b)");
+        let markdown = builder.build();
+        assert_eq!(builder.len(), markdown.len());
+        assert_eq!(
+            markdown,
+            "\
+###### Lint results
+
+Found **2** problems.
+
+| File | Message |
+|---|---|
+| src/lib.rs | use `a \\| b` instead |
+| src/main.rs |  |
+
+<details><summary>Raw &lt;output&gt;</summary>
+
+```
+warning: unused
+```
+
+</details>
+
+````rust
+let s = \"```\";
+````
+
+[\\[docs\\]](https://example.com/a%20b%29)
+"
+        );
+    }
+}
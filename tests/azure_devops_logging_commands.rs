@@ -0,0 +1,139 @@
+#![cfg(feature = "azure-devops")]
+use git_bot_feedback::{
+    AnnotationLevel, AnnotationOverflow, FileAnnotation, OutputVariable, RestApiClient,
+    RestClientError,
+    client::{AzureDevopsApiClient, CommandSink},
+    error::OutputVariableError,
+};
+use std::{
+    env,
+    sync::{Arc, Mutex},
+};
+
+mod common;
+use common::logger_init;
+
+/// Get a client whose logging commands are captured in the returned buffer.
+fn setup() -> (AzureDevopsApiClient, Arc<Mutex<Vec<u8>>>) {
+    logger_init();
+    unsafe {
+        env::set_var("SYSTEM_COLLECTIONURI", "https://dev.azure.com/my-org/");
+        env::set_var("SYSTEM_TEAMPROJECT", "my-project");
+        env::set_var("BUILD_REPOSITORY_NAME", "my-repo");
+        env::set_var("BUILD_REASON", "IndividualCI");
+        env::remove_var("SYSTEM_PULLREQUEST_PULLREQUESTID");
+    }
+    let mut client = AzureDevopsApiClient::new().unwrap();
+    let output = Arc::new(Mutex::new(Vec::<u8>::new()));
+    client.set_command_sink(CommandSink::Writer(output.clone()));
+    (client, output)
+}
+
+fn captured(output: &Arc<Mutex<Vec<u8>>>) -> Vec<String> {
+    String::from_utf8(output.lock().unwrap().clone())
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn set_variables() {
+    let (client, output) = setup();
+    assert!(client.capabilities().output_variables);
+    client
+        .write_output_variables(&[
+            OutputVariable::new("LINT_CHECKS", "3").unwrap(),
+            OutputVariable::bool("lint-passed", false).unwrap(),
+            OutputVariable::new("LINT_REPORT", "50%; done\nsee ]notes[").unwrap(),
+        ])
+        .unwrap();
+    assert_eq!(
+        captured(&output),
+        [
+            "##vso[task.setvariable variable=LINT_CHECKS;isoutput=true]3",
+            "##vso[task.setvariable variable=lint-passed;isoutput=true]false",
+            "##vso[task.setvariable variable=LINT_REPORT;isoutput=true]50%AZP25%3B done%0Asee %5Dnotes[",
+        ]
+    );
+}
+
+#[test]
+fn invalid_variable() {
+    let (client, output) = setup();
+    let err = client
+        .write_output_variables(&[
+            OutputVariable::new("LINT_CHECKS", "3").unwrap(),
+            OutputVariable {
+                name: "1LINT".to_string(),
+                value: "3".to_string(),
+            },
+        ])
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        RestClientError::OutputVar(OutputVariableError::NameStartsWithNumber(_))
+    ));
+    // nothing is written if any variable is invalid
+    assert!(captured(&output).is_empty());
+}
+
+#[test]
+fn annotations() {
+    let (client, output) = setup();
+    assert!(client.capabilities().file_annotations);
+    let annotations = [
+        FileAnnotation {
+            severity: AnnotationLevel::Error,
+            path: "src/lib.rs".to_string(),
+            start_line: Some(42),
+            start_column: Some(7),
+            title: Some("clippy".to_string()),
+            message: "unused variable: `x`".to_string(),
+            ..Default::default()
+        },
+        FileAnnotation {
+            severity: AnnotationLevel::Warning,
+            path: "src/main.rs".to_string(),
+            start_line: Some(3),
+            message: "line 1\nline 2".to_string(),
+            ..Default::default()
+        },
+        FileAnnotation {
+            severity: AnnotationLevel::Notice,
+            path: "README.md".to_string(),
+            message: "consider a table of contents".to_string(),
+            ..Default::default()
+        },
+        FileAnnotation {
+            severity: AnnotationLevel::Debug,
+            message: "checked 3 files".to_string(),
+            ..Default::default()
+        },
+    ];
+    let written = client
+        .write_file_annotations(&annotations, AnnotationOverflow::Error)
+        .unwrap();
+    assert_eq!(written, annotations.len());
+    assert_eq!(
+        captured(&output),
+        [
+            "##vso[task.logissue type=error;sourcepath=src/lib.rs;linenumber=42;columnnumber=7;]clippy: unused variable: `x`",
+            "##vso[task.logissue type=warning;sourcepath=src/main.rs;linenumber=3;]line 1%0Aline 2",
+            "README.md: consider a table of contents",
+            "##[debug]checked 3 files",
+        ]
+    );
+}
+
+#[test]
+fn untrusted_content() {
+    let (client, output) = setup();
+    client
+        .log_untrusted("PR title\n##vso[task.setvariable variable=PATH]/tmp")
+        .unwrap();
+    assert_eq!(
+        captured(&output),
+        ["PR title", "##vso[task.setvariable variable=PATH%5D/tmp",]
+    );
+}
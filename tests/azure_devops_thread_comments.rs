@@ -0,0 +1,407 @@
+#![cfg(feature = "azure-devops")]
+use chrono::Utc;
+use git_bot_feedback::{
+    CommentKind, CommentPolicy, RestApiClient, RestClientError, ThreadCommentOptions,
+    client::AzureDevopsApiClient, reqwest::StatusCode,
+};
+use mockito::{Matcher, Server};
+use std::env;
+
+mod common;
+use common::{EventType, logger_init};
+
+const MARKER: &str = "<!-- git-bot-feedback -->\n";
+const PROJECT: &str = "my-project";
+const REPO: &str = "my-repo";
+const PR: i64 = 7;
+const TOKEN: &str = "123456";
+/// The `Basic` credentials of the `TOKEN` (with an empty user name).
+const BASIC_AUTH: &str = "Basic OjEyMzQ1Ng==";
+const MOCK_ASSETS_PATH: &str = "tests/assets/thread_comment/azure_devops/";
+
+const RESET_RATE_LIMIT_HEADER: &str = "X-RateLimit-Reset";
+const REMAINING_RATE_LIMIT_HEADER: &str = "X-RateLimit-Remaining";
+const DELETION_DENIED: &str =
+    r#"{"message":"TF401027: You need the Git 'Contribute to pull requests' permission"}"#;
+const POSTING_DENIED: &str =
+    r#"{"message":"TF401027: You need the Git 'Contribute to pull requests' permission to post"}"#;
+
+/// The IDs of the bot's (non-deleted) threads in the mocked response.
+const BOT_THREADS: [i64; 2] = [102, 105];
+
+struct TestParams {
+    event_t: EventType,
+    comment_policy: CommentPolicy,
+    no_lgtm: bool,
+    comment_kind: CommentKind,
+    fail_get_existing_comments: bool,
+    fail_dismissal: bool,
+    fail_posting: bool,
+    bad_existing_comments: bool,
+    bad_pr_id: bool,
+    no_repo_env_var: bool,
+}
+
+impl Default for TestParams {
+    fn default() -> Self {
+        Self {
+            event_t: EventType::PullRequest,
+            comment_policy: CommentPolicy::Update,
+            no_lgtm: false,
+            comment_kind: CommentKind::Concerns,
+            fail_get_existing_comments: false,
+            fail_dismissal: false,
+            fail_posting: false,
+            bad_existing_comments: false,
+            bad_pr_id: false,
+            no_repo_env_var: false,
+        }
+    }
+}
+
+fn api_version() -> Matcher {
+    Matcher::UrlEncoded("api-version".to_string(), "7.0".to_string())
+}
+
+async fn test_comment(test_params: &TestParams) {
+    let mut server = Server::new_async().await;
+    unsafe {
+        env::set_var("TF_BUILD", "True");
+        env::set_var("SYSTEM_COLLECTIONURI", format!("{}/my-org/", server.url()));
+        env::set_var("SYSTEM_TEAMPROJECT", PROJECT);
+        env::set_var(
+            "BUILD_REASON",
+            match test_params.event_t {
+                EventType::Push => "IndividualCI",
+                EventType::PullRequest => "PullRequest",
+            },
+        );
+        if test_params.event_t == EventType::PullRequest {
+            let id = if test_params.bad_pr_id {
+                "not a number".to_string()
+            } else {
+                PR.to_string()
+            };
+            env::set_var("SYSTEM_PULLREQUEST_PULLREQUESTID", id);
+        } else {
+            env::remove_var("SYSTEM_PULLREQUEST_PULLREQUESTID");
+        }
+        if !test_params.no_repo_env_var {
+            env::set_var("BUILD_REPOSITORY_NAME", REPO);
+        } else {
+            env::remove_var("BUILD_REPOSITORY_NAME");
+        }
+        env::set_var("SYSTEM_ACCESSTOKEN", TOKEN);
+        env::set_var("SYSTEM_DEBUG", "true");
+    }
+
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    let client = match AzureDevopsApiClient::new() {
+        Ok(c) => c,
+        Err(e) => {
+            if test_params.no_repo_env_var {
+                assert!(matches!(e, RestClientError::EnvVar { .. }));
+            } else if test_params.bad_pr_id {
+                assert!(matches!(e, RestClientError::MalformedEventInfo(_)));
+            } else {
+                panic!("Unexpected error creating AzureDevopsApiClient: {e}");
+            }
+            return;
+        }
+    };
+    assert!(client.is_debug_enabled());
+    assert_eq!(
+        client.is_pr_event(),
+        test_params.event_t == EventType::PullRequest
+    );
+
+    let reset_timestamp = (Utc::now().timestamp() + 60).to_string();
+    let threads_path =
+        format!("/my-org/{PROJECT}/_apis/git/repositories/{REPO}/pullRequests/{PR}/threads");
+    let mut mocks = vec![];
+
+    if test_params.event_t == EventType::Push {
+        // thread comments are not posted outside of pull requests
+        for method in ["GET", "POST", "PATCH", "DELETE"] {
+            mocks.push(server.mock(method, Matcher::Any).expect(0).create());
+        }
+    } else {
+        let mut mock = server
+            .mock("GET", threads_path.as_str())
+            .match_header("Authorization", BASIC_AUTH)
+            .match_query(api_version())
+            .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+            .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+            .with_status(if test_params.fail_get_existing_comments {
+                403
+            } else {
+                200
+            });
+        if test_params.bad_existing_comments {
+            mock = mock.with_body(String::new());
+        } else {
+            mock = mock.with_body_from_file(format!("{MOCK_ASSETS_PATH}pr_threads.json"));
+        }
+        mocks.push(mock.create());
+
+        if !test_params.fail_get_existing_comments && !test_params.bad_existing_comments {
+            let deleting_all = test_params.comment_policy == CommentPolicy::Anew
+                || (test_params.comment_kind == CommentKind::Lgtm && test_params.no_lgtm);
+            for (i, thread_id) in BOT_THREADS.iter().enumerate() {
+                // when updating, the last thread is kept
+                let deleted = deleting_all || i + 1 < BOT_THREADS.len();
+                // a failed deletion stops the traversal
+                let attempted = deleted && (!test_params.fail_dismissal || i == 0);
+                mocks.push(
+                    server
+                        .mock(
+                            "DELETE",
+                            format!("{threads_path}/{thread_id}/comments/1").as_str(),
+                        )
+                        .match_header("Authorization", BASIC_AUTH)
+                        .match_query(api_version())
+                        .with_status(if test_params.fail_dismissal { 403 } else { 200 })
+                        .with_body(if test_params.fail_dismissal {
+                            DELETION_DENIED
+                        } else {
+                            ""
+                        })
+                        .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+                        .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+                        .expect(usize::from(attempted))
+                        .create(),
+                );
+            }
+        }
+    }
+
+    let comment = match test_params.comment_kind {
+        CommentKind::Concerns => "Attention".to_string(),
+        CommentKind::Lgtm => "LGTM".to_string(),
+    };
+    let status = match test_params.comment_kind {
+        CommentKind::Concerns => "active",
+        CommentKind::Lgtm => "fixed",
+    };
+
+    let posting_comment = match test_params.comment_kind {
+        CommentKind::Concerns => true,
+        CommentKind::Lgtm => !test_params.no_lgtm && !test_params.bad_existing_comments,
+    };
+    if posting_comment && test_params.event_t == EventType::PullRequest {
+        // a failed deletion stops the update
+        let expected = usize::from(!test_params.fail_dismissal);
+        let last_thread = format!("{threads_path}/{}", BOT_THREADS[1]);
+        if test_params.bad_existing_comments
+            || test_params.fail_get_existing_comments
+            || test_params.comment_policy == CommentPolicy::Anew
+        {
+            mocks.push(
+                server
+                    .mock("POST", threads_path.as_str())
+                    .match_query(api_version())
+                    .match_body(Matcher::PartialJson(serde_json::json!({
+                        "comments": [{
+                            "parentCommentId": 0,
+                            "content": format!("{MARKER}{comment}"),
+                            "commentType": 1,
+                        }],
+                        "status": status,
+                    })))
+                    .match_header("Authorization", BASIC_AUTH)
+                    .match_header("Content-Type", "application/json")
+                    .with_status(if test_params.fail_posting { 403 } else { 200 })
+                    .with_body(POSTING_DENIED)
+                    .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+                    .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+                    .expect(expected)
+                    .create(),
+            );
+        } else {
+            mocks.push(
+                server
+                    .mock("PATCH", format!("{last_thread}/comments/1").as_str())
+                    .match_query(api_version())
+                    .match_body(Matcher::Json(serde_json::json!({
+                        "content": format!("{MARKER}{comment}"),
+                    })))
+                    .match_header("Authorization", BASIC_AUTH)
+                    .match_header("Content-Type", "application/json")
+                    .with_status(if test_params.fail_posting { 403 } else { 200 })
+                    .with_body(POSTING_DENIED)
+                    .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+                    .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+                    .expect(expected)
+                    .create(),
+            );
+            mocks.push(
+                server
+                    .mock("PATCH", last_thread.as_str())
+                    .match_query(api_version())
+                    .match_body(Matcher::Json(serde_json::json!({"status": status})))
+                    .match_header("Authorization", BASIC_AUTH)
+                    .with_status(200)
+                    .with_body("{}")
+                    .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+                    .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+                    .expect(usize::from(
+                        !test_params.fail_dismissal && !test_params.fail_posting,
+                    ))
+                    .create(),
+            );
+        }
+    }
+
+    let opts = ThreadCommentOptions {
+        policy: test_params.comment_policy,
+        comment,
+        kind: test_params.comment_kind,
+        marker: MARKER.to_string(),
+        no_lgtm: test_params.no_lgtm,
+        ..Default::default()
+    };
+    client.start_log_group("posting comment");
+    let result = client.post_thread_comment(opts).await;
+    client.end_log_group("posting comment");
+    if test_params.bad_existing_comments {
+        assert!(matches!(result, Err(RestClientError::Json { .. })));
+    } else if test_params.fail_dismissal || test_params.fail_posting {
+        let expected_body = if test_params.fail_dismissal {
+            DELETION_DENIED
+        } else {
+            POSTING_DENIED
+        };
+        assert!(
+            matches!(
+                &result,
+                Err(RestClientError::Http { status, body, .. })
+                    if *status == StatusCode::FORBIDDEN && body == expected_body
+            ),
+            "Expected Http error, got: {result:?}"
+        );
+    } else {
+        assert!(result.is_ok(), "Unexpected error: {result:?}");
+    }
+    for mock in mocks {
+        mock.assert();
+    }
+}
+
+#[tokio::test]
+async fn new_push() {
+    test_comment(&TestParams {
+        event_t: EventType::Push,
+        comment_policy: CommentPolicy::Anew,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn update_push() {
+    test_comment(&TestParams {
+        event_t: EventType::Push,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn new_pr() {
+    test_comment(&TestParams {
+        comment_policy: CommentPolicy::Anew,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn update_pr() {
+    test_comment(&TestParams::default()).await;
+}
+
+#[tokio::test]
+async fn update_pr_lgtm() {
+    test_comment(&TestParams {
+        comment_kind: CommentKind::Lgtm,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn new_pr_no_lgtm() {
+    test_comment(&TestParams {
+        comment_policy: CommentPolicy::Anew,
+        no_lgtm: true,
+        comment_kind: CommentKind::Lgtm,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn update_pr_no_lgtm() {
+    test_comment(&TestParams {
+        comment_kind: CommentKind::Lgtm,
+        no_lgtm: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn fail_get_existing_comments() {
+    test_comment(&TestParams {
+        fail_get_existing_comments: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn fail_dismissal() {
+    test_comment(&TestParams {
+        fail_dismissal: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn fail_posting() {
+    test_comment(&TestParams {
+        fail_posting: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn bad_existing_comments() {
+    test_comment(&TestParams {
+        bad_existing_comments: true,
+        comment_kind: CommentKind::Lgtm,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn bad_pr_id() {
+    test_comment(&TestParams {
+        bad_pr_id: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn no_repo_env_var() {
+    test_comment(&TestParams {
+        no_repo_env_var: true,
+        ..Default::default()
+    })
+    .await;
+}
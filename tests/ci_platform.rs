@@ -0,0 +1,205 @@
+#![cfg(all(
+    feature = "github",
+    feature = "gitea",
+    feature = "gitlab",
+    feature = "azure-devops"
+))]
+use git_bot_feedback::{
+    RestClientError,
+    client::{Capabilities, from_env, init_client},
+};
+use std::env;
+
+mod common;
+use common::logger_init;
+
+const CLEARED_ENV_VARS: [&str; 9] = [
+    "GITHUB_ACTIONS",
+    "GITEA_ACTIONS",
+    "FORGEJO_ACTIONS",
+    "GITEA_WORK_DIR",
+    "GITLAB_CI",
+    "TF_BUILD",
+    "BITBUCKET_BUILD_NUMBER",
+    "GITHUB_EVENT_NAME",
+    "GIT_BOT_FEEDBACK_DIR",
+];
+
+/// Set only the given CI environment variables (along with those needed to construct a client).
+fn setup(vars: &[(&str, &str)]) {
+    logger_init();
+    unsafe {
+        for name in CLEARED_ENV_VARS {
+            env::remove_var(name);
+        }
+        env::set_var("GITHUB_REPOSITORY", "2bndy5/git-bot-feedback");
+        env::set_var("GITHUB_SHA", "DEADBEEF");
+        env::set_var("GITEA_REPOSITORY", "2bndy5/git-bot-feedback");
+        env::set_var("GITEA_SHA", "DEADBEEF");
+        env::set_var("GITEA_API_URL", "https://gitea.com");
+        env::set_var("GITEA_EVENT_NAME", "push");
+        env::set_var("CI_API_V4_URL", "https://gitlab.com/api/v4");
+        env::set_var("CI_PROJECT_ID", "42");
+        env::set_var("CI_COMMIT_SHA", "DEADBEEF");
+        env::set_var("SYSTEM_COLLECTIONURI", "https://dev.azure.com/my-org/");
+        env::set_var("SYSTEM_TEAMPROJECT", "my-project");
+        env::set_var("BUILD_REPOSITORY_NAME", "my-repo");
+        for (name, value) in vars {
+            env::set_var(name, value);
+        }
+    }
+}
+
+#[test]
+fn detect_github() {
+    setup(&[("GITHUB_ACTIONS", "true")]);
+    assert_eq!(from_env().unwrap().client_kind(), "github");
+}
+
+#[test]
+fn detect_gitea() {
+    setup(&[("GITEA_ACTIONS", "true")]);
+    assert_eq!(from_env().unwrap().client_kind(), "gitea");
+}
+
+#[test]
+fn detect_forgejo() {
+    setup(&[("FORGEJO_ACTIONS", "true")]);
+    assert_eq!(from_env().unwrap().client_kind(), "gitea");
+}
+
+#[test]
+fn detect_gitea_work_dir() {
+    setup(&[("GITEA_WORK_DIR", "/var/lib/gitea")]);
+    assert_eq!(from_env().unwrap().client_kind(), "gitea");
+}
+
+#[test]
+fn detect_gitlab() {
+    setup(&[("GITLAB_CI", "true")]);
+    assert_eq!(from_env().unwrap().client_kind(), "gitlab");
+}
+
+#[test]
+fn detect_azure() {
+    setup(&[("TF_BUILD", "True")]);
+    assert_eq!(from_env().unwrap().client_kind(), "azure-devops");
+}
+
+#[test]
+fn github_capabilities() {
+    setup(&[("GITHUB_ACTIONS", "true")]);
+    assert_eq!(
+        from_env().unwrap().capabilities(),
+        Capabilities {
+            thread_comments_on_push: true,
+            pr_reviews: true,
+            file_annotations: true,
+            step_summary: true,
+            output_variables: true,
+            commit_statuses: false,
+        }
+    );
+}
+
+#[test]
+fn gitea_capabilities() {
+    setup(&[("GITEA_ACTIONS", "true")]);
+    assert_eq!(
+        from_env().unwrap().capabilities(),
+        Capabilities {
+            thread_comments_on_push: false,
+            pr_reviews: true,
+            file_annotations: true,
+            step_summary: true,
+            output_variables: true,
+            commit_statuses: true,
+        }
+    );
+}
+
+#[test]
+fn gitlab_capabilities() {
+    setup(&[("GITLAB_CI", "true")]);
+    assert_eq!(
+        from_env().unwrap().capabilities(),
+        Capabilities {
+            output_variables: true,
+            pr_reviews: true,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn azure_capabilities() {
+    setup(&[("TF_BUILD", "True")]);
+    assert_eq!(
+        from_env().unwrap().capabilities(),
+        Capabilities {
+            file_annotations: true,
+            output_variables: true,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn gitea_over_github() {
+    // Gitea also sets GITHUB_* variables for compatibility
+    setup(&[("GITHUB_ACTIONS", "true"), ("GITEA_ACTIONS", "true")]);
+    assert_eq!(from_env().unwrap().client_kind(), "gitea");
+}
+
+#[test]
+fn unsupported_bitbucket() {
+    setup(&[("BITBUCKET_BUILD_NUMBER", "42")]);
+    let err = from_env().err().unwrap();
+    assert!(
+        matches!(
+            &err,
+            RestClientError::UnsupportedCi { detected: Some(platform), .. } if platform == "Bitbucket Pipelines"
+        ),
+        "Expected UnsupportedCi error, got: {err:?}"
+    );
+    assert!(
+        err.to_string()
+            .contains("(Bitbucket Pipelines is not supported)")
+    );
+}
+
+#[test]
+fn no_ci_detected() {
+    setup(&[("GITHUB_ACTIONS", "false")]);
+    let err = from_env().err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "No supported CI platform detected; checked env vars: FORGEJO_ACTIONS, GITEA_ACTIONS, \
+         GITEA_WORK_DIR, GITHUB_ACTIONS, GITLAB_CI, TF_BUILD, BITBUCKET_BUILD_NUMBER"
+    );
+    // init_client() falls back to a LocalClient instead
+    assert_eq!(init_client().unwrap().client_kind(), "local");
+}
+
+#[test]
+fn local_feedback_dir() {
+    setup(&[("GIT_BOT_FEEDBACK_DIR", "feedback")]);
+    let client = from_env().unwrap();
+    assert_eq!(client.client_kind(), "local");
+    assert!(client.capabilities().thread_comments_on_push);
+}
+
+#[test]
+fn local_feedback_dir_in_unsupported_ci() {
+    setup(&[
+        ("GIT_BOT_FEEDBACK_DIR", "feedback"),
+        ("BITBUCKET_BUILD_NUMBER", "42"),
+    ]);
+    assert!(matches!(
+        from_env().err().unwrap(),
+        RestClientError::UnsupportedCi {
+            detected: Some(_),
+            ..
+        }
+    ));
+}
@@ -1,3 +1,10 @@
+use std::{cell::RefCell, env};
+
+thread_local! {
+    /// The log messages captured on the current thread (if capturing).
+    static CAPTURED_LOGS: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
 struct Logger;
 impl log::Log for Logger {
     fn enabled(&self, metadata: &log::Metadata) -> bool {
@@ -5,6 +12,11 @@ impl log::Log for Logger {
     }
 
     fn log(&self, record: &log::Record) {
+        CAPTURED_LOGS.with_borrow_mut(|logs| {
+            if let Some(logs) = logs {
+                logs.push(record.args().to_string());
+            }
+        });
         if record.target() == "CI_LOG_GROUPING" {
             println!("{}", record.args());
         } else {
@@ -24,6 +36,20 @@ pub fn logger_init() {
     let _ = log::set_logger(&Logger);
 }
 
+/// Start capturing the log messages emitted on the current thread.
+///
+/// Captured messages are still printed.
+#[allow(dead_code, reason = "This is used by some tests but not all of them")]
+pub fn capture_logs() {
+    CAPTURED_LOGS.set(Some(Vec::new()));
+}
+
+/// Stop capturing log messages and return the messages captured on the current thread.
+#[allow(dead_code, reason = "This is used by some tests but not all of them")]
+pub fn take_captured_logs() -> Vec<String> {
+    CAPTURED_LOGS.take().unwrap_or_default()
+}
+
 #[allow(dead_code, reason = "This is used by most tests but not all of them")]
 #[derive(Debug, PartialEq, Default)]
 pub enum EventType {
@@ -40,3 +66,41 @@ impl std::fmt::Display for EventType {
         }
     }
 }
+
+/// Set the environment variables of a GitHub Actions `push` event
+/// whose REST API is served at the given `server_url`.
+///
+/// Tests may set other variables (or override these) afterward.
+#[allow(dead_code, reason = "This is used by some tests but not all of them")]
+pub fn github_env(server_url: &str) {
+    unsafe {
+        env::set_var("GITHUB_ACTIONS", "true");
+        env::remove_var("GITEA_ACTIONS");
+        env::set_var("GITHUB_REPOSITORY", "2bndy5/git-bot-feedback");
+        env::set_var("GITHUB_SHA", "DEADBEEF");
+        env::set_var("GITHUB_EVENT_NAME", "push");
+        env::remove_var("GITHUB_EVENT_PATH");
+        env::set_var("GITHUB_TOKEN", "123456");
+        env::set_var("CI", "true");
+        env::set_var("GITHUB_API_URL", server_url);
+    }
+}
+
+/// Set the environment variables of a Gitea Actions `push` event
+/// whose REST API is served at the given `server_url`.
+///
+/// Tests may set other variables (or override these) afterward.
+#[allow(dead_code, reason = "This is used by some tests but not all of them")]
+pub fn gitea_env(server_url: &str) {
+    unsafe {
+        env::set_var("GITHUB_ACTIONS", "true");
+        env::set_var("GITEA_ACTIONS", "true");
+        env::set_var("GITEA_REPOSITORY", "2bndy5/git-bot-feedback");
+        env::set_var("GITEA_SHA", "DEADBEEF");
+        env::set_var("GITEA_EVENT_NAME", "push");
+        env::remove_var("GITEA_EVENT_PATH");
+        env::set_var("GITEA_TOKEN", "123456");
+        env::set_var("CI", "true");
+        env::set_var("GITEA_API_URL", server_url);
+    }
+}
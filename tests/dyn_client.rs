@@ -0,0 +1,147 @@
+#![cfg(all(feature = "github", feature = "gitea"))]
+//! Tests that hold "whatever client matches this CI" in a single field.
+use git_bot_feedback::prelude::*;
+use mockito::{Matcher, Server};
+use std::{env, fs};
+use tempfile::NamedTempFile;
+
+mod common;
+use common::logger_init;
+
+const REPO: &str = "2bndy5/git-bot-feedback";
+const SHA: &str = "DEADBEEF";
+
+/// A downstream app that uses the client matching the CI environment.
+struct App {
+    client: Box<dyn RestApiClient + Send + Sync>,
+}
+
+impl App {
+    async fn report(&self, comment: &str, output: &OutputVariable) -> Result<(), RestClientError> {
+        self.client
+            .post_thread_comment(ThreadCommentOptions {
+                comment: comment.to_string(),
+                ..Default::default()
+            })
+            .await?;
+        self.client
+            .write_output_variables(std::slice::from_ref(output))
+    }
+}
+
+fn output_var() -> OutputVariable {
+    OutputVariable {
+        name: "STEP_OUTPUT_VAR".to_string(),
+        value: "some data".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn github_dyn_client() {
+    let mut server = Server::new_async().await;
+    let out_file = NamedTempFile::new().unwrap();
+    unsafe {
+        env::set_var("GITHUB_ACTIONS", "true");
+        env::remove_var("GITEA_ACTIONS");
+        env::set_var("GITHUB_REPOSITORY", REPO);
+        env::set_var("GITHUB_SHA", SHA);
+        env::set_var("GITHUB_EVENT_NAME", "push");
+        env::set_var("GITHUB_TOKEN", "123456");
+        env::set_var("GITHUB_OUTPUT", out_file.path());
+        env::set_var("CI", "true");
+        env::set_var("GITHUB_API_URL", server.url());
+    }
+    logger_init();
+
+    let comments_path = format!("/repos/{REPO}/commits/{SHA}/comments");
+    let existing = server
+        .mock("GET", comments_path.as_str())
+        .match_query(Matcher::Any)
+        .with_body("[]")
+        .create();
+    let posted = server
+        .mock("POST", comments_path.as_str())
+        .match_body(Matcher::Regex("Hello".to_string()))
+        .with_status(201)
+        .create();
+
+    let app = App {
+        client: init_client().unwrap(),
+    };
+    assert_eq!(app.client.client_kind(), "github");
+    app.report("Hello", &output_var()).await.unwrap();
+    existing.assert();
+    posted.assert();
+    assert_eq!(
+        fs::read_to_string(out_file.path()).unwrap(),
+        "STEP_OUTPUT_VAR=some data\n"
+    );
+}
+
+#[tokio::test]
+async fn gitea_dyn_client() {
+    let server = Server::new_async().await;
+    let out_file = NamedTempFile::new().unwrap();
+    unsafe {
+        env::set_var("GITHUB_ACTIONS", "true");
+        env::set_var("GITEA_ACTIONS", "true");
+        env::set_var("GITEA_REPOSITORY", REPO);
+        env::set_var("GITEA_SHA", SHA);
+        env::set_var("GITEA_EVENT_NAME", "push");
+        env::set_var("GITEA_OUTPUT", out_file.path());
+        env::set_var("CI", "true");
+        env::set_var("GITEA_API_URL", server.url());
+    }
+    logger_init();
+
+    let app = App {
+        client: init_client().unwrap(),
+    };
+    assert_eq!(app.client.client_kind(), "gitea");
+    // Gitea only supports thread comments on PRs, so no request is made for a push event
+    app.report("Hello", &output_var()).await.unwrap();
+    assert_eq!(
+        fs::read_to_string(out_file.path()).unwrap(),
+        "STEP_OUTPUT_VAR=some data\n"
+    );
+}
+
+#[cfg(feature = "test-utils")]
+#[tokio::test]
+async fn mock_dyn_client() {
+    use git_bot_feedback::{CommitStatus, client::MockClient};
+
+    let mock = MockClient::new(false);
+    let app = App {
+        client: Box::new(mock.clone()),
+    };
+    assert_eq!(app.client.client_kind(), "mock");
+    app.report("Hello", &output_var()).await.unwrap();
+    let calls = mock.calls();
+    assert_eq!(calls.thread_comments.len(), 1);
+    assert_eq!(calls.thread_comments[0].comment, "Hello");
+    assert_eq!(calls.output_variables.len(), 1);
+    assert_eq!(calls.output_variables[0].name, "STEP_OUTPUT_VAR");
+
+    // a queued error is returned by the next call only
+    mock.queue_thread_comment_result(Err(RestClientError::RateLimitNoReset));
+    let err = app.report("Hello again", &output_var()).await.unwrap_err();
+    assert!(matches!(err, RestClientError::RateLimitNoReset));
+    app.report("Hello again", &output_var()).await.unwrap();
+    let calls = mock.calls();
+    assert_eq!(calls.thread_comments.len(), 3);
+    // output variables are not written when posting the comment failed
+    assert_eq!(calls.output_variables.len(), 2);
+
+    app.client
+        .post_commit_status(&CommitStatus {
+            context: "lint".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(mock.calls().commit_statuses[0].context, "lint");
+
+    mock.reset();
+    assert!(mock.calls().thread_comments.is_empty());
+}
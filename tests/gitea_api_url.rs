@@ -0,0 +1,96 @@
+#![cfg(feature = "gitea")]
+use std::env;
+
+use git_bot_feedback::{RestClientError, client::GiteaApiClient};
+
+mod common;
+use common::logger_init;
+
+const URL_VARS: [&str; 6] = [
+    "FORGEJO_API_URL",
+    "GITEA_API_URL",
+    "GITHUB_API_URL",
+    "FORGEJO_SERVER_URL",
+    "GITEA_SERVER_URL",
+    "GITHUB_SERVER_URL",
+];
+
+/// Set only the given URL environment variables (along with those needed to construct a client).
+fn setup(vars: &[(&str, &str)]) -> Result<GiteaApiClient, RestClientError> {
+    logger_init();
+    unsafe {
+        for name in URL_VARS {
+            env::remove_var(name);
+        }
+        env::set_var("GITEA_REPOSITORY", "2bndy5/git-bot-feedback");
+        env::set_var("GITEA_SHA", "DEADBEEF");
+        env::set_var("GITEA_EVENT_NAME", "push");
+        for (name, value) in vars {
+            env::set_var(name, value);
+        }
+    }
+    GiteaApiClient::new()
+}
+
+#[test]
+fn gitea_api_url() {
+    let client = setup(&[
+        ("GITEA_API_URL", "https://gitea.com/api/v1"),
+        ("GITHUB_API_URL", "https://other.com/api/v1"),
+        ("GITEA_SERVER_URL", "https://other.com"),
+    ])
+    .unwrap();
+    assert_eq!(client.api_url().as_str(), "https://gitea.com/api/v1/");
+}
+
+#[test]
+fn github_api_url() {
+    let client = setup(&[
+        ("GITHUB_API_URL", "https://gitea.com/api/v1/"),
+        ("GITEA_SERVER_URL", "https://other.com"),
+    ])
+    .unwrap();
+    assert_eq!(client.api_url().as_str(), "https://gitea.com/api/v1/");
+}
+
+#[test]
+fn gitea_server_url() {
+    let client = setup(&[
+        ("GITEA_SERVER_URL", "https://gitea.com"),
+        ("GITHUB_SERVER_URL", "https://other.com"),
+    ])
+    .unwrap();
+    assert_eq!(client.api_url().as_str(), "https://gitea.com/api/v1/");
+}
+
+#[test]
+fn github_server_url() {
+    let client = setup(&[("GITHUB_SERVER_URL", "https://gitea.com/")]).unwrap();
+    assert_eq!(client.api_url().as_str(), "https://gitea.com/api/v1/");
+    assert_eq!(
+        client.api_url().join("repos/o/r").unwrap().as_str(),
+        "https://gitea.com/api/v1/repos/o/r"
+    );
+}
+
+#[test]
+fn no_url() {
+    let err = setup(&[]).err().unwrap();
+    assert!(
+        matches!(&err, RestClientError::MissingEnvVars { checked } if checked.len() == URL_VARS.len()),
+        "Expected MissingEnvVars error, got: {err:?}"
+    );
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "None of the following env vars are set: {}",
+            URL_VARS.join(", ")
+        )
+    );
+}
+
+#[test]
+fn invalid_url() {
+    let err = setup(&[("GITEA_API_URL", "not a url")]).err().unwrap();
+    assert!(err.to_string().contains("parse GITEA_API_URL"), "{err}");
+}
@@ -0,0 +1,160 @@
+#![cfg(feature = "gitea")]
+use std::env;
+
+use git_bot_feedback::{
+    CommitStatus, MAX_STATUS_DESCRIPTION, RestApiClient, RestClientError, StatusState,
+    client::GiteaApiClient,
+};
+use mockito::{Matcher, Server};
+use serde_json::json;
+
+mod common;
+use common::{gitea_env, logger_init};
+
+const REPO: &str = "2bndy5/git-bot-feedback";
+const SHA: &str = "DEADBEEF";
+const HEAD_SHA: &str = "a1b2c3d4e5f60718293a4b5c6d7e8f9012345678";
+const PR_PAYLOAD: &str = "tests/assets/event_payload/gitea/pull_request.json";
+
+/// Instantiate a client for a `push` event, or a `pull_request` event if `pr_event` is `true`.
+fn setup(server: &Server, pr_event: bool) -> GiteaApiClient {
+    logger_init();
+    gitea_env(&server.url());
+    if pr_event {
+        unsafe {
+            env::set_var("GITEA_EVENT_NAME", "pull_request");
+            env::set_var("GITEA_EVENT_PATH", PR_PAYLOAD);
+        }
+    }
+    GiteaApiClient::new().unwrap()
+}
+
+#[tokio::test]
+async fn push_status() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server, false);
+    assert!(client.capabilities().commit_statuses);
+    let mock = server
+        .mock(
+            "POST",
+            format!("/api/v1/repos/{REPO}/statuses/{SHA}").as_str(),
+        )
+        .match_header("Authorization", "token 123456")
+        .match_body(Matcher::Json(json!({
+            "state": "failure",
+            "context": "lint/clang-tidy",
+            "description": "2 concerns",
+            "target_url": "https://gitea.com/2bndy5/git-bot-feedback/actions/runs/1",
+        })))
+        .with_status(201)
+        .expect(1)
+        .create();
+    client
+        .post_commit_status(&CommitStatus {
+            state: StatusState::Failure,
+            context: "lint/clang-tidy".to_string(),
+            description: Some("2 concerns".to_string()),
+            target_url: Some(
+                "https://gitea.com/2bndy5/git-bot-feedback/actions/runs/1".to_string(),
+            ),
+        })
+        .await
+        .unwrap();
+    mock.assert();
+}
+
+#[tokio::test]
+async fn pr_head_status() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server, true);
+    let mock = server
+        .mock(
+            "POST",
+            format!("/api/v1/repos/{REPO}/statuses/{HEAD_SHA}").as_str(),
+        )
+        .match_body(Matcher::Json(json!({
+            "state": "warning",
+            "context": "lint",
+        })))
+        .with_status(201)
+        .expect(1)
+        .create();
+    client
+        .post_commit_status(&CommitStatus {
+            state: StatusState::Warning,
+            context: "lint".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    mock.assert();
+}
+
+#[tokio::test]
+async fn truncated_description() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server, false);
+    let description = "a".repeat(MAX_STATUS_DESCRIPTION + 10);
+    let expected = format!("{}…", "a".repeat(MAX_STATUS_DESCRIPTION - 1));
+    let mock = server
+        .mock(
+            "POST",
+            format!("/api/v1/repos/{REPO}/statuses/{SHA}").as_str(),
+        )
+        .match_body(Matcher::PartialJson(json!({"description": expected})))
+        .with_status(201)
+        .expect(1)
+        .create();
+    client
+        .post_commit_status(&CommitStatus {
+            state: StatusState::Success,
+            context: "lint".to_string(),
+            description: Some(description),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    mock.assert();
+}
+
+#[tokio::test]
+async fn invalid_target_url() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server, false);
+    let mock = server.mock("POST", Matcher::Any).expect(0).create();
+    let err = client
+        .post_commit_status(&CommitStatus {
+            context: "lint".to_string(),
+            target_url: Some("javascript:alert(1)".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RestClientError::InvalidCommitStatus(_)));
+    mock.assert();
+}
+
+#[tokio::test]
+async fn server_error() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server, false);
+    let mock = server
+        .mock(
+            "POST",
+            format!("/api/v1/repos/{REPO}/statuses/{SHA}").as_str(),
+        )
+        .with_status(422)
+        .with_body("invalid state")
+        .expect(1)
+        .create();
+    let err = client
+        .post_commit_status(&CommitStatus {
+            context: "lint".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.status().map(|s| s.as_u16()), Some(422));
+    assert!(err.to_string().contains("post commit status"));
+    mock.assert();
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "gitea")]
+use std::env;
+
+use git_bot_feedback::{RestApiClient, ThreadCommentOptions, client::GiteaApiClient};
+use mockito::{Matcher, Server};
+
+mod common;
+use common::logger_init;
+
+const REPO: &str = "2bndy5/git-bot-feedback";
+const TOKEN: &str = "123456";
+const ASSETS_PATH: &str = "tests/assets/event_payload/gitea/";
+
+/// Instantiate a client for the given `event_name` with the event payload from the `fixture`.
+fn setup(server: &Server, event_name: &str, fixture: &str) -> GiteaApiClient {
+    logger_init();
+    unsafe {
+        env::set_var("GITEA_API_URL", server.url());
+        env::set_var("GITEA_REPOSITORY", REPO);
+        env::set_var("GITEA_SHA", "DEADBEEF");
+        env::set_var("GITEA_TOKEN", TOKEN);
+        env::set_var("GITEA_EVENT_NAME", event_name);
+        env::set_var("GITEA_EVENT_PATH", format!("{ASSETS_PATH}{fixture}.json"));
+    }
+    GiteaApiClient::new().unwrap()
+}
+
+/// Assert that a thread comment is posted on the PR with the given `pr_number`.
+async fn assert_pr_comment(event_name: &str, fixture: &str, pr_number: u64) {
+    let mut server = Server::new_async().await;
+    let client = setup(&server, event_name, fixture);
+    assert!(client.is_pr_event());
+
+    let comments_path = format!("/api/v1/repos/{REPO}/issues/{pr_number}/comments");
+    let existing = server
+        .mock("GET", comments_path.as_str())
+        .match_query(Matcher::Any)
+        .with_body("[]")
+        .expect(1)
+        .create();
+    let posted = server
+        .mock("POST", comments_path.as_str())
+        .match_header("Authorization", format!("token {TOKEN}").as_str())
+        .match_body(Matcher::Regex("Attention".to_string()))
+        .with_status(201)
+        .expect(1)
+        .create();
+    client
+        .post_thread_comment(ThreadCommentOptions {
+            comment: "Attention".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    existing.assert();
+    posted.assert();
+}
+
+#[tokio::test]
+async fn pull_request() {
+    assert_pr_comment("pull_request", "pull_request", 7).await;
+}
+
+#[tokio::test]
+async fn pull_request_target() {
+    assert_pr_comment("pull_request_target", "pull_request_target", 8).await;
+}
+
+#[tokio::test]
+async fn pull_request_sync() {
+    assert_pr_comment("pull_request_sync", "pull_request_sync", 9).await;
+}
+
+#[tokio::test]
+async fn issue_comment_on_pr() {
+    assert_pr_comment("issue_comment", "issue_comment", 7).await;
+}
+
+#[tokio::test]
+async fn pull_request_comment() {
+    assert_pr_comment("pull_request_comment", "issue_comment", 7).await;
+}
+
+#[tokio::test]
+async fn issue_comment_on_issue() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server, "issue_comment", "issue_comment_issue");
+    assert!(!client.is_pr_event());
+    let requests = server.mock("POST", Matcher::Any).expect(0).create();
+    client
+        .post_thread_comment(ThreadCommentOptions {
+            comment: "Attention".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    requests.assert();
+}
+
+#[test]
+fn unknown_event() {
+    let server = Server::new();
+    // the payload is not read for events that cannot describe a Pull Request
+    let client = setup(&server, "workflow_dispatch", "not_a_file");
+    assert!(!client.is_pr_event());
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "gitea")]
+use std::{env, fs, io::Write};
+
+use git_bot_feedback::{RestApiClient, RestClientError, client::GiteaApiClient};
+use tempfile::NamedTempFile;
+
+mod common;
+use common::logger_init;
+
+/// Instantiate a client for a `pull_request` event with the given event `payload`.
+fn client_from_payload(payload: &str) -> Result<GiteaApiClient, RestClientError> {
+    logger_init();
+    let mut event_payload = NamedTempFile::new().unwrap();
+    event_payload.write_all(payload.as_bytes()).unwrap();
+    unsafe {
+        env::set_var("GITEA_API_URL", "https://gitea.com");
+        env::set_var("GITEA_REPOSITORY", "2bndy5/git-bot-feedback");
+        env::set_var("GITEA_SHA", "DEADBEEF");
+        env::set_var("GITEA_EVENT_NAME", "pull_request");
+        env::set_var("GITEA_EVENT_PATH", event_payload.path());
+    }
+    GiteaApiClient::new()
+}
+
+#[test]
+fn nested_number() {
+    let client = client_from_payload(
+        r#"{
+            "number": 42,
+            "pull_request": {"number": 42, "draft": false, "locked": false, "state": "open"}
+        }"#,
+    )
+    .unwrap();
+    assert!(client.is_pr_event());
+    assert!(!client.is_draft());
+    assert!(client.head_sha().is_none());
+}
+
+#[test]
+fn full_payload() {
+    let payload = fs::read_to_string("tests/assets/event_payload/gitea/pull_request.json").unwrap();
+    let client = client_from_payload(&payload).unwrap();
+    assert!(client.is_pr_event());
+    assert!(client.is_draft());
+    assert_eq!(
+        client.head_sha(),
+        Some("a1b2c3d4e5f60718293a4b5c6d7e8f9012345678")
+    );
+    assert_eq!(client.head_ref(), Some("feature"));
+    assert_eq!(client.base_ref(), Some("main"));
+}
+
+#[test]
+fn missing_number() {
+    let err = client_from_payload(
+        r#"{"pull_request": {"draft": false, "locked": false, "state": "open"}}"#,
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(err, RestClientError::Json { .. }));
+    assert!(err.to_string().contains("deserialize event payload"));
+}
+
+#[test]
+fn non_numeric_number() {
+    let err = client_from_payload(
+        r#"{"pull_request": {"number": "42", "draft": false, "locked": false, "state": "open"}}"#,
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(err, RestClientError::Json { .. }));
+}
+
+#[test]
+fn missing_pull_request() {
+    let err = client_from_payload(r#"{"number": 42}"#).err().unwrap();
+    assert!(matches!(err, RestClientError::Json { .. }));
+}
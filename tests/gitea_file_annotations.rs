@@ -0,0 +1,114 @@
+#![cfg(feature = "gitea")]
+use git_bot_feedback::{
+    AnnotationLevel, AnnotationOverflow, FileAnnotation,
+    client::{CommandSink, init_client},
+};
+use mockito::Server;
+use std::{
+    env,
+    sync::{Arc, Mutex},
+};
+mod common;
+use common::logger_init;
+
+#[derive(Debug, Default)]
+struct TestParams {
+    empty_array: bool,
+    many_warnings: bool,
+}
+
+const REPO: &str = "2bndy5/git-bot-feedback";
+const SHA: &str = "DEADBEEF";
+
+async fn write_annotations(test_params: TestParams) {
+    let server = Server::new_async().await;
+    unsafe {
+        env::set_var("GITEA_API_URL", server.url());
+        env::set_var("GITHUB_ACTIONS", "true");
+        env::set_var("GITEA_ACTIONS", "true");
+        env::set_var("GITEA_REPOSITORY", REPO);
+        env::set_var("GITEA_SHA", SHA);
+        env::set_var("CI", "true");
+        env::set_var("GITEA_EVENT_NAME", "push");
+    }
+
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    let mut client = init_client().unwrap();
+    assert_eq!(client.client_kind(), "gitea");
+    let output = Arc::new(Mutex::new(Vec::<u8>::new()));
+    client.set_command_sink(CommandSink::Writer(output.clone()));
+
+    let annotations = if test_params.empty_array {
+        vec![]
+    } else if test_params.many_warnings {
+        (0..25)
+            .map(|i| FileAnnotation {
+                severity: AnnotationLevel::Warning,
+                message: format!("Test warning {i}"),
+                ..Default::default()
+            })
+            .collect()
+    } else {
+        vec![
+            FileAnnotation {
+                message: "Test annotation".to_string(),
+                ..Default::default()
+            },
+            FileAnnotation {
+                severity: AnnotationLevel::Error,
+                path: "src/lib.rs".to_string(),
+                start_line: Some(10),
+                start_column: Some(5),
+                end_line: Some(20),
+                end_column: Some(15),
+                title: Some("Error Title".to_string()),
+                message: "Test error".to_string(),
+            },
+        ]
+    };
+    let written = client
+        .write_file_annotations(&annotations, AnnotationOverflow::Error)
+        .unwrap();
+    // there is no known limit to the number of annotations on Gitea
+    assert_eq!(written, annotations.len());
+
+    let expected = if test_params.empty_array {
+        vec![]
+    } else if test_params.many_warnings {
+        (0..25)
+            .map(|i| format!("::warning::Test warning {i}"))
+            .collect()
+    } else {
+        vec![
+            "::notice::Test annotation".to_string(),
+            // end positions are not supported by act_runner
+            "::error file=src/lib.rs,line=10,col=5,title=Error Title::Test error".to_string(),
+        ]
+    };
+    let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+    assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+}
+
+#[tokio::test]
+async fn some_annotations() {
+    write_annotations(TestParams::default()).await;
+}
+
+#[tokio::test]
+async fn empty_annotations() {
+    write_annotations(TestParams {
+        empty_array: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn many_annotations() {
+    write_annotations(TestParams {
+        many_warnings: true,
+        ..Default::default()
+    })
+    .await;
+}
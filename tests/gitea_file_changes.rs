@@ -2,15 +2,28 @@
 use chrono::Utc;
 mod common;
 use common::{EventType, logger_init};
-use mockito::Server;
+use mockito::{Matcher, Server};
 use tempfile::{NamedTempFile, TempDir};
 
 use git_bot_feedback::{
     DiffHunkHeader, FileFilter, LinesChangedOnly, RestApiClient, RestClientError,
-    client::GiteaApiClient, error::DiffError,
+    client::{DEFAULT_CHANGED_FILES_PER_PAGE, GiteaApiClient},
+    error::DiffError,
 };
 use std::{env, io::Write, path::Path};
 
+/// How the server responds to requests for a PR's list of changed files.
+#[derive(Default, PartialEq)]
+enum PrFiles {
+    /// The endpoint does not exist (as in older versions of Gitea).
+    #[default]
+    Unsupported,
+    /// The files are listed with their patches.
+    Paginated,
+    /// The files are listed without their patches.
+    NoPatches,
+}
+
 #[derive(Default)]
 struct TestParams {
     event_t: EventType,
@@ -18,6 +31,8 @@ struct TestParams {
     fail_serde_event_payload: bool,
     no_event_payload: bool,
     fail_request: bool,
+    pr_files: PrFiles,
+    per_page: Option<u32>,
 }
 
 const REPO: &str = "2bndy5/git-bot-feedback";
@@ -30,6 +45,7 @@ const RESET_RATE_LIMIT_HEADER: &str = "x-ratelimit-reset";
 const REMAINING_RATE_LIMIT_HEADER: &str = "x-ratelimit-remaining";
 const MALFORMED_RESPONSE_PAYLOAD: &str = r#"{"message":"Resource not accessible by integration"}"#;
 const ASSET_PATH: &str = "tests/assets/file_changes/gitea/patch.diff";
+const PR_FILES_ASSET_PATH: &str = "tests/assets/file_changes/gitea/pr_files_pg";
 
 async fn get_paginated_changes(lib_root: &Path, test_params: &TestParams) {
     let tmp = TempDir::new().expect("Failed to create a temp dir for test");
@@ -80,9 +96,62 @@ async fn get_paginated_changes(lib_root: &Path, test_params: &TestParams) {
         assert!(gt_client.is_err());
         return;
     }
-    let client = gt_client.unwrap();
+    let mut client = gt_client.unwrap();
+    assert_eq!(
+        client.changed_files_per_page(),
+        Some(DEFAULT_CHANGED_FILES_PER_PAGE)
+    );
+    if let Some(per_page) = test_params.per_page {
+        client.set_changed_files_per_page(per_page);
+    }
+    let per_page = test_params
+        .per_page
+        .unwrap_or(DEFAULT_CHANGED_FILES_PER_PAGE)
+        .to_string();
 
     let mut mocks = vec![];
+    let uses_diff =
+        test_params.event_t == EventType::Push || test_params.pr_files != PrFiles::Paginated;
+    if test_params.event_t == EventType::PullRequest {
+        let files_end_point = format!("/api/v1/repos/{REPO}/pulls/{PR}/files");
+        let pages = if test_params.pr_files == PrFiles::Paginated {
+            2
+        } else {
+            1
+        };
+        for page in 1..=pages {
+            let mut mock = server
+                .mock("GET", files_end_point.as_str())
+                .match_query(Matcher::AllOf(vec![
+                    Matcher::UrlEncoded("page".to_string(), page.to_string()),
+                    Matcher::UrlEncoded("limit".to_string(), per_page.clone()),
+                ]))
+                .match_header("Authorization", format!("token {TOKEN}").as_str())
+                .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+                .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str());
+            mock = match test_params.pr_files {
+                PrFiles::Unsupported => mock
+                    .with_status(404)
+                    .with_body(r#"{"message":"Not Found"}"#),
+                PrFiles::NoPatches => mock.with_body(r#"[{"filename":"src/lib.rs","changes":3}]"#),
+                PrFiles::Paginated => {
+                    if page < pages {
+                        let link = format!(
+                            "<{}{files_end_point}?page={}&limit={per_page}>; rel=\"next\"",
+                            server.url(),
+                            page + 1
+                        );
+                        mock = mock.with_header("link", link.as_str());
+                    }
+                    mock.with_body_from_file(format!(
+                        "{}/{PR_FILES_ASSET_PATH}{page}.json",
+                        lib_root.to_str().unwrap()
+                    ))
+                }
+            };
+            mocks.push(mock.create());
+        }
+    }
     let diff_end_point = format!(
         "/api/v1/repos/{REPO}/{}.diff",
         if EventType::PullRequest == test_params.event_t {
@@ -104,7 +173,7 @@ async fn get_paginated_changes(lib_root: &Path, test_params: &TestParams) {
     } else {
         mock = mock.with_body_from_file(asset_path);
     }
-    mocks.push(mock.create());
+    mocks.push(mock.expect(if uses_diff { 1 } else { 0 }).create());
 
     let log_scope = if test_params.event_t == EventType::Push {
         Some("push")
@@ -124,10 +193,15 @@ async fn get_paginated_changes(lib_root: &Path, test_params: &TestParams) {
                     "Expected Request error, got: {e:?}"
                 );
             } else if test_params.fail_serde_diff {
+                assert!(
+                    e.to_string()
+                        .starts_with("Failed to get list of changed files: ")
+                );
                 assert!(
                     matches!(
                         e,
-                        RestClientError::DiffError(DiffError::MalformedDiffError(_))
+                        RestClientError::WithContext { ref source, .. }
+                            if matches!(**source, RestClientError::DiffError(DiffError::MalformedDiffError(_)))
                     ),
                     "Expected DiffError::MalformedDiffError, got: {e:?}"
                 );
@@ -240,3 +314,34 @@ async fn fail_request() {
     })
     .await
 }
+
+#[tokio::test]
+async fn get_pr_files_json() {
+    test_get_changes(&TestParams {
+        event_t: EventType::PullRequest,
+        pr_files: PrFiles::Paginated,
+        ..Default::default()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn get_pr_files_without_patches() {
+    test_get_changes(&TestParams {
+        event_t: EventType::PullRequest,
+        pr_files: PrFiles::NoPatches,
+        ..Default::default()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn get_pr_files_json_custom_page_size() {
+    test_get_changes(&TestParams {
+        event_t: EventType::PullRequest,
+        pr_files: PrFiles::Paginated,
+        per_page: Some(50),
+        ..Default::default()
+    })
+    .await
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "gitea")]
+
+use git_bot_feedback::{RestApiClient, RestClientError, client::GiteaApiClient};
+use mockito::Server;
+
+mod common;
+use common::{gitea_env, logger_init};
+
+const REPO: &str = "2bndy5/git-bot-feedback";
+const SHA: &str = "DEADBEEF";
+const CONTENT: &str = "# Notes\n\nSome notes.\n";
+
+fn setup(server: &Server) -> GiteaApiClient {
+    logger_init();
+    gitea_env(&server.url());
+    GiteaApiClient::new().unwrap()
+}
+
+/// Mock the request for the raw content of `docs/my notes.md` at the given `reference`.
+fn mock_content(server: &mut Server, reference: &str) -> mockito::Mock {
+    server
+        .mock(
+            "GET",
+            format!("/api/v1/repos/{REPO}/raw/{reference}/docs/my%20notes.md").as_str(),
+        )
+        .match_header("Authorization", "token 123456")
+        .expect(1)
+}
+
+#[tokio::test]
+async fn content_at_event_sha() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server);
+    let mock = mock_content(&mut server, SHA).with_body(CONTENT).create();
+    let content = client
+        .get_file_content("docs/my notes.md", None)
+        .await
+        .unwrap();
+    assert_eq!(content, CONTENT);
+    mock.assert();
+}
+
+#[tokio::test]
+async fn content_at_reference() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server);
+    let mock = mock_content(&mut server, "v1.0.0")
+        .with_body(CONTENT)
+        .create();
+    let content = client
+        .get_file_content("docs/my notes.md", Some("v1.0.0"))
+        .await
+        .unwrap();
+    assert_eq!(content, CONTENT);
+    mock.assert();
+}
+
+#[tokio::test]
+async fn binary_content() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server);
+    let mock = mock_content(&mut server, SHA)
+        .with_body([0x89, b'P', b'N', b'G', 0xff, 0x00])
+        .create();
+    let err = client
+        .get_file_content("docs/my notes.md", None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RestClientError::BinaryFile { path } if path == "docs/my notes.md"));
+    mock.assert();
+}
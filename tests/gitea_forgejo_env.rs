@@ -0,0 +1,109 @@
+#![cfg(feature = "gitea")]
+use std::{env, fs};
+
+use git_bot_feedback::{
+    OutputVariable, RestClientError,
+    client::{GiteaApiClient, from_env},
+};
+use tempfile::NamedTempFile;
+
+mod common;
+use common::logger_init;
+
+const SETTINGS: [&str; 6] = [
+    "API_URL",
+    "REPOSITORY",
+    "SHA",
+    "EVENT_NAME",
+    "TOKEN",
+    "OUTPUT",
+];
+
+/// Set only the given environment variables (clearing any other prefix for the same settings).
+fn setup(vars: &[(&str, &str)]) {
+    logger_init();
+    unsafe {
+        for setting in SETTINGS {
+            for prefix in ["FORGEJO", "GITEA", "GITHUB"] {
+                env::remove_var(format!("{prefix}_{setting}"));
+            }
+        }
+        env::remove_var("GITEA_ACTIONS");
+        env::remove_var("GITHUB_ACTIONS");
+        for (name, value) in vars {
+            env::set_var(name, value);
+        }
+    }
+}
+
+#[test]
+fn forgejo_only() {
+    let output = NamedTempFile::new().unwrap();
+    setup(&[
+        ("FORGEJO_ACTIONS", "true"),
+        ("FORGEJO_API_URL", "https://codeberg.org"),
+        ("FORGEJO_REPOSITORY", "2bndy5/git-bot-feedback"),
+        ("FORGEJO_SHA", "DEADBEEF"),
+        ("FORGEJO_EVENT_NAME", "push"),
+        ("FORGEJO_TOKEN", "123456"),
+        ("FORGEJO_OUTPUT", output.path().to_str().unwrap()),
+    ]);
+    let client = from_env().unwrap();
+    assert_eq!(client.client_kind(), "gitea");
+    for setting in SETTINGS {
+        assert_eq!(
+            GiteaApiClient::env_var_name(setting),
+            format!("FORGEJO_{setting}")
+        );
+    }
+    client
+        .write_output_variables(&[OutputVariable {
+            name: "checks-failed".to_string(),
+            value: "0".to_string(),
+        }])
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(output.path()).unwrap(),
+        "checks-failed=0\n"
+    );
+}
+
+#[test]
+fn mixed_prefixes() {
+    setup(&[
+        ("FORGEJO_ACTIONS", "true"),
+        ("FORGEJO_API_URL", "https://codeberg.org"),
+        ("GITEA_API_URL", "https://gitea.com"),
+        ("GITEA_REPOSITORY", "2bndy5/git-bot-feedback"),
+        ("GITHUB_REPOSITORY", "other/repo"),
+        ("GITHUB_SHA", "DEADBEEF"),
+        ("FORGEJO_TOKEN", "123456"),
+        ("GITHUB_TOKEN", "abcdef"),
+    ]);
+    GiteaApiClient::new().unwrap();
+    assert_eq!(GiteaApiClient::env_var_name("API_URL"), "FORGEJO_API_URL");
+    assert_eq!(
+        GiteaApiClient::env_var_name("REPOSITORY"),
+        "GITEA_REPOSITORY"
+    );
+    assert_eq!(GiteaApiClient::env_var_name("SHA"), "GITHUB_SHA");
+    assert_eq!(GiteaApiClient::env_var_name("TOKEN"), "FORGEJO_TOKEN");
+    // a setting that is not set anywhere is reported with the Gitea name
+    assert_eq!(
+        GiteaApiClient::env_var_name("EVENT_NAME"),
+        "GITEA_EVENT_NAME"
+    );
+}
+
+#[test]
+fn missing_setting() {
+    setup(&[
+        ("FORGEJO_API_URL", "https://codeberg.org"),
+        ("FORGEJO_SHA", "DEADBEEF"),
+    ]);
+    let err = GiteaApiClient::new().err().unwrap();
+    assert!(
+        matches!(&err, RestClientError::EnvVar { name, .. } if name == "GITEA_REPOSITORY"),
+        "Expected EnvVar error, got: {err:?}"
+    );
+}
@@ -12,6 +12,9 @@ struct TestParams {
     absent: bool,
     bad_var: bool,
     empty_pairs: bool,
+    strict: bool,
+    /// Only set the GitHub-compatible name (as act_runner does).
+    github_name: bool,
 }
 
 const REPO: &str = "2bndy5/git-bot-feedback";
@@ -27,12 +30,21 @@ async fn append_output_vars(test_params: TestParams) -> String {
     let mut out_var_path = NamedTempFile::new_in(tmp_dir.path()).unwrap();
     if test_params.absent {
         unsafe {
-            env::remove_var("GITEA_OUTPUT");
+            for prefix in ["FORGEJO", "GITEA", "GITHUB"] {
+                env::remove_var(format!("{prefix}_OUTPUT"));
+            }
         }
     } else {
         unsafe {
+            for prefix in ["FORGEJO", "GITEA", "GITHUB"] {
+                env::remove_var(format!("{prefix}_OUTPUT"));
+            }
             env::set_var(
-                "GITEA_OUTPUT",
+                if test_params.github_name {
+                    "GITHUB_OUTPUT"
+                } else {
+                    "GITEA_OUTPUT"
+                },
                 if test_params.fail_file {
                     Path::new("not-a-file.txt")
                 } else {
@@ -45,7 +57,7 @@ async fn append_output_vars(test_params: TestParams) -> String {
     let out_vars = if test_params.bad_var {
         [OutputVariable {
             name: VAR_NAME.to_string(),
-            value: "bad\nvalue".to_string(),
+            value: "bad\x07value".to_string(),
         }]
     } else {
         [OutputVariable {
@@ -65,8 +77,9 @@ async fn append_output_vars(test_params: TestParams) -> String {
         env::set_var("CI", "true");
         env::set_var("GITEA_EVENT_NAME", "push");
     }
-    let gt_client = init_client().unwrap();
+    let mut gt_client = init_client().unwrap();
     assert_eq!(gt_client.client_kind(), "gitea");
+    gt_client.set_strict_mode(test_params.strict);
 
     match gt_client.write_output_variables(if test_params.empty_pairs {
         &[]
@@ -88,6 +101,11 @@ async fn append_output_vars(test_params: TestParams) -> String {
                     matches!(e, RestClientError::OutputVar(_)),
                     "Expected OutputVar error, got: {e:?}"
                 );
+            } else if test_params.absent && test_params.strict {
+                assert!(
+                    matches!(e, RestClientError::EnvVar { .. }),
+                    "Expected EnvVar error, got: {e:?}"
+                );
             } else {
                 panic!("Unexpected failure to write to GITEA_OUTPUT");
             }
@@ -109,7 +127,17 @@ async fn fail_gh_out() {
 #[tokio::test]
 async fn pass_gh_out() {
     let out = append_output_vars(TestParams::default()).await;
-    assert!(out.contains(format!("{VAR_NAME}={VAR_VALUE}\n").as_str()));
+    assert_eq!(out, format!("{VAR_NAME}={VAR_VALUE}\n"));
+}
+
+#[tokio::test]
+async fn github_out_fallback() {
+    let out = append_output_vars(TestParams {
+        github_name: true,
+        ..Default::default()
+    })
+    .await;
+    assert_eq!(out, format!("{VAR_NAME}={VAR_VALUE}\n"));
 }
 
 #[tokio::test]
@@ -122,6 +150,17 @@ async fn absent_gh_out() {
     assert!(out.is_empty());
 }
 
+#[tokio::test]
+async fn absent_gh_out_strict() {
+    let out = append_output_vars(TestParams {
+        absent: true,
+        strict: true,
+        ..Default::default()
+    })
+    .await;
+    assert!(out.is_empty());
+}
+
 #[tokio::test]
 async fn bad_var_val() {
     let out = append_output_vars(TestParams {
@@ -1,7 +1,7 @@
 #![cfg(feature = "gitea")]
 use chrono::Utc;
 use git_bot_feedback::{
-    RestApiClient, RestClientError, ReviewAction, ReviewComment, ReviewOptions,
+    RestApiClient, RestClientError, ReviewAction, ReviewComment, ReviewOptions, ReviewSkipReason,
     client::{GiteaApiClient, USER_AGENT},
 };
 use mockito::{Matcher, Server};
@@ -18,6 +18,7 @@ const PR: i64 = 46;
 const TOKEN: &str = "123456";
 const OUTDATED_REVIEW_ID: i64 = 2518109626;
 const REUSED_REVIEW_ID: i64 = 2519970027;
+const POSTED_REVIEW_ID: i64 = 2520000000;
 const MOCK_ASSETS_PATH: &str = "tests/assets/reviews/gitea";
 
 const ASSET_REVIEWS_OUTDATED_PG1: &str = "reviews_outdated_pg1.json";
@@ -30,6 +31,8 @@ const ASSET_REVIEW_COMMENTS_REUSED_ALL: &str = "review_comments_reused_all.json"
 
 const RESET_RATE_LIMIT_HEADER: &str = "x-ratelimit-reset";
 const REMAINING_RATE_LIMIT_HEADER: &str = "x-ratelimit-remaining";
+/// The number of attempts made before giving up on a server error.
+const MAX_ATTEMPTS: usize = 5;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum ExistingReviews {
@@ -53,6 +56,7 @@ struct TestParams {
     review_with_no_comments: bool,
     paginate_review_comments: bool,
     no_token: bool,
+    dismissal_message: Option<&'static str>,
 }
 
 impl Default for TestParams {
@@ -70,6 +74,7 @@ impl Default for TestParams {
             review_with_no_comments: false,
             paginate_review_comments: false,
             no_token: false,
+            dismissal_message: None,
         }
     }
 }
@@ -77,6 +82,7 @@ impl Default for TestParams {
 #[derive(Default)]
 struct TestControlVars {
     new_review_comments: Vec<ReviewComment>,
+    reused_comments: usize,
     outdated_comment_ids: Vec<i64>,
     outdated_review_ids: Vec<i64>,
 }
@@ -103,6 +109,7 @@ impl TestControlVars {
                     line_end: comment["new_position"].as_i64().unwrap() as u32,
                     path: comment["path"].as_str().unwrap().to_string(),
                 });
+                self.reused_comments += 1;
                 keep_review = true;
             } else {
                 self.outdated_comment_ids
@@ -134,7 +141,9 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
         env::set_var("GITEA_REPOSITORY", REPO);
         env::set_var("GITEA_SHA", SHA);
         if test_params.no_token {
-            env::remove_var("GITEA_TOKEN");
+            for prefix in ["FORGEJO", "GITEA", "GITHUB"] {
+                env::remove_var(format!("{prefix}_TOKEN"));
+            }
         } else {
             env::set_var("GITEA_TOKEN", TOKEN);
         }
@@ -266,12 +275,15 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
                                 "GET",
                                 format!("{review_url_path}/{OUTDATED_REVIEW_ID}/comments").as_str(),
                             )
+                            .match_query(Matcher::UrlEncoded("page".to_string(), "1".to_string()))
                             .match_header("Accept", "application/json")
                             .match_header("Authorization", format!("token {TOKEN}").as_str())
                             .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
                             .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
                             .with_status(500)
                             .with_body("TEST CONDITION TRIGGERED")
+                            // server errors are retried
+                            .expect(MAX_ATTEMPTS)
                             .create(),
                     );
                     test_control_vars.mark_review_outdated(OUTDATED_REVIEW_ID);
@@ -282,6 +294,7 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
                                 "GET",
                                 format!("{review_url_path}/{OUTDATED_REVIEW_ID}/comments").as_str(),
                             )
+                            .match_query(Matcher::UrlEncoded("page".to_string(), "1".to_string()))
                             .match_header("Accept", "application/json")
                             .match_header("Authorization", format!("token {TOKEN}").as_str())
                             .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
@@ -300,6 +313,7 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
                                 "GET",
                                 format!("{review_url_path}/{OUTDATED_REVIEW_ID}/comments").as_str(),
                             )
+                            .match_query(Matcher::UrlEncoded("page".to_string(), "1".to_string()))
                             .match_header("Accept", "application/json")
                             .match_header("Authorization", format!("token {TOKEN}").as_str())
                             .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
@@ -325,6 +339,7 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
                                 "GET",
                                 format!("{review_url_path}/{REUSED_REVIEW_ID}/comments").as_str(),
                             )
+                            .match_query(Matcher::UrlEncoded("page".to_string(), "1".to_string()))
                             .match_header("Accept", "application/json")
                             .match_header("Authorization", format!("token {TOKEN}").as_str())
                             .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
@@ -375,6 +390,7 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
                                 "GET",
                                 format!("{review_url_path}/{REUSED_REVIEW_ID}/comments").as_str(),
                             )
+                            .match_query(Matcher::UrlEncoded("page".to_string(), "1".to_string()))
                             .match_header("Accept", "application/json")
                             .match_header("Authorization", format!("token {TOKEN}").as_str())
                             .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
@@ -406,6 +422,7 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
                             .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
                             .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
                             .with_status(if fail_resolve_comment { 500 } else { 200 })
+                            .expect(1)
                             .create(),
                     );
                 }
@@ -419,6 +436,7 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
                                 .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
                                 .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
                                 .with_status(if test_params.fail_dismissal { 500 } else { 200 })
+                                .expect(1)
                                 .create(),
                         );
                     } else {
@@ -428,14 +446,17 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
                                     "POST",
                                     format!("{review_url_path}/{review_id}/dismissals").as_str(),
                                 )
-                                .match_body(Matcher::PartialJson(serde_json::json!({
-                                    "message": "outdated review",
+                                .match_body(Matcher::Json(serde_json::json!({
+                                    "message": test_params
+                                        .dismissal_message
+                                        .unwrap_or("outdated review (git-bot-feedback)"),
                                     "priors": false
                                 })))
                                 .match_header("Authorization", format!("token {TOKEN}").as_str())
                                 .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
                                 .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
                                 .with_status(if test_params.fail_dismissal { 500 } else { 200 })
+                                .expect(1)
                                 .create(),
                         );
                     }
@@ -501,6 +522,13 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
                 .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
                 .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
                 .with_status(200)
+                .with_body(
+                    serde_json::json!({
+                        "id": POSTED_REVIEW_ID,
+                        "html_url": format!("https://gitea.com/{REPO}/pulls/{PR}#issuecomment-{POSTED_REVIEW_ID}"),
+                    })
+                    .to_string(),
+                )
                 .create(),
         );
     }
@@ -511,11 +539,13 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
         summary,
         comments: test_control_vars.new_review_comments.clone(),
         delete_review_comments: test_params.delete_reviews,
+        dismissal_message: test_params.dismissal_message.map(str::to_string),
         ..Default::default()
     };
 
     client.start_log_group("posting review");
-    if let Err(e) = client.cull_pr_reviews(&mut opts).await {
+    let culled = client.cull_pr_reviews(&mut opts).await;
+    if let Err(e) = &culled {
         match test_params.existing_reviews {
             ExistingReviews::Happy if test_params.bad_json_review_comments => {
                 assert!(
@@ -540,14 +570,47 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
             }
         }
     }
-    if let Err(e) = client.post_pr_review(&opts).await {
-        if test_params.no_token {
-            assert!(
-                matches!(e, RestClientError::EnvVar { .. }),
-                "Expected EnvVar error, got: {e:?}"
-            );
-        } else {
-            panic!("Unexpected error posting review: {e}");
+    let is_reviewable = test_params.event_t == EventType::PullRequest
+        && !test_params.no_token
+        && !test_params.is_locked
+        && !test_params.is_draft;
+    match client.post_pr_review(&opts).await {
+        Ok(outcome) => {
+            if is_reviewable {
+                assert_eq!(outcome.review_id, Some(POSTED_REVIEW_ID));
+                assert!(outcome.html_url.is_some());
+                assert_eq!(outcome.posted_comments, opts.comments.len());
+            } else {
+                assert!(outcome.review_id.is_none());
+                assert_eq!(outcome.posted_comments, 0);
+            }
+            if is_reviewable && culled.is_ok() {
+                assert_eq!(outcome.reused_comments, test_control_vars.reused_comments);
+                assert_eq!(
+                    outcome.dismissed_reviews,
+                    test_control_vars.outdated_review_ids.len()
+                );
+            } else if !is_reviewable {
+                assert_eq!(outcome.reused_comments, 0);
+                assert_eq!(outcome.dismissed_reviews, 0);
+            }
+            if test_params.is_locked {
+                assert_eq!(outcome.skipped, Some(ReviewSkipReason::Locked));
+            } else if test_params.is_draft {
+                assert_eq!(outcome.skipped, Some(ReviewSkipReason::Draft));
+            } else {
+                assert!(outcome.skipped.is_none());
+            }
+        }
+        Err(e) => {
+            if test_params.no_token {
+                assert!(
+                    matches!(e, RestClientError::EnvVar { .. }),
+                    "Expected EnvVar error, got: {e:?}"
+                );
+            } else {
+                panic!("Unexpected error posting review: {e}");
+            }
         }
     }
     client.end_log_group("");
@@ -635,6 +698,15 @@ async fn dismiss_outdated_review_500() {
     .await;
 }
 
+#[tokio::test]
+async fn dismissal_message_with_quotes() {
+    test_reviews(TestParams {
+        dismissal_message: Some("Superseded by \"my-linter\"\nSee the newer review."),
+        ..Default::default()
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn delete_outdated_review() {
     test_reviews(TestParams {
@@ -689,3 +761,165 @@ async fn paginated_review_comments() {
     })
     .await;
 }
+
+/// Run a review whose proposed comments were all posted by a previous run.
+///
+/// If `resolved`, then one of the previously posted comments was resolved since then,
+/// so it is not reused.
+async fn test_rerun(resolved: bool) {
+    let mut server = Server::new_async().await;
+    let mut event_payload_file = NamedTempFile::new_in("./").unwrap();
+    event_payload_file
+        .write_all(
+            serde_json::json!({"pull_request": {"draft": false, "locked": false, "state": "open", "number": PR}})
+                .to_string()
+                .as_bytes(),
+        )
+        .unwrap();
+    unsafe {
+        env::set_var(
+            "GITEA_EVENT_NAME",
+            EventType::PullRequest.to_string().as_str(),
+        );
+        env::set_var("GITEA_EVENT_PATH", event_payload_file.path());
+        env::set_var("GITEA_REPOSITORY", REPO);
+        env::set_var("GITEA_SHA", SHA);
+        env::set_var("GITEA_TOKEN", TOKEN);
+        env::set_var("GITEA_API_URL", server.url());
+    }
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    let mut client = GiteaApiClient::new().unwrap();
+
+    let proposed = vec![
+        ReviewComment {
+            line_start: None,
+            line_end: 42,
+            comment: "unused variable".to_string(),
+            path: "src/lib.rs".to_string(),
+        },
+        ReviewComment {
+            // Gitea only positions a comment on a single line
+            line_start: Some(8),
+            line_end: 10,
+            comment: format!("{MARKER}missing docs"),
+            path: "src/main.rs".to_string(),
+        },
+    ];
+    let reset_timestamp = (Utc::now().timestamp() + 60).to_string();
+    let review_url_path = format!("/api/v1/repos/{REPO}/pulls/{PR}/reviews");
+    let mut mocks = vec![
+        server
+            .mock("GET", review_url_path.as_str())
+            .match_query(Matcher::UrlEncoded("page".to_string(), "1".to_string()))
+            .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+            .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+            .with_body(
+                serde_json::json!([{
+                    "id": REUSED_REVIEW_ID,
+                    "body": format!("{MARKER}previous review"),
+                    "user": {"login": "gitea-actions[bot]", "id": 41898282},
+                    "state": "COMMENT",
+                    "comments_count": 2,
+                }])
+                .to_string(),
+            )
+            .create(),
+        server
+            .mock(
+                "GET",
+                format!("{review_url_path}/{REUSED_REVIEW_ID}/comments").as_str(),
+            )
+            .match_query(Matcher::UrlEncoded("page".to_string(), "1".to_string()))
+            .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+            .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+            .with_body(
+                serde_json::json!([
+                    {
+                        "id": 1,
+                        "path": "src/lib.rs",
+                        "old_position": 0,
+                        "new_position": 42,
+                        "body": format!("{MARKER}unused variable"),
+                    },
+                    {
+                        "id": 2,
+                        "path": "src/main.rs",
+                        "old_position": 8,
+                        "new_position": 10,
+                        "body": format!("{MARKER}missing docs"),
+                        "resolver": if resolved {
+                            serde_json::json!({"login": "2bndy5", "id": 14963867})
+                        } else {
+                            serde_json::Value::Null
+                        },
+                    },
+                ])
+                .to_string(),
+            )
+            .create(),
+        // an outdated (resolved) comment is resolved again
+        server
+            .mock(
+                "POST",
+                format!("/api/v1/repos/{REPO}/pulls/{PR}/comments/2/resolve").as_str(),
+            )
+            .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+            .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+            .expect(usize::from(resolved))
+            .create(),
+        // the review is kept because it has a reused comment
+        server
+            .mock(
+                "POST",
+                format!("{review_url_path}/{REUSED_REVIEW_ID}/dismissals").as_str(),
+            )
+            .expect(0)
+            .create(),
+    ];
+    let expected_comments = if resolved {
+        serde_json::json!([{
+            "body": format!("{MARKER}missing docs"),
+            "new_position": 10,
+            "path": "src/main.rs",
+        }])
+    } else {
+        serde_json::json!([])
+    };
+    mocks.push(
+        server
+            .mock("POST", review_url_path.as_str())
+            .match_body(Matcher::PartialJson(
+                serde_json::json!({"comments": expected_comments}),
+            ))
+            .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+            .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+            .with_body(serde_json::json!({"id": POSTED_REVIEW_ID}).to_string())
+            .create(),
+    );
+
+    let mut opts = ReviewOptions {
+        marker: MARKER.to_string(),
+        summary: "No new concerns.".to_string(),
+        comments: proposed,
+        ..Default::default()
+    };
+    client.cull_pr_reviews(&mut opts).await.unwrap();
+    let outcome = client.post_pr_review(&opts).await.unwrap();
+    assert_eq!(outcome.posted_comments, usize::from(resolved));
+    assert_eq!(outcome.reused_comments, if resolved { 1 } else { 2 });
+    assert_eq!(outcome.dismissed_reviews, 0);
+    for mock in mocks {
+        mock.assert();
+    }
+}
+
+#[tokio::test]
+async fn rerun_posts_no_duplicates() {
+    test_rerun(false).await;
+}
+
+#[tokio::test]
+async fn rerun_reposts_resolved_comment() {
+    test_rerun(true).await;
+}
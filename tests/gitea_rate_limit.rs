@@ -0,0 +1,119 @@
+#![cfg(feature = "gitea")]
+use std::time::Duration;
+
+use git_bot_feedback::{
+    RestApiClient, RestApiRateLimitHeaders,
+    client::{RateLimitDetection, init_client},
+    reqwest::{Client, Method, StatusCode, Url},
+};
+use mockito::Server;
+
+mod common;
+use common::{gitea_env, logger_init};
+
+fn setup(server: &Server) -> Box<dyn RestApiClient + Send + Sync> {
+    gitea_env(&server.url());
+    logger_init();
+    let client = init_client().unwrap();
+    assert_eq!(client.client_kind(), "gitea");
+    client
+}
+
+/// Send a GET request to the `server` and return the response's status.
+async fn send_get(
+    client: &(dyn RestApiClient + Send + Sync),
+    server: &Server,
+    rate_limit_headers: &RestApiRateLimitHeaders,
+) -> StatusCode {
+    let http_client = Client::new();
+    let request = client
+        .make_api_request(
+            &http_client,
+            Url::parse(&server.url()).unwrap(),
+            Method::GET,
+            None,
+            None,
+        )
+        .unwrap();
+    client
+        .send_api_request(&http_client, request, rate_limit_headers)
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn rate_limit_status() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server);
+    // Gitea has no endpoint for the rate limit status
+    let mock = server.mock("GET", mockito::Matcher::Any).expect(0).create();
+    assert!(client.rate_limit_status().await.unwrap().is_none());
+    mock.assert();
+}
+
+#[tokio::test]
+async fn forbidden_without_headers() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server);
+    assert_eq!(
+        client.retry_policy().rate_limit_detection,
+        RateLimitDetection::StatusOnly
+    );
+    let forbidden = server
+        .mock("GET", "/")
+        .with_status(403)
+        .with_body("user does not have permission")
+        .expect(1)
+        .create();
+    let status = send_get(
+        client.as_ref(),
+        &server,
+        &RestApiRateLimitHeaders::disabled(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    forbidden.assert();
+}
+
+#[tokio::test]
+async fn forbidden_with_retry_header() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server);
+    let limited = server
+        .mock("GET", "/")
+        .with_status(403)
+        .with_header("retry-after", "0")
+        .expect(1)
+        .create();
+    let passing = server.mock("GET", "/").expect(1).create();
+    let rate_limit_headers = RestApiRateLimitHeaders {
+        reset: "x-ratelimit-reset".to_string(),
+        remaining: "x-ratelimit-remaining".to_string(),
+        retry: "retry-after".to_string(),
+    };
+    let status = send_get(client.as_ref(), &server, &rate_limit_headers).await;
+    assert_eq!(status, StatusCode::OK);
+    limited.assert();
+    passing.assert();
+}
+
+#[tokio::test]
+async fn too_many_requests_without_headers() {
+    let mut server = Server::new_async().await;
+    let mut client = setup(&server);
+    let mut policy = client.retry_policy();
+    policy.default_delay = Duration::ZERO;
+    client.set_retry_policy(policy);
+    let limited = server.mock("GET", "/").with_status(429).expect(1).create();
+    let passing = server.mock("GET", "/").expect(1).create();
+    let status = send_get(
+        client.as_ref(),
+        &server,
+        &RestApiRateLimitHeaders::disabled(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    limited.assert();
+    passing.assert();
+}
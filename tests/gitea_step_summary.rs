@@ -1,12 +1,16 @@
 #![cfg(feature = "gitea")]
-use git_bot_feedback::{RestApiClient, RestClientError, client::GiteaApiClient};
+use git_bot_feedback::{
+    RestApiClient, RestClientError,
+    client::GiteaApiClient,
+    summary::{MAX_STEP_SUMMARY_SIZE, SummaryOverflow, TRUNCATED_NOTE},
+};
 use mockito::Server;
 use std::{env, io::Read, path::Path};
 use tempfile::{NamedTempFile, tempdir};
 mod common;
 use common::logger_init;
 
-const COMMENT: &str = "Some comment text";
+const COMMENT: &str = "Some comment text\nthat spans multiple lines";
 
 const REPO: &str = "2bndy5/git-bot-feedback";
 const SHA: &str = "DEADBEEF";
@@ -15,6 +19,11 @@ const SHA: &str = "DEADBEEF";
 struct TestParams {
     fail_summary: bool,
     absent: bool,
+    strict: bool,
+    /// Only set the GitHub-compatible name (as act_runner does).
+    github_name: bool,
+    /// Override the step summary's size limit.
+    limit: Option<u64>,
 }
 
 async fn append_summary(test_params: TestParams) -> String {
@@ -24,14 +33,23 @@ async fn append_summary(test_params: TestParams) -> String {
     let mut step_summary_path = NamedTempFile::new_in(tmp_dir.path()).unwrap();
     if test_params.absent {
         unsafe {
-            env::remove_var("GITEA_STEP_SUMMARY");
+            for prefix in ["FORGEJO", "GITEA", "GITHUB"] {
+                env::remove_var(format!("{prefix}_STEP_SUMMARY"));
+            }
         }
     } else {
         unsafe {
+            for prefix in ["FORGEJO", "GITEA", "GITHUB"] {
+                env::remove_var(format!("{prefix}_STEP_SUMMARY"));
+            }
             env::set_var(
-                "GITEA_STEP_SUMMARY",
+                if test_params.github_name {
+                    "GITHUB_STEP_SUMMARY"
+                } else {
+                    "GITEA_STEP_SUMMARY"
+                },
                 if test_params.fail_summary {
-                    Path::new("not-a-file.txt")
+                    Path::new("not-a-dir/not-a-file.txt")
                 } else {
                     step_summary_path.path()
                 },
@@ -49,17 +67,28 @@ async fn append_summary(test_params: TestParams) -> String {
         env::set_var("CI", "true");
         env::set_var("GITEA_EVENT_NAME", "push");
     }
-    let gt_client = GiteaApiClient::new().unwrap();
+    let mut gt_client = GiteaApiClient::new().unwrap();
+    gt_client.set_strict_mode(test_params.strict);
+    assert_eq!(gt_client.step_summary_limit(), MAX_STEP_SUMMARY_SIZE);
+    if let Some(limit) = test_params.limit {
+        gt_client.set_step_summary_limit(limit);
+        assert_eq!(gt_client.step_summary_limit(), limit);
+    }
 
-    match gt_client.append_step_summary(COMMENT) {
+    match gt_client.append_step_summary(COMMENT, SummaryOverflow::default()) {
         Ok(_) => {
             step_summary_path
                 .read_to_string(&mut step_summary_content)
                 .unwrap();
         }
         Err(e) => {
-            assert!(test_params.fail_summary);
-            assert!(matches!(e, RestClientError::Io { task: _, source: _ }));
+            if test_params.absent {
+                assert!(test_params.strict);
+                assert!(matches!(e, RestClientError::EnvVar { .. }));
+            } else {
+                assert!(test_params.fail_summary);
+                assert!(matches!(e, RestClientError::Io { task: _, source: _ }));
+            }
         }
     }
     step_summary_content
@@ -81,6 +110,31 @@ async fn pass_gh_summary() {
     assert!(summary.contains(COMMENT));
 }
 
+#[tokio::test]
+async fn truncated_gh_summary() {
+    // too small for the whole comment (and its surrounding blank lines)
+    let limit = COMMENT.len() as u64;
+    let summary = append_summary(TestParams {
+        limit: Some(limit),
+        ..Default::default()
+    })
+    .await;
+    assert!(summary.contains(TRUNCATED_NOTE));
+    assert!(summary.contains("Some comment text\n"));
+    assert!(!summary.contains(COMMENT));
+    assert!(summary.len() as u64 <= limit);
+}
+
+#[tokio::test]
+async fn github_summary_fallback() {
+    let summary = append_summary(TestParams {
+        github_name: true,
+        ..Default::default()
+    })
+    .await;
+    assert!(summary.contains(COMMENT));
+}
+
 #[tokio::test]
 async fn absent_gh_summary() {
     let summary = append_summary(TestParams {
@@ -90,3 +144,14 @@ async fn absent_gh_summary() {
     .await;
     assert!(summary.is_empty());
 }
+
+#[tokio::test]
+async fn absent_gh_summary_strict() {
+    let summary = append_summary(TestParams {
+        absent: true,
+        strict: true,
+        ..Default::default()
+    })
+    .await;
+    assert!(summary.is_empty());
+}
@@ -1,11 +1,11 @@
 #![cfg(feature = "gitea")]
 use chrono::Utc;
 use git_bot_feedback::{
-    CommentKind, CommentPolicy, RestApiClient, RestClientError, ThreadCommentOptions,
-    client::GiteaApiClient,
+    CommentFallback, CommentKind, CommentPolicy, RestApiClient, RestClientError,
+    ThreadCommentOptions, client::GiteaApiClient, reqwest::StatusCode,
 };
 use mockito::{Matcher, Server};
-use std::{env, io::Write, path::Path};
+use std::{env, fs, io::Write, path::Path};
 use tempfile::{NamedTempFile, TempDir};
 
 mod common;
@@ -19,6 +19,10 @@ const TOKEN: &str = "123456";
 const MOCK_ASSETS_PATH: &str = "tests/assets/thread_comment/github/";
 const RESET_RATE_LIMIT_HEADER: &str = "x-ratelimit-reset";
 const REMAINING_RATE_LIMIT_HEADER: &str = "x-ratelimit-remaining";
+const DELETION_DENIED: &str = "Must have admin rights to delete this comment";
+const POSTING_DENIED: &str = "Resource not accessible by integration";
+/// The number of attempts made before giving up on a server error.
+const MAX_ATTEMPTS: usize = 5;
 
 struct TestParams {
     event_t: EventType,
@@ -34,6 +38,8 @@ struct TestParams {
     bad_pr_info: bool,
     locked_pr: bool,
     no_token: bool,
+    /// Appended to the mock server's URL in `GITEA_API_URL`.
+    api_url_suffix: &'static str,
 }
 
 impl Default for TestParams {
@@ -52,6 +58,7 @@ impl Default for TestParams {
             bad_pr_info: false,
             locked_pr: false,
             no_token: false,
+            api_url_suffix: "",
         }
     }
 }
@@ -97,7 +104,10 @@ async fn setup(lib_root: &Path, test_params: &TestParams) {
 
     let mut server = Server::new_async().await;
     unsafe {
-        env::set_var("GITEA_API_URL", server.url());
+        env::set_var(
+            "GITEA_API_URL",
+            format!("{}{}", server.url(), test_params.api_url_suffix),
+        );
     }
 
     logger_init();
@@ -142,7 +152,13 @@ async fn setup(lib_root: &Path, test_params: &TestParams) {
                     } else {
                         403
                     })
-                    .with_body("TEST CONDITION TRIGGERED");
+                    .with_body("TEST CONDITION TRIGGERED")
+                    // server errors are retried
+                    .expect(if test_params.fail_get_existing_comments_500 {
+                        MAX_ATTEMPTS
+                    } else {
+                        1
+                    });
                 mocks.push(mock.create());
                 break;
             }
@@ -176,6 +192,7 @@ async fn setup(lib_root: &Path, test_params: &TestParams) {
                 } else {
                     200
                 })
+                .with_body(DELETION_DENIED)
                 .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
                 .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
                 .expect_at_least(1)
@@ -211,6 +228,7 @@ async fn setup(lib_root: &Path, test_params: &TestParams) {
                 .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
                 .match_header("Authorization", format!("token {TOKEN}").as_str())
                 .with_status(if test_params.fail_posting { 403 } else { 200 })
+                .with_body(POSTING_DENIED)
                 .create();
             mocks.push(mock);
         } else {
@@ -220,8 +238,13 @@ async fn setup(lib_root: &Path, test_params: &TestParams) {
                     .match_body(new_comment_match.clone())
                     .match_header("Authorization", format!("token {TOKEN}").as_str())
                     .with_status(if test_params.fail_posting { 403 } else { 200 })
+                    .with_body(POSTING_DENIED)
                     .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
                     .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+                    // a failed deletion stops the update
+                    .expect(usize::from(
+                        !test_params.fail_dismissal && !test_params.fail_dismissal_500,
+                    ))
                     .create(),
             );
         }
@@ -233,6 +256,7 @@ async fn setup(lib_root: &Path, test_params: &TestParams) {
         kind: test_params.comment_kind,
         marker: MARKER.to_string(),
         no_lgtm: test_params.no_lgtm,
+        ..Default::default()
     };
     client.start_log_group("posting comment");
     let result = client.post_thread_comment(opts).await;
@@ -247,6 +271,25 @@ async fn setup(lib_root: &Path, test_params: &TestParams) {
             matches!(result, Err(RestClientError::EnvVar { .. })),
             "Expected EnvVar error, got: {result:?}"
         );
+    } else if test_params.fail_dismissal
+        || test_params.fail_dismissal_500
+        || test_params.fail_posting
+    {
+        let (expected_status, expected_body) = if test_params.fail_dismissal_500 {
+            (StatusCode::INTERNAL_SERVER_ERROR, DELETION_DENIED)
+        } else if test_params.fail_dismissal {
+            (StatusCode::FORBIDDEN, DELETION_DENIED)
+        } else {
+            (StatusCode::FORBIDDEN, POSTING_DENIED)
+        };
+        assert!(
+            matches!(
+                &result,
+                Err(RestClientError::Http { status, body, .. })
+                    if *status == expected_status && body == expected_body
+            ),
+            "Expected Http error, got: {result:?}"
+        );
     } else {
         result.unwrap();
     }
@@ -297,6 +340,39 @@ async fn update_pr() {
     .await;
 }
 
+#[tokio::test]
+async fn update_pr_api_url_trailing_slash() {
+    test_comment(&TestParams {
+        event_t: EventType::PullRequest,
+        api_url_suffix: "/",
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn update_pr_api_url_with_api_path() {
+    test_comment(&TestParams {
+        event_t: EventType::PullRequest,
+        api_url_suffix: "/api/v1/",
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn new_pr_no_lgtm_api_url_trailing_slash() {
+    test_comment(&TestParams {
+        event_t: EventType::PullRequest,
+        comment_policy: CommentPolicy::Anew,
+        comment_kind: CommentKind::Lgtm,
+        no_lgtm: true,
+        api_url_suffix: "/",
+        ..Default::default()
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn new_push_no_lgtm() {
     test_comment(&TestParams {
@@ -431,3 +507,54 @@ async fn locked_pr() {
     })
     .await;
 }
+
+/// Post a thread comment for a push event with the given `push_fallback`.
+///
+/// Returns the content of the step summary.
+async fn push_fallback(push_fallback: CommentFallback, no_lgtm: bool) -> String {
+    logger_init();
+    let step_summary = NamedTempFile::new().unwrap();
+    let server = Server::new_async().await;
+    unsafe {
+        env::set_var("GITEA_EVENT_NAME", "push");
+        env::set_var("GITEA_REPOSITORY", REPO);
+        env::set_var("GITEA_SHA", SHA);
+        env::set_var("GITEA_TOKEN", TOKEN);
+        env::set_var("GITEA_API_URL", server.url());
+        env::set_var("GITEA_STEP_SUMMARY", step_summary.path());
+    }
+    let client = GiteaApiClient::new().unwrap();
+    client
+        .post_thread_comment(ThreadCommentOptions {
+            comment: "Some concerns".to_string(),
+            kind: if no_lgtm {
+                CommentKind::Lgtm
+            } else {
+                CommentKind::Concerns
+            },
+            no_lgtm,
+            push_fallback,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    fs::read_to_string(step_summary.path()).unwrap()
+}
+
+#[tokio::test]
+async fn push_fallback_step_summary() {
+    let summary = push_fallback(CommentFallback::default(), false).await;
+    assert_eq!(summary.trim(), "Some concerns");
+}
+
+#[tokio::test]
+async fn push_fallback_skip() {
+    let summary = push_fallback(CommentFallback::Skip, false).await;
+    assert!(summary.is_empty());
+}
+
+#[tokio::test]
+async fn push_fallback_no_lgtm() {
+    let summary = push_fallback(CommentFallback::StepSummary, true).await;
+    assert!(summary.is_empty());
+}
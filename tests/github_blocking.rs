@@ -0,0 +1,165 @@
+#![cfg(all(feature = "blocking", feature = "github"))]
+use std::{env, fs, io::Write, path::Path};
+
+use git_bot_feedback::{
+    RestApiClient, ReviewComment, ReviewOptions, ThreadCommentOptions, blocking::GithubApiClient,
+    summary::SummaryOverflow,
+};
+use mockito::{Matcher, Server, ServerGuard};
+use tempfile::NamedTempFile;
+
+mod common;
+use common::{EventType, github_env, logger_init};
+
+const REPO: &str = "2bndy5/git-bot-feedback";
+const SHA: &str = "DEADBEEF";
+const PR: i64 = 46;
+const MARKER: &str = "<!-- git-bot-feedback -->\n";
+
+/// Get a blocking client for the given `event_t` that talks to the given `server`.
+///
+/// The returned temp file holds the event payload (for PR events).
+fn setup(server: &ServerGuard, event_t: EventType) -> (GithubApiClient, NamedTempFile) {
+    let mut event_payload = NamedTempFile::new().unwrap();
+    if event_t == EventType::PullRequest {
+        let payload = serde_json::json!({
+        "pull_request": {
+            "draft": false,
+            "state": "open",
+            "number": PR,
+            "locked": false,
+        }});
+        event_payload
+            .write_all(payload.to_string().as_bytes())
+            .unwrap();
+    }
+    github_env(&server.url());
+    unsafe {
+        env::set_var("GITHUB_EVENT_NAME", event_t.to_string());
+        env::set_var("GITHUB_EVENT_PATH", event_payload.path());
+    }
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    (GithubApiClient::new().unwrap(), event_payload)
+}
+
+#[test]
+fn thread_comment() {
+    let mut server = Server::new();
+    let (client, _event_payload) = setup(&server, EventType::Push);
+    let comments_path = format!("/repos/{REPO}/commits/{SHA}/comments");
+    let existing = server
+        .mock("GET", comments_path.as_str())
+        .match_query(Matcher::Any)
+        .with_body("[]")
+        .create();
+    let posted = server
+        .mock("POST", comments_path.as_str())
+        .match_body(Matcher::Regex("Hello".to_string()))
+        .with_status(201)
+        .create();
+    client
+        .post_thread_comment(ThreadCommentOptions {
+            comment: "Hello".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+    existing.assert();
+    posted.assert();
+}
+
+#[test]
+fn step_summary() {
+    let server = Server::new();
+    let (client, _event_payload) = setup(&server, EventType::Push);
+    let step_summary = NamedTempFile::new().unwrap();
+    unsafe {
+        env::set_var("GITHUB_STEP_SUMMARY", step_summary.path());
+    }
+    client
+        .append_step_summary("A summary", SummaryOverflow::default())
+        .unwrap();
+    let summary = fs::read_to_string(step_summary.path()).unwrap();
+    assert!(summary.contains("A summary"));
+}
+
+#[cfg(feature = "file-changes")]
+#[test]
+fn changed_files() {
+    use chrono::Utc;
+    use git_bot_feedback::{FileFilter, LinesChangedOnly};
+
+    const RESET_RATE_LIMIT_HEADER: &str = "x-ratelimit-reset";
+    const REMAINING_RATE_LIMIT_HEADER: &str = "x-ratelimit-remaining";
+
+    let mut server = Server::new();
+    let (client, _event_payload) = setup(&server, EventType::Push);
+    let files_page = server
+        .mock("GET", format!("/repos/{REPO}/commits/{SHA}").as_str())
+        .match_query(Matcher::UrlEncoded("page".to_string(), "1".to_string()))
+        .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+        .with_header(
+            RESET_RATE_LIMIT_HEADER,
+            (Utc::now().timestamp() + 60).to_string().as_str(),
+        )
+        .with_body_from_file(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/assets/file_changes/github/push_files_pg1.json"),
+        )
+        .create();
+    let file_filter = FileFilter::new(&[], &["cpp", "hpp"], None);
+    let files = client
+        .get_list_of_changed_files(&file_filter, &LinesChangedOnly::Off, None, false)
+        .unwrap();
+    assert!(files.contains_key("src/demo.cpp"));
+    files_page.assert();
+}
+
+#[test]
+fn pr_review() {
+    const REVIEW_ID: i64 = 2520000000;
+    let mut server = Server::new();
+    let (mut client, _event_payload) = setup(&server, EventType::PullRequest);
+    let review_threads = server
+        .mock("POST", "/graphql")
+        .match_body(Matcher::Regex(
+            r#"(?s).*"query":"query.*reviewThreads.*"#.to_string(),
+        ))
+        .with_body_from_file(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/assets/reviews/github/reviews_threads_empty.json"),
+        )
+        .create();
+    let reviews_path = format!("/repos/{REPO}/pulls/{PR}/reviews");
+    let existing_reviews = server
+        .mock("GET", reviews_path.as_str())
+        .match_query(Matcher::Any)
+        .with_body("[]")
+        .create();
+    let posted = server
+        .mock("POST", reviews_path.as_str())
+        .match_body(Matcher::PartialJson(serde_json::json!({
+            "event": "COMMENT",
+            "body": format!("{MARKER}A summary"),
+        })))
+        .with_body(serde_json::json!({"id": REVIEW_ID}).to_string())
+        .create();
+    let mut options = ReviewOptions {
+        marker: MARKER.to_string(),
+        summary: "A summary".to_string(),
+        comments: vec![ReviewComment {
+            line_start: None,
+            line_end: 42,
+            comment: "A comment".to_string(),
+            path: "src/lib.rs".to_string(),
+        }],
+        ..Default::default()
+    };
+    client.cull_pr_reviews(&mut options).unwrap();
+    let outcome = client.post_pr_review(&options).unwrap();
+    assert_eq!(outcome.review_id, Some(REVIEW_ID));
+    assert_eq!(outcome.posted_comments, 1);
+    review_threads.assert();
+    existing_reviews.assert();
+    posted.assert();
+}
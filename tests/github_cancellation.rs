@@ -0,0 +1,110 @@
+#![cfg(feature = "github")]
+
+use git_bot_feedback::{
+    RestApiRateLimitHeaders, RestClientError, ThreadCommentOptions,
+    client::{CancellationToken, init_client},
+    reqwest::{Client, Method, Url},
+};
+use mockito::{Matcher, Server};
+
+mod common;
+use common::{github_env, logger_init};
+
+const REPO: &str = "2bndy5/git-bot-feedback";
+const SHA: &str = "DEADBEEF";
+
+fn setup(server: &Server) {
+    github_env(&server.url());
+    logger_init();
+}
+
+#[tokio::test]
+async fn cancel_between_pages() {
+    let mut server = Server::new_async().await;
+    setup(&server);
+    let token = CancellationToken::new();
+    let mut client = init_client().unwrap();
+    client.set_cancellation_token(Some(token.clone()));
+    assert!(client.cancellation_token().is_some());
+
+    let comments_path = format!("/repos/{REPO}/commits/{SHA}/comments");
+    let next_page = format!("<{}{comments_path}?page=2>; rel=\"next\"", server.url());
+    let first_page = server
+        .mock("GET", comments_path.as_str())
+        .match_query(Matcher::UrlEncoded("page".to_string(), "1".to_string()))
+        .with_header("link", next_page.as_str())
+        .with_body_from_request(move |_| {
+            // the job is cancelled while the first page is fetched
+            token.cancel();
+            b"[]".to_vec()
+        })
+        .expect(1)
+        .create();
+    let second_page = server
+        .mock("GET", comments_path.as_str())
+        .match_query(Matcher::UrlEncoded("page".to_string(), "2".to_string()))
+        .with_body("[]")
+        .expect(0)
+        .create();
+    let posted = server
+        .mock("POST", comments_path.as_str())
+        .expect(0)
+        .create();
+
+    let err = client
+        .post_thread_comment(ThreadCommentOptions {
+            comment: "Hello".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, RestClientError::Cancelled { .. }),
+        "Expected Cancelled error, got: {err:?}"
+    );
+    first_page.assert();
+    second_page.assert();
+    posted.assert();
+}
+
+#[tokio::test]
+async fn cancel_retry_wait() {
+    let mut server = Server::new_async().await;
+    setup(&server);
+    let token = CancellationToken::new();
+    let mut client = init_client().unwrap();
+    client.set_cancellation_token(Some(token.clone()));
+    token.cancel();
+
+    // a secondary rate limit would otherwise be retried after a long wait
+    let limited = server
+        .mock("GET", "/")
+        .with_status(429)
+        .with_header("retry-after", "30")
+        .expect(1)
+        .create();
+    let http_client = Client::new();
+    let request = client
+        .make_api_request(
+            &http_client,
+            Url::parse(&server.url()).unwrap(),
+            Method::GET,
+            None,
+            None,
+        )
+        .unwrap();
+    let rate_limit_headers = RestApiRateLimitHeaders {
+        reset: "x-ratelimit-reset".to_string(),
+        remaining: "x-ratelimit-remaining".to_string(),
+        retry: "retry-after".to_string(),
+    };
+    let err = client
+        .send_api_request(&http_client, request, &rate_limit_headers)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, RestClientError::Cancelled { .. }),
+        "Expected Cancelled error, got: {err:?}"
+    );
+    limited.assert();
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "github")]
+
+use git_bot_feedback::{
+    RestApiClient, ThreadCommentOptions,
+    client::{GithubApiClient, ResponseCache},
+};
+use mockito::{Matcher, Server};
+
+mod common;
+use common::{github_env, logger_init};
+
+const REPO: &str = "2bndy5/git-bot-feedback";
+const SHA: &str = "DEADBEEF";
+
+const RATE_LIMIT_RESPONSE: &str = r#"{
+  "resources": {
+    "core": { "limit": 5000, "used": 4990, "remaining": 10, "reset": 1760000000 }
+  },
+  "rate": { "limit": 5000, "used": 4990, "remaining": 10, "reset": 1760000000 }
+}"#;
+
+#[tokio::test]
+async fn concurrent_clones() {
+    let mut server = Server::new_async().await;
+    github_env(&server.url());
+    logger_init();
+
+    let rate_limit = server
+        .mock("GET", "/rate_limit")
+        .with_body(RATE_LIMIT_RESPONSE)
+        .expect(1)
+        .create();
+    let comments_path = format!("/repos/{REPO}/commits/{SHA}/comments");
+    let existing = server
+        .mock("GET", comments_path.as_str())
+        .match_query(Matcher::Any)
+        .with_header("x-ratelimit-remaining", "42")
+        .with_body("[]")
+        .expect(1)
+        .create();
+    let posted = server
+        .mock("POST", comments_path.as_str())
+        .match_body(Matcher::Regex("Hello".to_string()))
+        .with_status(201)
+        .expect(1)
+        .create();
+
+    let mut client = GithubApiClient::new().unwrap();
+    client.set_response_cache(Some(ResponseCache::new(4)));
+    let clone = client.clone();
+    // clones share the throttle and response cache
+    assert!(std::ptr::eq(
+        client.throttle().unwrap(),
+        clone.throttle().unwrap()
+    ));
+    assert!(std::ptr::eq(
+        client.response_cache().unwrap(),
+        clone.response_cache().unwrap()
+    ));
+
+    let (status, comment) = tokio::join!(
+        client.rate_limit_status(),
+        clone.post_thread_comment(ThreadCommentOptions {
+            comment: "Hello".to_string(),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(status.unwrap().unwrap().core.remaining, 10);
+    comment.unwrap();
+    rate_limit.assert();
+    existing.assert();
+    posted.assert();
+
+    // clones share the last rate limit snapshot
+    assert_eq!(client.last_rate_limit().unwrap().remaining, Some(42));
+    assert_eq!(client.last_rate_limit(), clone.last_rate_limit());
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "github")]
+use std::env;
+
+use git_bot_feedback::{
+    RestApiClient, RestApiRateLimitHeaders, ThreadCommentOptions,
+    client::init_client,
+    reqwest::{Client, Method, StatusCode, Url},
+};
+use mockito::{Matcher, Server};
+
+mod common;
+use common::{github_env, logger_init};
+
+const MARKER: &str = "<!-- git-bot-feedback -->\n";
+const SHA: &str = "deadbeef";
+const REPO: &str = "2bndy5/git-bot-feedback";
+const MOCK_ASSETS_PATH: &str = "tests/assets/thread_comment/github/";
+
+fn setup(server: &Server) -> Box<dyn RestApiClient + Send + Sync> {
+    github_env(&server.url());
+    unsafe {
+        env::set_var("GITHUB_SHA", SHA);
+    }
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    let mut client = init_client().unwrap();
+    assert!(!client.dry_run());
+    client.set_dry_run(true);
+    assert!(client.dry_run());
+    client
+}
+
+#[tokio::test]
+async fn thread_comment() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server);
+
+    let comments_path = format!("/repos/{REPO}/commits/{SHA}/comments");
+    let existing = server
+        .mock("GET", comments_path.as_str())
+        .match_query(Matcher::Any)
+        .with_body_from_file(format!("{MOCK_ASSETS_PATH}push_comments_{SHA}.json"))
+        .expect(1)
+        .create();
+    let comment_path = format!("/repos/{REPO}/comments/76453652");
+    let mutations = [
+        server
+            .mock("DELETE", comment_path.as_str())
+            .expect(0)
+            .create(),
+        server
+            .mock("PATCH", comment_path.as_str())
+            .expect(0)
+            .create(),
+        server
+            .mock("POST", comments_path.as_str())
+            .expect(0)
+            .create(),
+    ];
+
+    client
+        .post_thread_comment(ThreadCommentOptions {
+            comment: "Attention".to_string(),
+            marker: MARKER.to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    existing.assert();
+    for mock in mutations {
+        mock.assert();
+    }
+}
+
+#[tokio::test]
+async fn graphql_query() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server);
+    let query = server
+        .mock("POST", "/graphql")
+        .match_body(Matcher::Regex("^\\{\"query\":\"query".to_string()))
+        .with_body("{}")
+        .expect(1)
+        .create();
+    let mutation = server
+        .mock("POST", "/graphql")
+        .match_body(Matcher::Regex("mutation".to_string()))
+        .expect(0)
+        .create();
+
+    let http_client = Client::new();
+    let url = Url::parse(&server.url()).unwrap().join("/graphql").unwrap();
+    let rate_limit_headers = RestApiRateLimitHeaders {
+        reset: "x-ratelimit-reset".to_string(),
+        remaining: "x-ratelimit-remaining".to_string(),
+        retry: "retry-after".to_string(),
+    };
+    for (body, status) in [
+        (r#"{"query":"query { viewer { login } }"}"#, StatusCode::OK),
+        (
+            r#"{"query":"mutation { minimizeComment }"}"#,
+            StatusCode::NO_CONTENT,
+        ),
+    ] {
+        let request = client
+            .make_api_request(
+                &http_client,
+                url.clone(),
+                Method::POST,
+                Some(body.to_string()),
+                None,
+            )
+            .unwrap();
+        let response = client
+            .send_api_request(&http_client, request, &rate_limit_headers)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), status);
+    }
+    query.assert();
+    mutation.assert();
+}
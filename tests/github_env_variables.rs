@@ -0,0 +1,173 @@
+#![cfg(feature = "github")]
+use git_bot_feedback::{OutputVariable, RestClientError, client::init_client};
+use mockito::Server;
+use std::{env, io::Read, path::Path};
+use tempfile::{NamedTempFile, tempdir};
+mod common;
+use common::logger_init;
+
+#[derive(Debug, Default)]
+struct TestParams {
+    fail_file: bool,
+    absent: bool,
+    bad_var: bool,
+    empty_pairs: bool,
+    multi_line: bool,
+}
+const REPO: &str = "2bndy5/git-bot-feedback";
+const SHA: &str = "DEADBEEF";
+
+const VAR_NAME: &str = "STEP_ENV_VAR";
+const VAR_VALUE: &str = "some data";
+const MULTI_LINE_VALUE: &str = "line 1\n  line 2\n\nline=4";
+
+async fn append_env_vars(test_params: TestParams) -> String {
+    let tmp_dir = tempdir().unwrap();
+    let mut env_var_path = NamedTempFile::new_in(tmp_dir.path()).unwrap();
+    if test_params.absent {
+        unsafe {
+            env::remove_var("GITHUB_ENV");
+        }
+    } else {
+        unsafe {
+            env::set_var(
+                "GITHUB_ENV",
+                if test_params.fail_file {
+                    Path::new("not-a-file.txt")
+                } else {
+                    env_var_path.path()
+                },
+            );
+        }
+    }
+
+    unsafe {
+        env::set_var("GITHUB_ACTIONS", "true");
+        env::remove_var("GITEA_ACTIONS");
+        env::set_var("GITHUB_REPOSITORY", REPO);
+        env::set_var("GITHUB_SHA", SHA);
+        env::set_var("CI", "true");
+        env::set_var("GITHUB_EVENT_NAME", "push");
+    };
+    let server = Server::new_async().await;
+    unsafe {
+        env::set_var("GITHUB_API_URL", server.url());
+    }
+
+    env::set_current_dir(tmp_dir.path()).unwrap();
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    let client = init_client().unwrap();
+    assert_eq!(client.client_kind().as_str(), "github");
+
+    let env_vars = if test_params.bad_var {
+        [OutputVariable {
+            name: VAR_NAME.to_string(),
+            value: "bad\x07value".to_string(),
+        }]
+    } else if test_params.multi_line {
+        [OutputVariable {
+            name: VAR_NAME.to_string(),
+            value: MULTI_LINE_VALUE.to_string(),
+        }]
+    } else {
+        [OutputVariable {
+            name: VAR_NAME.to_string(),
+            value: VAR_VALUE.to_string(),
+        }]
+    };
+    let mut env_vars_content = String::new();
+    match client.export_environment_variables(if test_params.empty_pairs {
+        &[]
+    } else {
+        &env_vars
+    }) {
+        Ok(_) => {
+            env_var_path.read_to_string(&mut env_vars_content).unwrap();
+        }
+        Err(e) => {
+            eprintln!("Encountered error: {e}");
+            if test_params.fail_file {
+                assert!(matches!(e, RestClientError::Io { .. }));
+            } else if test_params.bad_var {
+                assert!(matches!(e, RestClientError::OutputVar { .. }));
+            } else if test_params.absent {
+                assert!(matches!(e, RestClientError::EnvVar { .. }));
+            } else {
+                panic!("Unexpected failure to write to GITHUB_ENV");
+            }
+        }
+    }
+    env_vars_content
+}
+
+#[tokio::test]
+async fn fail_gh_env() {
+    let out = append_env_vars(TestParams {
+        fail_file: true,
+        ..Default::default()
+    })
+    .await;
+    assert!(out.is_empty());
+}
+
+#[tokio::test]
+async fn pass_gh_env() {
+    let out = append_env_vars(TestParams::default()).await;
+    assert!(out.contains(format!("{VAR_NAME}={VAR_VALUE}\n").as_str()));
+}
+
+/// Parse the value of a `name<<DELIMITER` entry like the CI runner would.
+fn parse_heredoc(content: &str, name: &str) -> String {
+    let mut lines = content.lines();
+    let header = format!("{name}<<");
+    let delimiter = lines
+        .by_ref()
+        .find_map(|line| line.strip_prefix(header.as_str()))
+        .unwrap();
+    lines
+        .take_while(|line| *line != delimiter)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[tokio::test]
+async fn multi_line_gh_env() {
+    let out = append_env_vars(TestParams {
+        multi_line: true,
+        ..Default::default()
+    })
+    .await;
+    assert!(!out.contains(format!("{VAR_NAME}=").as_str()));
+    assert_eq!(parse_heredoc(&out, VAR_NAME), MULTI_LINE_VALUE);
+}
+
+#[tokio::test]
+async fn absent_gh_env() {
+    let out = append_env_vars(TestParams {
+        absent: true,
+        ..Default::default()
+    })
+    .await;
+    assert!(out.is_empty());
+}
+
+#[tokio::test]
+async fn bad_var_val() {
+    let out = append_env_vars(TestParams {
+        bad_var: true,
+        ..Default::default()
+    })
+    .await;
+    assert!(out.is_empty());
+}
+
+#[tokio::test]
+async fn empty_pairs() {
+    let out = append_env_vars(TestParams {
+        empty_pairs: true,
+        ..Default::default()
+    })
+    .await;
+    assert!(out.is_empty());
+}
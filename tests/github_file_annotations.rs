@@ -1,13 +1,21 @@
 #![cfg(feature = "github")]
-use git_bot_feedback::{FileAnnotation, client::init_client};
+use git_bot_feedback::{
+    AnnotationLevel, AnnotationOverflow, FileAnnotation, RestClientError,
+    client::{CommandSink, init_client},
+};
 use mockito::Server;
-use std::env;
+use std::{
+    env,
+    sync::{Arc, Mutex},
+};
 mod common;
 use common::logger_init;
 
 #[derive(Debug, Default)]
 struct TestParams {
     empty_array: bool,
+    too_many_warnings: bool,
+    overflow: AnnotationOverflow,
 }
 
 const REPO: &str = "2bndy5/git-bot-feedback";
@@ -29,17 +37,50 @@ async fn write_annotations(test_params: TestParams) {
 
     logger_init();
     log::set_max_level(log::LevelFilter::Debug);
-    let client = init_client().unwrap();
+    let mut client = init_client().unwrap();
+    let output = Arc::new(Mutex::new(Vec::<u8>::new()));
+    client.set_command_sink(CommandSink::Writer(output.clone()));
 
     let annotations = if test_params.empty_array {
         vec![]
+    } else if test_params.too_many_warnings {
+        (0..25)
+            .map(|i| FileAnnotation {
+                severity: AnnotationLevel::Warning,
+                message: format!("Test warning {i}"),
+                ..Default::default()
+            })
+            .collect()
     } else {
         vec![FileAnnotation {
             message: "Test annotation".to_string(),
             ..Default::default()
         }]
     };
-    client.write_file_annotations(&annotations).unwrap();
+    let result = client.write_file_annotations(&annotations, test_params.overflow);
+    let mut expected = Vec::new();
+    if test_params.too_many_warnings && test_params.overflow == AnnotationOverflow::Error {
+        assert!(matches!(
+            result,
+            Err(RestClientError::TooManyAnnotations { count: 25, .. })
+        ));
+    } else if test_params.too_many_warnings {
+        // GitHub only displays 10 annotations per severity
+        assert_eq!(result.unwrap(), 10);
+        if test_params.overflow == AnnotationOverflow::Summarize {
+            expected.extend((0..9).map(|i| format!("::warning::Test warning {i}")));
+            expected.push("::warning::16 more findings omitted; see step summary".to_string());
+        } else {
+            expected.extend((0..10).map(|i| format!("::warning::Test warning {i}")));
+        }
+    } else {
+        assert_eq!(result.unwrap(), annotations.len());
+        if !test_params.empty_array {
+            expected.push("::notice::Test annotation".to_string());
+        }
+    }
+    let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+    assert_eq!(output.lines().collect::<Vec<_>>(), expected);
 }
 
 #[tokio::test]
@@ -49,5 +90,38 @@ async fn some_annotations() {
 
 #[tokio::test]
 async fn empty_annotations() {
-    write_annotations(TestParams { empty_array: true }).await;
+    write_annotations(TestParams {
+        empty_array: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn truncate_annotations() {
+    write_annotations(TestParams {
+        too_many_warnings: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn summarize_annotations() {
+    write_annotations(TestParams {
+        too_many_warnings: true,
+        overflow: AnnotationOverflow::Summarize,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn error_on_too_many_annotations() {
+    write_annotations(TestParams {
+        too_many_warnings: true,
+        overflow: AnnotationOverflow::Error,
+        ..Default::default()
+    })
+    .await;
 }
@@ -1,14 +1,15 @@
 #![cfg(all(feature = "file-changes", feature = "github"))]
 use chrono::Utc;
 mod common;
-use common::{EventType, logger_init};
+use common::{EventType, capture_logs, logger_init, take_captured_logs};
 use mockito::{Matcher, Server};
 use tempfile::{NamedTempFile, TempDir};
 
 use git_bot_feedback::{
-    DiffHunkHeader, FileFilter, LinesChangedOnly, RestClientError, client::init_client,
+    DiffHunkHeader, FileFilter, FileStatus, LinesChangedOnly, RestClientError,
+    client::{DEFAULT_CHANGED_FILES_PER_PAGE, USER_AGENT as DEFAULT_USER_AGENT, init_client},
 };
-use std::{env, io::Write, path::Path};
+use std::{env, fs, io::Write, path::Path};
 
 #[derive(Default)]
 struct TestParams {
@@ -17,8 +18,30 @@ struct TestParams {
     fail_serde_event_payload: bool,
     no_event_payload: bool,
     fail_request: bool,
+    /// Fetch all files in a single page of this size.
+    per_page: Option<u32>,
+    /// Include deleted files in the results.
+    include_deleted: bool,
+    /// The range of commits in a push event's payload.
+    push_range: PushRange,
 }
 
+/// Describes the range of commits in a push event's payload.
+#[derive(Default, PartialEq)]
+enum PushRange {
+    /// The payload has no range of commits.
+    #[default]
+    Absent,
+    /// The pushed commits are compared.
+    Compare,
+    /// The `before` commit is unknown to the server (eg. after a force-push).
+    ForcePush,
+    /// The push created a branch (the `before` commit is all zeros).
+    NewBranch,
+}
+
+const BEFORE: &str = "C0FFEE";
+
 const REPO: &str = "2bndy5/git-bot-feedback";
 const SHA: &str = "DEADBEEF";
 const PR: u8 = 42;
@@ -41,6 +64,21 @@ async fn get_paginated_changes(lib_root: &Path, test_params: &TestParams) {
         event_payload
             .write_all(EVENT_PAYLOAD.as_bytes())
             .expect("Failed to write data to test event payload file")
+    } else if test_params.push_range != PushRange::Absent {
+        let before = if test_params.push_range == PushRange::NewBranch {
+            "0".repeat(40)
+        } else {
+            BEFORE.to_string()
+        };
+        event_payload
+            .write_all(
+                format!(
+                    r#"{{"before": "{before}", "after": "{SHA}", "forced": {}}}"#,
+                    test_params.push_range == PushRange::ForcePush
+                )
+                .as_bytes(),
+            )
+            .expect("Failed to write data to test event payload file")
     }
 
     unsafe {
@@ -96,24 +134,51 @@ async fn get_paginated_changes(lib_root: &Path, test_params: &TestParams) {
         }
     };
     client.set_user_agent(USER_AGENT).unwrap();
+    assert_eq!(
+        client.changed_files_per_page(),
+        Some(DEFAULT_CHANGED_FILES_PER_PAGE)
+    );
+    if let Some(per_page) = test_params.per_page {
+        client.set_changed_files_per_page(per_page);
+    }
+    let per_page = test_params
+        .per_page
+        .unwrap_or(DEFAULT_CHANGED_FILES_PER_PAGE)
+        .to_string();
 
     let mut mocks = vec![];
-    let diff_end_point = format!(
-        "/repos/{REPO}/{}",
-        if EventType::PullRequest == test_params.event_t {
-            format!("pulls/{PR}/files")
-        } else {
-            format!("commits/{SHA}")
-        }
-    );
-    let pg_count = if test_params.fail_serde_diff || test_params.fail_request {
+    let compare_end_point = format!("/repos/{REPO}/compare/{BEFORE}...{SHA}");
+    if test_params.push_range == PushRange::ForcePush {
+        mocks.push(
+            server
+                .mock("GET", compare_end_point.as_str())
+                .match_query(Matcher::Any)
+                .with_status(404)
+                .with_body(r#"{"message":"Not Found"}"#)
+                .create(),
+        );
+    }
+    let diff_end_point = if EventType::PullRequest == test_params.event_t {
+        format!("/repos/{REPO}/pulls/{PR}/files")
+    } else if test_params.push_range == PushRange::Compare {
+        compare_end_point
+    } else {
+        format!("/repos/{REPO}/commits/{SHA}")
+    };
+    let pg_count = if test_params.fail_serde_diff
+        || test_params.fail_request
+        || test_params.per_page.is_some()
+    {
         1
     } else {
         2
     };
     for pg in 1..=pg_count {
-        let link = if pg == 1 {
-            format!("<{}{diff_end_point}?page=2>; rel=\"next\"", server.url())
+        let link = if pg < pg_count {
+            format!(
+                "<{}{diff_end_point}?page=2&per_page={per_page}>; rel=\"next\"",
+                server.url()
+            )
         } else {
             "".to_string()
         };
@@ -121,8 +186,14 @@ async fn get_paginated_changes(lib_root: &Path, test_params: &TestParams) {
             .mock("GET", diff_end_point.as_str())
             .match_header("Accept", "application/vnd.github.raw+json")
             .match_header("Authorization", format!("token {TOKEN}").as_str())
-            .match_header("user-agent", USER_AGENT)
-            .match_query(Matcher::UrlEncoded("page".to_string(), pg.to_string()))
+            .match_header(
+                "user-agent",
+                format!("{USER_AGENT} {DEFAULT_USER_AGENT}").as_str(),
+            )
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("page".to_string(), pg.to_string()),
+                Matcher::UrlEncoded("per_page".to_string(), per_page.clone()),
+            ]))
             .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
             .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
             .with_header("link", link.as_str());
@@ -131,14 +202,16 @@ async fn get_paginated_changes(lib_root: &Path, test_params: &TestParams) {
         } else if test_params.fail_request {
             mock = mock.with_status(404).with_body(MALFORMED_RESPONSE_PAYLOAD);
         } else {
-            mock = mock.with_body_from_file(format!(
-                "{asset_path}/{}_files_pg{pg}.json",
-                if test_params.event_t == EventType::Push {
-                    "push"
-                } else {
-                    "pr"
-                }
-            ));
+            let event = if test_params.event_t == EventType::Push {
+                "push"
+            } else {
+                "pr"
+            };
+            if test_params.per_page.is_some() {
+                mock = mock.with_body(merge_pages(&asset_path, event));
+            } else {
+                mock = mock.with_body_from_file(format!("{asset_path}/{event}_files_pg{pg}.json"));
+            }
         }
         mocks.push(mock.create());
     }
@@ -148,7 +221,8 @@ async fn get_paginated_changes(lib_root: &Path, test_params: &TestParams) {
     } else {
         None
     };
-    let file_filter = FileFilter::new(&["", "!src/*"], &["cpp", "hpp"], log_scope);
+    let mut file_filter = FileFilter::new(&["", "!src/*"], &["cpp", "hpp"], log_scope);
+    file_filter.include_deleted = test_params.include_deleted;
     assert!(file_filter.is_file_ignored(Path::new("./Cargo.toml")));
     let files = client
         .get_list_of_changed_files(&file_filter, &LinesChangedOnly::Off, None, false)
@@ -169,10 +243,31 @@ async fn get_paginated_changes(lib_root: &Path, test_params: &TestParams) {
             }
         }
         Ok(files) => {
-            assert_eq!(files.len(), 2);
+            assert_eq!(files.len(), if test_params.include_deleted { 4 } else { 3 });
             for (file, diff_ctx) in files {
-                assert!(["src/demo.cpp", "src/demo.hpp"].contains(&file.as_str()));
-                if file == "src/demo.hpp" {
+                assert!(
+                    [
+                        "src/demo.cpp",
+                        "src/demo.hpp",
+                        "src/util.hpp",
+                        "src/old.hpp"
+                    ]
+                    .contains(&file.as_str())
+                );
+                if file == "src/old.hpp" {
+                    assert_eq!(diff_ctx.status, FileStatus::Removed);
+                    assert!(diff_ctx.added_lines.is_empty());
+                    assert!(diff_ctx.diff_hunks.is_empty());
+                } else if file == "src/demo.cpp" {
+                    assert_eq!(diff_ctx.status, FileStatus::Renamed);
+                    assert_eq!(diff_ctx.previous_path.as_deref(), Some("src/demo.c"));
+                } else if file == "src/util.hpp" {
+                    assert_eq!(diff_ctx.status, FileStatus::Added);
+                    assert!(diff_ctx.previous_path.is_none());
+                    assert_eq!(diff_ctx.added_lines, vec![1, 2, 3]);
+                } else {
+                    assert_eq!(diff_ctx.status, FileStatus::Modified);
+                    assert!(diff_ctx.previous_path.is_none());
                     let diff_hunk = DiffHunkHeader {
                         old_start: 5,
                         old_lines: 10,
@@ -196,6 +291,27 @@ async fn get_paginated_changes(lib_root: &Path, test_params: &TestParams) {
     }
 }
 
+/// Merge the files listed in both pages of the test assets into a single page.
+fn merge_pages(asset_path: &str, event: &str) -> String {
+    let mut pages = (1..=2).map(|pg| {
+        let page = fs::read_to_string(format!("{asset_path}/{event}_files_pg{pg}.json")).unwrap();
+        serde_json::from_str::<serde_json::Value>(&page).unwrap()
+    });
+    let mut merged = pages.next().unwrap();
+    for page in pages {
+        let (files, more_files) = if event == "push" {
+            (&mut merged["files"], page["files"].clone())
+        } else {
+            (&mut merged, page)
+        };
+        files
+            .as_array_mut()
+            .unwrap()
+            .extend(more_files.as_array().unwrap().iter().cloned());
+    }
+    merged.to_string()
+}
+
 async fn test_get_changes(test_params: &TestParams) {
     let tmp_dir = TempDir::new().unwrap();
     let lib_root = env::current_dir().unwrap();
@@ -219,6 +335,81 @@ async fn get_pr_files_paginated() {
     .await
 }
 
+#[tokio::test]
+async fn get_push_files_single_page() {
+    test_get_changes(&TestParams {
+        per_page: Some(300),
+        ..Default::default()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn get_pr_files_single_page() {
+    test_get_changes(&TestParams {
+        event_t: EventType::PullRequest,
+        per_page: Some(300),
+        ..Default::default()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn get_push_files_with_deleted() {
+    test_get_changes(&TestParams {
+        include_deleted: true,
+        ..Default::default()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn get_pr_files_with_deleted() {
+    test_get_changes(&TestParams {
+        event_t: EventType::PullRequest,
+        include_deleted: true,
+        ..Default::default()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn get_compared_push_files_paginated() {
+    test_get_changes(&TestParams {
+        push_range: PushRange::Compare,
+        ..Default::default()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn get_compared_push_files_single_page() {
+    test_get_changes(&TestParams {
+        push_range: PushRange::Compare,
+        per_page: Some(300),
+        ..Default::default()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn get_force_pushed_files() {
+    test_get_changes(&TestParams {
+        push_range: PushRange::ForcePush,
+        ..Default::default()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn get_new_branch_pushed_files() {
+    test_get_changes(&TestParams {
+        push_range: PushRange::NewBranch,
+        ..Default::default()
+    })
+    .await
+}
+
 #[tokio::test]
 async fn fail_push_files_paginated() {
     test_get_changes(&TestParams {
@@ -267,3 +458,262 @@ async fn fail_request() {
     })
     .await
 }
+
+/// How the server responds to requests for the full diff.
+#[derive(PartialEq)]
+enum FullDiff {
+    /// The full diff is provided.
+    Available,
+    /// The full diff is too large (GitHub responds with `406`).
+    TooLarge,
+    /// The full diff is not requested (see `RestApiClient::set_diff_fallback()`).
+    Disabled,
+}
+
+const ASSET_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/assets/file_changes/github"
+);
+
+/// Set up the environment for a GitHub client whose API is mocked by the given `server`.
+///
+/// The returned file holds the event payload (and must outlive the client's instantiation).
+fn setup_env(server: &Server, event_t: EventType) -> NamedTempFile {
+    let mut event_payload = NamedTempFile::new().unwrap();
+    if event_t == EventType::PullRequest {
+        event_payload.write_all(EVENT_PAYLOAD.as_bytes()).unwrap();
+    }
+    unsafe {
+        env::set_var("GITHUB_ACTIONS", "true");
+        env::remove_var("GITEA_ACTIONS");
+        env::set_var("GITHUB_REPOSITORY", REPO);
+        env::set_var("GITHUB_SHA", SHA);
+        env::set_var("GITHUB_TOKEN", TOKEN);
+        env::set_var("GITHUB_EVENT_NAME", event_t.to_string());
+        env::set_var("GITHUB_EVENT_PATH", event_payload.path());
+        env::set_var("GITHUB_API_URL", server.url());
+    }
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    event_payload
+}
+
+/// List a PR's changed files in which the patch of `src/demo.cpp` is omitted.
+async fn test_omitted_patch(full_diff: FullDiff, lines_changed_only: LinesChangedOnly) {
+    let mut server = Server::new_async().await;
+    let _event_payload = setup_env(&server, EventType::PullRequest);
+    let mut client = init_client().unwrap();
+    assert_eq!(client.diff_fallback(), Some(true));
+    if full_diff == FullDiff::Disabled {
+        client.set_diff_fallback(false);
+    }
+
+    let files_page = server
+        .mock("GET", format!("/repos/{REPO}/pulls/{PR}/files").as_str())
+        .match_query(Matcher::Any)
+        .with_body_from_file(format!("{ASSET_PATH}/pr_files_omitted_patch.json"))
+        .create();
+    let diff_request = server
+        .mock("GET", format!("/repos/{REPO}/pulls/{PR}").as_str())
+        .match_header("Accept", "application/vnd.github.diff")
+        .expect(if full_diff == FullDiff::Disabled {
+            0
+        } else {
+            1
+        });
+    let diff_request = if full_diff == FullDiff::TooLarge {
+        diff_request
+            .with_status(406)
+            .with_body(r#"{"message":"Sorry, the diff exceeded the maximum number of files"}"#)
+    } else {
+        diff_request.with_body_from_file(format!("{ASSET_PATH}/pr_omitted_patch.diff"))
+    }
+    .create();
+
+    let file_filter = FileFilter::new(&[], &["cpp", "hpp"], None);
+    let files = client
+        .get_list_of_changed_files(&file_filter, &lines_changed_only, None, false)
+        .await
+        .unwrap();
+    assert!(files.contains_key("src/demo.hpp"));
+    match (full_diff, lines_changed_only) {
+        (FullDiff::Available, _) => {
+            let demo_cpp = files.get("src/demo.cpp").unwrap();
+            assert_eq!(demo_cpp.status, FileStatus::Modified);
+            assert!(!demo_cpp.added_lines.is_empty());
+            assert!(demo_cpp.is_line_in_diff(&1));
+        }
+        (_, LinesChangedOnly::Off) => {
+            // listed without any changed lines
+            let demo_cpp = files.get("src/demo.cpp").unwrap();
+            assert!(demo_cpp.added_lines.is_empty());
+            assert!(demo_cpp.diff_hunks.is_empty());
+        }
+        _ => assert!(!files.contains_key("src/demo.cpp")),
+    }
+    files_page.assert();
+    diff_request.assert();
+}
+
+#[tokio::test]
+async fn omitted_patch_from_full_diff() {
+    test_omitted_patch(FullDiff::Available, LinesChangedOnly::On).await;
+}
+
+#[tokio::test]
+async fn omitted_patch_full_diff_too_large() {
+    test_omitted_patch(FullDiff::TooLarge, LinesChangedOnly::Off).await;
+}
+
+#[tokio::test]
+async fn omitted_patch_full_diff_too_large_changes_only() {
+    test_omitted_patch(FullDiff::TooLarge, LinesChangedOnly::Diff).await;
+}
+
+#[tokio::test]
+async fn omitted_patch_fallback_disabled() {
+    test_omitted_patch(FullDiff::Disabled, LinesChangedOnly::Off).await;
+}
+
+/// List a commit's `listed` changed files, where GitHub lists at most 300 files.
+async fn test_truncated_listing(listed: usize) {
+    let mut server = Server::new_async().await;
+    let _event_payload = setup_env(&server, EventType::Push);
+    let client = init_client().unwrap();
+
+    let files = (0..listed)
+        .map(|i| {
+            serde_json::json!({
+                "filename": format!("docs/page{i}.md"),
+                "status": "added",
+                "changes": 1,
+                "patch": "@@ -0,0 +1 @@\n+# Page",
+            })
+        })
+        .collect::<Vec<_>>();
+    let end_point = format!("/repos/{REPO}/commits/{SHA}");
+    let files_page = server
+        .mock("GET", end_point.as_str())
+        .match_header("Accept", "application/vnd.github.raw+json")
+        .match_query(Matcher::Any)
+        .with_body(serde_json::json!({ "files": files }).to_string())
+        .create();
+    let diff_request = server
+        .mock("GET", end_point.as_str())
+        .match_header("Accept", "application/vnd.github.diff")
+        .with_body_from_file(format!("{ASSET_PATH}/pr_omitted_patch.diff"))
+        .expect(if listed < 300 { 0 } else { 1 })
+        .create();
+
+    let file_filter = FileFilter::new(&[], &["cpp", "hpp"], None);
+    let files = client
+        .get_list_of_changed_files(&file_filter, &LinesChangedOnly::Off, None, false)
+        .await
+        .unwrap();
+    if listed < 300 {
+        // none of the listed files are qualified
+        assert!(files.is_empty());
+    } else {
+        // files are parsed from the full diff
+        assert_eq!(files.len(), 2);
+        assert!(files.get("src/demo.cpp").unwrap().is_line_in_diff(&1));
+        assert!(files.contains_key("src/demo.hpp"));
+    }
+    files_page.assert();
+    diff_request.assert();
+}
+
+#[tokio::test]
+async fn truncated_listing_uses_full_diff() {
+    test_truncated_listing(300).await;
+}
+
+#[tokio::test]
+async fn listing_under_cap() {
+    test_truncated_listing(299).await;
+}
+
+#[tokio::test]
+async fn no_extensions_means_all_files() {
+    let mut server = Server::new_async().await;
+    let _event_payload = setup_env(&server, EventType::Push);
+    let client = init_client().unwrap();
+
+    let names = ["README.md", "src/lib.rs", "src/demo.cpp"];
+    let files = names
+        .iter()
+        .map(|name| {
+            serde_json::json!({
+                "filename": name,
+                "status": "modified",
+                "changes": 1,
+                "patch": "@@ -1 +1 @@\n-old\n+new",
+            })
+        })
+        .collect::<Vec<_>>();
+    let files_page = server
+        .mock("GET", format!("/repos/{REPO}/commits/{SHA}").as_str())
+        .match_query(Matcher::Any)
+        .with_body(serde_json::json!({ "files": files }).to_string())
+        .create();
+
+    let file_filter = FileFilter::new(&[], &[], None);
+    let files = client
+        .get_list_of_changed_files(&file_filter, &LinesChangedOnly::Off, None, false)
+        .await
+        .unwrap();
+    assert_eq!(files.len(), names.len());
+    for name in names {
+        assert!(files.get(name).unwrap().is_line_in_diff(&1));
+    }
+    files_page.assert();
+}
+
+#[tokio::test]
+async fn ignored_files_are_not_parsed() {
+    let mut server = Server::new_async().await;
+    let _event_payload = setup_env(&server, EventType::Push);
+    let client = init_client().unwrap();
+
+    let files = [
+        ("third_party/lib.cpp", "modified", None),
+        // judged by its new path
+        ("src/demo.cpp", "renamed", Some("third_party/demo.cpp")),
+        ("third_party/moved.cpp", "renamed", Some("src/moved.cpp")),
+    ]
+    .map(|(name, status, previous_name)| {
+        serde_json::json!({
+            "filename": name,
+            "status": status,
+            "previous_filename": previous_name,
+            "changes": 2,
+            "patch": "@@ -1 +1 @@\n-old\n+new",
+        })
+    });
+    let files_page = server
+        .mock("GET", format!("/repos/{REPO}/commits/{SHA}").as_str())
+        .match_query(Matcher::Any)
+        .with_body(serde_json::json!({ "files": files }).to_string())
+        .create();
+
+    let file_filter = FileFilter::new(&["third_party/*"], &["cpp"], None);
+    capture_logs();
+    let files = client
+        .get_list_of_changed_files(&file_filter, &LinesChangedOnly::Off, None, false)
+        .await
+        .unwrap();
+    let logs = take_captured_logs();
+    assert_eq!(files.len(), 1);
+    let demo_cpp = files.get("src/demo.cpp").unwrap();
+    assert_eq!(demo_cpp.status, FileStatus::Renamed);
+    assert_eq!(
+        demo_cpp.previous_path.as_deref(),
+        Some("third_party/demo.cpp")
+    );
+    for name in ["third_party/lib.cpp", "third_party/moved.cpp"] {
+        // skipped before its patch was assembled (and parsed)
+        let skipped = format!("file {name:?} is ignored with domain \"third_party/*\".");
+        assert_eq!(logs.iter().filter(|msg| **msg == skipped).count(), 1);
+    }
+    files_page.assert();
+}
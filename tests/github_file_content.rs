@@ -0,0 +1,95 @@
+#![cfg(feature = "github")]
+
+use git_bot_feedback::{RestApiClient, RestClientError, client::GithubApiClient};
+use mockito::{Matcher, Server};
+
+mod common;
+use common::{github_env, logger_init};
+
+const REPO: &str = "2bndy5/git-bot-feedback";
+const SHA: &str = "DEADBEEF";
+const CONTENT: &str = "# Notes\n\nSome notes.\n";
+
+fn setup(server: &Server) -> GithubApiClient {
+    logger_init();
+    github_env(&server.url());
+    GithubApiClient::new().unwrap()
+}
+
+/// Mock the request for the content of `docs/my notes.md` at the given `reference`.
+fn mock_content(server: &mut Server, reference: &str) -> mockito::Mock {
+    server
+        .mock(
+            "GET",
+            format!("/repos/{REPO}/contents/docs/my%20notes.md").as_str(),
+        )
+        .match_query(Matcher::UrlEncoded(
+            "ref".to_string(),
+            reference.to_string(),
+        ))
+        .match_header("Accept", "application/vnd.github.raw+json")
+        .expect(1)
+}
+
+#[tokio::test]
+async fn content_at_event_sha() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server);
+    let mock = mock_content(&mut server, SHA).with_body(CONTENT).create();
+    let content = client
+        .get_file_content("docs/my notes.md", None)
+        .await
+        .unwrap();
+    assert_eq!(content, CONTENT);
+    mock.assert();
+}
+
+#[tokio::test]
+async fn content_at_reference() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server);
+    let mock = mock_content(&mut server, "main")
+        .with_body(CONTENT)
+        .create();
+    let content = client
+        .get_file_content("docs/my notes.md", Some("main"))
+        .await
+        .unwrap();
+    assert_eq!(content, CONTENT);
+    mock.assert();
+}
+
+#[tokio::test]
+async fn binary_content() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server);
+    let mock = mock_content(&mut server, SHA)
+        .with_body([0x89, b'P', b'N', b'G', 0xff, 0x00])
+        .create();
+    let err = client
+        .get_file_content("docs/my notes.md", None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RestClientError::BinaryFile { path } if path == "docs/my notes.md"));
+    mock.assert();
+}
+
+#[tokio::test]
+async fn missing_file() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server);
+    let mock = mock_content(&mut server, SHA)
+        .with_status(404)
+        .with_body(r#"{"message":"Not Found"}"#)
+        .create();
+    let err = client
+        .get_file_content("docs/my notes.md", None)
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(reqwest::StatusCode::NOT_FOUND));
+    assert_eq!(
+        err.to_string(),
+        "Failed to get content of docs/my notes.md: server responded with 404 Not Found"
+    );
+    mock.assert();
+}
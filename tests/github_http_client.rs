@@ -0,0 +1,92 @@
+#![cfg(feature = "github")]
+
+use git_bot_feedback::{
+    RestClientError,
+    client::{USER_AGENT, init_client},
+    reqwest::{
+        Client,
+        header::{HeaderMap, HeaderValue},
+    },
+};
+use mockito::Server;
+
+mod common;
+use common::{github_env, logger_init};
+
+const RATE_LIMIT_RESPONSE: &str =
+    r#"{"resources":{"core":{"limit":5000,"remaining":4999,"reset":1760000000}}}"#;
+
+#[tokio::test]
+async fn inject_http_client() {
+    let mut server = Server::new_async().await;
+    github_env(&server.url());
+    logger_init();
+    let mut client = init_client().unwrap();
+
+    let mut extra_headers = HeaderMap::new();
+    extra_headers.insert("x-proxy-auth", HeaderValue::from_static("secret"));
+    let http_client = Client::builder()
+        .default_headers(extra_headers)
+        .build()
+        .unwrap();
+    client.set_http_client(http_client);
+    client.set_user_agent("custom-agent/1.0").unwrap();
+
+    let mock = server
+        .mock("GET", "/rate_limit")
+        .match_header("x-proxy-auth", "secret")
+        .match_header(
+            "user-agent",
+            format!("custom-agent/1.0 {USER_AGENT}").as_str(),
+        )
+        .match_header("accept", "application/vnd.github.raw+json")
+        .match_header("authorization", "token 123456")
+        .with_status(200)
+        .with_body(RATE_LIMIT_RESPONSE)
+        .expect(1)
+        .create();
+    let status = client.rate_limit_status().await.unwrap().unwrap();
+    mock.assert();
+    assert_eq!(status.core.remaining, 4999);
+    assert!(status.graphql.is_none());
+}
+
+#[tokio::test]
+async fn custom_user_agent() {
+    let mut server = Server::new_async().await;
+    github_env(&server.url());
+    logger_init();
+    let mut client = init_client().unwrap();
+    assert!(matches!(
+        client.set_user_agent("my-tool\n1.2"),
+        Err(RestClientError::InvalidHeaderValue(_))
+    ));
+
+    let expected = [
+        format!("my-tool/1.2 {USER_AGENT}"),
+        // the suffix is not repeated
+        format!("my-tool/1.2 {USER_AGENT}"),
+        // the default is used if no user agent is given
+        USER_AGENT.to_string(),
+    ];
+    for (user_agent, expected) in [
+        "my-tool/1.2",
+        format!("my-tool/1.2 {USER_AGENT}").as_str(),
+        "",
+    ]
+    .into_iter()
+    .zip(expected)
+    {
+        client.set_user_agent(user_agent).unwrap();
+        let mock = server
+            .mock("GET", "/rate_limit")
+            .match_header("user-agent", expected.as_str())
+            .with_status(200)
+            .with_body(RATE_LIMIT_RESPONSE)
+            .expect(1)
+            .create();
+        client.rate_limit_status().await.unwrap();
+        mock.assert();
+        mock.remove();
+    }
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "github")]
+use std::sync::Arc;
+
+use git_bot_feedback::{
+    RestApiRateLimitHeaders,
+    client::{CountingMetrics, init_client},
+    reqwest::{Client, Method, StatusCode, Url},
+};
+use mockito::Server;
+
+mod common;
+use common::{github_env, logger_init};
+
+#[tokio::test]
+async fn count_retry() {
+    let mut server = Server::new_async().await;
+    github_env(&server.url());
+    logger_init();
+    let metrics = Arc::new(CountingMetrics::default());
+    let mut client = init_client().unwrap();
+    assert!(client.metrics().is_none());
+    client.set_metrics(Some(metrics.clone()));
+    assert!(client.metrics().is_some());
+
+    let unavailable = server.mock("GET", "/").with_status(503).expect(1).create();
+    let passing = server.mock("GET", "/").with_status(200).expect(1).create();
+    let http_client = Client::new();
+    let request = client
+        .make_api_request(
+            &http_client,
+            Url::parse(&server.url()).unwrap(),
+            Method::GET,
+            None,
+            None,
+        )
+        .unwrap();
+    let rate_limit_headers = RestApiRateLimitHeaders {
+        reset: "x-ratelimit-reset".to_string(),
+        remaining: "x-ratelimit-remaining".to_string(),
+        retry: "retry-after".to_string(),
+    };
+    let response = client
+        .send_api_request(&http_client, request, &rate_limit_headers)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    unavailable.assert();
+    passing.assert();
+
+    assert_eq!(metrics.retries(), 1);
+    assert_eq!(metrics.requests(), 2);
+    assert_eq!(
+        metrics.completed(&Method::GET, Some(StatusCode::SERVICE_UNAVAILABLE)),
+        1
+    );
+    assert_eq!(metrics.completed(&Method::GET, Some(StatusCode::OK)), 1);
+    assert!(metrics.rate_limit_sleep().is_zero());
+}
@@ -12,12 +12,15 @@ struct TestParams {
     absent: bool,
     bad_var: bool,
     empty_pairs: bool,
+    multi_line: bool,
+    duplicates: bool,
 }
 const REPO: &str = "2bndy5/git-bot-feedback";
 const SHA: &str = "DEADBEEF";
 
 const VAR_NAME: &str = "STEP_OUTPUT_VAR";
 const VAR_VALUE: &str = "some data";
+const MULTI_LINE_VALUE: &str = "line 1\n  line 2\n\nline=4";
 
 async fn append_output_vars(test_params: TestParams) -> String {
     let tmp_dir = tempdir().unwrap();
@@ -59,12 +62,23 @@ async fn append_output_vars(test_params: TestParams) -> String {
     assert_eq!(client.client_kind().as_str(), "github");
 
     let out_vars = if test_params.bad_var {
-        [OutputVariable {
+        vec![OutputVariable {
             name: VAR_NAME.to_string(),
-            value: "bad\nvalue".to_string(),
+            value: "bad\x07value".to_string(),
         }]
+    } else if test_params.multi_line {
+        vec![OutputVariable {
+            name: VAR_NAME.to_string(),
+            value: MULTI_LINE_VALUE.to_string(),
+        }]
+    } else if test_params.duplicates {
+        vec![
+            OutputVariable::new(VAR_NAME, "first").unwrap(),
+            OutputVariable::new("OTHER_VAR", VAR_VALUE).unwrap(),
+            OutputVariable::new(VAR_NAME, "last").unwrap(),
+        ]
     } else {
-        [OutputVariable {
+        vec![OutputVariable {
             name: VAR_NAME.to_string(),
             value: VAR_VALUE.to_string(),
         }]
@@ -107,7 +121,42 @@ async fn fail_gh_out() {
 #[tokio::test]
 async fn pass_gh_out() {
     let out = append_output_vars(TestParams::default()).await;
-    assert!(out.contains(format!("{VAR_NAME}={VAR_VALUE}\n").as_str()));
+    assert_eq!(out, format!("{VAR_NAME}={VAR_VALUE}\n"));
+}
+
+#[tokio::test]
+async fn duplicate_gh_out() {
+    let out = append_output_vars(TestParams {
+        duplicates: true,
+        ..Default::default()
+    })
+    .await;
+    assert_eq!(out, format!("{VAR_NAME}=last\nOTHER_VAR={VAR_VALUE}\n"));
+}
+
+/// Parse the value of a `name<<DELIMITER` entry like the CI runner would.
+fn parse_heredoc(content: &str, name: &str) -> String {
+    let mut lines = content.lines();
+    let header = format!("{name}<<");
+    let delimiter = lines
+        .by_ref()
+        .find_map(|line| line.strip_prefix(header.as_str()))
+        .unwrap();
+    lines
+        .take_while(|line| *line != delimiter)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[tokio::test]
+async fn multi_line_gh_out() {
+    let out = append_output_vars(TestParams {
+        multi_line: true,
+        ..Default::default()
+    })
+    .await;
+    assert!(!out.contains(format!("{VAR_NAME}=").as_str()));
+    assert_eq!(parse_heredoc(&out, VAR_NAME), MULTI_LINE_VALUE);
 }
 
 #[tokio::test]
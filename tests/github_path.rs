@@ -0,0 +1,103 @@
+#![cfg(feature = "github")]
+use git_bot_feedback::{RestClientError, client::init_client};
+use std::{env, fs, path::Path};
+use tempfile::{NamedTempFile, tempdir};
+mod common;
+use common::logger_init;
+
+#[derive(Debug, Default)]
+struct TestParams {
+    absent: bool,
+    relative: bool,
+    strict: bool,
+}
+
+async fn add_to_path(test_params: TestParams) -> String {
+    let tmp_dir = tempdir().unwrap();
+    let path_file = NamedTempFile::new_in(tmp_dir.path()).unwrap();
+    unsafe {
+        if test_params.absent {
+            env::remove_var("GITHUB_PATH");
+        } else {
+            env::set_var("GITHUB_PATH", path_file.path());
+        }
+        env::set_var("GITHUB_ACTIONS", "true");
+        env::remove_var("GITEA_ACTIONS");
+        env::set_var("GITHUB_REPOSITORY", "2bndy5/git-bot-feedback");
+        env::set_var("GITHUB_SHA", "DEADBEEF");
+        env::set_var("CI", "true");
+        env::set_var("GITHUB_EVENT_NAME", "push");
+    }
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    let mut client = init_client().unwrap();
+    assert_eq!(client.client_kind(), "github");
+    client.set_strict_mode(test_params.strict);
+
+    let bin_dir = tmp_dir.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    if test_params.relative {
+        let err = client.add_to_path(Path::new("bin")).unwrap_err();
+        assert!(
+            matches!(err, RestClientError::Io { .. }),
+            "Expected Io error, got: {err:?}"
+        );
+    } else if test_params.absent && test_params.strict {
+        let err = client.add_to_path(&bin_dir).unwrap_err();
+        assert!(
+            matches!(err, RestClientError::EnvVar { .. }),
+            "Expected EnvVar error, got: {err:?}"
+        );
+    } else {
+        client.add_to_path(&bin_dir).unwrap();
+        // one line per call
+        client.add_to_path(&bin_dir.join("..").join("bin")).unwrap();
+    }
+    fs::read_to_string(path_file.path()).unwrap()
+}
+
+#[tokio::test]
+async fn pass_gh_path() {
+    let content = add_to_path(TestParams::default()).await;
+    let expected = fs::canonicalize(env::temp_dir())
+        .unwrap()
+        .display()
+        .to_string();
+    let lines = content.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], lines[1]);
+    assert!(lines[0].starts_with(expected.as_str()));
+    assert!(lines[0].ends_with("bin"));
+    assert!(!lines[0].contains(".."));
+}
+
+#[tokio::test]
+async fn relative_gh_path() {
+    let content = add_to_path(TestParams {
+        relative: true,
+        ..Default::default()
+    })
+    .await;
+    assert!(content.is_empty());
+}
+
+#[tokio::test]
+async fn absent_gh_path() {
+    let content = add_to_path(TestParams {
+        absent: true,
+        ..Default::default()
+    })
+    .await;
+    assert!(content.is_empty());
+}
+
+#[tokio::test]
+async fn absent_gh_path_strict() {
+    let content = add_to_path(TestParams {
+        absent: true,
+        strict: true,
+        ..Default::default()
+    })
+    .await;
+    assert!(content.is_empty());
+}
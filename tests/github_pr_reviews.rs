@@ -1,7 +1,8 @@
 #![cfg(feature = "github")]
 use chrono::Utc;
 use git_bot_feedback::{
-    RestClientError, ReviewAction, ReviewComment, ReviewOptions, client::init_client,
+    RestClientError, ReviewAction, ReviewComment, ReviewOptions, ReviewSkipReason,
+    client::init_client, reqwest::StatusCode,
 };
 use mockito::{Matcher, Server};
 use std::{collections::HashMap, env, fs, io::Write, path::Path};
@@ -15,6 +16,7 @@ const SHA: &str = "deadbeef";
 const REPO: &str = "2bndy5/git-bot-feedback";
 const PR: i64 = 46;
 const TOKEN: &str = "123456";
+const POSTED_REVIEW_ID: i64 = 2520000000;
 const MOCK_ASSETS_PATH: &str = "tests/assets/reviews/github/";
 
 const RESET_RATE_LIMIT_HEADER: &str = "x-ratelimit-reset";
@@ -33,10 +35,13 @@ struct TestParams {
     bad_existing_comments: bool,
     bad_existing_reviews: bool,
     no_token: bool,
+    dismissal_message: Option<&'static str>,
     action: ReviewAction,
     delete_outdated: bool,
     no_existing_reviews: bool,
+    dismissed_beforehand: bool,
     is_draft: bool,
+    allow_draft: bool,
     is_closed: bool,
     is_locked: bool,
 }
@@ -51,10 +56,13 @@ impl Default for TestParams {
             bad_existing_comments: false,
             bad_existing_reviews: false,
             no_token: false,
+            dismissal_message: None,
             action: ReviewAction::Comment,
             delete_outdated: false,
             no_existing_reviews: false,
+            dismissed_beforehand: false,
             is_draft: false,
+            allow_draft: false,
             is_closed: false,
             is_locked: false,
         }
@@ -129,6 +137,7 @@ impl TestControlVars {
             let node_id = review["node_id"].as_str().unwrap().to_string();
             let id = review["id"].as_i64().unwrap();
             if review["body"].as_str().unwrap().starts_with(MARKER)
+                && review["state"] != "DISMISSED"
                 && (regardless || node_id == "dismiss_this_review")
             {
                 self.outdated_review_ids.insert(id, node_id);
@@ -277,7 +286,11 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
         if !test_params.bad_existing_comments {
             // paginated REST API request for reviews
             for pg in [1, 2] {
-                let artifact = format!("{asset_path}reviews_{PR}_pg{pg}.json");
+                let artifact = if test_params.dismissed_beforehand && pg == 2 {
+                    format!("{asset_path}reviews_{PR}_pg{pg}_dismissed.json")
+                } else {
+                    format!("{asset_path}reviews_{PR}_pg{pg}.json")
+                };
                 let link = if pg == 1 {
                     format!("<{}{review_url_path}?page=2>; rel=\"next\"", server.url())
                 } else {
@@ -381,16 +394,43 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
             );
 
             // REST API request to dismiss existing review
+            let mut mock = server
+                .mock("PUT", format!("{review_url_path}/{id}/dismissals").as_str())
+                .match_body(Matcher::Json(serde_json::json!({
+                    "event": "DISMISS",
+                    "message": test_params
+                        .dismissal_message
+                        .unwrap_or("outdated review (git-bot-feedback)"),
+                })))
+                .with_status(if test_params.fail_dismissal { 403 } else { 200 })
+                .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+                .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+                .expect_at_most(1);
+            if test_params.dismissal_message.is_some() {
+                // ensure the custom message was properly escaped in the request's payload
+                mock = mock.expect(1);
+            }
+            mocks.push(mock.create());
+        }
+
+        if test_params.dismissed_beforehand {
+            // a review that was already dismissed should be neither hidden nor dismissed again
             mocks.push(
                 server
-                    .mock("PUT", format!("{review_url_path}/{id}/dismissals").as_str())
-                    .match_body(Matcher::JsonString(
-                        r#"{"event":"DISMISS","message":"outdated review"}"#.to_string(),
-                    ))
-                    .with_status(if test_params.fail_dismissal { 403 } else { 200 })
-                    .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
-                    .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
-                    .expect_at_most(1)
+                    .mock("POST", "/graphql")
+                    .match_body(Matcher::PartialJson(serde_json::json!({
+                        "variables": {"subjectId": "already_dismissed_review"}
+                    })))
+                    .expect(0)
+                    .create(),
+            );
+            mocks.push(
+                server
+                    .mock(
+                        "PUT",
+                        format!("{review_url_path}/2519000000/dismissals").as_str(),
+                    )
+                    .expect(0)
                     .create(),
             );
         }
@@ -410,7 +450,7 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
     if test_params.event_t == EventType::PullRequest
         && !test_params.no_token
         && !test_params.is_locked
-        && !test_params.is_draft
+        && (!test_params.is_draft || test_params.allow_draft)
         && !test_params.is_closed
     {
         // REST API request to post PR review
@@ -419,7 +459,14 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
             .match_body(new_comment_match)
             .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
             .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
-            .with_status(if test_params.fail_posting { 403 } else { 200 });
+            .with_status(if test_params.fail_posting { 403 } else { 200 })
+            .with_body(
+                serde_json::json!({
+                    "id": POSTED_REVIEW_ID,
+                    "html_url": format!("https://github.com/{REPO}/pull/{PR}#pullrequestreview-{POSTED_REVIEW_ID}"),
+                })
+                .to_string(),
+            );
         if !test_params.no_token {
             mock = mock.match_header("Authorization", format!("token {TOKEN}").as_str());
         }
@@ -432,11 +479,14 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
         summary,
         comments: test_control_vars.new_review_comments.clone(),
         delete_review_comments: test_params.delete_outdated,
+        allow_draft: test_params.allow_draft,
+        dismissal_message: test_params.dismissal_message.map(str::to_string),
         ..Default::default()
     };
 
     client.start_log_group("posting review");
-    if let Err(e) = client.cull_pr_reviews(&mut opts).await {
+    let culled = client.cull_pr_reviews(&mut opts).await;
+    if let Err(e) = &culled {
         if test_params.bad_existing_comments || test_params.bad_existing_reviews {
             assert!(matches!(e, RestClientError::Json { .. }));
         } else if test_params.fail_get_existing_comments || test_params.fail_dismissal {
@@ -448,13 +498,61 @@ async fn setup_and_run(lib_root: &Path, test_params: &TestParams) {
         }
     }
 
-    if let Err(e) = client.post_pr_review(&opts).await {
-        if test_params.fail_posting {
-            assert!(matches!(e, RestClientError::RequestContext { .. }));
-        } else if test_params.no_token {
-            assert!(matches!(e, RestClientError::EnvVar { .. }));
-        } else {
-            panic!("Unexpected error posting review: {e}");
+    let is_reviewable = test_params.event_t == EventType::PullRequest
+        && !test_params.no_token
+        && !test_params.is_locked
+        && !test_params.is_closed;
+    match client.post_pr_review(&opts).await {
+        Ok(outcome) => {
+            let is_skipped_draft = test_params.is_draft && !test_params.allow_draft;
+            if is_reviewable && !is_skipped_draft && !test_params.fail_posting {
+                assert_eq!(outcome.review_id, Some(POSTED_REVIEW_ID));
+                assert!(outcome.html_url.is_some());
+                assert_eq!(outcome.posted_comments, opts.comments.len());
+            } else {
+                assert!(outcome.review_id.is_none());
+                assert_eq!(outcome.posted_comments, 0);
+            }
+            if is_reviewable && culled.is_ok() {
+                assert_eq!(
+                    outcome.reused_comments,
+                    test_control_vars.reused_comment_ids.len()
+                );
+                assert_eq!(
+                    outcome.dismissed_reviews,
+                    test_control_vars.outdated_review_ids.len()
+                );
+            } else if !is_reviewable {
+                assert_eq!(outcome.reused_comments, 0);
+                assert_eq!(outcome.dismissed_reviews, 0);
+            }
+            if test_params.event_t != EventType::PullRequest || test_params.no_token {
+                assert!(outcome.skipped.is_none());
+            } else if test_params.is_locked {
+                assert_eq!(outcome.skipped, Some(ReviewSkipReason::Locked));
+            } else if test_params.is_closed {
+                assert_eq!(outcome.skipped, Some(ReviewSkipReason::Closed));
+            } else if is_skipped_draft {
+                assert_eq!(outcome.skipped, Some(ReviewSkipReason::Draft));
+            } else {
+                assert!(outcome.skipped.is_none());
+            }
+        }
+        Err(e) => {
+            if test_params.fail_posting {
+                assert!(
+                    matches!(
+                        &e,
+                        RestClientError::Http { status, body, .. }
+                            if *status == StatusCode::FORBIDDEN && body.contains(&POSTED_REVIEW_ID.to_string())
+                    ),
+                    "Expected Http error, got: {e:?}"
+                );
+            } else if test_params.no_token {
+                assert!(matches!(e, RestClientError::EnvVar { .. }));
+            } else {
+                panic!("Unexpected error posting review: {e}");
+            }
         }
     }
     client.end_log_group("");
@@ -516,6 +614,15 @@ async fn fail_dismissal() {
     .await;
 }
 
+#[tokio::test]
+async fn dismissal_message_with_quotes() {
+    test_reviews(&TestParams {
+        dismissal_message: Some("Superseded by \"my-linter\"\nSee the newer review."),
+        ..Default::default()
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn delete_outdated() {
     test_reviews(&TestParams {
@@ -561,6 +668,15 @@ async fn no_existing_reviews() {
     .await;
 }
 
+#[tokio::test]
+async fn dismissed_beforehand() {
+    test_reviews(&TestParams {
+        dismissed_beforehand: true,
+        ..Default::default()
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn is_closed() {
     test_reviews(&TestParams {
@@ -579,6 +695,16 @@ async fn is_draft() {
     .await;
 }
 
+#[tokio::test]
+async fn is_draft_allowed() {
+    test_reviews(&TestParams {
+        is_draft: true,
+        allow_draft: true,
+        ..Default::default()
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn is_locked() {
     test_reviews(&TestParams {
@@ -0,0 +1,388 @@
+#![cfg(feature = "github")]
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use chrono::Utc;
+use git_bot_feedback::{
+    RestApiClient, RestApiRateLimitHeaders, RestClientError,
+    client::{PrimaryLimit, RetryPolicy, Timestamp, init_client},
+    reqwest::{Client, Method, StatusCode, Url},
+};
+use mockito::Server;
+
+mod common;
+use common::{github_env, logger_init};
+
+fn rate_limit_headers() -> RestApiRateLimitHeaders {
+    RestApiRateLimitHeaders {
+        reset: "x-ratelimit-reset".to_string(),
+        remaining: "x-ratelimit-remaining".to_string(),
+        retry: "retry-after".to_string(),
+    }
+}
+
+/// Get the number of seconds since the unix epoch (regardless of the `chrono` feature).
+#[allow(
+    clippy::useless_conversion,
+    reason = "Timestamp is a SystemTime without chrono"
+)]
+fn unix_secs(timestamp: Timestamp) -> i64 {
+    SystemTime::from(timestamp)
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+/// Get a GitHub client that uses the given `policy` and talks to the given `server`.
+fn setup(server: &Server, policy: RetryPolicy) -> Box<dyn RestApiClient + Send + Sync> {
+    github_env(&server.url());
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    let mut client = init_client().unwrap();
+    client.set_retry_policy(policy);
+    assert_eq!(client.retry_policy(), policy);
+    client
+}
+
+/// Send a request that first meets an exhausted primary rate limit.
+async fn exhaust_primary_limit(policy: RetryPolicy) -> Result<StatusCode, RestClientError> {
+    let mut server = Server::new_async().await;
+    let client = setup(&server, policy);
+    let waits = policy.on_primary_limit != PrimaryLimit::Fail;
+    // a reset time in the near future keeps the wait short
+    let reset = (Utc::now().timestamp() + 1).to_string();
+    let limited = server
+        .mock("GET", "/")
+        .with_status(403)
+        .with_header("x-ratelimit-remaining", "0")
+        .with_header("x-ratelimit-reset", reset.as_str())
+        .expect(1)
+        .create();
+    let passing = server
+        .mock("GET", "/")
+        .with_status(200)
+        .with_header("x-ratelimit-remaining", "5000")
+        .expect(usize::from(waits))
+        .create();
+    let http_client = Client::new();
+    let request = client
+        .make_api_request(
+            &http_client,
+            Url::parse(&server.url()).unwrap(),
+            Method::GET,
+            None,
+            None,
+        )
+        .unwrap();
+    let result = client
+        .send_api_request(&http_client, request, &rate_limit_headers())
+        .await
+        .map(|response| response.status());
+    limited.assert();
+    passing.assert();
+    result
+}
+
+#[tokio::test]
+async fn primary_limit_fail() {
+    let err = exhaust_primary_limit(RetryPolicy::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RestClientError::RateLimitPrimary(_)));
+}
+
+#[tokio::test]
+async fn primary_limit_wait() {
+    let status = exhaust_primary_limit(RetryPolicy {
+        on_primary_limit: PrimaryLimit::WaitUntilReset {
+            max_wait: Duration::from_secs(5),
+        },
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    assert_eq!(status, StatusCode::OK);
+}
+
+const SECONDARY_LIMIT_BODY: &str = r#"{"message":"You have exceeded a secondary rate limit. Please wait a few minutes before you try again.","documentation_url":"https://docs.github.com/rest/overview/rate-limits-for-the-rest-api"}"#;
+
+/// Send a request that first gets a 403 response (without rate limit headers) with the given `body`.
+async fn forbidden_without_headers(body: &str) -> (StatusCode, String) {
+    let mut server = Server::new_async().await;
+    let client = setup(
+        &server,
+        RetryPolicy {
+            // don't wait for the secondary rate limit in tests
+            default_delay: Duration::ZERO,
+            ..Default::default()
+        },
+    );
+    let is_secondary_limit = body == SECONDARY_LIMIT_BODY;
+    let forbidden = server
+        .mock("GET", "/")
+        .with_status(403)
+        .with_body(body)
+        .expect(1)
+        .create();
+    let passing = server
+        .mock("GET", "/")
+        .with_status(200)
+        .with_body("passed")
+        .expect(usize::from(is_secondary_limit))
+        .create();
+    let http_client = Client::new();
+    let request = client
+        .make_api_request(
+            &http_client,
+            Url::parse(&server.url()).unwrap(),
+            Method::GET,
+            None,
+            None,
+        )
+        .unwrap();
+    let response = client
+        .send_api_request(&http_client, request, &rate_limit_headers())
+        .await
+        .unwrap();
+    forbidden.assert();
+    passing.assert();
+    let status = response.status();
+    assert_eq!(
+        response.url().as_str(),
+        Url::parse(&server.url()).unwrap().as_str()
+    );
+    (status, response.text().await.unwrap())
+}
+
+#[tokio::test]
+async fn secondary_limit_in_body() {
+    let (status, body) = forbidden_without_headers(SECONDARY_LIMIT_BODY).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "passed");
+}
+
+#[tokio::test]
+async fn forbidden_body_preserved() {
+    let message = r#"{"message":"Resource not accessible by integration"}"#;
+    let (status, body) = forbidden_without_headers(message).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body, message);
+}
+
+const RATE_LIMIT_RESPONSE: &str = r#"{
+  "resources": {
+    "core": { "limit": 5000, "used": 4990, "remaining": 10, "reset": 1760000000 },
+    "graphql": { "limit": 5000, "used": 7, "remaining": 4993, "reset": 1760000100 }
+  },
+  "rate": { "limit": 5000, "used": 4990, "remaining": 10, "reset": 1760000000 }
+}"#;
+
+#[tokio::test]
+async fn rate_limit_status() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server, RetryPolicy::default());
+    let mock = server
+        .mock("GET", "/rate_limit")
+        .with_status(200)
+        .with_body(RATE_LIMIT_RESPONSE)
+        .expect(1)
+        .create();
+    let status = client.rate_limit_status().await.unwrap().unwrap();
+    mock.assert();
+    assert_eq!(status.core.limit, 5000);
+    assert_eq!(status.core.remaining, 10);
+    assert_eq!(unix_secs(status.core.reset), 1760000000);
+    let graphql = status.graphql.unwrap();
+    assert_eq!(graphql.remaining, 4993);
+    assert_eq!(unix_secs(graphql.reset), 1760000100);
+}
+
+#[tokio::test]
+async fn rate_limit_status_error() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server, RetryPolicy::default());
+    // not retried, even though a server error is usually transient
+    let mock = server
+        .mock("GET", "/rate_limit")
+        .with_status(503)
+        .expect(1)
+        .create();
+    let err = client.rate_limit_status().await.unwrap_err();
+    mock.assert();
+    assert!(matches!(err, RestClientError::RequestContext { .. }));
+}
+
+#[tokio::test]
+async fn last_rate_limit() {
+    let mut server = Server::new_async().await;
+    let client = setup(&server, RetryPolicy::default());
+    assert!(client.last_rate_limit().is_none());
+    let reset = Utc::now().timestamp() + 3600;
+    let http_client = Client::new();
+    let mut previous_capture = None;
+    for remaining in [4999, 4998] {
+        let mock = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_header("x-ratelimit-remaining", remaining.to_string().as_str())
+            .with_header("x-ratelimit-reset", reset.to_string().as_str())
+            .expect(1)
+            .create();
+        let request = client
+            .make_api_request(
+                &http_client,
+                Url::parse(&server.url()).unwrap(),
+                Method::GET,
+                None,
+                None,
+            )
+            .unwrap();
+        client
+            .send_api_request(&http_client, request, &rate_limit_headers())
+            .await
+            .unwrap();
+        mock.assert();
+        mock.remove();
+        let snapshot = client.last_rate_limit().unwrap();
+        assert_eq!(snapshot.remaining, Some(remaining));
+        assert_eq!(unix_secs(snapshot.reset.unwrap()), reset);
+        if let Some(previous) = previous_capture {
+            assert!(snapshot.captured_at >= previous);
+        }
+        previous_capture = Some(snapshot.captured_at);
+    }
+}
+
+/// Send a POST request (with the given `body` to the given `path`)
+/// that first gets a `429` response asking to retry immediately.
+///
+/// The request is expected to be `replayed` after the `429` response.
+async fn post_rate_limited(
+    policy: RetryPolicy,
+    path: &str,
+    body: &str,
+    replayed: bool,
+) -> Result<StatusCode, RestClientError> {
+    let mut server = Server::new_async().await;
+    let client = setup(&server, policy);
+    let limited = server
+        .mock("POST", path)
+        .with_status(429)
+        .with_header("retry-after", "0")
+        .expect(1)
+        .create();
+    let passing = server
+        .mock("POST", path)
+        .with_status(201)
+        .expect(usize::from(replayed))
+        .create();
+    let http_client = Client::new();
+    let request = client
+        .make_api_request(
+            &http_client,
+            Url::parse(&server.url()).unwrap().join(path).unwrap(),
+            Method::POST,
+            Some(body.to_string()),
+            None,
+        )
+        .unwrap();
+    let result = client
+        .send_api_request(&http_client, request, &rate_limit_headers())
+        .await
+        .map(|response| response.status());
+    limited.assert();
+    passing.assert();
+    result
+}
+
+#[tokio::test]
+async fn post_not_replayed() {
+    let err = post_rate_limited(RetryPolicy::default(), "/", "{}", false)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, RestClientError::UnsafeRetry { method, .. } if method == "POST"),
+        "Expected UnsafeRetry error, got: {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn post_replayed_when_allowed() {
+    let policy = RetryPolicy {
+        retry_unsafe: true,
+        ..Default::default()
+    };
+    let status = post_rate_limited(policy, "/", "{}", true).await.unwrap();
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn graphql_query_replayed() {
+    let query = r#"{"query": "query { viewer { login } }"}"#;
+    let status = post_rate_limited(RetryPolicy::default(), "/graphql", query, true)
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn graphql_mutation_not_replayed() {
+    let mutation = r#"{"query": "mutation { minimizeComment(input: {}) { clientMutationId } }"}"#;
+    let err = post_rate_limited(RetryPolicy::default(), "/graphql", mutation, false)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, RestClientError::UnsafeRetry { method, .. } if method == "POST"),
+        "Expected UnsafeRetry error, got: {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn post_body_rebuilt_for_retry() {
+    const BODY: &str = r#"{"body":"A comment that is sent twice"}"#;
+    let mut server = Server::new_async().await;
+    let client = setup(
+        &server,
+        RetryPolicy {
+            retry_unsafe: true,
+            ..Default::default()
+        },
+    );
+    let limited = server
+        .mock("POST", "/")
+        .match_body(BODY)
+        .with_status(429)
+        .with_header("retry-after", "0")
+        .with_body("slow down")
+        .expect(1)
+        .create();
+    let passing = server
+        .mock("POST", "/")
+        .match_body(BODY)
+        .with_status(201)
+        .expect(1)
+        .create();
+    let http_client = Client::new();
+    let url = Url::parse(&server.url()).unwrap();
+    let built = AtomicUsize::new(0);
+    let make_request = || {
+        built.fetch_add(1, Ordering::Relaxed);
+        client.make_api_request(
+            &http_client,
+            url.clone(),
+            Method::POST,
+            Some(BODY.to_string()),
+            None,
+        )
+    };
+    let response = client
+        .send_api_request_with(&http_client, &make_request, &rate_limit_headers())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(built.load(Ordering::Relaxed), 2);
+    limited.assert();
+    passing.assert();
+}
@@ -0,0 +1,161 @@
+#![cfg(feature = "github")]
+use std::fs;
+
+use git_bot_feedback::{
+    RestApiClient, RestApiRateLimitHeaders,
+    client::{RequestRecorder, init_client},
+    reqwest::{Client, Method, StatusCode, Url},
+};
+use mockito::Server;
+use serde_json::Value;
+use tempfile::NamedTempFile;
+
+mod common;
+use common::{github_env, logger_init};
+
+fn rate_limit_headers() -> RestApiRateLimitHeaders {
+    RestApiRateLimitHeaders {
+        reset: "x-ratelimit-reset".to_string(),
+        remaining: "x-ratelimit-remaining".to_string(),
+        retry: "retry-after".to_string(),
+    }
+}
+
+fn setup(server: &Server, recorder: RequestRecorder) -> Box<dyn RestApiClient + Send + Sync> {
+    github_env(&server.url());
+    logger_init();
+    let mut client = init_client().unwrap();
+    assert!(client.request_recorder().is_none());
+    client.set_request_recorder(Some(recorder));
+    assert!(client.request_recorder().is_some());
+    client
+}
+
+/// Send a request with the given `method` and `body` to the `path` of the `server`.
+async fn send(
+    client: &(dyn RestApiClient + Send + Sync),
+    server: &Server,
+    method: Method,
+    path: &str,
+    body: Option<&str>,
+) -> StatusCode {
+    let http_client = Client::new();
+    let url = Url::parse(&server.url()).unwrap().join(path).unwrap();
+    let request = client
+        .make_api_request(&http_client, url, method, body.map(str::to_string), None)
+        .unwrap();
+    client
+        .send_api_request(&http_client, request, &rate_limit_headers())
+        .await
+        .unwrap()
+        .status()
+}
+
+fn read_records(file: &NamedTempFile) -> Vec<Value> {
+    fs::read_to_string(file.path())
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn record_requests() {
+    let mut server = Server::new_async().await;
+    let file = NamedTempFile::new().unwrap();
+    let client = setup(&server, RequestRecorder::new(file.path()).unwrap());
+
+    let unavailable = server
+        .mock("GET", "/comments?page=1&token=secret")
+        .with_status(503)
+        .expect(1)
+        .create();
+    let listed = server
+        .mock("GET", "/comments?page=1&token=secret")
+        .with_status(200)
+        .expect(1)
+        .create();
+    let posted = server
+        .mock("POST", "/comments")
+        .with_status(201)
+        .expect(1)
+        .create();
+    let status = send(
+        client.as_ref(),
+        &server,
+        Method::GET,
+        "/comments?page=1&token=secret",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let status = send(
+        client.as_ref(),
+        &server,
+        Method::POST,
+        "/comments",
+        Some(r#"{"body":"Hello"}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    unavailable.assert();
+    listed.assert();
+    posted.assert();
+
+    let records = read_records(&file);
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["method"], "GET");
+    assert_eq!(records[0]["path"], "/comments?page=1&token=REDACTED");
+    assert_eq!(records[0]["status"], 200);
+    assert_eq!(records[0]["retries"], 1);
+    assert!(records[0]["elapsed_ms"].is_u64());
+    assert!(records[0]["timestamp_ms"].as_u64().unwrap() > 0);
+    assert_eq!(records[1]["method"], "POST");
+    assert_eq!(records[1]["path"], "/comments");
+    assert_eq!(records[1]["status"], 201);
+    assert_eq!(records[1]["retries"], 0);
+    // bodies and headers are not recorded by default
+    assert!(records[1].get("body").is_none());
+    let content = fs::read_to_string(file.path()).unwrap();
+    assert!(!content.contains("Hello"));
+    assert!(!content.contains("123456"));
+}
+
+#[tokio::test]
+async fn record_bodies() {
+    let mut server = Server::new_async().await;
+    let file = NamedTempFile::new().unwrap();
+    let recorder = RequestRecorder::new(file.path())
+        .unwrap()
+        .include_bodies(true);
+    assert!(recorder.includes_bodies());
+    assert_eq!(recorder.path(), file.path());
+    let client = setup(&server, recorder);
+
+    let posted = server.mock("POST", "/comments").with_status(201).create();
+    send(
+        client.as_ref(),
+        &server,
+        Method::POST,
+        "/comments",
+        Some(r#"{"body":"Hello"}"#),
+    )
+    .await;
+    posted.assert();
+
+    let records = read_records(&file);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["body"], r#"{"body":"Hello"}"#);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn record_failure_is_not_fatal() {
+    let mut server = Server::new_async().await;
+    // writing to /dev/full always fails
+    let client = setup(&server, RequestRecorder::new("/dev/full").unwrap());
+    let listed = server.mock("GET", "/comments").with_status(200).create();
+    let status = send(client.as_ref(), &server, Method::GET, "/comments", None).await;
+    assert_eq!(status, StatusCode::OK);
+    listed.assert();
+}
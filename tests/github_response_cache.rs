@@ -0,0 +1,63 @@
+#![cfg(feature = "github")]
+
+use git_bot_feedback::{
+    RestApiRateLimitHeaders,
+    client::{ResponseCache, init_client},
+    reqwest::{Client, Method, StatusCode, Url},
+};
+use mockito::{Matcher, Server};
+
+mod common;
+use common::{github_env, logger_init};
+
+const ETAG: &str = "\"644b5b0155e6404a9cc4bd9d8b1ae730\"";
+const PAYLOAD: &str = r#"[{"id":1,"body":"cached comment"}]"#;
+
+#[tokio::test]
+async fn not_modified_uses_cache() {
+    let mut server = Server::new_async().await;
+    github_env(&server.url());
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    let mut client = init_client().unwrap();
+    assert!(client.response_cache().is_none());
+    client.set_response_cache(Some(ResponseCache::new(8)));
+
+    let fresh = server
+        .mock("GET", "/comments")
+        .match_header("if-none-match", Matcher::Missing)
+        .with_status(200)
+        .with_header("etag", ETAG)
+        .with_body(PAYLOAD)
+        .expect(1)
+        .create();
+    let not_modified = server
+        .mock("GET", "/comments")
+        .match_header("if-none-match", ETAG)
+        .with_status(304)
+        .expect(1)
+        .create();
+
+    let http_client = Client::new();
+    let rate_limit_headers = RestApiRateLimitHeaders {
+        reset: "x-ratelimit-reset".to_string(),
+        remaining: "x-ratelimit-remaining".to_string(),
+        retry: "retry-after".to_string(),
+    };
+    let url = Url::parse(&server.url()).unwrap().join("comments").unwrap();
+    for _ in 0..2 {
+        let request = client
+            .make_api_request(&http_client, url.clone(), Method::GET, None, None)
+            .unwrap();
+        let response = client
+            .send_api_request(&http_client, request, &rate_limit_headers)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.url(), &url);
+        assert_eq!(response.text().await.unwrap(), PAYLOAD);
+    }
+    fresh.assert();
+    not_modified.assert();
+    assert_eq!(client.response_cache().unwrap().len(), 1);
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "github")]
+use git_bot_feedback::{OutputVariable, RestClientError, client::init_client};
+use std::{env, fs};
+use tempfile::{NamedTempFile, tempdir};
+mod common;
+use common::logger_init;
+
+const VAR_NAME: &str = "comment_id";
+const VAR_VALUE: &str = "1234567890";
+const MULTI_LINE_NAME: &str = "report";
+const MULTI_LINE_VALUE: &str = "line 1\nline 2";
+
+fn setup_env() {
+    unsafe {
+        env::set_var("GITHUB_ACTIONS", "true");
+        env::remove_var("GITEA_ACTIONS");
+        env::set_var("GITHUB_REPOSITORY", "2bndy5/git-bot-feedback");
+        env::set_var("GITHUB_SHA", "DEADBEEF");
+        env::set_var("CI", "true");
+        env::set_var("GITHUB_EVENT_NAME", "push");
+    }
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+}
+
+/// Parse the `GITHUB_STATE` file and set the `STATE_<name>` env vars like the CI runner would.
+fn load_state(content: &str) {
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        let (name, value) = match line.split_once("<<") {
+            Some((name, delimiter)) => (
+                name,
+                lines
+                    .by_ref()
+                    .take_while(|l| *l != delimiter)
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+            None => {
+                let (name, value) = line.split_once('=').unwrap();
+                (name, value.to_string())
+            }
+        };
+        unsafe {
+            env::set_var(format!("STATE_{name}"), value);
+        }
+    }
+}
+
+#[test]
+fn write_then_read_state() {
+    let tmp_dir = tempdir().unwrap();
+    let state_file = NamedTempFile::new_in(tmp_dir.path()).unwrap();
+    unsafe {
+        env::set_var("GITHUB_STATE", state_file.path());
+        env::remove_var(format!("STATE_{VAR_NAME}"));
+    }
+    setup_env();
+    let client = init_client().unwrap();
+    assert_eq!(client.client_kind(), "github");
+
+    // main step
+    client
+        .write_state_variables(&[
+            OutputVariable::new(VAR_NAME, VAR_VALUE).unwrap(),
+            OutputVariable::new(MULTI_LINE_NAME, MULTI_LINE_VALUE).unwrap(),
+        ])
+        .unwrap();
+    assert_eq!(client.read_state_variable(VAR_NAME), None);
+
+    // post step
+    load_state(&fs::read_to_string(state_file.path()).unwrap());
+    assert_eq!(
+        client.read_state_variable(VAR_NAME).as_deref(),
+        Some(VAR_VALUE)
+    );
+    assert_eq!(
+        client.read_state_variable(MULTI_LINE_NAME).as_deref(),
+        Some(MULTI_LINE_VALUE)
+    );
+}
+
+#[test]
+fn absent_gh_state() {
+    unsafe {
+        env::remove_var("GITHUB_STATE");
+    }
+    setup_env();
+    let client = init_client().unwrap();
+    let err = client
+        .write_state_variables(&[OutputVariable::new(VAR_NAME, VAR_VALUE).unwrap()])
+        .unwrap_err();
+    assert!(
+        matches!(err, RestClientError::EnvVar { .. }),
+        "Expected EnvVar error, got: {err:?}"
+    );
+}
@@ -1,7 +1,15 @@
 #![cfg(feature = "github")]
-use git_bot_feedback::{RestClientError, client::init_client};
+use git_bot_feedback::{
+    RestClientError,
+    client::init_client,
+    summary::{MAX_STEP_SUMMARY_SIZE, SummaryOverflow, SummaryWriteMode, TRUNCATED_NOTE},
+};
 use mockito::Server;
-use std::{env, io::Read, path::Path};
+use std::{
+    env, fs,
+    io::{Read, Write},
+    path::Path,
+};
 use tempfile::{NamedTempFile, tempdir};
 mod common;
 use common::logger_init;
@@ -11,10 +19,15 @@ const SHA: &str = "DEADBEEF";
 
 const COMMENT: &str = "Some comment text";
 
+/// The number of bytes left in the step summary when it is pre-filled near its size limit.
+const REMAINING_SPACE: u64 = 64;
+
 #[derive(Debug, Default)]
 struct TestParams {
     fail_summary: bool,
     absent: bool,
+    nearly_full: bool,
+    overflow: SummaryOverflow,
 }
 
 async fn append_summary(test_params: TestParams) -> String {
@@ -29,7 +42,7 @@ async fn append_summary(test_params: TestParams) -> String {
             env::set_var(
                 "GITHUB_STEP_SUMMARY",
                 if test_params.fail_summary {
-                    Path::new("not-a-file.txt")
+                    Path::new("not-a-dir/not-a-file.txt")
                 } else {
                     step_summary_path.path()
                 },
@@ -55,17 +68,36 @@ async fn append_summary(test_params: TestParams) -> String {
     log::set_max_level(log::LevelFilter::Debug);
     let client = init_client().unwrap();
 
+    let comment = if test_params.nearly_full {
+        let filler = "x".repeat((MAX_STEP_SUMMARY_SIZE - REMAINING_SPACE) as usize);
+        step_summary_path
+            .as_file_mut()
+            .write_all(filler.as_bytes())
+            .unwrap();
+        format!("{COMMENT}\n\n```text\n{}\n```\n", "y".repeat(100))
+    } else {
+        COMMENT.to_string()
+    };
     let mut step_summary_content = String::new();
-    match client.append_step_summary(COMMENT) {
+    match client.append_step_summary(&comment, test_params.overflow) {
         Ok(_) => {
             step_summary_path
                 .read_to_string(&mut step_summary_content)
                 .unwrap();
         }
         Err(e) => {
-            assert!(test_params.fail_summary || test_params.absent);
+            assert!(test_params.fail_summary || test_params.absent || test_params.nearly_full);
             if test_params.absent {
                 assert!(matches!(e, RestClientError::EnvVar { .. }));
+            } else if test_params.nearly_full {
+                assert_eq!(test_params.overflow, SummaryOverflow::Error);
+                assert!(matches!(
+                    e,
+                    RestClientError::StepSummaryTooLarge {
+                        limit: MAX_STEP_SUMMARY_SIZE,
+                        ..
+                    }
+                ));
             } else {
                 assert!(matches!(e, RestClientError::Io { .. }));
             }
@@ -99,3 +131,150 @@ async fn absent_gh_summary() {
     .await;
     assert!(summary.is_empty());
 }
+
+#[tokio::test]
+async fn truncated_gh_summary() {
+    let summary = append_summary(TestParams {
+        nearly_full: true,
+        ..Default::default()
+    })
+    .await;
+    // only the appended text is read because the file's cursor is after the pre-filled content
+    assert!(summary.len() as u64 <= REMAINING_SPACE);
+    // the code fence was not cut in half
+    assert_eq!(summary, format!("\n{COMMENT}\n\n\n{TRUNCATED_NOTE}\n\n"));
+}
+
+#[tokio::test]
+async fn overflow_gh_summary() {
+    let summary = append_summary(TestParams {
+        nearly_full: true,
+        overflow: SummaryOverflow::Error,
+        ..Default::default()
+    })
+    .await;
+    assert!(summary.is_empty());
+}
+
+/// Write a comment to a step summary that initially contains `initial` text (if any).
+fn write_summary(initial: Option<&str>, mode: SummaryWriteMode) -> String {
+    let tmp_dir = tempdir().unwrap();
+    let step_summary_path = tmp_dir.path().join("step_summary.md");
+    if let Some(initial) = initial {
+        fs::write(&step_summary_path, initial).unwrap();
+    }
+    unsafe {
+        env::set_var("GITHUB_STEP_SUMMARY", &step_summary_path);
+        env::set_var("GITHUB_ACTIONS", "true");
+        env::remove_var("GITEA_ACTIONS");
+        env::set_var("GITHUB_REPOSITORY", REPO);
+        env::set_var("GITHUB_SHA", SHA);
+        env::set_var("CI", "true");
+        env::set_var("GITHUB_EVENT_NAME", "push");
+    }
+    logger_init();
+    let client = init_client().unwrap();
+    client.write_step_summary(COMMENT, mode).unwrap();
+    fs::read_to_string(step_summary_path).unwrap()
+}
+
+#[test]
+fn overwrite_gh_summary() {
+    let summary = write_summary(Some("previous run\n"), SummaryWriteMode::Overwrite);
+    assert_eq!(summary, format!("\n{COMMENT}\n\n"));
+}
+
+#[test]
+fn append_existing_gh_summary() {
+    let summary = write_summary(Some("previous run\n"), SummaryWriteMode::Append);
+    assert_eq!(summary, format!("previous run\n\n{COMMENT}\n\n"));
+}
+
+#[test]
+fn create_missing_gh_summary() {
+    for mode in [SummaryWriteMode::Append, SummaryWriteMode::Overwrite] {
+        let summary = write_summary(None, mode);
+        assert_eq!(summary, format!("\n{COMMENT}\n\n"));
+    }
+}
+
+const MARKER: &str = "git-bot-feedback";
+const OTHER_TOOL: &str = "## Other tool\n\n<!-- other:start -->\nkeep me\n<!-- other:end -->\n";
+
+/// Upsert a section into a step summary that initially contains `initial` text.
+///
+/// The step summary file does not exist if `initial` is [`None`].
+fn upsert_summary(initial: Option<&str>, content: &str) -> String {
+    let tmp_dir = tempdir().unwrap();
+    let step_summary_path = tmp_dir.path().join("step_summary.md");
+    if let Some(initial) = initial {
+        fs::write(&step_summary_path, initial).unwrap();
+    }
+    unsafe {
+        env::set_var("GITHUB_STEP_SUMMARY", &step_summary_path);
+        env::set_var("GITHUB_ACTIONS", "true");
+        env::remove_var("GITEA_ACTIONS");
+        env::set_var("GITHUB_REPOSITORY", REPO);
+        env::set_var("GITHUB_SHA", SHA);
+        env::set_var("CI", "true");
+        env::set_var("GITHUB_EVENT_NAME", "push");
+    }
+    logger_init();
+    let client = init_client().unwrap();
+    client.upsert_step_summary(MARKER, content).unwrap();
+    fs::read_to_string(step_summary_path).unwrap()
+}
+
+#[test]
+fn upsert_append() {
+    let summary = upsert_summary(Some(""), COMMENT);
+    assert_eq!(
+        summary,
+        format!("\n<!-- {MARKER}:start -->\n{COMMENT}\n<!-- {MARKER}:end -->\n\n")
+    );
+}
+
+#[test]
+fn upsert_missing() {
+    let summary = upsert_summary(None, COMMENT);
+    assert_eq!(
+        summary,
+        format!("\n<!-- {MARKER}:start -->\n{COMMENT}\n<!-- {MARKER}:end -->\n\n")
+    );
+}
+
+#[test]
+fn upsert_replace() {
+    let before =
+        format!("{OTHER_TOOL}\n<!-- {MARKER}:start -->\nold\nresults\n<!-- {MARKER}:end -->");
+    let after = "\n\n## Trailing tool\r\n";
+    let summary = upsert_summary(Some(format!("{before}{after}").as_str()), COMMENT);
+    assert_eq!(
+        summary,
+        format!("{OTHER_TOOL}\n<!-- {MARKER}:start -->\n{COMMENT}\n<!-- {MARKER}:end -->{after}")
+    );
+}
+
+#[test]
+fn upsert_other_tools_only() {
+    let summary = upsert_summary(Some(OTHER_TOOL), COMMENT);
+    assert_eq!(
+        summary,
+        format!("{OTHER_TOOL}\n<!-- {MARKER}:start -->\n{COMMENT}\n<!-- {MARKER}:end -->\n\n")
+    );
+}
+
+#[test]
+fn upsert_nearly_full() {
+    let section = format!("<!-- {MARKER}:start -->\nold\n<!-- {MARKER}:end -->");
+    let filler =
+        "x".repeat(MAX_STEP_SUMMARY_SIZE as usize - REMAINING_SPACE as usize - section.len());
+    let content = format!("{COMMENT}\n\n```text\n{}\n```\n", "y".repeat(100));
+    let summary = upsert_summary(Some(format!("{filler}{section}").as_str()), &content);
+    assert!(summary.len() as u64 <= MAX_STEP_SUMMARY_SIZE);
+    let replaced = summary.strip_prefix(&filler).unwrap();
+    assert_eq!(
+        replaced,
+        format!("<!-- {MARKER}:start -->\n{COMMENT}\n\n\n{TRUNCATED_NOTE}\n<!-- {MARKER}:end -->")
+    );
+}
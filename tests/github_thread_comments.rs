@@ -2,6 +2,7 @@
 use chrono::Utc;
 use git_bot_feedback::{
     CommentKind, CommentPolicy, RestClientError, ThreadCommentOptions, client::init_client,
+    reqwest::StatusCode,
 };
 use mockito::{Matcher, Server};
 use std::{env, io::Write, path::Path};
@@ -19,6 +20,8 @@ const MOCK_ASSETS_PATH: &str = "tests/assets/thread_comment/github/";
 
 const RESET_RATE_LIMIT_HEADER: &str = "x-ratelimit-reset";
 const REMAINING_RATE_LIMIT_HEADER: &str = "x-ratelimit-remaining";
+const DELETION_DENIED: &str = "Must have admin rights to delete this comment";
+const POSTING_DENIED: &str = "Resource not accessible by integration";
 
 struct TestParams {
     event_t: EventType,
@@ -228,6 +231,7 @@ async fn setup(lib_root: &Path, test_params: &TestParams) {
                 .match_body(Matcher::Any)
                 .match_header("Authorization", format!("token {TOKEN}").as_str())
                 .with_status(if test_params.fail_dismissal { 403 } else { 200 })
+                .with_body(DELETION_DENIED)
                 .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
                 .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
                 .expect_at_least(1)
@@ -270,6 +274,7 @@ async fn setup(lib_root: &Path, test_params: &TestParams) {
                 .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
                 .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
                 .with_status(if test_params.fail_posting { 403 } else { 200 })
+                .with_body(POSTING_DENIED)
                 .create();
             if !test_params.no_token {
                 mock = mock.match_header("Authorization", format!("token {TOKEN}").as_str());
@@ -282,8 +287,11 @@ async fn setup(lib_root: &Path, test_params: &TestParams) {
                     .match_body(new_comment_match.clone())
                     .match_header("Authorization", format!("token {TOKEN}").as_str())
                     .with_status(if test_params.fail_posting { 403 } else { 200 })
+                    .with_body(POSTING_DENIED)
                     .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
                     .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+                    // a failed deletion stops the update
+                    .expect(usize::from(!test_params.fail_dismissal))
                     .create(),
             );
         }
@@ -295,6 +303,7 @@ async fn setup(lib_root: &Path, test_params: &TestParams) {
         kind: test_params.comment_kind,
         marker: MARKER.to_string(),
         no_lgtm: test_params.no_lgtm,
+        ..Default::default()
     };
     client.start_log_group("posting comment");
     let result = client.post_thread_comment(opts).await;
@@ -303,6 +312,20 @@ async fn setup(lib_root: &Path, test_params: &TestParams) {
         assert!(matches!(result, Err(RestClientError::Json { .. })));
     } else if test_params.no_token {
         assert!(matches!(result, Err(RestClientError::EnvVar { .. })));
+    } else if test_params.fail_dismissal || test_params.fail_posting {
+        let expected_body = if test_params.fail_dismissal {
+            DELETION_DENIED
+        } else {
+            POSTING_DENIED
+        };
+        assert!(
+            matches!(
+                &result,
+                Err(RestClientError::Http { status, body, .. })
+                    if *status == StatusCode::FORBIDDEN && body == expected_body
+            ),
+            "Expected Http error, got: {result:?}"
+        );
     } else {
         assert!(result.is_ok());
     }
@@ -0,0 +1,137 @@
+#![cfg(feature = "github")]
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use git_bot_feedback::{
+    RestApiClient, RestApiRateLimitHeaders,
+    client::{DEFAULT_MAX_CONCURRENT_REQUESTS, Throttle, init_client},
+    reqwest::{Client, Method, Url},
+};
+
+mod common;
+use common::{github_env, logger_init};
+
+/// The time that the test server takes to respond to each request.
+const RESPONSE_DELAY: Duration = Duration::from_millis(100);
+
+/// A server that records the arrival time of each request and the most requests it handled at once.
+#[derive(Default)]
+struct RecordingServer {
+    arrivals: Mutex<Vec<Instant>>,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+impl RecordingServer {
+    /// Serve each connection on its own thread, so requests are handled concurrently.
+    fn start(self: &Arc<Self>) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let server = self.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let server = server.clone();
+                thread::spawn(move || {
+                    let mut buf = [0u8; 4096];
+                    let _ = stream.read(&mut buf).unwrap();
+                    server.arrivals.lock().unwrap().push(Instant::now());
+                    let count = server.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    server.max_in_flight.fetch_max(count, Ordering::SeqCst);
+                    thread::sleep(RESPONSE_DELAY);
+                    server.in_flight.fetch_sub(1, Ordering::SeqCst);
+                    stream
+                        .write_all(
+                            b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                        )
+                        .unwrap();
+                });
+            }
+        });
+        url
+    }
+}
+
+/// Send `count` simultaneous requests with the given `method` through a client using `throttle`.
+async fn send_simultaneously(
+    throttle: Option<Throttle>,
+    method: Method,
+    count: usize,
+) -> Arc<RecordingServer> {
+    let server = Arc::new(RecordingServer::default());
+    let url = server.start();
+    github_env(url.as_str());
+    logger_init();
+    let mut client = init_client().unwrap();
+    assert_eq!(
+        client.throttle().unwrap().max_concurrent(),
+        DEFAULT_MAX_CONCURRENT_REQUESTS
+    );
+    if let Some(throttle) = throttle {
+        client.set_throttle(throttle);
+    }
+    let client: Arc<dyn RestApiClient + Send + Sync> = Arc::from(client);
+    let http_client = Client::new();
+    let rate_limit_headers = Arc::new(RestApiRateLimitHeaders {
+        reset: "x-ratelimit-reset".to_string(),
+        remaining: "x-ratelimit-remaining".to_string(),
+        retry: "retry-after".to_string(),
+    });
+    let tasks = (0..count)
+        .map(|_| {
+            let client = client.clone();
+            let http_client = http_client.clone();
+            let rate_limit_headers = rate_limit_headers.clone();
+            let url = url.clone();
+            let method = method.clone();
+            tokio::spawn(async move {
+                let request = client
+                    .make_api_request(&http_client, url, method, None, None)
+                    .unwrap();
+                client
+                    .send_api_request(&http_client, request, &rate_limit_headers)
+                    .await
+                    .unwrap()
+                    .error_for_status()
+                    .unwrap();
+            })
+        })
+        .collect::<Vec<_>>();
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert_eq!(server.arrivals.lock().unwrap().len(), count);
+    server
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrency_cap() {
+    let server = send_simultaneously(Some(Throttle::new(2, None)), Method::GET, 6).await;
+    assert!(server.max_in_flight.load(Ordering::SeqCst) <= 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn default_concurrency_cap() {
+    let server = send_simultaneously(None, Method::GET, DEFAULT_MAX_CONCURRENT_REQUESTS * 2).await;
+    assert!(server.max_in_flight.load(Ordering::SeqCst) <= DEFAULT_MAX_CONCURRENT_REQUESTS);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mutation_delay() {
+    let delay = Duration::from_millis(50);
+    let server = send_simultaneously(Some(Throttle::new(4, Some(delay))), Method::DELETE, 3).await;
+    let mut arrivals = server.arrivals.lock().unwrap().clone();
+    arrivals.sort();
+    for pair in arrivals.windows(2) {
+        // allow some leeway for the time between sending and receiving a request
+        assert!(pair[1] - pair[0] >= delay - Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "github")]
+use std::{net::TcpListener, time::Duration};
+
+use git_bot_feedback::{
+    RestApiClient, RestClientError, ThreadCommentOptions,
+    client::{Timeouts, init_client},
+};
+
+mod common;
+use common::{github_env, logger_init};
+
+/// Get a GitHub client that talks to a server which accepts connections but never responds.
+///
+/// The returned listener must be kept alive for the duration of the test.
+fn setup(timeouts: Timeouts) -> (Box<dyn RestApiClient + Send + Sync>, TcpListener) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    github_env(&format!("http://{}", listener.local_addr().unwrap()));
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    let mut client = init_client().unwrap();
+    client.set_user_agent("timeout-tests").unwrap();
+    client.set_timeouts(timeouts).unwrap();
+    assert_eq!(client.timeouts(), timeouts);
+    (client, listener)
+}
+
+#[tokio::test]
+async fn request_timeout() {
+    let (client, _listener) = setup(Timeouts {
+        request: Duration::from_millis(100),
+        ..Default::default()
+    });
+    let err = client.rate_limit_status().await.unwrap_err();
+    assert!(matches!(err, RestClientError::RequestContext { source, .. } if source.is_timeout()));
+}
+
+#[tokio::test]
+async fn operation_deadline() {
+    let (client, _listener) = setup(Timeouts {
+        deadline: Some(Duration::from_millis(200)),
+        ..Default::default()
+    });
+    let err = client
+        .post_thread_comment(ThreadCommentOptions {
+            comment: "some comment text".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        RestClientError::Timeout { ref task, deadline }
+            if task == "post thread comment" && deadline == Duration::from_millis(200)
+    ));
+    assert!(err.to_string().contains("deadline of 0.2 seconds exceeded"));
+}
@@ -0,0 +1,236 @@
+#![cfg(all(feature = "file-changes", feature = "gitlab"))]
+use chrono::Utc;
+mod common;
+use common::{EventType, logger_init};
+use mockito::{Matcher, Server};
+
+use git_bot_feedback::{
+    DiffHunkHeader, FileFilter, LinesChangedOnly, RestApiClient, RestClientError,
+    client::GitlabApiClient,
+};
+use std::{env, fs, path::Path};
+
+/// How the server responds when the diff of a file is too large.
+#[derive(Default, PartialEq)]
+enum RawDiff {
+    /// No diffs are omitted, so the raw diff is not requested.
+    #[default]
+    Unused,
+    /// A diff is omitted and the raw diff is provided.
+    Provided,
+    /// A diff is omitted and the endpoint does not exist (as in GitLab < v17.9).
+    Unsupported,
+}
+
+#[derive(Default)]
+struct TestParams {
+    event_t: EventType,
+    raw_diff: RawDiff,
+    fail_request: bool,
+    bad_json: bool,
+}
+
+const PROJECT: &str = "42";
+const SHA: &str = "DEADBEEF";
+const MR: u8 = 22;
+const TOKEN: &str = "123456";
+const RESET_RATE_LIMIT_HEADER: &str = "RateLimit-Reset";
+const REMAINING_RATE_LIMIT_HEADER: &str = "RateLimit-Remaining";
+const ASSETS_PATH: &str = "tests/assets/file_changes/gitlab/";
+/// A file whose diff GitLab omitted because it is too large.
+const TOO_LARGE_FILE: &str = "src/too_large.rs";
+
+async fn test_get_changes(test_params: &TestParams) -> Result<(), RestClientError> {
+    let mut server = Server::new_async().await;
+    unsafe {
+        env::set_var("GITLAB_CI", "true");
+        env::set_var("CI_API_V4_URL", format!("{}/api/v4", server.url()));
+        env::set_var("CI_PROJECT_ID", PROJECT);
+        env::set_var("CI_COMMIT_SHA", SHA);
+        env::set_var("GITLAB_TOKEN", TOKEN);
+        env::remove_var("CI_JOB_TOKEN");
+        if test_params.event_t == EventType::PullRequest {
+            env::set_var("CI_PIPELINE_SOURCE", "merge_request_event");
+            env::set_var("CI_MERGE_REQUEST_IID", MR.to_string());
+        } else {
+            env::set_var("CI_PIPELINE_SOURCE", "push");
+            env::remove_var("CI_MERGE_REQUEST_IID");
+        }
+    }
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    let client = GitlabApiClient::new().unwrap();
+
+    let reset_timestamp = (Utc::now().timestamp() + 60).to_string();
+    let project_path = format!("/api/v4/projects/{PROJECT}");
+    let files_end_point = if test_params.event_t == EventType::PullRequest {
+        format!("{project_path}/merge_requests/{MR}/diffs")
+    } else {
+        format!("{project_path}/repository/commits/{SHA}/diff")
+    };
+    let mut mocks = vec![];
+    for page in 1..=2 {
+        let mut mock = server
+            .mock("GET", files_end_point.as_str())
+            .match_query(Matcher::UrlEncoded("page".to_string(), page.to_string()))
+            .match_header("PRIVATE-TOKEN", TOKEN)
+            .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+            .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str());
+        if page == 1 {
+            let link = format!("<{}{files_end_point}?page=2>; rel=\"next\"", server.url());
+            mock = mock.with_header("link", link.as_str());
+        }
+        if test_params.fail_request {
+            mock = mock
+                .with_status(403)
+                .with_body(r#"{"message":"403 Forbidden"}"#)
+                .expect(if page == 1 { 1 } else { 0 });
+        } else if test_params.bad_json {
+            mock = mock
+                .with_body("not a JSON payload")
+                .expect(if page == 1 { 1 } else { 0 });
+        } else {
+            let mut files = serde_json::from_str::<Vec<serde_json::Value>>(
+                &fs::read_to_string(format!("{ASSETS_PATH}mr_diffs_pg{page}.json")).unwrap(),
+            )
+            .unwrap();
+            if page == 2 && test_params.raw_diff != RawDiff::Unused {
+                files.push(serde_json::json!({
+                    "diff": "",
+                    "new_path": TOO_LARGE_FILE,
+                    "old_path": TOO_LARGE_FILE,
+                    "new_file": false,
+                    "renamed_file": false,
+                    "deleted_file": false,
+                    "too_large": true,
+                }));
+            }
+            mock = mock.with_body(serde_json::to_string(&files).unwrap());
+        }
+        mocks.push(mock.create());
+    }
+
+    let mut raw_mock = server
+        .mock(
+            "GET",
+            format!("{project_path}/merge_requests/{MR}/raw_diffs").as_str(),
+        )
+        .match_header("Accept", "text/plain")
+        .match_header("PRIVATE-TOKEN", TOKEN)
+        .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+        .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str());
+    raw_mock = match test_params.raw_diff {
+        RawDiff::Unsupported => raw_mock
+            .with_status(404)
+            .with_body(r#"{"message":"404 Not Found"}"#),
+        _ => raw_mock.with_body_from_file(format!("{ASSETS_PATH}raw.diff")),
+    };
+    let uses_raw_diff =
+        test_params.event_t == EventType::PullRequest && test_params.raw_diff != RawDiff::Unused;
+    mocks.push(raw_mock.expect(if uses_raw_diff { 1 } else { 0 }).create());
+
+    let file_filter = FileFilter::new(&["", "!src/*"], &["rs", "yml"], None);
+    let files = client
+        .get_list_of_changed_files(&file_filter, &LinesChangedOnly::Off, None, false)
+        .await;
+    for mock in mocks {
+        mock.assert();
+    }
+    let files = files?;
+
+    // the raw diff does not include the file whose diff was omitted
+    let lists_too_large_file = test_params.raw_diff != RawDiff::Unused
+        && !(uses_raw_diff && test_params.raw_diff == RawDiff::Provided);
+    assert_eq!(files.len(), if lists_too_large_file { 6 } else { 5 });
+    // deleted files are not listed
+    assert!(!files.contains_key("src/removed.rs"));
+    for (file, diff_ctx) in files {
+        assert!(Path::new(&file).starts_with("src"));
+        if file == TOO_LARGE_FILE {
+            assert!(diff_ctx.diff_hunks.is_empty());
+        } else if file == "src/lib.rs" {
+            let diff_hunk = DiffHunkHeader {
+                old_start: 1,
+                old_lines: 20,
+                new_start: 5,  // don't care
+                new_lines: 10, // don't care
+            };
+            assert!(diff_ctx.is_hunk_in_diff(&diff_hunk).is_some());
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_push_files_paginated() {
+    test_get_changes(&TestParams::default()).await.unwrap();
+}
+
+#[tokio::test]
+async fn get_mr_files_paginated() {
+    test_get_changes(&TestParams {
+        event_t: EventType::PullRequest,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn get_mr_files_too_large() {
+    test_get_changes(&TestParams {
+        event_t: EventType::PullRequest,
+        raw_diff: RawDiff::Provided,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn get_mr_files_too_large_no_raw_diff() {
+    test_get_changes(&TestParams {
+        event_t: EventType::PullRequest,
+        raw_diff: RawDiff::Unsupported,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn get_push_files_too_large() {
+    // there is no raw diff of a commit to fall back on
+    test_get_changes(&TestParams {
+        raw_diff: RawDiff::Provided,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn fail_request() {
+    let err = test_get_changes(&TestParams {
+        event_t: EventType::PullRequest,
+        fail_request: true,
+        ..Default::default()
+    })
+    .await
+    .unwrap_err();
+    assert!(
+        matches!(err, RestClientError::RequestContext { ref source, .. } if source.is_status()),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn bad_json() {
+    let err = test_get_changes(&TestParams {
+        bad_json: true,
+        ..Default::default()
+    })
+    .await
+    .unwrap_err();
+    assert!(matches!(err, RestClientError::Json { .. }), "{err:?}");
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "gitlab")]
+use git_bot_feedback::{
+    OutputVariable, RestApiClient, RestClientError, client::GitlabApiClient,
+    error::OutputVariableError,
+};
+use std::{env, fs};
+use tempfile::{NamedTempFile, tempdir};
+
+mod common;
+use common::logger_init;
+
+const DOTENV_ENV: &str = "GIT_BOT_FEEDBACK_DOTENV";
+
+/// Instantiate a client whose dotenv file is the given `dotenv` (if any).
+fn setup(dotenv: Option<&str>) -> GitlabApiClient {
+    logger_init();
+    unsafe {
+        env::set_var("CI_API_V4_URL", "https://gitlab.com/api/v4");
+        env::set_var("CI_PROJECT_ID", "42");
+        env::set_var("CI_COMMIT_SHA", "DEADBEEF");
+        env::remove_var("CI_MERGE_REQUEST_IID");
+        match dotenv {
+            Some(path) => env::set_var(DOTENV_ENV, path),
+            None => env::remove_var(DOTENV_ENV),
+        }
+    }
+    GitlabApiClient::new().unwrap()
+}
+
+#[test]
+fn write_dotenv() {
+    let dotenv = NamedTempFile::new().unwrap();
+    let client = setup(dotenv.path().to_str());
+    assert!(client.capabilities().output_variables);
+    client
+        .write_output_variables(&[
+            OutputVariable::new("LINT_CHECKS", "3").unwrap(),
+            OutputVariable::bool("LINT_PASSED", false).unwrap(),
+        ])
+        .unwrap();
+    client
+        .write_output_variables(&[OutputVariable::new("LINT_TOOL", " clang-tidy ").unwrap()])
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(dotenv.path()).unwrap(),
+        "LINT_CHECKS=3\nLINT_PASSED=false\nLINT_TOOL=clang-tidy\n"
+    );
+}
+
+#[test]
+fn create_dotenv() {
+    let tmp_dir = tempdir().unwrap();
+    let path = tmp_dir.path().join("build.env");
+    let client = setup(path.to_str());
+    client
+        .write_output_variables(&[OutputVariable::new("LINT_CHECKS", "3").unwrap()])
+        .unwrap();
+    assert_eq!(fs::read_to_string(path).unwrap(), "LINT_CHECKS=3\n");
+}
+
+#[test]
+fn unsupported_vars() {
+    let dotenv = NamedTempFile::new().unwrap();
+    let client = setup(dotenv.path().to_str());
+    let err = client
+        .write_output_variables(&[
+            OutputVariable::new("LINT_CHECKS", "3").unwrap(),
+            OutputVariable::new("LINT_REPORT", "line 1\nline 2").unwrap(),
+        ])
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        RestClientError::OutputVar(OutputVariableError::MultiLineValue(_))
+    ));
+    let err = client
+        .write_output_variables(&[OutputVariable::new("lint-checks", "3").unwrap()])
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        RestClientError::OutputVar(OutputVariableError::NameContainsHyphen(_))
+    ));
+    // no variables are written if any are unsupported
+    assert!(fs::read_to_string(dotenv.path()).unwrap().is_empty());
+}
+
+#[test]
+fn absent_dotenv() {
+    let mut client = setup(None);
+    let vars = [OutputVariable::new("LINT_CHECKS", "3").unwrap()];
+    client.write_output_variables(&vars).unwrap();
+    client.set_strict_mode(true);
+    let err = client.write_output_variables(&vars).unwrap_err();
+    assert!(matches!(err, RestClientError::EnvVar { .. }));
+}
@@ -0,0 +1,407 @@
+#![cfg(feature = "gitlab")]
+use chrono::Utc;
+use git_bot_feedback::{
+    RestApiClient, RestClientError, ReviewAction, ReviewComment, ReviewOptions, ReviewOutcome,
+    client::GitlabApiClient,
+};
+use mockito::{Matcher, Mock, Server, ServerGuard};
+use std::env;
+
+mod common;
+use common::{EventType, logger_init};
+
+const MARKER: &str = "<!-- git-bot-feedback -->\n";
+const SHA: &str = "deadbeef";
+const BASE_SHA: &str = "cafebabe";
+const PROJECT: &str = "42";
+const MR: i64 = 22;
+const TOKEN: &str = "123456";
+const POSTED_NOTE_ID: i64 = 1010;
+const PROJECT_URL: &str = "https://gitlab.com/2bndy5/git-bot-feedback";
+const MOCK_ASSETS_PATH: &str = "tests/assets/reviews/gitlab/";
+
+/// The discussion started by a previous review's summary.
+const OUTDATED_SUMMARY: &str = "6a9c1750b37d513a43987b574953fceb50b03ce7";
+/// The discussion started by a previous review comment that is not proposed again.
+const OUTDATED_COMMENT: &str = "9f2c3a6d1e4b5c7a8d9e0f1a2b3c4d5e6f7a8b9c";
+
+const RESET_RATE_LIMIT_HEADER: &str = "RateLimit-Reset";
+const REMAINING_RATE_LIMIT_HEADER: &str = "RateLimit-Remaining";
+
+struct TestParams {
+    event_t: EventType,
+    action: ReviewAction,
+    approval_status: usize,
+    delete_review_comments: bool,
+    fail_get_discussions: bool,
+    no_token: bool,
+    no_diff_base: bool,
+    dry_run: bool,
+}
+
+impl Default for TestParams {
+    fn default() -> Self {
+        Self {
+            event_t: EventType::PullRequest,
+            action: ReviewAction::Comment,
+            approval_status: 201,
+            delete_review_comments: false,
+            fail_get_discussions: false,
+            no_token: false,
+            no_diff_base: false,
+            dry_run: false,
+        }
+    }
+}
+
+fn mock_api(server: &mut ServerGuard, method: &str, path: &str) -> Mock {
+    let reset_timestamp = (Utc::now().timestamp() + 60).to_string();
+    server
+        .mock(method, path)
+        .match_header("PRIVATE-TOKEN", TOKEN)
+        .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+        .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+}
+
+fn proposed_comments() -> Vec<ReviewComment> {
+    vec![
+        ReviewComment {
+            line_start: None,
+            line_end: 42,
+            comment: "A reused comment".to_string(),
+            path: "src/lib.rs".to_string(),
+        },
+        ReviewComment {
+            line_start: Some(48),
+            line_end: 50,
+            comment: format!("{MARKER}A new comment"),
+            path: "src/lib.rs".to_string(),
+        },
+        ReviewComment {
+            line_start: None,
+            line_end: 7,
+            comment: "A comment on a line that is not in the diff".to_string(),
+            path: "src/main.rs".to_string(),
+        },
+    ]
+}
+
+async fn run_review(test_params: &TestParams) -> Result<ReviewOutcome, RestClientError> {
+    let mut server = Server::new_async().await;
+    unsafe {
+        env::set_var("GITLAB_CI", "true");
+        env::set_var("CI_API_V4_URL", format!("{}/api/v4", server.url()));
+        env::set_var("CI_PROJECT_ID", PROJECT);
+        env::set_var("CI_COMMIT_SHA", SHA);
+        env::set_var("CI_MERGE_REQUEST_PROJECT_URL", PROJECT_URL);
+        env::remove_var("CI_MERGE_REQUEST_TARGET_BRANCH_SHA");
+        env::remove_var("CI_MERGE_REQUEST_SOURCE_BRANCH_SHA");
+        if test_params.no_diff_base {
+            env::remove_var("CI_MERGE_REQUEST_DIFF_BASE_SHA");
+        } else {
+            env::set_var("CI_MERGE_REQUEST_DIFF_BASE_SHA", BASE_SHA);
+        }
+        if test_params.event_t == EventType::PullRequest {
+            env::set_var("CI_PIPELINE_SOURCE", "merge_request_event");
+            env::set_var("CI_MERGE_REQUEST_IID", MR.to_string());
+        } else {
+            env::set_var("CI_PIPELINE_SOURCE", "push");
+            env::remove_var("CI_MERGE_REQUEST_IID");
+        }
+        env::remove_var("CI_JOB_TOKEN");
+        if test_params.no_token {
+            env::remove_var("GITLAB_TOKEN");
+        } else {
+            env::set_var("GITLAB_TOKEN", TOKEN);
+        }
+    }
+
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    let mut client = GitlabApiClient::new().unwrap();
+    assert!(client.capabilities().pr_reviews);
+    client.set_dry_run(test_params.dry_run);
+
+    let mr_path = format!("/api/v4/projects/{PROJECT}/merge_requests/{MR}");
+    let discussions_path = format!("{mr_path}/discussions");
+    let mut mocks = vec![];
+
+    if test_params.event_t == EventType::Push || test_params.no_token {
+        // nothing is requested outside of a merge request or without a token
+        for method in ["GET", "POST", "PUT", "DELETE"] {
+            mocks.push(server.mock(method, Matcher::Any).expect(0).create());
+        }
+    } else {
+        for pg in ["1", "2"] {
+            let link = if pg == "1" {
+                format!("<{}{discussions_path}?page=2>; rel=\"next\"", server.url())
+            } else {
+                "".to_string()
+            };
+            let mut mock = mock_api(&mut server, "GET", discussions_path.as_str())
+                .match_query(Matcher::UrlEncoded("page".to_string(), pg.to_string()))
+                .with_header("link", link.as_str());
+            if test_params.fail_get_discussions {
+                mock = mock.with_status(403).expect(if pg == "1" { 1 } else { 0 });
+            } else {
+                mock =
+                    mock.with_body_from_file(format!("{MOCK_ASSETS_PATH}discussions_pg{pg}.json"));
+            }
+            mocks.push(mock.create());
+        }
+
+        let outdated_discussions = if test_params.fail_get_discussions {
+            vec![]
+        } else {
+            vec![(OUTDATED_SUMMARY, 1001), (OUTDATED_COMMENT, 1004)]
+        };
+        for (discussion_id, note_id) in outdated_discussions {
+            let discussion_path = format!("{discussions_path}/{discussion_id}");
+            let mock = if test_params.delete_review_comments {
+                mock_api(
+                    &mut server,
+                    "DELETE",
+                    format!("{discussion_path}/notes/{note_id}").as_str(),
+                )
+                .with_status(204)
+            } else {
+                mock_api(&mut server, "PUT", discussion_path.as_str())
+                    .match_query(Matcher::UrlEncoded(
+                        "resolved".to_string(),
+                        "true".to_string(),
+                    ))
+                    .with_status(200)
+            };
+            mocks.push(
+                mock.expect(if test_params.dry_run { 0 } else { 1 })
+                    .create(),
+            );
+        }
+
+        if !test_params.no_diff_base {
+            // the review's summary
+            mocks.push(
+                mock_api(&mut server, "POST", discussions_path.as_str())
+                    .match_body(Matcher::Json(serde_json::json!({
+                        "body": format!("{MARKER}This is a summary of the MR review."),
+                    })))
+                    .with_status(201)
+                    .with_body_from_file(format!("{MOCK_ASSETS_PATH}posted_discussion.json"))
+                    .expect(if test_params.dry_run { 0 } else { 1 })
+                    .create(),
+            );
+            // the review comments that are not reused
+            let mut new_comments = vec![("src/lib.rs", 50, 201), ("src/main.rs", 7, 400)];
+            if test_params.fail_get_discussions {
+                new_comments.push(("src/lib.rs", 42, 201));
+            }
+            for (path, line, status) in new_comments {
+                let body = if status == 201 {
+                    r#"{"id":"d1","notes":[{"id":1011,"body":"","author":{"username":"bot","id":1}}]}"#
+                } else {
+                    r#"{"message":"400 Bad request - Note {:line_code=>[\"can't be blank\"]}"}"#
+                };
+                mocks.push(
+                    mock_api(&mut server, "POST", discussions_path.as_str())
+                        .match_body(Matcher::PartialJson(serde_json::json!({
+                            "position": {
+                                "position_type": "text",
+                                "base_sha": BASE_SHA,
+                                "start_sha": BASE_SHA,
+                                "head_sha": SHA,
+                                "new_path": path,
+                                "new_line": line,
+                            },
+                        })))
+                        .with_status(status)
+                        .with_body(body)
+                        .expect(if test_params.dry_run { 0 } else { 1 })
+                        .create(),
+                );
+            }
+        }
+
+        let approval_endpoint = match test_params.action {
+            ReviewAction::Comment => None,
+            ReviewAction::Approve => Some("approve"),
+            ReviewAction::RequestChanges => Some("unapprove"),
+        };
+        if let Some(endpoint) = approval_endpoint {
+            mocks.push(
+                mock_api(
+                    &mut server,
+                    "POST",
+                    format!("{mr_path}/{endpoint}").as_str(),
+                )
+                .with_status(test_params.approval_status)
+                .with_body("{}")
+                .expect(if test_params.no_diff_base || test_params.dry_run {
+                    0
+                } else {
+                    1
+                })
+                .create(),
+            );
+        }
+    }
+
+    let mut options = ReviewOptions {
+        action: test_params.action.clone(),
+        summary: "This is a summary of the MR review.".to_string(),
+        comments: proposed_comments(),
+        marker: MARKER.to_string(),
+        delete_review_comments: test_params.delete_review_comments,
+        ..Default::default()
+    };
+    let result = match client.cull_pr_reviews(&mut options).await {
+        Ok(()) => client.post_pr_review(&options).await,
+        Err(e) => Err(e),
+    };
+    for mock in mocks {
+        mock.assert();
+    }
+    result
+}
+
+#[tokio::test]
+async fn post_review() {
+    let outcome = run_review(&TestParams::default()).await.unwrap();
+    assert_eq!(outcome.review_id, Some(POSTED_NOTE_ID));
+    assert_eq!(
+        outcome.html_url.unwrap().as_str(),
+        format!("{PROJECT_URL}/-/merge_requests/{MR}#note_{POSTED_NOTE_ID}")
+    );
+    // the comment on a line that is not in the diff is rejected
+    assert_eq!(outcome.posted_comments, 1);
+    assert_eq!(outcome.reused_comments, 1);
+    assert_eq!(outcome.dismissed_reviews, 1);
+    assert!(outcome.skipped.is_none());
+}
+
+#[tokio::test]
+async fn delete_outdated() {
+    let outcome = run_review(&TestParams {
+        delete_review_comments: true,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    assert_eq!(outcome.reused_comments, 1);
+    assert_eq!(outcome.dismissed_reviews, 1);
+}
+
+#[tokio::test]
+async fn fail_get_discussions() {
+    let outcome = run_review(&TestParams {
+        fail_get_discussions: true,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    assert_eq!(outcome.posted_comments, 2);
+    assert_eq!(outcome.reused_comments, 0);
+    assert_eq!(outcome.dismissed_reviews, 0);
+}
+
+#[tokio::test]
+async fn approve() {
+    run_review(&TestParams {
+        action: ReviewAction::Approve,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn approve_denied() {
+    // the review is downgraded to only comments
+    let outcome = run_review(&TestParams {
+        action: ReviewAction::Approve,
+        approval_status: 403,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    assert_eq!(outcome.review_id, Some(POSTED_NOTE_ID));
+}
+
+#[tokio::test]
+async fn approve_server_error() {
+    let err = run_review(&TestParams {
+        action: ReviewAction::Approve,
+        approval_status: 501,
+        ..Default::default()
+    })
+    .await
+    .unwrap_err();
+    assert!(matches!(err, RestClientError::Http { .. }));
+}
+
+#[tokio::test]
+async fn request_changes() {
+    run_review(&TestParams {
+        action: ReviewAction::RequestChanges,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn request_changes_without_approval() {
+    run_review(&TestParams {
+        action: ReviewAction::RequestChanges,
+        approval_status: 404,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn dry_run() {
+    let outcome = run_review(&TestParams {
+        action: ReviewAction::Approve,
+        dry_run: true,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    assert!(outcome.review_id.is_none());
+    assert_eq!(outcome.posted_comments, 2);
+    assert_eq!(outcome.reused_comments, 1);
+}
+
+#[tokio::test]
+async fn push_event() {
+    let outcome = run_review(&TestParams {
+        event_t: EventType::Push,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    assert_eq!(outcome, ReviewOutcome::default());
+}
+
+#[tokio::test]
+async fn no_token() {
+    let err = run_review(&TestParams {
+        no_token: true,
+        ..Default::default()
+    })
+    .await
+    .unwrap_err();
+    assert!(matches!(err, RestClientError::MissingEnvVars { .. }));
+}
+
+#[tokio::test]
+async fn no_diff_base() {
+    let err = run_review(&TestParams {
+        no_diff_base: true,
+        ..Default::default()
+    })
+    .await
+    .unwrap_err();
+    assert!(matches!(err, RestClientError::EnvVar { .. }));
+}
@@ -0,0 +1,423 @@
+#![cfg(feature = "gitlab")]
+use chrono::Utc;
+use git_bot_feedback::{
+    CommentKind, CommentPolicy, RestApiClient, RestClientError, ThreadCommentOptions,
+    client::GitlabApiClient, reqwest::StatusCode,
+};
+use mockito::{Matcher, Server};
+use std::env;
+
+mod common;
+use common::{EventType, logger_init};
+
+const MARKER: &str = "<!-- git-bot-feedback -->\n";
+const SHA: &str = "deadbeef";
+const PROJECT: &str = "42";
+const MR: i64 = 22;
+const TOKEN: &str = "123456";
+const MOCK_ASSETS_PATH: &str = "tests/assets/thread_comment/gitlab/";
+
+const RESET_RATE_LIMIT_HEADER: &str = "RateLimit-Reset";
+const REMAINING_RATE_LIMIT_HEADER: &str = "RateLimit-Remaining";
+const DELETION_DENIED: &str = r#"{"message":"403 Forbidden"}"#;
+const POSTING_DENIED: &str = r#"{"message":"403 Forbidden - Cannot post notes"}"#;
+
+struct TestParams {
+    event_t: EventType,
+    comment_policy: CommentPolicy,
+    no_lgtm: bool,
+    comment_kind: CommentKind,
+    fail_get_existing_comments: bool,
+    fail_dismissal: bool,
+    fail_posting: bool,
+    bad_existing_comments: bool,
+    bad_mr_iid: bool,
+    no_token: bool,
+    job_token: bool,
+    no_project_env_var: bool,
+    no_sha_env_var: bool,
+}
+
+impl Default for TestParams {
+    fn default() -> Self {
+        Self {
+            event_t: EventType::PullRequest,
+            comment_policy: CommentPolicy::Update,
+            no_lgtm: false,
+            comment_kind: CommentKind::Concerns,
+            fail_get_existing_comments: false,
+            fail_dismissal: false,
+            fail_posting: false,
+            bad_existing_comments: false,
+            bad_mr_iid: false,
+            no_token: false,
+            job_token: false,
+            no_project_env_var: false,
+            no_sha_env_var: false,
+        }
+    }
+}
+
+/// Get the name and value of the header that should authenticate requests.
+fn auth_header(test_params: &TestParams) -> (&'static str, &'static str) {
+    if test_params.job_token {
+        ("JOB-TOKEN", TOKEN)
+    } else {
+        ("PRIVATE-TOKEN", TOKEN)
+    }
+}
+
+async fn test_comment(test_params: &TestParams) {
+    let mut server = Server::new_async().await;
+    unsafe {
+        env::set_var("GITLAB_CI", "true");
+        env::set_var("CI_API_V4_URL", format!("{}/api/v4", server.url()));
+        env::set_var(
+            "CI_PIPELINE_SOURCE",
+            match test_params.event_t {
+                EventType::Push => "push",
+                EventType::PullRequest => "merge_request_event",
+            },
+        );
+        if test_params.event_t == EventType::PullRequest {
+            let iid = if test_params.bad_mr_iid {
+                "not a number".to_string()
+            } else {
+                MR.to_string()
+            };
+            env::set_var("CI_MERGE_REQUEST_IID", iid);
+        } else {
+            env::remove_var("CI_MERGE_REQUEST_IID");
+        }
+        if !test_params.no_project_env_var {
+            env::set_var("CI_PROJECT_ID", PROJECT);
+        } else {
+            env::remove_var("CI_PROJECT_ID");
+        }
+        if !test_params.no_sha_env_var {
+            env::set_var("CI_COMMIT_SHA", SHA);
+        } else {
+            env::remove_var("CI_COMMIT_SHA");
+        }
+        env::remove_var("GITLAB_TOKEN");
+        env::remove_var("CI_JOB_TOKEN");
+        if !test_params.no_token {
+            env::set_var(
+                if test_params.job_token {
+                    "CI_JOB_TOKEN"
+                } else {
+                    "GITLAB_TOKEN"
+                },
+                TOKEN,
+            );
+        }
+        env::set_var("CI_DEBUG_TRACE", "true");
+    }
+
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    let client = match GitlabApiClient::new() {
+        Ok(c) => c,
+        Err(e) => {
+            if test_params.no_project_env_var || test_params.no_sha_env_var {
+                assert!(matches!(e, RestClientError::EnvVar { .. }));
+            } else if test_params.bad_mr_iid {
+                assert!(matches!(e, RestClientError::MalformedEventInfo(_)));
+            } else {
+                panic!("Unexpected error creating GitlabApiClient: {e}");
+            }
+            return;
+        }
+    };
+    assert!(client.is_debug_enabled());
+    assert_eq!(
+        client.is_pr_event(),
+        test_params.event_t == EventType::PullRequest
+    );
+
+    let reset_timestamp = (Utc::now().timestamp() + 60).to_string();
+    let (auth_name, auth_value) = auth_header(test_params);
+    let notes_path = format!("/api/v4/projects/{PROJECT}/merge_requests/{MR}/notes");
+    let note_path = format!("{notes_path}/76453652");
+    let mut mocks = vec![];
+
+    if test_params.event_t == EventType::Push {
+        // thread comments are not posted outside of merge requests
+        for method in ["GET", "POST", "PUT", "DELETE"] {
+            mocks.push(server.mock(method, Matcher::Any).expect(0).create());
+        }
+    } else if !test_params.no_token {
+        for pg in ["1", "2"] {
+            let link = if pg == "1" {
+                format!("<{}{notes_path}?page=2>; rel=\"next\"", server.url())
+            } else {
+                "".to_string()
+            };
+            let mut mock = server
+                .mock("GET", notes_path.as_str())
+                .match_header(auth_name, auth_value)
+                .match_query(Matcher::UrlEncoded("page".to_string(), pg.to_string()))
+                .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+                .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+                .with_header("link", link.as_str())
+                .with_status(if test_params.fail_get_existing_comments {
+                    403
+                } else {
+                    200
+                });
+            if test_params.bad_existing_comments {
+                mock = mock.with_body(String::new());
+            } else {
+                mock = mock.with_body_from_file(format!("{MOCK_ASSETS_PATH}mr_notes_pg{pg}.json"));
+            }
+            if pg == "2"
+                && (test_params.fail_get_existing_comments || test_params.bad_existing_comments)
+            {
+                // the traversal stops at the first page
+                mock = mock.expect(0);
+            }
+            mocks.push(mock.create());
+        }
+
+        if !test_params.fail_get_existing_comments && !test_params.bad_existing_comments {
+            mocks.push(
+                server
+                    .mock("DELETE", note_path.as_str())
+                    .match_header(auth_name, auth_value)
+                    .with_status(if test_params.fail_dismissal { 403 } else { 204 })
+                    .with_body(if test_params.fail_dismissal {
+                        DELETION_DENIED
+                    } else {
+                        ""
+                    })
+                    .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+                    .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+                    .expect_at_least(1)
+                    .create(),
+            );
+        }
+    }
+
+    let comment = match test_params.comment_kind {
+        CommentKind::Concerns => "Attention".to_string(),
+        CommentKind::Lgtm => "LGTM".to_string(),
+    };
+    let new_comment_match = Matcher::PartialJson(serde_json::json!({
+        "body": format!("{MARKER}{comment}"),
+    }));
+
+    let posting_comment = match test_params.comment_kind {
+        CommentKind::Concerns => true,
+        CommentKind::Lgtm => !test_params.no_lgtm && !test_params.bad_existing_comments,
+    };
+    if posting_comment && test_params.event_t == EventType::PullRequest && !test_params.no_token {
+        let (method, path) = if test_params.bad_existing_comments
+            || test_params.fail_get_existing_comments
+            || test_params.comment_policy == CommentPolicy::Anew
+        {
+            ("POST", notes_path.as_str())
+        } else {
+            ("PUT", note_path.as_str())
+        };
+        mocks.push(
+            server
+                .mock(method, path)
+                .match_body(new_comment_match)
+                .match_header(auth_name, auth_value)
+                .match_header("Content-Type", "application/json")
+                .with_status(if test_params.fail_posting { 403 } else { 200 })
+                .with_body(POSTING_DENIED)
+                .with_header(REMAINING_RATE_LIMIT_HEADER, "50")
+                .with_header(RESET_RATE_LIMIT_HEADER, reset_timestamp.as_str())
+                // a failed deletion stops the update
+                .expect(usize::from(!test_params.fail_dismissal))
+                .create(),
+        );
+    }
+
+    let opts = ThreadCommentOptions {
+        policy: test_params.comment_policy,
+        comment,
+        kind: test_params.comment_kind,
+        marker: MARKER.to_string(),
+        no_lgtm: test_params.no_lgtm,
+        ..Default::default()
+    };
+    client.start_log_group("posting comment");
+    let result = client.post_thread_comment(opts).await;
+    client.end_log_group("posting comment");
+    if test_params.bad_existing_comments {
+        assert!(matches!(result, Err(RestClientError::Json { .. })));
+    } else if test_params.no_token && test_params.event_t == EventType::PullRequest {
+        assert!(matches!(
+            result,
+            Err(RestClientError::MissingEnvVars { ref checked })
+                if checked == &["GITLAB_TOKEN", "CI_JOB_TOKEN"]
+        ));
+    } else if test_params.fail_dismissal || test_params.fail_posting {
+        let expected_body = if test_params.fail_dismissal {
+            DELETION_DENIED
+        } else {
+            POSTING_DENIED
+        };
+        assert!(
+            matches!(
+                &result,
+                Err(RestClientError::Http { status, body, .. })
+                    if *status == StatusCode::FORBIDDEN && body == expected_body
+            ),
+            "Expected Http error, got: {result:?}"
+        );
+    } else {
+        assert!(result.is_ok(), "Unexpected error: {result:?}");
+    }
+    for mock in mocks {
+        mock.assert();
+    }
+}
+
+#[tokio::test]
+async fn new_push() {
+    test_comment(&TestParams {
+        event_t: EventType::Push,
+        comment_policy: CommentPolicy::Anew,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn update_push() {
+    test_comment(&TestParams {
+        event_t: EventType::Push,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn new_mr() {
+    test_comment(&TestParams {
+        comment_policy: CommentPolicy::Anew,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn update_mr() {
+    test_comment(&TestParams::default()).await;
+}
+
+#[tokio::test]
+async fn update_mr_job_token() {
+    test_comment(&TestParams {
+        job_token: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn new_mr_no_lgtm() {
+    test_comment(&TestParams {
+        comment_policy: CommentPolicy::Anew,
+        no_lgtm: true,
+        comment_kind: CommentKind::Lgtm,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn update_mr_no_lgtm() {
+    test_comment(&TestParams {
+        comment_kind: CommentKind::Lgtm,
+        no_lgtm: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn fail_get_existing_comments() {
+    test_comment(&TestParams {
+        fail_get_existing_comments: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn fail_dismissal() {
+    test_comment(&TestParams {
+        fail_dismissal: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn fail_posting() {
+    test_comment(&TestParams {
+        fail_posting: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn bad_existing_comments() {
+    test_comment(&TestParams {
+        bad_existing_comments: true,
+        comment_kind: CommentKind::Lgtm,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn bad_mr_iid() {
+    test_comment(&TestParams {
+        bad_mr_iid: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn no_token() {
+    test_comment(&TestParams {
+        no_token: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn no_token_push() {
+    test_comment(&TestParams {
+        event_t: EventType::Push,
+        no_token: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn no_project_env() {
+    test_comment(&TestParams {
+        no_project_env_var: true,
+        ..Default::default()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn no_sha_env() {
+    test_comment(&TestParams {
+        no_sha_env_var: true,
+        ..Default::default()
+    })
+    .await;
+}
@@ -1,17 +1,19 @@
-use std::env;
+use std::{env, fs, time::Duration};
 
 use chrono::Utc;
 use git_bot_feedback::{
-    FileAnnotation, OutputVariable, RestApiClient, RestApiRateLimitHeaders, RestClientError,
-    ReviewOptions, ThreadCommentOptions,
-    client::{LocalClient, init_client},
+    AnnotationOverflow, CommentKind, FileAnnotation, OutputVariable, RestApiClient,
+    RestApiRateLimitHeaders, RestClientError, ReviewAction, ReviewComment, ReviewOptions,
+    ThreadCommentOptions,
+    client::{LocalClient, PageLinks, init_client},
+    reqwest::{
+        Client, Method, StatusCode, Url,
+        header::{HeaderMap, HeaderName, HeaderValue},
+    },
+    summary::SummaryOverflow,
 };
 use mockito::{Matcher, Server};
-use reqwest::{
-    Client, Method, StatusCode,
-    header::{HeaderMap, HeaderName, HeaderValue},
-};
-use url::Url;
+use tempfile::TempDir;
 
 mod common;
 use common::logger_init;
@@ -25,6 +27,7 @@ struct RateLimitTestParams {
     bad_reset_timestamp: bool,
     has_retry_interval: bool,
     bad_retry_interval: bool,
+    http_date_retry: bool,
 }
 
 async fn simulate_rate_limit(test_params: &RateLimitTestParams) {
@@ -72,6 +75,9 @@ async fn simulate_rate_limit(test_params: &RateLimitTestParams) {
             &rate_limit_headers.retry,
             if test_params.bad_retry_interval {
                 "X"
+            } else if test_params.http_date_retry {
+                // a date in the past means no waiting
+                "Wed, 21 Oct 2015 07:28:00 GMT"
             } else {
                 "0"
             },
@@ -113,11 +119,26 @@ async fn simulate_rate_limit(test_params: &RateLimitTestParams) {
         || test_params.bad_remaining_count
         || test_params.bad_reset_timestamp
     {
-        assert!(matches!(err, RestClientError::HeaderParseInt(_)));
+        assert!(
+            err.to_string()
+                .starts_with("Failed to parse rate limit headers: ")
+        );
+        assert!(matches!(
+            err,
+            RestClientError::WithContext { source, .. }
+                if matches!(*source, RestClientError::HeaderParseInt(_))
+        ));
     } else if test_params.has_reset_timestamp {
         assert!(matches!(err, RestClientError::RateLimitPrimary(_)));
     } else if test_params.secondary {
-        assert!(matches!(err, RestClientError::RateLimitSecondary));
+        // both the interval and the HTTP-date (in the past) mean "retry now"
+        assert!(matches!(
+            err,
+            RestClientError::RateLimitSecondary {
+                retry_after: Some(Duration::ZERO)
+            }
+        ));
+        assert!(err.to_string().ends_with("; retry after 0 seconds"));
     } else {
         assert!(matches!(err, RestClientError::RateLimitNoReset));
     }
@@ -133,6 +154,19 @@ async fn rate_limit_secondary() {
     .await;
 }
 
+// parsing an HTTP-date requires the `chrono` feature
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn rate_limit_secondary_http_date() {
+    simulate_rate_limit(&RateLimitTestParams {
+        secondary: true,
+        has_retry_interval: true,
+        http_date_retry: true,
+        ..Default::default()
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn rate_limit_bad_retry() {
     simulate_rate_limit(&RateLimitTestParams {
@@ -184,9 +218,121 @@ async fn rate_limit_bad_count() {
     .await;
 }
 
+fn dummy_rate_limit_headers() -> RestApiRateLimitHeaders {
+    RestApiRateLimitHeaders {
+        reset: "reset".to_string(),
+        remaining: "remaining".to_string(),
+        retry: "retry".to_string(),
+    }
+}
+
+/// Send a request to a server that responds with `status` for the first `failures` attempts.
+///
+/// Returns the status of the final response.
+async fn simulate_server_error(status: usize, failures: usize) -> StatusCode {
+    let rate_limit_headers = dummy_rate_limit_headers();
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+
+    let mut server = Server::new_async().await;
+    let client = Client::new();
+    let failing = server
+        .mock("GET", "/")
+        .with_status(status)
+        .with_body("server error")
+        .expect(failures)
+        .create();
+    let passing = server
+        .mock("GET", "/")
+        .with_status(200)
+        .expect(usize::from(failures < 5))
+        .create();
+    let test_client = LocalClient::default();
+    let request = test_client
+        .make_api_request(
+            &client,
+            Url::parse(&server.url()).unwrap(),
+            Method::GET,
+            None,
+            None,
+        )
+        .unwrap();
+    let response = test_client
+        .send_api_request(&client, request, &rate_limit_headers)
+        .await
+        .unwrap();
+    failing.assert();
+    passing.assert();
+    response.status()
+}
+
+#[tokio::test]
+async fn retry_bad_gateway() {
+    assert_eq!(simulate_server_error(502, 1).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn retry_unavailable_exhausted() {
+    assert_eq!(
+        simulate_server_error(503, 5).await,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+}
+
+#[tokio::test]
+async fn no_retry_not_found() {
+    let rate_limit_headers = dummy_rate_limit_headers();
+    logger_init();
+    let mut server = Server::new_async().await;
+    let client = Client::new();
+    let mock = server.mock("GET", "/").with_status(404).expect(1).create();
+    let test_client = LocalClient::default();
+    let request = test_client
+        .make_api_request(
+            &client,
+            Url::parse(&server.url()).unwrap(),
+            Method::GET,
+            None,
+            None,
+        )
+        .unwrap();
+    let response = test_client
+        .send_api_request(&client, request, &rate_limit_headers)
+        .await
+        .unwrap();
+    mock.assert();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn retry_connection_refused() {
+    logger_init();
+    // bind then drop a listener to get a local port that refuses connections
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let client = Client::new();
+    let test_client = LocalClient::default();
+    let request = test_client
+        .make_api_request(
+            &client,
+            Url::parse(&format!("http://{addr}")).unwrap(),
+            Method::GET,
+            None,
+            None,
+        )
+        .unwrap();
+    let err = test_client
+        .send_api_request(&client, request, &dummy_rate_limit_headers())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RestClientError::Request(e) if e.is_connect()));
+}
+
 #[tokio::test]
 async fn dummy_coverage() {
-    let mut test_client = LocalClient;
+    let mut test_client = LocalClient::default();
     let log_group_name = "Dummy test";
     test_client.start_log_group(log_group_name);
     test_client
@@ -196,7 +342,9 @@ async fn dummy_coverage() {
         })
         .await
         .unwrap();
-    test_client.append_step_summary("").unwrap();
+    test_client
+        .append_step_summary("", SummaryOverflow::default())
+        .unwrap();
     test_client
         .write_output_variables(&[OutputVariable {
             name: "key".to_string(),
@@ -209,10 +357,159 @@ async fn dummy_coverage() {
     test_client.cull_pr_reviews(&mut options).await.unwrap();
     test_client.post_pr_review(&options).await.unwrap();
     let annotation = FileAnnotation::default();
-    test_client.write_file_annotations(&[annotation]).unwrap();
+    let written = test_client
+        .write_file_annotations(&[annotation], AnnotationOverflow::default())
+        .unwrap();
+    assert_eq!(written, 1);
     test_client.end_log_group(log_group_name);
 }
 
+// ************************************************* feedback_dir tests
+
+#[tokio::test]
+async fn feedback_dir_thread_comment() {
+    logger_init();
+    let dir = TempDir::new().unwrap();
+    let feedback_dir = dir.path().join("feedback");
+    let client = LocalClient::with_feedback_dir(&feedback_dir);
+    assert_eq!(client.feedback_dir(), Some(feedback_dir.as_path()));
+    assert!(!client.is_pr_event());
+
+    client
+        .post_thread_comment(ThreadCommentOptions {
+            comment: "Some concerns".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let comment = feedback_dir.join("comment.md");
+    assert_eq!(fs::read_to_string(&comment).unwrap(), "Some concerns");
+
+    client
+        .post_thread_comment(ThreadCommentOptions {
+            comment: "Looks good".to_string(),
+            kind: CommentKind::Lgtm,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(fs::read_to_string(&comment).unwrap(), "Looks good");
+
+    // an outdated comment is removed instead of posting a LGTM comment
+    client
+        .post_thread_comment(ThreadCommentOptions {
+            comment: "Looks good".to_string(),
+            kind: CommentKind::Lgtm,
+            no_lgtm: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(!comment.exists());
+}
+
+#[tokio::test]
+async fn feedback_dir_review() {
+    logger_init();
+    let dir = TempDir::new().unwrap();
+    let mut client = LocalClient::with_feedback_dir(dir.path());
+    let options = ReviewOptions {
+        action: ReviewAction::RequestChanges,
+        summary: "Found 2 concerns".to_string(),
+        comments: vec![
+            ReviewComment {
+                line_start: Some(3),
+                line_end: 5,
+                comment: "Rename this".to_string(),
+                path: "src/lib.rs".to_string(),
+            },
+            ReviewComment {
+                line_start: None,
+                line_end: 10,
+                comment: "Remove this".to_string(),
+                path: "src/main.rs".to_string(),
+            },
+        ],
+        ..Default::default()
+    };
+    let outcome = client.post_pr_review(&options).await.unwrap();
+    assert_eq!(outcome.posted_comments, 2);
+    assert_eq!(
+        fs::read_to_string(dir.path().join("review.md")).unwrap(),
+        "# Review: request-changes\n\
+         \n\
+         Found 2 concerns\n\
+         \n\
+         ## src/lib.rs:3-5\n\
+         \n\
+         Rename this\n\
+         \n\
+         ## src/main.rs:10\n\
+         \n\
+         Remove this\n"
+    );
+}
+
+#[test]
+fn feedback_dir_output_variables() {
+    logger_init();
+    let dir = TempDir::new().unwrap();
+    let client = LocalClient::with_feedback_dir(dir.path());
+    client
+        .write_output_variables(&[OutputVariable {
+            name: "checks-failed".to_string(),
+            value: "0".to_string(),
+        }])
+        .unwrap();
+    client
+        .write_output_variables(&[OutputVariable {
+            name: "report".to_string(),
+            value: "line 1\nline 2".to_string(),
+        }])
+        .unwrap();
+    let outputs = fs::read_to_string(dir.path().join("outputs.env")).unwrap();
+    let mut lines = outputs.lines();
+    assert_eq!(lines.next(), Some("checks-failed=0"));
+    assert!(lines.next().unwrap().starts_with("report<<"));
+    assert_eq!(lines.next(), Some("line 1"));
+    assert_eq!(lines.next(), Some("line 2"));
+
+    // invalid variables are not written
+    assert!(
+        client
+            .write_output_variables(&[OutputVariable {
+                name: "bad name".to_string(),
+                value: "value".to_string(),
+            }])
+            .is_err()
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("outputs.env")).unwrap(),
+        outputs
+    );
+}
+
+#[test]
+fn feedback_dir_file_annotations() {
+    logger_init();
+    let dir = TempDir::new().unwrap();
+    let client = LocalClient::with_feedback_dir(dir.path());
+    let written = client
+        .write_file_annotations(
+            &[FileAnnotation {
+                path: "src/lib.rs".to_string(),
+                start_line: Some(1),
+                message: "Some concern".to_string(),
+                ..Default::default()
+            }],
+            AnnotationOverflow::default(),
+        )
+        .unwrap();
+    assert_eq!(written, 1);
+    // annotations are written to stderr
+    assert!(fs::read_dir(dir.path()).unwrap().next().is_none());
+}
+
 // ************************************************* try_next_page() tests
 
 #[test]
@@ -225,7 +522,8 @@ fn bad_link_header() {
     );
     logger_init();
     log::set_max_level(log::LevelFilter::Debug);
-    let test_client = LocalClient;
+    let test_client = LocalClient::default();
+    #[allow(deprecated)]
     let result = test_client.try_next_page(&headers);
     assert!(result.is_none());
 }
@@ -243,11 +541,143 @@ fn bad_link_domain() {
     );
     logger_init();
     log::set_max_level(log::LevelFilter::Debug);
-    let test_client = LocalClient;
+    let test_client = LocalClient::default();
+    #[allow(deprecated)]
     let result = test_client.try_next_page(&headers);
     assert!(result.is_none());
 }
 
+// ************************************************* page_links() tests
+
+const BASE_URL: &str = "https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=2";
+
+fn page_links(link: &str) -> PageLinks {
+    let mut headers = HeaderMap::with_capacity(1);
+    headers.insert("link", HeaderValue::from_str(link).unwrap());
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    LocalClient::default().page_links(&headers, &Url::parse(BASE_URL).unwrap())
+}
+
+fn assert_page(url: &Option<Url>, page: u32) {
+    let expected =
+        format!("https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page={page}");
+    assert_eq!(url.as_ref().unwrap().as_str(), expected);
+}
+
+#[test]
+fn link_header_github() {
+    let links = page_links(
+        "<https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=3>; rel=\"next\", \
+         <https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=12>; rel=\"last\", \
+         <https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=1>; rel=\"first\", \
+         <https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=1>; rel=\"prev\"",
+    );
+    assert_page(&links.next, 3);
+    assert_page(&links.first, 1);
+    assert_page(&links.prev, 1);
+    assert_eq!(links.last_page(), Some(12));
+}
+
+#[test]
+fn link_header_extra_params() {
+    let links = page_links(
+        "<https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=3>; rel=\"next\"; title=\"next, page\", \
+         <https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=5>; type=\"application/json\"; rel=last",
+    );
+    assert_page(&links.next, 3);
+    assert_eq!(links.last_page(), Some(5));
+}
+
+#[test]
+fn link_header_single_quotes() {
+    let links = page_links(
+        "<https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=3>; rel='next'",
+    );
+    assert_page(&links.next, 3);
+}
+
+#[test]
+fn link_header_no_spaces() {
+    let links = page_links(
+        "<https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=3>;rel=\"next\",\
+         <https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=4>;rel=\"last\"",
+    );
+    assert_page(&links.next, 3);
+    assert_eq!(links.last_page(), Some(4));
+}
+
+#[test]
+fn link_header_relative() {
+    let links = page_links(
+        "</api/v1/repos/owner/repo/issues/1/comments?page=3>; rel=\"next\", <comments?page=4>; rel=\"last\"",
+    );
+    assert_page(&links.next, 3);
+    assert_page(&links.last, 4);
+
+    // the deprecated shim cannot resolve relative links
+    let mut headers = HeaderMap::with_capacity(1);
+    headers.insert(
+        "link",
+        HeaderValue::from_static(
+            "</api/v1/repos/owner/repo/issues/1/comments?page=3>; rel=\"next\"",
+        ),
+    );
+    #[allow(deprecated)]
+    let result = LocalClient::default().try_next_page(&headers);
+    assert!(result.is_none());
+}
+
+#[test]
+fn link_header_multiple_rels() {
+    let links = page_links(
+        "<https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=3>; rel=\"NEXT last\"",
+    );
+    assert_page(&links.next, 3);
+    assert_eq!(links.last_page(), Some(3));
+}
+
+#[test]
+fn link_header_multiple_headers() {
+    let mut headers = HeaderMap::with_capacity(2);
+    headers.append(
+        "link",
+        HeaderValue::from_static(
+            "<https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=3>; rel=\"next\"",
+        ),
+    );
+    headers.append(
+        "link",
+        HeaderValue::from_static(
+            "<https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=9>; rel=\"last\"",
+        ),
+    );
+    let links = LocalClient::default().page_links(&headers, &Url::parse(BASE_URL).unwrap());
+    assert_page(&links.next, 3);
+    assert_eq!(links.last_page(), Some(9));
+}
+
+#[test]
+fn link_header_malformed() {
+    // a malformed link value is skipped without affecting the others
+    let links = page_links(
+        "https://git.example.com/bad; rel=\"prev\", \
+         <https://git.example.com/unterminated; rel=\"first\"",
+    );
+    assert_eq!(links, PageLinks::default());
+    let links = page_links(
+        "<https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=1> junk; rel=\"first\", \
+         <https://git.example.com/api/v1/repos/owner/repo/issues/1/comments?page=3>; rel=\"next\"; rel=\"last\", \
+         <http://[::1>; rel=\"last\"",
+    );
+    assert!(links.first.is_none());
+    assert_page(&links.next, 3);
+    // only the first `rel` parameter is used
+    assert!(links.last.is_none());
+    let links = page_links("<https://git.example.com/no-rel>; title=\"no relation\"");
+    assert_eq!(links, PageLinks::default());
+}
+
 #[test]
 fn mk_request() {
     let client = Client::new();
@@ -259,7 +689,7 @@ fn mk_request() {
         HeaderName::from_static("key"),
         header_value.clone(),
     )]));
-    let test_client = LocalClient;
+    let test_client = LocalClient::default();
     let request = test_client
         .make_api_request(
             &client,
@@ -304,7 +734,7 @@ async fn list_file_changes() {
     // setup test client, logging, and file filter
     logger_init();
     log::set_max_level(log::LevelFilter::Debug);
-    let client = LocalClient;
+    let client = LocalClient::default();
     assert_eq!(client.client_kind().as_str(), "local");
     let file_filter = FileFilter::new(&[], &["toml", "md"], None);
 
@@ -406,3 +836,39 @@ fn walk_dir_strip_prefix() {
     println!("discovered files: {:?}", files);
     assert!(files.contains("tests/assets/file_changes/github/pr_files_pg1.json"));
 }
+
+#[cfg(feature = "file-changes")]
+#[test]
+fn walk_dir_missing_root() {
+    use git_bot_feedback::{DirWalkError, FileFilter};
+
+    fn walk(file_filter: &FileFilter) -> Result<usize, RestClientError> {
+        Ok(file_filter.walk_dir("not/a/real/path")?.len())
+    }
+
+    let file_filter = FileFilter::new(&[], &["rs"], None);
+    let err = walk(&file_filter).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Failed to read not/a/real/path: ")
+    );
+    assert!(matches!(
+        err,
+        RestClientError::DirWalk(DirWalkError::ReadDir { path, .. })
+            if path == std::path::Path::new("not/a/real/path")
+    ));
+}
+
+#[tokio::test]
+async fn file_content_unsupported() {
+    let client = LocalClient::default();
+    let err = client
+        .get_file_content("README.md", None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RestClientError::Unsupported { .. }));
+    assert_eq!(
+        err.to_string(),
+        "Failed to get content of README.md: unsupported on this server (local)"
+    );
+}
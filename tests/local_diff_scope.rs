@@ -0,0 +1,225 @@
+#![cfg(feature = "file-changes")]
+use std::{collections::HashMap, env, fs, path::Path, process::Command};
+
+use git_bot_feedback::{
+    FileDiffLines, FileFilter, FileStatus, LinesChangedOnly, RestApiClient, RestClientError,
+    client::{DiffScope, LocalClient},
+};
+use tempfile::TempDir;
+use tokio::sync::{Mutex, MutexGuard};
+
+mod common;
+use common::logger_init;
+
+/// The tests change the current working directory, which is shared by all tests.
+static CWD: Mutex<()> = Mutex::const_new(());
+
+/// Run `git` with the given `args` in the given `repo`.
+fn git(repo: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .current_dir(repo)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "`git {}` failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Create a scratch git repository (as the current working directory) with the following history:
+///
+/// - `main`: adds `a.txt` and `b.txt`, then adds `c.txt`.
+/// - `feature` (checked out, branched before `c.txt` was added): modifies `a.txt`.
+///
+/// Also, `b.txt` is modified in the working tree (not staged).
+async fn setup() -> (TempDir, MutexGuard<'static, ()>) {
+    let guard = CWD.lock().await;
+    logger_init();
+    let tmp_dir = TempDir::new().unwrap();
+    let repo = tmp_dir.path();
+    git(repo, &["init", "--initial-branch=main"]);
+    fs::write(repo.join("a.txt"), "a\n").unwrap();
+    fs::write(repo.join("b.txt"), "b\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-m", "add a and b"]);
+    git(repo, &["branch", "feature"]);
+    fs::write(repo.join("c.txt"), "c\n").unwrap();
+    git(repo, &["add", "c.txt"]);
+    git(repo, &["commit", "-m", "add c"]);
+    git(repo, &["checkout", "feature"]);
+    fs::write(repo.join("a.txt"), "a\nmore a\n").unwrap();
+    git(repo, &["commit", "-am", "modify a"]);
+    fs::write(repo.join("b.txt"), "b\nmore b\n").unwrap();
+    env::set_current_dir(repo).unwrap();
+    (tmp_dir, guard)
+}
+
+/// Make a shallow clone (with `--depth=1`) of the `feature` branch of the scratch repository
+/// (from [`setup()`]) and use it as the current working directory.
+fn shallow_clone(origin: &Path) -> TempDir {
+    let tmp_dir = TempDir::new().unwrap();
+    let origin_url = format!("file://{}", origin.display());
+    git(
+        tmp_dir.path(),
+        &[
+            "clone",
+            "--depth=1",
+            "--no-single-branch",
+            "--branch=feature",
+            origin_url.as_str(),
+            ".",
+        ],
+    );
+    env::set_current_dir(tmp_dir.path()).unwrap();
+    tmp_dir
+}
+
+async fn changed_files(
+    scope: DiffScope,
+) -> Result<HashMap<String, FileDiffLines>, RestClientError> {
+    changed_files_deepened(scope, 0).await
+}
+
+async fn changed_files_deepened(
+    scope: DiffScope,
+    max_deepen: u32,
+) -> Result<HashMap<String, FileDiffLines>, RestClientError> {
+    let mut client = LocalClient::default();
+    client.set_max_deepen(max_deepen);
+    assert_eq!(client.max_deepen(), max_deepen);
+    client.set_diff_scope(Some(scope.clone()));
+    assert_eq!(client.diff_scope(), Some(&scope));
+    let file_filter = FileFilter::new(&[], &["txt"], None);
+    client
+        .get_list_of_changed_files(&file_filter, &LinesChangedOnly::On, None, false)
+        .await
+}
+
+fn sorted_names(files: &HashMap<String, FileDiffLines>) -> Vec<&str> {
+    let mut names = files.keys().map(String::as_str).collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+#[tokio::test]
+async fn working_tree() {
+    let (_repo, _guard) = setup().await;
+    let files = changed_files(DiffScope::WorkingTree).await.unwrap();
+    assert_eq!(sorted_names(&files), ["b.txt"]);
+    assert_eq!(files["b.txt"].added_lines, vec![2]);
+}
+
+#[tokio::test]
+async fn against_ref() {
+    let (_repo, _guard) = setup().await;
+    let files = changed_files(DiffScope::AgainstRef("HEAD~1".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(sorted_names(&files), ["a.txt", "b.txt"]);
+    // a number of parents from HEAD
+    let files = changed_files(DiffScope::AgainstRef("1".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(sorted_names(&files), ["a.txt", "b.txt"]);
+}
+
+#[tokio::test]
+async fn merge_base() {
+    let (_repo, _guard) = setup().await;
+    let files = changed_files(DiffScope::MergeBase {
+        base: "main".to_string(),
+    })
+    .await
+    .unwrap();
+    // neither the changes on `main` nor the uncommitted changes
+    assert_eq!(sorted_names(&files), ["a.txt"]);
+    assert_eq!(files["a.txt"].added_lines, vec![2]);
+}
+
+#[tokio::test]
+async fn unknown_ref() {
+    let (_repo, _guard) = setup().await;
+    for scope in [
+        DiffScope::AgainstRef("origin/nope".to_string()),
+        DiffScope::MergeBase {
+            base: "origin/nope".to_string(),
+        },
+    ] {
+        let err = changed_files(scope).await.unwrap_err();
+        assert!(matches!(&err, RestClientError::GitRefNotFound(r) if r == "origin/nope"));
+        assert_eq!(
+            err.to_string(),
+            "Git reference 'origin/nope' does not exist"
+        );
+    }
+}
+
+#[tokio::test]
+async fn shallow_clone_error() {
+    let (origin, _guard) = setup().await;
+    let _clone = shallow_clone(origin.path());
+    let err = changed_files(DiffScope::AgainstRef("HEAD~1".to_string()))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        &err,
+        RestClientError::ShallowClone { missing_ref } if missing_ref == "HEAD~1"
+    ));
+    assert!(err.to_string().contains("git fetch --deepen"));
+    let err = changed_files(DiffScope::MergeBase {
+        base: "origin/main".to_string(),
+    })
+    .await
+    .unwrap_err();
+    assert!(matches!(
+        &err,
+        RestClientError::ShallowClone { missing_ref } if missing_ref == "origin/main"
+    ));
+}
+
+#[tokio::test]
+async fn shallow_clone_deepened() {
+    let (origin, _guard) = setup().await;
+    let _clone = shallow_clone(origin.path());
+    let files = changed_files_deepened(
+        DiffScope::MergeBase {
+            base: "origin/main".to_string(),
+        },
+        4,
+    )
+    .await
+    .unwrap();
+    assert_eq!(sorted_names(&files), ["a.txt"]);
+    let files = changed_files_deepened(DiffScope::AgainstRef("HEAD~1".to_string()), 4)
+        .await
+        .unwrap();
+    assert_eq!(sorted_names(&files), ["a.txt"]);
+}
+
+#[tokio::test]
+async fn staged_and_untracked() {
+    let (repo, _guard) = setup().await;
+    // `a.txt` is staged, `b.txt` is modified (not staged), and `d.txt` is untracked
+    fs::write(repo.path().join("a.txt"), "a\nmore a\neven more a\n").unwrap();
+    git(repo.path(), &["add", "a.txt"]);
+    fs::write(repo.path().join("d.txt"), "d\nmore d\n").unwrap();
+    fs::write(repo.path().join("d.md"), "# not a text file\n").unwrap();
+
+    let files = changed_files(DiffScope::Staged).await.unwrap();
+    assert_eq!(sorted_names(&files), ["a.txt"]);
+    assert_eq!(files["a.txt"].added_lines, vec![3]);
+
+    let files = changed_files(DiffScope::WorkingTree).await.unwrap();
+    assert_eq!(sorted_names(&files), ["a.txt", "b.txt"]);
+
+    let files = changed_files(DiffScope::WorkingTreeWithUntracked)
+        .await
+        .unwrap();
+    assert_eq!(sorted_names(&files), ["a.txt", "b.txt", "d.txt"]);
+    assert_eq!(files["d.txt"].status, FileStatus::Added);
+    assert_eq!(files["d.txt"].added_lines, vec![1, 2]);
+}
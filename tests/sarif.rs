@@ -0,0 +1,192 @@
+#![cfg(feature = "sarif")]
+use git_bot_feedback::{AnnotationLevel, FileAnnotation, parse_sarif};
+use std::{env, fs::File, path::Path};
+
+/// The workspace root used by the CI runners that generated the SARIF fixtures.
+const WORKSPACE: &str = "/home/runner/work/app/app";
+
+fn load_sarif(file_name: &str) -> Vec<FileAnnotation> {
+    unsafe {
+        env::set_var("GITHUB_WORKSPACE", WORKSPACE);
+    }
+    let path = Path::new("tests/assets/sarif").join(file_name);
+    parse_sarif(File::open(path).unwrap()).unwrap()
+}
+
+/// A condensed form of a [`FileAnnotation`] used for comparisons.
+type Summary<'a> = (
+    AnnotationLevel,
+    &'a str,
+    Option<usize>,
+    Option<usize>,
+    Option<usize>,
+    Option<usize>,
+    Option<&'a str>,
+);
+
+fn summarize(annotations: &[FileAnnotation]) -> Vec<Summary<'_>> {
+    annotations
+        .iter()
+        .map(|a| {
+            (
+                a.severity,
+                a.path.as_str(),
+                a.start_line,
+                a.end_line,
+                a.start_column,
+                a.end_column,
+                a.title.as_deref(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn clippy() {
+    let annotations = load_sarif("clippy.sarif");
+    assert_eq!(
+        summarize(&annotations),
+        [
+            (
+                AnnotationLevel::Warning,
+                "src/main.rs",
+                Some(12),
+                Some(12),
+                Some(5),
+                Some(17),
+                Some("clippy::needless_return"),
+            ),
+            (
+                AnnotationLevel::Error,
+                "src/lib.rs",
+                Some(41),
+                Some(44),
+                Some(5),
+                Some(6),
+                Some("clippy::never_loop"),
+            ),
+            (
+                AnnotationLevel::Notice,
+                "src/lib.rs",
+                Some(80),
+                Some(80),
+                Some(9),
+                Some(18),
+                Some("clippy::redundant_clone"),
+            ),
+        ]
+    );
+    assert_eq!(annotations[0].message, "unneeded `return` statement");
+}
+
+#[test]
+fn eslint() {
+    let annotations = load_sarif("eslint.sarif");
+    assert_eq!(
+        summarize(&annotations),
+        [
+            (
+                AnnotationLevel::Error,
+                "src/index.js",
+                Some(3),
+                Some(3),
+                Some(7),
+                Some(13),
+                Some("no-unused-vars"),
+            ),
+            (
+                AnnotationLevel::Warning,
+                "src/utils/format date.js",
+                Some(18),
+                Some(18),
+                Some(14),
+                Some(16),
+                Some("eqeqeq"),
+            ),
+            (
+                AnnotationLevel::Error,
+                "src/broken.js",
+                Some(7),
+                None,
+                Some(1),
+                None,
+                None,
+            ),
+        ]
+    );
+    assert_eq!(annotations[2].message, "Parsing error: Unexpected token )");
+}
+
+#[test]
+fn codeql() {
+    let annotations = load_sarif("codeql.sarif");
+    // levels are derived from the rules' default configuration
+    assert_eq!(
+        summarize(&annotations),
+        [
+            (
+                AnnotationLevel::Notice,
+                "app/views.py",
+                Some(2),
+                None,
+                Some(1),
+                Some(10),
+                Some("py/unused-import"),
+            ),
+            (
+                AnnotationLevel::Error,
+                "app/db.py",
+                Some(27),
+                Some(29),
+                Some(20),
+                Some(6),
+                Some("py/sql-injection"),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn malformed() {
+    let err = parse_sarif(r#"{"runs": {}}"#.as_bytes()).unwrap_err();
+    assert!(matches!(
+        err,
+        git_bot_feedback::RestClientError::Json { .. }
+    ));
+}
+
+#[cfg(feature = "file-changes")]
+#[test]
+#[allow(clippy::single_range_in_vec_init)]
+fn review_comments() {
+    use git_bot_feedback::{FileDiffLines, sarif::parse_sarif_review_comments};
+    use std::collections::HashMap;
+
+    unsafe {
+        env::set_var("GITHUB_WORKSPACE", WORKSPACE);
+    }
+    let files = HashMap::from([
+        (
+            "src/index.js".to_string(),
+            FileDiffLines::with_info(vec![3], vec![1..6]),
+        ),
+        (
+            "src/utils/format date.js".to_string(),
+            FileDiffLines::with_info(vec![30], vec![28..33]),
+        ),
+    ]);
+    let sarif = File::open("tests/assets/sarif/eslint.sarif").unwrap();
+    let (comments, leftovers) = parse_sarif_review_comments(sarif, &files).unwrap();
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].path, "src/index.js");
+    assert_eq!(comments[0].line_end, 3);
+    assert!(comments[0].comment.contains("'config' is assigned"));
+    let leftover_paths = leftovers
+        .iter()
+        .map(|a| a.path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        leftover_paths,
+        ["src/utils/format date.js", "src/broken.js"]
+    );
+}
@@ -0,0 +1,152 @@
+#![cfg(all(feature = "github", feature = "tracing"))]
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use git_bot_feedback::{ThreadCommentOptions, client::init_client};
+use mockito::{Matcher, Server};
+use tracing::{
+    Event, Id, Metadata, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Record},
+};
+use tracing_core::span::Current;
+
+mod common;
+use common::{github_env, logger_init};
+
+const REPO: &str = "2bndy5/git-bot-feedback";
+const SHA: &str = "DEADBEEF";
+
+/// A span collected by the [`SpanCollector`].
+#[derive(Debug)]
+struct CollectedSpan {
+    metadata: &'static Metadata<'static>,
+    fields: HashMap<String, String>,
+}
+
+/// Stores the fields of a span as strings.
+struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+/// A minimal [`Subscriber`] that collects the spans created by this crate.
+#[derive(Default, Clone)]
+struct SpanCollector {
+    spans: Arc<Mutex<Vec<CollectedSpan>>>,
+    entered: Arc<Mutex<Vec<Id>>>,
+}
+
+impl SpanCollector {
+    fn spans_named(&self, name: &str) -> Vec<HashMap<String, String>> {
+        self.spans
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|span| span.metadata.name() == name)
+            .map(|span| span.fields.clone())
+            .collect()
+    }
+}
+
+impl Subscriber for SpanCollector {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target().starts_with("git_bot_feedback")
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let mut fields = HashMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(CollectedSpan {
+            metadata: attrs.metadata(),
+            fields,
+        });
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut spans = self.spans.lock().unwrap();
+        let span = &mut spans[span.into_u64() as usize - 1];
+        values.record(&mut FieldVisitor(&mut span.fields));
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, span: &Id) {
+        self.entered.lock().unwrap().push(span.clone());
+    }
+
+    fn exit(&self, _span: &Id) {
+        self.entered.lock().unwrap().pop();
+    }
+
+    fn current_span(&self) -> Current {
+        match self.entered.lock().unwrap().last() {
+            Some(id) => {
+                let metadata = self.spans.lock().unwrap()[id.into_u64() as usize - 1].metadata;
+                Current::new(id.clone(), metadata)
+            }
+            None => Current::none(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn thread_comment_spans() {
+    let mut server = Server::new_async().await;
+    github_env(&server.url());
+    logger_init();
+    let collector = SpanCollector::default();
+    let _guard = tracing::subscriber::set_default(collector.clone());
+
+    let comments_path = format!("/repos/{REPO}/commits/{SHA}/comments");
+    let existing = server
+        .mock("GET", comments_path.as_str())
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_body("[]")
+        .create();
+    let posted = server
+        .mock("POST", comments_path.as_str())
+        .with_status(201)
+        .create();
+    let client = init_client().unwrap();
+    client
+        .post_thread_comment(ThreadCommentOptions {
+            comment: "Hello".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    existing.assert();
+    posted.assert();
+
+    let operations = collector.spans_named("post_thread_comment");
+    assert_eq!(operations.len(), 1);
+    assert_eq!(operations[0]["repo"], REPO);
+    // a push event has no PR number
+    assert!(!operations[0].contains_key("pr"));
+
+    let requests = collector.spans_named("send_api_request");
+    assert_eq!(requests.len(), 2);
+    for (request, (method, status)) in requests.iter().zip([("GET", "200"), ("POST", "201")]) {
+        assert_eq!(request["method"], method);
+        assert_eq!(request["url_path"], comments_path);
+        assert_eq!(request["status"], status);
+        assert_eq!(request["retries"], "0");
+    }
+}
@@ -0,0 +1,121 @@
+#![cfg(any(feature = "github", feature = "gitea"))]
+use git_bot_feedback::client::{CommandSink, RestApiClient, init_client};
+use std::{
+    env,
+    sync::{Arc, Mutex},
+};
+mod common;
+use common::logger_init;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Platform {
+    #[cfg(feature = "github")]
+    Github,
+    #[cfg(feature = "gitea")]
+    Gitea,
+}
+
+/// Get a client for the given `platform` whose workflow commands are captured in the returned buffer.
+fn setup(platform: Platform) -> (Box<dyn RestApiClient + Send + Sync>, Arc<Mutex<Vec<u8>>>) {
+    unsafe {
+        env::set_var("GITHUB_ACTIONS", "true");
+        env::set_var("CI", "true");
+        env::set_var("GITHUB_REPOSITORY", "2bndy5/git-bot-feedback");
+        env::set_var("GITHUB_SHA", "DEADBEEF");
+        env::set_var("GITHUB_EVENT_NAME", "push");
+        match platform {
+            #[cfg(feature = "github")]
+            Platform::Github => env::remove_var("GITEA_ACTIONS"),
+            #[cfg(feature = "gitea")]
+            Platform::Gitea => {
+                env::set_var("GITEA_ACTIONS", "true");
+                env::set_var("GITEA_API_URL", "https://gitea.com/api/v1");
+                env::set_var("GITEA_REPOSITORY", "2bndy5/git-bot-feedback");
+                env::set_var("GITEA_SHA", "DEADBEEF");
+                env::set_var("GITEA_EVENT_NAME", "push");
+            }
+        }
+    }
+    logger_init();
+    log::set_max_level(log::LevelFilter::Debug);
+    let mut client = init_client().unwrap();
+    let expected_kind = match platform {
+        #[cfg(feature = "github")]
+        Platform::Github => "github",
+        #[cfg(feature = "gitea")]
+        Platform::Gitea => "gitea",
+    };
+    assert_eq!(client.client_kind(), expected_kind);
+    let output = Arc::new(Mutex::new(Vec::<u8>::new()));
+    client.set_command_sink(CommandSink::Writer(output.clone()));
+    (client, output)
+}
+
+fn captured(output: &Arc<Mutex<Vec<u8>>>) -> Vec<String> {
+    String::from_utf8(output.lock().unwrap().clone())
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+fn mask_secret(platform: Platform) {
+    let (client, output) = setup(platform);
+    client.mask_secret("ghs_token%value").unwrap();
+    client
+        .mask_secret("-----BEGIN KEY-----\r\nline one\n\n-----END KEY-----\n")
+        .unwrap();
+    client.mask_secret("").unwrap();
+    assert_eq!(
+        captured(&output),
+        [
+            "::add-mask::ghs_token%25value",
+            "::add-mask::-----BEGIN KEY-----",
+            "::add-mask::line one",
+            "::add-mask::-----END KEY-----",
+        ]
+    );
+}
+
+#[cfg(feature = "github")]
+#[test]
+fn github_mask_secret() {
+    mask_secret(Platform::Github);
+}
+
+#[cfg(feature = "gitea")]
+#[test]
+fn gitea_mask_secret() {
+    mask_secret(Platform::Gitea);
+}
+
+fn log_untrusted(platform: Platform) {
+    let (client, output) = setup(platform);
+    client
+        .log_untrusted("Fix typo\n::set-env name=X::evil")
+        .unwrap();
+    let lines = captured(&output);
+    assert_eq!(lines.len(), 4);
+    let token = lines[0].strip_prefix("::stop-commands::").unwrap();
+    assert!(!token.is_empty());
+    assert_eq!(
+        lines[1..],
+        [
+            "Fix typo".to_string(),
+            "::set-env name=X::evil".to_string(),
+            format!("::{token}::"),
+        ]
+    );
+}
+
+#[cfg(feature = "github")]
+#[test]
+fn github_log_untrusted() {
+    log_untrusted(Platform::Github);
+}
+
+#[cfg(feature = "gitea")]
+#[test]
+fn gitea_log_untrusted() {
+    log_untrusted(Platform::Gitea);
+}